@@ -0,0 +1,133 @@
+//! Admin API authentication.
+//!
+//! Every request must carry the node admin token as a bearer token. The token is read
+//! from the `admin.token` file of the Hermes home directory, which is created with a
+//! random token, only readable by its owner, if it does not exist. Clients running as
+//! the same user as the node read it from the same file.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{PoisonError, RwLock},
+};
+
+use hyper::{header::AUTHORIZATION, Body, Method, Request, Uri};
+use rand::RngCore;
+
+/// Name of the admin token file in the Hermes home directory.
+const TOKEN_FILE: &str = "admin.token";
+
+/// Size of the generated tokens, in bytes.
+const TOKEN_SIZE: usize = 32;
+
+/// Admin token of the node and the file it is read from.
+static TOKEN: RwLock<Option<(PathBuf, String)>> = RwLock::new(None);
+
+/// Load the admin token from the Hermes home directory, creating it if needed.
+pub(super) fn init(hermes_home: &Path) -> anyhow::Result<()> {
+    let path = hermes_home.join(TOKEN_FILE);
+    if !path.exists() {
+        create_token_file(&path)?;
+    }
+    let token = read_token_file(&path)?;
+    *TOKEN.write().unwrap_or_else(PoisonError::into_inner) = Some((path, token));
+    Ok(())
+}
+
+/// Read the admin token file again, e.g. after the token has been rotated.
+pub(super) fn reload() -> anyhow::Result<()> {
+    let mut token = TOKEN.write().unwrap_or_else(PoisonError::into_inner);
+    let (path, token) = token
+        .as_mut()
+        .ok_or_else(|| anyhow::anyhow!("Admin API is not initialized"))?;
+    *token = read_token_file(path)?;
+    Ok(())
+}
+
+/// Does the request carry the admin token.
+pub(super) fn is_authorized(req: &Request<Body>) -> bool {
+    let Some(bearer) = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    TOKEN
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .is_some_and(|(_, token)| constant_time_eq(bearer.as_bytes(), token.as_bytes()))
+}
+
+/// Build an admin API request, authorized with the admin token of the node running from
+/// the Hermes home directory.
+pub(crate) fn request(
+    hermes_home: &Path, method: Method, uri: Uri,
+) -> anyhow::Result<Request<Body>> {
+    let path = hermes_home.join(TOKEN_FILE);
+    let token = read_token_file(&path)
+        .map_err(|err| anyhow::anyhow!("Cannot read admin token {}: {err}", path.display()))?;
+    Ok(Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .body(Body::empty())?)
+}
+
+/// Read a token from a file.
+fn read_token_file(path: &Path) -> anyhow::Result<String> {
+    let token = std::fs::read_to_string(path)?.trim().to_string();
+    anyhow::ensure!(!token.is_empty(), "Admin token is empty");
+    Ok(token)
+}
+
+/// Create a token file with a random token, only readable by its owner.
+fn create_token_file(path: &Path) -> anyhow::Result<()> {
+    let mut token = [0u8; TOKEN_SIZE];
+    rand::thread_rng().fill_bytes(&mut token);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.write_all(hex::encode(token).as_bytes())?;
+    Ok(())
+}
+
+/// Compare two byte strings in a time which does not depend on where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn admin_token_test() {
+        let dir = TempDir::new().unwrap();
+        init(dir.path()).unwrap();
+        let token = read_token_file(&dir.path().join(TOKEN_FILE)).unwrap();
+        assert_eq!(token.len(), TOKEN_SIZE * 2);
+
+        let authorized = request(dir.path(), Method::GET, Uri::from_static("/apps")).unwrap();
+        assert!(is_authorized(&authorized));
+        let unauthorized = Request::get("/apps")
+            .header(AUTHORIZATION, "Bearer 00")
+            .body(Body::empty())
+            .unwrap();
+        assert!(!is_authorized(&unauthorized));
+        assert!(!is_authorized(&Request::new(Body::empty())));
+
+        // Rotated token.
+        std::fs::write(dir.path().join(TOKEN_FILE), "rotated\n").unwrap();
+        assert!(is_authorized(&authorized));
+        reload().unwrap();
+        assert!(!is_authorized(&authorized));
+    }
+}
@@ -0,0 +1,638 @@
+//! Hermes node admin API.
+//!
+//! Serves the operational endpoints of the node: app management, metrics, event queue
+//! inspection and log level control. It listens on the loopback interface by default, or
+//! on a Unix socket, is not reachable by Hermes applications, and every request must be
+//! authenticated with the node admin token.
+
+mod auth;
+pub(crate) mod query;
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+pub(crate) use auth::request;
+use hyper::{
+    header::{CONTENT_TYPE, WWW_AUTHENTICATE},
+    server::Server,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, StatusCode,
+};
+use query::Query;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, level_filters::LevelFilter, warn};
+
+use crate::{
+    app::ApplicationName,
+    bandwidth,
+    event::{
+        queue,
+        trace::{self, EventTrace},
+    },
+    ipfs::{
+        hermes_ipfs_fetches, hermes_ipfs_gc, hermes_ipfs_pin_file, hermes_ipfs_pins,
+        hermes_ipfs_release_pin, hermes_ipfs_usage,
+    },
+    logger,
+    packaging::attestation::StateReport,
+    reactor,
+    runtime_extensions::hermes::{
+        cardano,
+        cron::{self, history},
+        http_gateway::variant_metrics,
+        sqlite::maintenance,
+    },
+};
+
+/// Admin API address
+pub(crate) const ADMIN_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5001);
+
+/// Loaded apps and their modules, as a JSON array.
+const APPS_ROUTE: &str = "/apps";
+
+/// Unloads the app given by the `app` query parameter.
+const APPS_UNLOAD_ROUTE: &str = "/apps/unload";
+
+/// Makes the app given by the `app` query parameter index the Cardano `network` again
+/// from the `from_slot` query parameter.
+pub(crate) const APPS_REINDEX_ROUTE: &str = "/apps/reindex";
+
+/// Index state of the app given by the `app` query parameter, as a JSON `StateReport`.
+pub(crate) const APPS_STATE_ROUTE: &str = "/apps/state";
+
+/// Runs the maintenance of the persistent `SQLite` databases of the app given by the
+/// `app` query parameter, returns a JSON array of their `MaintenanceReport`s.
+pub(crate) const APPS_MAINTAIN_ROUTE: &str = "/apps/maintain";
+
+/// Reloads the runtime configuration of the admin API, i.e. its token.
+const CONFIG_RELOAD_ROUTE: &str = "/config/reload";
+
+/// Node metrics, as a JSON object.
+const METRICS_ROUTE: &str = "/metrics";
+
+/// Event queue counters, as a JSON object.
+const EVENT_QUEUE_ROUTE: &str = "/events/queue";
+
+/// Maximum log level. `GET` returns it, `PUT` sets it from the `level` query parameter.
+const LOG_LEVEL_ROUTE: &str = "/log/level";
+
+/// Live feed of dispatched events, as newline delimited JSON `EventTrace`s.
+pub(crate) const EVENTS_ROUTE: &str = "/events";
+
+/// IPFS resource usage of every app, as a JSON object keyed by app name.
+const IPFS_USAGE_ROUTE: &str = "/ipfs/usage";
+
+/// File fetches in flight on the IPFS nodes, as a JSON array of `IpfsFetch`es, the
+/// longest running first. Helps to diagnose stuck fetches.
+const IPFS_FETCHES_ROUTE: &str = "/ipfs/fetches";
+
+/// Files pinned on the IPFS nodes and the apps holding their pins, as a JSON array of
+/// `IpfsPin`s.
+pub(crate) const IPFS_PINS_ROUTE: &str = "/ipfs/pins";
+
+/// Pins the file given by the `cid` query parameter on behalf of the `app`.
+pub(crate) const IPFS_PINS_ADD_ROUTE: &str = "/ipfs/pins/add";
+
+/// Releases the pin of the `app` on the file given by the `cid` query parameter.
+pub(crate) const IPFS_PINS_RM_ROUTE: &str = "/ipfs/pins/rm";
+
+/// Removes the unpinned blocks from the IPFS blockstores, returns an `IpfsGcReport` of
+/// the removed blocks. Takes the optional `min_age` query parameter, in seconds, to only
+/// remove blocks stored that long ago, and `dry_run=true` to only report them.
+pub(crate) const IPFS_GC_ROUTE: &str = "/ipfs/gc";
+
+/// Request metrics of the module variants of gateway routes, as a JSON array.
+const GATEWAY_VARIANTS_ROUTE: &str = "/gateway/variants";
+
+/// Latest cron executions of an app, as a JSON array of `CronExecution`s.
+/// Takes the `app` and optional `tag` query parameters.
+pub(crate) const CRON_HISTORY_ROUTE: &str = "/cron/history";
+
+/// Pauses the delivery of the cron events of the app given by the `app` query parameter.
+pub(crate) const CRON_PAUSE_ROUTE: &str = "/cron/pause";
+
+/// Resumes the delivery of the cron events of the app given by the `app` query
+/// parameter, the missed ticks fire according to the misfire policy of their entry.
+pub(crate) const CRON_RESUME_ROUTE: &str = "/cron/resume";
+
+/// Filters the events streamed by the events route, taken from the query string.
+#[derive(Debug, Default)]
+struct EventFilter {
+    /// Only events dispatched to this app
+    app: Option<String>,
+    /// Only events with this name
+    event: Option<String>,
+}
+
+impl EventFilter {
+    /// Filter of the `app` and `event` query parameters.
+    fn from_query(query: &Query) -> Self {
+        Self {
+            app: query.get("app").map(ToString::to_string),
+            event: query.get("event").map(ToString::to_string),
+        }
+    }
+
+    /// Does the trace pass the filter.
+    fn matches(&self, trace: &EventTrace) -> bool {
+        self.app.as_ref().map_or(true, |app| *app == trace.app)
+            && self
+                .event
+                .as_ref()
+                .map_or(true, |event| *event == trace.event)
+    }
+}
+
+/// Where the admin API listens.
+#[derive(Debug, Clone)]
+pub(crate) enum Listener {
+    /// TCP address.
+    Tcp(SocketAddr),
+    /// Unix socket path.
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl std::fmt::Display for Listener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{addr}"),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Spawns a OS thread running the admin API.
+///
+/// # Errors
+///
+/// If the admin token can not be loaded from the Hermes home directory.
+pub(crate) fn spawn(listener: Listener, hermes_home: &Path) -> anyhow::Result<()> {
+    auth::init(hermes_home)?;
+    std::thread::spawn(move || executor(listener));
+    Ok(())
+}
+
+/// Starts the admin API
+fn executor(listener: Listener) {
+    let res = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build();
+
+    let rt = match res {
+        Ok(rt) => rt,
+        Err(err) => {
+            error!(error = ?err, "Failed to start admin API background thread");
+            return;
+        },
+    };
+
+    info!("Starting admin API on {listener}");
+
+    rt.block_on(async move {
+        if let Err(err) = serve(listener).await {
+            error!("Admin API server failed: {:?}", err);
+        }
+    });
+}
+
+/// Serves the admin API on the listener.
+async fn serve(listener: Listener) -> anyhow::Result<()> {
+    match listener {
+        Listener::Tcp(addr) => {
+            let admin_service =
+                make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(router)) });
+            Server::bind(&addr).serve(admin_service).await?;
+        },
+        #[cfg(unix)]
+        Listener::Unix(path) => {
+            // A stale socket of a previous run prevents binding.
+            if path.exists() {
+                std::fs::remove_file(&path)?;
+            }
+            let listener = tokio::net::UnixListener::bind(&path)?;
+            let accept = hyper::server::accept::poll_fn(move |cx| {
+                listener
+                    .poll_accept(cx)
+                    .map(|res| Some(res.map(|(stream, _)| stream)))
+            });
+            let admin_service =
+                make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(router)) });
+            Server::builder(accept).serve(admin_service).await?;
+        },
+    }
+    Ok(())
+}
+
+/// Routes admin API requests.
+async fn router(req: Request<Body>) -> anyhow::Result<Response<Body>> {
+    if !auth::is_authorized(&req) {
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .header(WWW_AUTHENTICATE, "Bearer")
+            .body("Unauthorized".into())?);
+    }
+
+    let params = Query::parse(req.uri().query());
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, EVENTS_ROUTE) => events(EventFilter::from_query(&params)),
+        (&Method::GET, IPFS_USAGE_ROUTE) => ipfs_usage(),
+        (&Method::GET, IPFS_FETCHES_ROUTE) => json(&hermes_ipfs_fetches()),
+        (&Method::GET, IPFS_PINS_ROUTE) => ipfs_pins().await,
+        (&Method::POST, IPFS_PINS_ADD_ROUTE) => {
+            ipfs_pin(params.get("app"), params.get("cid"), true).await
+        },
+        (&Method::POST, IPFS_PINS_RM_ROUTE) => {
+            ipfs_pin(params.get("app"), params.get("cid"), false).await
+        },
+        (&Method::POST, IPFS_GC_ROUTE) => {
+            ipfs_gc(params.get("min_age"), params.get("dry_run")).await
+        },
+        (&Method::GET, GATEWAY_VARIANTS_ROUTE) => gateway_variants(),
+        (&Method::GET, CRON_HISTORY_ROUTE) => cron_history(params.get("app"), params.get("tag")),
+        (&Method::POST, CRON_PAUSE_ROUTE) => pause_cron(params.get("app"), true),
+        (&Method::POST, CRON_RESUME_ROUTE) => pause_cron(params.get("app"), false),
+        (&Method::GET, APPS_ROUTE) => apps(),
+        (&Method::POST, APPS_UNLOAD_ROUTE) => unload_app(params.get("app")),
+        (&Method::POST, APPS_REINDEX_ROUTE) => {
+            reindex_app(
+                params.get("app"),
+                params.get("network"),
+                params.get("from_slot"),
+            )
+        },
+        (&Method::GET, APPS_STATE_ROUTE) => app_state(params.get("app")).await,
+        (&Method::POST, APPS_MAINTAIN_ROUTE) => maintain_app(params.get("app")).await,
+        (&Method::POST, CONFIG_RELOAD_ROUTE) => reload_config(),
+        (&Method::GET, METRICS_ROUTE) => metrics(),
+        (&Method::GET, EVENT_QUEUE_ROUTE) => json(&queue::stats()),
+        (&Method::GET, LOG_LEVEL_ROUTE) => json(&logger::level()?.to_string()),
+        (&Method::PUT, LOG_LEVEL_ROUTE) => set_log_level(params.get("level")),
+        _ => {
+            Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body("Not Found".into())?)
+        },
+    }
+}
+
+/// Streams the traces of dispatched events until the client disconnects.
+fn events(filter: EventFilter) -> anyhow::Result<Response<Body>> {
+    let mut traces = trace::subscribe();
+    let (mut sender, body) = Body::channel();
+
+    tokio::spawn(async move {
+        loop {
+            let trace = match traces.recv().await {
+                Ok(trace) => trace,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Event trace subscriber lagged behind");
+                    continue;
+                },
+                Err(RecvError::Closed) => break,
+            };
+            if !filter.matches(&trace) {
+                continue;
+            }
+            let Ok(mut line) = serde_json::to_vec(&trace) else {
+                continue;
+            };
+            line.push(b'\n');
+            // Fails only when the client went away.
+            if sender.send_data(line.into()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/x-ndjson")
+        .body(body)?)
+}
+
+/// Reports the IPFS resource usage of every app.
+fn ipfs_usage() -> anyhow::Result<Response<Body>> {
+    let usage: serde_json::Map<String, serde_json::Value> = hermes_ipfs_usage()
+        .into_iter()
+        .map(|(app_name, usage)| Ok((app_name.to_string(), serde_json::to_value(usage)?)))
+        .collect::<anyhow::Result<_>>()?;
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&usage)?.into())?)
+}
+
+/// Lists the files pinned on the IPFS nodes.
+async fn ipfs_pins() -> anyhow::Result<Response<Body>> {
+    // IPFS node calls block until the node responds.
+    match tokio::task::spawn_blocking(hermes_ipfs_pins).await? {
+        Ok(pins) => json(&pins),
+        Err(err) => ipfs_unavailable(&err),
+    }
+}
+
+/// Pins a file on behalf of an app, or releases the pin of the app.
+async fn ipfs_pin(
+    app: Option<&str>, cid: Option<&str>, pin: bool,
+) -> anyhow::Result<Response<Body>> {
+    let (Some(app), Some(cid)) = (app, cid) else {
+        return bad_request("Missing `app` or `cid` query parameter".to_string());
+    };
+    let app_name = ApplicationName(app.to_string());
+    let path = format!("/ipfs/{cid}");
+    let res = tokio::task::spawn_blocking(move || {
+        if pin {
+            hermes_ipfs_pin_file(&app_name, &path)
+        } else {
+            hermes_ipfs_release_pin(&app_name, &path)
+        }
+    })
+    .await?;
+    match res {
+        Ok(true) => {
+            info!(app, cid, pin, "Changed IPFS pin from the admin API");
+            Ok(Response::new(Body::empty()))
+        },
+        Ok(false) => {
+            Ok(Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(format!("IPFS node failed to update the pin of {cid}").into())?)
+        },
+        Err(err) => ipfs_unavailable(&err),
+    }
+}
+
+/// Removes the unpinned blocks from the IPFS blockstores.
+async fn ipfs_gc(min_age: Option<&str>, dry_run: Option<&str>) -> anyhow::Result<Response<Body>> {
+    let Ok(min_age) = min_age.map_or(Ok(0), str::parse::<u64>) else {
+        return bad_request("Invalid `min_age` query parameter".to_string());
+    };
+    let dry_run = dry_run == Some("true");
+    let res =
+        tokio::task::spawn_blocking(move || hermes_ipfs_gc(Duration::from_secs(min_age), dry_run))
+            .await?;
+    match res {
+        Ok(report) => json(&report),
+        Err(err) => ipfs_unavailable(&err),
+    }
+}
+
+/// IPFS error response.
+fn ipfs_unavailable(err: &impl std::fmt::Debug) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .body(format!("IPFS request failed: {err:?}").into())?)
+}
+
+/// Reports the request metrics of the module variants of gateway routes.
+fn gateway_variants() -> anyhow::Result<Response<Body>> {
+    let metrics = variant_metrics()
+        .into_iter()
+        .map(|(app_name, route, module, metrics)| {
+            let mut entry = serde_json::json!({
+                "app": app_name.to_string(),
+                "route": route,
+                "module": module,
+            });
+            if let (Some(entry), serde_json::Value::Object(metrics)) =
+                (entry.as_object_mut(), serde_json::to_value(metrics)?)
+            {
+                entry.extend(metrics);
+            }
+            Ok(entry)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&metrics)?.into())?)
+}
+
+/// Reports the latest cron executions of an app, optionally of a single tag.
+fn cron_history(app: Option<&str>, tag: Option<&str>) -> anyhow::Result<Response<Body>> {
+    let Some(app) = app else {
+        return bad_request("Missing `app` query parameter".to_string());
+    };
+
+    let tag = tag.map(ToString::to_string);
+    let executions = history::history(&ApplicationName(app.to_string()), tag.as_ref());
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&executions)?.into())?)
+}
+
+/// Pauses or resumes the delivery of the cron events of an app, responding whether the
+/// app was not already in that state, and whether it is paused.
+fn pause_cron(app: Option<&str>, pause: bool) -> anyhow::Result<Response<Body>> {
+    let Some(app) = app else {
+        return bad_request("Missing `app` query parameter".to_string());
+    };
+    let app_name = ApplicationName(app.to_string());
+    if let Err(err) = reactor::get_app(&app_name) {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(err.to_string().into())?);
+    }
+    let changed = if pause {
+        cron::pause(&app_name)
+    } else {
+        cron::resume(&app_name)
+    };
+    info!(
+        app,
+        pause, changed, "Paused or resumed the cron events from the admin API"
+    );
+    json(&serde_json::json!({
+        "changed": changed,
+        "paused": cron::is_paused(&app_name),
+    }))
+}
+
+/// JSON response.
+fn json(value: &impl serde::Serialize) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(value)?.into())?)
+}
+
+/// Bad request response.
+fn bad_request(reason: String) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(reason.into())?)
+}
+
+/// Lists the loaded apps and their modules.
+fn apps() -> anyhow::Result<Response<Body>> {
+    let apps = reactor::get_all_app_names()?
+        .into_iter()
+        .filter_map(|app_name| {
+            let app = reactor::get_app(&app_name).ok()?;
+            Some(serde_json::json!({
+                "name": app_name.to_string(),
+                "modules": app.module_names(),
+            }))
+        })
+        .collect::<Vec<_>>();
+    json(&apps)
+}
+
+/// Unloads an app.
+fn unload_app(app: Option<&str>) -> anyhow::Result<Response<Body>> {
+    let Some(app) = app else {
+        return bad_request("Missing `app` query parameter".to_string());
+    };
+    if let Err(err) = reactor::unload_app(&ApplicationName(app.to_string())) {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(err.to_string().into())?);
+    }
+    info!(app, "Unloaded app from the admin API");
+    Ok(Response::new(Body::empty()))
+}
+
+/// Makes an app index a Cardano network again from a slot.
+fn reindex_app(
+    app: Option<&str>, network: Option<&str>, from_slot: Option<&str>,
+) -> anyhow::Result<Response<Body>> {
+    let (Some(app), Some(network), Some(from_slot)) = (app, network, from_slot) else {
+        return bad_request("Missing `app`, `network` or `from_slot` query parameter".to_string());
+    };
+    let Ok(network) = cardano_chain_follower::Network::from_str(network) else {
+        return bad_request(format!("Invalid Cardano network {network}"));
+    };
+    let Ok(from_slot) = from_slot.parse::<u64>() else {
+        return bad_request("Invalid `from_slot` query parameter".to_string());
+    };
+    let app_name = ApplicationName(app.to_string());
+    if let Err(err) = reactor::get_app(&app_name) {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(err.to_string().into())?);
+    }
+    cardano::reindex(app_name, network.into(), from_slot)?;
+    info!(app, %network, from_slot, "Requested app reindex from the admin API");
+    Ok(Response::new(Body::empty()))
+}
+
+/// Reports the index state of an app.
+async fn app_state(app: Option<&str>) -> anyhow::Result<Response<Body>> {
+    let Some(app) = app else {
+        return bad_request("Missing `app` query parameter".to_string());
+    };
+    let app_name = ApplicationName(app.to_string());
+    if let Err(err) = reactor::get_app(&app_name) {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(err.to_string().into())?);
+    }
+    // Counting the rows and hashing the database files blocks on the disk.
+    let report = tokio::task::spawn_blocking(move || StateReport::take(&app_name)).await??;
+    json(&report)
+}
+
+/// Runs the maintenance of the persistent databases of an app.
+async fn maintain_app(app: Option<&str>) -> anyhow::Result<Response<Body>> {
+    let Some(app) = app else {
+        return bad_request("Missing `app` query parameter".to_string());
+    };
+    let app_name = ApplicationName(app.to_string());
+    if let Err(err) = reactor::get_app(&app_name) {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(err.to_string().into())?);
+    }
+    info!(app, "Running the database maintenance from the admin API");
+    // The maintenance blocks until the databases are maintained.
+    let reports = tokio::task::spawn_blocking(move || maintenance::maintain(&app_name)).await?;
+    json(&reports)
+}
+
+/// Reloads the admin token.
+fn reload_config() -> anyhow::Result<Response<Body>> {
+    auth::reload()?;
+    info!("Reloaded admin API token");
+    Ok(Response::new(Body::empty()))
+}
+
+/// Reports the node metrics.
+fn metrics() -> anyhow::Result<Response<Body>> {
+    let cardano: BTreeMap<_, _> = cardano::followed_networks()
+        .into_iter()
+        .map(|chain_id| {
+            let network: cardano_chain_follower::Network = chain_id.into();
+            (network.to_string(), cardano::sync_status(chain_id))
+        })
+        .collect();
+
+    let bandwidth: BTreeMap<_, _> = bandwidth::usage()
+        .into_iter()
+        .map(|(app_name, report)| (app_name.to_string(), report))
+        .collect();
+
+    let sqlite_maintenance: BTreeMap<_, _> = maintenance::reports()
+        .into_iter()
+        .map(|(app_name, reports)| (app_name.to_string(), reports))
+        .collect();
+
+    json(&serde_json::json!({
+        "apps": reactor::get_all_app_names()?.len(),
+        "bandwidth": bandwidth,
+        "cardano": cardano,
+        "event_queue": queue::stats(),
+        "ipfs_fetches_in_flight": hermes_ipfs_fetches().len(),
+        "log_level": logger::level()?.to_string(),
+        "sqlite_maintenance": sqlite_maintenance,
+    }))
+}
+
+/// Sets the maximum log level.
+fn set_log_level(level: Option<&str>) -> anyhow::Result<Response<Body>> {
+    let Some(level) = level else {
+        return bad_request("Missing `level` query parameter".to_string());
+    };
+    let level = match LevelFilter::from_str(level) {
+        Ok(level) => level,
+        Err(err) => return bad_request(format!("Invalid log level {level}: {err}")),
+    };
+    logger::set_level(level)?;
+    info!(%level, "Changed log level from the admin API");
+    Ok(Response::new(Body::empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_filter_test() {
+        let trace = |app: &str, event: &str| {
+            EventTrace {
+                event: event.to_string(),
+                app: app.to_string(),
+                module: "module".to_string(),
+                queue_wait_us: 0,
+                execution_us: 0,
+                error: None,
+            }
+        };
+
+        let query = query::encode(&[("app", Some("my app&co")), ("event", None)]);
+        let filter = EventFilter::from_query(&Query::parse(query.strip_prefix('?')));
+        assert_eq!(filter.app.as_deref(), Some("my app&co"));
+        assert!(filter.matches(&trace("my app&co", "on-cron")));
+        assert!(!filter.matches(&trace("my app", "on-cron")));
+
+        let filter = EventFilter::from_query(&Query::parse(Some("event=on-cardano-block")));
+        assert!(filter.matches(&trace("app", "on-cardano-block")));
+        assert!(!filter.matches(&trace("app", "on-cron")));
+        assert!(EventFilter::from_query(&Query::default()).matches(&trace("app", "on-cron")));
+    }
+}
@@ -0,0 +1,106 @@
+//! Admin API query strings.
+//!
+//! Query parameter values are percent-encoded by the clients and decoded by the admin
+//! API, so app names, tags or event names can hold any character, e.g. `&` or `=`.
+
+use std::fmt::Write;
+
+/// Decoded query parameters of an admin API request.
+#[derive(Debug, Default)]
+pub(crate) struct Query(Vec<(String, String)>);
+
+impl Query {
+    /// Parse and decode `key=value` parameters separated by `&`.
+    ///
+    /// Parameters without `=` or which do not decode to UTF-8 are ignored.
+    pub(crate) fn parse(query: Option<&str>) -> Self {
+        Self(
+            query
+                .unwrap_or_default()
+                .split('&')
+                .filter_map(|param| param.split_once('='))
+                .filter_map(|(key, value)| Some((decode(key)?, decode(value)?)))
+                .collect(),
+        )
+    }
+
+    /// Value of the first parameter named `key`.
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find_map(|(k, value)| (k == key).then_some(value.as_str()))
+    }
+}
+
+/// Query string, starting with `?`, of the set parameters, their values encoded.
+///
+/// Empty if no parameter is set.
+pub(crate) fn encode(params: &[(&str, Option<&str>)]) -> String {
+    let mut query = String::new();
+    for (key, value) in params {
+        let Some(value) = value else {
+            continue;
+        };
+        query.push(if query.is_empty() { '?' } else { '&' });
+        query.push_str(key);
+        query.push('=');
+        query.push_str(&encode_value(value));
+    }
+    query
+}
+
+/// Percent-encode every byte of `value` but the URI unreserved characters.
+pub(crate) fn encode_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// Percent-decode `value`, `+` standing for a space, `None` if it is not valid.
+fn decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let hex = [input.next()?, input.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            },
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_round_trip_test() {
+        let app = "my app&more=1/ü";
+        let query = encode(&[
+            ("app", Some(app)),
+            ("tag", None),
+            ("event", Some("on-cron")),
+        ]);
+        assert_eq!(query, "?app=my%20app%26more%3D1%2F%C3%BC&event=on-cron");
+
+        let params = Query::parse(query.strip_prefix('?'));
+        assert_eq!(params.get("app"), Some(app));
+        assert_eq!(params.get("event"), Some("on-cron"));
+        assert_eq!(params.get("tag"), None);
+
+        assert_eq!(encode(&[("app", None)]), "");
+        assert_eq!(Query::parse(Some("a=b+c&bad=%ZZ&c")).get("a"), Some("b c"));
+        assert_eq!(Query::parse(Some("bad=%ZZ")).get("bad"), None);
+        assert_eq!(Query::parse(None).get("a"), None);
+    }
+}
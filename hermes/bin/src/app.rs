@@ -1,11 +1,14 @@
 //! Hermes app implementation.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use crate::{
-    event::HermesEventPayload,
+    event::{
+        trace::{self, EventTrace},
+        HermesEventPayload,
+    },
     runtime_context::HermesRuntimeContext,
-    runtime_extensions::new_context,
+    runtime_extensions::{hermes::delivery, new_context},
     vfs::Vfs,
     wasm::module::{Module, ModuleId},
 };
@@ -20,6 +23,24 @@ impl std::fmt::Display for ApplicationName {
     }
 }
 
+impl std::str::FromStr for ApplicationName {
+    type Err = anyhow::Error;
+
+    /// Parses an app name, which names the data files of the app, so it is made of ASCII
+    /// letters, digits, `-`, `_` and `.`, and does not start with a `.`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        anyhow::ensure!(
+            !name.is_empty()
+                && !name.starts_with('.')
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')),
+            "Invalid app name `{name}`"
+        );
+        Ok(Self(name.to_string()))
+    }
+}
+
 /// Hermes application
 pub(crate) struct Application {
     /// Application name
@@ -28,20 +49,28 @@ pub(crate) struct Application {
     /// WASM modules
     indexed_modules: HashMap<ModuleId, Module>,
 
+    /// Ids of the WASM modules by their package name
+    module_ids: HashMap<String, ModuleId>,
+
     /// Application's `Vfs` instance
     vfs: Arc<Vfs>,
 }
 
 impl Application {
-    /// Create a new Hermes app
-    pub(crate) fn new(app_name: String, vfs: Vfs, modules: Vec<Module>) -> Self {
+    /// Create a new Hermes app from its modules and their package names
+    pub(crate) fn new(app_name: String, vfs: Vfs, modules: Vec<(String, Module)>) -> Self {
+        let module_ids = modules
+            .iter()
+            .map(|(name, module)| (name.clone(), module.id().clone()))
+            .collect();
         let indexed_modules = modules
             .into_iter()
-            .map(|module| (module.id().clone(), module))
+            .map(|(_, module)| (module.id().clone(), module))
             .collect();
         Self {
             name: ApplicationName(app_name),
             indexed_modules,
+            module_ids,
             vfs: Arc::new(vfs),
         }
     }
@@ -51,13 +80,25 @@ impl Application {
         &self.name
     }
 
+    /// Get the id of a module by its package name
+    pub(crate) fn module_id(&self, name: &str) -> Option<&ModuleId> {
+        self.module_ids.get(name)
+    }
+
+    /// Get the package names of the modules
+    pub(crate) fn module_names(&self) -> Vec<String> {
+        self.module_ids.keys().cloned().collect()
+    }
+
     /// Get vfs
     pub(crate) fn vfs(&self) -> &Vfs {
         self.vfs.as_ref()
     }
 
     /// Dispatch event for all available modules.
-    pub(crate) fn dispatch_event(&self, event: &dyn HermesEventPayload) -> anyhow::Result<()> {
+    pub(crate) fn dispatch_event(
+        &self, event: &dyn HermesEventPayload, queued_at: Instant,
+    ) -> anyhow::Result<()> {
         for module in self.indexed_modules.values() {
             module_dispatch_event(
                 module,
@@ -65,6 +106,7 @@ impl Application {
                 module.id().clone(),
                 self.vfs.clone(),
                 event,
+                queued_at,
             )?;
         }
         Ok(())
@@ -72,7 +114,7 @@ impl Application {
 
     /// Dispatch event for the target module by the `module_id`.
     pub(crate) fn dispatch_event_for_target_module(
-        &self, module_id: ModuleId, event: &dyn HermesEventPayload,
+        &self, module_id: ModuleId, event: &dyn HermesEventPayload, queued_at: Instant,
     ) -> anyhow::Result<()> {
         let module = self
             .indexed_modules
@@ -84,15 +126,22 @@ impl Application {
             module_id,
             self.vfs.clone(),
             event,
+            queued_at,
         )
     }
 }
 
 /// Dispatch event
+///
+/// At-least-once events are delivered again until the module acknowledges them.
+/// Publishes an `EventTrace` of the execution if anyone is listening.
 pub(crate) fn module_dispatch_event(
     module: &Module, app_name: ApplicationName, module_id: ModuleId, vfs: Arc<Vfs>,
-    event: &dyn HermesEventPayload,
+    event: &dyn HermesEventPayload, queued_at: Instant,
 ) -> anyhow::Result<()> {
+    let traced = trace::is_enabled().then(|| (app_name.clone(), module_id.clone()));
+    let started_at = Instant::now();
+
     let runtime_ctx = HermesRuntimeContext::new(
         app_name,
         module_id,
@@ -104,6 +153,39 @@ pub(crate) fn module_dispatch_event(
     // Advise Runtime Extensions of a new context
     new_context(&runtime_ctx);
 
-    module.execute_event(event, runtime_ctx)?;
-    Ok(())
+    let result = delivery::deliver(
+        runtime_ctx.app_name(),
+        runtime_ctx.module_id(),
+        event.event_name(),
+        || module.execute_event(event, runtime_ctx.clone()),
+    );
+
+    if let Some((app_name, module_id)) = traced {
+        trace::publish(EventTrace::new(
+            event.event_name(),
+            &app_name,
+            &module_id,
+            trace::queue_wait(queued_at, started_at),
+            started_at.elapsed(),
+            &result,
+        ));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn application_name_from_str_test() {
+        assert!("athena".parse::<ApplicationName>().is_ok());
+        assert!("my-app_1.0".parse::<ApplicationName>().is_ok());
+
+        assert!("".parse::<ApplicationName>().is_err());
+        assert!("..".parse::<ApplicationName>().is_err());
+        assert!(".hidden".parse::<ApplicationName>().is_err());
+        assert!("../athena".parse::<ApplicationName>().is_err());
+        assert!("/etc/athena".parse::<ApplicationName>().is_err());
+    }
 }
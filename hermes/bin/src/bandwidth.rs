@@ -0,0 +1,403 @@
+//! Per-app network bandwidth accounting.
+//!
+//! The bytes each app sends and receives through the IPFS and Cardano runtime extensions
+//! are counted in total, and for the current UTC day and month. An app can cap its daily
+//! and monthly traffic in the `bandwidth` resources of its metadata. Once a cap is
+//! reached, depending on the cap `action`, the app is warned about, rate limited, or
+//! blocked from starting new transfers until the period ends.
+//!
+//! The usage of the current day and month is stored as a job of the `bandwidth` kind in
+//! the jobs database, at most every `SAVE_INTERVAL`, and restored when the app is loaded
+//! again, so restarting the node does not reset the caps. The kind has no job handler.
+
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{Datelike, NaiveDate, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::ApplicationName, jobs};
+
+/// Bytes in a megabyte, the unit of the caps.
+const MEGABYTE: u64 = 1_048_576;
+
+/// Bytes per second a rate limited app can transfer.
+const CAPPED_RATE: u64 = 16_384;
+
+/// Job kind of the stored usage of the apps.
+const JOB_KIND: &str = "bandwidth";
+
+/// Key of the job of the stored usage of an app.
+const USAGE_KEY: &str = "usage";
+
+/// Minimum interval between two stores of the usage of an app.
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Bandwidth of every app.
+static APPS: Lazy<DashMap<ApplicationName, AppBandwidth>> = Lazy::new(DashMap::new);
+
+/// What happens to the traffic of an app which reached one of its caps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CapAction {
+    /// A warning is logged, the traffic is not limited.
+    #[default]
+    Warn,
+    /// New transfers are rejected while the traffic since the cap was reached exceeds
+    /// `CAPPED_RATE` bytes per second, transfers in progress are not slowed down.
+    RateLimit,
+    /// No new transfer is started.
+    Block,
+}
+
+/// Bandwidth caps of an app, uncapped when not set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BandwidthCaps {
+    /// Maximum traffic in a UTC day, in megabytes.
+    #[serde(default)]
+    pub(crate) daily: Option<u64>,
+    /// Maximum traffic in a UTC month, in megabytes.
+    #[serde(default)]
+    pub(crate) monthly: Option<u64>,
+    /// What happens once a cap is reached.
+    #[serde(default)]
+    pub(crate) action: CapAction,
+}
+
+/// Direction of a transfer.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Direction {
+    /// Sent by the app.
+    Sent,
+    /// Received by the app.
+    Received,
+}
+
+/// Bytes sent and received by an app.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Usage {
+    /// Bytes sent.
+    pub(crate) sent: u64,
+    /// Bytes received.
+    pub(crate) received: u64,
+}
+
+impl Usage {
+    /// Bytes sent and received.
+    fn total(self) -> u64 {
+        self.sent.saturating_add(self.received)
+    }
+
+    /// Count `bytes` transferred in `direction`.
+    fn add(&mut self, direction: Direction, bytes: u64) {
+        match direction {
+            Direction::Sent => self.sent = self.sent.saturating_add(bytes),
+            Direction::Received => self.received = self.received.saturating_add(bytes),
+        }
+    }
+}
+
+/// Bandwidth usage of an app, as reported in the node metrics.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(crate) struct BandwidthReport {
+    /// Bytes transferred since the node started.
+    pub(crate) total: Usage,
+    /// Bytes transferred in the current UTC day.
+    pub(crate) today: Usage,
+    /// Bytes transferred in the current UTC month.
+    pub(crate) this_month: Usage,
+    /// Whether the app reached one of its caps.
+    pub(crate) capped: bool,
+}
+
+/// Usage of an app in the current periods, as stored.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct StoredUsage {
+    /// Day the usages are counted for, as `YYYY-MM-DD`.
+    day: String,
+    /// Bytes transferred in `day`.
+    today: Usage,
+    /// Bytes transferred in the month of `day`.
+    this_month: Usage,
+}
+
+/// Bandwidth state of an app.
+#[derive(Debug, Default)]
+struct AppBandwidth {
+    /// Caps of the app.
+    caps: BandwidthCaps,
+    /// Bytes transferred since the node started.
+    total: Usage,
+    /// Day the `today` and `this_month` usages are counted for.
+    day: Option<NaiveDate>,
+    /// Bytes transferred in `day`.
+    today: Usage,
+    /// Bytes transferred in the month of `day`.
+    this_month: Usage,
+    /// When the app was rate limited, and the bytes it transferred since.
+    rate_limited: Option<(Instant, u64)>,
+    /// Whether the app was warned it reached a cap in the current period.
+    warned: bool,
+    /// When the usage was last stored.
+    saved: Option<Instant>,
+}
+
+impl AppBandwidth {
+    /// Start the new periods if `today` is a new day.
+    fn roll(&mut self, today: NaiveDate) {
+        if self.day == Some(today) {
+            return;
+        }
+        let same_month = self
+            .day
+            .is_some_and(|day| (day.year(), day.month()) == (today.year(), today.month()));
+        if !same_month {
+            self.this_month = Usage::default();
+        }
+        self.day = Some(today);
+        self.today = Usage::default();
+        self.rate_limited = None;
+        self.warned = false;
+    }
+
+    /// Restore the stored usage of the current periods, unless the usage of the app is
+    /// already counted, e.g. when the app is loaded again by the same node.
+    fn restore(&mut self, stored: &StoredUsage, today: NaiveDate) {
+        if self.day.is_some() {
+            return;
+        }
+        let Ok(day) = stored.day.parse() else {
+            return;
+        };
+        self.day = Some(day);
+        self.today = stored.today;
+        self.this_month = stored.this_month;
+        self.roll(today);
+    }
+
+    /// Usage to store at `now`, if it was not stored in the last `SAVE_INTERVAL`.
+    fn usage_to_save(&mut self, now: Instant) -> Option<StoredUsage> {
+        if self
+            .saved
+            .is_some_and(|saved| now.saturating_duration_since(saved) < SAVE_INTERVAL)
+        {
+            return None;
+        }
+        let day = self.day?;
+        self.saved = Some(now);
+        Some(StoredUsage {
+            day: day.to_string(),
+            today: self.today,
+            this_month: self.this_month,
+        })
+    }
+
+    /// Count `bytes` transferred in `direction`.
+    fn record(&mut self, direction: Direction, bytes: u64, today: NaiveDate) {
+        self.roll(today);
+        self.total.add(direction, bytes);
+        self.today.add(direction, bytes);
+        self.this_month.add(direction, bytes);
+        if let Some((_, capped_bytes)) = &mut self.rate_limited {
+            *capped_bytes = capped_bytes.saturating_add(bytes);
+        }
+    }
+
+    /// Whether the app reached one of its caps.
+    fn is_capped(&self) -> bool {
+        let reached = |cap: Option<u64>, usage: Usage| {
+            cap.is_some_and(|cap| usage.total() >= cap.saturating_mul(MEGABYTE))
+        };
+        reached(self.caps.daily, self.today) || reached(self.caps.monthly, self.this_month)
+    }
+
+    /// Whether the app can start a new transfer.
+    fn admit(&mut self, app_name: &ApplicationName, today: NaiveDate, now: Instant) -> bool {
+        self.roll(today);
+        if !self.is_capped() {
+            return true;
+        }
+        match self.caps.action {
+            CapAction::Warn => {
+                if !self.warned {
+                    self.warned = true;
+                    tracing::warn!(app_name = %app_name, "App reached its bandwidth cap");
+                }
+                true
+            },
+            CapAction::RateLimit => {
+                let (since, bytes) = *self.rate_limited.get_or_insert((now, 0));
+                let allowed = u64::try_from(now.saturating_duration_since(since).as_millis())
+                    .unwrap_or(u64::MAX)
+                    .saturating_mul(CAPPED_RATE)
+                    / 1000;
+                bytes <= allowed
+            },
+            CapAction::Block => false,
+        }
+    }
+}
+
+/// Set the bandwidth caps of an app, restoring its stored usage.
+pub(crate) fn set_app_caps(app_name: ApplicationName, caps: BandwidthCaps) {
+    let stored = load(&app_name);
+    let mut app = APPS.entry(app_name).or_default();
+    app.caps = caps;
+    if let Some(stored) = stored {
+        app.restore(&stored, Utc::now().date_naive());
+    }
+}
+
+/// Count `bytes` transferred by an app in `direction`.
+pub(crate) fn record(app_name: &ApplicationName, direction: Direction, bytes: usize) {
+    let to_save = {
+        let mut app = APPS.entry(app_name.clone()).or_default();
+        app.record(
+            direction,
+            u64::try_from(bytes).unwrap_or(u64::MAX),
+            Utc::now().date_naive(),
+        );
+        app.usage_to_save(Instant::now())
+    };
+    if let Some(usage) = to_save {
+        save(app_name, &usage);
+    }
+}
+
+/// Store the usage of an app, a failure is only logged.
+fn save(app_name: &ApplicationName, usage: &StoredUsage) {
+    let saved = serde_json::to_vec(usage)
+        .map_err(anyhow::Error::from)
+        .and_then(|payload| {
+            jobs::schedule(JOB_KIND, app_name, USAGE_KEY, &payload, SystemTime::now())
+        });
+    if let Err(err) = saved {
+        tracing::warn!(app_name = %app_name, "Failed to store bandwidth usage: {err}");
+    }
+}
+
+/// Load the stored usage of an app, if any.
+fn load(app_name: &ApplicationName) -> Option<StoredUsage> {
+    let stored = match jobs::scheduled(JOB_KIND, app_name) {
+        Ok(stored) => stored,
+        Err(err) => {
+            tracing::warn!(app_name = %app_name, "Failed to load bandwidth usage: {err}");
+            return None;
+        },
+    };
+    let (_, payload, _) = stored.into_iter().find(|(key, ..)| key == USAGE_KEY)?;
+    serde_json::from_slice(&payload)
+        .map_err(|err| {
+            tracing::warn!(app_name = %app_name, "Invalid stored bandwidth usage: {err}");
+        })
+        .ok()
+}
+
+/// Whether an app can start a new transfer, according to its caps.
+pub(crate) fn admit(app_name: &ApplicationName) -> bool {
+    APPS.entry(app_name.clone()).or_default().admit(
+        app_name,
+        Utc::now().date_naive(),
+        Instant::now(),
+    )
+}
+
+/// Bandwidth usage of every app.
+pub(crate) fn usage() -> Vec<(ApplicationName, BandwidthReport)> {
+    let today = Utc::now().date_naive();
+    APPS.iter_mut()
+        .map(|mut entry| {
+            entry.roll(today);
+            let report = BandwidthReport {
+                total: entry.total,
+                today: entry.today,
+                this_month: entry.this_month,
+                capped: entry.is_capped(),
+            };
+            (entry.key().clone(), report)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn bandwidth_caps_test() {
+        let app_name = ApplicationName("bandwidth_caps_test".to_string());
+        let day = NaiveDate::from_ymd_opt(2024, 1, 30).unwrap();
+        let now = Instant::now();
+        let mut app = AppBandwidth {
+            caps: serde_json::from_str(r#"{"daily": 1, "monthly": 2, "action": "block"}"#).unwrap(),
+            ..AppBandwidth::default()
+        };
+
+        app.record(Direction::Sent, MEGABYTE / 2, day);
+        app.record(Direction::Received, MEGABYTE / 2 - 1, day);
+        assert!(app.admit(&app_name, day, now));
+        app.record(Direction::Received, 1, day);
+        assert!(!app.admit(&app_name, day, now));
+
+        // The daily cap is reset the next day, the monthly one the next month.
+        let next_day = day.succ_opt().unwrap();
+        assert!(app.admit(&app_name, next_day, now));
+        app.record(Direction::Sent, MEGABYTE, next_day);
+        assert_eq!(app.this_month.total(), 2 * MEGABYTE);
+        assert!(!app.admit(&app_name, next_day, now));
+        assert_eq!(app.total.total(), 3 * MEGABYTE);
+
+        app.caps.action = CapAction::RateLimit;
+        assert!(app.admit(&app_name, next_day, now));
+        app.record(Direction::Sent, CAPPED_RATE, next_day);
+        assert!(!app.admit(&app_name, next_day, now));
+        assert!(app.admit(&app_name, next_day, now + Duration::from_secs(1)));
+
+        app.caps.action = CapAction::Warn;
+        assert!(app.admit(&app_name, next_day, now));
+
+        let next_month = next_day.succ_opt().unwrap();
+        assert!(app.admit(&app_name, next_month, now));
+        assert_eq!(app.this_month.total(), 0);
+
+        assert!(serde_json::from_str::<BandwidthCaps>(r#"{"action": "drop"}"#).is_err());
+    }
+
+    #[test]
+    fn stored_usage_is_restored() {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 30).unwrap();
+        let now = Instant::now();
+        let mut app = AppBandwidth::default();
+        app.record(Direction::Sent, 10, day);
+        app.record(Direction::Received, 5, day);
+
+        let stored = app.usage_to_save(now).unwrap();
+        // Stored at most every `SAVE_INTERVAL`.
+        assert!(app.usage_to_save(now).is_none());
+        assert!(app.usage_to_save(now + SAVE_INTERVAL).is_some());
+        let stored: StoredUsage =
+            serde_json::from_slice(&serde_json::to_vec(&stored).unwrap()).unwrap();
+
+        // The node restarted the same day.
+        let mut restarted = AppBandwidth::default();
+        restarted.restore(&stored, day);
+        assert_eq!(restarted.today, app.today);
+        assert_eq!(restarted.this_month.total(), 15);
+
+        // The node restarted the next day of the same month.
+        let mut restarted = AppBandwidth::default();
+        restarted.restore(&stored, day.succ_opt().unwrap());
+        assert_eq!(restarted.today.total(), 0);
+        assert_eq!(restarted.this_month.total(), 15);
+
+        // The usage already counted by the node is kept.
+        let mut counted = AppBandwidth::default();
+        counted.record(Direction::Sent, 1, day);
+        counted.restore(&stored, day);
+        assert_eq!(counted.today.total(), 1);
+    }
+}
@@ -0,0 +1,130 @@
+//! cli app attest and verify-attestation commands
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use clap::Args;
+use console::{style, Emoji};
+use hyper::Method;
+
+use crate::{
+    admin::{query, ADMIN_ADDR, APPS_STATE_ROUTE},
+    cli::admin_call,
+    packaging::{
+        attestation::{StateAttestation, StateReport},
+        sign::{
+            certificate::{self, Certificate},
+            keys::PrivateKey,
+        },
+    },
+};
+
+/// Attest the index state of an app running on a hermes node
+///
+/// Signs a report of the table row counts, the latest indexed slots and the database
+/// hashes of the app, so the nodes replicating the app can prove they hold the same
+/// data.
+#[derive(Args)]
+pub(crate) struct AttestCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// App to attest the state of
+    app: String,
+
+    /// Defines the location of the ED2559 private key the attestation is signed with.
+    private_key: PathBuf,
+
+    /// Defines the location of the x.509 certificate associated with the signing key.
+    cert: PathBuf,
+
+    /// Path of the attestation, `<app>.attest.cose` in the current directory by default.
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+impl AttestCommand {
+    /// Run the app attest command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let private_key = PrivateKey::from_file(self.private_key)?;
+        let cert = Certificate::from_file(self.cert)?;
+
+        let body = admin_call(
+            Method::GET,
+            format!(
+                "http://{}{APPS_STATE_ROUTE}{}",
+                self.addr,
+                query::encode(&[("app", Some(self.app.as_str()))])
+            )
+            .parse()?,
+        )?;
+        let report: StateReport = serde_json::from_slice(&body)?;
+        let attestation = StateAttestation::sign(&report, &private_key, &cert)?;
+
+        let output = self.output.unwrap_or_else(|| {
+            PathBuf::from(format!("{}.{}", self.app, StateAttestation::FILE_EXTENSION))
+        });
+        std::fs::write(&output, attestation.to_bytes()?)?;
+
+        print_report(&report);
+        println!("{} Attested to {}", Emoji::new("✅", ""), output.display());
+        Ok(())
+    }
+}
+
+/// Verify an app state attestation and show the attested state
+#[derive(Args)]
+pub(crate) struct VerifyAttestationCommand {
+    /// Path to the attestation
+    attestation: PathBuf,
+
+    /// Path to the trusted certificate, to verify the attestation signature with
+    #[clap(name = "cert", short)]
+    certificates: Vec<PathBuf>,
+}
+
+impl VerifyAttestationCommand {
+    /// Run the app verify-attestation command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        for cert_path in self.certificates {
+            let cert = Certificate::from_file(cert_path)?;
+            certificate::storage::add_certificate(cert)?;
+        }
+
+        let attestation = StateAttestation::from_reader(std::fs::File::open(self.attestation)?)?;
+        print_report(&attestation.report()?);
+        match attestation.verify() {
+            Ok(()) => println!("Signature: {}", style("verified").green()),
+            Err(err) => {
+                println!("Signature: {}", style(format!("NOT verified: {err}")).red());
+                anyhow::bail!("Attestation signature is not verified");
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Print the state report.
+fn print_report(report: &StateReport) {
+    let taken_at = i64::try_from(report.taken_at)
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map_or_else(
+            || report.taken_at.to_string(),
+            |taken_at| taken_at.to_rfc3339(),
+        );
+    println!("App: {}", report.app);
+    println!("Taken at: {taken_at}");
+    println!("Tables:");
+    for (table, rows) in &report.tables {
+        println!("  {table}: {rows} rows");
+    }
+    println!("Indexed slots:");
+    for (network, slot) in &report.indexed_slots {
+        println!("  {network}: {slot}");
+    }
+    println!("Databases:");
+    for (db_name, hash) in &report.databases {
+        println!("  {db_name}: {hash}");
+    }
+}
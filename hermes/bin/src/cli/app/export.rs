@@ -0,0 +1,68 @@
+//! cli app export command
+
+use std::path::PathBuf;
+
+use clap::Args;
+use console::Emoji;
+
+use super::app_data_files;
+use crate::{
+    app::ApplicationName,
+    packaging::{
+        app_data::AppDataArchive,
+        sign::{certificate::Certificate, keys::PrivateKey},
+    },
+};
+
+/// Application data export
+///
+/// The app should not be running while its data is exported, so the archive holds a
+/// consistent copy of it. Only the `SQLite` database of the app alone is exported, not
+/// the one shared by the apps, nor an encrypted one.
+#[derive(Args)]
+pub(crate) struct ExportCommand {
+    /// Name of the app to export the data of.
+    app: ApplicationName,
+
+    /// Defines the location of the ED2559 private key the archive is signed with.
+    private_key: PathBuf,
+
+    /// Defines the location of the x.509 certificate associated with the signing key.
+    cert: PathBuf,
+
+    /// Path of the archive, `<app>.hdata` in the current directory by default.
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    /// Run cli command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        println!("{} Export application data", Emoji::new("📤", ""));
+
+        let private_key = PrivateKey::from_file(self.private_key)?;
+        let cert = Certificate::from_file(self.cert)?;
+        let (vfs_file, sqlite_file) = app_data_files(&self.app)?;
+        anyhow::ensure!(
+            vfs_file.exists(),
+            "App {} has no data at {}",
+            self.app,
+            vfs_file.display()
+        );
+        let output = self.output.unwrap_or_else(|| {
+            PathBuf::from(format!("{}.{}", self.app, AppDataArchive::FILE_EXTENSION))
+        });
+
+        AppDataArchive::export(
+            &output,
+            &self.app,
+            &vfs_file,
+            sqlite_file.exists().then_some(sqlite_file.as_path()),
+            &private_key,
+            &cert,
+        )?;
+
+        println!("{} Exported to {}", Emoji::new("✅", ""), output.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+//! cli app import command
+
+use std::path::PathBuf;
+
+use clap::Args;
+use console::Emoji;
+
+use super::app_data_files;
+use crate::packaging::{
+    app_data::AppDataArchive,
+    sign::certificate::{self, Certificate},
+};
+
+/// Application data import
+///
+/// The data replaces the data of the app on this node, which should not be running. The
+/// `SQLite` database is imported as the database of the app alone.
+#[derive(Args)]
+pub(crate) struct ImportCommand {
+    /// Path to the application data archive.
+    archive: PathBuf,
+
+    /// Path to the trusted certificate
+    #[clap(name = "cert", short)]
+    certificates: Vec<PathBuf>,
+
+    /// Flag which disables archive signature verification
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    untrusted: bool,
+
+    /// Replace the existing data of the app
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    force: bool,
+}
+
+impl ImportCommand {
+    /// Run cli command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        println!("{} Import application data", Emoji::new("📥", ""));
+
+        for cert_path in self.certificates {
+            let cert = Certificate::from_file(cert_path)?;
+            certificate::storage::add_certificate(cert)?;
+        }
+
+        let archive = AppDataArchive::from_file(&self.archive)?;
+        println!("{} Verifying archive", Emoji::new("🧐", ""));
+        let app_name = archive.validate(self.untrusted)?;
+
+        let (vfs_file, sqlite_file) = app_data_files(&app_name)?;
+        let replaced_files = [
+            Some(&vfs_file),
+            archive.has_sqlite().then_some(&sqlite_file),
+        ];
+        for file in replaced_files.into_iter().flatten() {
+            anyhow::ensure!(
+                self.force || !file.exists(),
+                "App {app_name} already has data at {}, use --force to replace it",
+                file.display()
+            );
+        }
+        archive.extract(&vfs_file, &sqlite_file)?;
+
+        println!("{} Imported the data of {app_name}", Emoji::new("✅", ""));
+        Ok(())
+    }
+}
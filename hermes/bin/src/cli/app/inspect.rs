@@ -0,0 +1,81 @@
+//! cli app inspect command
+
+use std::path::PathBuf;
+
+use clap::Args;
+use console::{style, Emoji};
+
+use crate::packaging::{
+    app::ApplicationPackage,
+    sign::certificate::{self, Certificate},
+};
+
+/// Application package inspection
+#[derive(Args)]
+pub(crate) struct InspectCommand {
+    /// Defines the location of the builded application package.
+    package: PathBuf,
+
+    /// Path to the trusted certificate, to verify the package signature with
+    #[clap(name = "cert", short)]
+    certificates: Vec<PathBuf>,
+}
+
+impl InspectCommand {
+    /// Run cli command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        println!("{} Inspect application package", Emoji::new("🔍", ""));
+
+        for cert_path in self.certificates {
+            let cert = Certificate::from_file(cert_path)?;
+            certificate::storage::add_certificate(cert)?;
+        }
+
+        let package = ApplicationPackage::from_file(self.package)?;
+        let metadata = package.get_metadata()?;
+        println!("Name: {}", metadata.get_name()?);
+        for field in ["version", "build_date", "toolchain"] {
+            if let Some(value) = metadata.get(field) {
+                println!("{field}: {value}");
+            }
+        }
+
+        let signed = match package.get_author_signature()? {
+            None => style("not signed".to_string()).yellow(),
+            Some(_) => {
+                match package.validate(false) {
+                    Ok(()) => style("signed, verified".to_string()).green(),
+                    Err(err) => style(format!("signed, NOT verified: {err}")).red(),
+                }
+            },
+        };
+        println!("Signature: {signed}");
+
+        println!("Modules:");
+        for module_info in package.get_modules()? {
+            println!("  {}", module_info.get_name());
+            match module_info.get_provenance()? {
+                Some(provenance) => {
+                    println!("    repo: {}", provenance.repo);
+                    println!("    commit: {}", provenance.commit);
+                    println!("    toolchain: {}", provenance.toolchain);
+                    if let Some(digest) = provenance.cargo_lock_digest {
+                        println!("    Cargo.lock digest: {digest}");
+                    }
+                },
+                None => println!("    {}", style("no provenance").yellow()),
+            }
+        }
+
+        match package.get_sbom()? {
+            Some(sbom) => {
+                println!(
+                    "SBOM: {} `{}`, {} packages",
+                    sbom.spdx_version, sbom.name, sbom.packages
+                );
+            },
+            None => println!("SBOM: {}", style("none").yellow()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+//! cli app maintain command
+
+use std::net::SocketAddr;
+
+use clap::Args;
+use console::{style, Emoji};
+use hyper::Method;
+
+use crate::{
+    admin::{ADMIN_ADDR, APPS_MAINTAIN_ROUTE},
+    cli::admin_call,
+    runtime_extensions::hermes::sqlite::maintenance::MaintenanceReport,
+};
+
+/// Maintain the `SQLite` databases of an app running on a hermes node now
+///
+/// Runs an incremental vacuum, `ANALYZE` and an integrity check on the default and named
+/// databases of the app, as done daily in its maintenance window.
+#[derive(Args)]
+pub(crate) struct MaintainCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// App to maintain the databases of
+    app: String,
+
+    /// Print the maintenance reports as JSON
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    json: bool,
+}
+
+impl MaintainCommand {
+    /// Run the app maintain command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let body = admin_call(
+            Method::POST,
+            format!("http://{}{APPS_MAINTAIN_ROUTE}?app={}", self.addr, self.app).parse()?,
+        )?;
+        if self.json {
+            println!("{}", String::from_utf8_lossy(&body));
+            return Ok(());
+        }
+
+        let reports: Vec<MaintenanceReport> = serde_json::from_slice(&body)?;
+        if reports.is_empty() {
+            println!("App {} has no database to maintain", self.app);
+        }
+        for report in &reports {
+            print_report(report);
+        }
+        anyhow::ensure!(
+            reports.iter().all(MaintenanceReport::is_healthy),
+            "Some databases of {} are not healthy",
+            self.app
+        );
+        Ok(())
+    }
+}
+
+/// Print the maintenance report of a database.
+fn print_report(report: &MaintenanceReport) {
+    let database = report.database.as_deref().unwrap_or("default");
+    if let Some(error) = &report.error {
+        println!(
+            "{} {database}: {}",
+            Emoji::new("❌", "FAILED"),
+            style(error).red()
+        );
+    } else if report.is_healthy() {
+        println!(
+            "{} {database}: freed {} pages in {} ms",
+            Emoji::new("✅", "OK"),
+            report.freed_pages,
+            report.duration
+        );
+    } else {
+        println!("{} {database}:", Emoji::new("⚠️", "CORRUPT"));
+        for problem in &report.integrity {
+            println!("    {}", style(problem).yellow());
+        }
+    }
+}
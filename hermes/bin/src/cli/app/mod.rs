@@ -1,17 +1,44 @@
 //! cli app command
 
+use std::path::PathBuf;
+
 use clap::Subcommand;
 
+use crate::{app::ApplicationName, cli::Cli, runtime_extensions::app_config, vfs::Vfs};
+
+mod attest;
+mod export;
+mod import;
+mod inspect;
+mod maintain;
 mod package;
+mod reindex;
 mod sign;
+mod validate;
 
 /// Hermes cli app commands
 #[derive(Subcommand)]
 pub(crate) enum Commands {
     /// package application
     Package(package::PackageCommand),
+    /// validate application manifest
+    Validate(validate::ValidateCommand),
     /// sign application
     Sign(sign::SignCommand),
+    /// inspect application package provenance and SBOM
+    Inspect(inspect::InspectCommand),
+    /// export application data
+    Export(export::ExportCommand),
+    /// import application data
+    Import(import::ImportCommand),
+    /// reindex a Cardano network in a running application
+    Reindex(reindex::ReindexCommand),
+    /// maintain the SQLite databases of a running application
+    Maintain(maintain::MaintainCommand),
+    /// attest the index state of a running application
+    Attest(attest::AttestCommand),
+    /// verify an application state attestation
+    VerifyAttestation(attest::VerifyAttestationCommand),
 }
 
 impl Commands {
@@ -19,7 +46,31 @@ impl Commands {
     pub(crate) fn exec(self) -> anyhow::Result<()> {
         match self {
             Commands::Package(cmd) => cmd.exec(),
+            Commands::Validate(cmd) => cmd.exec(),
             Commands::Sign(cmd) => cmd.exec(),
+            Commands::Inspect(cmd) => cmd.exec(),
+            Commands::Export(cmd) => cmd.exec(),
+            Commands::Import(cmd) => cmd.exec(),
+            Commands::Reindex(cmd) => cmd.exec(),
+            Commands::Maintain(cmd) => cmd.exec(),
+            Commands::Attest(cmd) => cmd.exec(),
+            Commands::VerifyAttestation(cmd) => cmd.exec(),
         }
     }
 }
+
+/// Paths of the VFS and of the own `SQLite` database files of an app, in the Hermes
+/// home.
+///
+/// The database shared by the apps holds the data of the other apps too, so it is never
+/// part of the data of an app.
+fn app_data_files(app_name: &ApplicationName) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let hermes_home = Cli::hermes_home()?;
+    let mut vfs_file = hermes_home.join(&app_name.0);
+    vfs_file.set_extension(Vfs::FILE_EXTENSION);
+
+    app_config::set_sqlite_db_dir(&hermes_home)?;
+    let sqlite_file = app_config::get_app_own_sqlite_db_file(app_name)
+        .ok_or(anyhow::anyhow!("App {app_name} has no SQLite database"))?;
+    Ok((vfs_file, sqlite_file))
+}
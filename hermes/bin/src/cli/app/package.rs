@@ -2,12 +2,19 @@
 
 use std::path::PathBuf;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use clap::Args;
 use console::Emoji;
+use temp_dir::TempDir;
 
+use super::validate::validate_manifest;
 use crate::packaging::app::{ApplicationPackage, Manifest};
 
+/// Environment variable overriding the build date of the package, as a UNIX timestamp
+/// in seconds, so the package can be rebuilt identically.
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+const SOURCE_DATE_EPOCH_ENV: &str = "SOURCE_DATE_EPOCH";
+
 /// Hermes application packaging
 #[derive(Args)]
 pub(crate) struct PackageCommand {
@@ -24,6 +31,11 @@ pub(crate) struct PackageCommand {
     /// The package name, instead of taking it from the manifest file.
     #[clap(long)]
     name: Option<String>,
+
+    /// Rebuild the package from the same manifest and check both builds have the same
+    /// content hash.
+    #[clap(long)]
+    verify_reproducible: bool,
 }
 
 impl PackageCommand {
@@ -46,13 +58,55 @@ impl PackageCommand {
             })
             .unwrap_or(manifest_dir.into());
 
+        println!("{} Validating manifest...", Emoji::new("🧐", ""));
+        validate_manifest(&self.manifest)?;
+
         println!("{} Building package...", Emoji::new("🛠️", ""));
         let manifest = Manifest::from_file(&self.manifest)?;
         let package_name = self.name.as_deref();
-        let build_time = Utc::now();
-        ApplicationPackage::build_from_manifest(&manifest, output_path, package_name, build_time)?;
+        let build_time = build_time()?;
+        let package = ApplicationPackage::build_from_manifest(
+            &manifest,
+            output_path,
+            package_name,
+            build_time,
+        )?;
+        let content_hash = package.content_hash()?;
+        println!("Content hash: {}", content_hash.to_hex());
+
+        if self.verify_reproducible {
+            println!("{} Verifying reproducibility...", Emoji::new("🔁", ""));
+            let rebuild_dir = TempDir::new()?;
+            let rebuild = ApplicationPackage::build_from_manifest(
+                &manifest,
+                rebuild_dir.path(),
+                package_name,
+                build_time,
+            )?;
+            let rebuild_hash = rebuild.content_hash()?;
+            anyhow::ensure!(
+                rebuild_hash == content_hash,
+                "Package is not reproducible, the rebuilt package content hash is {}",
+                rebuild_hash.to_hex()
+            );
+        }
 
         println!("{} Done", Emoji::new("✅", ""));
         Ok(())
     }
 }
+
+/// Build date of the package, taken from `SOURCE_DATE_EPOCH` if set, otherwise now.
+fn build_time() -> anyhow::Result<DateTime<Utc>> {
+    let Ok(epoch) = std::env::var(SOURCE_DATE_EPOCH_ENV) else {
+        return Ok(Utc::now());
+    };
+    epoch
+        .trim()
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .ok_or(anyhow::anyhow!(
+            "Invalid {SOURCE_DATE_EPOCH_ENV} `{epoch}`, expected a UNIX timestamp in seconds"
+        ))
+}
@@ -0,0 +1,55 @@
+//! cli app reindex command
+
+use std::net::SocketAddr;
+
+use clap::Args;
+use console::Emoji;
+use hyper::Method;
+
+use crate::{
+    admin::{ADMIN_ADDR, APPS_REINDEX_ROUTE},
+    cli::admin_call,
+};
+
+/// Index a Cardano network again from a slot, in an app running on a hermes node
+///
+/// Every module of the app resets the data it indexed from the slot onwards, and is
+/// delivered the blocks again from its last checkpoint before the slot.
+#[derive(Args)]
+pub(crate) struct ReindexCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// App to reindex
+    app: String,
+
+    /// Slot to index the network again from
+    #[clap(long)]
+    from_slot: u64,
+
+    /// Cardano network to index again, `mainnet`, `preprod`, `preview` or `testnet`
+    #[clap(long, default_value_t = cardano_chain_follower::Network::Mainnet.to_string())]
+    network: String,
+}
+
+impl ReindexCommand {
+    /// Run the app reindex command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        admin_call(
+            Method::POST,
+            format!(
+                "http://{}{APPS_REINDEX_ROUTE}?app={}&network={}&from_slot={}",
+                self.addr, self.app, self.network, self.from_slot
+            )
+            .parse()?,
+        )?;
+        println!(
+            "{} Reindexing {} from slot {}",
+            Emoji::new("🔁", ""),
+            self.app,
+            self.from_slot
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+//! cli app validate command
+
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use console::{style, Emoji};
+
+use crate::packaging::app::check_manifest;
+
+/// Application package manifest validation
+#[derive(Args)]
+pub(crate) struct ValidateCommand {
+    /// Defines the location of the manifest to validate, against the manifests JSON
+    /// schema, the module packages it references and the application VFS permissions.
+    manifest: PathBuf,
+}
+
+impl ValidateCommand {
+    /// Run cli command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        println!("{} Validate application manifest", Emoji::new("🧐", ""));
+        validate_manifest(&self.manifest)?;
+        println!("{} Done", Emoji::new("✅", ""));
+        Ok(())
+    }
+}
+
+/// Print the diagnostics of the manifest, as `<file>:<line>:<column>: <message>`.
+///
+/// ## Errors
+///
+/// Returns an error if the manifest is not valid.
+pub(super) fn validate_manifest(manifest: &Path) -> anyhow::Result<()> {
+    let diagnostics = check_manifest(manifest)?;
+    for diagnostic in &diagnostics {
+        println!(
+            "{}",
+            style(format!("{}:{diagnostic}", manifest.display())).red()
+        );
+    }
+    anyhow::ensure!(
+        diagnostics.is_empty(),
+        "Invalid manifest {}, {} error(s)",
+        manifest.display(),
+        diagnostics.len()
+    );
+    Ok(())
+}
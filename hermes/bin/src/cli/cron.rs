@@ -0,0 +1,160 @@
+//! cli cron command
+
+use std::net::SocketAddr;
+
+use chrono::{TimeZone, Utc};
+use clap::{Args, Subcommand};
+use console::{style, Emoji};
+use hyper::{Method, Uri};
+
+use crate::{
+    admin::{query, ADMIN_ADDR, CRON_HISTORY_ROUTE, CRON_PAUSE_ROUTE, CRON_RESUME_ROUTE},
+    cli::admin_call,
+    runtime_extensions::hermes::cron::history::CronExecution,
+};
+
+/// Hermes cli cron commands
+#[derive(Subcommand)]
+pub(crate) enum Commands {
+    /// Show the latest cron executions of an app running on a hermes node
+    History(HistoryCommand),
+    /// Pause the delivery of the cron events of an app running on a hermes node, its
+    /// crontab entries stay scheduled
+    Pause(PauseCommand),
+    /// Resume the delivery of the cron events of an app running on a hermes node, the
+    /// ticks missed meanwhile fire according to the misfire policy of their entry
+    Resume(PauseCommand),
+}
+
+impl Commands {
+    /// Execute cli cron command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        match self {
+            Commands::History(cmd) => cmd.exec(),
+            Commands::Pause(cmd) => cmd.exec(true),
+            Commands::Resume(cmd) => cmd.exec(false),
+        }
+    }
+}
+
+/// Pause or resume the delivery of the cron events of an app running on a hermes node
+#[derive(Args)]
+pub(crate) struct PauseCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// App to pause or resume the cron events of
+    app: String,
+}
+
+impl PauseCommand {
+    /// Run the cron pause or resume command
+    pub(crate) fn exec(self, pause: bool) -> anyhow::Result<()> {
+        let route = if pause {
+            CRON_PAUSE_ROUTE
+        } else {
+            CRON_RESUME_ROUTE
+        };
+        let body = admin_call(
+            Method::POST,
+            format!(
+                "http://{}{route}{}",
+                self.addr,
+                query::encode(&[("app", Some(self.app.as_str()))])
+            )
+            .parse()?,
+        )?;
+        let changed = serde_json::from_slice::<serde_json::Value>(&body)?
+            .get("changed")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or_default();
+        match (pause, changed) {
+            (true, true) => {
+                println!(
+                    "{} Paused the cron events of {}",
+                    Emoji::new("⏸️", ""),
+                    self.app
+                )
+            },
+            (false, true) => {
+                println!(
+                    "{} Resumed the cron events of {}",
+                    Emoji::new("▶️", ""),
+                    self.app
+                )
+            },
+            (true, false) => println!("Cron events of {} are already paused", self.app),
+            (false, false) => println!("Cron events of {} are not paused", self.app),
+        }
+        Ok(())
+    }
+}
+
+/// Show the latest cron executions of an app running on a hermes node
+#[derive(Args)]
+pub(crate) struct HistoryCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// App to show the cron executions of
+    #[clap(long)]
+    app: String,
+
+    /// Only show executions of the crontab entries with this tag
+    #[clap(long)]
+    tag: Option<String>,
+
+    /// Print the executions as JSON
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    json: bool,
+}
+
+impl HistoryCommand {
+    /// Run the cron history command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let body = admin_call(Method::GET, self.uri()?)?;
+        if self.json {
+            println!("{}", String::from_utf8_lossy(&body));
+            return Ok(());
+        }
+
+        let executions: Vec<CronExecution> = serde_json::from_slice(&body)?;
+        for execution in executions {
+            Self::print(&execution);
+        }
+        Ok(())
+    }
+
+    /// Cron history route URI, with the app and tag as query parameters.
+    fn uri(&self) -> anyhow::Result<Uri> {
+        let query = query::encode(&[
+            ("app", Some(self.app.as_str())),
+            ("tag", self.tag.as_deref()),
+        ]);
+        Ok(format!("http://{}{CRON_HISTORY_ROUTE}{query}", self.addr).parse()?)
+    }
+
+    /// Print a single cron execution line.
+    fn print(execution: &CronExecution) {
+        let started = i64::try_from(execution.started)
+            .map(|started| Utc.timestamp_nanos(started))
+            .map(|started| started.to_rfc3339())
+            .unwrap_or_default();
+        let result = match (&execution.retrigger, &execution.error) {
+            (_, Some(err)) => style(err.clone()).red(),
+            (retrigger, None) => {
+                style(format!("ok retrigger={}", retrigger.unwrap_or_default())).green()
+            },
+        };
+        println!(
+            "{started} {}/{} {} [{}] {}ms {result}",
+            execution.app,
+            execution.module,
+            style(&execution.tag).yellow(),
+            execution.when,
+            execution.duration / 1_000_000,
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Developer mode of the run command.
+//!
+//! A single WASM module is run as an app of its own, without an application package.
+//! Synthetic events typed on the standard input are injected into the module, and the
+//! outcome of every event it executes is printed, along with its responses to the HTTP
+//! requests. The module logs go to the logger as usual.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fs::File,
+    io::BufRead,
+    path::Path,
+    sync::mpsc::{channel, RecvTimeoutError},
+    time::Duration,
+};
+
+use console::style;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    app::{Application, ApplicationName},
+    event::{self, trace, HermesEvent, HermesEventPayload, TargetApp, TargetModule},
+    runtime_extensions::{
+        bindings::hermes::cardano::api::CardanoBlockchainId,
+        hermes::{
+            cardano, cron,
+            http_gateway::{HTTPEvent, HTTPEventMsg},
+        },
+    },
+    vfs::VfsBootstrapper,
+    wasm::module::Module,
+};
+
+/// Prefix of the name of the app a module is run as.
+const APP_NAME_PREFIX: &str = "dev-";
+
+/// Schedule of the injected cron ticks, when not given.
+const DEFAULT_CRON_WHEN: &str = "* * * * *";
+
+/// Seconds waited for the response of the module to an injected HTTP request.
+const HTTP_RESPONSE_TIMEOUT: u64 = 5;
+
+/// Help of the commands read from the standard input.
+const HELP: &str = "\
+Commands:
+  cron <tag> [<when>]      fire a cron tick of the tag, `* * * * *` by default
+  http <json>              send an HTTP request, e.g. {\"method\":\"POST\",\"path\":\"/api\",\"headers\":{\"accept\":[\"*/*\"]},\"body\":\"{}\"}
+  block <chain> <file>     deliver the raw CBOR block of a fixture file, the chain being mainnet, preprod, preview or local
+  help                     print this help
+  quit                     stop the node";
+
+/// HTTP request injected into the module.
+#[derive(Deserialize)]
+struct HttpRequest {
+    /// HTTP method, `GET` by default.
+    #[serde(default = "default_method")]
+    method: String,
+    /// Request path.
+    path: String,
+    /// Request headers, by name.
+    #[serde(default)]
+    headers: BTreeMap<String, Vec<String>>,
+    /// Request body.
+    #[serde(default)]
+    body: String,
+}
+
+/// Default method of the injected HTTP requests.
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Build the app a single module is run as, named after the module file.
+pub(super) fn build_app(module_path: &Path, hermes_home_dir: &Path) -> anyhow::Result<Application> {
+    let module_name = module_path
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .ok_or_else(|| anyhow::anyhow!("Invalid module file name {}", module_path.display()))?
+        .to_string();
+    let app_name = format!("{APP_NAME_PREFIX}{module_name}");
+
+    let module = Module::from_reader(File::open(module_path)?)?;
+    let vfs = VfsBootstrapper::new(hermes_home_dir, app_name.clone()).bootstrap()?;
+    Ok(Application::new(app_name, vfs, vec![(module_name, module)]))
+}
+
+/// Print the outcome of every event executed by the app, from now on.
+pub(super) fn print_events(app_name: &ApplicationName) -> anyhow::Result<()> {
+    let app = app_name.to_string();
+    let mut traces = trace::subscribe();
+    std::thread::Builder::new()
+        .name("dev-events".to_string())
+        .spawn(move || {
+            loop {
+                let trace = match traces.blocking_recv() {
+                    Ok(trace) => trace,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                if trace.app != app {
+                    continue;
+                }
+                let result = match &trace.error {
+                    None => style("ok".to_string()).green(),
+                    Some(err) => style(err.clone()).red(),
+                };
+                println!(
+                    "{} exec={}us {result}",
+                    style(&trace.event).yellow(),
+                    trace.execution_us,
+                );
+            }
+        })?;
+    Ok(())
+}
+
+/// Inject the events typed on the standard input into the app, until `quit` or the end
+/// of the input.
+pub(super) fn inject_events(app_name: &ApplicationName) -> anyhow::Result<()> {
+    println!("{HELP}");
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        let (command, args) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(command, args)| (command, args.trim()));
+        let res = match command {
+            "" => Ok(()),
+            "cron" => inject_cron(app_name, args),
+            "http" => inject_http(app_name, args),
+            "block" => inject_block(app_name, args),
+            "help" => {
+                println!("{HELP}");
+                Ok(())
+            },
+            "quit" | "exit" => break,
+            _ => {
+                Err(anyhow::anyhow!(
+                    "Unknown command `{command}`, type `help` for the commands"
+                ))
+            },
+        };
+        if let Err(err) = res {
+            println!("{}", style(err).red());
+        }
+    }
+    Ok(())
+}
+
+/// Fire a cron tick, from `<tag> [<when>]`.
+fn inject_cron(app_name: &ApplicationName, args: &str) -> anyhow::Result<()> {
+    let (tag, when) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    anyhow::ensure!(
+        !tag.is_empty(),
+        "Missing cron tag, expected `cron <tag> [<when>]`"
+    );
+    let when = match when.trim() {
+        "" => DEFAULT_CRON_WHEN.to_string(),
+        when => cron::parse_crontab(when).map_err(|err| anyhow::anyhow!(err))?,
+    };
+    send(app_name, cron::tick_event(tag.to_string(), when))
+}
+
+/// Send an HTTP request, from its JSON form, and print the response of the module.
+fn inject_http(app_name: &ApplicationName, args: &str) -> anyhow::Result<()> {
+    let request: HttpRequest = serde_json::from_str(args)
+        .map_err(|err| anyhow::anyhow!("Invalid HTTP request JSON: {err}"))?;
+    let (sender, receiver) = channel();
+    send(app_name, HTTPEvent {
+        headers: request.headers.into_iter().collect(),
+        method: request.method,
+        path: request.path,
+        body: request.body.into(),
+        sender,
+    })?;
+
+    match receiver.recv_timeout(Duration::from_secs(HTTP_RESPONSE_TIMEOUT)) {
+        Ok(HTTPEventMsg::HttpEventResponse((code, headers, body))) => {
+            println!("{}", style(format!("HTTP {code}")).cyan());
+            for (name, values) in headers {
+                println!("{name}: {}", values.join(", "));
+            }
+            println!("{}", String::from_utf8_lossy(&body));
+        },
+        Ok(HTTPEventMsg::HTTPEventReceiver) | Err(RecvTimeoutError::Disconnected) => {
+            println!("{}", style("No HTTP response from the module").yellow());
+        },
+        Err(RecvTimeoutError::Timeout) => {
+            println!(
+                "{}",
+                style(format!(
+                    "No HTTP response from the module within {HTTP_RESPONSE_TIMEOUT}s"
+                ))
+                .yellow()
+            );
+        },
+    }
+    Ok(())
+}
+
+/// Deliver the block of a fixture file, from `<chain> <file>`.
+fn inject_block(app_name: &ApplicationName, args: &str) -> anyhow::Result<()> {
+    let (chain, file) = args
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| anyhow::anyhow!("Expected `block <chain> <file>`"))?;
+    let blockchain = match chain {
+        "mainnet" => CardanoBlockchainId::Mainnet,
+        "preprod" => CardanoBlockchainId::Preprod,
+        "preview" => CardanoBlockchainId::Preview,
+        "local" => CardanoBlockchainId::LocalTestBlockchain,
+        _ => anyhow::bail!("Unknown chain `{chain}`"),
+    };
+    let block = std::fs::read(file.trim())
+        .map_err(|err| anyhow::anyhow!("Failed to read block fixture {}: {err}", file.trim()))?;
+    send(app_name, cardano::block_event(blockchain, block))
+}
+
+/// Send an event to the module of the app.
+fn send(app_name: &ApplicationName, payload: impl HermesEventPayload) -> anyhow::Result<()> {
+    event::queue::send(HermesEvent::new(
+        payload,
+        TargetApp::List(vec![app_name.clone()]),
+        TargetModule::All,
+    ))
+}
@@ -0,0 +1,113 @@
+//! cli events command
+
+use std::net::SocketAddr;
+
+use clap::{Args, Subcommand};
+use console::style;
+use hyper::{body::HttpBody, Client, Method, Uri};
+
+use crate::{
+    admin::{self, query, ADMIN_ADDR, EVENTS_ROUTE},
+    cli::Cli,
+    event::trace::EventTrace,
+};
+
+/// Hermes cli events commands
+#[derive(Subcommand)]
+pub(crate) enum Commands {
+    /// Stream the events dispatched by a running hermes node
+    Tail(TailCommand),
+}
+
+impl Commands {
+    /// Execute cli events command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        match self {
+            Commands::Tail(cmd) => cmd.exec(),
+        }
+    }
+}
+
+/// Stream the events dispatched by a running hermes node
+#[derive(Args)]
+pub(crate) struct TailCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// Only show events dispatched to this app
+    #[clap(long)]
+    app: Option<String>,
+
+    /// Only show events with this name, e.g. `on-cardano-block`
+    #[clap(long)]
+    event: Option<String>,
+
+    /// Print each event as a JSON line
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    json: bool,
+}
+
+impl TailCommand {
+    /// Run the events tail command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        rt.block_on(self.tail())
+    }
+
+    /// Read the events stream, printing every event as it arrives.
+    async fn tail(&self) -> anyhow::Result<()> {
+        let request = admin::request(&Cli::hermes_home()?, Method::GET, self.uri()?)?;
+        let response = Client::new().request(request).await?;
+        anyhow::ensure!(
+            response.status().is_success(),
+            "Admin API responded with {}",
+            response.status()
+        );
+
+        let mut body = response.into_body();
+        let mut buffer = Vec::new();
+        while let Some(chunk) = body.data().await {
+            buffer.extend_from_slice(&chunk?);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                self.print(&line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Events route URI, with the filters as query parameters.
+    fn uri(&self) -> anyhow::Result<Uri> {
+        let query = query::encode(&[
+            ("app", self.app.as_deref()),
+            ("event", self.event.as_deref()),
+        ]);
+        Ok(format!("http://{}{EVENTS_ROUTE}{query}", self.addr).parse()?)
+    }
+
+    /// Print a single event trace line.
+    fn print(&self, line: &[u8]) -> anyhow::Result<()> {
+        if self.json {
+            println!("{}", String::from_utf8_lossy(line).trim_end());
+            return Ok(());
+        }
+
+        let trace: EventTrace = serde_json::from_slice(line)?;
+        let result = match &trace.error {
+            None => style("ok".to_string()).green(),
+            Some(err) => style(err.clone()).red(),
+        };
+        println!(
+            "{} {}/{} wait={}us exec={}us {result}",
+            style(&trace.event).yellow(),
+            trace.app,
+            trace.module,
+            trace.queue_wait_us,
+            trace.execution_us,
+        );
+        Ok(())
+    }
+}
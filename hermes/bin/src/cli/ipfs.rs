@@ -0,0 +1,233 @@
+//! cli ipfs command
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use clap::{Args, Subcommand};
+use console::{style, Emoji};
+use hyper::Method;
+
+use crate::{
+    admin::{ADMIN_ADDR, IPFS_GC_ROUTE, IPFS_PINS_ADD_ROUTE, IPFS_PINS_RM_ROUTE, IPFS_PINS_ROUTE},
+    app::ApplicationName,
+    cli::{admin_call, Cli},
+    ipfs::{self, identity, IpfsGcReport, IpfsPin},
+};
+
+/// Hermes cli ipfs commands
+#[derive(Subcommand)]
+pub(crate) enum Commands {
+    /// Manage the files pinned on the IPFS nodes of a running hermes node
+    #[clap(subcommand)]
+    Pins(PinsCommands),
+    /// Manage the identities of the IPFS nodes
+    #[clap(subcommand)]
+    Id(IdCommands),
+}
+
+impl Commands {
+    /// Execute cli ipfs command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        match self {
+            Commands::Pins(cmd) => cmd.exec(),
+            Commands::Id(cmd) => cmd.exec(),
+        }
+    }
+}
+
+/// Hermes cli ipfs pins commands
+#[derive(Subcommand)]
+pub(crate) enum PinsCommands {
+    /// List the pinned files and the apps holding their pins
+    List(ListCommand),
+    /// Pin a file on behalf of an app
+    Add(PinCommand),
+    /// Release the pin of an app on a file, un-pinning it once no app holds a pin
+    Rm(PinCommand),
+    /// Remove the blocks which are not pinned from the blockstores, never touching the
+    /// blocks reachable from a recursive pin
+    Gc(GcCommand),
+}
+
+impl PinsCommands {
+    /// Execute cli ipfs pins command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        match self {
+            PinsCommands::List(cmd) => cmd.exec(),
+            PinsCommands::Add(cmd) => cmd.exec(IPFS_PINS_ADD_ROUTE),
+            PinsCommands::Rm(cmd) => cmd.exec(IPFS_PINS_RM_ROUTE),
+            PinsCommands::Gc(cmd) => cmd.exec(),
+        }
+    }
+}
+
+/// List the pinned files and the apps holding their pins
+#[derive(Args)]
+pub(crate) struct ListCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// Only list the files pinned by this app
+    #[clap(long)]
+    app: Option<String>,
+
+    /// Print the pins as JSON
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    json: bool,
+}
+
+impl ListCommand {
+    /// Run the ipfs pins list command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let body = admin_call(
+            Method::GET,
+            format!("http://{}{IPFS_PINS_ROUTE}", self.addr).parse()?,
+        )?;
+        let mut pins: Vec<IpfsPin> = serde_json::from_slice(&body)?;
+        if let Some(app) = &self.app {
+            pins.retain(|pin| pin.apps.contains(app));
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string(&pins)?);
+            return Ok(());
+        }
+        for pin in pins {
+            let apps = if pin.apps.is_empty() {
+                style("-".to_string()).dim()
+            } else {
+                style(pin.apps.join(",")).yellow()
+            };
+            println!("{} {apps}", pin.cid);
+        }
+        Ok(())
+    }
+}
+
+/// Pin a file on behalf of an app, or release the pin of an app
+#[derive(Args)]
+pub(crate) struct PinCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// App holding the pin
+    #[clap(long)]
+    app: String,
+
+    /// CID of the file
+    cid: String,
+}
+
+impl PinCommand {
+    /// Run the ipfs pins add or rm command, sending the request to the `route`
+    pub(crate) fn exec(self, route: &str) -> anyhow::Result<()> {
+        admin_call(
+            Method::POST,
+            format!(
+                "http://{}{route}?app={}&cid={}",
+                self.addr, self.app, self.cid
+            )
+            .parse()?,
+        )?;
+        println!("{} Done", Emoji::new("✅", ""));
+        Ok(())
+    }
+}
+
+/// Remove the blocks which are not pinned from the blockstores
+#[derive(Args)]
+pub(crate) struct GcCommand {
+    /// Address of the hermes node admin API
+    #[clap(long, default_value_t = ADMIN_ADDR)]
+    addr: SocketAddr,
+
+    /// Only remove the blocks stored at least this many seconds ago
+    #[clap(long, default_value_t = 0)]
+    min_age: u64,
+
+    /// Report the reclaimable blocks and bytes without removing them
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    dry_run: bool,
+}
+
+impl GcCommand {
+    /// Run the ipfs pins gc command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let body = admin_call(
+            Method::POST,
+            format!(
+                "http://{}{IPFS_GC_ROUTE}?min_age={}&dry_run={}",
+                self.addr, self.min_age, self.dry_run
+            )
+            .parse()?,
+        )?;
+        let report: IpfsGcReport = serde_json::from_slice(&body)?;
+        if report.dry_run {
+            println!(
+                "{} {} unpinned blocks, {} bytes reclaimable",
+                Emoji::new("🔍", ""),
+                report.blocks.len(),
+                report.bytes
+            );
+        } else {
+            println!(
+                "{} Removed {} unpinned blocks, {} bytes",
+                Emoji::new("🗑️", ""),
+                report.blocks.len(),
+                report.bytes
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Hermes cli ipfs id commands
+#[derive(Subcommand)]
+pub(crate) enum IdCommands {
+    /// Show the `PeerId` of an IPFS node
+    Show(IdCommand),
+    /// Replace the identity of an IPFS node by a new one, used from its next start
+    Rotate(IdCommand),
+}
+
+impl IdCommands {
+    /// Execute cli ipfs id command
+    pub(crate) fn exec(self) -> anyhow::Result<()> {
+        match self {
+            IdCommands::Show(cmd) => {
+                let repo_path = cmd.repo_path()?;
+                let peer_id = identity::peer_id(&repo_path)?.ok_or(anyhow::anyhow!(
+                    "The IPFS node has no identity yet, it is created when the node first starts"
+                ))?;
+                println!("{peer_id}");
+            },
+            IdCommands::Rotate(cmd) => {
+                let peer_id = identity::rotate(&cmd.repo_path()?)?;
+                println!(
+                    "{} New identity {}, used from the next start of the node",
+                    Emoji::new("🔑", ""),
+                    style(peer_id).yellow()
+                );
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Select the IPFS node of the identity
+#[derive(Args)]
+pub(crate) struct IdCommand {
+    /// Dedicated node of this app, with the `per-app` IPFS topology, instead of the
+    /// shared node
+    #[clap(long)]
+    app: Option<String>,
+}
+
+impl IdCommand {
+    /// Path of the repo of the selected node.
+    fn repo_path(&self) -> anyhow::Result<PathBuf> {
+        let app_name = self.app.clone().map(ApplicationName);
+        Ok(ipfs::repo_path(&Cli::hermes_home()?, app_name.as_ref()))
+    }
+}
@@ -2,6 +2,10 @@
 
 mod app;
 mod build_info;
+mod cron;
+mod dev;
+mod events;
+mod ipfs;
 mod module;
 mod run;
 
@@ -10,8 +14,10 @@ use std::path::PathBuf;
 use build_info::BUILD_INFO;
 use clap::{Parser, Subcommand};
 use console::{style, Emoji};
+use hyper::{body::to_bytes, Client, Method, Uri};
 
 use crate::{
+    admin,
     errors::Errors,
     logger::{self, LoggerConfigBuilder},
 };
@@ -44,6 +50,15 @@ enum Commands {
     /// app commands
     #[clap(subcommand)]
     App(app::Commands),
+    /// event debugging commands
+    #[clap(subcommand)]
+    Events(events::Commands),
+    /// cron commands
+    #[clap(subcommand)]
+    Cron(cron::Commands),
+    /// IPFS commands
+    #[clap(subcommand)]
+    Ipfs(ipfs::Commands),
 }
 
 impl Cli {
@@ -81,6 +96,9 @@ impl Cli {
             Commands::Run(cmd) => cmd.exec(),
             Commands::Module(cmd) => cmd.exec(),
             Commands::App(cmd) => cmd.exec(),
+            Commands::Events(cmd) => cmd.exec(),
+            Commands::Cron(cmd) => cmd.exec(),
+            Commands::Ipfs(cmd) => cmd.exec(),
         }
         .unwrap_or_else(errors.get_add_err_fn());
 
@@ -89,3 +107,22 @@ impl Cli {
         }
     }
 }
+
+/// Send a request to the admin API, returning the response body.
+pub(crate) fn admin_call(method: Method, uri: Uri) -> anyhow::Result<Vec<u8>> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    rt.block_on(async move {
+        let request = admin::request(&Cli::hermes_home()?, method, uri)?;
+        let response = Client::new().request(request).await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body()).await?;
+        anyhow::ensure!(
+            status.is_success(),
+            "Admin API responded with {status}: {}",
+            String::from_utf8_lossy(&body)
+        );
+        Ok(body.to_vec())
+    })
+}
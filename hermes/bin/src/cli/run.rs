@@ -1,25 +1,39 @@
 //! Run cli command
 
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
 use clap::Args;
 use console::Emoji;
+use hermes_ipfs::Multiaddr;
 
 use crate::{
-    cli::Cli,
-    ipfs,
+    admin,
+    cli::{dev, Cli},
+    ipfs, jobs,
     packaging::{
         app::{build_app, ApplicationPackage},
         sign::certificate::{self, Certificate},
     },
     reactor,
+    runtime_extensions::{app_config, hermes::cardano},
+    wasm::{latency, module_store, profiler},
 };
 
+/// Default Cardano chain data directory in the Hermes home directory.
+const CARDANO_DATA_DIR: &str = "cardano";
+
 /// Run cli command
 #[derive(Args)]
 pub(crate) struct Run {
     /// Path to the Hermes application package to run
-    app_package: PathBuf,
+    #[clap(required_unless_present = "dev")]
+    app_package: Option<PathBuf>,
+
+    /// Developer mode, run the single WASM module at this path without an application
+    /// package, and inject the events typed on the standard input into it, e.g. cron
+    /// ticks, HTTP requests or Cardano block fixtures
+    #[clap(long, conflicts_with = "app_package")]
+    dev: Option<PathBuf>,
 
     /// Path to the trusted certificate
     #[clap(name = "cert", short)]
@@ -28,36 +42,193 @@ pub(crate) struct Run {
     /// Flag which disables package signature verification
     #[clap(long, action = clap::ArgAction::SetTrue)]
     untrusted: bool,
+
+    /// Run a single IPFS node shared by all apps, or a dedicated node per app
+    #[clap(long, value_enum, default_value_t)]
+    ipfs_topology: ipfs::IpfsTopology,
+
+    /// Seconds during which IPFS `PubSub` messages already delivered to the apps are
+    /// dropped, 0 to deliver duplicates
+    #[clap(long, default_value_t = ipfs::DEFAULT_REPLAY_WINDOW.as_secs())]
+    ipfs_pubsub_replay_window: u64,
+
+    /// Seconds between re-publishing the IPFS DHT records put by the apps, so they do not
+    /// expire, 0 to disable
+    #[clap(long, default_value_t = ipfs::DEFAULT_DHT_REPUBLISH_INTERVAL.as_secs())]
+    ipfs_dht_republish_interval: u64,
+
+    /// Discover IPFS peers on the local network with mDNS
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    ipfs_mdns: bool,
+
+    /// Do not bootstrap the IPFS nodes with the public IPFS network bootstrap peers,
+    /// e.g. for air-gapped deployments
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    ipfs_no_default_bootstrap: bool,
+
+    /// Multiaddr of a peer to bootstrap the IPFS nodes with, e.g.
+    /// `/ip4/10.0.0.2/tcp/4001/p2p/<peer-id>`
+    #[clap(long = "ipfs-bootstrap-peer")]
+    ipfs_bootstrap_peers: Vec<Multiaddr>,
+
+    /// Multiaddr the IPFS nodes listen on, e.g. `/ip4/0.0.0.0/tcp/4001`, instead of
+    /// the default listener
+    #[clap(long = "ipfs-listen-addr")]
+    ipfs_listen_addrs: Vec<Multiaddr>,
+
+    /// Directory the Cardano chain data is stored in, `<hermes home>/cardano` by default.
+    /// The immutable blocks of a network, e.g. restored from a Mithril snapshot, are read
+    /// from its `<network>/immutable` subdirectory
+    #[clap(long)]
+    cardano_data_dir: Option<PathBuf>,
+
+    /// Number of epochs of immutable Cardano chain data kept, older chunks are pruned, 0
+    /// to keep it all
+    #[clap(long, default_value_t = 0)]
+    cardano_keep_epochs: u64,
+
+    /// Seconds between the reports of the memory profiler, which samples the memory
+    /// usage of the modules after every event and flags its growth, 0 to disable
+    #[clap(long, default_value_t = 0)]
+    profile_memory_interval: u64,
+
+    /// Milliseconds a host function call may take before a warning is logged, 0 to
+    /// disable
+    #[clap(long, default_value_t = 0)]
+    host_call_budget: u64,
+
+    /// Latency budget of a host function, overriding `--host-call-budget`, as
+    /// `<function>=<milliseconds>` where the function is named as in its interface, e.g.
+    /// `[method]statement.step=50`, optionally prefixed with the interface, e.g. `api.`
+    #[clap(long = "host-call-budget-fn", value_parser = latency::parse_function_budget)]
+    host_call_budgets: Vec<(String, Duration)>,
+
+    /// Address the admin API listens on
+    #[clap(long, default_value_t = admin::ADMIN_ADDR)]
+    admin_addr: SocketAddr,
+
+    /// Unix socket the admin API listens on, instead of `--admin-addr`
+    #[cfg(unix)]
+    #[clap(long)]
+    admin_socket: Option<PathBuf>,
+
+    /// Replicate the `SQLite` database of the app across nodes, `publish` its changes to
+    /// the following nodes, or `follow` the changes published by another node
+    #[cfg(feature = "replication")]
+    #[clap(long, value_enum, requires = "replication_key")]
+    replication: Option<crate::runtime_extensions::hermes::sqlite::replication::ReplicationRole>,
+
+    /// Path to the replication key in PEM format, the private key the changes are signed
+    /// with when publishing, or the public key of the publishing node when following
+    #[cfg(feature = "replication")]
+    #[clap(long, requires = "replication")]
+    replication_key: Option<PathBuf>,
+
+    /// Path to a runtime extension plugin shared library to load
+    #[cfg(feature = "plugins")]
+    #[clap(long = "plugin")]
+    plugins: Vec<PathBuf>,
 }
 
 impl Run {
     /// Run the hermes application
     pub(crate) fn exec(self) -> anyhow::Result<()> {
+        let admin_listener = self.admin_listener();
+
+        #[cfg(feature = "plugins")]
+        for plugin_path in &self.plugins {
+            crate::runtime_extensions::plugin::load(plugin_path)?;
+        }
+
         for cert_path in self.certificates {
             let cert = Certificate::from_file(cert_path)?;
             certificate::storage::add_certificate(cert)?;
         }
 
-        let package = ApplicationPackage::from_file(self.app_package)?;
-        package.validate(self.untrusted)?;
+        if self.host_call_budget > 0 || !self.host_call_budgets.is_empty() {
+            latency::init(
+                (self.host_call_budget > 0).then(|| Duration::from_millis(self.host_call_budget)),
+                self.host_call_budgets.into_iter().collect(),
+            )?;
+        }
+        if self.profile_memory_interval > 0 {
+            profiler::init(Duration::from_secs(self.profile_memory_interval))?;
+        }
+
+        let package = match self.app_package {
+            Some(app_package) => {
+                let package = ApplicationPackage::from_file(app_package)?;
+                package.validate(self.untrusted)?;
+                Some(package)
+            },
+            None => None,
+        };
 
         let hermes_home_dir = Cli::hermes_home()?;
+        app_config::set_sqlite_db_dir(&hermes_home_dir)?;
+        jobs::init(&hermes_home_dir)?;
+        module_store::init(&hermes_home_dir)?;
+
+        let cardano_data_dir = self
+            .cardano_data_dir
+            .unwrap_or_else(|| hermes_home_dir.join(CARDANO_DATA_DIR));
+        cardano::configure_chain_data(&cardano_data_dir, self.cardano_keep_epochs)?;
 
-        // enable bootstrapping the IPFS node to default addresses
-        let default_bootstrap = true;
+        let network = ipfs::IpfsNetworkConfig {
+            mdns: self.ipfs_mdns,
+            default_bootstrap: !self.ipfs_no_default_bootstrap,
+            bootstrap_peers: self.ipfs_bootstrap_peers,
+            listen_addrs: self.ipfs_listen_addrs,
+        };
         tracing::info!("{} Bootstrapping IPFS node", console::Emoji::new("🖧", ""),);
-        ipfs::bootstrap(hermes_home_dir.as_path(), default_bootstrap)?;
-        let app = build_app(&package, hermes_home_dir)?;
+        ipfs::bootstrap(
+            hermes_home_dir.as_path(),
+            network,
+            self.ipfs_topology,
+            Duration::from_secs(self.ipfs_pubsub_replay_window),
+            Duration::from_secs(self.ipfs_dht_republish_interval),
+        )?;
+        let app = match (&package, &self.dev) {
+            (Some(package), _) => build_app(package, &hermes_home_dir)?,
+            (None, Some(module_path)) => dev::build_app(module_path, &hermes_home_dir)?,
+            (None, None) => anyhow::bail!("Missing application package"),
+        };
+        ipfs::bootstrap_app(app.name())?;
+        #[cfg(feature = "replication")]
+        if let (Some(role), Some(key_path)) = (self.replication, &self.replication_key) {
+            crate::runtime_extensions::hermes::sqlite::replication::init(
+                app.name(),
+                role,
+                key_path,
+            )?;
+        }
 
         reactor::init()?;
+        admin::spawn(admin_listener, &hermes_home_dir)?;
         println!(
             "{} Loading application {}...",
             Emoji::new("🛠️", ""),
             app.name()
         );
+        let app_name = app.name().clone();
+        if self.dev.is_some() {
+            dev::print_events(&app_name)?;
+        }
         reactor::load_app(app)?;
+        if self.dev.is_some() {
+            dev::inject_events(&app_name)?;
+        }
         std::thread::yield_now();
 
         Ok(())
     }
+
+    /// Where the admin API listens.
+    fn admin_listener(&self) -> admin::Listener {
+        #[cfg(unix)]
+        if let Some(path) = &self.admin_socket {
+            return admin::Listener::Unix(path.clone());
+        }
+        admin::Listener::Tcp(self.admin_addr)
+    }
 }
@@ -1,6 +1,9 @@
 //! Hermes event's primitives.
 
 pub(crate) mod queue;
+pub(crate) mod trace;
+
+use std::time::Instant;
 
 use crate::{
     app::ApplicationName,
@@ -43,6 +46,21 @@ pub(crate) enum TargetModule {
     List(Vec<ModuleId>),
 }
 
+/// Priority of a Hermes event in the event queue.
+///
+/// The queued events of a higher priority are executed before the ones of a lower
+/// priority, the events of the same priority are executed in the order they are queued.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum EventPriority {
+    /// Latency sensitive events.
+    High,
+    /// Most of the events.
+    #[default]
+    Normal,
+    /// Bulk or housekeeping events, which can wait.
+    Low,
+}
+
 /// Hermes event
 pub(crate) struct HermesEvent {
     /// The payload carried by the `HermesEvent`.
@@ -53,6 +71,12 @@ pub(crate) struct HermesEvent {
 
     /// Target module
     target_module: TargetModule,
+
+    /// Time the event was created and queued
+    queued_at: Instant,
+
+    /// Priority of the event in the event queue
+    priority: EventPriority,
 }
 
 impl HermesEvent {
@@ -64,9 +88,18 @@ impl HermesEvent {
             payload: Box::new(payload),
             target_app,
             target_module,
+            queued_at: Instant::now(),
+            priority: EventPriority::default(),
         }
     }
 
+    /// Set the priority of the event in the event queue
+    #[must_use]
+    pub(crate) fn with_priority(mut self, priority: EventPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     /// Get event's payload
     pub(crate) fn payload(&self) -> &dyn HermesEventPayload {
         self.payload.as_ref()
@@ -81,4 +114,14 @@ impl HermesEvent {
     pub(crate) fn target_module(&self) -> &TargetModule {
         &self.target_module
     }
+
+    /// Get the time the event was queued
+    pub(crate) fn queued_at(&self) -> Instant {
+        self.queued_at
+    }
+
+    /// Get event's priority
+    pub(crate) fn priority(&self) -> EventPriority {
+        self.priority
+    }
 }
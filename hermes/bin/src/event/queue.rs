@@ -1,18 +1,40 @@
 //! Hermes event queue implementation.
 
 use std::{
-    sync::mpsc::{Receiver, Sender},
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{Receiver, Sender},
+    },
     thread::{self},
 };
 
 use once_cell::sync::OnceCell;
+use serde::Serialize;
 
-use super::{HermesEvent, TargetApp, TargetModule};
+use super::{EventPriority, HermesEvent, TargetApp, TargetModule};
 use crate::{app::ApplicationName, reactor};
 
 /// Singleton instance of the Hermes event queue.
 static EVENT_QUEUE_INSTANCE: OnceCell<HermesEventQueue> = OnceCell::new();
 
+/// Number of events added into the event queue.
+static QUEUED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of events taken out of the event queue and executed.
+static EXECUTED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// Event queue counters.
+#[derive(Debug, Serialize)]
+pub(crate) struct QueueStats {
+    /// Number of events added into the event queue.
+    pub(crate) queued: u64,
+    /// Number of events executed.
+    pub(crate) executed: u64,
+    /// Number of events waiting in the event queue, or being executed.
+    pub(crate) pending: u64,
+}
+
 /// Failed to add event into the event queue. Event queue is closed.
 #[derive(thiserror::Error, Debug, Clone)]
 #[error("Failed to add event into the event queue. Event queue is closed.")]
@@ -62,10 +84,22 @@ pub(crate) fn send(event: HermesEvent) -> anyhow::Result<()> {
     let queue = EVENT_QUEUE_INSTANCE.get().ok_or(NotInitializedError)?;
 
     queue.sender.send(event).map_err(|_| CannotAddEventError)?;
+    QUEUED_EVENTS.fetch_add(1, Ordering::Relaxed);
 
     Ok(())
 }
 
+/// Get the event queue counters.
+pub(crate) fn stats() -> QueueStats {
+    let executed = EXECUTED_EVENTS.load(Ordering::Relaxed);
+    let queued = QUEUED_EVENTS.load(Ordering::Relaxed);
+    QueueStats {
+        queued,
+        executed,
+        pending: queued.saturating_sub(executed),
+    }
+}
+
 /// Executes provided Hermes event filtering by target module.
 fn targeted_module_event_execution(target_app_name: &ApplicationName, event: &HermesEvent) {
     let Ok(app) = reactor::get_app(target_app_name) else {
@@ -75,15 +109,17 @@ fn targeted_module_event_execution(target_app_name: &ApplicationName, event: &He
 
     match event.target_module() {
         TargetModule::All => {
-            if let Err(err) = app.dispatch_event(event.payload()) {
+            if let Err(err) = app.dispatch_event(event.payload(), event.queued_at()) {
                 tracing::error!("{err}");
             }
         },
         TargetModule::List(target_modules) => {
             for target_module_id in target_modules {
-                if let Err(err) =
-                    app.dispatch_event_for_target_module(target_module_id.clone(), event.payload())
-                {
+                if let Err(err) = app.dispatch_event_for_target_module(
+                    target_module_id.clone(),
+                    event.payload(),
+                    event.queued_at(),
+                ) {
                     tracing::error!("{err}");
                 }
             }
@@ -109,9 +145,113 @@ fn targeted_app_event_execution(event: &HermesEvent) {
     }
 }
 
-/// Executes Hermes events from the provided receiver .
+/// Events waiting to be executed, a queue per event priority.
+#[derive(Default)]
+struct PriorityQueues {
+    /// Events of the high priority.
+    high: VecDeque<HermesEvent>,
+    /// Events of the normal priority.
+    normal: VecDeque<HermesEvent>,
+    /// Events of the low priority.
+    low: VecDeque<HermesEvent>,
+}
+
+impl PriorityQueues {
+    /// Add the event at the back of the queue of its priority.
+    fn push(&mut self, event: HermesEvent) {
+        match event.priority() {
+            EventPriority::High => self.high.push_back(event),
+            EventPriority::Normal => self.normal.push_back(event),
+            EventPriority::Low => self.low.push_back(event),
+        }
+    }
+
+    /// Take the oldest event of the highest priority.
+    fn pop(&mut self) -> Option<HermesEvent> {
+        self.high
+            .pop_front()
+            .or_else(|| self.normal.pop_front())
+            .or_else(|| self.low.pop_front())
+    }
+
+    /// Move every event already sent through the receiver into the queues.
+    ///
+    /// Blocks until an event is received when the queues are empty.
+    /// Returns `false` when the queues are empty and the event queue is closed.
+    fn fill(&mut self, receiver: &Receiver<HermesEvent>) -> bool {
+        if self.is_empty() {
+            let Ok(event) = receiver.recv() else {
+                return false;
+            };
+            self.push(event);
+        }
+        while let Ok(event) = receiver.try_recv() {
+            self.push(event);
+        }
+        true
+    }
+
+    /// Whether no event is waiting.
+    fn is_empty(&self) -> bool {
+        self.high.is_empty() && self.normal.is_empty() && self.low.is_empty()
+    }
+}
+
+/// Executes Hermes events from the provided receiver, the events of a higher priority
+/// first.
 fn event_execution_loop(receiver: Receiver<HermesEvent>) {
-    for event in receiver {
-        targeted_app_event_execution(&event);
+    let mut queues = PriorityQueues::default();
+    while queues.fill(&receiver) {
+        if let Some(event) = queues.pop() {
+            targeted_app_event_execution(&event);
+            EXECUTED_EVENTS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::HermesEventPayload, wasm::module::ModuleInstance};
+
+    /// Event payload named after its order in the test.
+    struct Payload(&'static str);
+
+    impl HermesEventPayload for Payload {
+        fn event_name(&self) -> &str {
+            self.0
+        }
+
+        fn execute(&self, _module: &mut ModuleInstance) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn event(name: &'static str, priority: EventPriority) -> HermesEvent {
+        HermesEvent::new(Payload(name), TargetApp::List(vec![]), TargetModule::All)
+            .with_priority(priority)
+    }
+
+    #[test]
+    fn test_priority_queues() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        sender.send(event("low", EventPriority::Low)).unwrap();
+        sender
+            .send(event("normal 1", EventPriority::Normal))
+            .unwrap();
+        sender.send(event("high", EventPriority::High)).unwrap();
+        sender
+            .send(event("normal 2", EventPriority::Normal))
+            .unwrap();
+
+        let mut queues = PriorityQueues::default();
+        assert!(queues.fill(&receiver));
+        let order: Vec<_> = std::iter::from_fn(|| queues.pop())
+            .map(|event| event.payload().event_name().to_string())
+            .collect();
+        assert_eq!(order, ["high", "normal 1", "normal 2", "low"]);
+
+        drop(sender);
+        assert!(!queues.fill(&receiver));
     }
 }
@@ -0,0 +1,77 @@
+//! Live feed of dispatched Hermes events, used for debugging.
+
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::{app::ApplicationName, wasm::module::ModuleId};
+
+/// Number of traces kept for slow subscribers before they start to lag behind.
+const TRACE_CAPACITY: usize = 1024;
+
+/// Broadcast channel of dispatched event traces.
+static TRACES: Lazy<broadcast::Sender<EventTrace>> =
+    Lazy::new(|| broadcast::channel(TRACE_CAPACITY).0);
+
+/// Trace of a single event dispatched to a single module.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct EventTrace {
+    /// Name of the event.
+    pub(crate) event: String,
+    /// Target application.
+    pub(crate) app: String,
+    /// Target module.
+    pub(crate) module: String,
+    /// Time the event spent in the queue before the module started executing it, in
+    /// microseconds.
+    pub(crate) queue_wait_us: u64,
+    /// Time the module spent executing the event, in microseconds.
+    pub(crate) execution_us: u64,
+    /// Error returned by the module, if the execution failed.
+    pub(crate) error: Option<String>,
+}
+
+impl EventTrace {
+    /// Create a new event trace.
+    pub(crate) fn new(
+        event: &str, app_name: &ApplicationName, module_id: &ModuleId, queue_wait: Duration,
+        execution: Duration, result: &anyhow::Result<()>,
+    ) -> Self {
+        Self {
+            event: event.to_string(),
+            app: app_name.to_string(),
+            module: module_id.to_string(),
+            queue_wait_us: as_micros(queue_wait),
+            execution_us: as_micros(execution),
+            error: result.as_ref().err().map(ToString::to_string),
+        }
+    }
+}
+
+/// Convert a duration to whole microseconds, saturating on overflow.
+fn as_micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Is anyone listening to the event traces.
+pub(crate) fn is_enabled() -> bool {
+    TRACES.receiver_count() > 0
+}
+
+/// Publish an event trace to all subscribers.
+pub(crate) fn publish(trace: EventTrace) {
+    // Nobody listening is not an error.
+    let _unused = TRACES.send(trace);
+}
+
+/// Subscribe to the event traces published from now on.
+pub(crate) fn subscribe() -> broadcast::Receiver<EventTrace> {
+    TRACES.subscribe()
+}
+
+/// Elapsed time between an event being queued and its dispatch starting.
+pub(crate) fn queue_wait(queued_at: Instant, started_at: Instant) -> Duration {
+    started_at.saturating_duration_since(queued_at)
+}
@@ -25,7 +25,10 @@ impl File {
     pub(crate) fn create(group: &hdf5::Group, file_name: &str) -> anyhow::Result<Self> {
         let builder = group.new_dataset_builder();
         let shape = hdf5::SimpleExtents::resizable([0]);
+        // Do not store the creation and modification times of the file, so the same
+        // content always gives the same package.
         let hdf5_ds = enable_compression(builder)
+            .obj_track_times(false)
             .empty::<u8>()
             .shape(shape)
             .create(file_name)?;
@@ -67,6 +67,9 @@ impl ResourceTrait for FsResource {
         for entry in entries {
             res.push(FsResource(entry?.path()));
         }
+        // The directory entries order depends on the filesystem, sort them so the packages
+        // are built the same way everywhere.
+        res.sort_by(|a, b| a.0.cmp(&b.0));
         Ok(res)
     }
 }
@@ -1,10 +1,21 @@
 //! Hermes IPFS State API
-use super::{is_valid_dht_content, is_valid_pubsub_content, HERMES_IPFS};
+use std::time::Duration;
+
+use hermes_ipfs::{GcOptions, IpfsPath as BaseIpfsPath, Keypair};
+
+use super::{
+    all_nodes, app_node, is_valid_dht_content, is_valid_pubsub_content, limits,
+    schedule_dht_republish, IpfsFetch, IpfsUsage,
+};
 use crate::{
     app::ApplicationName,
-    runtime_extensions::bindings::hermes::ipfs::api::{
-        DhtKey, DhtValue, Errno, IpfsContent, IpfsFile, IpfsPath, MessageData, MessageId, PeerId,
-        PubsubTopic,
+    bandwidth::{self, Direction},
+    runtime_extensions::bindings::hermes::{
+        init::api::HealthStatus,
+        ipfs::api::{
+            DhtKey, DhtRecord, DhtValue, Errno, IpfsContent, IpfsFile, IpfsPath, MessageData,
+            MessageId, PeerId, PubsubTopic, PubsubTopicHealth,
+        },
     },
 };
 
@@ -13,8 +24,18 @@ pub(crate) fn hermes_ipfs_add_file(
     app_name: &ApplicationName, contents: IpfsFile,
 ) -> Result<IpfsPath, Errno> {
     tracing::debug!(app_name = %app_name, "adding IPFS file");
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    limits::check_file_size(app_name, contents.len())?;
+    admit(app_name)?;
+    let ipfs = app_node(app_name)?;
+    let size = contents.len();
     let ipfs_path = ipfs.file_add(contents)?.to_string();
+    bandwidth::record(app_name, Direction::Sent, size);
+    ipfs.apps.record_usage(app_name, |usage| {
+        usage.files_added = usage.files_added.saturating_add(1);
+        usage.bytes_added = usage
+            .bytes_added
+            .saturating_add(u64::try_from(size).unwrap_or(u64::MAX));
+    });
     tracing::debug!(app_name = %app_name, path = %ipfs_path, "added IPFS file");
     ipfs.apps.pinned_file(app_name.clone(), &ipfs_path)?;
     Ok(ipfs_path)
@@ -43,9 +64,24 @@ pub(crate) fn hermes_ipfs_content_validate(
 pub(crate) fn hermes_ipfs_get_file(
     app_name: &ApplicationName, path: &IpfsPath,
 ) -> Result<IpfsFile, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     tracing::debug!(app_name = %app_name, path = %path, "get IPFS file");
-    let content = ipfs.file_get(path)?;
+    admit(app_name)?;
+    let fetch_id = ipfs.apps.fetch_started(app_name.clone(), path.clone());
+    let res = ipfs.file_get(path);
+    ipfs.apps.fetch_finished(fetch_id);
+    let content = res.inspect_err(|err| {
+        tracing::debug!(app_name = %app_name, path = %path, error = ?err, "failed to get IPFS file");
+        ipfs.apps.record_usage(app_name, |usage| {
+            usage.fetches_failed = usage.fetches_failed.saturating_add(1);
+        });
+    })?;
+    ipfs.apps.record_usage(app_name, |usage| {
+        usage.bytes_fetched = usage
+            .bytes_fetched
+            .saturating_add(u64::try_from(content.len()).unwrap_or(u64::MAX));
+    });
+    bandwidth::record(app_name, Direction::Received, content.len());
     tracing::debug!(app_name = %app_name, path = %path, "got IPFS file");
     Ok(content)
 }
@@ -54,7 +90,7 @@ pub(crate) fn hermes_ipfs_get_file(
 pub(crate) fn hermes_ipfs_pin_file(
     app_name: &ApplicationName, path: &IpfsPath,
 ) -> Result<bool, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     tracing::debug!(app_name = %app_name, path = %path, "pin IPFS file");
     let status = ipfs.file_pin(path)?;
     tracing::debug!(app_name = %app_name, path = %path, "pinned IPFS file");
@@ -66,7 +102,7 @@ pub(crate) fn hermes_ipfs_pin_file(
 pub(crate) fn hermes_ipfs_unpin_file(
     app_name: &ApplicationName, path: &IpfsPath,
 ) -> Result<bool, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     tracing::debug!(app_name = %app_name, path = %path, "un-pin IPFS file");
     let status = ipfs.file_unpin(path)?;
     tracing::debug!(app_name = %app_name, path = %path, "un-pinned IPFS file");
@@ -74,36 +110,122 @@ pub(crate) fn hermes_ipfs_unpin_file(
     Ok(status)
 }
 
+/// Release the pin of an IPFS file held by an app.
+///
+/// The app no longer holds the pin, and the file is un-pinned from the node once no other
+/// app holds a pin on it, so its blocks can be garbage collected.
+pub(crate) fn hermes_ipfs_release_pin(
+    app_name: &ApplicationName, path: &IpfsPath,
+) -> Result<bool, Errno> {
+    let ipfs = app_node(app_name)?;
+    let ipfs_path: BaseIpfsPath = path.parse().map_err(|_| Errno::InvalidIpfsPath)?;
+    let cid = ipfs_path.root().cid().ok_or(Errno::InvalidCid)?;
+    ipfs.apps.unpinned_file(app_name, path)?;
+    if !ipfs.apps.pin_owners(cid).is_empty() {
+        tracing::debug!(app_name = %app_name, path = %path, "released IPFS pin, still held by other apps");
+        return Ok(true);
+    }
+    tracing::debug!(app_name = %app_name, path = %path, "released IPFS pin, un-pinning file");
+    ipfs.file_unpin(path)
+}
+
 /// Get DHT Value
 pub(crate) fn hermes_ipfs_get_dht_value(
     app_name: &ApplicationName, key: DhtKey,
 ) -> Result<DhtValue, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     let key_str = format!("{key:x?}");
     tracing::debug!(app_name = %app_name, dht_key = %key_str, "get DHT value");
+    admit(app_name)?;
     let value = ipfs.dht_get(key)?;
+    bandwidth::record(app_name, Direction::Received, value.len());
     tracing::debug!(app_name = %app_name, dht_key = %key_str, "got DHT value");
     Ok(value)
 }
 
+/// Get DHT Record
+pub(crate) fn hermes_ipfs_get_dht_record(
+    app_name: &ApplicationName, key: DhtKey,
+) -> Result<DhtRecord, Errno> {
+    let ipfs = app_node(app_name)?;
+    let key_str = format!("{key:x?}");
+    tracing::debug!(app_name = %app_name, dht_key = %key_str, "get DHT record");
+    admit(app_name)?;
+    let record = ipfs.dht_get_record(key)?;
+    bandwidth::record(app_name, Direction::Received, record.value.len());
+    tracing::debug!(app_name = %app_name, dht_key = %key_str, ttl = ?record.ttl, "got DHT record");
+    Ok(record)
+}
+
+/// Get the DHT values held by up to `max` peers, to compare them.
+pub(crate) fn hermes_ipfs_get_dht_values(
+    app_name: &ApplicationName, key: DhtKey, max: usize,
+) -> Result<Vec<DhtValue>, Errno> {
+    let ipfs = app_node(app_name)?;
+    let key_str = format!("{key:x?}");
+    tracing::debug!(app_name = %app_name, dht_key = %key_str, max, "get DHT values");
+    admit(app_name)?;
+    let values = ipfs.dht_get_values(key, max)?;
+    bandwidth::record(
+        app_name,
+        Direction::Received,
+        values.iter().map(Vec::len).sum(),
+    );
+    tracing::debug!(app_name = %app_name, dht_key = %key_str, count = values.len(), "got DHT values");
+    Ok(values)
+}
+
 /// Put DHT Value
 pub(crate) fn hermes_ipfs_put_dht_value(
     app_name: &ApplicationName, key: DhtKey, value: DhtValue,
 ) -> Result<bool, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    limits::check_dht_value_size(app_name, value.len())?;
+    let ipfs = app_node(app_name)?;
     let key_str = format!("{key:x?}");
     tracing::debug!(app_name = %app_name, dht_key = %key_str, "putting DHT value");
-    let status = ipfs.dht_put(key.clone(), value)?;
+    admit(app_name)?;
+    let status = ipfs.dht_put(key.clone(), value.clone())?;
+    bandwidth::record(app_name, Direction::Sent, value.len());
+    ipfs.apps.record_usage(app_name, |usage| {
+        usage.dht_puts = usage.dht_puts.saturating_add(1);
+    });
     tracing::debug!(app_name = %app_name, dht_key = %key_str, "have put DHT value");
-    ipfs.apps.added_dht_key(app_name.clone(), key);
+    if status {
+        schedule_dht_republish(app_name, &key, &value);
+    }
+    ipfs.apps.added_dht_record(app_name.clone(), key, value);
     Ok(status)
 }
 
+/// Put a short lived DHT Value, which is neither re-published nor tracked as a record
+/// of the app, so a stale value is never put again over a newer one.
+pub(crate) fn hermes_ipfs_put_ephemeral_dht_value(
+    app_name: &ApplicationName, key: DhtKey, value: DhtValue,
+) -> Result<bool, Errno> {
+    limits::check_dht_value_size(app_name, value.len())?;
+    let ipfs = app_node(app_name)?;
+    let key_str = format!("{key:x?}");
+    tracing::debug!(app_name = %app_name, dht_key = %key_str, "putting ephemeral DHT value");
+    admit(app_name)?;
+    let size = value.len();
+    let status = ipfs.dht_put(key, value)?;
+    bandwidth::record(app_name, Direction::Sent, size);
+    ipfs.apps.record_usage(app_name, |usage| {
+        usage.dht_puts = usage.dht_puts.saturating_add(1);
+    });
+    Ok(status)
+}
+
+/// Keypair of the IPFS node of the app, whose `PeerId` identifies it.
+pub(crate) fn hermes_ipfs_keypair(app_name: &ApplicationName) -> Result<Keypair, Errno> {
+    Ok(app_node(app_name)?.keypair.clone())
+}
+
 /// Subscribe to a topic
 pub(crate) fn hermes_ipfs_subscribe(
     app_name: &ApplicationName, topic: PubsubTopic,
 ) -> Result<bool, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     tracing::debug!(app_name = %app_name, pubsub_topic = %topic, "subscribing to PubSub topic");
     if ipfs.apps.topic_subscriptions_contains(&topic) {
         tracing::debug!(app_name = %app_name, pubsub_topic = %topic, "topic subscription stream already exists");
@@ -119,18 +241,48 @@ pub(crate) fn hermes_ipfs_subscribe(
 
 /// Publish message to a topic
 pub(crate) fn hermes_ipfs_publish(
-    _app_name: &ApplicationName, topic: &PubsubTopic, message: MessageData,
+    app_name: &ApplicationName, topic: &PubsubTopic, message: MessageData,
 ) -> Result<MessageId, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
-    ipfs.pubsub_publish(topic.to_string(), message)
-        .map(|m| m.0 .0)
+    limits::check_message_size(app_name, message.len())?;
+    admit(app_name)?;
+    let ipfs = app_node(app_name)?;
+    let size = message.len();
+    let message_id = ipfs
+        .pubsub_publish(topic.to_string(), message)
+        .map(|m| m.0 .0)?;
+    ipfs.apps.published(topic.clone());
+    ipfs.apps.record_usage(app_name, |usage| {
+        usage.messages_published = usage.messages_published.saturating_add(1);
+    });
+    bandwidth::record(app_name, Direction::Sent, size);
+    Ok(message_id)
+}
+
+/// List the peers subscribed to a topic
+pub(crate) fn hermes_ipfs_pubsub_peers(
+    app_name: &ApplicationName, topic: &PubsubTopic,
+) -> Result<Vec<PeerId>, Errno> {
+    let ipfs = app_node(app_name)?;
+    ipfs.pubsub_peers(topic)
+}
+
+/// Health of a topic
+pub(crate) fn hermes_ipfs_pubsub_topic_health(
+    app_name: &ApplicationName, topic: &PubsubTopic,
+) -> Result<PubsubTopicHealth, Errno> {
+    let ipfs = app_node(app_name)?;
+    let peers = ipfs.pubsub_peers(topic)?;
+    Ok(PubsubTopicHealth {
+        peers: u32::try_from(peers.len()).unwrap_or(u32::MAX),
+        last_publish_accepted: ipfs.apps.last_publish(topic),
+    })
 }
 
 /// Evict Peer from node
 pub(crate) fn hermes_ipfs_evict_peer(
     app_name: &ApplicationName, peer: PeerId,
 ) -> Result<bool, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     tracing::debug!(app_name = %app_name, peer_id = %peer, "evicting peer");
     let status = ipfs.peer_evict(&peer.to_string())?;
     tracing::debug!(app_name = %app_name, peer_id = %peer, "evicted peer");
@@ -141,6 +293,120 @@ pub(crate) fn hermes_ipfs_evict_peer(
 #[allow(dead_code)]
 /// List pinned files
 pub(crate) fn hermes_ipfs_ls(app_name: &ApplicationName) -> Result<Vec<String>, Errno> {
-    let ipfs = HERMES_IPFS.get().ok_or(Errno::ServiceUnavailable)?;
+    let ipfs = app_node(app_name)?;
     Ok(ipfs.apps.list_pinned_files(app_name))
 }
+
+/// Health of the IPFS node serving the app, or of all IPFS nodes if no app is given.
+///
+/// A node is `degraded` while it has no connected peers, and `unavailable` if it was
+/// not started or does not respond. The worst status of the nodes is returned.
+pub(crate) fn hermes_ipfs_health(app_name: Option<&ApplicationName>) -> HealthStatus {
+    let nodes = match app_name {
+        Some(app_name) => app_node(app_name).into_iter().collect(),
+        None => all_nodes(),
+    };
+    if nodes.is_empty() {
+        return HealthStatus::Unavailable;
+    }
+
+    let mut health = HealthStatus::Ready;
+    for ipfs in nodes {
+        match ipfs.connected_peers() {
+            Ok(peers) if peers.is_empty() => health = HealthStatus::Degraded,
+            Ok(_) => (),
+            Err(_) => return HealthStatus::Unavailable,
+        }
+    }
+    health
+}
+
+/// IPFS resource usage of every app, across all IPFS nodes.
+pub(crate) fn hermes_ipfs_usage() -> Vec<(ApplicationName, IpfsUsage)> {
+    all_nodes()
+        .iter()
+        .flat_map(|ipfs| ipfs.apps.usage())
+        .collect()
+}
+
+/// File fetches in flight on every IPFS node, the longest running first on each node.
+pub(crate) fn hermes_ipfs_fetches() -> Vec<IpfsFetch> {
+    all_nodes()
+        .iter()
+        .flat_map(|ipfs| ipfs.apps.fetches())
+        .collect()
+}
+
+/// A file pinned on an IPFS node.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) struct IpfsPin {
+    /// CID of the pinned file.
+    pub(crate) cid: String,
+    /// Apps holding the pin, empty if it was not pinned by an app.
+    pub(crate) apps: Vec<String>,
+}
+
+/// Files pinned on every IPFS node, with the apps holding their pins.
+pub(crate) fn hermes_ipfs_pins() -> Result<Vec<IpfsPin>, Errno> {
+    let mut pins = Vec::new();
+    for ipfs in all_nodes() {
+        for cid in ipfs.pins()? {
+            let apps = ipfs
+                .apps
+                .pin_owners(&cid)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            pins.push(IpfsPin {
+                cid: cid.to_string(),
+                apps,
+            });
+        }
+    }
+    Ok(pins)
+}
+
+/// Result of a garbage collection of the IPFS blockstores.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub(crate) struct IpfsGcReport {
+    /// CIDs of the removed blocks, or of the reclaimable blocks in dry-run mode.
+    pub(crate) blocks: Vec<String>,
+    /// Size of the removed, or reclaimable, blocks in bytes.
+    pub(crate) bytes: u64,
+    /// Whether the blocks were only reported, not removed.
+    pub(crate) dry_run: bool,
+}
+
+/// Remove the blocks which are not pinned, and were stored at least `min_age` ago,
+/// from the blockstore of every IPFS node.
+///
+/// With `dry_run` the blocks are only reported.
+pub(crate) fn hermes_ipfs_gc(min_age: Duration, dry_run: bool) -> Result<IpfsGcReport, Errno> {
+    let mut report = IpfsGcReport {
+        dry_run,
+        ..IpfsGcReport::default()
+    };
+    for ipfs in all_nodes() {
+        let node_report = ipfs.gc(GcOptions { min_age, dry_run })?;
+        tracing::info!(
+            blocks = node_report.blocks.len(),
+            bytes = node_report.bytes,
+            dry_run,
+            "collected IPFS blockstore garbage"
+        );
+        report.bytes = report.bytes.saturating_add(node_report.bytes);
+        report
+            .blocks
+            .extend(node_report.blocks.iter().map(ToString::to_string));
+    }
+    Ok(report)
+}
+
+/// Check the app can start a new transfer, according to its bandwidth caps.
+fn admit(app_name: &ApplicationName) -> Result<(), Errno> {
+    if bandwidth::admit(app_name) {
+        Ok(())
+    } else {
+        Err(Errno::BandwidthCapExceeded)
+    }
+}
@@ -0,0 +1,115 @@
+//! `PubSub` message deduplication.
+//!
+//! The same gossipsub message can reach a node more than once while the mesh topology
+//! churns, and the same content can be re-published under a new message id. Messages are
+//! remembered by their gossipsub identity and by a hash of their topic and content for a
+//! replay window, so modules only receive them once.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use hermes_ipfs::rust_ipfs::libp2p::gossipsub::Message;
+
+use crate::packaging::hash::{Blake2b256, Blake2b256Hasher};
+
+/// Default replay window of `PubSub` messages.
+pub const DEFAULT_REPLAY_WINDOW: Duration = Duration::from_secs(120);
+
+/// Key a message is remembered by.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MessageKey {
+    /// Publisher and sequence number, which identify a gossipsub message.
+    Id(Vec<u8>, u64),
+    /// Hash of the topic and content.
+    Content(Blake2b256),
+}
+
+/// `PubSub` messages seen during the replay window.
+pub(crate) struct SeenMessages {
+    /// How long messages are remembered.
+    window: Duration,
+    /// Remembered message keys, and the same keys by the time they were seen.
+    seen: Mutex<(HashSet<MessageKey>, VecDeque<(Instant, MessageKey)>)>,
+}
+
+impl SeenMessages {
+    /// Create `SeenMessages` remembering messages for `window`.
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: Mutex::new((HashSet::new(), VecDeque::new())),
+        }
+    }
+
+    /// Remember the message, returning `true` if it was already seen during the replay
+    /// window.
+    pub(crate) fn is_duplicate(&self, msg: &Message) -> bool {
+        self.check(Self::keys(msg), Instant::now())
+    }
+
+    /// Remember the message keys, returning `true` if one of them was already seen.
+    fn check(&self, keys: Vec<MessageKey>, now: Instant) -> bool {
+        let mut guard = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        let (set, order) = &mut *guard;
+        while let Some((seen_at, _)) = order.front() {
+            if now.saturating_duration_since(*seen_at) < self.window {
+                break;
+            }
+            if let Some((_, key)) = order.pop_front() {
+                set.remove(&key);
+            }
+        }
+
+        if keys.iter().any(|key| set.contains(key)) {
+            return true;
+        }
+        for key in keys {
+            set.insert(key.clone());
+            order.push_back((now, key));
+        }
+        false
+    }
+
+    /// Keys the message is remembered by.
+    fn keys(msg: &Message) -> Vec<MessageKey> {
+        let mut hasher = Blake2b256Hasher::new();
+        hasher.update(msg.topic.as_str().as_bytes());
+        hasher.update(&msg.data);
+
+        let mut keys = vec![MessageKey::Content(hasher.finalize())];
+        if let (Some(source), Some(seq_no)) = (msg.source, msg.sequence_number) {
+            keys.push(MessageKey::Id(source.to_bytes(), seq_no));
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_window_test() {
+        let seen = SeenMessages::new(Duration::from_secs(10));
+        let now = Instant::now();
+        let id = MessageKey::Id(vec![1], 1);
+        let content = MessageKey::Content(Blake2b256::hash(b"announcement"));
+
+        assert!(!seen.check(vec![content.clone(), id.clone()], now));
+        // Same id with another content, e.g. re-delivered through another mesh peer.
+        assert!(seen.check(
+            vec![MessageKey::Content(Blake2b256::hash(b"other")), id.clone()],
+            now
+        ));
+        // Same content re-published under another id.
+        assert!(seen.check(
+            vec![content.clone(), MessageKey::Id(vec![1], 2)],
+            now + Duration::from_secs(5)
+        ));
+        // Forgotten after the window.
+        assert!(!seen.check(vec![content, id], now + Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,108 @@
+//! IPFS node identity.
+//!
+//! The keypair of a node is stored in the `identity.key` file of its repo, so the node
+//! keeps the same `PeerId` across restarts and bootstrap lists and provider records
+//! referencing it stay valid. It is created when the node first starts, only readable by
+//! its owner.
+
+use std::{io::Write, path::Path};
+
+use hermes_ipfs::{Keypair, PeerId};
+
+/// Name of the keypair file in the IPFS repo.
+const KEYPAIR_FILE: &str = "identity.key";
+
+/// Keypair of the node with the `repo_path` repo, generating it if needed.
+pub(super) fn load_or_generate(repo_path: &Path) -> anyhow::Result<Keypair> {
+    match load(repo_path)? {
+        Some(keypair) => Ok(keypair),
+        None => {
+            let keypair = Keypair::generate_ed25519();
+            store(repo_path, &keypair)?;
+            tracing::info!(peer_id = %keypair.public().to_peer_id(), "Generated IPFS node identity");
+            Ok(keypair)
+        },
+    }
+}
+
+/// `PeerId` of the node with the `repo_path` repo, `None` if it has no identity yet.
+///
+/// ## Errors
+///
+/// Returns errors if the keypair file cannot be read or decoded.
+pub(crate) fn peer_id(repo_path: &Path) -> anyhow::Result<Option<PeerId>> {
+    Ok(load(repo_path)?.map(|keypair| keypair.public().to_peer_id()))
+}
+
+/// Replace the identity of the node with the `repo_path` repo by a new one, returning
+/// its `PeerId`.
+///
+/// A running node keeps its identity until it restarts.
+///
+/// ## Errors
+///
+/// Returns errors if the keypair file cannot be written.
+pub(crate) fn rotate(repo_path: &Path) -> anyhow::Result<PeerId> {
+    let keypair = Keypair::generate_ed25519();
+    store(repo_path, &keypair)?;
+    Ok(keypair.public().to_peer_id())
+}
+
+/// Load the keypair of the node with the `repo_path` repo, if it exists.
+fn load(repo_path: &Path) -> anyhow::Result<Option<Keypair>> {
+    let path = repo_path.join(KEYPAIR_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(&path)?;
+    let keypair = Keypair::from_protobuf_encoding(&bytes)
+        .map_err(|err| anyhow::anyhow!("Invalid IPFS keypair file {}: {err}", path.display()))?;
+    Ok(Some(keypair))
+}
+
+/// Store the keypair in the `repo_path` repo, only readable by its owner.
+///
+/// The keypair is written to a temporary file first, so an existing keypair is never
+/// left half written.
+fn store(repo_path: &Path, keypair: &Keypair) -> anyhow::Result<()> {
+    std::fs::create_dir_all(repo_path)?;
+    let bytes = keypair.to_protobuf_encoding()?;
+
+    let tmp_path = repo_path.join(format!("{KEYPAIR_FILE}.tmp"));
+    let _unused = std::fs::remove_file(&tmp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    std::fs::rename(tmp_path, repo_path.join(KEYPAIR_FILE))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn identity_test() {
+        let dir = TempDir::new().unwrap();
+        let repo_path = dir.path().join("ipfs");
+        assert!(peer_id(&repo_path).unwrap().is_none());
+
+        let keypair = load_or_generate(&repo_path).unwrap();
+        let id = keypair.public().to_peer_id();
+        assert_eq!(peer_id(&repo_path).unwrap(), Some(id));
+        assert_eq!(
+            load_or_generate(&repo_path).unwrap().public().to_peer_id(),
+            id
+        );
+
+        let rotated = rotate(&repo_path).unwrap();
+        assert_ne!(rotated, id);
+        assert_eq!(peer_id(&repo_path).unwrap(), Some(rotated));
+    }
+}
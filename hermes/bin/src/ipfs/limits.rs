@@ -0,0 +1,118 @@
+//! IPFS content size limits of apps.
+
+use std::io::Read;
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::ApplicationName, runtime_extensions::bindings::hermes::ipfs::api::Errno};
+
+/// IPFS content size limits of the apps.
+static APP_LIMITS: Lazy<DashMap<ApplicationName, IpfsLimits>> = Lazy::new(DashMap::new);
+
+/// Limits on the size of the content an app puts on IPFS, unlimited when not set.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct IpfsLimits {
+    /// Maximum size of an added file, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_file_size: Option<u64>,
+    /// Maximum size of a DHT value, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_dht_value_size: Option<u64>,
+    /// Maximum size of a published `PubSub` message, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_message_size: Option<u64>,
+}
+
+impl IpfsLimits {
+    /// Create `IpfsLimits` from reader.
+    pub(crate) fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Convert `IpfsLimits` object to json bytes
+    pub(crate) fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let bytes = serde_json::to_vec(&self)?;
+        Ok(bytes)
+    }
+}
+
+/// Set the IPFS content size limits of an app.
+pub(crate) fn set_app_limits(app_name: ApplicationName, limits: IpfsLimits) {
+    APP_LIMITS.insert(app_name, limits);
+}
+
+/// Check the size of a file added by an app.
+pub(super) fn check_file_size(app_name: &ApplicationName, size: usize) -> Result<(), Errno> {
+    check(
+        app_name,
+        |limits| limits.max_file_size,
+        size,
+        Errno::FileTooLarge,
+    )
+}
+
+/// Check the size of a DHT value put by an app.
+pub(super) fn check_dht_value_size(app_name: &ApplicationName, size: usize) -> Result<(), Errno> {
+    check(
+        app_name,
+        |limits| limits.max_dht_value_size,
+        size,
+        Errno::DhtValueTooLarge,
+    )
+}
+
+/// Check the size of a `PubSub` message published by an app.
+pub(super) fn check_message_size(app_name: &ApplicationName, size: usize) -> Result<(), Errno> {
+    check(
+        app_name,
+        |limits| limits.max_message_size,
+        size,
+        Errno::PubsubMessageTooLarge,
+    )
+}
+
+/// Check `size` against the limit of an app, returning `errno` if it is exceeded.
+fn check(
+    app_name: &ApplicationName, limit: impl Fn(&IpfsLimits) -> Option<u64>, size: usize,
+    errno: Errno,
+) -> Result<(), Errno> {
+    let Some(max) = APP_LIMITS.get(app_name).and_then(|limits| limit(&limits)) else {
+        return Ok(());
+    };
+    if u64::try_from(size).unwrap_or(u64::MAX) > max {
+        tracing::debug!(app_name = %app_name, size, max, "IPFS content size limit exceeded");
+        return Err(errno);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipfs_limits_test() {
+        let app_name = ApplicationName("ipfs_limits_test".to_string());
+        assert!(check_file_size(&app_name, usize::MAX).is_ok());
+
+        let limits =
+            IpfsLimits::from_reader(r#"{"max_file_size": 10, "max_message_size": 0}"#.as_bytes())
+                .unwrap();
+        set_app_limits(app_name.clone(), limits);
+        assert!(check_file_size(&app_name, 10).is_ok());
+        assert!(matches!(
+            check_file_size(&app_name, 11),
+            Err(Errno::FileTooLarge)
+        ));
+        assert!(check_dht_value_size(&app_name, usize::MAX).is_ok());
+        assert!(matches!(
+            check_message_size(&app_name, 1),
+            Err(Errno::PubsubMessageTooLarge)
+        ));
+
+        assert!(IpfsLimits::from_reader(r#"{"max_size": 10}"#.as_bytes()).is_err());
+    }
+}
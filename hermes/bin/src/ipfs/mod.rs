@@ -1,20 +1,39 @@
 //! Hermes IPFS service.
 mod api;
+mod dedup;
+pub(crate) mod identity;
+mod limits;
 mod task;
 
-use std::{collections::HashSet, path::Path, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
 
 pub(crate) use api::{
     hermes_ipfs_add_file, hermes_ipfs_content_validate, hermes_ipfs_evict_peer,
-    hermes_ipfs_get_dht_value, hermes_ipfs_get_file, hermes_ipfs_pin_file, hermes_ipfs_publish,
-    hermes_ipfs_put_dht_value, hermes_ipfs_subscribe, hermes_ipfs_unpin_file,
+    hermes_ipfs_fetches, hermes_ipfs_gc, hermes_ipfs_get_dht_record, hermes_ipfs_get_dht_value,
+    hermes_ipfs_get_dht_values, hermes_ipfs_get_file, hermes_ipfs_health, hermes_ipfs_keypair,
+    hermes_ipfs_pin_file, hermes_ipfs_pins, hermes_ipfs_publish, hermes_ipfs_pubsub_peers,
+    hermes_ipfs_pubsub_topic_health, hermes_ipfs_put_dht_value,
+    hermes_ipfs_put_ephemeral_dht_value, hermes_ipfs_release_pin, hermes_ipfs_subscribe,
+    hermes_ipfs_unpin_file, hermes_ipfs_usage, IpfsGcReport, IpfsPin,
 };
 use dashmap::DashMap;
+use dedup::SeenMessages;
+pub use dedup::DEFAULT_REPLAY_WINDOW;
 use hermes_ipfs::{
-    AddIpfsFile, Cid, HermesIpfs, IpfsBuilder, IpfsPath as BaseIpfsPath,
-    MessageId as PubsubMessageId,
+    AddIpfsFile, Cid, GcOptions, GcReport, HermesIpfs, IpfsBuilder, IpfsPath as BaseIpfsPath,
+    Keypair, MessageId as PubsubMessageId, Multiaddr,
 };
-use once_cell::sync::OnceCell;
+pub(crate) use limits::{set_app_limits, IpfsLimits};
+use once_cell::sync::{Lazy, OnceCell};
 use task::{ipfs_command_handler, IpfsCommand};
 use tokio::{
     runtime::Builder,
@@ -24,8 +43,9 @@ use tokio::{
 
 use crate::{
     app::ApplicationName,
+    jobs::{self, Job, JobOutcome},
     runtime_extensions::bindings::hermes::ipfs::api::{
-        DhtKey, DhtValue, Errno, IpfsFile, IpfsPath, MessageData, PeerId, PubsubTopic,
+        DhtKey, DhtRecord, DhtValue, Errno, IpfsFile, IpfsPath, MessageData, PeerId, PubsubTopic,
     },
 };
 
@@ -40,65 +60,314 @@ use crate::{
 ///
 /// The IPFS Node is initialized in a separate thread and the sender channel is stored in
 /// the `HermesIpfsNode`.
-pub(crate) static HERMES_IPFS: OnceCell<HermesIpfsNode> = OnceCell::new();
+///
+/// Only set when running with the `IpfsTopology::Shared` topology.
+pub(crate) static HERMES_IPFS: OnceCell<Arc<HermesIpfsNode>> = OnceCell::new();
+
+/// Dedicated IPFS nodes of apps, when running with the `IpfsTopology::PerApp` topology.
+static APP_IPFS_NODES: Lazy<DashMap<ApplicationName, Arc<HermesIpfsNode>>> =
+    Lazy::new(DashMap::new);
+
+/// IPFS configuration, set when bootstrapping.
+static IPFS_CONFIG: OnceCell<IpfsConfig> = OnceCell::new();
+
+/// Topology of the IPFS nodes serving Hermes apps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum IpfsTopology {
+    /// A single node shared by all apps.
+    /// Pins, DHT keys and topic subscriptions are tracked per app.
+    #[default]
+    Shared,
+    /// A dedicated node per app, each with its own repo and keys.
+    PerApp,
+}
+
+/// Network configuration of the IPFS nodes.
+#[derive(Debug, Clone)]
+pub struct IpfsNetworkConfig {
+    /// Discover peers on the local network with mDNS.
+    pub mdns: bool,
+    /// Bootstrap nodes with the default addresses of the public IPFS network.
+    pub default_bootstrap: bool,
+    /// Additional addresses of the peers to bootstrap nodes with.
+    pub bootstrap_peers: Vec<Multiaddr>,
+    /// Addresses nodes listen on, the default listener when empty.
+    ///
+    /// With the `IpfsTopology::PerApp` topology every app node listens on them, so
+    /// ports should be `0`.
+    pub listen_addrs: Vec<Multiaddr>,
+}
+
+impl Default for IpfsNetworkConfig {
+    fn default() -> Self {
+        Self {
+            mdns: false,
+            default_bootstrap: true,
+            bootstrap_peers: Vec::new(),
+            listen_addrs: Vec::new(),
+        }
+    }
+}
+
+impl IpfsNetworkConfig {
+    /// IPFS node builder with this configuration, storing the repo in `storage_path`,
+    /// and the keypair of the node.
+    ///
+    /// The node identity is loaded from the repo, or generated on first use.
+    fn builder(&self, storage_path: PathBuf) -> anyhow::Result<(IpfsBuilder, Keypair)> {
+        let keypair = identity::load_or_generate(&storage_path)?;
+        let mut builder = IpfsBuilder::new().with_default().set_keypair(&keypair);
+        builder = if self.listen_addrs.is_empty() {
+            builder.set_default_listener()
+        } else {
+            builder.set_listening_addrs(self.listen_addrs.clone())
+        };
+        if self.mdns {
+            builder = builder.with_mdns();
+        }
+        Ok((builder.set_disk_storage(storage_path), keypair))
+    }
+}
+
+/// IPFS configuration
+struct IpfsConfig {
+    /// Directory holding the IPFS repos.
+    data_path: PathBuf,
+    /// Network configuration of the nodes.
+    network: IpfsNetworkConfig,
+    /// Topology of the nodes.
+    topology: IpfsTopology,
+    /// How long `PubSub` messages are remembered to drop duplicates.
+    pubsub_replay_window: Duration,
+    /// Interval between re-publishing the DHT records put by apps, zero to disable.
+    dht_republish_interval: Duration,
+}
+
+/// Directory holding the IPFS repos in the Hermes home directory.
+const IPFS_DIR: &str = "ipfs";
+
+/// Directory holding the repos of the dedicated app nodes in the IPFS directory.
+const APPS_DIR: &str = "apps";
+
+/// Kind of the jobs re-publishing the DHT records put by apps.
+const DHT_REPUBLISH_JOB: &str = "ipfs-dht-republish";
+
+/// Default interval between re-publishing the DHT records put by apps.
+pub const DEFAULT_DHT_REPUBLISH_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
 
 /// Bootstrap `HERMES_IPFS` node.
 ///
+/// With the `IpfsTopology::PerApp` topology no node is started here, app nodes are
+/// started by `bootstrap_app`.
+///
+/// Nodes discover and listen for peers as set by the `network` configuration.
+///
+/// `PubSub` messages already delivered during the `pubsub_replay_window` are dropped, a
+/// zero window disables the deduplication.
+///
+/// DHT records put by apps are re-published every `dht_republish_interval`, so they do
+/// not expire, a zero interval disables re-publishing. The re-publishing is scheduled as
+/// jobs, so it carries on after the node restarts.
+///
 /// ## Errors
 ///
 /// Returns errors if IPFS node fails to start.
-pub fn bootstrap(base_dir: &Path, default_bootstrap: bool) -> anyhow::Result<()> {
-    let ipfs_data_path = base_dir.join("ipfs");
+pub fn bootstrap(
+    base_dir: &Path, network: IpfsNetworkConfig, topology: IpfsTopology,
+    pubsub_replay_window: Duration, dht_republish_interval: Duration,
+) -> anyhow::Result<()> {
+    let ipfs_data_path = repo_path(base_dir, None);
+    IPFS_CONFIG
+        .set(IpfsConfig {
+            data_path: ipfs_data_path.clone(),
+            network: network.clone(),
+            topology,
+            pubsub_replay_window,
+            dht_republish_interval,
+        })
+        .map_err(|_| anyhow::anyhow!("IPFS already bootstrapped"))?;
+
+    if topology == IpfsTopology::Shared {
+        let (builder, keypair) = network.builder(ipfs_data_path)?;
+        let ipfs_node = HermesIpfsNode::init(builder, keypair, network, None)?;
+        HERMES_IPFS
+            .set(Arc::new(ipfs_node))
+            .map_err(|_| anyhow::anyhow!("failed to start IPFS node"))?;
+    }
+
+    jobs::register(DHT_REPUBLISH_JOB, republish_dht_record);
+    Ok(())
+}
+
+/// Schedule the re-publishing of a DHT record put by an app.
+fn schedule_dht_republish(app_name: &ApplicationName, key: &DhtKey, value: &DhtValue) {
+    let Some(interval) = IPFS_CONFIG
+        .get()
+        .map(|config| config.dht_republish_interval)
+        .filter(|interval| !interval.is_zero())
+    else {
+        return;
+    };
+    let run_at = SystemTime::now() + interval;
+    if let Err(err) = jobs::schedule(
+        DHT_REPUBLISH_JOB,
+        app_name,
+        &hex::encode(key),
+        value,
+        run_at,
+    ) {
+        tracing::warn!(app_name = %app_name, "failed to schedule DHT record re-publishing: {err}");
+    }
+}
+
+/// Put a DHT record of an app again, so it does not expire.
+///
+/// Records scheduled while re-publishing was enabled are dropped once it is disabled.
+fn republish_dht_record(job: &Job) -> anyhow::Result<JobOutcome> {
+    let interval = IPFS_CONFIG
+        .get()
+        .ok_or(anyhow::anyhow!("IPFS is not bootstrapped"))?
+        .dht_republish_interval;
+    if interval.is_zero() {
+        return Ok(JobOutcome::Done);
+    }
+    let key = hex::decode(&job.key)?;
+    let ipfs = app_node(&job.app).map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    tracing::debug!(app_name = %job.app, dht_key = job.key, "re-publishing DHT record");
+    match ipfs.dht_put(key.clone(), job.payload.clone()) {
+        Ok(true) => (),
+        Ok(false) => anyhow::bail!("DHT put failed"),
+        Err(err) => anyhow::bail!("DHT put failed: {err:?}"),
+    }
+    ipfs.apps
+        .added_dht_record(job.app.clone(), key, job.payload.clone());
+    Ok(JobOutcome::RunAt(SystemTime::now() + interval))
+}
+
+/// Bootstrap the dedicated IPFS node of an app.
+///
+/// Does nothing unless running with the `IpfsTopology::PerApp` topology.
+///
+/// ## Errors
+///
+/// Returns errors if IPFS is not bootstrapped, or the app node fails to start.
+pub(crate) fn bootstrap_app(app_name: &ApplicationName) -> anyhow::Result<()> {
+    let config = IPFS_CONFIG
+        .get()
+        .ok_or(anyhow::anyhow!("IPFS is not bootstrapped"))?;
+    if config.topology != IpfsTopology::PerApp || APP_IPFS_NODES.contains_key(app_name) {
+        return Ok(());
+    }
+
+    let (builder, keypair) = config
+        .network
+        .builder(config.data_path.join(APPS_DIR).join(&app_name.0))?;
     let ipfs_node = HermesIpfsNode::init(
-        IpfsBuilder::new()
-            .with_default()
-            .set_default_listener()
-            .set_disk_storage(ipfs_data_path.clone()),
-        default_bootstrap,
+        builder,
+        keypair,
+        config.network.clone(),
+        Some(app_name.clone()),
     )?;
-    HERMES_IPFS
-        .set(ipfs_node)
-        .map_err(|_| anyhow::anyhow!("failed to start IPFS node"))?;
+    APP_IPFS_NODES.insert(app_name.clone(), Arc::new(ipfs_node));
     Ok(())
 }
 
+/// Path of the repo of the shared IPFS node, or of the dedicated node of `app_name`, in
+/// the `base_dir` Hermes home directory.
+pub(crate) fn repo_path(base_dir: &Path, app_name: Option<&ApplicationName>) -> PathBuf {
+    let path = base_dir.join(IPFS_DIR);
+    match app_name {
+        Some(app_name) => path.join(APPS_DIR).join(&app_name.0),
+        None => path,
+    }
+}
+
+/// IPFS node serving the app.
+///
+/// ## Errors
+/// - `Errno::ServiceUnavailable`: No node is running for the app
+pub(crate) fn app_node(app_name: &ApplicationName) -> Result<Arc<HermesIpfsNode>, Errno> {
+    match APP_IPFS_NODES.get(app_name) {
+        Some(node) => Ok(node.value().clone()),
+        None => HERMES_IPFS.get().cloned().ok_or(Errno::ServiceUnavailable),
+    }
+}
+
+/// Stop delivering to an unloaded app the `PubSub` messages of the topics it subscribed
+/// to. Its pins and DHT records are kept.
+pub(crate) fn unload_app(app_name: &ApplicationName) {
+    for node in all_nodes() {
+        node.apps.removed_app_topic_subscriptions(app_name);
+    }
+}
+
+/// All running IPFS nodes.
+fn all_nodes() -> Vec<Arc<HermesIpfsNode>> {
+    HERMES_IPFS
+        .get()
+        .cloned()
+        .into_iter()
+        .chain(APP_IPFS_NODES.iter().map(|node| node.value().clone()))
+        .collect()
+}
+
 /// Hermes IPFS Internal Node
 pub(crate) struct HermesIpfsNode {
     /// Send events to the IPFS node.
     sender: Option<mpsc::Sender<IpfsCommand>>,
+    /// Identity of the node.
+    keypair: Keypair,
     /// State related to `ApplicationName`
     apps: AppIpfsState,
+    /// `PubSub` messages seen during the replay window.
+    seen_messages: SeenMessages,
 }
 
 impl HermesIpfsNode {
     /// Create, initialize, and bootstrap a new `HermesIpfsNode`
-    pub(crate) fn init(builder: IpfsBuilder, default_bootstrap: bool) -> anyhow::Result<Self> {
+    ///
+    /// The node is bootstrapped with the peers of the `network` configuration, it is not
+    /// bootstrapped when there are none.
+    ///
+    /// `owner` is the app the node is dedicated to, `None` for the shared node.
+    pub(crate) fn init(
+        builder: IpfsBuilder, keypair: Keypair, network: IpfsNetworkConfig,
+        owner: Option<ApplicationName>,
+    ) -> anyhow::Result<Self> {
         let runtime = Builder::new_current_thread().enable_all().build()?;
         let (sender, receiver) = mpsc::channel(1);
         let _handle = std::thread::spawn(move || {
             // Build and start IPFS node
             let _unused = runtime.block_on(async move {
                 let node = builder.start().await?;
-                if default_bootstrap {
+                let mut addresses = Vec::new();
+                if network.default_bootstrap {
                     // Add default addresses for bootstrapping
-                    let addresses = node.default_bootstrap().await?;
+                    addresses.extend(node.default_bootstrap().await?);
+                }
+                for address in network.bootstrap_peers {
+                    addresses.push(node.add_bootstrap(address).await?);
+                }
+                if !addresses.is_empty() {
                     // Connect to bootstrap nodes.
                     node.bootstrap().await?;
-                    tracing::debug!(
-                        "Bootstrapped IPFS node with default addresses: {:?}",
-                        addresses
-                    );
+                    tracing::debug!("Bootstrapped IPFS node with addresses: {:?}", addresses);
                 }
                 let hermes_node: HermesIpfs = node.into();
-                let h = tokio::spawn(ipfs_command_handler(hermes_node, receiver));
+                let h = tokio::spawn(ipfs_command_handler(hermes_node, receiver, owner));
                 let (..) = tokio::join!(h);
                 Ok::<(), anyhow::Error>(())
             });
             std::process::exit(0);
         });
+        let replay_window = IPFS_CONFIG
+            .get()
+            .map_or(DEFAULT_REPLAY_WINDOW, |config| config.pubsub_replay_window);
         Ok(Self {
             sender: Some(sender),
+            keypair,
             apps: AppIpfsState::new(),
+            seen_messages: SeenMessages::new(replay_window),
         })
     }
 
@@ -198,6 +467,28 @@ impl HermesIpfsNode {
         cmd_rx.blocking_recv().map_err(|_| Errno::DhtPutError)?
     }
 
+    /// Get DHT Record by Key
+    fn dht_get_record(&self, key: DhtKey) -> Result<DhtRecord, Errno> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        self.sender
+            .as_ref()
+            .ok_or(Errno::DhtGetError)?
+            .blocking_send(IpfsCommand::GetDhtRecord(key, cmd_tx))
+            .map_err(|_| Errno::DhtGetError)?;
+        cmd_rx.blocking_recv().map_err(|_| Errno::DhtGetError)?
+    }
+
+    /// Get the DHT values held by up to `max` peers by Key
+    fn dht_get_values(&self, key: DhtKey, max: usize) -> Result<Vec<DhtValue>, Errno> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        self.sender
+            .as_ref()
+            .ok_or(Errno::DhtGetError)?
+            .blocking_send(IpfsCommand::GetDhtValues(key, max, cmd_tx))
+            .map_err(|_| Errno::DhtGetError)?;
+        cmd_rx.blocking_recv().map_err(|_| Errno::DhtGetError)?
+    }
+
     /// Get DHT Value by Key
     fn dht_get(&self, key: DhtKey) -> Result<DhtValue, Errno> {
         let (cmd_tx, cmd_rx) = oneshot::channel();
@@ -249,6 +540,58 @@ impl HermesIpfsNode {
             .blocking_recv()
             .map_err(|_| Errno::PeerEvictionError)?
     }
+
+    /// List connected peers
+    fn connected_peers(&self) -> Result<Vec<PeerId>, Errno> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        self.sender
+            .as_ref()
+            .ok_or(Errno::ServiceUnavailable)?
+            .blocking_send(IpfsCommand::ConnectedPeers(cmd_tx))
+            .map_err(|_| Errno::ServiceUnavailable)?;
+        cmd_rx
+            .blocking_recv()
+            .map_err(|_| Errno::ServiceUnavailable)?
+    }
+
+    /// List the peers subscribed to a `PubSub` topic
+    fn pubsub_peers(&self, topic: &PubsubTopic) -> Result<Vec<PeerId>, Errno> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        self.sender
+            .as_ref()
+            .ok_or(Errno::ServiceUnavailable)?
+            .blocking_send(IpfsCommand::PubsubPeers(topic.clone(), cmd_tx))
+            .map_err(|_| Errno::ServiceUnavailable)?;
+        cmd_rx
+            .blocking_recv()
+            .map_err(|_| Errno::ServiceUnavailable)?
+    }
+
+    /// List pinned files
+    fn pins(&self) -> Result<Vec<Cid>, Errno> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        self.sender
+            .as_ref()
+            .ok_or(Errno::ServiceUnavailable)?
+            .blocking_send(IpfsCommand::ListPins(cmd_tx))
+            .map_err(|_| Errno::ServiceUnavailable)?;
+        cmd_rx
+            .blocking_recv()
+            .map_err(|_| Errno::ServiceUnavailable)?
+    }
+
+    /// Remove unpinned blocks older than `options.min_age` from the blockstore
+    fn gc(&self, options: GcOptions) -> Result<GcReport, Errno> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        self.sender
+            .as_ref()
+            .ok_or(Errno::ServiceUnavailable)?
+            .blocking_send(IpfsCommand::Gc(options, cmd_tx))
+            .map_err(|_| Errno::ServiceUnavailable)?;
+        cmd_rx
+            .blocking_recv()
+            .map_err(|_| Errno::ServiceUnavailable)?
+    }
 }
 
 impl Default for HermesIpfsNode {
@@ -256,6 +599,7 @@ impl Default for HermesIpfsNode {
         Self {
             sender: None,
             apps: AppIpfsState::new(),
+            seen_messages: SeenMessages::new(DEFAULT_REPLAY_WINDOW),
         }
     }
 }
@@ -265,13 +609,53 @@ struct AppIpfsState {
     /// List of pinned files per app.
     pinned_files: DashMap<ApplicationName, HashSet<Cid>>,
     /// List of DHT values per app.
-    dht_keys: DashMap<ApplicationName, HashSet<DhtKey>>,
+    dht_records: DashMap<ApplicationName, HashMap<DhtKey, DhtValue>>,
     /// List of subscriptions per app.
     topic_subscriptions: DashMap<PubsubTopic, HashSet<ApplicationName>>,
+    /// When a publish to a topic was last accepted, in nanoseconds since the UNIX epoch.
+    last_publish: DashMap<PubsubTopic, u64>,
     /// Collection of stream join handles per topic subscription.
     subscriptions_streams: DashMap<PubsubTopic, JoinHandle<()>>,
     /// List of evicted peers per app.
     evicted_peers: DashMap<ApplicationName, HashSet<PeerId>>,
+    /// Resource usage per app.
+    usage: DashMap<ApplicationName, IpfsUsage>,
+    /// In-flight file fetches, by fetch id.
+    fetches: DashMap<u64, (ApplicationName, IpfsPath, Instant)>,
+    /// Id of the next file fetch.
+    next_fetch_id: AtomicU64,
+}
+
+/// IPFS resource usage of an app.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub(crate) struct IpfsUsage {
+    /// Number of files added.
+    pub(crate) files_added: u64,
+    /// Bytes of file content added.
+    pub(crate) bytes_added: u64,
+    /// Bytes of file content fetched.
+    pub(crate) bytes_fetched: u64,
+    /// Number of files currently pinned.
+    pub(crate) pinned_files: u64,
+    /// Number of DHT values put.
+    pub(crate) dht_puts: u64,
+    /// Number of messages published to topics.
+    pub(crate) messages_published: u64,
+    /// Number of file fetches which failed.
+    pub(crate) fetches_failed: u64,
+    /// Number of file fetches currently in flight.
+    pub(crate) fetches_in_flight: u64,
+}
+
+/// A file fetch in flight.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) struct IpfsFetch {
+    /// App fetching the file.
+    pub(crate) app: String,
+    /// IPFS path of the file.
+    pub(crate) path: String,
+    /// Time since the fetch started, in milliseconds.
+    pub(crate) elapsed_ms: u64,
 }
 
 impl AppIpfsState {
@@ -279,10 +663,14 @@ impl AppIpfsState {
     fn new() -> Self {
         Self {
             pinned_files: DashMap::default(),
-            dht_keys: DashMap::default(),
+            dht_records: DashMap::default(),
             topic_subscriptions: DashMap::default(),
+            last_publish: DashMap::default(),
             subscriptions_streams: DashMap::default(),
             evicted_peers: DashMap::default(),
+            usage: DashMap::default(),
+            fetches: DashMap::default(),
+            next_fetch_id: AtomicU64::new(0),
         }
     }
 
@@ -319,13 +707,22 @@ impl AppIpfsState {
         })
     }
 
-    /// Keep track of `dht_key` of DHT value added by an app.
-    fn added_dht_key(&self, app_name: ApplicationName, dht_key: DhtKey) {
-        self.dht_keys
+    /// Apps which pinned the file with `cid`.
+    fn pin_owners(&self, cid: &Cid) -> Vec<ApplicationName> {
+        self.pinned_files
+            .iter()
+            .filter(|entry| entry.value().contains(cid))
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// Keep track of DHT value added by an app.
+    fn added_dht_record(&self, app_name: ApplicationName, dht_key: DhtKey, dht_value: DhtValue) {
+        self.dht_records
             .entry(app_name)
             .or_default()
             .value_mut()
-            .insert(dht_key);
+            .insert(dht_key, dht_value);
     }
 
     /// Keep track of `topic` subscription added by an app.
@@ -337,6 +734,21 @@ impl AppIpfsState {
             .insert(app_name);
     }
 
+    /// Forget the `topic` subscriptions of an app, and stop the streams of the topics no
+    /// app subscribes to anymore.
+    fn removed_app_topic_subscriptions(&self, app_name: &ApplicationName) {
+        self.topic_subscriptions.retain(|topic, apps| {
+            apps.remove(app_name);
+            if !apps.is_empty() {
+                return true;
+            }
+            if let Some((_, handle)) = self.subscriptions_streams.remove(topic) {
+                handle.abort();
+            }
+            false
+        });
+    }
+
     /// Keep track of `topic` stream handle.
     fn added_topic_stream(&self, topic: PubsubTopic, handle: JoinHandle<()>) {
         self.subscriptions_streams.entry(topic).insert(handle);
@@ -354,6 +766,77 @@ impl AppIpfsState {
             .map_or(vec![], |apps| apps.value().iter().cloned().collect())
     }
 
+    /// Keep track of an accepted publish to `topic`.
+    fn published(&self, topic: PubsubTopic) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|now| u64::try_from(now.as_nanos()).unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        self.last_publish.insert(topic, now);
+    }
+
+    /// When a publish to `topic` was last accepted.
+    fn last_publish(&self, topic: &PubsubTopic) -> Option<u64> {
+        self.last_publish.get(topic).map(|last| *last.value())
+    }
+
+    /// Update the resource usage of an app.
+    fn record_usage(&self, app_name: &ApplicationName, update: impl FnOnce(&mut IpfsUsage)) {
+        update(self.usage.entry(app_name.clone()).or_default().value_mut());
+    }
+
+    /// Resource usage of every app using this node.
+    fn usage(&self) -> Vec<(ApplicationName, IpfsUsage)> {
+        self.usage
+            .iter()
+            .map(|entry| {
+                let mut usage = entry.value().clone();
+                usage.pinned_files = self
+                    .pinned_files
+                    .get(entry.key())
+                    .map_or(0, |cids| u64::try_from(cids.len()).unwrap_or(u64::MAX));
+                let in_flight = self
+                    .fetches
+                    .iter()
+                    .filter(|fetch| fetch.value().0 == *entry.key())
+                    .count();
+                usage.fetches_in_flight = u64::try_from(in_flight).unwrap_or(u64::MAX);
+                (entry.key().clone(), usage)
+            })
+            .collect()
+    }
+
+    /// Keep track of a file fetch started by an app, returning the fetch id.
+    fn fetch_started(&self, app_name: ApplicationName, ipfs_path: IpfsPath) -> u64 {
+        let id = self.next_fetch_id.fetch_add(1, Ordering::Relaxed);
+        self.fetches
+            .insert(id, (app_name, ipfs_path, Instant::now()));
+        id
+    }
+
+    /// Stop tracking a finished file fetch.
+    fn fetch_finished(&self, id: u64) {
+        self.fetches.remove(&id);
+    }
+
+    /// File fetches in flight, the longest running first.
+    fn fetches(&self) -> Vec<IpfsFetch> {
+        let mut fetches: Vec<_> = self
+            .fetches
+            .iter()
+            .map(|fetch| {
+                let (app_name, ipfs_path, started) = fetch.value();
+                IpfsFetch {
+                    app: app_name.to_string(),
+                    path: ipfs_path.clone(),
+                    elapsed_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+                }
+            })
+            .collect();
+        fetches.sort_by(|a, b| b.elapsed_ms.cmp(&a.elapsed_ms));
+        fetches
+    }
+
     /// Add `peer_id` of evicted peer by an app.
     fn evicted_peer(&self, app_name: ApplicationName, peer_id: PeerId) {
         self.evicted_peers
@@ -1,21 +1,23 @@
 //! IPFS Task
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 
 use hermes_ipfs::{
-    subscription_stream_task, AddIpfsFile, Cid, HermesIpfs, IpfsPath as PathIpfsFile,
-    MessageId as PubsubMessageId, PeerId as TargetPeerId,
+    subscription_stream_task, AddIpfsFile, Cid, FetchRetryPolicy, GcOptions, GcReport, HermesIpfs,
+    IpfsPath as PathIpfsFile, MessageId as PubsubMessageId, PeerId as TargetPeerId,
 };
 use tokio::{
     sync::{mpsc, oneshot},
     task::JoinHandle,
 };
 
-use super::HERMES_IPFS;
+use super::{app_node, HERMES_IPFS};
 use crate::{
+    app::ApplicationName,
+    bandwidth::{self, Direction},
     event::{queue::send, HermesEvent},
     runtime_extensions::{
         bindings::hermes::ipfs::api::{
-            DhtKey, DhtValue, Errno, MessageData, PeerId, PubsubMessage, PubsubTopic,
+            DhtKey, DhtRecord, DhtValue, Errno, MessageData, PeerId, PubsubMessage, PubsubTopic,
         },
         hermes::ipfs::event::OnTopicEvent,
     },
@@ -33,6 +35,10 @@ pub(crate) enum IpfsCommand {
     UnPinFile(Cid, oneshot::Sender<Result<bool, Errno>>),
     /// Get DHT value
     GetDhtValue(DhtKey, oneshot::Sender<Result<DhtValue, Errno>>),
+    /// Get DHT record
+    GetDhtRecord(DhtKey, oneshot::Sender<Result<DhtRecord, Errno>>),
+    /// Get the DHT values held by up to the given number of peers
+    GetDhtValues(DhtKey, usize, oneshot::Sender<Result<Vec<DhtValue>, Errno>>),
     /// Put DHT value
     PutDhtValue(DhtKey, DhtValue, oneshot::Sender<Result<bool, Errno>>),
     /// Publish to a topic
@@ -45,12 +51,24 @@ pub(crate) enum IpfsCommand {
     Subscribe(PubsubTopic, oneshot::Sender<Result<JoinHandle<()>, Errno>>),
     /// Evict Peer from node
     EvictPeer(PeerId, oneshot::Sender<Result<bool, Errno>>),
+    /// List connected peers
+    ConnectedPeers(oneshot::Sender<Result<Vec<PeerId>, Errno>>),
+    /// List the peers subscribed to a topic
+    PubsubPeers(PubsubTopic, oneshot::Sender<Result<Vec<PeerId>, Errno>>),
+    /// List pinned files
+    ListPins(oneshot::Sender<Result<Vec<Cid>, Errno>>),
+    /// Remove unpinned blocks from the blockstore
+    Gc(GcOptions, oneshot::Sender<Result<GcReport, Errno>>),
 }
 
 /// Handle IPFS commands in asynchronous task.
+///
+/// `owner` is the app the node is dedicated to, `None` for the shared node.
 pub(crate) async fn ipfs_command_handler(
     hermes_node: HermesIpfs, mut queue_rx: mpsc::Receiver<IpfsCommand>,
+    owner: Option<ApplicationName>,
 ) -> anyhow::Result<()> {
+    let hermes_node = Arc::new(hermes_node);
     while let Some(ipfs_command) = queue_rx.recv().await {
         match ipfs_command {
             IpfsCommand::AddFile(ipfs_file, tx) => {
@@ -61,11 +79,18 @@ pub(crate) async fn ipfs_command_handler(
                 send_response(response, tx);
             },
             IpfsCommand::GetFile(ipfs_path, tx) => {
-                let response = hermes_node
-                    .get_ipfs_file(ipfs_path.into())
-                    .await
-                    .map_err(|_| Errno::FileGetError);
-                send_response(response, tx);
+                // A fetch retries for up to minutes, it must not hold up the other commands.
+                let hermes_node = hermes_node.clone();
+                tokio::spawn(async move {
+                    let response = hermes_node
+                        .get_ipfs_file_with_retry(ipfs_path.into(), &FetchRetryPolicy::default())
+                        .await
+                        .map_err(|err| {
+                            tracing::error!("{err}");
+                            Errno::FileGetError
+                        });
+                    send_response(response, tx);
+                });
             },
             IpfsCommand::PinFile(cid, tx) => {
                 let response = match hermes_node.insert_pin(&cid).await {
@@ -98,6 +123,32 @@ pub(crate) async fn ipfs_command_handler(
                 });
                 send_response(response, tx);
             },
+            IpfsCommand::GetDhtRecord(key, tx) => {
+                let response = hermes_node
+                    .dht_get_record(key.clone())
+                    .await
+                    .map(|(value, ttl)| {
+                        DhtRecord {
+                            value,
+                            ttl: ttl.map(|ttl| u64::try_from(ttl.as_nanos()).unwrap_or(u64::MAX)),
+                        }
+                    })
+                    .map_err(|err| {
+                        tracing::error!(dht_key = ?key, "failed to get DHT record: {}", err);
+                        Errno::DhtGetError
+                    });
+                send_response(response, tx);
+            },
+            IpfsCommand::GetDhtValues(key, max, tx) => {
+                let response = hermes_node
+                    .dht_get_records(key.clone(), max)
+                    .await
+                    .map_err(|err| {
+                        tracing::error!(dht_key = ?key, "failed to get DHT values: {}", err);
+                        Errno::DhtGetError
+                    });
+                send_response(response, tx);
+            },
             IpfsCommand::PutDhtValue(key, value, tx) => {
                 let response = hermes_node.dht_put(key, value).await.is_ok();
                 send_response(Ok(response), tx);
@@ -114,7 +165,10 @@ pub(crate) async fn ipfs_command_handler(
                     .pubsub_subscribe(topic)
                     .await
                     .map_err(|_| Errno::PubsubSubscribeError)?;
-                let handle = subscription_stream_task(stream, topic_stream_app_handler);
+                let owner = owner.clone();
+                let handle = subscription_stream_task(stream, move |msg| {
+                    topic_stream_app_handler(msg, owner.as_ref());
+                });
                 send_response(Ok(handle), tx);
             },
             IpfsCommand::EvictPeer(peer, tx) => {
@@ -122,16 +176,71 @@ pub(crate) async fn ipfs_command_handler(
                 let status = hermes_node.ban_peer(peer_id).await.is_ok();
                 send_response(Ok(status), tx);
             },
+            IpfsCommand::ConnectedPeers(tx) => {
+                let response = hermes_node
+                    .connected_peers()
+                    .await
+                    .map(|peers| peers.iter().map(ToString::to_string).collect())
+                    .map_err(|err| {
+                        tracing::error!("failed to list connected peers: {}", err);
+                        Errno::ServiceUnavailable
+                    });
+                send_response(response, tx);
+            },
+            IpfsCommand::PubsubPeers(topic, tx) => {
+                let response = hermes_node
+                    .pubsub_peers(topic.clone())
+                    .await
+                    .map(|peers| peers.iter().map(ToString::to_string).collect())
+                    .map_err(|err| {
+                        tracing::error!(topic = %topic, "failed to list topic peers: {}", err);
+                        Errno::ServiceUnavailable
+                    });
+                send_response(response, tx);
+            },
+            IpfsCommand::ListPins(tx) => {
+                let response = hermes_node.list_pins().await.map_err(|err| {
+                    tracing::error!("failed to list pins: {}", err);
+                    Errno::ServiceUnavailable
+                });
+                send_response(response, tx);
+            },
+            IpfsCommand::Gc(options, tx) => {
+                let response = hermes_node.gc_with(options).await.map_err(|err| {
+                    tracing::error!("failed to collect garbage: {}", err);
+                    Errno::ServiceUnavailable
+                });
+                send_response(response, tx);
+            },
         }
     }
     hermes_node.stop().await;
     Ok(())
 }
 
-/// Handler function for topic message streams.
-fn topic_stream_app_handler(msg: hermes_ipfs::rust_ipfs::libp2p::gossipsub::Message) {
-    if let Some(ipfs) = HERMES_IPFS.get() {
+/// Handler function for topic message streams of the node dedicated to `owner`, or of
+/// the shared node.
+fn topic_stream_app_handler(
+    msg: hermes_ipfs::rust_ipfs::libp2p::gossipsub::Message, owner: Option<&ApplicationName>,
+) {
+    let ipfs = match owner {
+        Some(app_name) => app_node(app_name).ok(),
+        None => HERMES_IPFS.get().cloned(),
+    };
+    if let Some(ipfs) = ipfs {
+        if ipfs.seen_messages.is_duplicate(&msg) {
+            tracing::debug!(topic = %msg.topic, "dropped duplicate PubSub message");
+            return;
+        }
         let msg_topic = msg.topic.into_string();
+        #[cfg(feature = "replication")]
+        {
+            use crate::runtime_extensions::hermes::sqlite::replication;
+            if replication::is_replication_topic(&msg_topic) {
+                replication::on_message(&msg_topic, &msg.data);
+                return;
+            }
+        }
         let on_topic_event = OnTopicEvent {
             message: PubsubMessage {
                 topic: msg_topic.clone(),
@@ -140,6 +249,13 @@ fn topic_stream_app_handler(msg: hermes_ipfs::rust_ipfs::libp2p::gossipsub::Mess
             },
         };
         let app_names = ipfs.apps.subscribed_apps(&msg_topic);
+        for app_name in &app_names {
+            bandwidth::record(
+                app_name,
+                Direction::Received,
+                on_topic_event.message.message.len(),
+            );
+        }
         // Dispatch Hermes Event
         if let Err(err) = send(HermesEvent::new(
             on_topic_event.clone(),
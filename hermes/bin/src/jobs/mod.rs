@@ -0,0 +1,239 @@
+//! Durable job scheduler.
+//!
+//! Runtime extensions schedule the work which must happen later, or again, even across
+//! restarts of the node, as jobs instead of keeping their own timers and retry state.
+//!
+//! Jobs are stored in the `jobs.db` `SQLite` database of the Hermes home directory, and
+//! identified by their kind, app and key: scheduling a job with the same identity
+//! replaces it. A worker thread leases the due jobs of every registered kind and runs
+//! them with the handler of their kind. A failed job is retried with an exponential
+//! backoff, and kept as failed after `MAX_ATTEMPTS`. Leases expire, so the jobs of a node
+//! which stopped while running them are run again.
+//!
+//! A kind without a handler is only stored, e.g. the crontab entries which the cron queue
+//! fires itself and restores when the node restarts.
+
+mod store;
+
+use std::{
+    path::Path,
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime},
+};
+
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+use store::Store;
+
+use crate::app::ApplicationName;
+
+/// Name of the jobs database file in the Hermes home directory.
+const DB_FILE: &str = "jobs.db";
+
+/// How long a job is leased to the worker running it.
+const LEASE: Duration = Duration::from_secs(5 * 60);
+
+/// Interval between polls of the due jobs.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum number of jobs of a kind leased at once.
+const BATCH_SIZE: i64 = 16;
+
+/// Number of runs of a job before it is kept as failed.
+const MAX_ATTEMPTS: u32 = 8;
+
+/// Delay before the first retry of a failed job, doubled on every later one.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Maximum delay before the retry of a failed job.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Jobs database, set by `init`.
+static STORE: OnceCell<Mutex<Store>> = OnceCell::new();
+
+/// Handlers of the job kinds.
+static HANDLERS: Lazy<DashMap<&'static str, JobHandler>> = Lazy::new(DashMap::new);
+
+/// Runs a job, returning what happens to it next.
+pub(crate) type JobHandler = fn(&Job) -> anyhow::Result<JobOutcome>;
+
+/// A job leased to the worker.
+#[derive(Debug)]
+pub(crate) struct Job {
+    /// Row id of the job.
+    id: i64,
+    /// Kind of the job, selecting its handler.
+    pub(crate) kind: String,
+    /// App the job runs for.
+    pub(crate) app: ApplicationName,
+    /// Key of the job, unique for its kind and app.
+    pub(crate) key: String,
+    /// Data of the job.
+    pub(crate) payload: Vec<u8>,
+    /// Number of failed runs of the job.
+    pub(crate) attempts: u32,
+    /// End of the lease, in milliseconds since the UNIX epoch.
+    lease_until: i64,
+}
+
+/// What happens to a job after it ran successfully.
+pub(crate) enum JobOutcome {
+    /// The job is finished and removed.
+    Done,
+    /// The job runs again at the time, e.g. a recurring job.
+    RunAt(SystemTime),
+}
+
+/// Open the jobs database in the Hermes home directory and start the worker.
+///
+/// ## Errors
+///
+/// Returns errors if the database cannot be opened, or the scheduler is already running.
+pub(crate) fn init(hermes_home: &Path) -> anyhow::Result<()> {
+    let store = Store::open(&hermes_home.join(DB_FILE))?;
+    STORE
+        .set(Mutex::new(store))
+        .map_err(|_| anyhow::anyhow!("Job scheduler already initialized"))?;
+
+    std::thread::Builder::new()
+        .name("jobs".to_string())
+        .spawn(|| {
+            loop {
+                run_due_jobs();
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        })?;
+    Ok(())
+}
+
+/// Register the handler of a job kind.
+pub(crate) fn register(kind: &'static str, handler: JobHandler) {
+    HANDLERS.insert(kind, handler);
+}
+
+/// Schedule a job to run at `run_at`, replacing the job of the app with the same kind and
+/// key.
+///
+/// ## Errors
+///
+/// Returns errors if the scheduler is not initialized, or the job cannot be stored.
+pub(crate) fn schedule(
+    kind: &str, app: &ApplicationName, key: &str, payload: &[u8], run_at: SystemTime,
+) -> anyhow::Result<()> {
+    store()?.upsert(kind, app, key, payload, millis(run_at))
+}
+
+/// Remove the job of the app with the kind and key, returning whether it existed.
+///
+/// ## Errors
+///
+/// Returns errors if the scheduler is not initialized, or the job cannot be removed.
+pub(crate) fn unschedule(kind: &str, app: &ApplicationName, key: &str) -> anyhow::Result<bool> {
+    store()?.remove(kind, app, key)
+}
+
+/// List the pending jobs of the app with the kind, returning their key, payload and run
+/// time.
+///
+/// ## Errors
+///
+/// Returns errors if the scheduler is not initialized, or the jobs cannot be read.
+pub(crate) fn scheduled(
+    kind: &str, app: &ApplicationName,
+) -> anyhow::Result<Vec<(String, Vec<u8>, SystemTime)>> {
+    Ok(store()?
+        .list(kind, app)?
+        .into_iter()
+        .map(|(key, payload, run_at)| {
+            let run_at = SystemTime::UNIX_EPOCH
+                + Duration::from_millis(u64::try_from(run_at).unwrap_or_default());
+            (key, payload, run_at)
+        })
+        .collect())
+}
+
+/// Jobs database.
+fn store() -> anyhow::Result<std::sync::MutexGuard<'static, Store>> {
+    Ok(STORE
+        .get()
+        .ok_or(anyhow::anyhow!("Job scheduler is not initialized"))?
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Run the due jobs of every registered kind.
+fn run_due_jobs() {
+    let handlers: Vec<(&'static str, JobHandler)> = HANDLERS
+        .iter()
+        .map(|entry| (*entry.key(), *entry.value()))
+        .collect();
+    for (kind, handler) in handlers {
+        let now = SystemTime::now();
+        let claimed = store()
+            .and_then(|store| store.claim(kind, millis(now), millis(now + LEASE), BATCH_SIZE));
+        let jobs = match claimed {
+            Ok(jobs) => jobs,
+            Err(err) => {
+                tracing::error!(kind, "Failed to lease jobs: {err}");
+                continue;
+            },
+        };
+        for job in jobs {
+            run_job(&job, handler);
+        }
+    }
+}
+
+/// Run a job and store what happens to it next.
+fn run_job(job: &Job, handler: JobHandler) {
+    let res = handler(job).and_then(|outcome| {
+        match outcome {
+            JobOutcome::Done => store()?.complete(job),
+            JobOutcome::RunAt(run_at) => store()?.reschedule(job, millis(run_at), None),
+        }
+    });
+    let Err(err) = res else {
+        return;
+    };
+
+    let attempts = job.attempts.saturating_add(1);
+    let error = err.to_string();
+    let stored = if attempts >= MAX_ATTEMPTS {
+        tracing::error!(kind = job.kind, app = %job.app, key = job.key, attempts, "Job failed: {error}");
+        store().and_then(|store| store.fail(job, &error))
+    } else {
+        tracing::warn!(kind = job.kind, app = %job.app, key = job.key, attempts, "Job failed, retrying: {error}");
+        let run_at = SystemTime::now() + backoff(attempts);
+        store().and_then(|store| store.reschedule(job, millis(run_at), Some(&error)))
+    };
+    if let Err(err) = stored {
+        tracing::error!(kind = job.kind, "Failed to store job: {err}");
+    }
+}
+
+/// Delay before the retry of a job which failed `attempts` times.
+fn backoff(attempts: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2_u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Milliseconds since the UNIX epoch.
+fn millis(time: SystemTime) -> i64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| {
+            i64::try_from(since.as_millis()).unwrap_or(i64::MAX)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_test() {
+        assert_eq!(backoff(1), INITIAL_BACKOFF);
+        assert_eq!(backoff(3), INITIAL_BACKOFF * 4);
+        assert_eq!(backoff(MAX_ATTEMPTS * 4), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,402 @@
+//! `SQLite` storage of the jobs.
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    path::Path,
+};
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_bind_blob, sqlite3_bind_int64, sqlite3_bind_null, sqlite3_bind_text,
+    sqlite3_close, sqlite3_column_blob, sqlite3_column_bytes, sqlite3_column_int64,
+    sqlite3_column_text, sqlite3_column_type, sqlite3_errmsg, sqlite3_exec, sqlite3_finalize,
+    sqlite3_open_v2, sqlite3_prepare_v2, sqlite3_step, sqlite3_stmt, SQLITE_DONE, SQLITE_NULL,
+    SQLITE_OK, SQLITE_OPEN_CREATE, SQLITE_OPEN_FULLMUTEX, SQLITE_OPEN_READWRITE, SQLITE_ROW,
+    SQLITE_TRANSIENT,
+};
+
+use super::Job;
+use crate::app::ApplicationName;
+
+/// Jobs table schema.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS jobs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind TEXT NOT NULL,
+        app TEXT NOT NULL,
+        key TEXT NOT NULL,
+        payload BLOB NOT NULL,
+        run_at INTEGER NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        lease_until INTEGER,
+        failed INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT,
+        UNIQUE (kind, app, key)
+    );
+    CREATE INDEX IF NOT EXISTS jobs_due ON jobs (kind, failed, run_at);
+";
+
+/// Parameter bound to a statement.
+enum Param<'a> {
+    /// Integer.
+    Int(i64),
+    /// Text.
+    Text(&'a str),
+    /// Blob.
+    Blob(&'a [u8]),
+    /// Null.
+    Null,
+}
+
+/// Jobs database connection.
+pub(super) struct Store {
+    /// Database connection.
+    db: *mut sqlite3,
+}
+
+// The connection is opened in serialized mode, so it can be used from any thread.
+unsafe impl Send for Store {}
+
+impl Store {
+    /// Open the jobs database at `path`, creating it if needed.
+    pub(super) fn open(path: &Path) -> anyhow::Result<Self> {
+        let c_path = CString::new(path.to_string_lossy().as_bytes())?;
+        let mut db: *mut sqlite3 = std::ptr::null_mut();
+        let rc = unsafe {
+            sqlite3_open_v2(
+                c_path.as_ptr(),
+                &mut db,
+                SQLITE_OPEN_CREATE | SQLITE_OPEN_READWRITE | SQLITE_OPEN_FULLMUTEX,
+                std::ptr::null(),
+            )
+        };
+        let store = Self { db };
+        if rc != SQLITE_OK {
+            anyhow::bail!("Failed to open jobs database: {}", store.errmsg());
+        }
+        store.execute(SCHEMA)?;
+        Ok(store)
+    }
+
+    /// Insert a job, replacing the job with the same kind, app and key.
+    ///
+    /// A replaced job loses its lease, so a run in progress cannot complete it.
+    pub(super) fn upsert(
+        &self, kind: &str, app: &ApplicationName, key: &str, payload: &[u8], run_at: i64,
+    ) -> anyhow::Result<()> {
+        self.run(
+            "INSERT INTO jobs (kind, app, key, payload, run_at) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (kind, app, key) DO UPDATE SET payload = excluded.payload,
+                run_at = excluded.run_at, attempts = 0, lease_until = NULL, failed = 0,
+                last_error = NULL",
+            &[
+                Param::Text(kind),
+                Param::Text(&app.0),
+                Param::Text(key),
+                Param::Blob(payload),
+                Param::Int(run_at),
+            ],
+            |_| Ok(()),
+        )?;
+        Ok(())
+    }
+
+    /// Remove the job with the kind, app and key.
+    ///
+    /// Returns whether the job existed.
+    pub(super) fn remove(
+        &self, kind: &str, app: &ApplicationName, key: &str,
+    ) -> anyhow::Result<bool> {
+        let removed = self.run(
+            "DELETE FROM jobs WHERE kind = ?1 AND app = ?2 AND key = ?3 RETURNING id",
+            &[Param::Text(kind), Param::Text(&app.0), Param::Text(key)],
+            |_| Ok(()),
+        )?;
+        Ok(!removed.is_empty())
+    }
+
+    /// List the pending jobs of the kind and app, returning their key, payload and run
+    /// time.
+    pub(super) fn list(
+        &self, kind: &str, app: &ApplicationName,
+    ) -> anyhow::Result<Vec<(String, Vec<u8>, i64)>> {
+        self.run(
+            "SELECT key, payload, run_at FROM jobs WHERE kind = ?1 AND app = ?2 AND failed = 0
+             ORDER BY run_at",
+            &[Param::Text(kind), Param::Text(&app.0)],
+            |stmt| {
+                Ok((
+                    column_text(stmt, 0)?,
+                    column_blob(stmt, 1)?,
+                    column_int(stmt, 2),
+                ))
+            },
+        )
+    }
+
+    /// Lease up to `limit` jobs of the kind which are due at `now` until `lease_until`.
+    ///
+    /// Jobs whose lease expired, e.g. because the node running them stopped, are leased
+    /// again.
+    pub(super) fn claim(
+        &self, kind: &str, now: i64, lease_until: i64, limit: i64,
+    ) -> anyhow::Result<Vec<Job>> {
+        self.run(
+            "UPDATE jobs SET lease_until = ?1 WHERE id IN (
+                SELECT id FROM jobs WHERE kind = ?2 AND failed = 0 AND run_at <= ?3
+                    AND (lease_until IS NULL OR lease_until <= ?3)
+                ORDER BY run_at LIMIT ?4
+             ) RETURNING id, app, key, payload, attempts",
+            &[
+                Param::Int(lease_until),
+                Param::Text(kind),
+                Param::Int(now),
+                Param::Int(limit),
+            ],
+            |stmt| {
+                Ok(Job {
+                    id: column_int(stmt, 0),
+                    kind: kind.to_string(),
+                    app: ApplicationName(column_text(stmt, 1)?),
+                    key: column_text(stmt, 2)?,
+                    payload: column_blob(stmt, 3)?,
+                    attempts: u32::try_from(column_int(stmt, 4)).unwrap_or(u32::MAX),
+                    lease_until,
+                })
+            },
+        )
+    }
+
+    /// Remove a finished job, unless it lost its lease.
+    pub(super) fn complete(&self, job: &Job) -> anyhow::Result<()> {
+        self.run(
+            "DELETE FROM jobs WHERE id = ?1 AND lease_until = ?2",
+            &[Param::Int(job.id), Param::Int(job.lease_until)],
+            |_| Ok(()),
+        )?;
+        Ok(())
+    }
+
+    /// Release the lease of a job and run it again at `run_at`, unless it lost its lease.
+    ///
+    /// `error` is recorded as the last error of the job, `None` resets its attempts.
+    pub(super) fn reschedule(
+        &self, job: &Job, run_at: i64, error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let attempts = if error.is_some() {
+            i64::from(job.attempts.saturating_add(1))
+        } else {
+            0
+        };
+        self.run(
+            "UPDATE jobs SET run_at = ?1, attempts = ?2, last_error = ?3, lease_until = NULL
+             WHERE id = ?4 AND lease_until = ?5",
+            &[
+                Param::Int(run_at),
+                Param::Int(attempts),
+                error.map_or(Param::Null, Param::Text),
+                Param::Int(job.id),
+                Param::Int(job.lease_until),
+            ],
+            |_| Ok(()),
+        )?;
+        Ok(())
+    }
+
+    /// Mark a job as failed, it is kept for inspection but never run again.
+    pub(super) fn fail(&self, job: &Job, error: &str) -> anyhow::Result<()> {
+        self.run(
+            "UPDATE jobs SET failed = 1, attempts = ?1, last_error = ?2, lease_until = NULL
+             WHERE id = ?3 AND lease_until = ?4",
+            &[
+                Param::Int(i64::from(job.attempts.saturating_add(1))),
+                Param::Text(error),
+                Param::Int(job.id),
+                Param::Int(job.lease_until),
+            ],
+            |_| Ok(()),
+        )?;
+        Ok(())
+    }
+
+    /// Execute SQL statements without parameters.
+    fn execute(&self, sql: &str) -> anyhow::Result<()> {
+        let c_sql = CString::new(sql)?;
+        let rc = unsafe {
+            sqlite3_exec(
+                self.db,
+                c_sql.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        if rc != SQLITE_OK {
+            anyhow::bail!("Jobs database error: {}", self.errmsg());
+        }
+        Ok(())
+    }
+
+    /// Run a statement with the parameters, mapping every result row with `map_row`.
+    fn run<T>(
+        &self, sql: &str, params: &[Param],
+        mut map_row: impl FnMut(*mut sqlite3_stmt) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        let c_sql = CString::new(sql)?;
+        let mut stmt: *mut sqlite3_stmt = std::ptr::null_mut();
+        let rc = unsafe {
+            sqlite3_prepare_v2(self.db, c_sql.as_ptr(), -1, &mut stmt, std::ptr::null_mut())
+        };
+        if rc != SQLITE_OK {
+            anyhow::bail!("Jobs database error: {}", self.errmsg());
+        }
+
+        let res = self.bind_and_step(stmt, params, &mut map_row);
+        unsafe { sqlite3_finalize(stmt) };
+        res
+    }
+
+    /// Bind the parameters to a prepared statement and step through its rows.
+    fn bind_and_step<T>(
+        &self, stmt: *mut sqlite3_stmt, params: &[Param],
+        map_row: &mut impl FnMut(*mut sqlite3_stmt) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        for (index, param) in (1..).zip(params) {
+            let rc = unsafe {
+                match param {
+                    Param::Int(value) => sqlite3_bind_int64(stmt, index, *value),
+                    Param::Text(value) => {
+                        sqlite3_bind_text(
+                            stmt,
+                            index,
+                            value.as_ptr().cast::<c_char>(),
+                            i32::try_from(value.len())?,
+                            SQLITE_TRANSIENT(),
+                        )
+                    },
+                    Param::Blob(value) => {
+                        sqlite3_bind_blob(
+                            stmt,
+                            index,
+                            value.as_ptr().cast::<std::ffi::c_void>(),
+                            i32::try_from(value.len())?,
+                            SQLITE_TRANSIENT(),
+                        )
+                    },
+                    Param::Null => sqlite3_bind_null(stmt, index),
+                }
+            };
+            if rc != SQLITE_OK {
+                anyhow::bail!("Jobs database error: {}", self.errmsg());
+            }
+        }
+
+        let mut rows = Vec::new();
+        loop {
+            match unsafe { sqlite3_step(stmt) } {
+                SQLITE_ROW => rows.push(map_row(stmt)?),
+                SQLITE_DONE => return Ok(rows),
+                _ => anyhow::bail!("Jobs database error: {}", self.errmsg()),
+            }
+        }
+    }
+
+    /// Message of the latest error of the connection.
+    fn errmsg(&self) -> String {
+        unsafe {
+            let msg = sqlite3_errmsg(self.db);
+            if msg.is_null() {
+                return "unknown error".to_string();
+            }
+            CStr::from_ptr(msg).to_string_lossy().into_owned()
+        }
+    }
+}
+
+impl Drop for Store {
+    fn drop(&mut self) {
+        unsafe { sqlite3_close(self.db) };
+    }
+}
+
+/// Integer column of the current row.
+fn column_int(stmt: *mut sqlite3_stmt, index: i32) -> i64 {
+    unsafe { sqlite3_column_int64(stmt, index) }
+}
+
+/// Text column of the current row.
+fn column_text(stmt: *mut sqlite3_stmt, index: i32) -> anyhow::Result<String> {
+    unsafe {
+        let text = sqlite3_column_text(stmt, index);
+        if text.is_null() {
+            return Ok(String::new());
+        }
+        Ok(CStr::from_ptr(text.cast::<c_char>()).to_str()?.to_string())
+    }
+}
+
+/// Blob column of the current row.
+fn column_blob(stmt: *mut sqlite3_stmt, index: i32) -> anyhow::Result<Vec<u8>> {
+    unsafe {
+        if sqlite3_column_type(stmt, index) == SQLITE_NULL {
+            return Ok(Vec::new());
+        }
+        let blob = sqlite3_column_blob(stmt, index);
+        let len = usize::try_from(sqlite3_column_bytes(stmt, index))?;
+        if blob.is_null() || len == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(std::slice::from_raw_parts(blob.cast::<u8>(), len).to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn jobs_store_test() {
+        let dir = TempDir::new().unwrap();
+        let store = Store::open(&dir.path().join("jobs.db")).unwrap();
+        let app = ApplicationName("app".to_string());
+
+        store.upsert("kind", &app, "a", b"payload", 10).unwrap();
+        store.upsert("kind", &app, "b", b"", 20).unwrap();
+        store.upsert("other", &app, "a", b"", 0).unwrap();
+
+        assert!(store.claim("kind", 5, 100, 10).unwrap().is_empty());
+        let jobs = store.claim("kind", 10, 100, 10).unwrap();
+        assert_eq!(jobs.len(), 1);
+        let job = jobs.first().unwrap();
+        assert_eq!(
+            (job.key.as_str(), job.payload.as_slice()),
+            ("a", &b"payload"[..])
+        );
+        // `b` is due, `a` is leased.
+        let job_b = store.claim("kind", 50, 200, 10).unwrap().pop().unwrap();
+        assert_eq!(job_b.key, "b");
+        store.complete(&job_b).unwrap();
+
+        // Lease expired, claimed again.
+        let job = store.claim("kind", 100, 300, 1).unwrap().pop().unwrap();
+        store.reschedule(&job, 150, Some("error")).unwrap();
+        let job = store.claim("kind", 150, 400, 10).unwrap().pop().unwrap();
+        assert_eq!(job.attempts, 1);
+
+        // Replaced while running, the run does not complete it.
+        store.upsert("kind", &app, "a", b"new", 500).unwrap();
+        store.complete(&job).unwrap();
+        let job = store.claim("kind", 500, 600, 10).unwrap().pop().unwrap();
+        assert_eq!(job.payload, b"new");
+        store.fail(&job, "error").unwrap();
+        assert!(store.claim("kind", 1000, 1100, 10).unwrap().is_empty());
+        assert_eq!(store.claim("other", 1000, 1100, 10).unwrap().len(), 1);
+
+        let listed = store.list("other", &app).unwrap();
+        assert_eq!(listed, vec![("a".to_string(), Vec::new(), 0)]);
+        assert!(store.remove("other", &app, "a").unwrap());
+        assert!(!store.remove("other", &app, "a").unwrap());
+        assert!(store.list("other", &app).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Node keystore.
+//!
+//! The node holds a secret in the `keystore/node.secret` file of the Hermes home
+//! directory, from which the keys used by the runtime extensions are derived, e.g. the
+//! keys encrypting the app databases. It is created when first needed, only readable by
+//! its owner, so the derived keys stay the same across restarts.
+
+use std::{io::Write, path::Path};
+
+use once_cell::sync::OnceCell;
+
+use crate::cli::Cli;
+
+/// Name of the keystore directory in the Hermes home directory.
+const KEYSTORE_DIR: &str = "keystore";
+
+/// Name of the node secret file in the keystore.
+const SECRET_FILE: &str = "node.secret";
+
+/// Size of the node secret and of the derived keys, in bytes.
+pub(crate) const KEY_SIZE: usize = 32;
+
+/// Node secret, loaded on first use.
+static NODE_SECRET: OnceCell<[u8; KEY_SIZE]> = OnceCell::new();
+
+/// Derive the key used for `purpose`, e.g. `hermes-sqlite`, in `context`, e.g. an app
+/// name, from the node secret.
+///
+/// `purpose` is at most 16 bytes long, the keys of different purposes or contexts are
+/// unrelated.
+///
+/// ## Errors
+///
+/// Returns errors if the node secret can not be read or generated.
+pub(crate) fn derive_key(purpose: &str, context: &[u8]) -> anyhow::Result<[u8; KEY_SIZE]> {
+    let secret = NODE_SECRET
+        .get_or_try_init(|| load_or_generate(&Cli::hermes_home()?.join(KEYSTORE_DIR)))?;
+    derive(secret, purpose, context)
+}
+
+/// Derive the key used for `purpose` in `context` from `secret`.
+fn derive(secret: &[u8], purpose: &str, context: &[u8]) -> anyhow::Result<[u8; KEY_SIZE]> {
+    anyhow::ensure!(
+        purpose.len() <= blake2b_simd::PERSONALBYTES,
+        "Key purpose {purpose} is longer than {} bytes",
+        blake2b_simd::PERSONALBYTES
+    );
+    let hash = blake2b_simd::Params::new()
+        .hash_length(KEY_SIZE)
+        .key(secret)
+        .personal(purpose.as_bytes())
+        .hash(context);
+    let mut key = [0; KEY_SIZE];
+    key.copy_from_slice(hash.as_bytes());
+    Ok(key)
+}
+
+/// Secret of the node with the `keystore_path` keystore, generating it if needed.
+fn load_or_generate(keystore_path: &Path) -> anyhow::Result<[u8; KEY_SIZE]> {
+    let path = keystore_path.join(SECRET_FILE);
+    if path.exists() {
+        let bytes = std::fs::read(&path)?;
+        return <[u8; KEY_SIZE]>::try_from(bytes.as_slice())
+            .map_err(|_| anyhow::anyhow!("Invalid node secret file {}", path.display()));
+    }
+
+    let secret: [u8; KEY_SIZE] = rand::random();
+    store(keystore_path, &secret)?;
+    tracing::info!("Generated node secret");
+    Ok(secret)
+}
+
+/// Store the secret in the `keystore_path` keystore, only readable by its owner.
+///
+/// The secret is written to a temporary file first, so it is never left half written.
+fn store(keystore_path: &Path, secret: &[u8]) -> anyhow::Result<()> {
+    std::fs::create_dir_all(keystore_path)?;
+
+    let tmp_path = keystore_path.join(format!("{SECRET_FILE}.tmp"));
+    let _unused = std::fs::remove_file(&tmp_path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(secret)?;
+    file.sync_all()?;
+    std::fs::rename(tmp_path, keystore_path.join(SECRET_FILE))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn keystore_test() {
+        let dir = TempDir::new().unwrap();
+        let keystore_path = dir.path().join(KEYSTORE_DIR);
+
+        let secret = load_or_generate(&keystore_path).unwrap();
+        assert_eq!(load_or_generate(&keystore_path).unwrap(), secret);
+
+        let key = derive(&secret, "hermes-sqlite", b"app").unwrap();
+        assert_eq!(derive(&secret, "hermes-sqlite", b"app").unwrap(), key);
+        assert_ne!(derive(&secret, "hermes-sqlite", b"other-app").unwrap(), key);
+        assert_ne!(derive(&secret, "hermes-other", b"app").unwrap(), key);
+        assert!(derive(&secret, "purpose-longer-than-16-bytes", b"app").is_err());
+    }
+}
@@ -2,13 +2,17 @@
 //! This file exists, so that doc tests can be used inside binary crates.
 #![type_length_limit = "45079293105"]
 
+pub mod admin;
 pub mod app;
+pub mod bandwidth;
 #[allow(dead_code)]
 pub mod cli;
 pub mod errors;
 pub mod event;
 pub mod hdf5;
 pub mod ipfs;
+pub mod jobs;
+pub mod keystore;
 pub mod logger;
 pub mod packaging;
 pub mod reactor;
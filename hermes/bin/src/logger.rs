@@ -3,13 +3,19 @@
 use std::str::FromStr;
 
 use derive_more::Display;
-use tracing::level_filters::LevelFilter;
+use once_cell::sync::OnceCell;
+use tracing::{level_filters::LevelFilter, subscriber::Interest};
 use tracing_subscriber::{
+    filter::DynFilterFn,
     fmt::{format::FmtSpan, time},
-    FmtSubscriber,
+    layer::SubscriberExt,
+    reload, Layer, Registry,
 };
 
-use crate::runtime_extensions::bindings::hermes::logging;
+use crate::{runtime_extensions::bindings::hermes::logging, wasm::latency};
+
+/// Set by `init`, to change the log level at runtime.
+static LEVEL_HANDLE: OnceCell<reload::Handle<LevelFilter, Registry>> = OnceCell::new();
 
 /// All valid logging levels.
 #[derive(Clone, Copy, Display, Default)]
@@ -147,7 +153,11 @@ impl LoggerConfigBuilder {
 /// - Events emit when the span close
 /// - Maximum verbosity level
 pub(crate) fn init(logger_config: &LoggerConfig) -> anyhow::Result<()> {
-    let subscriber = FmtSubscriber::builder()
+    let (level, handle) =
+        reload::Layer::new(LevelFilter::from_level(logger_config.log_level.into()));
+    LEVEL_HANDLE.get_or_init(|| handle);
+
+    let fmt_layer = tracing_subscriber::fmt::layer()
         .json()
         .with_level(true)
         .with_thread_names(logger_config.with_thread)
@@ -156,8 +166,46 @@ pub(crate) fn init(logger_config: &LoggerConfig) -> anyhow::Result<()> {
         .with_line_number(logger_config.with_line_num)
         .with_timer(time::UtcTime::rfc_3339())
         .with_span_events(FmtSpan::CLOSE)
-        .with_max_level(LevelFilter::from_level(logger_config.log_level.into()))
-        .finish();
+        .with_filter(level);
+    // Times the host function calls, once their latency budgets are set.
+    let latency_layer = latency::LatencyLayer.with_filter(
+        DynFilterFn::new(|metadata, _| latency::is_host_call(metadata)).with_callsite_filter(
+            |metadata| {
+                if metadata.is_span() && metadata.name() == latency::HOST_CALL_SPAN {
+                    Interest::sometimes()
+                } else {
+                    Interest::never()
+                }
+            },
+        ),
+    );
 
+    let subscriber = Registry::default().with(fmt_layer).with(latency_layer);
     Ok(tracing::subscriber::set_global_default(subscriber)?)
 }
+
+/// Get the maximum verbosity level of the logs.
+///
+/// # Errors
+///
+/// If the logger is not initialized.
+pub(crate) fn level() -> anyhow::Result<LevelFilter> {
+    let handle = LEVEL_HANDLE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Logger is not initialized"))?;
+    handle
+        .clone_current()
+        .ok_or_else(|| anyhow::anyhow!("Logger is not initialized"))
+}
+
+/// Change the maximum verbosity level of the logs at runtime.
+///
+/// # Errors
+///
+/// If the logger is not initialized.
+pub(crate) fn set_level(level: LevelFilter) -> anyhow::Result<()> {
+    let handle = LEVEL_HANDLE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Logger is not initialized"))?;
+    Ok(handle.reload(level)?)
+}
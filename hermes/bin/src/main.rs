@@ -1,11 +1,15 @@
 //! The Hermes Node.
 
+mod admin;
 mod app;
+mod bandwidth;
 mod cli;
 mod errors;
 mod event;
 mod hdf5;
 mod ipfs;
+mod jobs;
+mod keystore;
 mod logger;
 mod packaging;
 mod reactor;
@@ -2,7 +2,13 @@
 
 use super::ApplicationPackage;
 use crate::{
-    app::Application,
+    app::{Application, ApplicationName},
+    bandwidth, ipfs,
+    runtime_extensions::{
+        app_config,
+        hermes::sqlite::maintenance,
+        wasi::{cli, filesystem},
+    },
     vfs::{PermissionLevel, Vfs, VfsBootstrapper},
 };
 
@@ -14,11 +20,67 @@ pub(crate) fn build_app<P: AsRef<std::path::Path>>(
     let mut bootstrapper = VfsBootstrapper::new(vfs_dir_path, app_name.clone());
     mount_to_vfs(package, &mut bootstrapper)?;
     let vfs = bootstrapper.bootstrap()?;
+    ipfs::set_app_limits(
+        ApplicationName(app_name.clone()),
+        package.get_ipfs_limits()?,
+    );
+    let metadata = package.get_metadata()?;
+    let resources = metadata.get("resources");
+    let bandwidth_caps = resources
+        .and_then(|resources| resources.get("bandwidth"))
+        .map(|caps| serde_json::from_value(caps.clone()))
+        .transpose()?
+        .unwrap_or_default();
+    bandwidth::set_app_caps(ApplicationName(app_name.clone()), bandwidth_caps);
+    let encrypted_db = resources
+        .and_then(|resources| resources.get("sqlite-db"))
+        .and_then(|sqlite_db| sqlite_db.get("encrypted"))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or_default();
+    anyhow::ensure!(
+        !encrypted_db || cfg!(feature = "sqlcipher"),
+        "App {app_name} requires an encrypted database, but this node is built without SQLCipher support"
+    );
+    app_config::set_app_sqlite_db_encrypted(ApplicationName(app_name.clone()), encrypted_db);
+    let maintenance_window = resources
+        .and_then(|resources| resources.get("sqlite-db"))
+        .and_then(|sqlite_db| sqlite_db.get("maintenance"))
+        .map(|window| serde_json::from_value(window.clone()))
+        .transpose()?
+        .unwrap_or_default();
+    maintenance::set_app_window(ApplicationName(app_name.clone()), maintenance_window);
 
     let mut modules = Vec::new();
     for module_info in package.get_modules()? {
         let module = module_info.get_component()?;
-        modules.push(module);
+        if let Some(preopens) = module_info.get_preopens()? {
+            let preopens = preopens
+                .iter()
+                .map(|preopen| {
+                    vfs.root()
+                        .get_dir(&preopen.dir.as_str().into())
+                        .map_err(|_| {
+                            anyhow::anyhow!(
+                                "Preopened directory {} of module {} not found",
+                                preopen.dir,
+                                module_info.get_name()
+                            )
+                        })?;
+                    Ok((preopen.dir.clone(), preopen.permission()))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            filesystem::set_preopens(
+                ApplicationName(app_name.clone()),
+                module.id().clone(),
+                preopens,
+            );
+        }
+        cli::set_environment(
+            ApplicationName(app_name.clone()),
+            module.id().clone(),
+            module_info.get_env()?.vars(),
+        );
+        modules.push((module_info.get_name(), module));
     }
     let app = Application::new(app_name, vfs, modules);
 
@@ -75,6 +137,9 @@ fn mount_to_vfs(
                 PermissionLevel::Read,
             );
         }
+        if let Some(env) = module_info.get_env_file() {
+            bootstrapper.with_mounted_file(lib_module_dir_path.clone(), env, PermissionLevel::Read);
+        }
         if let Some(settings_schema) = module_info.get_settings_schema_file() {
             bootstrapper.with_mounted_file(
                 lib_module_dir_path.clone(),
@@ -18,6 +18,10 @@ pub(crate) struct SignaturePayload {
     www: Option<Blake2b256>,
     /// Hash of the share directory content.
     share: Option<Blake2b256>,
+    /// Hash of the ipfs_limits.json file.
+    ipfs_limits: Option<Blake2b256>,
+    /// Hash of the sbom.spdx.json file.
+    sbom: Option<Blake2b256>,
 }
 
 /// A `SignaturePayload` module object.
@@ -31,6 +35,10 @@ pub(crate) struct SignaturePayloadModule {
     config: Option<Blake2b256>,
     /// Hash of the whole replaced module's share package directory.
     share: Option<Blake2b256>,
+    /// Hash of the module's preopens.json package file.
+    preopens: Option<Blake2b256>,
+    /// Hash of the module's provenance.json package file.
+    provenance: Option<Blake2b256>,
 }
 
 /// `SignaturePayload` builder object.
@@ -45,6 +53,10 @@ pub(crate) struct SignaturePayloadBuilder {
     www: Option<Blake2b256>,
     /// Hash of the share directory content.
     share: Option<Blake2b256>,
+    /// Hash of the ipfs_limits.json file.
+    ipfs_limits: Option<Blake2b256>,
+    /// Hash of the sbom.spdx.json file.
+    sbom: Option<Blake2b256>,
 }
 
 impl SignaturePayloadBuilder {
@@ -56,6 +68,8 @@ impl SignaturePayloadBuilder {
             modules: vec![],
             www: None,
             share: None,
+            ipfs_limits: None,
+            sbom: None,
         }
     }
 
@@ -74,6 +88,16 @@ impl SignaturePayloadBuilder {
         self.share = Some(share);
     }
 
+    /// Set the ipfs_limits.json file hash.
+    pub(crate) fn with_ipfs_limits(&mut self, ipfs_limits: Blake2b256) {
+        self.ipfs_limits = Some(ipfs_limits);
+    }
+
+    /// Set the sbom.spdx.json file hash.
+    pub(crate) fn with_sbom(&mut self, sbom: Blake2b256) {
+        self.sbom = Some(sbom);
+    }
+
     /// Create a new `SignaturePayload`.
     pub(crate) fn build(self) -> SignaturePayload {
         SignaturePayload {
@@ -82,6 +106,8 @@ impl SignaturePayloadBuilder {
             modules: self.modules,
             www: self.www,
             share: self.share,
+            ipfs_limits: self.ipfs_limits,
+            sbom: self.sbom,
         }
     }
 }
@@ -96,6 +122,10 @@ pub(crate) struct SignaturePayloadModuleBuilder {
     config: Option<Blake2b256>,
     /// Hash of the whole replaced module's share package directory.
     share: Option<Blake2b256>,
+    /// Hash of the module's preopens.json package file.
+    preopens: Option<Blake2b256>,
+    /// Hash of the module's provenance.json package file.
+    provenance: Option<Blake2b256>,
 }
 
 impl SignaturePayloadModuleBuilder {
@@ -106,6 +136,8 @@ impl SignaturePayloadModuleBuilder {
             package,
             config: None,
             share: None,
+            preopens: None,
+            provenance: None,
         }
     }
 
@@ -119,6 +151,16 @@ impl SignaturePayloadModuleBuilder {
         self.share = Some(share);
     }
 
+    /// Set the preopens.json file hash.
+    pub(crate) fn with_preopens(&mut self, preopens: Blake2b256) {
+        self.preopens = Some(preopens);
+    }
+
+    /// Set the provenance.json file hash.
+    pub(crate) fn with_provenance(&mut self, provenance: Blake2b256) {
+        self.provenance = Some(provenance);
+    }
+
     /// Create a new `SignaturePayloadModule`.
     pub(crate) fn build(self) -> SignaturePayloadModule {
         SignaturePayloadModule {
@@ -126,6 +168,8 @@ impl SignaturePayloadModuleBuilder {
             package: self.package,
             config: self.config,
             share: self.share,
+            preopens: self.preopens,
+            provenance: self.provenance,
         }
     }
 }
@@ -154,6 +198,12 @@ impl SignaturePayloadEncoding for SignaturePayload {
                     if let Some(share) = &module.share {
                         json.insert("share".into(), share.to_hex().into());
                     }
+                    if let Some(preopens) = &module.preopens {
+                        json.insert("preopens".into(), preopens.to_hex().into());
+                    }
+                    if let Some(provenance) = &module.provenance {
+                        json.insert("provenance".into(), provenance.to_hex().into());
+                    }
                     json.into()
                 })
                 .collect();
@@ -166,6 +216,12 @@ impl SignaturePayloadEncoding for SignaturePayload {
         if let Some(share) = &self.share {
             json.insert("share".into(), share.to_hex().into());
         }
+        if let Some(ipfs_limits) = &self.ipfs_limits {
+            json.insert("ipfs_limits".into(), ipfs_limits.to_hex().into());
+        }
+        if let Some(sbom) = &self.sbom {
+            json.insert("sbom".into(), sbom.to_hex().into());
+        }
 
         json.into()
     }
@@ -226,11 +282,25 @@ impl SignaturePayloadEncoding for SignaturePayload {
                 .map(Blake2b256::from_hex)
                 .transpose()?;
 
+            let preopens = json_module
+                .get("preopens")
+                .and_then(|val| val.as_str())
+                .map(Blake2b256::from_hex)
+                .transpose()?;
+
+            let provenance = json_module
+                .get("provenance")
+                .and_then(|val| val.as_str())
+                .map(Blake2b256::from_hex)
+                .transpose()?;
+
             modules.push(SignaturePayloadModule {
                 name,
                 package,
                 config,
                 share,
+                preopens,
+                provenance,
             });
         }
 
@@ -246,12 +316,26 @@ impl SignaturePayloadEncoding for SignaturePayload {
             .map(Blake2b256::from_hex)
             .transpose()?;
 
+        let ipfs_limits = json
+            .get("ipfs_limits")
+            .and_then(|val| val.as_str())
+            .map(Blake2b256::from_hex)
+            .transpose()?;
+
+        let sbom = json
+            .get("sbom")
+            .and_then(|val| val.as_str())
+            .map(Blake2b256::from_hex)
+            .transpose()?;
+
         Ok(SignaturePayload {
             metadata,
             icon,
             modules,
             www,
             share,
+            ipfs_limits,
+            sbom,
         })
     }
 }
@@ -318,10 +402,14 @@ mod tests {
                 SignaturePayloadModuleBuilder::new("module_1".to_string(), hash.clone());
             payload_module_builder.with_config(hash.clone());
             payload_module_builder.with_share(hash.clone());
+            payload_module_builder.with_preopens(hash.clone());
+            payload_module_builder.with_provenance(hash.clone());
 
             let mut payload_builder = SignaturePayloadBuilder::new(hash.clone(), hash.clone());
             payload_builder.with_www(hash.clone());
             payload_builder.with_share(hash.clone());
+            payload_builder.with_ipfs_limits(hash.clone());
+            payload_builder.with_sbom(hash.clone());
             payload_builder.with_module(payload_module_builder.build());
             let payload = payload_builder.build();
 
@@ -337,10 +425,14 @@ mod tests {
                         "package": hash.to_hex(),
                         "config": hash.to_hex(),
                         "share": hash.to_hex(),
+                        "preopens": hash.to_hex(),
+                        "provenance": hash.to_hex(),
                     }
                 ],
                 "www": hash.to_hex(),
                 "share": hash.to_hex(),
+                "ipfs_limits": hash.to_hex(),
+                "sbom": hash.to_hex(),
             });
             assert_eq!(json, expected_json);
 
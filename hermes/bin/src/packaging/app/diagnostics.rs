@@ -0,0 +1,185 @@
+//! Diagnostics of an application package manifest, reported before packaging.
+//!
+//! Besides the manifest JSON schema, e.g. unknown fields, every module package is
+//! checked to open and to link against the WIT interfaces of this Hermes node, and the
+//! preopened directories of the modules are checked against the permissions of the
+//! application VFS. Every diagnostic is located in the manifest file.
+
+use std::{fmt::Display, path::Path};
+
+use super::{
+    super::{module::ModulePackage, schema_validation, FileError},
+    Manifest,
+};
+use crate::vfs::{PermissionLevel, VfsBootstrapper};
+
+/// Diagnostic of a manifest, located in the manifest file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Diagnostic {
+    /// 1-based line.
+    pub(crate) line: usize,
+    /// 1-based column.
+    pub(crate) column: usize,
+    /// What is wrong.
+    pub(crate) message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Check the manifest at the path, returning its diagnostics, empty if the manifest is
+/// valid.
+///
+/// ## Errors
+///
+/// Returns an error if the manifest file can not be read.
+pub(crate) fn check_manifest(path: &Path) -> anyhow::Result<Vec<Diagnostic>> {
+    let text = std::fs::read_to_string(path).map_err(|_| FileError::from_path(path, None))?;
+    let at = |pointer: &str, message: String| {
+        let (line, column) = schema_validation::locate(&text, pointer);
+        Diagnostic {
+            line,
+            column,
+            message,
+        }
+    };
+
+    let json: serde_json::Value = match serde_json::from_str(&text) {
+        Ok(json) => json,
+        Err(err) => {
+            return Ok(vec![Diagnostic {
+                line: err.line(),
+                column: err.column(),
+                message: format!("Invalid JSON: {err}"),
+            }]);
+        },
+    };
+    let schema_validator = schema_validation::SchemaValidator::from_str(Manifest::MANIFEST_SCHEMA)?;
+    let schema_errors = schema_validator.errors(&json);
+    if !schema_errors.is_empty() {
+        return Ok(schema_errors
+            .into_iter()
+            .map(|(pointer, message)| at(&pointer, message))
+            .collect());
+    }
+    let manifest = match Manifest::from_file(path) {
+        Ok(manifest) => manifest,
+        Err(err) => return Ok(vec![at("", err.to_string())]),
+    };
+
+    let mut diagnostics = Vec::new();
+    for (i, module) in manifest.modules.iter().enumerate() {
+        let package_path = module.package.upload_to_fs();
+        match ModulePackage::from_file(&package_path) {
+            Ok(module_package) => {
+                if let Err(err) = module_package.get_component() {
+                    diagnostics.push(at(
+                        &format!("/modules/{i}/package"),
+                        format!(
+                            "Module package `{}` does not match the Hermes WIT interfaces: {err}",
+                            package_path.display()
+                        ),
+                    ));
+                }
+                if module.config.is_some()
+                    && !matches!(module_package.get_config_schema(), Ok(Some(_)))
+                {
+                    diagnostics.push(at(
+                        &format!("/modules/{i}/config"),
+                        format!(
+                            "Module package `{}` has no config schema to validate the config with",
+                            package_path.display()
+                        ),
+                    ));
+                }
+            },
+            Err(err) => {
+                diagnostics.push(at(
+                    &format!("/modules/{i}/package"),
+                    format!(
+                        "Module package `{}` can not be opened: {err}",
+                        package_path.display()
+                    ),
+                ));
+            },
+        }
+
+        for (j, preopen) in module.preopens.iter().flatten().enumerate() {
+            if !preopen.read_only
+                && VfsBootstrapper::structure_permission(&preopen.dir) == PermissionLevel::Read
+            {
+                diagnostics.push(at(
+                    &format!("/modules/{i}/preopens/{j}/read_only"),
+                    format!(
+                        "Missing write permission on `{}`, the directory is read only in the application VFS",
+                        preopen.dir
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn check_manifest_test() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("manifest.json");
+
+        std::fs::write(&path, "{\n  \"name\": \"app\",\n}").unwrap();
+        let diagnostics = check_manifest(&path).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(diagnostics.first(), Some(d) if d.line == 3));
+
+        std::fs::write(
+            &path,
+            r#"{
+  "$schema": "https://raw.githubusercontent.com/input-output-hk/hermes/main/hermes/schemas/hermes_app_manifest.schema.json",
+  "name": "app",
+  "modules": [
+    {
+      "package": "missing.hmod",
+      "preopens": [{ "dir": "/srv/share", "read_only": false }],
+      "extra": 1
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+        assert_eq!(check_manifest(&path).unwrap(), vec![Diagnostic {
+            line: 8,
+            column: 7,
+            message: "Unknown field `extra`".to_string(),
+        }]);
+
+        std::fs::write(
+            &path,
+            r#"{
+  "$schema": "https://raw.githubusercontent.com/input-output-hk/hermes/main/hermes/schemas/hermes_app_manifest.schema.json",
+  "name": "app",
+  "modules": [
+    {
+      "package": "missing.hmod",
+      "preopens": [
+        { "dir": "/tmp/cache", "read_only": false },
+        { "dir": "/srv/share", "read_only": false }
+      ]
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+        let diagnostics = check_manifest(&path).unwrap();
+        let locations: Vec<_> = diagnostics.iter().map(|d| (d.line, d.column)).collect();
+        assert_eq!(locations, vec![(6, 7), (9, 32)]);
+    }
+}
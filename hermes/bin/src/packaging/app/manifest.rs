@@ -2,8 +2,12 @@
 
 use std::path::Path;
 
-use super::super::{schema_validation::SchemaValidator, FileError};
-use crate::hdf5::resources::ResourceBuilder;
+use super::{
+    super::{schema_validation::SchemaValidator, FileError},
+    preopens::Preopen,
+    provenance::ManifestProvenance,
+};
+use crate::{hdf5::resources::ResourceBuilder, ipfs::IpfsLimits};
 
 /// Hermes application package manifest.json definition.
 #[derive(Debug, PartialEq, Eq)]
@@ -20,6 +24,10 @@ pub(crate) struct Manifest {
     pub(crate) www: Option<ResourceBuilder>,
     /// Path to the share directory.
     pub(crate) share: Option<ResourceBuilder>,
+    /// Size limits of the content the application puts on IPFS.
+    pub(crate) ipfs_limits: Option<IpfsLimits>,
+    /// Path to the SPDX JSON SBOM file.
+    pub(crate) sbom: Option<ResourceBuilder>,
 }
 
 /// `Manifest` `modules` item field definition.
@@ -33,11 +41,15 @@ pub(crate) struct ManifestModule {
     pub(crate) config: Option<ResourceBuilder>,
     /// Path to the WASM module share directory.
     pub(crate) share: Option<ResourceBuilder>,
+    /// VFS directories preopened for the WASM module.
+    pub(crate) preopens: Option<Vec<Preopen>>,
+    /// Provenance of the WASM module.
+    pub(crate) provenance: Option<ManifestProvenance>,
 }
 
 impl Manifest {
     /// WASM module manifest.json schema.
-    const MANIFEST_SCHEMA: &'static str =
+    pub(super) const MANIFEST_SCHEMA: &'static str =
         include_str!("../../../../schemas/hermes_app_manifest.schema.json");
 
     /// Default package name.
@@ -85,6 +97,9 @@ impl Manifest {
             if let Some(share) = m.share.as_mut() {
                 share.make_relative_to(dir_path);
             }
+            if let Some(cargo_lock) = m.provenance.as_mut().and_then(|p| p.cargo_lock.as_mut()) {
+                cargo_lock.make_relative_to(dir_path);
+            }
         });
         if let Some(www) = manifest.www.as_mut() {
             www.make_relative_to(dir_path);
@@ -92,6 +107,9 @@ impl Manifest {
         if let Some(share) = manifest.share.as_mut() {
             share.make_relative_to(dir_path);
         }
+        if let Some(sbom) = manifest.sbom.as_mut() {
+            sbom.make_relative_to(dir_path);
+        }
 
         Ok(manifest)
     }
@@ -103,7 +121,8 @@ mod serde_def {
 
     use serde::Deserialize;
 
-    use crate::hdf5::resources::ResourceBuilder;
+    use super::{ManifestProvenance, Preopen};
+    use crate::{hdf5::resources::ResourceBuilder, ipfs::IpfsLimits};
 
     #[derive(Deserialize)]
     pub(crate) struct ManifestSerde {
@@ -117,6 +136,8 @@ mod serde_def {
         modules: Vec<ManifestModuleSerde>,
         www: Option<ResourceBuilder>,
         share: Option<ResourceBuilder>,
+        ipfs_limits: Option<IpfsLimits>,
+        sbom: Option<ResourceBuilder>,
     }
 
     #[derive(Deserialize)]
@@ -125,6 +146,8 @@ mod serde_def {
         name: Option<String>,
         config: Option<ResourceBuilder>,
         share: Option<ResourceBuilder>,
+        preopens: Option<Vec<Preopen>>,
+        provenance: Option<ManifestProvenance>,
     }
 
     impl From<ManifestSerde> for super::Manifest {
@@ -142,11 +165,15 @@ mod serde_def {
                             name: der.name,
                             config: der.config,
                             share: der.share,
+                            preopens: der.preopens,
+                            provenance: der.provenance,
                         }
                     })
                     .collect(),
                 www: def.www,
                 share: def.share,
+                ipfs_limits: def.ipfs_limits,
+                sbom: def.sbom,
             }
         }
     }
@@ -175,10 +202,22 @@ mod tests {
                         "package": "module.hmod",
                         "name": "module_name",
                         "config": "config.json",
-                        "share": "share"
+                        "share": "share",
+                        "preopens": [{ "dir": "/srv/share" }],
+                        "provenance": {
+                            "repo": "https://github.com/input-output-hk/hermes",
+                            "commit": "0123abc",
+                            "toolchain": "rustc 1.78.0",
+                            "cargo_lock": "Cargo.lock"
+                        }
                     }],
                     "www": "www",
-                    "share": "share"
+                    "share": "share",
+                    "ipfs_limits": {
+                        "max_file_size": 1_048_576,
+                        "max_message_size": 4096
+                    },
+                    "sbom": "sbom.spdx.json"
                 }).to_string();
             std::fs::write(&path, manifest_json_data).unwrap();
             let manifest = Manifest::from_file(&path).unwrap();
@@ -191,9 +230,25 @@ mod tests {
                     name: Some("module_name".to_string()),
                     config: Some(ResourceBuilder::Fs(dir_path.join("config.json"))),
                     share: Some(ResourceBuilder::Fs(dir_path.join("share"))),
+                    preopens: Some(vec![Preopen {
+                        dir: "/srv/share".to_string(),
+                        read_only: true,
+                    }]),
+                    provenance: Some(ManifestProvenance {
+                        repo: "https://github.com/input-output-hk/hermes".to_string(),
+                        commit: "0123abc".to_string(),
+                        toolchain: "rustc 1.78.0".to_string(),
+                        cargo_lock: Some(ResourceBuilder::Fs(dir_path.join("Cargo.lock"))),
+                    }),
                 }],
                 www: Some(ResourceBuilder::Fs(dir_path.join("www"))),
                 share: Some(ResourceBuilder::Fs(dir_path.join("share"))),
+                ipfs_limits: Some(IpfsLimits {
+                    max_file_size: Some(1_048_576),
+                    max_dht_value_size: None,
+                    max_message_size: Some(4096),
+                }),
+                sbom: Some(ResourceBuilder::Fs(dir_path.join("sbom.spdx.json"))),
             });
         }
 
@@ -224,9 +279,13 @@ mod tests {
                     name: Some("module_name".to_string()),
                     config: Some(ResourceBuilder::Fs("/config.json".into())),
                     share: Some(ResourceBuilder::Fs("/share".into())),
+                    preopens: None,
+                    provenance: None,
                 }],
                 www: Some(ResourceBuilder::Fs("/www".into())),
                 share: Some(ResourceBuilder::Fs("/share".into())),
+                ipfs_limits: None,
+                sbom: None,
             });
         }
 
@@ -254,9 +313,13 @@ mod tests {
                     name: Some("module_name".to_string()),
                     config: Some(ResourceBuilder::Fs(dir_path.join("config.json"))),
                     share: Some(ResourceBuilder::Fs(dir_path.join("share"))),
+                    preopens: None,
+                    provenance: None,
                 }],
                 www: Some(ResourceBuilder::Fs(dir_path.join("www"))),
                 share: Some(ResourceBuilder::Fs(dir_path.join("share"))),
+                ipfs_limits: None,
+                sbom: None,
             });
         }
 
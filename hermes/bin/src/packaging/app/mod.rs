@@ -2,19 +2,25 @@
 
 mod app_builder;
 mod author_payload;
+mod diagnostics;
 mod manifest;
 mod module_info;
+mod preopens;
+mod provenance;
 #[cfg(test)]
 mod tests;
 
 pub(crate) use app_builder::build_app;
 use chrono::{DateTime, Utc};
+pub(crate) use diagnostics::check_manifest;
 pub(crate) use manifest::{Manifest, ManifestModule};
 pub(crate) use module_info::AppModuleInfo;
+use preopens::Preopens;
+use provenance::{Provenance, Sbom};
 
 use super::{
     hash::Blake2b256,
-    metadata::{Metadata, MetadataSchema},
+    metadata::{Metadata, MetadataSchema, TOOLCHAIN},
     module::{self, ModulePackage},
     package::Package,
     sign::{
@@ -30,6 +36,7 @@ use crate::{
         resources::{BytesResource, ResourceTrait},
         Dir, File, Path,
     },
+    ipfs::IpfsLimits,
 };
 
 /// Hermes application package.
@@ -47,14 +54,22 @@ impl ApplicationPackage {
     const FILE_EXTENSION: &'static str = "happ";
     /// Application package icon file path.
     const ICON_FILE: &'static str = "icon.svg";
+    /// Application package IPFS content size limits file path.
+    const IPFS_LIMITS_FILE: &'static str = "ipfs_limits.json";
     /// Application package 'lib' directory path.
     const LIB_DIR: &'static str = "lib";
     /// Application package metadata file path.
     const METADATA_FILE: &'static str = "metadata.json";
     /// Application package overridden module's config file name.
     const MODULE_CONFIG_FILE: &'static str = "config.json";
+    /// Application package module's preopened directories file name.
+    const MODULE_PREOPENS_FILE: &'static str = "preopens.json";
+    /// Application package module's provenance file name.
+    const MODULE_PROVENANCE_FILE: &'static str = "provenance.json";
     /// Application package overridden module's 'share' dir name.
     const MODULE_SHARE_DIR: &'static str = "share";
+    /// Application package SPDX SBOM file path.
+    const SBOM_FILE: &'static str = "sbom.spdx.json";
     /// Application package `srv` directory name.
     const SRV_DIR: &'static str = "srv";
     /// Application package `srv/share` directory path.
@@ -96,6 +111,14 @@ impl ApplicationPackage {
         Ok(Self(package))
     }
 
+    /// Calculate the hash of the whole package content, i.e. of the names and contents
+    /// of all its files, so two builds of the same application can be compared.
+    pub(crate) fn content_hash(&self) -> anyhow::Result<Blake2b256> {
+        self.0
+            .calculate_dir_hash(&Path::default())?
+            .ok_or(anyhow::anyhow!("Cannot read the package content"))
+    }
+
     /// Validate package with its signature and other contents.
     /// If `untrusted` flag is `true` the signature will not be verified.
     pub(crate) fn validate(&self, untrusted: bool) -> anyhow::Result<()> {
@@ -126,6 +149,8 @@ impl ApplicationPackage {
             Err(err) => errors.add_err(err),
         }
 
+        self.get_sbom().map_or_else(errors.get_add_err_fn(), |_| ());
+
         if !untrusted {
             self.verify_author_sign()
                 .unwrap_or_else(errors.get_add_err_fn());
@@ -224,6 +249,28 @@ impl ApplicationPackage {
                 signature_payload_module_builder.with_share(share_hash);
             }
 
+            let usr_module_preopens_path: Path = format!(
+                "{}/{}/{}",
+                Self::USR_LIB_DIR,
+                module_name,
+                Self::MODULE_PREOPENS_FILE
+            )
+            .into();
+            if let Some(preopens_hash) = self.0.calculate_file_hash(usr_module_preopens_path)? {
+                signature_payload_module_builder.with_preopens(preopens_hash);
+            }
+
+            let usr_module_provenance_path: Path = format!(
+                "{}/{}/{}",
+                Self::USR_LIB_DIR,
+                module_name,
+                Self::MODULE_PROVENANCE_FILE
+            )
+            .into();
+            if let Some(provenance_hash) = self.0.calculate_file_hash(usr_module_provenance_path)? {
+                signature_payload_module_builder.with_provenance(provenance_hash);
+            }
+
             signature_payload_builder.with_module(signature_payload_module_builder.build());
         }
 
@@ -233,6 +280,12 @@ impl ApplicationPackage {
         if let Some(share_hash) = self.0.calculate_dir_hash(&Self::SRV_SHARE_DIR.into())? {
             signature_payload_builder.with_share(share_hash);
         }
+        if let Some(ipfs_limits_hash) = self.0.calculate_file_hash(Self::IPFS_LIMITS_FILE.into())? {
+            signature_payload_builder.with_ipfs_limits(ipfs_limits_hash);
+        }
+        if let Some(sbom_hash) = self.0.calculate_file_hash(Self::SBOM_FILE.into())? {
+            signature_payload_builder.with_sbom(sbom_hash);
+        }
 
         Ok(signature_payload_builder.build())
     }
@@ -261,6 +314,23 @@ impl ApplicationPackage {
         self.get_metadata_file().map(Metadata::from_reader)?
     }
 
+    /// Get the IPFS content size limits from package, unlimited if not set.
+    pub(crate) fn get_ipfs_limits(&self) -> anyhow::Result<IpfsLimits> {
+        self.0
+            .get_file(Self::IPFS_LIMITS_FILE.into())
+            .ok()
+            .map_or_else(|| Ok(IpfsLimits::default()), IpfsLimits::from_reader)
+    }
+
+    /// Get the SPDX SBOM summary from package, if present.
+    pub(crate) fn get_sbom(&self) -> anyhow::Result<Option<Sbom>> {
+        self.0
+            .get_file(Self::SBOM_FILE.into())
+            .ok()
+            .map(Sbom::from_reader)
+            .transpose()
+    }
+
     /// Get author `Signature` object from package.
     pub(crate) fn get_author_signature(
         &self,
@@ -287,8 +357,21 @@ impl ApplicationPackage {
             let app_config = usr_lib_module
                 .get_file(Self::MODULE_CONFIG_FILE.into())
                 .ok();
+            let app_preopens = usr_lib_module
+                .get_file(Self::MODULE_PREOPENS_FILE.into())
+                .ok();
+            let app_provenance = usr_lib_module
+                .get_file(Self::MODULE_PROVENANCE_FILE.into())
+                .ok();
 
-            let module_info = AppModuleInfo::new(name, package, app_config, app_share);
+            let module_info = AppModuleInfo::new(
+                name,
+                package,
+                app_config,
+                app_share,
+                app_preopens,
+                app_provenance,
+            );
             modules.push(module_info);
         }
         Ok(modules)
@@ -319,6 +402,14 @@ impl ApplicationPackage {
             Self::METADATA_FILE.into(),
         )
         .unwrap_or_else(errors.get_add_err_fn());
+        if let Some(ipfs_limits) = &manifest.ipfs_limits {
+            write_ipfs_limits(ipfs_limits, package, Self::IPFS_LIMITS_FILE)
+                .unwrap_or_else(errors.get_add_err_fn());
+        }
+        if let Some(sbom) = &manifest.sbom {
+            validate_and_write_sbom(&sbom.build(), package, Self::SBOM_FILE.into())
+                .unwrap_or_else(errors.get_add_err_fn());
+        }
 
         package
             .create_dir(Self::LIB_DIR.into())
@@ -337,6 +428,8 @@ impl ApplicationPackage {
                 &Self::USR_LIB_DIR.into(),
                 Self::MODULE_CONFIG_FILE,
                 Self::MODULE_SHARE_DIR,
+                Self::MODULE_PREOPENS_FILE,
+                Self::MODULE_PROVENANCE_FILE,
             )
             .unwrap_or_else(errors.get_add_err_fn());
         }
@@ -365,7 +458,8 @@ fn validate_and_write_icon(
 }
 
 /// Validate metadata.json file and write it to the package to the provided dir path.
-/// Also updates `Metadata` object by setting `build_date` and `name` properties.
+/// Also updates `Metadata` object by setting `build_date`, `toolchain` and `name`
+/// properties.
 fn validate_and_write_metadata(
     resource: &impl ResourceTrait, build_date: DateTime<Utc>, name: &str, dir: &Dir, path: Path,
 ) -> anyhow::Result<()> {
@@ -374,6 +468,7 @@ fn validate_and_write_metadata(
     let mut metadata = Metadata::<ApplicationPackage>::from_reader(metadata_reader)
         .map_err(|err| FileError::from_string(resource.to_string(), Some(err)))?;
     metadata.set_build_date(build_date);
+    metadata.set_toolchain(TOOLCHAIN);
     metadata.set_name(name);
 
     let resource = BytesResource::new(resource.name()?, metadata.to_bytes()?);
@@ -381,10 +476,28 @@ fn validate_and_write_metadata(
     Ok(())
 }
 
+/// Write IPFS content size limits file to the package to the provided path.
+fn write_ipfs_limits(ipfs_limits: &IpfsLimits, dir: &Dir, file_name: &str) -> anyhow::Result<()> {
+    let resource = BytesResource::new(file_name.to_string(), ipfs_limits.to_bytes()?);
+    dir.copy_resource_file(&resource, file_name.into())?;
+    Ok(())
+}
+
+/// Validate the SPDX SBOM file and write it to the package to the provided path.
+fn validate_and_write_sbom(
+    resource: &impl ResourceTrait, dir: &Dir, path: Path,
+) -> anyhow::Result<()> {
+    Sbom::from_reader(resource.get_reader()?)
+        .map_err(|err| FileError::from_string(resource.to_string(), Some(err)))?;
+    dir.copy_resource_file(resource, path)?;
+    Ok(())
+}
+
 /// Validate WASM module package and write it to the package to the provided dir path.
 fn validate_and_write_module(
     manifest: &ManifestModule, dir: &Dir, modules_path: &Path, usr_modules_path: &Path,
-    config_file_name: &str, share_dir_name: &str,
+    config_file_name: &str, share_dir_name: &str, preopens_file_name: &str,
+    provenance_file_name: &str,
 ) -> anyhow::Result<()> {
     let module_package = ModulePackage::from_file(manifest.package.upload_to_fs())?;
     module_package.validate(true)?;
@@ -418,6 +531,16 @@ fn validate_and_write_module(
             share_dir_name.into(),
         )?;
     }
+    if let Some(preopens) = &manifest.preopens {
+        let preopens = Preopens::new(preopens.clone())?;
+        let resource = BytesResource::new(preopens_file_name.to_string(), preopens.to_bytes()?);
+        module_overridable_dir.copy_resource_file(&resource, preopens_file_name.into())?;
+    }
+    if let Some(provenance) = &manifest.provenance {
+        let provenance = Provenance::from_manifest(provenance)?;
+        let resource = BytesResource::new(provenance_file_name.to_string(), provenance.to_bytes()?);
+        module_overridable_dir.copy_resource_file(&resource, provenance_file_name.into())?;
+    }
     Ok(())
 }
 
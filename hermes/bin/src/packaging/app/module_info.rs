@@ -1,8 +1,8 @@
 //! An application's module info object
 
 use super::{
-    module::{Config, ConfigInfo, SignaturePayload},
-    Metadata, ModulePackage, Signature,
+    module::{Config, ConfigInfo, Env, SignaturePayload},
+    Metadata, ModulePackage, Preopens, Provenance, Signature,
 };
 use crate::{
     hdf5::{Dir, File},
@@ -19,18 +19,25 @@ pub(crate) struct AppModuleInfo {
     app_config: Option<File>,
     /// Application defined module's `share` directory
     app_share: Option<Dir>,
+    /// Application defined module's `preopens.json` file
+    app_preopens: Option<File>,
+    /// Application defined module's `provenance.json` file
+    app_provenance: Option<File>,
 }
 
 impl AppModuleInfo {
     /// Create a new `AppModuleInfo` instance
     pub(crate) fn new(
         name: String, package: ModulePackage, app_config: Option<File>, app_share: Option<Dir>,
+        app_preopens: Option<File>, app_provenance: Option<File>,
     ) -> Self {
         Self {
             name,
             package,
             app_config,
             app_share,
+            app_preopens,
+            app_provenance,
         }
     }
 
@@ -42,6 +49,7 @@ impl AppModuleInfo {
     /// Validate module package with its signature and other contents.
     /// If `untrusted` flag is `true` the signature will not be verified.
     pub(crate) fn validate(&self, untrusted: bool) -> anyhow::Result<()> {
+        self.get_provenance()?;
         self.package.validate(untrusted)
     }
 
@@ -75,6 +83,32 @@ impl AppModuleInfo {
         Ok(Some(config_info))
     }
 
+    /// Get module's preopened directories, `None` if the module sees the whole VFS
+    pub(crate) fn get_preopens(&self) -> anyhow::Result<Option<Preopens>> {
+        self.app_preopens
+            .clone()
+            .map(Preopens::from_reader)
+            .transpose()
+    }
+
+    /// Get module's provenance, if defined by the application
+    pub(crate) fn get_provenance(&self) -> anyhow::Result<Option<Provenance>> {
+        self.app_provenance
+            .clone()
+            .map(Provenance::from_reader)
+            .transpose()
+    }
+
+    /// Get module's environment variables
+    pub(crate) fn get_env(&self) -> anyhow::Result<Env> {
+        self.package.get_env()
+    }
+
+    /// Get module's environment variables file
+    pub(super) fn get_env_file(&self) -> Option<File> {
+        self.package.get_env_file()
+    }
+
     /// Get module's WASM component file
     pub(super) fn get_component_file(&self) -> anyhow::Result<File> {
         self.package.get_component_file()
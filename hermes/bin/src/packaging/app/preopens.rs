@@ -0,0 +1,101 @@
+//! Application package module's preopened directories JSON
+
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vfs::PermissionLevel;
+
+/// A VFS directory preopened for a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Preopen {
+    /// Absolute path of the VFS directory.
+    pub(crate) dir: String,
+    /// Whether the module can only read the directory.
+    #[serde(default = "Preopen::default_read_only")]
+    pub(crate) read_only: bool,
+}
+
+impl Preopen {
+    /// Preopens are read only unless stated otherwise.
+    fn default_read_only() -> bool {
+        true
+    }
+
+    /// Permission level of the module on the directory.
+    pub(crate) fn permission(&self) -> PermissionLevel {
+        (!self.read_only).into()
+    }
+}
+
+/// VFS directories preopened for a module, instead of the whole application VFS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Preopens(Vec<Preopen>);
+
+impl Preopens {
+    /// Create `Preopens`, validating the directories paths.
+    pub(crate) fn new(preopens: Vec<Preopen>) -> anyhow::Result<Self> {
+        for (i, preopen) in preopens.iter().enumerate() {
+            anyhow::ensure!(
+                preopen.dir.starts_with('/'),
+                "Preopened directory `{}` must be an absolute path",
+                preopen.dir
+            );
+            anyhow::ensure!(
+                preopen
+                    .dir
+                    .split('/')
+                    .all(|elem| elem != "." && elem != ".."),
+                "Preopened directory `{}` must not contain `.` or `..`",
+                preopen.dir
+            );
+            anyhow::ensure!(
+                preopens
+                    .iter()
+                    .skip(i.saturating_add(1))
+                    .all(|other| other.dir != preopen.dir),
+                "Directory `{}` is preopened more than once",
+                preopen.dir
+            );
+        }
+        Ok(Self(preopens))
+    }
+
+    /// Create `Preopens` from reader.
+    pub(crate) fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        Self::new(serde_json::from_reader(reader)?)
+    }
+
+    /// Convert `Preopens` object to json bytes
+    pub(crate) fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let bytes = serde_json::to_vec(&self.0)?;
+        Ok(bytes)
+    }
+
+    /// Preopened directories.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Preopen> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preopens_validation_test() {
+        let preopens = Preopens::from_reader(
+            r#"[{"dir": "/lib/module"}, {"dir": "/srv/share", "read_only": false}]"#.as_bytes(),
+        )
+        .unwrap();
+        let permissions: Vec<_> = preopens.iter().map(Preopen::permission).collect();
+        assert_eq!(permissions, vec![
+            PermissionLevel::Read,
+            PermissionLevel::ReadAndWrite
+        ]);
+
+        assert!(Preopens::from_reader(r#"[{"dir": "lib"}]"#.as_bytes()).is_err());
+        assert!(Preopens::from_reader(r#"[{"dir": "/lib/../srv"}]"#.as_bytes()).is_err());
+        assert!(Preopens::from_reader(r#"[{"dir": "/lib"}, {"dir": "/lib"}]"#.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Application package module's provenance JSON and application SPDX SBOM.
+
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+
+use super::super::hash::Blake2b256;
+use crate::hdf5::resources::{ResourceBuilder, ResourceTrait};
+
+/// Provenance of a module, as defined in the application manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct ManifestProvenance {
+    /// URL of the source repository of the module.
+    pub(crate) repo: String,
+    /// Commit of the source repository the module was built from.
+    pub(crate) commit: String,
+    /// Toolchain the module was built with.
+    pub(crate) toolchain: String,
+    /// Path to the `Cargo.lock` file the module was built with.
+    pub(crate) cargo_lock: Option<ResourceBuilder>,
+}
+
+/// Provenance of a module, as stored in the application package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Provenance {
+    /// URL of the source repository of the module.
+    pub(crate) repo: String,
+    /// Commit of the source repository the module was built from.
+    pub(crate) commit: String,
+    /// Toolchain the module was built with.
+    pub(crate) toolchain: String,
+    /// Blake2b hash hex of the `Cargo.lock` file the module was built with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cargo_lock_digest: Option<String>,
+}
+
+impl Provenance {
+    /// Create `Provenance` from its manifest definition, hashing its `Cargo.lock` file.
+    pub(crate) fn from_manifest(manifest: &ManifestProvenance) -> anyhow::Result<Self> {
+        let cargo_lock_digest = manifest
+            .cargo_lock
+            .as_ref()
+            .map(|cargo_lock| {
+                let mut bytes = Vec::new();
+                cargo_lock.build().get_reader()?.read_to_end(&mut bytes)?;
+                anyhow::Ok(Blake2b256::hash(&bytes).to_hex())
+            })
+            .transpose()?;
+        Ok(Self {
+            repo: manifest.repo.clone(),
+            commit: manifest.commit.clone(),
+            toolchain: manifest.toolchain.clone(),
+            cargo_lock_digest,
+        })
+    }
+
+    /// Create `Provenance` from reader.
+    pub(crate) fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Convert `Provenance` object to json bytes
+    pub(crate) fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let bytes = serde_json::to_vec(self)?;
+        Ok(bytes)
+    }
+}
+
+/// Summary of an SPDX JSON SBOM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Sbom {
+    /// SPDX specification version, e.g. `SPDX-2.3`.
+    pub(crate) spdx_version: String,
+    /// Name of the SPDX document.
+    pub(crate) name: String,
+    /// Number of packages listed.
+    pub(crate) packages: usize,
+}
+
+impl Sbom {
+    /// Create `Sbom` from reader, checking it is an SPDX JSON document.
+    pub(crate) fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        let json: serde_json::Value = serde_json::from_reader(reader)
+            .map_err(|err| anyhow::anyhow!("SBOM is not a JSON document: {err}"))?;
+        let spdx_version = json
+            .get("spdxVersion")
+            .and_then(serde_json::Value::as_str)
+            .filter(|version| version.starts_with("SPDX-"))
+            .ok_or(anyhow::anyhow!(
+                "SBOM is not an SPDX document, missing `spdxVersion`"
+            ))?
+            .to_string();
+        let name = json
+            .get("name")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let packages = json
+            .get("packages")
+            .and_then(serde_json::Value::as_array)
+            .map_or(0, Vec::len);
+        Ok(Self {
+            spdx_version,
+            name,
+            packages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn provenance_test() {
+        let dir = TempDir::new().unwrap();
+        let cargo_lock = dir.child("Cargo.lock");
+        std::fs::write(&cargo_lock, "version = 3").unwrap();
+
+        let provenance = Provenance::from_manifest(&ManifestProvenance {
+            repo: "https://github.com/input-output-hk/hermes".to_string(),
+            commit: "0123abc".to_string(),
+            toolchain: "rustc 1.78.0".to_string(),
+            cargo_lock: Some(ResourceBuilder::Fs(cargo_lock)),
+        })
+        .unwrap();
+        assert_eq!(
+            provenance.cargo_lock_digest,
+            Some(Blake2b256::hash(b"version = 3").to_hex())
+        );
+        let bytes = provenance.to_bytes().unwrap();
+        assert_eq!(
+            Provenance::from_reader(bytes.as_slice()).unwrap(),
+            provenance
+        );
+    }
+
+    #[test]
+    fn sbom_test() {
+        let sbom = Sbom::from_reader(
+            r#"{"spdxVersion": "SPDX-2.3", "name": "app", "packages": [{}, {}]}"#.as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(sbom, Sbom {
+            spdx_version: "SPDX-2.3".to_string(),
+            name: "app".to_string(),
+            packages: 2,
+        });
+
+        assert!(Sbom::from_reader(r#"{"bomFormat": "CycloneDX"}"#.as_bytes()).is_err());
+        assert!(Sbom::from_reader("not json".as_bytes()).is_err());
+    }
+}
@@ -153,6 +153,8 @@ fn prepare_package_dir(
             package: ResourceBuilder::Fs(module_package_path),
             config: Some(ResourceBuilder::Fs(config_path)),
             share: Some(ResourceBuilder::Fs(app_module_share_path)),
+            preopens: None,
+            provenance: None,
         });
     }
 
@@ -163,6 +165,8 @@ fn prepare_package_dir(
         modules,
         www: Some(ResourceBuilder::Fs(www_path)),
         share: Some(ResourceBuilder::Fs(share_path)),
+        ipfs_limits: None,
+        sbom: None,
     }
 }
 
@@ -245,11 +249,38 @@ fn from_dir_test() {
     // to have a corresponded values update `app_package_content`.
     app_package_content.metadata.set_name(&manifest.name);
     app_package_content.metadata.set_build_date(build_date);
+    app_package_content.metadata.set_toolchain(TOOLCHAIN);
 
     // check app package integrity
     check_app_integrity(&app_package_content, &package, &manifest);
 }
 
+#[test]
+fn reproducible_build_test() {
+    let dir = TempDir::new().unwrap();
+    let rebuild_dir = TempDir::new().unwrap();
+
+    let mut app_package_content = prepare_default_package_content(2);
+    let build_date = DateTime::default();
+    let manifest = prepare_package_dir(
+        "app".to_string(),
+        &[],
+        build_date,
+        dir.path(),
+        &mut app_package_content,
+    );
+
+    let package =
+        ApplicationPackage::build_from_manifest(&manifest, dir.path(), None, build_date).unwrap();
+    let rebuild =
+        ApplicationPackage::build_from_manifest(&manifest, rebuild_dir.path(), None, build_date)
+            .unwrap();
+    assert_eq!(
+        package.content_hash().unwrap(),
+        rebuild.content_hash().unwrap()
+    );
+}
+
 #[test]
 fn author_sing_test() {
     let dir = TempDir::new().unwrap();
@@ -310,6 +341,69 @@ fn author_sign_package(package: &ApplicationPackage) {
     assert!(package.validate(false).is_ok());
 }
 
+#[test]
+fn sbom_and_provenance_test() {
+    let dir = TempDir::new().unwrap();
+
+    let mut app_package_content = prepare_default_package_content(1);
+    let build_date = DateTime::default();
+    let mut manifest = prepare_package_dir(
+        "app".to_string(),
+        &[],
+        build_date,
+        dir.path(),
+        &mut app_package_content,
+    );
+
+    let sbom_path = dir.child("sbom.spdx.json");
+    let sbom = serde_json::json!({ "spdxVersion": "SPDX-2.3", "name": "app", "packages": [{}] });
+    std::fs::write(&sbom_path, sbom.to_string()).unwrap();
+    manifest.sbom = Some(ResourceBuilder::Fs(sbom_path));
+    let cargo_lock_path = dir.child("Cargo.lock");
+    std::fs::write(&cargo_lock_path, "version = 3").unwrap();
+    for module in &mut manifest.modules {
+        module.provenance = Some(provenance::ManifestProvenance {
+            repo: "https://github.com/input-output-hk/hermes".to_string(),
+            commit: "0123abc".to_string(),
+            toolchain: "rustc 1.78.0".to_string(),
+            cargo_lock: Some(ResourceBuilder::Fs(cargo_lock_path.clone())),
+        });
+    }
+
+    let package =
+        ApplicationPackage::build_from_manifest(&manifest, dir.path(), None, build_date).unwrap();
+    author_sign_package(&package);
+
+    assert_eq!(package.get_sbom().unwrap().unwrap().packages, 1);
+    for module_info in package.get_modules().unwrap() {
+        let provenance = module_info.get_provenance().unwrap().unwrap();
+        assert_eq!(provenance.commit, "0123abc");
+        assert_eq!(
+            provenance.cargo_lock_digest,
+            Some(Blake2b256::hash(b"version = 3").to_hex())
+        );
+    }
+
+    // The SBOM is covered by the author signature.
+    let new_sbom = serde_json::json!({ "spdxVersion": "SPDX-2.3", "name": "app", "packages": [] });
+    package
+        .0
+        .remove_file(ApplicationPackage::SBOM_FILE.into())
+        .unwrap();
+    package
+        .0
+        .copy_resource_file(
+            &BytesResource::new(
+                ApplicationPackage::SBOM_FILE.to_string(),
+                new_sbom.to_string().into_bytes(),
+            ),
+            ApplicationPackage::SBOM_FILE.into(),
+        )
+        .unwrap();
+    assert!(package.validate(true).is_ok());
+    assert!(package.validate(false).is_err());
+}
+
 #[test]
 fn corrupted_metadata_test() {
     let dir = TempDir::new().unwrap();
@@ -0,0 +1,243 @@
+//! Hermes application data archive, holding the data of an app so it can be moved to
+//! another node.
+//!
+//! The archive is a HDF5 package holding the VFS of the app and its `SQLite` database,
+//! signed by the author of the export. The signature payload names the app and holds the
+//! hashes of the archived files.
+//!
+//! Databases encrypted with the key of their node can not be read on another node, so
+//! they are not archived.
+
+use std::{io::Read, path::Path};
+
+use super::{
+    package::Package,
+    sign::{certificate::Certificate, keys::PrivateKey, signature::Signature},
+    MissingPackageFileError,
+};
+use crate::{
+    app::ApplicationName,
+    hdf5::resources::{BytesResource, FsResource},
+};
+
+/// Header a plain, not encrypted, `SQLite` database file starts with.
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Hermes application data archive.
+pub(crate) struct AppDataArchive(Package);
+
+impl AppDataArchive {
+    /// Application data archive signature file path.
+    const AUTHOR_COSE_FILE: &'static str = "author.cose";
+    /// Application data archive file extension.
+    pub(crate) const FILE_EXTENSION: &'static str = "hdata";
+    /// Application data archive `SQLite` database file path.
+    const SQLITE_FILE: &'static str = "sqlite.db";
+    /// Application data archive VFS file path.
+    const VFS_FILE: &'static str = "vfs.hfs";
+
+    /// Export the data of an app, its VFS and optionally its `SQLite` database, into a
+    /// new archive signed with the private key.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the database is encrypted, or the archive can not be written.
+    pub(crate) fn export<P: AsRef<Path>>(
+        path: P, app_name: &ApplicationName, vfs_file: &Path, sqlite_file: Option<&Path>,
+        private_key: &PrivateKey, certificate: &Certificate,
+    ) -> anyhow::Result<Self> {
+        if let Some(sqlite_file) = sqlite_file {
+            ensure_not_encrypted(std::fs::File::open(sqlite_file)?)?;
+        }
+        let package = Package::create(path)?;
+        package.copy_resource_file(&FsResource::new(vfs_file), Self::VFS_FILE.into())?;
+        if let Some(sqlite_file) = sqlite_file {
+            package.copy_resource_file(&FsResource::new(sqlite_file), Self::SQLITE_FILE.into())?;
+        }
+        let archive = Self(package);
+
+        let mut signature = Signature::new(archive.signature_payload(app_name)?);
+        signature.add_sign(private_key, certificate)?;
+        let signature_resource =
+            BytesResource::new(Self::AUTHOR_COSE_FILE.to_string(), signature.to_bytes()?);
+        archive
+            .0
+            .copy_resource_file(&signature_resource, Self::AUTHOR_COSE_FILE.into())?;
+
+        Ok(archive)
+    }
+
+    /// Open an existing archive.
+    pub(crate) fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        Ok(Self(Package::open(path)?))
+    }
+
+    /// Validate the archive, returning the name of the app its data belongs to.
+    ///
+    /// The archived files must match the signature payload, and unless `untrusted` the
+    /// signature is verified against the trusted certificates.
+    pub(crate) fn validate(&self, untrusted: bool) -> anyhow::Result<ApplicationName> {
+        let signature = self.get_author_signature()?;
+        let app_name: ApplicationName = signature
+            .payload()
+            .get("app")
+            .and_then(serde_json::Value::as_str)
+            .ok_or(anyhow::anyhow!("Missing app name in the signature payload"))?
+            .parse()?;
+
+        let expected_payload = self.signature_payload(&app_name)?;
+        anyhow::ensure!(
+            &expected_payload == signature.payload(),
+            "Application data archive signature payload mismatch.\nExpected: {expected_payload}\nGot: {}",
+            signature.payload()
+        );
+        if !untrusted {
+            signature.verify()?;
+        }
+        Ok(app_name)
+    }
+
+    /// Whether the archive holds a `SQLite` database.
+    pub(crate) fn has_sqlite(&self) -> bool {
+        self.0.get_file(Self::SQLITE_FILE.into()).is_ok()
+    }
+
+    /// Write the archived files, the VFS and the `SQLite` database if archived, to their
+    /// paths, overwriting existing files.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the archived database is encrypted, or the files can not be
+    /// written.
+    pub(crate) fn extract(&self, vfs_file: &Path, sqlite_file: &Path) -> anyhow::Result<()> {
+        if let Ok(sqlite) = self.0.get_file(Self::SQLITE_FILE.into()) {
+            ensure_not_encrypted(sqlite)?;
+        }
+        let mut vfs = self.0.get_file(Self::VFS_FILE.into())?;
+        std::io::copy(&mut vfs, &mut std::fs::File::create(vfs_file)?)?;
+        if let Ok(mut sqlite) = self.0.get_file(Self::SQLITE_FILE.into()) {
+            std::io::copy(&mut sqlite, &mut std::fs::File::create(sqlite_file)?)?;
+        }
+        Ok(())
+    }
+
+    /// Get the author signature of the archive.
+    fn get_author_signature(&self) -> anyhow::Result<Signature<serde_json::Value>> {
+        let file = self
+            .0
+            .get_file(Self::AUTHOR_COSE_FILE.into())
+            .map_err(|_| MissingPackageFileError(Self::AUTHOR_COSE_FILE.to_string()))?;
+        Signature::from_reader(file)
+    }
+
+    /// Build the signature payload of the archived data of the app.
+    fn signature_payload(&self, app_name: &ApplicationName) -> anyhow::Result<serde_json::Value> {
+        let vfs_hash = self
+            .0
+            .calculate_file_hash(Self::VFS_FILE.into())?
+            .ok_or(MissingPackageFileError(Self::VFS_FILE.to_string()))?;
+
+        let mut payload = serde_json::json!({
+            "app": app_name.0,
+            "vfs": vfs_hash.to_hex(),
+        });
+        if let Some(sqlite_hash) = self.0.calculate_file_hash(Self::SQLITE_FILE.into())? {
+            payload["sqlite"] = sqlite_hash.to_hex().into();
+        }
+        Ok(payload)
+    }
+}
+
+/// Fails if the `SQLite` database read from the reader is encrypted, i.e. not empty and
+/// without the header of a plain database.
+fn ensure_not_encrypted(reader: impl Read) -> anyhow::Result<()> {
+    let mut header = Vec::with_capacity(SQLITE_HEADER.len());
+    reader
+        .take(SQLITE_HEADER.len().try_into()?)
+        .read_to_end(&mut header)?;
+    anyhow::ensure!(
+        header.is_empty() || header == SQLITE_HEADER,
+        "The SQLite database is encrypted with the key of its node, it can not be moved to \
+         another node"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+    use crate::packaging::sign::{
+        certificate::{self, tests::certificate_str},
+        keys::tests::private_key_str,
+    };
+
+    #[test]
+    fn app_data_archive_test() {
+        let dir = TempDir::new().unwrap();
+        let vfs_file = dir.child("app.hfs");
+        std::fs::write(&vfs_file, b"vfs").unwrap();
+        let sqlite_file = dir.child("app.db");
+        let sqlite = [SQLITE_HEADER, b"tables"].concat();
+        std::fs::write(&sqlite_file, &sqlite).unwrap();
+
+        let private_key = PrivateKey::from_str(&private_key_str()).unwrap();
+        let certificate = Certificate::from_str(&certificate_str()).unwrap();
+        certificate::storage::add_certificate(certificate.clone()).unwrap();
+
+        let archive_file = dir.child("app.hdata");
+        let app_name = ApplicationName("app".to_string());
+        AppDataArchive::export(
+            &archive_file,
+            &app_name,
+            &vfs_file,
+            Some(&sqlite_file),
+            &private_key,
+            &certificate,
+        )
+        .unwrap();
+
+        let archive = AppDataArchive::from_file(&archive_file).unwrap();
+        assert_eq!(archive.validate(false).unwrap(), app_name);
+
+        let imported_vfs_file = dir.child("imported.hfs");
+        let imported_sqlite_file = dir.child("imported.db");
+        archive
+            .extract(&imported_vfs_file, &imported_sqlite_file)
+            .unwrap();
+        assert_eq!(std::fs::read(imported_vfs_file).unwrap(), b"vfs");
+        assert_eq!(std::fs::read(imported_sqlite_file).unwrap(), sqlite);
+
+        archive
+            .0
+            .remove_file(AppDataArchive::SQLITE_FILE.into())
+            .unwrap();
+        assert!(archive.validate(true).is_err());
+
+        // Archives naming files out of the Hermes home are refused.
+        let traversal_archive_file = dir.child("traversal.hdata");
+        let traversal = AppDataArchive::export(
+            &traversal_archive_file,
+            &ApplicationName("../app".to_string()),
+            &vfs_file,
+            None,
+            &private_key,
+            &certificate,
+        )
+        .unwrap();
+        assert!(traversal.validate(true).is_err());
+
+        // Encrypted databases are not exported.
+        std::fs::write(&sqlite_file, b"encrypted pages").unwrap();
+        assert!(AppDataArchive::export(
+            dir.child("encrypted.hdata"),
+            &app_name,
+            &vfs_file,
+            Some(&sqlite_file),
+            &private_key,
+            &certificate,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,132 @@
+//! Hermes application state attestation, a signed report of the index state of an app
+//! on a node.
+//!
+//! The nodes replicating an app hold the same data when their attestations report the
+//! same state, so the attestations let the operators prove the integrity of the data.
+
+use std::{collections::BTreeMap, io::Read};
+
+use serde::{Deserialize, Serialize};
+
+use super::sign::{certificate::Certificate, keys::PrivateKey, signature::Signature};
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::{
+        app_config::get_app_persistent_sqlite_db_cfg,
+        hermes::{cardano, sqlite},
+    },
+};
+
+/// Index state of an app on a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct StateReport {
+    /// Name of the app.
+    pub(crate) app: String,
+    /// When the state was reported, in seconds since the UNIX epoch.
+    pub(crate) taken_at: u64,
+    /// Number of rows of every table of the app database, keyed by table name.
+    pub(crate) tables: BTreeMap<String, u64>,
+    /// Latest slot indexed by the app on every Cardano network, keyed by network name.
+    pub(crate) indexed_slots: BTreeMap<String, u64>,
+    /// Blake2b hash hex of the canonical dump of every database of the app, keyed by
+    /// database name.
+    pub(crate) databases: BTreeMap<String, String>,
+}
+
+impl StateReport {
+    /// Report the current index state of the app.
+    ///
+    /// The databases are hashed from a dump of their content, so the hashes of nodes
+    /// holding the same data match, whatever the layout of their database files.
+    pub(crate) fn take(app_name: &ApplicationName) -> anyhow::Result<Self> {
+        let has_default_db = get_app_persistent_sqlite_db_cfg(app_name.clone())
+            .and_then(|config| config.db_file)
+            .is_some_and(|db_file| db_file.exists());
+        let tables = if has_default_db {
+            sqlite::table_row_counts(app_name)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            app: app_name.to_string(),
+            taken_at: chrono::Utc::now().timestamp().try_into()?,
+            tables,
+            indexed_slots: cardano::indexed_slots(app_name),
+            databases: sqlite::database_hashes(app_name)?,
+        })
+    }
+}
+
+/// Signed state report of an app.
+pub(crate) struct StateAttestation(Signature<serde_json::Value>);
+
+impl StateAttestation {
+    /// State attestation file extension.
+    pub(crate) const FILE_EXTENSION: &'static str = "attest.cose";
+
+    /// Sign the state report with the private key.
+    pub(crate) fn sign(
+        report: &StateReport, private_key: &PrivateKey, certificate: &Certificate,
+    ) -> anyhow::Result<Self> {
+        let mut signature = Signature::new(serde_json::to_value(report)?);
+        signature.add_sign(private_key, certificate)?;
+        Ok(Self(signature))
+    }
+
+    /// Create `StateAttestation` from reader.
+    pub(crate) fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        Ok(Self(Signature::from_reader(reader)?))
+    }
+
+    /// Convert `StateAttestation` object to COSE bytes.
+    pub(crate) fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        self.0.to_bytes()
+    }
+
+    /// Get the attested state report.
+    pub(crate) fn report(&self) -> anyhow::Result<StateReport> {
+        Ok(serde_json::from_value(self.0.payload().clone())?)
+    }
+
+    /// Verify the signatures of the attestation against the trusted certificates.
+    pub(crate) fn verify(&self) -> anyhow::Result<()> {
+        self.0.verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packaging::{
+        hash::Blake2b256,
+        sign::{
+            certificate::{self, tests::certificate_str},
+            keys::tests::private_key_str,
+        },
+    };
+
+    #[test]
+    fn state_attestation_test() {
+        let report = StateReport {
+            app: "app".to_string(),
+            taken_at: 1_700_000_000,
+            tables: BTreeMap::from([("blocks".to_string(), 3)]),
+            indexed_slots: BTreeMap::from([("preprod".to_string(), 49_075_522)]),
+            databases: BTreeMap::from([(
+                "(default)".to_string(),
+                Blake2b256::hash(b"dump").to_hex(),
+            )]),
+        };
+
+        let private_key = PrivateKey::from_str(&private_key_str()).unwrap();
+        let certificate = Certificate::from_str(&certificate_str()).unwrap();
+        certificate::storage::add_certificate(certificate.clone()).unwrap();
+
+        let attestation = StateAttestation::sign(&report, &private_key, &certificate).unwrap();
+        let bytes = attestation.to_bytes().unwrap();
+        let attestation = StateAttestation::from_reader(bytes.as_slice()).unwrap();
+        attestation.verify().unwrap();
+        assert_eq!(attestation.report().unwrap(), report);
+    }
+}
@@ -10,6 +10,11 @@ use chrono::{DateTime, Utc};
 
 use super::schema_validation::SchemaValidator;
 
+/// Toolchain a package is built with, i.e. the version of Hermes and of the compiler it
+/// was built with.
+pub(crate) const TOOLCHAIN: &str =
+    build_info::format!("hermes {} ({})", $.crate_info.version, $.compiler);
+
 /// Metadata object.
 pub(crate) struct Metadata<T> {
     /// metadata JSON object.
@@ -69,12 +74,22 @@ impl<T: MetadataSchema> Metadata<T> {
             .to_string())
     }
 
+    /// Get a property of the `Metadata` object.
+    pub(crate) fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.json.get(key)
+    }
+
     /// Set `build_date` property to the `Metadata` object.
     pub(crate) fn set_build_date(&mut self, date: DateTime<Utc>) {
         self.json
             .insert("build_date".to_string(), date.timestamp().into());
     }
 
+    /// Set `toolchain` property to the `Metadata` object.
+    pub(crate) fn set_toolchain(&mut self, toolchain: &str) {
+        self.json.insert("toolchain".to_string(), toolchain.into());
+    }
+
     /// Set `name` property to the `Metadata` object.
     pub(crate) fn set_name(&mut self, name: &str) {
         self.json.insert("name".to_string(), name.into());
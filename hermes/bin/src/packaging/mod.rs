@@ -1,11 +1,13 @@
 //! Hermes packaging.
 
 pub(crate) mod app;
+pub(crate) mod app_data;
+pub(crate) mod attestation;
 pub(crate) mod hash;
 pub(crate) mod metadata;
 pub(crate) mod module;
 pub(crate) mod package;
-mod schema_validation;
+pub(crate) mod schema_validation;
 pub(crate) mod sign;
 
 use std::{fmt::Display, path::Path};
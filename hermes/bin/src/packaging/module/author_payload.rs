@@ -18,6 +18,8 @@ pub(crate) struct SignaturePayload {
     settings: Option<SignaturePayloadSettings>,
     /// Hash of the share directory content.
     share: Option<Blake2b256>,
+    /// Hash of the env JSON file.
+    env: Option<Blake2b256>,
 }
 
 /// A `SignaturePayload` config object.
@@ -50,6 +52,8 @@ pub(crate) struct SignaturePayloadBuilder {
     settings_schema: Option<Blake2b256>,
     /// Hash of the share directory content.
     share: Option<Blake2b256>,
+    /// Hash of the env JSON file.
+    env: Option<Blake2b256>,
 }
 
 impl SignaturePayloadBuilder {
@@ -62,6 +66,7 @@ impl SignaturePayloadBuilder {
             config_schema: None,
             settings_schema: None,
             share: None,
+            env: None,
         }
     }
 
@@ -85,6 +90,11 @@ impl SignaturePayloadBuilder {
         self.share = Some(share);
     }
 
+    /// Set the env file hash.
+    pub(crate) fn with_env(&mut self, env: Blake2b256) {
+        self.env = Some(env);
+    }
+
     /// Create a new `SignaturePayload`.
     pub(crate) fn build(self) -> SignaturePayload {
         SignaturePayload {
@@ -100,6 +110,7 @@ impl SignaturePayloadBuilder {
                 .settings_schema
                 .map(|schema| SignaturePayloadSettings { schema }),
             share: self.share,
+            env: self.env,
         }
     }
 }
@@ -134,6 +145,9 @@ impl SignaturePayloadEncoding for SignaturePayload {
         if let Some(share) = &self.share {
             json.insert("share".to_string(), share.to_hex().into());
         }
+        if let Some(env) = &self.env {
+            json.insert("env".to_string(), env.to_hex().into());
+        }
 
         json.into()
     }
@@ -203,12 +217,19 @@ impl SignaturePayloadEncoding for SignaturePayload {
             .map(Blake2b256::from_hex)
             .transpose()?;
 
+        let env = json
+            .get("env")
+            .and_then(|val| val.as_str())
+            .map(Blake2b256::from_hex)
+            .transpose()?;
+
         Ok(SignaturePayload {
             metadata,
             component,
             config,
             settings,
             share,
+            env,
         })
     }
 }
@@ -245,6 +266,7 @@ mod tests {
             payload_builder.with_config_schema(hash.clone());
             payload_builder.with_settings_schema(hash.clone());
             payload_builder.with_share(hash.clone());
+            payload_builder.with_env(hash.clone());
             let payload = payload_builder.build();
 
             let json = payload.to_json();
@@ -261,6 +283,7 @@ mod tests {
                     "schema": hash.to_hex(),
                 },
                 "share": hash.to_hex(),
+                "env": hash.to_hex(),
             });
             assert_eq!(json, expected_json);
 
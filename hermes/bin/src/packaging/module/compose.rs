@@ -0,0 +1,60 @@
+//! WASM component composition.
+//!
+//! A module component can import interfaces which are exported by shared adapter
+//! components (e.g. common bindings), instead of embedding them. These imports are
+//! satisfied at packaging time by composing the component with its adapters, using
+//! `wasm-tools compose`, so the packaged module is a single self-contained component.
+
+use std::{io::Read, process::Command};
+
+use temp_dir::TempDir;
+
+use crate::hdf5::resources::{BytesResource, ResourceTrait};
+
+/// `wasm-tools` executable, overridable with the `HERMES_WASM_TOOLS` env var.
+const WASM_TOOLS: &str = "wasm-tools";
+
+/// Env var overriding the `wasm-tools` executable.
+const WASM_TOOLS_ENV: &str = "HERMES_WASM_TOOLS";
+
+/// Compose a component with the adapter components satisfying its imports.
+pub(crate) fn compose(
+    component: &impl ResourceTrait, adapters: &[impl ResourceTrait],
+) -> anyhow::Result<BytesResource> {
+    let dir = TempDir::new()?;
+
+    let component_path = dir.child("component.wasm");
+    std::fs::write(&component_path, read(component)?)?;
+
+    let mut cmd =
+        Command::new(std::env::var(WASM_TOOLS_ENV).unwrap_or_else(|_| WASM_TOOLS.to_string()));
+    cmd.arg("compose").arg(&component_path);
+    for (i, adapter) in adapters.iter().enumerate() {
+        let adapter_path = dir.child(format!("adapter-{i}.wasm"));
+        std::fs::write(&adapter_path, read(adapter)?)?;
+        cmd.arg("--definitions").arg(adapter_path);
+    }
+    let composed_path = dir.child("composed.wasm");
+    cmd.arg("--output").arg(&composed_path);
+
+    let output = cmd
+        .output()
+        .map_err(|err| anyhow::anyhow!("Failed to run `wasm-tools compose`: {err}"))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "`wasm-tools compose` failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+
+    Ok(BytesResource::new(
+        component.name()?,
+        std::fs::read(composed_path)?,
+    ))
+}
+
+/// Read all the content of a resource.
+fn read(resource: &impl ResourceTrait) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    resource.get_reader()?.read_to_end(&mut data)?;
+    Ok(data)
+}
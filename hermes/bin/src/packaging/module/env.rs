@@ -0,0 +1,73 @@
+//! WASM module package environment variables JSON
+
+use std::{collections::BTreeMap, io::Read};
+
+/// Prefix of the environment variable names reserved by Hermes.
+const RESERVED_PREFIX: &str = "HERMES_";
+
+/// Environment variables of a module, provided through `wasi:cli/environment`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Env(BTreeMap<String, String>);
+
+impl Env {
+    /// Create `Env` from environment variables, validating their names.
+    pub(crate) fn new(vars: BTreeMap<String, String>) -> anyhow::Result<Self> {
+        let invalid: Vec<_> = vars.keys().filter(|key| !is_allowed_key(key)).collect();
+        anyhow::ensure!(
+            invalid.is_empty(),
+            "Invalid environment variable names {invalid:?}, names must match \
+             `[A-Za-z_][A-Za-z0-9_]*` and must not start with `{RESERVED_PREFIX}`"
+        );
+        Ok(Self(vars))
+    }
+
+    /// Create `Env` from reader.
+    pub(crate) fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        Self::new(serde_json::from_reader(reader)?)
+    }
+
+    /// Convert `Env` object to json bytes
+    pub(crate) fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let bytes = serde_json::to_vec(&self.0)?;
+        Ok(bytes)
+    }
+
+    /// Environment variables as name and value pairs.
+    pub(crate) fn vars(&self) -> Vec<(String, String)> {
+        self.0
+            .iter()
+            .map(|(key, val)| (key.clone(), val.clone()))
+            .collect()
+    }
+}
+
+/// Check if an environment variable name is a POSIX name, which is not reserved.
+fn is_allowed_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !key.starts_with(RESERVED_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_keys_validation_test() {
+        let env =
+            Env::from_reader(r#"{"NETWORK": "preprod", "_db_path": "/tmp"}"#.as_bytes()).unwrap();
+        assert_eq!(env.vars(), vec![
+            ("NETWORK".to_string(), "preprod".to_string()),
+            ("_db_path".to_string(), "/tmp".to_string()),
+        ]);
+
+        assert!(Env::from_reader(r#"{"1ST": "x"}"#.as_bytes()).is_err());
+        assert!(Env::from_reader(r#"{"A-B": "x"}"#.as_bytes()).is_err());
+        assert!(Env::from_reader(r#"{"": "x"}"#.as_bytes()).is_err());
+        assert!(Env::from_reader(r#"{"HERMES_HOME": "x"}"#.as_bytes()).is_err());
+        assert!(Env::from_reader(r#"{"NETWORK": 1}"#.as_bytes()).is_err());
+    }
+}
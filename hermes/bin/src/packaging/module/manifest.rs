@@ -1,6 +1,6 @@
 //! WASM module package manifest.json struct.
 
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use super::super::{schema_validation::SchemaValidator, FileError};
 use crate::hdf5::resources::ResourceBuilder;
@@ -14,6 +14,10 @@ pub(crate) struct Manifest {
     pub(crate) metadata: ResourceBuilder,
     /// Path to the  WASM component file.
     pub(crate) component: ResourceBuilder,
+    /// Paths to the adapter WASM component files composed with the component.
+    pub(crate) compose: Vec<ResourceBuilder>,
+    /// WASM module environment variables.
+    pub(crate) env: BTreeMap<String, String>,
     /// WASM module config.
     pub(crate) config: Option<ManifestConfig>,
     /// WASM module settings.
@@ -74,6 +78,9 @@ impl Manifest {
             .ok_or_else(|| FileError::from_path(path, None))?;
         manifest.metadata.make_relative_to(dir_path);
         manifest.component.make_relative_to(dir_path);
+        for adapter in &mut manifest.compose {
+            adapter.make_relative_to(dir_path);
+        }
         if let Some(config) = manifest.config.as_mut() {
             if let Some(config_file) = config.file.as_mut() {
                 config_file.make_relative_to(dir_path);
@@ -95,6 +102,8 @@ impl Manifest {
 mod serde_def {
     //! Serde definition of the manifest objects.
 
+    use std::collections::BTreeMap;
+
     use serde::Deserialize;
 
     use crate::hdf5::resources::ResourceBuilder;
@@ -107,6 +116,10 @@ mod serde_def {
         metadata: ResourceBuilder,
         #[serde(default = "super::Manifest::default_component_path")]
         component: ResourceBuilder,
+        #[serde(default)]
+        compose: Vec<ResourceBuilder>,
+        #[serde(default)]
+        env: BTreeMap<String, String>,
         config: Option<ConfigSerde>,
         settings: Option<SettingsSerde>,
         share: Option<ResourceBuilder>,
@@ -129,6 +142,8 @@ mod serde_def {
                 name: def.name,
                 metadata: def.metadata,
                 component: def.component,
+                compose: def.compose,
+                env: def.env,
                 config: def.config.map(|def| {
                     super::ManifestConfig {
                         file: def.file,
@@ -162,6 +177,10 @@ mod tests {
                     "name": "module_name",
                     "metadata": "metadata.json",
                     "component": "module.wasm",
+                    "compose": ["shared.wasm"],
+                    "env": {
+                        "NETWORK": "preprod"
+                    },
                     "config": {
                         "file": "config.json",
                         "schema": "config.schema.json"
@@ -177,6 +196,8 @@ mod tests {
                 name: "module_name".to_string(),
                 metadata: ResourceBuilder::Fs(dir_path.join("metadata.json")),
                 component: ResourceBuilder::Fs(dir_path.join("module.wasm")),
+                compose: vec![ResourceBuilder::Fs(dir_path.join("shared.wasm"))],
+                env: BTreeMap::from([("NETWORK".to_string(), "preprod".to_string())]),
                 config: ManifestConfig {
                     file: Some(ResourceBuilder::Fs(dir_path.join("config.json"))),
                     schema: ResourceBuilder::Fs(dir_path.join("config.schema.json")),
@@ -211,6 +232,8 @@ mod tests {
                 name: "module".to_string(),
                 metadata: ResourceBuilder::Fs("/metadata.json".into()),
                 component: ResourceBuilder::Fs("/module.wasm".into()),
+                compose: Vec::new(),
+                env: BTreeMap::new(),
                 config: ManifestConfig {
                     file: Some(ResourceBuilder::Fs("/config.json".into())),
                     schema: ResourceBuilder::Fs("/config.schema.json".into()),
@@ -235,6 +258,8 @@ mod tests {
                 name: "module".to_string(),
                 metadata: ResourceBuilder::Fs(dir_path.join("metadata.json")),
                 component: ResourceBuilder::Fs(dir_path.join("module.wasm")),
+                compose: Vec::new(),
+                env: BTreeMap::new(),
                 config: None,
                 settings: None,
                 share: None,
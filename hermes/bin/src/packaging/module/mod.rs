@@ -1,8 +1,10 @@
 //! Hermes WASM module package.
 
 mod author_payload;
+mod compose;
 mod config;
 mod config_info;
+mod env;
 mod manifest;
 mod settings;
 #[cfg(test)]
@@ -12,6 +14,7 @@ pub(crate) use author_payload::{SignaturePayload, SignaturePayloadBuilder};
 use chrono::{DateTime, Utc};
 pub(crate) use config::{Config, ConfigSchema};
 pub(crate) use config_info::ConfigInfo;
+pub(crate) use env::Env;
 pub(crate) use manifest::{Manifest, ManifestConfig};
 pub(crate) use settings::SettingsSchema;
 
@@ -28,7 +31,7 @@ use super::{
 use crate::{
     errors::Errors,
     hdf5::{
-        resources::{bytes::BytesResource, ResourceTrait},
+        resources::{bytes::BytesResource, ResourceBuilder, ResourceTrait},
         Dir, File, Path,
     },
     wasm::module::Module,
@@ -51,6 +54,8 @@ impl ModulePackage {
     const CONFIG_FILE: &'static str = "config.json";
     /// Module package config schema file path.
     const CONFIG_SCHEMA_FILE: &'static str = "config.schema.json";
+    /// Module package environment variables file path.
+    const ENV_FILE: &'static str = "env.json";
     /// Module package file extension.
     pub(crate) const FILE_EXTENSION: &'static str = "hmod";
     /// Module package metadata file path.
@@ -108,6 +113,7 @@ impl ModulePackage {
             .map_or_else(errors.get_add_err_fn(), |_| ());
         self.get_settings_schema()
             .map_or_else(errors.get_add_err_fn(), |_| ());
+        self.get_env().map_or_else(errors.get_add_err_fn(), |_| ());
 
         if !untrusted {
             self.verify_sign().unwrap_or_else(errors.get_add_err_fn());
@@ -189,6 +195,9 @@ impl ModulePackage {
         if let Some(share_hash) = self.0.calculate_dir_hash(&Self::SHARE_DIR.into())? {
             signature_payload_builder.with_share(share_hash);
         }
+        if let Some(env_hash) = self.0.calculate_file_hash(Self::ENV_FILE.into())? {
+            signature_payload_builder.with_env(env_hash);
+        }
 
         Ok(signature_payload_builder.build())
     }
@@ -275,6 +284,17 @@ impl ModulePackage {
             .transpose()
     }
 
+    /// Get environment variables `File` object from package if present.
+    pub(super) fn get_env_file(&self) -> Option<File> {
+        self.0.get_file(Self::ENV_FILE.into()).ok()
+    }
+
+    /// Get `Env` object from package, empty if not present.
+    pub(crate) fn get_env(&self) -> anyhow::Result<Env> {
+        self.get_env_file()
+            .map_or_else(|| Ok(Env::default()), Env::from_reader)
+    }
+
     /// Get share dir from package if present.
     pub(super) fn get_share_dir(&self) -> Option<Dir> {
         self.0.get_dir(&Self::SHARE_DIR.into()).ok()
@@ -299,12 +319,30 @@ impl ModulePackage {
         )
         .unwrap_or_else(errors.get_add_err_fn());
 
-        validate_and_write_component(
-            &manifest.component.build(),
-            package,
-            Self::COMPONENT_FILE.into(),
-        )
-        .unwrap_or_else(errors.get_add_err_fn());
+        if manifest.compose.is_empty() {
+            validate_and_write_component(
+                &manifest.component.build(),
+                package,
+                Self::COMPONENT_FILE.into(),
+            )
+            .unwrap_or_else(errors.get_add_err_fn());
+        } else {
+            let adapters: Vec<_> = manifest
+                .compose
+                .iter()
+                .map(ResourceBuilder::build)
+                .collect();
+            compose::compose(&manifest.component.build(), &adapters)
+                .and_then(|component| {
+                    validate_and_write_component(&component, package, Self::COMPONENT_FILE.into())
+                })
+                .unwrap_or_else(errors.get_add_err_fn());
+        }
+
+        if !manifest.env.is_empty() {
+            validate_and_write_env(&manifest.env, package, Self::ENV_FILE.into())
+                .unwrap_or_else(errors.get_add_err_fn());
+        }
 
         if let Some(config) = &manifest.config {
             validate_and_write_config(
@@ -362,6 +400,16 @@ fn validate_and_write_component(
     Ok(())
 }
 
+/// Validate environment variables and write them to the package to the provided dir path.
+fn validate_and_write_env(
+    vars: &std::collections::BTreeMap<String, String>, dir: &Dir, path: Path,
+) -> anyhow::Result<()> {
+    let env = Env::new(vars.clone())?;
+    let resource = BytesResource::new(ModulePackage::ENV_FILE.to_string(), env.to_bytes()?);
+    dir.copy_resource_file(&resource, path)?;
+    Ok(())
+}
+
 /// Validate config schema and config file and write them to the package.
 fn validate_and_write_config(
     manifest: &ManifestConfig, dir: &Dir, config_schema_path: Path, config_file_path: Path,
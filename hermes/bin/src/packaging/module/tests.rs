@@ -147,6 +147,8 @@ pub(crate) fn prepare_module_package_dir(
         name: module_name,
         metadata: ResourceBuilder::Fs(metadata_path),
         component: ResourceBuilder::Fs(component_path),
+        compose: Vec::new(),
+        env: std::collections::BTreeMap::new(),
         config: manifest::ManifestConfig {
             file: Some(ResourceBuilder::Fs(config_path)),
             schema: ResourceBuilder::Fs(config_schema_path),
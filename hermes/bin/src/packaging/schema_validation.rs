@@ -2,7 +2,7 @@
 
 use std::io::Read;
 
-use jsonschema::{Draft, JSONSchema};
+use jsonschema::{error::ValidationErrorKind, Draft, JSONSchema};
 use serde::de::DeserializeOwned;
 
 use crate::errors::Errors;
@@ -16,7 +16,6 @@ pub(crate) struct SchemaValidator {
 
 impl SchemaValidator {
     /// Create a new json schema validator from reader.
-    #[allow(dead_code)]
     pub(crate) fn from_reader<R: Read>(reader: R) -> anyhow::Result<Self> {
         let schema = serde_json::from_reader(reader)?;
         Self::from_json(&schema)
@@ -51,6 +50,34 @@ impl SchemaValidator {
         Ok(())
     }
 
+    /// Validate JSON value against current schema, returning every error along with the
+    /// JSON pointer of the invalid value. Every unexpected property is reported on its
+    /// own, pointing to the property.
+    pub(crate) fn errors(&self, json: &serde_json::Value) -> Vec<(String, String)> {
+        let Err(errors) = self.schema.validate(json) else {
+            return Vec::new();
+        };
+        errors
+            .flat_map(|err| {
+                let pointer = err.instance_path.to_string();
+                match &err.kind {
+                    ValidationErrorKind::AdditionalProperties { unexpected } => {
+                        unexpected
+                            .iter()
+                            .map(|property| {
+                                (
+                                    format!("{pointer}/{}", escape_pointer_token(property)),
+                                    format!("Unknown field `{property}`"),
+                                )
+                            })
+                            .collect()
+                    },
+                    _ => vec![(pointer, err.to_string())],
+                }
+            })
+            .collect()
+    }
+
     /// Validate and deserialize JSON value from reader against current schema.
     pub(crate) fn deserialize_and_validate<R: Read, T: DeserializeOwned>(
         &self, reader: R,
@@ -61,3 +88,204 @@ impl SchemaValidator {
         Ok(val)
     }
 }
+
+/// Escape a JSON pointer reference token.
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Unescape a JSON pointer reference token.
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Locate the value a JSON pointer points to in a JSON text, as its 1-based line and
+/// column. An object member is located at its key. When the pointer can not be
+/// followed to its end, the deepest value found is located.
+pub(crate) fn locate(text: &str, pointer: &str) -> (usize, usize) {
+    let mut scanner = JsonScanner {
+        text: text.as_bytes(),
+        pos: 0,
+    };
+    scanner.skip_whitespace();
+    let mut location = scanner.pos;
+    for token in pointer.split('/').skip(1).map(unescape_pointer_token) {
+        match scanner.find(&token) {
+            Some((at, value)) => {
+                location = at;
+                scanner.pos = value;
+            },
+            None => break,
+        }
+    }
+    line_column(text, location)
+}
+
+/// 1-based line and column of a byte offset in a text.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = text.get(..offset).unwrap_or(text);
+    let line = before.matches('\n').count().saturating_add(1);
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        .saturating_add(1);
+    (line, column)
+}
+
+/// Scanner of a JSON text, tracking the byte offset of the values.
+struct JsonScanner<'a> {
+    /// JSON text.
+    text: &'a [u8],
+    /// Current byte offset.
+    pos: usize,
+}
+
+impl JsonScanner<'_> {
+    /// Current byte.
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    /// Skip the whitespaces.
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos = self.pos.saturating_add(1);
+        }
+    }
+
+    /// Skip the given byte, after the whitespaces.
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek() == Some(byte)).then(|| self.pos = self.pos.saturating_add(1))
+    }
+
+    /// Read the string starting at the current byte.
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.pos = self.pos.saturating_add(1);
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos = self.pos.saturating_add(2),
+                b'"' => break,
+                _ => self.pos = self.pos.saturating_add(1),
+            }
+        }
+        self.pos = self.pos.saturating_add(1);
+        serde_json::from_slice(self.text.get(start..self.pos)?).ok()
+    }
+
+    /// Skip the value starting at the current byte.
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        let mut depth = 0_usize;
+        loop {
+            match self.peek()? {
+                b',' | b'}' | b']' if depth == 0 => return Some(()),
+                b if b.is_ascii_whitespace() && depth == 0 => return Some(()),
+                b'"' => {
+                    self.string()?;
+                    if depth == 0 {
+                        return Some(());
+                    }
+                },
+                b'{' | b'[' => {
+                    depth = depth.saturating_add(1);
+                    self.pos = self.pos.saturating_add(1);
+                },
+                b'}' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    self.pos = self.pos.saturating_add(1);
+                    if depth == 0 {
+                        return Some(());
+                    }
+                },
+                _ => self.pos = self.pos.saturating_add(1),
+            }
+        }
+    }
+
+    /// Find the member or item `token` of the object or array starting at the current
+    /// byte, returning the offset it is located at and the offset of its value.
+    fn find(&mut self, token: &str) -> Option<(usize, usize)> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => {
+                self.pos = self.pos.saturating_add(1);
+                loop {
+                    self.skip_whitespace();
+                    let key_pos = self.pos;
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    self.skip_whitespace();
+                    if key == token {
+                        return Some((key_pos, self.pos));
+                    }
+                    self.skip_value()?;
+                    self.expect(b',')?;
+                }
+            },
+            b'[' => {
+                let index: usize = token.parse().ok()?;
+                self.pos = self.pos.saturating_add(1);
+                for _ in 0..index {
+                    self.skip_value()?;
+                    self.expect(b',')?;
+                }
+                self.skip_whitespace();
+                Some((self.pos, self.pos))
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_test() {
+        let text = r#"{
+    "name": "app",
+    "tags": ["a", {"b": [1, 2.5e3]}, true],
+    "mod/ules": [
+        {
+            "package": "a.hmod",
+            "extra": null
+        }
+    ]
+}"#;
+        assert_eq!(locate(text, ""), (1, 1));
+        assert_eq!(locate(text, "/name"), (2, 5));
+        assert_eq!(locate(text, "/tags/1/b/1"), (3, 29));
+        assert_eq!(locate(text, "/tags/2"), (3, 38));
+        assert_eq!(locate(text, "/mod~1ules/0/extra"), (7, 13));
+        // Not found, the deepest value found is located.
+        assert_eq!(locate(text, "/mod~1ules/0/missing"), (5, 9));
+        assert_eq!(locate(text, "/mod~1ules/3"), (4, 5));
+    }
+
+    #[test]
+    fn errors_test() {
+        let validator = SchemaValidator::from_json(&serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": { "name": { "type": "string" } }
+        }))
+        .unwrap();
+        assert!(validator
+            .errors(&serde_json::json!({ "name": "app" }))
+            .is_empty());
+        assert_eq!(
+            validator.errors(&serde_json::json!({ "name": "app", "a/b": 1 })),
+            vec![("/a~1b".to_string(), "Unknown field `a/b`".to_string())]
+        );
+        let errors = validator.errors(&serde_json::json!({ "name": 1 }));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors.first().map(|(pointer, _)| pointer.as_str()),
+            Some("/name")
+        );
+    }
+}
@@ -6,7 +6,10 @@ use once_cell::sync::OnceCell;
 use crate::{
     app::{Application, ApplicationName},
     event,
-    runtime_extensions::hermes::init,
+    runtime_extensions::{
+        self,
+        hermes::{cron, http_gateway, init},
+    },
 };
 
 /// Global Hermes reactor state
@@ -50,7 +53,25 @@ pub(crate) fn load_app(app: Application) -> anyhow::Result<()> {
     let app_name = app.name().clone();
     reactor.apps.insert(app_name.clone(), app);
 
-    init::emit_init_event(app_name)?;
+    // Load the stored crontab entries before the init event replaces them, and reschedule
+    // them after it, so the missed ticks are delivered to an initialized app.
+    let crontabs = cron::stored_crontabs(&app_name);
+    init::emit_init_event(app_name.clone())?;
+    http_gateway::emit_warmup_events(&app_name)?;
+    cron::restore_crontabs(&app_name, crontabs);
+    Ok(())
+}
+
+/// Unload Hermes application from the Hermes Reactor.
+/// Events queued for the application are dropped, and the runtime extensions release
+/// its state, e.g. its crontab entries, timers and open databases.
+pub(crate) fn unload_app(app_name: &ApplicationName) -> anyhow::Result<()> {
+    let reactor = REACTOR_STATE.get().ok_or(NotInitializedError)?;
+    reactor
+        .apps
+        .remove(app_name)
+        .ok_or(anyhow::anyhow!("Application {app_name} not found"))?;
+    runtime_extensions::unload_app(app_name);
     Ok(())
 }
 
@@ -2,11 +2,16 @@
 
 use std::sync::Arc;
 
-use crate::{app::ApplicationName, vfs::Vfs, wasm::module::ModuleId};
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::wasi::cli::{GuestOutput, Stream},
+    vfs::Vfs,
+    wasm::{module::ModuleId, profiler::MemoryUsage},
+};
 
 /// Hermes Runtime Context. This is passed to the WASM runtime.
 #[derive(Clone, Debug)]
-pub(crate) struct HermesRuntimeContext {
+pub struct HermesRuntimeContext {
     /// Hermes application name
     app_name: ApplicationName,
 
@@ -21,6 +26,15 @@ pub(crate) struct HermesRuntimeContext {
 
     /// App Virtual file system
     vfs: Arc<Vfs>,
+
+    /// Memory usage of the module instance, tracked by the memory profiler
+    memory_usage: MemoryUsage,
+
+    /// Output written by the module to its stdout
+    guest_stdout: GuestOutput,
+
+    /// Output written by the module to its stderr
+    guest_stderr: GuestOutput,
 }
 
 impl HermesRuntimeContext {
@@ -29,12 +43,17 @@ impl HermesRuntimeContext {
         app_name: ApplicationName, module_id: ModuleId, event_name: String, exc_counter: u32,
         vfs: Arc<Vfs>,
     ) -> Self {
+        let guest_stdout = GuestOutput::new(Stream::Stdout, app_name.clone(), module_id.clone());
+        let guest_stderr = GuestOutput::new(Stream::Stderr, app_name.clone(), module_id.clone());
         Self {
             app_name,
             module_id,
             event_name,
             exc_counter,
             vfs,
+            memory_usage: MemoryUsage::default(),
+            guest_stdout,
+            guest_stderr,
         }
     }
 
@@ -48,6 +67,18 @@ impl HermesRuntimeContext {
         &self.module_id
     }
 
+    /// Get the application name, for runtime extension plugins
+    #[allow(dead_code)]
+    pub fn app(&self) -> &str {
+        &self.app_name.0
+    }
+
+    /// Get the module id, for runtime extension plugins
+    #[allow(dead_code)]
+    pub fn module(&self) -> String {
+        self.module_id.to_string()
+    }
+
     /// Get the event name
     #[allow(dead_code)]
     pub(crate) fn event_name(&self) -> &str {
@@ -65,4 +96,24 @@ impl HermesRuntimeContext {
     pub(crate) fn vfs(&self) -> &Vfs {
         self.vfs.as_ref()
     }
+
+    /// Get the memory usage of the module instance
+    pub(crate) fn memory_usage(&self) -> &MemoryUsage {
+        &self.memory_usage
+    }
+
+    /// Get the memory usage of the module instance, to track its growth
+    pub(crate) fn memory_usage_mut(&mut self) -> &mut MemoryUsage {
+        &mut self.memory_usage
+    }
+
+    /// Get the output written by the module to its stdout
+    pub(crate) fn guest_stdout(&self) -> &GuestOutput {
+        &self.guest_stdout
+    }
+
+    /// Get the output written by the module to its stderr
+    pub(crate) fn guest_stderr(&self) -> &GuestOutput {
+        &self.guest_stderr
+    }
 }
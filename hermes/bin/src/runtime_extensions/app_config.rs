@@ -1,6 +1,9 @@
 //! Hermes application configuration for modules.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use dashmap::DashSet;
+use once_cell::sync::{Lazy, OnceCell};
 
 use crate::app::ApplicationName;
 
@@ -11,28 +14,115 @@ use crate::app::ApplicationName;
 
 const MAX_CONFIG_DB_SIZE: u32 = 1_048_576;
 
+/// Apps whose persistent `SQLite` database is encrypted.
+static ENCRYPTED_SQLITE_DBS: Lazy<DashSet<ApplicationName>> = Lazy::new(DashSet::new);
+
+/// Directory the persistent `SQLite` databases are stored in, set by
+/// `set_sqlite_db_dir`, the working directory if not set.
+static SQLITE_DB_DIR: OnceCell<PathBuf> = OnceCell::new();
+
 /// Represents config object for `SQLite`
 pub(crate) struct SqliteConfig {
     /// Path to the `SQLite` database file, not set if it's in-memory database.
     pub(crate) db_file: Option<PathBuf>,
     /// Maximum size of the `SQLite` database in bytes.
     pub(crate) max_db_size: u32,
+    /// Whether the database is encrypted, with a key derived from the node keystore.
+    pub(crate) encrypted: bool,
+}
+
+/// Sets whether the persistent `SQLite` database of the app is encrypted.
+pub(crate) fn set_app_sqlite_db_encrypted(app_name: ApplicationName, encrypted: bool) {
+    if encrypted {
+        ENCRYPTED_SQLITE_DBS.insert(app_name);
+    } else {
+        ENCRYPTED_SQLITE_DBS.remove(&app_name);
+    }
+}
+
+/// Sets the directory the persistent `SQLite` databases are stored in.
+///
+/// ## Errors
+///
+/// Returns an error if the directory is already set.
+pub(crate) fn set_sqlite_db_dir(dir: &Path) -> anyhow::Result<()> {
+    SQLITE_DB_DIR
+        .set(dir.to_path_buf())
+        .map_err(|_| anyhow::anyhow!("SQLite database directory already set"))
+}
+
+/// Path of a persistent `SQLite` database file, or directory, in the database directory.
+fn sqlite_db_path(path: impl AsRef<Path>) -> PathBuf {
+    match SQLITE_DB_DIR.get() {
+        Some(dir) => dir.join(path),
+        None => path.as_ref().to_path_buf(),
+    }
+}
+
+/// Gets the path of the persistent `SQLite` database file used by the app alone, when it
+/// does not share a database file with the other apps.
+pub(crate) fn get_app_own_sqlite_db_file(app_name: &ApplicationName) -> Option<PathBuf> {
+    let ApplicationName(name) = app_name;
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(sqlite_db_path(format!("hermes_datastore-{name}.db")))
 }
 
 /// Gets `SQLite` config for persistent datastore
 pub(crate) fn get_app_persistent_sqlite_db_cfg(app_name: ApplicationName) -> Option<SqliteConfig> {
+    let encrypted = ENCRYPTED_SQLITE_DBS.contains(&app_name);
+    // Apps encrypting their database with their own key can not share a database file.
+    let db_file = if encrypted {
+        get_app_own_sqlite_db_file(&app_name)?
+    } else if app_name.0.is_empty() {
+        return None;
+    } else {
+        sqlite_db_path("hermes_datastore.db")
+    };
+
+    Some(SqliteConfig {
+        db_file: Some(db_file),
+        max_db_size: MAX_CONFIG_DB_SIZE,
+        encrypted,
+    })
+}
+
+/// Gets `SQLite` config for a named persistent datastore of the app.
+///
+/// Every named database has its own file, and size limit, in a directory of the app.
+pub(crate) fn get_app_named_sqlite_db_cfg(
+    app_name: ApplicationName, db_name: &str,
+) -> Option<SqliteConfig> {
+    let encrypted = ENCRYPTED_SQLITE_DBS.contains(&app_name);
     let ApplicationName(name) = app_name;
 
-    if name.is_empty() {
+    if name.is_empty() || db_name.is_empty() {
         return None;
     }
 
     Some(SqliteConfig {
-        db_file: Some(PathBuf::from("hermes_datastore.db")),
+        db_file: Some(
+            get_app_named_sqlite_db_dir(ApplicationName(name))?.join(format!("{db_name}.db")),
+        ),
         max_db_size: MAX_CONFIG_DB_SIZE,
+        encrypted,
     })
 }
 
+/// Gets the directory of the named persistent datastores of the app.
+pub(crate) fn get_app_named_sqlite_db_dir(app_name: ApplicationName) -> Option<PathBuf> {
+    let ApplicationName(name) = app_name;
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(sqlite_db_path(format!("hermes_datastore-{name}")))
+}
+
 /// Gets `SQLite` config for in-memory datastore
 pub(crate) fn get_app_in_memory_sqlite_db_cfg(app_name: ApplicationName) -> Option<SqliteConfig> {
     let ApplicationName(name) = app_name;
@@ -44,5 +134,6 @@ pub(crate) fn get_app_in_memory_sqlite_db_cfg(app_name: ApplicationName) -> Opti
     Some(SqliteConfig {
         db_file: None,
         max_db_size: MAX_CONFIG_DB_SIZE,
+        encrypted: false,
     })
 }
@@ -1,7 +1,45 @@
 //! Binary host implementation for WASM runtime.
 
+use super::state::get_state;
 use crate::{
-    runtime_context::HermesRuntimeContext, runtime_extensions::bindings::hermes::binary::api::Host,
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::bindings::hermes::binary::api::{Bstr, Buffer, Host, HostBuffer},
 };
 
+impl HostBuffer for HermesRuntimeContext {
+    /// Size of the buffer in bytes.
+    fn size(&mut self, resource: wasmtime::component::Resource<Buffer>) -> wasmtime::Result<u64> {
+        let mut app_state = get_state().get_app_state(self.app_name())?;
+        let data = app_state.get_object(&resource)?;
+        Ok(u64::try_from(data.len())?)
+    }
+
+    /// Read bytes from the buffer.
+    ///
+    /// **Parameters**
+    ///
+    /// - `offset` : Position of the first byte to read.
+    /// - `len` : Maximum number of bytes to read.
+    fn read(
+        &mut self, resource: wasmtime::component::Resource<Buffer>, offset: u64, len: u64,
+    ) -> wasmtime::Result<Bstr> {
+        let mut app_state = get_state().get_app_state(self.app_name())?;
+        let data = app_state.get_object(&resource)?;
+
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(data.len());
+        let end = start
+            .saturating_add(usize::try_from(len).unwrap_or(usize::MAX))
+            .min(data.len());
+        Ok(data.get(start..end).map(<[u8]>::to_vec).unwrap_or_default())
+    }
+
+    fn drop(&mut self, res: wasmtime::component::Resource<Buffer>) -> wasmtime::Result<()> {
+        let app_state = get_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(res)?;
+        Ok(())
+    }
+}
+
 impl Host for HermesRuntimeContext {}
@@ -1,6 +1,19 @@
 //! Binary runtime extension implementation.
 
+use crate::{app::ApplicationName, runtime_extensions::bindings::hermes::binary::api::Buffer};
+
 mod host;
+mod state;
 
 /// Advise Runtime Extensions of a new context
-pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
+pub(crate) fn new_context(ctx: &crate::runtime_context::HermesRuntimeContext) {
+    state::get_state().add_app(ctx.app_name().clone());
+}
+
+/// Move data into a new host buffer owned by the app.
+pub(crate) fn new_buffer(
+    app_name: &ApplicationName, data: Vec<u8>,
+) -> anyhow::Result<wasmtime::component::Resource<Buffer>> {
+    let app_state = state::get_state().get_app_state(app_name)?;
+    Ok(app_state.create_resource(data))
+}
@@ -0,0 +1,18 @@
+//! Binary state
+
+use once_cell::sync::Lazy;
+
+use crate::runtime_extensions::{
+    bindings::hermes::binary::api::Buffer, resource_manager::ApplicationResourceStorage,
+};
+
+/// Map of app name to buffer resource holder
+pub(super) type State = ApplicationResourceStorage<Buffer, Vec<u8>>;
+
+/// Global state to hold the buffer resources.
+static BINARY_STATE: Lazy<State> = Lazy::new(ApplicationResourceStorage::new);
+
+/// Get the binary state.
+pub(super) fn get_state() -> &'static State {
+    &BINARY_STATE
+}
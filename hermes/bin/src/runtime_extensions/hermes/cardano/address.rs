@@ -0,0 +1,111 @@
+//! Cardano address utilities.
+//!
+//! Addresses are handled as their raw bytes, as found in transactions, and only
+//! converted to text at the edges.
+
+use pallas::ledger::addresses::{Address, ByronAddress, Network, StakeAddress};
+
+use crate::runtime_extensions::bindings::hermes::cardano::api::{
+    AddressError, CardanoBlockchainId,
+};
+
+/// Parses a bech32 Shelley era address or stake address, or a base58 Byron address,
+/// into its raw bytes.
+pub(super) fn parse(address: &str) -> Result<Vec<u8>, AddressError> {
+    Address::from_bech32(address)
+        .or_else(|_| ByronAddress::from_base58(address).map(Address::Byron))
+        .map(|address| address.to_vec())
+        .map_err(|_| AddressError::InvalidAddress)
+}
+
+/// Formats the raw bytes of an address in bech32, or in base58 for a Byron address.
+pub(super) fn format(address: &[u8]) -> Result<String, AddressError> {
+    match decode(address)? {
+        Address::Byron(address) => Ok(address.to_base58()),
+        address => {
+            address
+                .to_bech32()
+                .map_err(|_| AddressError::InvalidAddress)
+        },
+    }
+}
+
+/// Raw bytes of the stake address the address delegates to, if it has a stake key or
+/// script delegation part.
+pub(super) fn stake_address(address: &[u8]) -> Result<Option<Vec<u8>>, AddressError> {
+    let Address::Shelley(address) = decode(address)? else {
+        return Ok(None);
+    };
+    Ok(StakeAddress::try_from(address)
+        .ok()
+        .map(|stake_address| Address::Stake(stake_address).to_vec()))
+}
+
+/// Key or script hash of the payment part of the address, if it has one.
+pub(super) fn payment_credential(address: &[u8]) -> Result<Option<Vec<u8>>, AddressError> {
+    let Address::Shelley(address) = decode(address)? else {
+        return Ok(None);
+    };
+    Ok(Some(address.payment().as_hash().to_vec()))
+}
+
+/// Checks the network tag of the address is the one of the blockchain.
+///
+/// Byron mainnet addresses carry no network tag.
+pub(super) fn check_network(
+    chain_id: CardanoBlockchainId, address: &[u8],
+) -> Result<(), AddressError> {
+    let network = decode(address)?.network().unwrap_or(Network::Mainnet);
+    let expected = match chain_id {
+        CardanoBlockchainId::Mainnet => Network::Mainnet,
+        CardanoBlockchainId::Preprod
+        | CardanoBlockchainId::Preview
+        | CardanoBlockchainId::LocalTestBlockchain => Network::Testnet,
+    };
+    if network == expected {
+        Ok(())
+    } else {
+        Err(AddressError::NetworkMismatch)
+    }
+}
+
+/// Decodes the raw bytes of an address.
+fn decode(address: &[u8]) -> Result<Address, AddressError> {
+    Address::from_bytes(address).map_err(|_| AddressError::InvalidAddress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CIP-19 mainnet address with a payment key hash and a stake key hash.
+    const BASE_ADDRESS: &str = "addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgse35a3x";
+
+    /// CIP-19 mainnet stake address of the stake key hash of `BASE_ADDRESS`.
+    const STAKE_ADDRESS: &str = "stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw";
+
+    #[test]
+    fn address_test() {
+        let address = parse(BASE_ADDRESS).unwrap();
+        assert_eq!(format(&address).unwrap(), BASE_ADDRESS);
+
+        let stake_address = stake_address(&address).unwrap().unwrap();
+        assert_eq!(format(&stake_address).unwrap(), STAKE_ADDRESS);
+        assert_eq!(
+            payment_credential(&address).unwrap().unwrap(),
+            hex::decode("9493315cd92eb5d8c4304e67b7e16ae36d61d34502694657811a2c8e").unwrap()
+        );
+        assert_eq!(payment_credential(&stake_address).unwrap(), None);
+
+        assert!(check_network(CardanoBlockchainId::Mainnet, &address).is_ok());
+        assert!(matches!(
+            check_network(CardanoBlockchainId::Preprod, &address),
+            Err(AddressError::NetworkMismatch)
+        ));
+
+        assert!(matches!(
+            parse("addr1invalid"),
+            Err(AddressError::InvalidAddress)
+        ));
+    }
+}
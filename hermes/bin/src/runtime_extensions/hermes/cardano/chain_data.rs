@@ -0,0 +1,250 @@
+//! Cardano chain data directory and its pruning.
+//!
+//! The immutable chain data of every network is stored in the `<network>/immutable`
+//! directory of the chain data directory, in the format of the Cardano node immutable
+//! database, e.g. as restored from a Mithril snapshot. Followers read the blocks stored
+//! there instead of fetching them from the network.
+//!
+//! The immutable database is split in chunk files, each holding the blocks of a fixed
+//! range of slots. When a retention is set, the chunks older than the last epochs are
+//! periodically removed, oldest first. Chunks which a running follower has not read past
+//! yet are kept, and followers read the blocks below the first stored one from the
+//! network.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use once_cell::sync::OnceCell;
+use tracing::{error, info};
+
+use super::STATE;
+
+/// Name of the directory of a network immutable chain data.
+const IMMUTABLE_DIR: &str = "immutable";
+
+/// Extensions of the files making up a chunk of the immutable chain data.
+const CHUNK_FILE_EXTENSIONS: [&str; 3] = ["chunk", "primary", "secondary"];
+
+/// Interval between the pruning of the chain data.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Networks the chain data can be stored for.
+const NETWORKS: [cardano_chain_follower::Network; 4] = [
+    cardano_chain_follower::Network::Mainnet,
+    cardano_chain_follower::Network::Preprod,
+    cardano_chain_follower::Network::Preview,
+    cardano_chain_follower::Network::Testnet,
+];
+
+/// Chain data configuration, set by `configure`.
+static CONFIG: OnceCell<ChainDataConfig> = OnceCell::new();
+
+/// Chain data configuration.
+struct ChainDataConfig {
+    /// Directory the chain data of every network is stored in.
+    dir: PathBuf,
+    /// Number of epochs of immutable chain data kept, all of them when `0`.
+    keep_epochs: u64,
+}
+
+/// Sets the directory the chain data is stored in, and the number of epochs of it kept,
+/// `0` to keep it all.
+///
+/// ## Errors
+///
+/// Returns an error if the directory can not be created or if the chain data is already
+/// configured.
+pub(crate) fn configure(dir: &Path, keep_epochs: u64) -> anyhow::Result<()> {
+    fs::create_dir_all(dir)?;
+    CONFIG
+        .set(ChainDataConfig {
+            dir: dir.to_path_buf(),
+            keep_epochs,
+        })
+        .map_err(|_| anyhow::anyhow!("Cardano chain data is already configured"))?;
+
+    if keep_epochs > 0 {
+        std::thread::Builder::new()
+            .name("cardano-chain-data-pruner".to_string())
+            .spawn(|| {
+                loop {
+                    prune_all();
+                    std::thread::sleep(PRUNE_INTERVAL);
+                }
+            })?;
+    }
+    Ok(())
+}
+
+/// Directory of the immutable chain data of the network, if it holds any.
+pub(super) fn immutable_dir(network: cardano_chain_follower::Network) -> Option<PathBuf> {
+    let dir = CONFIG
+        .get()?
+        .dir
+        .join(network.to_string())
+        .join(IMMUTABLE_DIR);
+    dir.is_dir().then_some(dir)
+}
+
+/// Prunes the immutable chain data of every network.
+fn prune_all() {
+    let Some(config) = CONFIG.get() else {
+        return;
+    };
+    for network in NETWORKS {
+        let Some(dir) = immutable_dir(network) else {
+            continue;
+        };
+        let Some((chunk_slots, chunks_per_epoch)) = chunk_layout(network) else {
+            continue;
+        };
+        let keep_chunks = chunks_per_epoch.saturating_mul(config.keep_epochs);
+        // The chunk a follower is reading, and the ones after it, are still to be read.
+        let read_chunk = followers_slot(network)
+            .and_then(|slot| slot.checked_div(chunk_slots))
+            .unwrap_or(u64::MAX);
+        match prune(&dir, keep_chunks, read_chunk) {
+            Ok(0) => (),
+            Ok(pruned) => info!(%network, pruned, "Pruned Cardano immutable chain data chunks"),
+            Err(err) => error!(%network, "Failed to prune Cardano immutable chain data: {err}"),
+        }
+    }
+}
+
+/// Number of slots of an immutable chunk of the network, and number of chunks of an
+/// epoch.
+///
+/// Chunks span the slots of a Byron epoch, so a Shelley epoch is split over many of them.
+fn chunk_layout(network: cardano_chain_follower::Network) -> Option<(u64, u64)> {
+    let genesis = cardano_chain_follower::network_genesis_values(&network)?;
+    // The Byron epoch length is in seconds.
+    let chunk_slots =
+        u64::from(genesis.byron_epoch_length).checked_div(u64::from(genesis.byron_slot_length))?;
+    let chunks_per_epoch = u64::from(genesis.shelley_epoch_length).checked_div(chunk_slots)?;
+    Some((chunk_slots, chunks_per_epoch))
+}
+
+/// Slot of the least advanced running follower of the network, if any is running.
+fn followers_slot(network: cardano_chain_follower::Network) -> Option<u64> {
+    STATE
+        .subscriptions
+        .iter()
+        .filter(|entry| entry.key().2 == network && entry.value().follower_handle.is_some())
+        .map(|entry| entry.value().current_slot)
+        .min()
+}
+
+/// Removes the files of the chunks of the immutable chain data directory older than the
+/// last `keep_chunks` chunks, and than the `read_chunk` chunk, oldest first. Returns the
+/// number of chunks removed.
+fn prune(dir: &Path, keep_chunks: u64, read_chunk: u64) -> anyhow::Result<u64> {
+    let mut chunks = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "chunk")
+        {
+            if let Some(number) = chunk_number(&path) {
+                chunks.push(number);
+            }
+        }
+    }
+
+    let Some(last) = chunks.iter().max().copied() else {
+        return Ok(0);
+    };
+    let first_kept = last
+        .saturating_sub(keep_chunks.saturating_sub(1))
+        .min(read_chunk);
+
+    // Followers read the blocks below the first stored one from the network, so the
+    // oldest chunks are removed first.
+    chunks.sort_unstable();
+    let mut pruned = 0;
+    for number in chunks.into_iter().filter(|number| *number < first_kept) {
+        for extension in CHUNK_FILE_EXTENSIONS {
+            let path = dir.join(format!("{number:05}.{extension}"));
+            match fs::remove_file(&path) {
+                Ok(()) => (),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+        pruned += 1;
+    }
+    Ok(pruned)
+}
+
+/// Number of the chunk stored in the file.
+fn chunk_number(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the files of the chunks to the directory.
+    fn write_chunks(dir: &Path, chunks: std::ops::Range<u64>) -> anyhow::Result<()> {
+        for number in chunks {
+            for extension in CHUNK_FILE_EXTENSIONS {
+                fs::write(dir.join(format!("{number:05}.{extension}")), [])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorted names of the files of the directory.
+    fn file_names(dir: &Path) -> anyhow::Result<Vec<std::ffi::OsString>> {
+        let mut files = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<Result<Vec<_>, _>>()?;
+        files.sort();
+        Ok(files)
+    }
+
+    #[test]
+    fn prune_keeps_the_last_chunks() -> anyhow::Result<()> {
+        let dir = temp_dir::TempDir::new()?;
+        write_chunks(dir.path(), 0..5)?;
+        fs::write(dir.path().join("clean"), [])?;
+
+        assert_eq!(prune(dir.path(), 2, u64::MAX)?, 3);
+        assert_eq!(prune(dir.path(), 2, u64::MAX)?, 0);
+
+        assert_eq!(file_names(dir.path())?, [
+            "00003.chunk",
+            "00003.primary",
+            "00003.secondary",
+            "00004.chunk",
+            "00004.primary",
+            "00004.secondary",
+            "clean",
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn prune_keeps_the_chunks_followers_read() -> anyhow::Result<()> {
+        let dir = temp_dir::TempDir::new()?;
+        write_chunks(dir.path(), 0..5)?;
+
+        assert_eq!(prune(dir.path(), 1, 2)?, 2);
+        assert_eq!(file_names(dir.path())?, [
+            "00002.chunk",
+            "00002.primary",
+            "00002.secondary",
+            "00003.chunk",
+            "00003.primary",
+            "00003.secondary",
+            "00004.chunk",
+            "00004.primary",
+            "00004.secondary",
+        ]);
+        Ok(())
+    }
+}
@@ -1,16 +1,21 @@
 //! A Chain Follower task is responsible for managing a Cardano Chain Follower
 //! that is controlled by the Cardano Runtime Extension.
 
-use std::time::Duration;
+use std::{collections::VecDeque, sync::Arc, time::Duration};
 
 use anyhow::Context;
 use tracing::{error, instrument, trace, warn};
 
-use super::{ModuleStateKey, Result, STATE};
+use super::{
+    sync, wallclock_slot, ModuleStateKey, Result, STABILITY_WINDOW_SLOTS, STATE,
+    SYNC_TOLERANCE_SLOTS,
+};
 use crate::{
     app::ApplicationName,
     event::{HermesEvent, TargetApp, TargetModule},
-    runtime_extensions::bindings::hermes::cardano::api::{BlockSrc, CardanoBlockchainId},
+    runtime_extensions::bindings::hermes::cardano::api::{
+        BlockSrc, CardanoBlockchainId, CardanoTxn, TxnFilter,
+    },
     wasm::module::ModuleId,
 };
 
@@ -18,10 +23,87 @@ use crate::{
 struct EventSubscriptions {
     /// Whether the module is subscribed to block events.
     blocks: bool,
+    /// Maximum number of blocks delivered in a single event during backfill.
+    block_batch_size: u32,
     /// Whether the module is subscribed to rollback events.
     rollbacks: bool,
     /// Whether the module is subscribed to transaction events.
     txns: bool,
+    /// Filter of the transactions sent as transaction events, all of them if not set.
+    txn_filter: Option<Arc<TxnFilter>>,
+    /// Whether the module is subscribed to immutable window events.
+    immutable_window: bool,
+}
+
+/// How long a partial batch of blocks waits for more blocks before it is delivered.
+const BLOCK_BATCH_FLUSH_DELAY: Duration = Duration::from_millis(500);
+
+/// Blocks waiting to be delivered to a module as a single batch.
+type PendingBlocks = Vec<cardano_chain_follower::MultiEraBlockData>;
+
+/// Minimum number of slots spanned by an immutable window before it is announced.
+const IMMUTABLE_WINDOW_MIN_SLOTS: u64 = 3600;
+
+/// Slot and hash of a block.
+type BlockPoint = (u64, Vec<u8>);
+
+/// Slot and number of a block.
+type BlockPosition = (u64, u64);
+
+/// Points of the blocks followed for a module, tracked until they become immutable.
+#[derive(Default)]
+struct ImmutableBlocks {
+    /// Points of the followed blocks which are not immutable yet, in chain order.
+    mutable: VecDeque<BlockPoint>,
+    /// First and last points of the blocks which became immutable and were not
+    /// announced yet.
+    window: Option<(BlockPoint, BlockPoint)>,
+}
+
+impl ImmutableBlocks {
+    /// Adds a followed block, returning the immutable window to announce, if any.
+    ///
+    /// Blocks at or before `immutable_slot` can no longer be rolled back. A window is
+    /// returned once it spans at least `IMMUTABLE_WINDOW_MIN_SLOTS`.
+    fn push(
+        &mut self, point: BlockPoint, immutable_slot: Option<u64>,
+    ) -> Option<(BlockPoint, BlockPoint)> {
+        self.mutable.push_back(point);
+        let immutable_slot = immutable_slot?;
+
+        while self
+            .mutable
+            .front()
+            .is_some_and(|(slot, _)| *slot <= immutable_slot)
+        {
+            let Some(point) = self.mutable.pop_front() else {
+                break;
+            };
+            match &mut self.window {
+                Some((_, last)) => *last = point,
+                None => self.window = Some((point.clone(), point)),
+            }
+        }
+
+        let (first, last) = self.window.as_ref()?;
+        if last.0.saturating_sub(first.0) < IMMUTABLE_WINDOW_MIN_SLOTS {
+            return None;
+        }
+        self.window.take()
+    }
+
+    /// Forgets the blocks after `slot`, which were rolled back.
+    fn rolled_back(&mut self, slot: u64) {
+        while self.mutable.back().is_some_and(|(s, _)| *s > slot) {
+            self.mutable.pop_back();
+        }
+    }
+
+    /// Forgets all the blocks.
+    fn clear(&mut self) {
+        self.mutable.clear();
+        self.window = None;
+    }
 }
 
 /// Chain follower executor commands.
@@ -120,6 +202,8 @@ async fn executor(
     let module_state_key = (app_name, module_id, network);
 
     let mut stopped = false;
+    let mut pending_blocks = PendingBlocks::new();
+    let mut immutable_blocks = ImmutableBlocks::default();
 
     'exec_loop: loop {
         tokio::select! {
@@ -128,7 +212,27 @@ async fn executor(
                     break 'exec_loop;
                 };
 
+                // Blocks read before the read pointer is moved are not delivered.
+                if matches!(cmd, Command::SetReadPointer(..)) {
+                    pending_blocks.clear();
+                    immutable_blocks.clear();
+                }
+
                 stopped = process_command(cmd, &follower).await;
+
+                if stopped {
+                    if let Err(e) = flush_pending_blocks(&module_state_key, chain_id, &mut pending_blocks) {
+                        error!(error = ?e, "Failed to send pending blocks");
+                        break 'exec_loop;
+                    }
+                }
+            }
+
+            () = tokio::time::sleep(BLOCK_BATCH_FLUSH_DELAY), if !pending_blocks.is_empty() => {
+                if let Err(e) = flush_pending_blocks(&module_state_key, chain_id, &mut pending_blocks) {
+                    error!(error = ?e, "Failed to send pending blocks");
+                    break 'exec_loop;
+                }
             }
 
             result = follower.next(), if !stopped => {
@@ -138,9 +242,9 @@ async fn executor(
                             break 'exec_loop;
                         };
 
-                        match process_chain_update(chain_update, &module_state_key, chain_id, &event_subscriptions) {
-                            Ok(current_slot) => {
-                                if update_current_slot(&module_state_key, current_slot).is_err() {
+                        match process_chain_update(chain_update, &module_state_key, chain_id, &event_subscriptions, &mut pending_blocks, &mut immutable_blocks) {
+                            Ok(current_position) => {
+                                if update_current_position(&module_state_key, current_position).is_err() {
                                     break 'exec_loop;
                                 }
                             }
@@ -209,24 +313,109 @@ async fn process_command(cmd: Command, follower: &cardano_chain_follower::Follow
 fn process_chain_update(
     chain_update: cardano_chain_follower::ChainUpdate, module_state_key: &ModuleStateKey,
     chain_id: CardanoBlockchainId, event_subscriptions: &EventSubscriptions,
-) -> anyhow::Result<u64> {
+    pending_blocks: &mut PendingBlocks, immutable_blocks: &mut ImmutableBlocks,
+) -> anyhow::Result<BlockPosition> {
     match chain_update {
         cardano_chain_follower::ChainUpdate::Block(block_data) => {
-            process_block_chain_update(module_state_key, chain_id, block_data, event_subscriptions)
-                .context("Processing block chain update")
+            let decoded_block_data = block_data.decode().context("Decode block")?;
+            sync::block_received(module_state_key.2, decoded_block_data.slot());
+
+            let immutable_window = if event_subscriptions.immutable_window {
+                let point = (
+                    decoded_block_data.slot(),
+                    decoded_block_data.hash().to_vec(),
+                );
+                let immutable_slot = wallclock_slot(module_state_key.2)
+                    .map(|tip_slot| tip_slot.saturating_sub(STABILITY_WINDOW_SLOTS));
+                immutable_blocks.push(point, immutable_slot)
+            } else {
+                immutable_blocks.clear();
+                None
+            };
+
+            let position = if event_subscriptions.blocks && event_subscriptions.block_batch_size > 1
+            {
+                process_batched_block_chain_update(
+                    module_state_key,
+                    chain_id,
+                    block_data,
+                    event_subscriptions,
+                    pending_blocks,
+                )
+                .context("Processing batched block chain update")?
+            } else {
+                flush_pending_blocks(module_state_key, chain_id, pending_blocks)
+                    .context("Sending pending blocks")?;
+                process_block_chain_update(
+                    module_state_key,
+                    chain_id,
+                    block_data,
+                    event_subscriptions,
+                )
+                .context("Processing block chain update")?
+            };
+
+            if let Some((from_slot, to_slot)) = immutable_window {
+                // The blocks of the window are delivered before it is announced.
+                flush_pending_blocks(module_state_key, chain_id, pending_blocks)
+                    .context("Sending pending blocks")?;
+                build_and_send_immutable_window_event(
+                    module_state_key,
+                    chain_id,
+                    from_slot,
+                    to_slot,
+                )
+                .context("Sending Cardano immutable window event to Event Queue")?;
+            }
+
+            Ok(position)
         },
         cardano_chain_follower::ChainUpdate::Rollback(block_data) => {
-            process_rollback_chain_update(
+            // The blocks received before the rollback are delivered before it.
+            flush_pending_blocks(module_state_key, chain_id, pending_blocks)
+                .context("Sending pending blocks")?;
+            let position = process_rollback_chain_update(
                 module_state_key,
                 chain_id,
                 &block_data,
                 event_subscriptions,
             )
-            .context("Processing rollback chain update")
+            .context("Processing rollback chain update")?;
+            immutable_blocks.rolled_back(position.0);
+
+            Ok(position)
         },
     }
 }
 
+/// Sends the blocks to the given module as block events, each with its transaction
+/// events if the module is subscribed to them, returning the number of blocks sent.
+pub(super) fn send_blocks(
+    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId,
+    blocks: Vec<cardano_chain_follower::MultiEraBlockData>,
+) -> anyhow::Result<u64> {
+    let (txns, txn_filter) = get_event_subscriptions(module_state_key)
+        .map_or((false, None), |event_subscriptions| {
+            (event_subscriptions.txns, event_subscriptions.txn_filter)
+        });
+    let event_subscriptions = EventSubscriptions {
+        blocks: true,
+        block_batch_size: 0,
+        rollbacks: false,
+        txns,
+        txn_filter,
+        immutable_window: false,
+    };
+
+    let mut block_count: u64 = 0;
+    for block_data in blocks {
+        process_block_chain_update(module_state_key, chain_id, block_data, &event_subscriptions)?;
+        block_count = block_count.saturating_add(1);
+    }
+
+    Ok(block_count)
+}
+
 /// Processes a block chain update.
 ///
 /// This means decoding the block data, building and sending the event to the
@@ -235,7 +424,7 @@ fn process_block_chain_update(
     module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId,
     block_data: cardano_chain_follower::MultiEraBlockData,
     event_subscriptions: &EventSubscriptions,
-) -> anyhow::Result<u64> {
+) -> anyhow::Result<BlockPosition> {
     let decoded_block_data = block_data.decode().context("Decode block")?;
 
     let block_number = decoded_block_data.number();
@@ -245,8 +434,14 @@ fn process_block_chain_update(
         let txs = decoded_block_data.txs();
         let tx_count = txs.len();
 
-        build_and_send_txns_event(module_state_key, chain_id, slot, txs)
-            .context("Sending Cardano block transaction events to Event Queue")?;
+        build_and_send_txns_event(
+            module_state_key,
+            chain_id,
+            slot,
+            txs,
+            event_subscriptions.txn_filter.as_deref(),
+        )
+        .context("Sending Cardano block transaction events to Event Queue")?;
 
         trace!(
             block_number,
@@ -262,7 +457,81 @@ fn process_block_chain_update(
         trace!(block_number, "Generated Cardano block event");
     }
 
-    Ok(slot)
+    Ok((slot, block_number))
+}
+
+/// Processes a block chain update for a module receiving blocks in batches.
+///
+/// The block is added to the pending batch, which is delivered once it is full, or
+/// straight away when the follower is close to the tip of the chain.
+fn process_batched_block_chain_update(
+    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId,
+    block_data: cardano_chain_follower::MultiEraBlockData,
+    event_subscriptions: &EventSubscriptions, pending_blocks: &mut PendingBlocks,
+) -> anyhow::Result<BlockPosition> {
+    let decoded_block_data = block_data.decode().context("Decode block")?;
+    let position = (decoded_block_data.slot(), decoded_block_data.number());
+    let slot = position.0;
+    pending_blocks.push(block_data);
+
+    let batch_full = pending_blocks.len()
+        >= usize::try_from(event_subscriptions.block_batch_size).unwrap_or(usize::MAX);
+    let backfilling = wallclock_slot(module_state_key.2)
+        .is_some_and(|tip_slot| tip_slot.saturating_sub(slot) > SYNC_TOLERANCE_SLOTS);
+    if batch_full || !backfilling {
+        flush_pending_blocks(module_state_key, chain_id, pending_blocks)?;
+    }
+
+    Ok(position)
+}
+
+/// Sends the pending blocks to the given module as a single
+/// [`super::event::OnCardanoBlocksEvent`], followed by the transaction events of the
+/// blocks if the module is subscribed to them.
+fn flush_pending_blocks(
+    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId,
+    pending_blocks: &mut PendingBlocks,
+) -> anyhow::Result<()> {
+    if pending_blocks.is_empty() {
+        return Ok(());
+    }
+    let event_subscriptions = get_event_subscriptions(module_state_key)?;
+
+    let mut txns = Vec::new();
+    let mut blocks = Vec::with_capacity(pending_blocks.len());
+    for block_data in pending_blocks.drain(..) {
+        if event_subscriptions.txns {
+            let decoded_block_data = block_data.decode().context("Decode block")?;
+            let slot = decoded_block_data.slot();
+            let block_txns = matching_txns(
+                decoded_block_data.txs(),
+                event_subscriptions.txn_filter.as_deref(),
+            );
+            txns.push((slot, block_txns));
+        }
+        blocks.push(block_data.into_raw_data());
+    }
+
+    let block_count = blocks.len();
+    let on_blocks_event = super::event::OnCardanoBlocksEvent {
+        blockchain: chain_id,
+        blocks,
+        source: BlockSrc::NODE,
+    };
+    crate::event::queue::send(HermesEvent::new(
+        on_blocks_event,
+        TargetApp::List(vec![module_state_key.0.clone()]),
+        TargetModule::List(vec![module_state_key.1.clone()]),
+    ))
+    .context("Sending Cardano blocks event to Event Queue")?;
+    trace!(block_count, "Generated Cardano blocks event");
+
+    for (slot, block_txns) in txns {
+        send_txn_events(module_state_key, chain_id, slot, block_txns)
+            .context("Sending Cardano block transaction events to Event Queue")?;
+    }
+
+    Ok(())
 }
 
 /// Processes a rollback chain update.
@@ -273,22 +542,26 @@ fn process_rollback_chain_update(
     module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId,
     block_data: &cardano_chain_follower::MultiEraBlockData,
     event_subscriptions: &EventSubscriptions,
-) -> anyhow::Result<u64> {
+) -> anyhow::Result<BlockPosition> {
     let decoded_block_data = block_data.decode().context("Decode rollback block")?;
 
     let slot = decoded_block_data.slot();
+    let block_number = decoded_block_data.number();
 
     if event_subscriptions.rollbacks {
-        build_and_send_rollback_event(module_state_key, chain_id, slot)
+        // Number of blocks received after the intersection, which are rolled back.
+        let depth = current_block_number(module_state_key).map_or(0, |tip_block_number| {
+            tip_block_number.saturating_sub(block_number)
+        });
+        let hash = decoded_block_data.hash().to_vec();
+
+        build_and_send_rollback_event(module_state_key, chain_id, (slot, hash), depth)
             .context("Sending Cardano rollback event to Event Queue")?;
 
-        trace!(
-            block_number = decoded_block_data.number(),
-            "Generated Cardano rollback event"
-        );
+        trace!(block_number, depth, "Generated Cardano rollback event");
     }
 
-    Ok(slot)
+    Ok((slot, block_number))
 }
 
 /// Builds a [`super::event::OnCardanoBlockEvent`] from the block data and
@@ -314,17 +587,43 @@ fn build_and_send_block_event(
 }
 
 /// Builds [`super::event::OnCardanoTxnEvent`] for every transaction on the block data
-/// and sends them to the given module through the Event Queue.
+/// matching the filter and sends them to the given module through the Event Queue.
 fn build_and_send_txns_event(
     module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId, slot: u64,
-    txs: Vec<pallas::ledger::traverse::MultiEraTx>,
+    txs: Vec<pallas::ledger::traverse::MultiEraTx>, txn_filter: Option<&TxnFilter>,
 ) -> anyhow::Result<()> {
-    for (tx, index) in txs.into_iter().zip(0u32..) {
+    send_txn_events(
+        module_state_key,
+        chain_id,
+        slot,
+        matching_txns(txs, txn_filter),
+    )
+}
+
+/// Encodes the transactions of a block matching the filter, along with their index in
+/// the block.
+fn matching_txns(
+    txs: Vec<pallas::ledger::traverse::MultiEraTx>, txn_filter: Option<&TxnFilter>,
+) -> Vec<(u32, CardanoTxn)> {
+    txs.into_iter()
+        .zip(0u32..)
+        .filter(|(tx, _)| txn_filter.map_or(true, |filter| super::txn_filter::matches(filter, tx)))
+        .map(|(tx, index)| (index, tx.encode()))
+        .collect()
+}
+
+/// Sends a [`super::event::OnCardanoTxnEvent`] for every encoded transaction of the
+/// block at `slot` to the given module through the Event Queue.
+fn send_txn_events(
+    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId, slot: u64,
+    txns: Vec<(u32, CardanoTxn)>,
+) -> anyhow::Result<()> {
+    for (index, txn) in txns {
         let on_txn_event = super::event::OnCardanoTxnEvent {
             blockchain: chain_id,
             slot,
             txn_index: index,
-            txn: tx.encode(),
+            txn,
         };
 
         // Stop at the first error.
@@ -341,11 +640,15 @@ fn build_and_send_txns_event(
 /// Builds a [`super::event::OnCardanoRollback`] from the block data and
 /// sends it to the given module through the Event Queue.
 fn build_and_send_rollback_event(
-    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId, slot: u64,
+    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId, intersection: BlockPoint,
+    depth: u64,
 ) -> anyhow::Result<()> {
+    let (slot, hash) = intersection;
     let on_rollback_event = super::event::OnCardanoRollback {
         blockchain: chain_id,
         slot,
+        hash,
+        depth,
     };
 
     crate::event::queue::send(HermesEvent::new(
@@ -355,6 +658,31 @@ fn build_and_send_rollback_event(
     ))
 }
 
+/// Builds a [`super::event::OnCardanoImmutableWindow`] and sends it to the given module
+/// through the Event Queue.
+fn build_and_send_immutable_window_event(
+    module_state_key: &ModuleStateKey, chain_id: CardanoBlockchainId, from_slot: BlockPoint,
+    to_slot: BlockPoint,
+) -> anyhow::Result<()> {
+    trace!(
+        from_slot = from_slot.0,
+        to_slot = to_slot.0,
+        "Generated Cardano immutable window event"
+    );
+
+    let on_immutable_window_event = super::event::OnCardanoImmutableWindow {
+        blockchain: chain_id,
+        from_slot,
+        to_slot,
+    };
+
+    crate::event::queue::send(HermesEvent::new(
+        on_immutable_window_event,
+        TargetApp::List(vec![module_state_key.0.clone()]),
+        TargetModule::List(vec![module_state_key.1.clone()]),
+    ))
+}
+
 /// Gets the event subscription flags for a given module.
 fn get_event_subscriptions(
     module_state_key: &ModuleStateKey,
@@ -366,19 +694,64 @@ fn get_event_subscriptions(
 
     Ok(EventSubscriptions {
         blocks: sub_state.subscribed_to_blocks,
+        block_batch_size: sub_state.block_batch_size,
         rollbacks: sub_state.subscribed_to_rollbacks,
         txns: sub_state.subscribed_to_txns,
+        txn_filter: sub_state.txn_filter.clone(),
+        immutable_window: sub_state.subscribed_to_immutable_window,
     })
 }
 
-/// Updates the module's state with the current slot the follower is at.
-fn update_current_slot(module_state_key: &ModuleStateKey, current_slot: u64) -> anyhow::Result<()> {
+/// Updates the module's state with the current slot and block number the follower is
+/// at.
+fn update_current_position(
+    module_state_key: &ModuleStateKey, current_position: BlockPosition,
+) -> anyhow::Result<()> {
     let mut sub_state = STATE
         .subscriptions
         .get_mut(module_state_key)
         .ok_or(anyhow::anyhow!("Module subscription not found"))?;
 
+    let (current_slot, current_block_number) = current_position;
     sub_state.current_slot = current_slot;
+    sub_state.current_block_number = Some(current_block_number);
 
     Ok(())
 }
+
+/// Gets the number of the block the follower of a given module is at, if it received any.
+fn current_block_number(module_state_key: &ModuleStateKey) -> Option<u64> {
+    STATE
+        .subscriptions
+        .get(module_state_key)
+        .and_then(|sub_state| sub_state.current_block_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point of a block at `slot`.
+    fn point(slot: u64) -> BlockPoint {
+        (slot, slot.to_be_bytes().to_vec())
+    }
+
+    #[test]
+    fn immutable_window_test() {
+        let mut blocks = ImmutableBlocks::default();
+        assert!(blocks.push(point(100), None).is_none());
+        assert!(blocks.push(point(200), Some(150)).is_none());
+
+        // Rolled back blocks never become immutable.
+        assert!(blocks.push(point(5000), None).is_none());
+        blocks.rolled_back(4000);
+        assert_eq!(
+            blocks.push(point(4500), Some(4600)),
+            Some((point(100), point(4500)))
+        );
+
+        assert!(blocks.push(point(9000), Some(8000)).is_none());
+        assert!(blocks.push(point(9100), Some(9050)).is_none());
+        assert_eq!(blocks.window, Some((point(9000), point(9000))));
+    }
+}
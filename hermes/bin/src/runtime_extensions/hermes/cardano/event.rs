@@ -3,7 +3,7 @@
 use crate::{
     event::HermesEventPayload,
     runtime_extensions::bindings::hermes::cardano::api::{
-        BlockSrc, CardanoBlock, CardanoBlockchainId, CardanoTxn,
+        BlockSrc, CardanoBlock, CardanoBlockchainId, CardanoTxn, Slot,
     },
 };
 
@@ -31,6 +31,35 @@ impl HermesEventPayload for OnCardanoBlockEvent {
     }
 }
 
+/// On Cardano blocks event, a batch of blocks delivered during backfill.
+pub(super) struct OnCardanoBlocksEvent {
+    /// The blockchain id the blocks originated from.
+    pub(super) blockchain: CardanoBlockchainId,
+    /// The raw CBOR data of the blocks, in chain order.
+    pub(super) blocks: Vec<CardanoBlock>,
+    /// Source information about where the blocks came from, and if we are at tip or not.
+    pub(super) source: BlockSrc,
+}
+
+impl HermesEventPayload for OnCardanoBlocksEvent {
+    fn event_name(&self) -> &str {
+        "on-cardano-blocks"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        module
+            .instance
+            .hermes_cardano_event_on_blocks()
+            .call_on_cardano_blocks(
+                &mut module.store,
+                self.blockchain,
+                &self.blocks,
+                self.source,
+            )?;
+        Ok(())
+    }
+}
+
 /// On Cardano txn event
 pub(super) struct OnCardanoTxnEvent {
     /// The blockchain id the block originated from.
@@ -68,8 +97,12 @@ impl HermesEventPayload for OnCardanoTxnEvent {
 pub(super) struct OnCardanoRollback {
     /// The blockchain id the block originated from.
     pub(super) blockchain: CardanoBlockchainId,
-    /// The slot the transaction is in.
+    /// The slot of the intersection block the chain is rolled back to.
     pub(super) slot: u64,
+    /// The hash of the intersection block the chain is rolled back to.
+    pub(super) hash: Vec<u8>,
+    /// The number of blocks rolled back.
+    pub(super) depth: u64,
 }
 
 impl HermesEventPayload for OnCardanoRollback {
@@ -81,7 +114,89 @@ impl HermesEventPayload for OnCardanoRollback {
         module
             .instance
             .hermes_cardano_event_on_rollback()
-            .call_on_cardano_rollback(&mut module.store, self.blockchain, self.slot)?;
+            .call_on_cardano_rollback(
+                &mut module.store,
+                self.blockchain,
+                self.slot,
+                &self.hash,
+                self.depth,
+            )?;
+        Ok(())
+    }
+}
+
+/// On Cardano immutable window event
+pub(super) struct OnCardanoImmutableWindow {
+    /// The blockchain id the blocks originated from.
+    pub(super) blockchain: CardanoBlockchainId,
+    /// The slot and hash of the first block of the window.
+    pub(super) from_slot: (u64, Vec<u8>),
+    /// The slot and hash of the last block of the window.
+    pub(super) to_slot: (u64, Vec<u8>),
+}
+
+impl HermesEventPayload for OnCardanoImmutableWindow {
+    fn event_name(&self) -> &str {
+        "on-cardano-immutable-window"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        module
+            .instance
+            .hermes_cardano_event_on_immutable_window()
+            .call_on_cardano_immutable_window(
+                &mut module.store,
+                self.blockchain,
+                (self.from_slot.0, self.from_slot.1.as_slice()),
+                (self.to_slot.0, self.to_slot.1.as_slice()),
+            )?;
+        Ok(())
+    }
+}
+
+/// On Cardano reindex event
+pub(super) struct OnCardanoReindex {
+    /// The blockchain id the data was indexed from.
+    pub(super) blockchain: CardanoBlockchainId,
+    /// The slot the data must be indexed again from.
+    pub(super) from_slot: u64,
+}
+
+impl HermesEventPayload for OnCardanoReindex {
+    fn event_name(&self) -> &str {
+        "on-cardano-reindex"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        let res = module
+            .instance
+            .hermes_cardano_event_on_reindex()
+            .call_on_cardano_reindex(&mut module.store, self.blockchain, self.from_slot)?;
+
+        let follow_from = match res {
+            None => return Ok(()),
+            Some(Slot::Genesis) => cardano_chain_follower::Point::Origin,
+            Some(Slot::Point((slot, hash))) => cardano_chain_follower::Point::Specific(slot, hash),
+            Some(Slot::Tip | Slot::Continue) => {
+                anyhow::bail!("Module returned an invalid checkpoint to reindex from")
+            },
+        };
+
+        let app_name = module.store.data().app_name().clone();
+        let module_id = module.store.data().module_id().clone();
+        tracing::info!(
+            app_name = %app_name,
+            module_id = %module_id,
+            from_slot = self.from_slot,
+            checkpoint_slot = follow_from.slot_or_default(),
+            "Reindexing Cardano blockchain data"
+        );
+        super::subscribe(
+            self.blockchain,
+            app_name,
+            module_id,
+            super::SubscriptionType::Blocks(follow_from.into()),
+        )?;
         Ok(())
     }
 }
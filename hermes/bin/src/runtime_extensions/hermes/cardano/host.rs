@@ -1,10 +1,18 @@
 //!  Cardano Blockchain host implementation for WASM runtime.
 
 use crate::{
+    app::ApplicationName,
+    bandwidth::{self, Direction},
     runtime_context::HermesRuntimeContext,
-    runtime_extensions::bindings::hermes::cardano::api::{
-        CardanoBlock, CardanoBlockchainId, CardanoTxn, FetchError, Host, Slot, TxnError,
-        UnsubscribeOptions,
+    runtime_extensions::{
+        bindings::hermes::{
+            binary::api::Buffer,
+            cardano::api::{
+                CardanoBlock, CardanoBlockchainId, CardanoTxn, FetchError, Host, Slot, SyncStatus,
+                TxnError, TxnFilter, UnsubscribeOptions,
+            },
+        },
+        hermes::{binary::new_buffer, error::HermesError},
     },
 };
 
@@ -19,7 +27,7 @@ impl Host for HermesRuntimeContext {
     /// **Returns**
     ///
     /// - `ok(u64)` : The slot we are synching from now.
-    /// - `error(fetch-error)` : If an error occurred.
+    /// - `error(hermes-error)` : If an error occurred, with a `fetch-error` code.
     ///
     /// **Notes**
     ///
@@ -38,7 +46,7 @@ impl Host for HermesRuntimeContext {
     /// unsubscribed.
     fn subscribe_blocks(
         &mut self, net: CardanoBlockchainId, whence: Slot,
-    ) -> wasmtime::Result<Result<u64, FetchError>> {
+    ) -> wasmtime::Result<Result<u64, HermesError>> {
         let sub_type = match whence {
             Slot::Genesis => {
                 super::SubscriptionType::Blocks(cardano_chain_follower::Point::Origin.into())
@@ -61,10 +69,41 @@ impl Host for HermesRuntimeContext {
 
         match res {
             Ok(slot) => Ok(Ok(slot)),
-            Err(_) => Ok(Err(FetchError::InvalidSlot)),
+            Err(_) => Ok(Err(FetchError::InvalidSlot.into())),
         }
     }
 
+    /// Subscribe to the Blockchain block data, delivered in batches.
+    ///
+    /// **Parameters**
+    ///
+    /// - `net` : The blockchain network to fetch block from, and subscribe to.
+    /// - `whence`: Where to start fetching blocks from.
+    /// - `max-blocks`: The maximum number of blocks delivered in a single event.
+    ///
+    /// **Returns**
+    ///
+    /// - `ok(u64)` : The slot we are synching from now.
+    /// - `error(hermes-error)` : If an error occurred, with a `fetch-error` code.
+    ///
+    /// **Notes**
+    ///
+    /// Same as `subscribe-blocks`, but while the blockchain is being backfilled, blocks
+    /// are delivered as `on-cardano-blocks` events of up to `max-blocks` blocks instead
+    /// of one `on-cardano-block` event per block.
+    fn subscribe_blocks_batched(
+        &mut self, net: CardanoBlockchainId, whence: Slot, max_blocks: u32,
+    ) -> wasmtime::Result<Result<u64, HermesError>> {
+        super::subscribe(
+            net,
+            self.app_name().clone(),
+            self.module_id().clone(),
+            super::SubscriptionType::BlockBatchSize(max_blocks),
+        )?;
+
+        self.subscribe_blocks(net, whence)
+    }
+
     /// Unsubscribe from the blockchain events listed.
     ///
     /// **Parameters**
@@ -98,12 +137,20 @@ impl Host for HermesRuntimeContext {
     /// **Parameters**
     ///
     /// - `net` : The blockchain network to subscribe to txn events from.
-    fn subscribe_txn(&mut self, net: CardanoBlockchainId) -> wasmtime::Result<()> {
+    /// - `filter` : The transactions to send events for, all of them if `none`.
+    ///
+    /// **Notes**
+    ///
+    /// Only the transactions matching the filter are sent. Subscribing again replaces
+    /// the filter.
+    fn subscribe_txn(
+        &mut self, net: CardanoBlockchainId, filter: Option<TxnFilter>,
+    ) -> wasmtime::Result<()> {
         super::subscribe(
             net,
             self.app_name().clone(),
             self.module_id().clone(),
-            super::SubscriptionType::Transactions,
+            super::SubscriptionType::Transactions(filter),
         )?;
 
         Ok(())
@@ -133,6 +180,63 @@ impl Host for HermesRuntimeContext {
         Ok(())
     }
 
+    /// Subscribe to immutable window events, does not alter the blockchain sync in
+    /// anyway.
+    ///
+    /// **Parameters**
+    ///
+    /// - `net` : The blockchain network to subscribe to immutable window events from.
+    fn subscribe_immutable_window(&mut self, net: CardanoBlockchainId) -> wasmtime::Result<()> {
+        super::subscribe(
+            net,
+            self.app_name().clone(),
+            self.module_id().clone(),
+            super::SubscriptionType::ImmutableWindow,
+        )?;
+
+        Ok(())
+    }
+
+    /// Deliver the blocks of a range of the requested blockchain again.
+    ///
+    /// **Parameters**
+    ///
+    /// - `net`   : The blockchain network to read the blocks from.
+    /// - `first` : The first block to deliver, `genesis` or a `point`.
+    /// - `last`  : The last block to deliver, a `point` or the `tip`.
+    ///
+    /// **Returns**
+    ///
+    /// - `ok(u64)` : The number of blocks delivered.
+    /// - `error(hermes-error)` : If an error occurred, with a `fetch-error` code.
+    fn redeliver_blocks(
+        &mut self, net: CardanoBlockchainId, first: Slot, last: Slot,
+    ) -> wasmtime::Result<Result<u64, HermesError>> {
+        let from = match first {
+            Slot::Genesis => cardano_chain_follower::Point::Origin,
+            Slot::Point((slot, hash)) => cardano_chain_follower::Point::Specific(slot, hash),
+            Slot::Tip | Slot::Continue => return Ok(Err(FetchError::InvalidSlot.into())),
+        };
+        let to = match last {
+            Slot::Point((slot, hash)) => cardano_chain_follower::Point::Specific(slot, hash).into(),
+            Slot::Tip => cardano_chain_follower::PointOrTip::Tip,
+            Slot::Genesis | Slot::Continue => return Ok(Err(FetchError::InvalidSlot.into())),
+        };
+
+        let res = super::redeliver_blocks(
+            net,
+            self.app_name().clone(),
+            self.module_id().clone(),
+            from,
+            to,
+        );
+
+        match res {
+            Ok(block_count) => Ok(Ok(block_count)),
+            Err(_) => Ok(Err(FetchError::InvalidSlot.into())),
+        }
+    }
+
     /// Fetch a block from the requested blockchain at the requested slot.
     ///
     /// **Parameters**
@@ -143,7 +247,8 @@ impl Host for HermesRuntimeContext {
     /// **Returns**
     ///
     /// - `cardano-block` : The block requested.
-    /// - `fetch-error` : An error if the block can not be fetched.
+    /// - `hermes-error` : An error with a `fetch-error` code, if the block can not be
+    ///   fetched.
     ///
     /// **Notes**
     ///
@@ -155,17 +260,43 @@ impl Host for HermesRuntimeContext {
     /// to automated block fetch.
     fn fetch_block(
         &mut self, net: CardanoBlockchainId, whence: Slot,
-    ) -> wasmtime::Result<Result<CardanoBlock, FetchError>> {
-        let at = match whence {
-            Slot::Genesis => cardano_chain_follower::Point::Origin.into(),
-            Slot::Point((slot, hash)) => cardano_chain_follower::Point::Specific(slot, hash).into(),
-            Slot::Tip => cardano_chain_follower::PointOrTip::Tip,
-            Slot::Continue => todo!(),
-        };
+    ) -> wasmtime::Result<Result<CardanoBlock, HermesError>> {
+        Ok(fetch_raw_block(self.app_name(), net, whence).map_err(HermesError::from))
+    }
+
+    /// Get the sync status of the followers of a blockchain.
+    ///
+    /// **Parameters**
+    ///
+    /// - `net` : The blockchain network to get the sync status of.
+    ///
+    /// **Returns**
+    ///
+    /// - `sync-status` : The sync status of the blockchain followers, which the module
+    ///   does not need to be subscribed to.
+    fn get_sync_status(&mut self, net: CardanoBlockchainId) -> wasmtime::Result<SyncStatus> {
+        Ok(super::sync_status(net).into())
+    }
 
-        match super::read_block(net, at) {
-            Ok(block_data) => Ok(Ok(block_data.into_raw_data())),
-            Err(_) => Ok(Err(FetchError::InvalidSlot)),
+    /// Fetch a block from the requested blockchain at the requested slot into a host
+    /// buffer.
+    ///
+    /// **Parameters**
+    ///
+    /// - `net`    : The blockchain network to get a block from.
+    /// - `whence` : Which block to get.
+    ///
+    /// **Returns**
+    ///
+    /// - `buffer` : The raw CBOR data of the block requested.
+    /// - `hermes-error` : An error with a `fetch-error` code, if the block can not be
+    ///   fetched.
+    fn fetch_block_buffer(
+        &mut self, net: CardanoBlockchainId, whence: Slot,
+    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Buffer>, HermesError>> {
+        match fetch_raw_block(self.app_name(), net, whence) {
+            Ok(block_data) => Ok(Ok(new_buffer(self.app_name(), block_data)?)),
+            Err(err) => Ok(Err(err.into())),
         }
     }
 
@@ -192,6 +323,93 @@ impl Host for HermesRuntimeContext {
         Ok(block_data.txs().into_iter().map(|tx| tx.encode()).collect())
     }
 
+    /// Parse a textual Cardano address.
+    ///
+    /// **Parameters**
+    ///
+    /// - `address` : A Shelley era address or stake address in bech32, or a Byron address
+    ///   in base58.
+    ///
+    /// **Returns**
+    ///
+    /// - `bstr` : The raw bytes of the address, as found in transactions.
+    /// - `hermes-error` : An error with an `address-error` code, if the address is not
+    ///   valid.
+    fn parse_address(&mut self, address: String) -> wasmtime::Result<Result<Vec<u8>, HermesError>> {
+        Ok(super::address::parse(&address).map_err(HermesError::from))
+    }
+
+    /// Format the raw bytes of a Cardano address as text.
+    ///
+    /// **Parameters**
+    ///
+    /// - `address` : The raw bytes of the address.
+    ///
+    /// **Returns**
+    ///
+    /// - `string` : The address in bech32, or in base58 for a Byron address.
+    /// - `hermes-error` : An error with an `address-error` code, if the address is not
+    ///   valid.
+    fn format_address(
+        &mut self, address: Vec<u8>,
+    ) -> wasmtime::Result<Result<String, HermesError>> {
+        Ok(super::address::format(&address).map_err(HermesError::from))
+    }
+
+    /// Get the stake address a payment address delegates to.
+    ///
+    /// **Parameters**
+    ///
+    /// - `address` : The raw bytes of the address.
+    ///
+    /// **Returns**
+    ///
+    /// - `some(bstr)` : The raw bytes of the stake address of the delegation part of the
+    ///   address.
+    /// - `none` : The address has no stake key or script delegation part.
+    /// - `hermes-error` : An error with an `address-error` code, if the address is not
+    ///   valid.
+    fn stake_address(
+        &mut self, address: Vec<u8>,
+    ) -> wasmtime::Result<Result<Option<Vec<u8>>, HermesError>> {
+        Ok(super::address::stake_address(&address).map_err(HermesError::from))
+    }
+
+    /// Get the payment credential of a payment address.
+    ///
+    /// **Parameters**
+    ///
+    /// - `address` : The raw bytes of the address.
+    ///
+    /// **Returns**
+    ///
+    /// - `some(bstr)` : The key or script hash of the payment part of the address.
+    /// - `none` : The address has no payment part.
+    /// - `hermes-error` : An error with an `address-error` code, if the address is not
+    ///   valid.
+    fn payment_credential(
+        &mut self, address: Vec<u8>,
+    ) -> wasmtime::Result<Result<Option<Vec<u8>>, HermesError>> {
+        Ok(super::address::payment_credential(&address).map_err(HermesError::from))
+    }
+
+    /// Check an address belongs to a blockchain, according to its network tag.
+    ///
+    /// **Parameters**
+    ///
+    /// - `net` : The blockchain the address should belong to.
+    /// - `address` : The raw bytes of the address.
+    ///
+    /// **Returns**
+    ///
+    /// - `hermes-error` : An error with an `address-error` code, `network-mismatch` if
+    ///   the address belongs to another network.
+    fn check_address_network(
+        &mut self, net: CardanoBlockchainId, address: Vec<u8>,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        Ok(super::address::check_network(net, &address).map_err(HermesError::from))
+    }
+
     /// Post a transactions to the blockchain.
     ///
     /// This can be used to post a pre-formed transaction to the required blockchain.
@@ -211,7 +429,49 @@ impl Host for HermesRuntimeContext {
     /// All calls to this function will return `post-txn-not-allowed` error.
     fn post_txn(
         &mut self, _net: CardanoBlockchainId, _txn: CardanoTxn,
-    ) -> wasmtime::Result<Result<(), TxnError>> {
-        todo!()
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        Ok(Err(TxnError::PostTxnNotAllowed.into()))
+    }
+}
+
+/// Read the raw block data at `whence` from the requested blockchain, counted as
+/// received by the app.
+fn fetch_raw_block(
+    app_name: &ApplicationName, net: CardanoBlockchainId, whence: Slot,
+) -> Result<Vec<u8>, FetchError> {
+    let block = super::read_block(net, fetch_point(whence)?)
+        .map(cardano_chain_follower::MultiEraBlockData::into_raw_data)
+        .map_err(|_| FetchError::InvalidSlot)?;
+    bandwidth::record(app_name, Direction::Received, block.len());
+    Ok(block)
+}
+
+/// Point of the block a fetch at `whence` reads.
+/// `continue` does not name a block, so it is an invalid slot to fetch.
+fn fetch_point(whence: Slot) -> Result<cardano_chain_follower::PointOrTip, FetchError> {
+    match whence {
+        Slot::Genesis => Ok(cardano_chain_follower::Point::Origin.into()),
+        Slot::Point((slot, hash)) => Ok(cardano_chain_follower::Point::Specific(slot, hash).into()),
+        Slot::Tip => Ok(cardano_chain_follower::PointOrTip::Tip),
+        Slot::Continue => Err(FetchError::InvalidSlot),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_point_test() {
+        assert!(matches!(
+            fetch_point(Slot::Tip),
+            Ok(cardano_chain_follower::PointOrTip::Tip)
+        ));
+        assert!(fetch_point(Slot::Genesis).is_ok());
+        assert!(fetch_point(Slot::Point((10, vec![0; 32]))).is_ok());
+        assert!(matches!(
+            fetch_point(Slot::Continue),
+            Err(FetchError::InvalidSlot)
+        ));
     }
 }
@@ -1,16 +1,38 @@
 //! Cardano Blockchain runtime extension implementation.
 
+use std::{collections::BTreeMap, sync::Arc};
+
 use dashmap::DashMap;
 
 use crate::{
-    app::ApplicationName, runtime_extensions::bindings::hermes::cardano::api::CardanoBlockchainId,
+    app::ApplicationName,
+    event::HermesEventPayload,
+    runtime_extensions::bindings::hermes::{
+        cardano::api::{BlockSrc, CardanoBlock, CardanoBlockchainId, TxnFilter},
+        init::api::HealthStatus,
+    },
     wasm::module::ModuleId,
 };
 
+mod address;
+mod chain_data;
 mod chain_follower_task;
 mod event;
 mod host;
+mod sync;
 mod tokio_runtime_task;
+mod txn_filter;
+
+pub(crate) use chain_data::configure as configure_chain_data;
+pub(crate) use sync::status as sync_status;
+
+/// Number of slots a follower can lag behind the wall-clock slot and still be
+/// considered in sync.
+const SYNC_TOLERANCE_SLOTS: u64 = 600;
+
+/// Number of slots after which a block can no longer be rolled back, the `3k/f`
+/// stability window of the Cardano networks.
+const STABILITY_WINDOW_SLOTS: u64 = 129_600;
 
 /// Cardano Runtime Extension internal result type.
 pub(super) type Result<T> = anyhow::Result<T>;
@@ -20,15 +42,25 @@ pub(super) type Result<T> = anyhow::Result<T>;
 struct SubscriptionState {
     /// Whether the module is subscribed to receive block events.
     subscribed_to_blocks: bool,
+    /// Maximum number of blocks delivered in a single event during backfill, blocks
+    /// are delivered one by one when it is `0` or `1`.
+    block_batch_size: u32,
     /// Whether the module is subscribed to receive transaction events.
     subscribed_to_txns: bool,
+    /// Filter of the transactions the module receives events for, all of them if not
+    /// set.
+    txn_filter: Option<Arc<TxnFilter>>,
     /// Whether the module is subscribed to receive rollback events.
     subscribed_to_rollbacks: bool,
+    /// Whether the module is subscribed to receive immutable window events.
+    subscribed_to_immutable_window: bool,
     /// Handle to the cardano chain follower from which the module is receiving
     /// events.
     follower_handle: Option<chain_follower_task::Handle>,
     /// Current slot that the subscription is at.
     current_slot: u64,
+    /// Number of the block that the subscription is at, if it received any.
+    current_block_number: Option<u64>,
 }
 
 /// Triple representing the key of the subscription state map.
@@ -64,10 +96,15 @@ pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {
 pub(super) enum SubscriptionType {
     /// Subscribe to block events from a given point.
     Blocks(cardano_chain_follower::PointOrTip),
+    /// Deliver block events in batches of up to the given number of blocks.
+    BlockBatchSize(u32),
     /// Subscribe to rollback events.
     Rollbacks,
-    /// Subscribe to transaction events.
-    Transactions,
+    /// Subscribe to the events of the transactions matching the filter, or of all of
+    /// them.
+    Transactions(Option<TxnFilter>),
+    /// Subscribe to immutable window events.
+    ImmutableWindow,
     /// Continue previously stopped subscription event generation.
     Continue,
 }
@@ -88,6 +125,7 @@ pub(super) fn subscribe(
         SubscriptionType::Blocks(follow_from) => {
             if let Some(handle) = sub_state.follower_handle.as_ref() {
                 handle.set_read_pointer_sync(follow_from)?;
+                sync::follower_restarted(network);
             } else {
                 let (follower_handle, starting_point) = STATE.tokio_rt_handle.spawn_follower_sync(
                     app_name,
@@ -102,11 +140,18 @@ pub(super) fn subscribe(
 
             sub_state.subscribed_to_blocks = true;
         },
+        SubscriptionType::BlockBatchSize(max_blocks) => {
+            sub_state.block_batch_size = max_blocks;
+        },
         SubscriptionType::Rollbacks => {
             sub_state.subscribed_to_rollbacks = true;
         },
-        SubscriptionType::Transactions => {
+        SubscriptionType::Transactions(filter) => {
             sub_state.subscribed_to_txns = true;
+            sub_state.txn_filter = filter.map(Arc::new);
+        },
+        SubscriptionType::ImmutableWindow => {
+            sub_state.subscribed_to_immutable_window = true;
         },
         SubscriptionType::Continue => {
             if let Some(handle) = sub_state.follower_handle.as_ref() {
@@ -135,12 +180,17 @@ pub(super) fn unsubscribe(
 
         if opts & UnsubscribeOptions::TRANSACTION == UnsubscribeOptions::TRANSACTION {
             sub_state.subscribed_to_txns = false;
+            sub_state.txn_filter = None;
         }
 
         if opts & UnsubscribeOptions::ROLLBACK == UnsubscribeOptions::ROLLBACK {
             sub_state.subscribed_to_rollbacks = false;
         }
 
+        if opts & UnsubscribeOptions::IMMUTABLE_WINDOW == UnsubscribeOptions::IMMUTABLE_WINDOW {
+            sub_state.subscribed_to_immutable_window = false;
+        }
+
         if opts & UnsubscribeOptions::STOP == UnsubscribeOptions::STOP {
             if let Some(handle) = sub_state.follower_handle.as_ref() {
                 handle.stop()?;
@@ -158,6 +208,124 @@ pub(super) fn read_block(
     STATE.tokio_rt_handle.read_block(chain_id, at)
 }
 
+/// Creates a block event of a Cardano network from raw CBOR block data, e.g. a canned
+/// block fixture delivered to a module by hand.
+pub(crate) fn block_event(
+    blockchain: CardanoBlockchainId, block: CardanoBlock,
+) -> impl HermesEventPayload {
+    event::OnCardanoBlockEvent {
+        blockchain,
+        block,
+        source: BlockSrc::NODE,
+    }
+}
+
+/// Reads the blocks from `from` to `to` of a Cardano network and sends them to a module
+/// as block events, returning the number of blocks sent.
+pub(super) fn redeliver_blocks(
+    chain_id: CardanoBlockchainId, app_name: ApplicationName, module_id: ModuleId,
+    from: cardano_chain_follower::Point, to: cardano_chain_follower::PointOrTip,
+) -> Result<u64> {
+    let blocks = STATE.tokio_rt_handle.read_block_range(chain_id, from, to)?;
+
+    chain_follower_task::send_blocks(&(app_name, module_id, chain_id.into()), chain_id, blocks)
+}
+
+/// Asks every module of an app to reset the data it indexed from a Cardano network from
+/// the given slot, and follows the network again from the checkpoint each module
+/// returns.
+pub(crate) fn reindex(
+    app_name: ApplicationName, chain_id: CardanoBlockchainId, from_slot: u64,
+) -> anyhow::Result<()> {
+    let reindex_event = event::OnCardanoReindex {
+        blockchain: chain_id,
+        from_slot,
+    };
+    crate::event::queue::send(crate::event::HermesEvent::new(
+        reindex_event,
+        crate::event::TargetApp::List(vec![app_name]),
+        crate::event::TargetModule::All,
+    ))
+}
+
+/// Health of the chain followers of the given network.
+///
+/// The network is `unavailable` when no module follows it, and `degraded` while the
+/// most advanced follower is still catching up with the tip of the chain.
+pub(crate) fn health(chain_id: CardanoBlockchainId) -> HealthStatus {
+    if matches!(chain_id, CardanoBlockchainId::LocalTestBlockchain) {
+        return HealthStatus::Unavailable;
+    }
+    let network: cardano_chain_follower::Network = chain_id.into();
+
+    let synced_slot = STATE
+        .subscriptions
+        .iter()
+        .filter(|entry| entry.key().2 == network && entry.value().follower_handle.is_some())
+        .map(|entry| entry.value().current_slot)
+        .max();
+    let Some(synced_slot) = synced_slot else {
+        return HealthStatus::Unavailable;
+    };
+
+    match wallclock_slot(network) {
+        Some(slot) if slot.saturating_sub(synced_slot) > SYNC_TOLERANCE_SLOTS => {
+            HealthStatus::Degraded
+        },
+        _ => HealthStatus::Ready,
+    }
+}
+
+/// Latest slot indexed by the modules of the app on every network they received blocks
+/// of, keyed by network name.
+pub(crate) fn indexed_slots(app_name: &ApplicationName) -> BTreeMap<String, u64> {
+    let mut slots = BTreeMap::new();
+    for entry in STATE.subscriptions.iter() {
+        let (entry_app_name, _, network) = entry.key();
+        if entry_app_name != app_name || entry.value().current_block_number.is_none() {
+            continue;
+        }
+        let slot = slots.entry(network.to_string()).or_default();
+        *slot = entry.value().current_slot.max(*slot);
+    }
+    slots
+}
+
+/// Networks that are followed by at least one module.
+pub(crate) fn followed_networks() -> Vec<CardanoBlockchainId> {
+    let mut networks: Vec<CardanoBlockchainId> = Vec::new();
+    for entry in STATE.subscriptions.iter() {
+        if entry.value().follower_handle.is_none() {
+            continue;
+        }
+        let chain_id = entry.key().2.into();
+        if !networks.contains(&chain_id) {
+            networks.push(chain_id);
+        }
+    }
+    networks
+}
+
+/// Slot the tip of the given network is expected to be at, derived from the current
+/// time and the network genesis values.
+fn wallclock_slot(network: cardano_chain_follower::Network) -> Option<u64> {
+    let genesis = cardano_chain_follower::network_genesis_values(&network)?;
+    let now = u64::try_from(chrono::Utc::now().timestamp()).ok()?;
+    let elapsed = now.checked_sub(genesis.shelley_known_time)?;
+    Some(genesis.shelley_known_slot + elapsed / u64::from(genesis.shelley_slot_length))
+}
+
+impl From<cardano_chain_follower::Network> for CardanoBlockchainId {
+    fn from(network: cardano_chain_follower::Network) -> Self {
+        match network {
+            cardano_chain_follower::Network::Mainnet => CardanoBlockchainId::Mainnet,
+            cardano_chain_follower::Network::Preprod => CardanoBlockchainId::Preprod,
+            cardano_chain_follower::Network::Preview => CardanoBlockchainId::Preview,
+            cardano_chain_follower::Network::Testnet => CardanoBlockchainId::LocalTestBlockchain,
+        }
+    }
+}
+
 impl From<CardanoBlockchainId> for cardano_chain_follower::Network {
     fn from(chain_id: CardanoBlockchainId) -> Self {
         match chain_id {
@@ -205,7 +373,7 @@ mod test {
             CardanoBlockchainId::Preprod,
             app_name.clone(),
             module_id.clone(),
-            SubscriptionType::Transactions,
+            SubscriptionType::Transactions(None),
         )
         .unwrap();
 
@@ -0,0 +1,186 @@
+//! Chain follower sync metrics, per network.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use super::{chain_data, wallclock_slot, STATE};
+use crate::runtime_extensions::bindings::hermes::cardano::api::{
+    CardanoBlockchainId, SyncStatus as WitSyncStatus,
+};
+
+/// Period over which the block rate is measured.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Sync counters of every network.
+static COUNTERS: Lazy<DashMap<cardano_chain_follower::Network, Counters>> = Lazy::new(DashMap::new);
+
+/// Sync counters of a network.
+struct Counters {
+    /// Blocks of the network received by its followers, each counted once.
+    blocks: u64,
+    /// Slot of the last counted block.
+    counted_slot: Option<u64>,
+    /// Times a follower was restarted from another point.
+    restarts: u64,
+    /// Start of the current block rate window.
+    window_start: Instant,
+    /// Blocks received in the current block rate window.
+    window_blocks: u64,
+    /// Blocks per second over the last complete window.
+    blocks_per_second: f64,
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self {
+            blocks: 0,
+            counted_slot: None,
+            restarts: 0,
+            window_start: Instant::now(),
+            window_blocks: 0,
+            blocks_per_second: 0.0,
+        }
+    }
+}
+
+/// Sync status of the followers of a network.
+#[derive(Debug, Serialize)]
+pub(crate) struct SyncStatus {
+    /// Slot of the most advanced follower, if any follows the network.
+    pub(crate) current_slot: Option<u64>,
+    /// Slot the tip of the network is expected to be at.
+    pub(crate) tip_slot: Option<u64>,
+    /// Blocks of the network received by its followers.
+    pub(crate) blocks: u64,
+    /// Blocks received per second, over the last few seconds.
+    pub(crate) blocks_per_second: f64,
+    /// Slot of the tip of the Mithril snapshot immutable blocks are read from, if any.
+    pub(crate) mithril_tip_slot: Option<u64>,
+    /// Share of the Mithril snapshot read by the most advanced follower, from `0` to `1`.
+    pub(crate) mithril_progress: Option<f64>,
+    /// Times a follower was restarted from another point.
+    pub(crate) follower_restarts: u64,
+}
+
+impl Counters {
+    /// Counts the block at the slot, unless a block at or after it was already counted
+    /// because another follower of the network received it.
+    fn block_received(&mut self, slot: u64) {
+        if self.counted_slot.is_some_and(|counted| slot <= counted) {
+            return;
+        }
+        self.counted_slot = Some(slot);
+        self.blocks = self.blocks.saturating_add(1);
+        self.window_blocks = self.window_blocks.saturating_add(1);
+
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= RATE_WINDOW {
+            #[allow(clippy::cast_precision_loss)]
+            let blocks_per_second = self.window_blocks as f64 / elapsed.as_secs_f64();
+            self.blocks_per_second = blocks_per_second;
+            self.window_start = Instant::now();
+            self.window_blocks = 0;
+        }
+    }
+}
+
+impl From<SyncStatus> for WitSyncStatus {
+    fn from(status: SyncStatus) -> Self {
+        Self {
+            current_slot: status.current_slot,
+            tip_slot: status.tip_slot,
+            blocks_per_second: status.blocks_per_second,
+            mithril_tip_slot: status.mithril_tip_slot,
+            mithril_progress: status.mithril_progress,
+            follower_restarts: status.follower_restarts,
+        }
+    }
+}
+
+/// Records a block at the slot received by a follower of the network.
+pub(super) fn block_received(network: cardano_chain_follower::Network, slot: u64) {
+    COUNTERS.entry(network).or_default().block_received(slot);
+}
+
+/// Records a follower of the network restarted from another point.
+pub(super) fn follower_restarted(network: cardano_chain_follower::Network) {
+    let mut counters = COUNTERS.entry(network).or_default();
+    counters.restarts = counters.restarts.saturating_add(1);
+}
+
+/// Sync status of the followers of the given network.
+pub(crate) fn status(chain_id: CardanoBlockchainId) -> SyncStatus {
+    let network: cardano_chain_follower::Network = chain_id.into();
+
+    let current_slot = STATE
+        .subscriptions
+        .iter()
+        .filter(|entry| entry.key().2 == network && entry.value().follower_handle.is_some())
+        .map(|entry| entry.value().current_slot)
+        .max();
+
+    let (blocks, blocks_per_second, follower_restarts) =
+        COUNTERS.get(&network).map_or((0, 0.0, 0), |counters| {
+            // The rate is stale once no block was received for a whole window.
+            let blocks_per_second = if counters.window_start.elapsed() >= RATE_WINDOW * 2 {
+                0.0
+            } else {
+                counters.blocks_per_second
+            };
+            (counters.blocks, blocks_per_second, counters.restarts)
+        });
+
+    let mithril_tip_slot = chain_data::immutable_dir(network)
+        .and_then(|dir| cardano_chain_follower::mithril_snapshot_tip(dir).ok())
+        .map(|tip| tip.slot_or_default());
+    let mithril_progress = mithril_tip_slot
+        .map(|tip_slot| snapshot_progress(current_slot.unwrap_or_default(), tip_slot));
+
+    SyncStatus {
+        current_slot,
+        tip_slot: wallclock_slot(network),
+        blocks,
+        blocks_per_second,
+        mithril_tip_slot,
+        mithril_progress,
+        follower_restarts,
+    }
+}
+
+/// Share of a Mithril snapshot ending at `tip_slot` read by a follower at `slot`.
+#[allow(clippy::cast_precision_loss)]
+fn snapshot_progress(slot: u64, tip_slot: u64) -> f64 {
+    if tip_slot == 0 {
+        return 1.0;
+    }
+    (slot as f64 / tip_slot as f64).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_counted_once_per_network() {
+        let mut counters = Counters::default();
+        // Two followers of the network receive the same blocks.
+        for slot in [10, 10, 20, 20, 30] {
+            counters.block_received(slot);
+        }
+        assert_eq!(counters.blocks, 3);
+
+        // A follower behind the others does not count the blocks again.
+        counters.block_received(15);
+        assert_eq!(counters.blocks, 3);
+    }
+
+    #[test]
+    fn snapshot_progress_is_capped() {
+        assert!((snapshot_progress(50, 200) - 0.25).abs() < f64::EPSILON);
+        assert!((snapshot_progress(300, 200) - 1.0).abs() < f64::EPSILON);
+        assert!((snapshot_progress(0, 0) - 1.0).abs() < f64::EPSILON);
+    }
+}
@@ -3,7 +3,7 @@
 
 use tracing::{error, instrument, trace};
 
-use super::{Result, STATE};
+use super::{chain_data, Result, STATE};
 use crate::{
     app::ApplicationName, runtime_extensions::bindings::hermes::cardano::api::CardanoBlockchainId,
     wasm::module::ModuleId,
@@ -39,6 +39,18 @@ enum Command {
         response_tx:
             tokio::sync::oneshot::Sender<Result<cardano_chain_follower::MultiEraBlockData>>,
     },
+    /// Instructs the Tokio runtime background thread to read a range of blocks.
+    ReadBlockRange {
+        /// Cardano blockchain from which the blocks will be fetched.
+        chain_id: CardanoBlockchainId,
+        /// Chain point of the first block of the range.
+        from: cardano_chain_follower::Point,
+        /// Chain point of the last block of the range.
+        to: cardano_chain_follower::PointOrTip,
+        /// Response channel sender.
+        response_tx:
+            tokio::sync::oneshot::Sender<Result<Vec<cardano_chain_follower::MultiEraBlockData>>>,
+    },
 }
 
 /// Tokio runtime handle command channel sender type.
@@ -100,6 +112,29 @@ impl Handle {
 
         response_rx.blocking_recv()?
     }
+
+    /// Reads a range of blocks from a Cardano network.
+    ///
+    /// # Errors
+    ///
+    /// Return Err if there were any errors while fetching the blocks.
+    pub fn read_block_range(
+        &self, chain_id: CardanoBlockchainId, from: cardano_chain_follower::Point,
+        to: cardano_chain_follower::PointOrTip,
+    ) -> Result<Vec<cardano_chain_follower::MultiEraBlockData>> {
+        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+
+        let cmd = Command::ReadBlockRange {
+            chain_id,
+            from,
+            to,
+            response_tx,
+        };
+
+        self.cmd_tx.blocking_send(cmd)?;
+
+        response_rx.blocking_recv()?
+    }
 }
 
 /// Spawns a OS thread running the Tokio runtime task.
@@ -151,6 +186,15 @@ fn executor(mut cmd_rx: CommandReceiver) {
                     let res = read_block(chain_id, at).await;
                     drop(response_tx.send(res));
                 },
+                Command::ReadBlockRange {
+                    chain_id,
+                    from,
+                    to,
+                    response_tx,
+                } => {
+                    let res = read_block_range(chain_id, from, to).await;
+                    drop(response_tx.send(res));
+                },
             }
         }
     });
@@ -167,8 +211,8 @@ async fn spawn_follower(
 )> {
     trace!("Spawning chain follower executor");
 
-    let config = cardano_chain_follower::FollowerConfigBuilder::default().build();
     let network = chain_id.into();
+    let config = follower_config_builder(network).build();
 
     let follower = cardano_chain_follower::Follower::connect(
         follower_connect_address(network),
@@ -208,7 +252,7 @@ async fn read_block(
         // since we'll not poll the
         // follower's future so the following process will
         // not be executed.
-        let cfg = cardano_chain_follower::FollowerConfigBuilder::default()
+        let cfg = follower_config_builder(network)
             .chain_update_buffer_size(1)
             .build();
 
@@ -225,6 +269,50 @@ async fn read_block(
     }
 }
 
+/// Reads a range of blocks from the given chain.
+async fn read_block_range(
+    chain_id: CardanoBlockchainId, from: cardano_chain_follower::Point,
+    to: cardano_chain_follower::PointOrTip,
+) -> Result<Vec<cardano_chain_follower::MultiEraBlockData>> {
+    trace!("Reading block range");
+
+    let network = chain_id.into();
+
+    if let Some(reader) = STATE.readers.get(&network) {
+        let blocks = reader.read_block_range(from, to).await?;
+
+        Ok(blocks)
+    } else {
+        // See `read_block`, the follower is only used to read blocks.
+        let cfg = follower_config_builder(network)
+            .chain_update_buffer_size(1)
+            .build();
+
+        let reader = cardano_chain_follower::Follower::connect(
+            follower_connect_address(network),
+            network,
+            cfg,
+        )
+        .await?;
+
+        let blocks = reader.read_block_range(from, to).await?;
+
+        Ok(blocks)
+    }
+}
+
+/// Returns the configuration builder of the followers of the network, reading the
+/// immutable blocks from the chain data directory when it holds any.
+fn follower_config_builder(
+    network: cardano_chain_follower::Network,
+) -> cardano_chain_follower::FollowerConfigBuilder {
+    let builder = cardano_chain_follower::FollowerConfigBuilder::default();
+    match chain_data::immutable_dir(network) {
+        Some(dir) => builder.mithril_snapshot_path(dir),
+        None => builder,
+    }
+}
+
 /// Returns the peer address used to connect to each Cardano network.
 const fn follower_connect_address(network: cardano_chain_follower::Network) -> &'static str {
     match network {
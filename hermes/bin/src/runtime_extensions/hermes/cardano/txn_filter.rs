@@ -0,0 +1,52 @@
+//! Cardano transaction filters.
+
+use pallas::ledger::traverse::{MultiEraPolicyAssets, MultiEraTx};
+
+use crate::runtime_extensions::bindings::hermes::cardano::api::TxnFilter;
+
+/// Whether the transaction matches any of the criteria of the filter.
+///
+/// A filter without criteria matches every transaction.
+pub(super) fn matches(filter: &TxnFilter, tx: &MultiEraTx) -> bool {
+    if filter.addresses.is_empty()
+        && filter.metadata_labels.is_empty()
+        && filter.policies.is_empty()
+    {
+        return true;
+    }
+
+    let outputs = tx.outputs();
+
+    let pays_address = !filter.addresses.is_empty()
+        && outputs.iter().any(|output| {
+            output
+                .address()
+                .is_ok_and(|address| filter.addresses.contains(&address.to_vec()))
+        });
+    if pays_address {
+        return true;
+    }
+
+    let metadata = tx.metadata();
+    let has_label = filter
+        .metadata_labels
+        .iter()
+        .any(|label| metadata.find(*label).is_some());
+    if has_label {
+        return true;
+    }
+
+    !filter.policies.is_empty()
+        && (has_policy(filter, &tx.mints())
+            || outputs
+                .iter()
+                .any(|output| has_policy(filter, &output.non_ada_assets())))
+}
+
+/// Whether any of the assets belongs to a policy of the filter.
+fn has_policy(filter: &TxnFilter, assets: &[MultiEraPolicyAssets]) -> bool {
+    assets.iter().any(|policy_assets| {
+        let policy: &[u8] = policy_assets.policy().as_ref();
+        filter.policies.iter().any(|id| id.as_slice() == policy)
+    })
+}
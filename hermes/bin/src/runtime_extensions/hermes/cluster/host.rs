@@ -0,0 +1,49 @@
+//! Cluster host implementation for WASM runtime.
+
+use super::state;
+use crate::{
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::bindings::hermes::cluster::api::{ElectionName, Host},
+};
+
+impl Host for HermesRuntimeContext {
+    /// Identifies this node in the elections, the `PeerId` of the IPFS node of the app,
+    /// empty if it is not running.
+    fn node_id(&mut self) -> wasmtime::Result<String> {
+        Ok(state::node_id(self.app_name()).unwrap_or_default())
+    }
+
+    /// Campaign for the leadership of an election.
+    ///
+    /// **Parameters**
+    ///
+    /// - `election` : The election to campaign in.
+    ///
+    /// **Returns**
+    ///
+    /// - `true` : This node leads the election.
+    /// - `false` : Another node leads the election, or the lease could not be taken.
+    fn campaign(&mut self, election: ElectionName) -> wasmtime::Result<bool> {
+        Ok(state::campaign(self.app_name(), &election))
+    }
+
+    /// Whether this node leads an election, according to its latest lease.
+    fn is_leader(&mut self, election: ElectionName) -> wasmtime::Result<bool> {
+        Ok(state::is_leader(self.app_name(), &election))
+    }
+
+    /// The node which leads an election.
+    fn leader(&mut self, election: ElectionName) -> wasmtime::Result<Option<String>> {
+        Ok(state::leader(self.app_name(), &election))
+    }
+
+    /// Stop campaigning in an election, giving up the lease if this node holds it.
+    ///
+    /// **Returns**
+    ///
+    /// - `true` : This node led the election.
+    /// - `false` : This node did not lead the election.
+    fn resign(&mut self, election: ElectionName) -> wasmtime::Result<bool> {
+        Ok(state::resign(self.app_name(), &election))
+    }
+}
@@ -0,0 +1,480 @@
+//! Leader election state of the apps.
+//!
+//! The leader of an election holds a lease stored in the IPFS DHT under a key made of
+//! the app and election names. A campaigning node takes the lease when nobody holds it,
+//! or when it expired, and renews it while it leads, from a background thread.
+//!
+//! A lease is signed by the IPFS node identity of its holder, and names the holder by
+//! its `PeerId`, so it can only be taken or renewed by that node. Leases not signed by
+//! their holder are ignored.
+//!
+//! The lease is read from several of the peers holding it, and only counts once a
+//! majority of them agree on it, so a single stale or forged record does not decide the
+//! election. Leases last long enough for the slowest recent DHT round trips to renew
+//! them before they expire.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use dashmap::DashMap;
+use hermes_ipfs::{Keypair, PublicKey};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::ApplicationName,
+    ipfs::{hermes_ipfs_get_dht_values, hermes_ipfs_keypair, hermes_ipfs_put_ephemeral_dht_value},
+};
+
+/// Prefix of the DHT key of the lease of an election, followed by the app and election
+/// names.
+const KEY_PREFIX: &str = "hermes/cluster/";
+
+/// Shortest time a lease is held without being renewed.
+const MIN_LEASE_TTL: Duration = Duration::from_secs(15);
+
+/// How many times the campaigning nodes renew or try to take a lease during its TTL, so
+/// a leader can miss a renewal before its lease expires.
+const RENEWALS_PER_LEASE: u32 = 3;
+
+/// Number of peers the lease of an election is read from and compared.
+const LEASE_RECORDS: usize = 3;
+
+/// Slowest recent DHT round trip of an election round, in milliseconds, decaying over
+/// the following rounds.
+static ROUND_LATENCY: AtomicU64 = AtomicU64::new(0);
+
+/// Elections this node campaigns in, keyed by app and election name, with the latest
+/// lease the node knows of.
+static CAMPAIGNS: Lazy<DashMap<(ApplicationName, String), Option<Lease>>> = Lazy::new(DashMap::new);
+
+/// Background thread renewing the leases of the campaigns, started by the first one.
+static RENEWER: Lazy<()> = Lazy::new(|| {
+    std::thread::spawn(|| {
+        loop {
+            std::thread::sleep(renew_interval(lease_ttl()));
+            renew_all();
+        }
+    });
+});
+
+/// Lease on the leadership of an election.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Lease {
+    /// `PeerId` of the node holding the lease.
+    holder: String,
+    /// Incremented every time the lease changes holder.
+    term: u64,
+    /// When the lease expires, in milliseconds since the UNIX epoch.
+    expires: u64,
+}
+
+/// Lease signed by its holder, as stored in the DHT.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedLease {
+    /// JSON encoded `Lease`, signed as is.
+    lease: String,
+    /// Hex encoded protobuf encoding of the public key of the holder.
+    public_key: String,
+    /// Hex encoded signature of the DHT key and the lease.
+    signature: String,
+}
+
+impl Lease {
+    /// Whether the lease is not expired at `now`.
+    fn is_live(&self, now: u64) -> bool {
+        self.expires > now
+    }
+
+    /// Whether the lease is held by `node_id` and not expired at `now`.
+    fn is_held_by(&self, node_id: &str, now: u64) -> bool {
+        self.holder == node_id && self.is_live(now)
+    }
+}
+
+/// Identifies this node in the elections of the app, the `PeerId` of the IPFS node of
+/// the app, `None` if it is not running.
+pub(super) fn node_id(app_name: &ApplicationName) -> Option<String> {
+    hermes_ipfs_keypair(app_name)
+        .ok()
+        .map(|keypair| peer_id(&keypair))
+}
+
+/// Campaign for the leadership of an election of the app, returning whether this node
+/// leads it.
+pub(super) fn campaign(app_name: &ApplicationName, election: &str) -> bool {
+    Lazy::force(&RENEWER);
+    let lease = round(app_name, election);
+    let leads = is_held_by_node(app_name, lease.as_ref());
+    CAMPAIGNS.insert((app_name.clone(), election.to_string()), lease);
+    leads
+}
+
+/// Whether this node leads an election of the app.
+pub(super) fn is_leader(app_name: &ApplicationName, election: &str) -> bool {
+    CAMPAIGNS
+        .get(&(app_name.clone(), election.to_string()))
+        .is_some_and(|lease| is_held_by_node(app_name, lease.as_ref()))
+}
+
+/// The node which leads an election of the app.
+///
+/// The lease is read from the DHT unless this node campaigns in the election.
+pub(super) fn leader(app_name: &ApplicationName, election: &str) -> Option<String> {
+    let lease = match CAMPAIGNS.get(&(app_name.clone(), election.to_string())) {
+        Some(lease) => lease.clone(),
+        None => agreed_lease(&read_leases(app_name, election)),
+    };
+    lease
+        .filter(|lease| lease.is_live(now()))
+        .map(|lease| lease.holder)
+}
+
+/// Stop campaigning in an election of the app, returning whether this node led it.
+///
+/// The lease is expired at once, so another node takes it over at its next renewal.
+pub(super) fn resign(app_name: &ApplicationName, election: &str) -> bool {
+    let Some((_, Some(lease))) = CAMPAIGNS.remove(&(app_name.clone(), election.to_string())) else {
+        return false;
+    };
+    let Ok(keypair) = hermes_ipfs_keypair(app_name) else {
+        return false;
+    };
+    let now = now();
+    if !lease.is_held_by(&peer_id(&keypair), now) {
+        return false;
+    }
+    put_lease(app_name, election, &keypair, &Lease {
+        expires: now,
+        ..lease
+    });
+    tracing::info!(app_name = %app_name, election, "Resigned the leadership");
+    true
+}
+
+/// Whether the lease is held by the IPFS node of the app and not expired.
+fn is_held_by_node(app_name: &ApplicationName, lease: Option<&Lease>) -> bool {
+    let Some(node_id) = node_id(app_name) else {
+        return false;
+    };
+    lease.is_some_and(|lease| lease.is_held_by(&node_id, now()))
+}
+
+/// Renew or try to take the leases of every election this node campaigns in.
+fn renew_all() {
+    let campaigns: Vec<_> = CAMPAIGNS.iter().map(|entry| entry.key().clone()).collect();
+    for (app_name, election) in campaigns {
+        let lease = round(&app_name, &election);
+        // The node may have resigned meanwhile.
+        if let Some(mut entry) = CAMPAIGNS.get_mut(&(app_name.clone(), election.clone())) {
+            let led = is_held_by_node(&app_name, entry.as_ref());
+            let leads = is_held_by_node(&app_name, lease.as_ref());
+            if leads != led {
+                tracing::info!(app_name = %app_name, election, leads, "Leadership changed");
+            }
+            *entry = lease;
+        }
+    }
+}
+
+/// Take or renew the lease of an election of the app when possible, returning the
+/// lease the peers agree on.
+fn round(app_name: &ApplicationName, election: &str) -> Option<Lease> {
+    let keypair = hermes_ipfs_keypair(app_name).ok()?;
+    let started = Instant::now();
+    let current = read_leases(app_name, election);
+    let Some(lease) = next_lease(&current, &peer_id(&keypair), now(), lease_ttl()) else {
+        return agreed_lease(&current);
+    };
+    if !put_lease(app_name, election, &keypair, &lease) {
+        return agreed_lease(&current);
+    }
+    // Another node may have taken the lease at the same time, the one most peers hold
+    // wins.
+    let confirmed = read_leases(app_name, election);
+    record_round_latency(started.elapsed());
+    agreed_lease(&confirmed)
+}
+
+/// The lease `node_id` puts at `now` given the `current` leases read from the peers,
+/// `None` while another node holds it.
+///
+/// The lease most peers agree on is renewed by its holder, and left alone by the other
+/// nodes until it expires. Without such a lease, it is taken once no peer holds a live
+/// lease of another node.
+fn next_lease(current: &[Lease], node_id: &str, now: u64, ttl: Duration) -> Option<Lease> {
+    let expires = now.saturating_add(u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX));
+    if let Some(agreed) = agreed_lease(current).filter(|lease| lease.is_live(now)) {
+        return (agreed.holder == node_id).then_some(Lease { expires, ..agreed });
+    }
+    if current
+        .iter()
+        .any(|lease| lease.holder != node_id && lease.is_live(now))
+    {
+        return None;
+    }
+    match current.iter().max_by_key(|lease| lease.term) {
+        Some(latest) if latest.holder == node_id => {
+            Some(Lease {
+                expires,
+                ..latest.clone()
+            })
+        },
+        latest => {
+            Some(Lease {
+                holder: node_id.to_string(),
+                term: latest.map_or(0, |lease| lease.term).saturating_add(1),
+                expires,
+            })
+        },
+    }
+}
+
+/// The lease held by a majority of the peers it was read from, `None` if they do not
+/// agree.
+///
+/// Leases of the same holder and term are the same lease, the latest renewal is
+/// returned.
+fn agreed_lease(leases: &[Lease]) -> Option<Lease> {
+    leases
+        .iter()
+        .filter(|candidate| {
+            let votes = leases
+                .iter()
+                .filter(|lease| lease.holder == candidate.holder && lease.term == candidate.term)
+                .count();
+            votes.saturating_mul(2) > leases.len()
+        })
+        .max_by_key(|lease| lease.expires)
+        .cloned()
+}
+
+/// How long a lease is held without being renewed, long enough for its holder to renew
+/// it `RENEWALS_PER_LEASE` times, giving each renewal twice the slowest recent round trip
+/// to the DHT.
+fn lease_ttl() -> Duration {
+    ttl_for_latency(Duration::from_millis(ROUND_LATENCY.load(Ordering::Acquire)))
+}
+
+/// Lease TTL for the DHT round trip latency.
+fn ttl_for_latency(latency: Duration) -> Duration {
+    latency
+        .saturating_mul(RENEWALS_PER_LEASE.saturating_mul(2))
+        .max(MIN_LEASE_TTL)
+}
+
+/// How often the campaigning nodes renew or try to take the leases with the TTL.
+fn renew_interval(ttl: Duration) -> Duration {
+    ttl / RENEWALS_PER_LEASE
+}
+
+/// Record the DHT round trip latency of an election round. A slower round raises the
+/// latency at once, faster rounds lower it gradually.
+fn record_round_latency(latency: Duration) {
+    let latency = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+    let _unused = ROUND_LATENCY.fetch_update(Ordering::AcqRel, Ordering::Acquire, |slowest| {
+        Some(decayed_latency(slowest, latency))
+    });
+}
+
+/// The slowest latency, in milliseconds, once a round with the latency is recorded.
+fn decayed_latency(slowest: u64, latency: u64) -> u64 {
+    latency.max(slowest.saturating_sub(slowest / 8))
+}
+
+/// `PeerId` of the node with the keypair.
+fn peer_id(keypair: &Keypair) -> String {
+    keypair.public().to_peer_id().to_string()
+}
+
+/// DHT key of the lease of an election of the app.
+fn key(app_name: &ApplicationName, election: &str) -> Vec<u8> {
+    format!("{KEY_PREFIX}{app_name}/{election}").into_bytes()
+}
+
+/// Message signed by the holder of the lease stored under the DHT key, binding the lease
+/// to the election.
+fn signed_message(key: &[u8], lease: &str) -> Vec<u8> {
+    [key, b"\n", lease.as_bytes()].concat()
+}
+
+/// Sign the lease stored under the DHT key with the keypair of its holder, returning the
+/// DHT value.
+fn sign_lease(key: &[u8], keypair: &Keypair, lease: &Lease) -> anyhow::Result<Vec<u8>> {
+    let lease = serde_json::to_string(lease)?;
+    let signature = keypair.sign(&signed_message(key, &lease))?;
+    Ok(serde_json::to_vec(&SignedLease {
+        lease,
+        public_key: hex::encode(keypair.public().encode_protobuf()),
+        signature: hex::encode(signature),
+    })?)
+}
+
+/// The lease of the DHT value stored under the DHT key, if signed by its holder.
+fn verify_lease(key: &[u8], value: &[u8]) -> anyhow::Result<Lease> {
+    let signed: SignedLease = serde_json::from_slice(value)?;
+    let public_key = PublicKey::try_decode_protobuf(&hex::decode(&signed.public_key)?)?;
+    let signature = hex::decode(&signed.signature)?;
+    anyhow::ensure!(
+        public_key.verify(&signed_message(key, &signed.lease), &signature),
+        "Invalid lease signature"
+    );
+    let lease: Lease = serde_json::from_str(&signed.lease)?;
+    anyhow::ensure!(
+        lease.holder == public_key.to_peer_id().to_string(),
+        "Lease not signed by its holder"
+    );
+    Ok(lease)
+}
+
+/// Read the leases of an election of the app from up to `LEASE_RECORDS` peers, dropping
+/// the ones not signed by their holder.
+fn read_leases(app_name: &ApplicationName, election: &str) -> Vec<Lease> {
+    let key = key(app_name, election);
+    let Ok(values) = hermes_ipfs_get_dht_values(app_name, key.clone(), LEASE_RECORDS) else {
+        return Vec::new();
+    };
+    values
+        .iter()
+        .filter_map(|value| {
+            verify_lease(&key, value)
+                .inspect_err(|err| {
+                    tracing::warn!(app_name = %app_name, election, "Ignoring lease: {err}");
+                })
+                .ok()
+        })
+        .collect()
+}
+
+/// Put the lease of an election of the app, signed with the keypair of the node, into
+/// the DHT, returning whether it was put.
+fn put_lease(app_name: &ApplicationName, election: &str, keypair: &Keypair, lease: &Lease) -> bool {
+    let key = key(app_name, election);
+    let value = match sign_lease(&key, keypair, lease) {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::error!(app_name = %app_name, election, "Failed to sign the lease: {err}");
+            return false;
+        },
+    };
+    match hermes_ipfs_put_ephemeral_dht_value(app_name, key, value) {
+        Ok(put) => put,
+        Err(err) => {
+            tracing::warn!(app_name = %app_name, election, "Failed to put the lease: {err:?}");
+            false
+        },
+    }
+}
+
+/// Now, in milliseconds since the UNIX epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|now| u64::try_from(now.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(holder: &str, term: u64, expires: u64) -> Lease {
+        Lease {
+            holder: holder.to_string(),
+            term,
+            expires,
+        }
+    }
+
+    #[test]
+    fn test_next_lease() {
+        let ttl = u64::try_from(MIN_LEASE_TTL.as_millis()).unwrap();
+        let next = |current: &[Lease]| next_lease(current, "a", 100, MIN_LEASE_TTL);
+
+        // Nobody holds the lease.
+        assert_eq!(next(&[]), Some(lease("a", 1, 100 + ttl)));
+        // The holder renews its lease, even once expired.
+        assert_eq!(next(&[lease("a", 3, 200)]), Some(lease("a", 3, 100 + ttl)));
+        assert_eq!(next(&[lease("a", 3, 50)]), Some(lease("a", 3, 100 + ttl)));
+        // Another node holds the lease until it expires.
+        assert_eq!(next(&[lease("b", 3, 200)]), None);
+        assert_eq!(next(&[lease("b", 3, 100)]), Some(lease("a", 4, 100 + ttl)));
+
+        // The lease most peers agree on is renewed by its holder only.
+        let split = [lease("a", 3, 200), lease("a", 3, 150), lease("b", 4, 200)];
+        assert_eq!(next(&split), Some(lease("a", 3, 100 + ttl)));
+        let split = [lease("b", 4, 200), lease("b", 4, 200), lease("a", 3, 200)];
+        assert_eq!(next(&split), None);
+        // Without a majority, a live lease of another node on any peer holds it off.
+        assert_eq!(next(&[lease("a", 3, 200), lease("b", 4, 200)]), None);
+        // Once it expired, the lease is taken over from the latest term.
+        assert_eq!(
+            next(&[lease("c", 5, 50), lease("b", 4, 50)]),
+            Some(lease("a", 6, 100 + ttl))
+        );
+        // A renewal only some peers hold yet is kept on.
+        let partial = [lease("a", 5, 200), lease("b", 4, 50), lease("b", 4, 50)];
+        assert_eq!(next(&partial), Some(lease("a", 5, 100 + ttl)));
+    }
+
+    #[test]
+    fn test_agreed_lease() {
+        assert_eq!(agreed_lease(&[]), None);
+        assert_eq!(agreed_lease(&[lease("a", 1, 10)]), Some(lease("a", 1, 10)));
+        // The latest renewal of the lease most peers hold.
+        assert_eq!(
+            agreed_lease(&[lease("a", 1, 10), lease("b", 2, 30), lease("a", 1, 20)]),
+            Some(lease("a", 1, 20))
+        );
+        // No majority.
+        assert_eq!(agreed_lease(&[lease("a", 1, 10), lease("b", 2, 30)]), None);
+        assert_eq!(
+            agreed_lease(&[lease("a", 1, 10), lease("a", 2, 10), lease("b", 2, 10)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_signed_lease() {
+        let keypair = Keypair::generate_ed25519();
+        let key = key(&ApplicationName("athena".to_string()), "doc-sync");
+        let own_lease = lease(&peer_id(&keypair), 1, 100);
+
+        let value = sign_lease(&key, &keypair, &own_lease).unwrap();
+        assert_eq!(verify_lease(&key, &value).unwrap(), own_lease);
+        // The signature is bound to the election.
+        let other_key = self::key(&ApplicationName("athena".to_string()), "other");
+        assert!(verify_lease(&other_key, &value).is_err());
+
+        // A tampered lease is rejected.
+        let mut signed: SignedLease = serde_json::from_slice(&value).unwrap();
+        signed.lease = serde_json::to_string(&lease(&own_lease.holder, 1, 1000)).unwrap();
+        assert!(verify_lease(&key, &serde_json::to_vec(&signed).unwrap()).is_err());
+
+        // So is a lease signed by another node than its holder.
+        let forger = Keypair::generate_ed25519();
+        let forged = sign_lease(&key, &forger, &own_lease).unwrap();
+        assert!(verify_lease(&key, &forged).is_err());
+
+        // And an unsigned one.
+        let unsigned = serde_json::to_vec(&own_lease).unwrap();
+        assert!(verify_lease(&key, &unsigned).is_err());
+    }
+
+    #[test]
+    fn test_lease_ttl() {
+        // Fast round trips keep the minimum TTL.
+        assert_eq!(ttl_for_latency(Duration::from_millis(200)), MIN_LEASE_TTL);
+        // Slow ones stretch it, leaving each renewal twice the round trip.
+        let ttl = ttl_for_latency(Duration::from_secs(10));
+        assert_eq!(ttl, Duration::from_secs(60));
+        assert_eq!(renew_interval(ttl), Duration::from_secs(20));
+
+        // The slowest round trip is kept, and decays over faster ones.
+        assert_eq!(decayed_latency(800, 2000), 2000);
+        assert_eq!(decayed_latency(800, 100), 700);
+        assert_eq!(decayed_latency(0, 100), 100);
+    }
+}
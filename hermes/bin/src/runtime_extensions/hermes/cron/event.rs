@@ -2,12 +2,15 @@
 
 use std::ops::Sub;
 
-use chrono::Utc;
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
 use saffron::Cron;
 
-use super::{state::cron_queue_rm, Error};
+use super::{history, state::cron_queue_rm, Error};
 use crate::{
-    event::HermesEventPayload, runtime_extensions::bindings::hermes::cron::api::CronTagged,
+    event::HermesEventPayload,
+    runtime_extensions::bindings::hermes::cron::api::{CronDeliveryInfo, CronTagged},
+    wasm::module::ModuleInstance,
 };
 
 /// Duration in nanoseconds used for the Cron Service.
@@ -44,12 +47,14 @@ impl From<u64> for CronDuration {
 }
 
 /// On cron event
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct OnCronEvent {
     /// The tagged cron event that was triggered.
     pub(crate) tag: CronTagged,
     /// This cron event will not retrigger.
     pub(crate) last: bool,
+    /// Timezone the cron schedule is matched in, UTC if `None`.
+    pub(crate) timezone: Option<Tz>,
 }
 
 impl HermesEventPayload for OnCronEvent {
@@ -57,12 +62,56 @@ impl HermesEventPayload for OnCronEvent {
         "on-cron"
     }
 
-    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
-        let res: bool = module.instance.hermes_cron_event().call_on_cron(
+    fn execute(&self, module: &mut ModuleInstance) -> anyhow::Result<()> {
+        // Not sent by the cron queue, e.g. fired by hand, so it is delivered on schedule.
+        let next_fire = if self.last {
+            None
+        } else {
+            self.tick_after(None)
+        };
+        self.deliver(module, None, next_fire)
+    }
+}
+
+impl OnCronEvent {
+    /// Deliver the event to the module, with the scheduling metadata of the tick.
+    ///
+    /// # Parameters
+    ///
+    /// * `scheduled: Option<CronDuration>` - When the tick was scheduled to fire. If
+    ///   `None`, the delivery time is used.
+    /// * `next_fire: Option<CronDuration>` - When the crontab entry fires next, `None` if
+    ///   it does not retrigger.
+    pub(super) fn deliver(
+        &self, module: &mut ModuleInstance, scheduled: Option<CronDuration>,
+        next_fire: Option<CronDuration>,
+    ) -> anyhow::Result<()> {
+        let started = Utc::now();
+        let start = std::time::Instant::now();
+        let delivered = started
+            .timestamp_nanos_opt()
+            .and_then(|ts| u64::try_from(ts).ok())
+            .unwrap_or_default();
+        let delivery = delivery_info(
+            scheduled.map_or(delivered, u64::from),
+            delivered,
+            next_fire.filter(|_| !self.last),
+        );
+        let res = module.instance.hermes_cron_event().call_on_cron(
             &mut module.store,
             &self.tag,
             self.last,
-        )?;
+            delivery,
+        );
+        history::record(
+            module.store.data().app_name(),
+            module.store.data().module_id(),
+            &self.tag,
+            started,
+            start.elapsed(),
+            &res,
+        );
+        let res = res?;
         // if the response is `false`, check if the event would
         // re-trigger, if so, remove it.
         if !res && !self.last {
@@ -71,9 +120,7 @@ impl HermesEventPayload for OnCronEvent {
         }
         Ok(())
     }
-}
 
-impl OnCronEvent {
     /// Get the next scheduled cron event after the optional start timestamp, or after the
     /// current timestamp.
     ///
@@ -87,15 +134,7 @@ impl OnCronEvent {
     /// * `Some(CronDuration)` - The next timestamp for the `OnCronEvent`.
     /// * `None` if the timestamp could not be calculated.
     pub(crate) fn tick_after(&self, start: Option<CronDuration>) -> Option<CronDuration> {
-        let cron = self.cron()?;
-        if cron.any() {
-            let datetime = Self::start_datetime(start)?;
-            let cdt = cron.iter_after(datetime).next()?;
-            let timestamp = cdt.timestamp_nanos_opt()?;
-            timestamp.try_into().ok()
-        } else {
-            None
-        }
+        self.next_tick(start, false)
     }
 
     /// Get the next scheduled cron event from the optional start timestamp, or from the
@@ -111,15 +150,35 @@ impl OnCronEvent {
     /// * `Some(CronDuration)` - The next timestamp for the `OnCronEvent`.
     /// * `None` if the timestamp could not be calculated.
     pub(crate) fn tick_from(&self, start: Option<CronDuration>) -> Option<CronDuration> {
+        self.next_tick(start, true)
+    }
+
+    /// Get the next scheduled cron event from the optional start timestamp, or from the
+    /// current timestamp, including the start timestamp itself if `inclusive`.
+    ///
+    /// With a timezone, the schedule is matched against the wall clock of the timezone.
+    fn next_tick(&self, start: Option<CronDuration>, inclusive: bool) -> Option<CronDuration> {
         let cron = self.cron()?;
-        if cron.any() {
-            let datetime = Self::start_datetime(start)?;
-            let cdt = cron.iter_from(datetime).next()?;
-            let timestamp = cdt.timestamp_nanos_opt()?;
-            timestamp.try_into().ok()
-        } else {
-            None
+        if !cron.any() {
+            return None;
         }
+        let datetime = Self::start_datetime(start)?;
+        let cdt = match self.timezone {
+            None if inclusive => cron.iter_from(datetime).next()?,
+            None => cron.iter_after(datetime).next()?,
+            Some(tz) => {
+                // Iterate over the wall clock times as if they were UTC times.
+                let wall_clock = datetime.with_timezone(&tz).naive_local().and_utc();
+                let next = if inclusive {
+                    cron.iter_from(wall_clock).next()?
+                } else {
+                    cron.iter_after(wall_clock).next()?
+                };
+                wall_clock_to_utc(tz, next.naive_utc(), datetime, inclusive)?
+            },
+        };
+        let timestamp = cdt.timestamp_nanos_opt()?;
+        timestamp.try_into().ok()
     }
 
     /// Get the `Cron` from the inner `CronSchedule`.
@@ -142,6 +201,58 @@ impl OnCronEvent {
     }
 }
 
+/// Get the scheduling metadata of a tick delivered at `delivered`, which was scheduled
+/// at `scheduled`, and fires next at `next_fire` unless it is its last occurrence.
+fn delivery_info(
+    scheduled: u64, delivered: u64, next_fire: Option<CronDuration>,
+) -> CronDeliveryInfo {
+    let drift = i64::try_from(delivered)
+        .unwrap_or(i64::MAX)
+        .saturating_sub(i64::try_from(scheduled).unwrap_or(i64::MAX));
+    CronDeliveryInfo {
+        scheduled,
+        delivered,
+        drift,
+        next_fire: next_fire.map(u64::from),
+        remaining: next_fire.is_none().then_some(0),
+    }
+}
+
+/// Get the UTC time of a wall clock time of the timezone, which is after `start`, or at
+/// `start` if `inclusive`.
+///
+/// A wall clock time repeated when the clocks go back is the first of its occurrences
+/// which is not before `start`, and one skipped when the clocks go forward is shifted an
+/// hour later.
+fn wall_clock_to_utc(
+    tz: Tz, wall_clock: NaiveDateTime, start: DateTime<Utc>, inclusive: bool,
+) -> Option<DateTime<Utc>> {
+    let is_next = |datetime: &DateTime<Utc>| {
+        if inclusive {
+            *datetime >= start
+        } else {
+            *datetime > start
+        }
+    };
+    let datetime = match tz.from_local_datetime(&wall_clock) {
+        LocalResult::Single(datetime) => datetime.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, latest) => {
+            let earliest = earliest.with_timezone(&Utc);
+            if is_next(&earliest) {
+                earliest
+            } else {
+                latest.with_timezone(&Utc)
+            }
+        },
+        LocalResult::None => {
+            tz.from_local_datetime(&wall_clock.checked_add_signed(TimeDelta::try_hours(1)?)?)
+                .earliest()?
+                .with_timezone(&Utc)
+        },
+    };
+    is_next(&datetime).then_some(datetime)
+}
+
 impl PartialEq for CronTagged {
     fn eq(&self, other: &Self) -> bool {
         self.tag == other.tag && self.when == other.when
@@ -168,6 +279,86 @@ mod tests {
 
     use super::*;
 
+    fn tick(event: &OnCronEvent, start: DateTime<Utc>, inclusive: bool) -> DateTime<Utc> {
+        let start = CronDuration::try_from(start.timestamp_nanos_opt().unwrap()).unwrap();
+        let tick = if inclusive {
+            event.tick_from(Some(start))
+        } else {
+            event.tick_after(Some(start))
+        };
+        DateTime::from_timestamp_nanos(u64::from(tick.unwrap()).try_into().unwrap())
+    }
+
+    #[test]
+    fn test_timezone_ticks() {
+        let berlin = |when: &str| {
+            OnCronEvent {
+                tag: CronTagged {
+                    when: when.to_string(),
+                    tag: "tag".to_string(),
+                },
+                last: false,
+                timezone: Some(chrono_tz::Europe::Berlin),
+            }
+        };
+
+        // 09:00 in Berlin, before and after the clocks go forward on 2024-03-31.
+        let daily = berlin("0 9 * * *");
+        let start = Utc.with_ymd_and_hms(2024, 3, 29, 12, 0, 0).unwrap();
+        assert_eq!(
+            tick(&daily, start, true),
+            Utc.with_ymd_and_hms(2024, 3, 30, 8, 0, 0).unwrap()
+        );
+        let start = Utc.with_ymd_and_hms(2024, 3, 30, 8, 0, 0).unwrap();
+        assert_eq!(
+            tick(&daily, start, true),
+            Utc.with_ymd_and_hms(2024, 3, 30, 8, 0, 0).unwrap()
+        );
+        assert_eq!(
+            tick(&daily, start, false),
+            Utc.with_ymd_and_hms(2024, 3, 31, 7, 0, 0).unwrap()
+        );
+
+        // 02:30 does not exist on 2024-03-31 in Berlin, it fires at 03:30.
+        let skipped = berlin("30 2 * * *");
+        let start = Utc.with_ymd_and_hms(2024, 3, 30, 12, 0, 0).unwrap();
+        assert_eq!(
+            tick(&skipped, start, false),
+            Utc.with_ymd_and_hms(2024, 3, 31, 1, 30, 0).unwrap()
+        );
+
+        // 02:30 happens twice on 2024-10-27 in Berlin, it fires once.
+        let start = Utc.with_ymd_and_hms(2024, 10, 26, 12, 0, 0).unwrap();
+        let repeated = tick(&skipped, start, false);
+        assert_eq!(
+            repeated,
+            Utc.with_ymd_and_hms(2024, 10, 27, 0, 30, 0).unwrap()
+        );
+        assert_eq!(
+            tick(&skipped, repeated, false),
+            Utc.with_ymd_and_hms(2024, 10, 28, 1, 30, 0).unwrap()
+        );
+        // Added during the second 02:00 hour, it fires at the second 02:30.
+        let start = Utc.with_ymd_and_hms(2024, 10, 27, 1, 10, 0).unwrap();
+        assert_eq!(
+            tick(&skipped, start, false),
+            Utc.with_ymd_and_hms(2024, 10, 27, 1, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_delivery_info() {
+        let late = delivery_info(1_000, 1_500, Some(CronDuration(61_000)));
+        assert_eq!(late.drift, 500);
+        assert_eq!(late.next_fire, Some(61_000));
+        assert_eq!(late.remaining, None);
+
+        let early = delivery_info(1_500, 1_000, None);
+        assert_eq!(early.drift, -500);
+        assert_eq!(early.next_fire, None);
+        assert_eq!(early.remaining, Some(0));
+    }
+
     #[test]
     fn test_cron_queue() {
         let start = NaiveDate::from_ymd_opt(1970, 1, 1)
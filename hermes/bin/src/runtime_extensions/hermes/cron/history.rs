@@ -0,0 +1,154 @@
+//! Cron execution history.
+
+use std::{collections::VecDeque, time::Duration};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::bindings::hermes::cron::api::{
+        CronEventTag, CronExecution as WitCronExecution, CronTagged,
+    },
+    wasm::module::ModuleId,
+};
+
+/// Number of executions kept per crontab tag.
+const HISTORY_LEN: usize = 32;
+
+/// Latest executions of every app, keyed by app and crontab tag.
+static HISTORY: Lazy<DashMap<(ApplicationName, CronEventTag), VecDeque<CronExecution>>> =
+    Lazy::new(DashMap::new);
+
+/// Execution of an `on-cron` event by a module.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CronExecution {
+    /// App the event was delivered to.
+    pub(crate) app: String,
+    /// Module which handled the event.
+    pub(crate) module: String,
+    /// Schedule of the crontab entry.
+    pub(crate) when: String,
+    /// Tag of the crontab entry.
+    pub(crate) tag: String,
+    /// When the execution started, in nanoseconds since the UNIX epoch.
+    pub(crate) started: u64,
+    /// How long the execution took, in nanoseconds.
+    pub(crate) duration: u64,
+    /// Value returned by the module, if it did not fail.
+    pub(crate) retrigger: Option<bool>,
+    /// Trap or error, if the module failed.
+    pub(crate) error: Option<String>,
+}
+
+impl From<CronExecution> for WitCronExecution {
+    fn from(execution: CronExecution) -> Self {
+        Self {
+            entry: CronTagged {
+                when: execution.when,
+                tag: execution.tag,
+            },
+            started: execution.started,
+            duration: execution.duration,
+            result: match (execution.retrigger, execution.error) {
+                (Some(retrigger), None) => Ok(retrigger),
+                (_, error) => Err(error.unwrap_or_default()),
+            },
+        }
+    }
+}
+
+/// Record the execution of an `on-cron` event by a module.
+pub(crate) fn record(
+    app_name: &ApplicationName, module_id: &ModuleId, entry: &CronTagged, started: DateTime<Utc>,
+    duration: Duration, result: &anyhow::Result<bool>,
+) {
+    let execution = CronExecution {
+        app: app_name.to_string(),
+        module: module_id.to_string(),
+        when: entry.when.clone(),
+        tag: entry.tag.clone(),
+        started: started
+            .timestamp_nanos_opt()
+            .and_then(|started| started.try_into().ok())
+            .unwrap_or_default(),
+        duration: duration.as_nanos().try_into().unwrap_or(u64::MAX),
+        retrigger: result.as_ref().ok().copied(),
+        error: result.as_ref().err().map(|err| format!("{err:#}")),
+    };
+    if let Some(error) = &execution.error {
+        tracing::warn!(app_name = %app_name, tag = %entry.tag, "on-cron event failed: {error}");
+    }
+
+    let mut executions = HISTORY
+        .entry((app_name.clone(), entry.tag.clone()))
+        .or_default();
+    if executions.len() == HISTORY_LEN {
+        executions.pop_front();
+    }
+    executions.push_back(execution);
+}
+
+/// Latest executions of the crontab entries of an app, oldest first.
+/// If `tag` is `None`, the executions of all the tags are listed.
+pub(crate) fn history(
+    app_name: &ApplicationName, tag: Option<&CronEventTag>,
+) -> Vec<CronExecution> {
+    let mut executions: Vec<_> = HISTORY
+        .iter()
+        .filter(|entry| {
+            let (app, entry_tag) = entry.key();
+            app == app_name && tag.map_or(true, |tag| tag == entry_tag)
+        })
+        .flat_map(|entry| entry.value().clone())
+        .collect();
+    executions.sort_by_key(|execution| execution.started);
+    executions
+}
+
+/// Forget the executions of an app, when it is unloaded.
+pub(crate) fn remove_app(app_name: &ApplicationName) {
+    HISTORY.retain(|(app, _), _| app != app_name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_bounded() {
+        let app_name = ApplicationName("cron_history".to_string());
+        let module_id = ModuleId(rusty_ulid::Ulid::generate());
+        let entry = CronTagged {
+            when: "* * * * *".to_string(),
+            tag: "bounded".to_string(),
+        };
+
+        for _ in 0..=HISTORY_LEN {
+            record(
+                &app_name,
+                &module_id,
+                &entry,
+                Utc::now(),
+                Duration::ZERO,
+                &Ok(true),
+            );
+        }
+        record(
+            &app_name,
+            &module_id,
+            &entry,
+            Utc::now(),
+            Duration::ZERO,
+            &Err(anyhow::anyhow!("trap")),
+        );
+
+        let executions = history(&app_name, Some(&entry.tag));
+        assert_eq!(executions.len(), HISTORY_LEN);
+        let last = executions.last().unwrap();
+        assert_eq!(last.error.as_deref(), Some("trap"));
+        assert!(history(&app_name, Some(&"other".to_string())).is_empty());
+    }
+}
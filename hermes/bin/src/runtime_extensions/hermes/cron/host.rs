@@ -1,13 +1,23 @@
 //! Cron host implementation for WASM runtime.
 
+use chrono_tz::Tz;
+
 use super::{
-    mkcron_impl,
-    state::{cron_queue_add, cron_queue_delay, cron_queue_ls, cron_queue_rm},
+    history::history,
+    mkcron_impl, parse_crontab,
+    state::{
+        cron_queue_add, cron_queue_delay, cron_queue_ls, cron_queue_ls_schedule, cron_queue_rm,
+        cron_queue_set_jitter, cron_queue_set_misfire_policy, cron_queue_set_overlap_policy,
+        cron_queue_set_priority,
+    },
 };
 use crate::{
     runtime_context::HermesRuntimeContext,
     runtime_extensions::bindings::{
-        hermes::cron::api::{CronEventTag, CronSched, CronTagged, CronTime, Host},
+        hermes::cron::api::{
+            CronEventTag, CronExecution, CronPriority, CronSched, CronSchedule, CronTagged,
+            CronTime, Host, MisfirePolicy, OverlapPolicy,
+        },
         wasi::clocks::monotonic_clock::Instant,
     },
 };
@@ -38,7 +48,47 @@ impl Host for HermesRuntimeContext {
     /// this function.  This could be useful where a retriggering crontab event is desired
     /// to be stopped, but ONLY after it has triggered once more.
     fn add(&mut self, entry: CronTagged, retrigger: bool) -> wasmtime::Result<bool> {
-        Ok(cron_queue_add(self.app_name(), entry, retrigger))
+        if let Err(err) = parse_crontab(&entry.when) {
+            tracing::warn!(app_name = %self.app_name(), tag = %entry.tag, "{err}");
+            return Ok(false);
+        }
+        Ok(cron_queue_add(self.app_name(), entry, retrigger, None))
+    }
+
+    /// # Schedule Recurrent CRON event in a timezone
+    ///
+    /// Same as `add`, but the entry is matched against the wall clock of the timezone.
+    ///
+    /// ## Parameters
+    ///
+    /// - `entry`: The crontab entry to add.
+    /// - `retrigger`: Same as for `add`.
+    /// - `timezone`: The IANA name of the timezone the entry is matched in.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: Crontab added successfully.  (Or the crontab event already exists)
+    /// - `false`: Crontab failed to be added, or the timezone is unknown.
+    fn add_in_timezone(
+        &mut self, entry: CronTagged, retrigger: bool, timezone: String,
+    ) -> wasmtime::Result<bool> {
+        if let Err(err) = parse_crontab(&entry.when) {
+            tracing::warn!(app_name = %self.app_name(), tag = %entry.tag, "{err}");
+            return Ok(false);
+        }
+        let timezone = match timezone.parse::<Tz>() {
+            Ok(timezone) => timezone,
+            Err(err) => {
+                tracing::warn!(app_name = %self.app_name(), tag = %entry.tag, "Invalid crontab timezone: {err}");
+                return Ok(false);
+            },
+        };
+        Ok(cron_queue_add(
+            self.app_name(),
+            entry,
+            retrigger,
+            Some(timezone),
+        ))
     }
 
     /// # Schedule A Single cron event after a fixed delay.
@@ -88,6 +138,110 @@ impl Host for HermesRuntimeContext {
         Ok(cron_queue_ls(self.app_name(), tag))
     }
 
+    /// # List currently active cron schedule, with the schedule state of every entry.
+    ///
+    /// ## Parameters
+    ///
+    /// - `tag`: Optional, the tag to limit the list to.  If `none` then all crons listed.
+    ///
+    /// ## Returns
+    ///
+    /// - A list of the schedule state of the crontab entries, including their misfire
+    ///   policy and their last and next fire timestamps. The list is sorted from most
+    ///   crontab that will trigger soonest to latest.
+    fn ls_schedule(&mut self, tag: Option<CronEventTag>) -> wasmtime::Result<Vec<CronSchedule>> {
+        Ok(cron_queue_ls_schedule(self.app_name(), tag))
+    }
+
+    /// # Set the misfire policy of a crontab entry.
+    ///
+    /// A tick is missed when it is delivered more than a minute late, for example
+    /// because the host was suspended.
+    ///
+    /// ## Parameters
+    ///
+    /// - `entry`: The crontab entry to set the misfire policy of.
+    /// - `policy`: What to do with the missed ticks of the entry.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The misfire policy was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_misfire_policy(
+        &mut self, entry: CronTagged, policy: MisfirePolicy,
+    ) -> wasmtime::Result<bool> {
+        Ok(cron_queue_set_misfire_policy(
+            self.app_name(),
+            entry,
+            policy,
+        ))
+    }
+
+    /// # Set the jitter window of a crontab entry.
+    ///
+    /// Spreads the deliveries of the entry randomly, e.g. so the events of many apps
+    /// adding the same crontab entry do not all reach the event queue at once.
+    ///
+    /// ## Parameters
+    ///
+    /// - `entry`: The crontab entry to set the jitter window of.
+    /// - `window`: Every tick of the entry, including the pending one, is delivered after
+    ///   a random delay of up to this many nanoseconds.  `0` disables the jitter.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The jitter window was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_jitter(&mut self, entry: CronTagged, window: Instant) -> wasmtime::Result<bool> {
+        Ok(cron_queue_set_jitter(self.app_name(), entry, window))
+    }
+
+    /// # Set the overlap policy of a crontab entry.
+    ///
+    /// Lets a module with long running `on-cron` handlers opt out of receiving a tick
+    /// of the entry while the previous one is not handled yet.
+    ///
+    /// ## Parameters
+    ///
+    /// - `entry`: The crontab entry to set the overlap policy of.
+    /// - `policy`: What to do with a tick of the entry while its previous tick is waiting
+    ///   in the event queue or being handled.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The overlap policy was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_overlap_policy(
+        &mut self, entry: CronTagged, policy: OverlapPolicy,
+    ) -> wasmtime::Result<bool> {
+        Ok(cron_queue_set_overlap_policy(
+            self.app_name(),
+            entry,
+            policy,
+        ))
+    }
+
+    /// # Set the priority of a crontab entry.
+    ///
+    /// Lets a module have the ticks of a latency sensitive entry delivered ahead of the
+    /// other events waiting in the event queue, or the ticks of a bulk housekeeping
+    /// entry after them.
+    ///
+    /// ## Parameters
+    ///
+    /// - `entry`: The crontab entry to set the priority of.
+    /// - `priority`: The priority of the deliveries of the entry.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The priority was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_priority(
+        &mut self, entry: CronTagged, priority: CronPriority,
+    ) -> wasmtime::Result<bool> {
+        Ok(cron_queue_set_priority(self.app_name(), entry, priority))
+    }
+
     /// # Remove the requested crontab.
     ///
     /// Allows for management of scheduled cron events.
@@ -105,6 +259,62 @@ impl Host for HermesRuntimeContext {
         Ok(cron_queue_rm(self.app_name(), entry))
     }
 
+    /// # Cancel all the crontabs of a tag.
+    ///
+    /// Withdraws pending delays and recurring crontabs without knowing their schedule,
+    /// e.g. to cancel a retry scheduled with `delay`.
+    ///
+    /// ## Parameters
+    ///
+    /// - `tag`: The tag of the crontabs to cancel.
+    ///
+    /// ## Returns
+    ///
+    /// - The number of crontabs cancelled, they will not trigger.
+    fn cancel(&mut self, tag: CronEventTag) -> wasmtime::Result<u32> {
+        let cancelled = cron_queue_ls(self.app_name(), Some(tag))
+            .into_iter()
+            .filter(|(entry, _)| cron_queue_rm(self.app_name(), entry.clone()))
+            .count();
+        Ok(u32::try_from(cancelled)?)
+    }
+
+    /// # List the latest executions of the crontab entries.
+    ///
+    /// ## Parameters
+    ///
+    /// - `tag`: Optional, the tag to limit the list to.  If `none` then the executions of
+    ///   all tags are listed.
+    ///
+    /// ## Returns
+    ///
+    /// - The latest executions of the `on-cron` events of the app, oldest first.
+    fn history(&mut self, tag: Option<CronEventTag>) -> wasmtime::Result<Vec<CronExecution>> {
+        Ok(history(self.app_name(), tag.as_ref())
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// # Parse a crontab expression.
+    ///
+    /// Validates a standard crontab expression, so a schedule written by hand or read
+    /// from configuration can be checked before it is added.
+    ///
+    /// ## Parameters
+    ///
+    /// - `expr` - Standard 5 fields crontab expression, `minute hour day month dow`, e.g.
+    ///   `*/5 * * * *` for every 5 minutes.
+    ///
+    /// ## Returns
+    ///
+    /// - `Ok(CronSched)`: The normalized `cron-sched` ready for use in the cron functions
+    ///   above.
+    /// - `Err(String)`: Why the expression is invalid, or never matches any time.
+    fn parse(&mut self, expr: String) -> wasmtime::Result<Result<CronSched, String>> {
+        Ok(parse_crontab(&expr))
+    }
+
     /// # Make a crontab entry from individual time values.
     ///
     /// Creates the properly formatted cron entry
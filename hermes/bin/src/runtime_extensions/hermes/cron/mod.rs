@@ -6,21 +6,86 @@ use std::{
 };
 
 use chrono::{Datelike, TimeDelta, Timelike, Utc};
+use saffron::Cron;
 
+pub(crate) use self::persist::StoredCrontab;
 use self::{event::OnCronEvent, queue::CronJobDelay};
-use crate::runtime_extensions::bindings::{
-    hermes::cron::api::{CronComponent, CronEventTag, CronSched, CronTagged, CronTime},
-    wasi::clocks::monotonic_clock::Instant,
+use crate::{
+    app::ApplicationName,
+    event::HermesEventPayload,
+    runtime_extensions::bindings::{
+        hermes::cron::api::{CronComponent, CronEventTag, CronSched, CronTagged, CronTime},
+        wasi::clocks::monotonic_clock::Instant,
+    },
 };
 
 mod event;
+pub(crate) mod history;
 mod host;
+mod overlap;
+mod persist;
 mod queue;
 mod state;
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
 
+/// Load the crontab entries of the app stored before the node restarted.
+pub(crate) fn stored_crontabs(app_name: &ApplicationName) -> Vec<StoredCrontab> {
+    persist::load(app_name).unwrap_or_else(|err| {
+        tracing::warn!(app_name = %app_name, "Failed to load the stored crontab entries: {err}");
+        Vec::new()
+    })
+}
+
+/// Reschedule the stored crontab entries of the app.
+///
+/// The ticks missed while the node was down fire at once, according to the misfire
+/// policy of their entry.
+pub(crate) fn restore_crontabs(app_name: &ApplicationName, crontabs: Vec<StoredCrontab>) {
+    state::cron_queue_restore(app_name, crontabs);
+}
+
+/// Stop scheduling the crontab entries of an unloaded app, and forget its execution
+/// history. The stored entries are restored when the app is loaded again.
+pub(crate) fn unload_app(app_name: &ApplicationName) {
+    state::cron_queue_unload(app_name);
+    history::remove_app(app_name);
+}
+
+/// Pause the delivery of the crontab events of the app, without removing its crontab
+/// entries. The pause is kept across restarts of the node.
+///
+/// Returns `false` if the app is already paused.
+pub(crate) fn pause(app_name: &ApplicationName) -> bool {
+    state::cron_queue_pause(app_name)
+}
+
+/// Resume the delivery of the crontab events of the app.
+///
+/// The ticks missed while the app was paused fire at once, according to the misfire
+/// policy of their entry: `skip` drops them, `coalesce` fires once, and
+/// `fire-immediately` fires once per missed tick.
+///
+/// Returns `false` if the app is not paused.
+pub(crate) fn resume(app_name: &ApplicationName) -> bool {
+    state::cron_queue_resume(app_name)
+}
+
+/// Whether the delivery of the crontab events of the app is paused.
+pub(crate) fn is_paused(app_name: &ApplicationName) -> bool {
+    state::cron_queue_is_paused(app_name)
+}
+
+/// Create the event of a tick of a crontab entry, e.g. to fire an entry by hand.
+pub(crate) fn tick_event(tag: CronEventTag, when: CronSched) -> impl HermesEventPayload {
+    OnCronEvent {
+        tag: CronTagged { when, tag },
+        last: false,
+        timezone: None,
+    }
+}
+
 /// Cron Error.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -57,10 +122,35 @@ pub(crate) fn mkdelay_crontab(
         event: OnCronEvent {
             tag: CronTagged { when, tag },
             last: true,
+            timezone: None,
         },
     })
 }
 
+/// Number of fields of a crontab expression.
+const CRONTAB_FIELDS: usize = 5;
+
+/// Parse and validate a crontab expression, returning it normalized.
+pub(crate) fn parse_crontab(expr: &str) -> Result<CronSched, String> {
+    let fields: Vec<_> = expr.split_whitespace().collect();
+    if fields.len() != CRONTAB_FIELDS {
+        return Err(format!(
+            "Crontab expression `{expr}` has {} fields, expected {CRONTAB_FIELDS}: `minute hour day month dow`",
+            fields.len()
+        ));
+    }
+    let when = fields.join(" ");
+    let cron: Cron = when
+        .parse()
+        .map_err(|err| format!("Invalid crontab expression `{expr}`: {err}"))?;
+    if !cron.any() {
+        return Err(format!(
+            "Crontab expression `{expr}` never matches any time"
+        ));
+    }
+    Ok(when)
+}
+
 /// Convert `CronTime` arguments to a `CronSched`.
 pub(crate) fn mkcron_impl(
     dow: &CronTime, month: &CronTime, day: &CronTime, hour: &CronTime, minute: &CronTime,
@@ -406,6 +496,18 @@ mod tests {
         assert_eq!(tag, "test");
     }
 
+    #[test]
+    fn test_parse_crontab() {
+        assert_eq!(parse_crontab("*/5 * * * *"), Ok("*/5 * * * *".to_string()));
+        assert_eq!(
+            parse_crontab(" 0  12 * *  1-5 "),
+            Ok("0 12 * * 1-5".to_string())
+        );
+        assert!(parse_crontab("* * * *").is_err());
+        assert!(parse_crontab("61 * * * *").is_err());
+        assert!(parse_crontab("0 0 31 2 *").is_err());
+    }
+
     #[test]
     fn test_cron_component_merge() {
         assert_eq!(CronComponent::At(1).merge(CronComponent::At(2)), None);
@@ -0,0 +1,168 @@
+//! Overlap of the deliveries of the crontab entries.
+//!
+//! Every tick of a crontab entry sent to the event queue is tracked from the moment it is
+//! queued until the event is dropped, i.e. handled by every module of the app. A tick is
+//! only sent if the overlap policy of its entry allows it, given the deliveries of the
+//! entry still waiting in the event queue or being handled.
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use super::event::{CronDuration, OnCronEvent};
+use crate::{
+    app::ApplicationName,
+    event::HermesEventPayload,
+    runtime_extensions::bindings::hermes::cron::api::{CronTagged, OverlapPolicy},
+    wasm::module::ModuleInstance,
+};
+
+/// Deliveries of the crontab entries of every app not dropped yet.
+static DELIVERIES: Lazy<DashMap<(ApplicationName, CronTagged), Deliveries>> =
+    Lazy::new(DashMap::new);
+
+/// Deliveries of a crontab entry not dropped yet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Deliveries {
+    /// Deliveries waiting in the event queue.
+    waiting: usize,
+    /// Deliveries being handled.
+    running: usize,
+}
+
+/// A tick of a crontab entry delivered to an app, tracked until it is dropped.
+pub(super) struct CronDelivery {
+    /// App the tick is delivered to.
+    app_name: ApplicationName,
+    /// The crontab event.
+    event: OnCronEvent,
+    /// When the tick was scheduled to fire.
+    scheduled: CronDuration,
+    /// When the crontab entry fires next, `None` if it does not retrigger.
+    next_fire: Option<CronDuration>,
+    /// Whether a module started handling the tick.
+    started: std::sync::atomic::AtomicBool,
+}
+
+impl CronDelivery {
+    /// Create the delivery of a tick of a crontab entry to the app, if the overlap
+    /// policy of the entry allows it.
+    pub(super) fn admit(
+        app_name: &ApplicationName, event: OnCronEvent, policy: OverlapPolicy,
+        scheduled: CronDuration, next_fire: Option<CronDuration>,
+    ) -> Option<Self> {
+        let mut deliveries = DELIVERIES
+            .entry((app_name.clone(), event.tag.clone()))
+            .or_default();
+        let admitted = match policy {
+            OverlapPolicy::Queue => true,
+            OverlapPolicy::Skip => deliveries.waiting == 0 && deliveries.running == 0,
+            OverlapPolicy::Coalesce => deliveries.waiting == 0,
+        };
+        if !admitted {
+            tracing::debug!(app_name = %app_name, tag = event.tag.tag.as_str(), "Cron tick dropped, its previous delivery is not handled yet");
+            return None;
+        }
+        deliveries.waiting = deliveries.waiting.saturating_add(1);
+        Some(Self {
+            app_name: app_name.clone(),
+            event,
+            scheduled,
+            next_fire,
+            started: std::sync::atomic::AtomicBool::new(false),
+        })
+    }
+
+    /// Update the deliveries of the entry.
+    fn update(&self, update: impl FnOnce(&mut Deliveries)) {
+        let key = (self.app_name.clone(), self.event.tag.clone());
+        if let Some(mut deliveries) = DELIVERIES.get_mut(&key) {
+            update(&mut deliveries);
+        }
+        DELIVERIES.remove_if(&key, |_, deliveries| *deliveries == Deliveries::default());
+    }
+}
+
+impl HermesEventPayload for CronDelivery {
+    fn event_name(&self) -> &str {
+        self.event.event_name()
+    }
+
+    fn execute(&self, module: &mut ModuleInstance) -> anyhow::Result<()> {
+        if !self.started.swap(true, std::sync::atomic::Ordering::AcqRel) {
+            self.update(|deliveries| {
+                deliveries.waiting = deliveries.waiting.saturating_sub(1);
+                deliveries.running = deliveries.running.saturating_add(1);
+            });
+        }
+        self.event
+            .deliver(module, Some(self.scheduled), self.next_fire)
+    }
+}
+
+impl Drop for CronDelivery {
+    fn drop(&mut self) {
+        let started = *self.started.get_mut();
+        self.update(|deliveries| {
+            if started {
+                deliveries.running = deliveries.running.saturating_sub(1);
+            } else {
+                deliveries.waiting = deliveries.waiting.saturating_sub(1);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(tag: &str) -> OnCronEvent {
+        OnCronEvent {
+            tag: CronTagged {
+                when: "* * * * *".to_string(),
+                tag: tag.to_string(),
+            },
+            last: false,
+            timezone: None,
+        }
+    }
+
+    fn start(delivery: &CronDelivery) {
+        delivery
+            .started
+            .store(true, std::sync::atomic::Ordering::Release);
+        delivery.update(|deliveries| {
+            deliveries.waiting = deliveries.waiting.saturating_sub(1);
+            deliveries.running = deliveries.running.saturating_add(1);
+        });
+    }
+
+    #[test]
+    fn admit_test() {
+        let admit = |app_name: &ApplicationName, tag: &str, policy| {
+            CronDelivery::admit(app_name, event(tag), policy, CronDuration::from(0), None)
+        };
+        let app_name = ApplicationName("overlap".to_string());
+
+        let first = admit(&app_name, "skip", OverlapPolicy::Skip).unwrap();
+        assert!(admit(&app_name, "skip", OverlapPolicy::Skip).is_none());
+        start(&first);
+        assert!(admit(&app_name, "skip", OverlapPolicy::Skip).is_none());
+        drop(first);
+        assert!(admit(&app_name, "skip", OverlapPolicy::Skip).is_some());
+
+        let first = admit(&app_name, "coalesce", OverlapPolicy::Coalesce).unwrap();
+        assert!(admit(&app_name, "coalesce", OverlapPolicy::Coalesce).is_none());
+        start(&first);
+        let second = admit(&app_name, "coalesce", OverlapPolicy::Coalesce).unwrap();
+        assert!(admit(&app_name, "coalesce", OverlapPolicy::Coalesce).is_none());
+        drop(first);
+        drop(second);
+
+        let first = admit(&app_name, "queue", OverlapPolicy::Queue).unwrap();
+        let second = admit(&app_name, "queue", OverlapPolicy::Queue).unwrap();
+        drop(first);
+        drop(second);
+        assert!(DELIVERIES.is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+//! Persistence of the crontab entries across restarts of the node.
+//!
+//! Every crontab entry of an app is stored as a job of the `cron` kind in the jobs
+//! database, keyed by its tag and schedule, along with the timestamp of its next tick.
+//! The kind has no job handler, the cron queue fires the entries itself. When the app is
+//! loaded again its entries are restored at their stored tick, so the ticks missed while
+//! the node was down are delivered according to the misfire policy of the entry.
+
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use super::event::{CronDuration, OnCronEvent};
+use crate::{
+    app::ApplicationName,
+    jobs,
+    runtime_extensions::bindings::hermes::cron::api::{
+        CronPriority, CronTagged, MisfirePolicy, OverlapPolicy,
+    },
+};
+
+/// Job kind of the stored crontab entries.
+const JOB_KIND: &str = "cron";
+
+/// Job kind marking the cron queue of an app as paused.
+const PAUSED_JOB_KIND: &str = "cron-paused";
+
+/// Key of the job marking the cron queue of an app as paused.
+const PAUSED_KEY: &str = "paused";
+
+/// Crontab entry of an app, as stored.
+#[derive(Debug, Clone)]
+pub(crate) struct StoredCrontab {
+    /// Timestamp of the next tick of the entry.
+    pub(super) timestamp: CronDuration,
+    /// The crontab event.
+    pub(super) event: OnCronEvent,
+    /// What to do with the missed ticks of the entry.
+    pub(super) misfire: MisfirePolicy,
+    /// When the entry last fired.
+    pub(super) last_fire: Option<CronDuration>,
+    /// Longest random delay of the ticks of the entry.
+    pub(super) jitter: CronDuration,
+    /// What to do with a tick of the entry while the previous one is not handled yet.
+    pub(super) overlap: OverlapPolicy,
+    /// Priority of the ticks of the entry in the event queue.
+    pub(super) priority: CronPriority,
+}
+
+/// Payload of the job of a stored crontab entry.
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    /// Tag of the entry.
+    tag: String,
+    /// Schedule of the entry.
+    when: String,
+    /// Whether the entry fires only once.
+    last: bool,
+    /// Misfire policy of the entry.
+    misfire: String,
+    /// When the entry last fired, in nanoseconds since the UNIX epoch.
+    last_fire: Option<u64>,
+    /// Next tick of the entry, in nanoseconds since the UNIX epoch.
+    timestamp: u64,
+    /// IANA name of the timezone the entry is matched in, UTC if `None`.
+    #[serde(default)]
+    timezone: Option<String>,
+    /// Jitter window of the entry, in nanoseconds.
+    #[serde(default)]
+    jitter: u64,
+    /// Overlap policy of the entry.
+    #[serde(default)]
+    overlap: String,
+    /// Priority of the entry.
+    #[serde(default)]
+    priority: String,
+}
+
+/// Store a crontab entry of the app, replacing the stored entry with the same tag and
+/// schedule.
+pub(super) fn save(app_name: &ApplicationName, crontab: &StoredCrontab) -> anyhow::Result<()> {
+    let payload = Payload::from(crontab);
+    let run_at = SystemTime::UNIX_EPOCH + Duration::from_nanos(crontab.timestamp.into());
+    jobs::schedule(
+        JOB_KIND,
+        app_name,
+        &key(&crontab.event.tag),
+        &serde_json::to_vec(&payload)?,
+        run_at,
+    )
+}
+
+/// Remove the stored crontab entry of the app.
+pub(super) fn remove(app_name: &ApplicationName, cron_tagged: &CronTagged) -> anyhow::Result<()> {
+    jobs::unschedule(JOB_KIND, app_name, &key(cron_tagged))?;
+    Ok(())
+}
+
+/// Load the stored crontab entries of the app.
+///
+/// Entries which can not be read are skipped.
+pub(super) fn load(app_name: &ApplicationName) -> anyhow::Result<Vec<StoredCrontab>> {
+    Ok(jobs::scheduled(JOB_KIND, app_name)?
+        .into_iter()
+        .filter_map(|(key, payload, _run_at)| {
+            match serde_json::from_slice::<Payload>(&payload)
+                .map_err(anyhow::Error::from)
+                .and_then(StoredCrontab::try_from)
+            {
+                Ok(crontab) => Some(crontab),
+                Err(err) => {
+                    tracing::warn!(app_name = %app_name, key = key.as_str(), "Invalid stored crontab entry: {err}");
+                    None
+                },
+            }
+        })
+        .collect())
+}
+
+/// Store whether the cron queue of the app is paused.
+pub(super) fn save_paused(app_name: &ApplicationName, paused: bool) -> anyhow::Result<()> {
+    if paused {
+        jobs::schedule(
+            PAUSED_JOB_KIND,
+            app_name,
+            PAUSED_KEY,
+            &[],
+            SystemTime::now(),
+        )
+    } else {
+        jobs::unschedule(PAUSED_JOB_KIND, app_name, PAUSED_KEY)?;
+        Ok(())
+    }
+}
+
+/// Whether the cron queue of the app was stored as paused.
+pub(super) fn is_paused(app_name: &ApplicationName) -> anyhow::Result<bool> {
+    Ok(!jobs::scheduled(PAUSED_JOB_KIND, app_name)?.is_empty())
+}
+
+impl From<&StoredCrontab> for Payload {
+    fn from(crontab: &StoredCrontab) -> Self {
+        Self {
+            tag: crontab.event.tag.tag.clone(),
+            when: crontab.event.tag.when.clone(),
+            last: crontab.event.last,
+            misfire: misfire_name(crontab.misfire).to_string(),
+            last_fire: crontab.last_fire.map(u64::from),
+            timestamp: crontab.timestamp.into(),
+            timezone: crontab.event.timezone.map(|tz| tz.name().to_string()),
+            jitter: crontab.jitter.into(),
+            overlap: overlap_name(crontab.overlap).to_string(),
+            priority: priority_name(crontab.priority).to_string(),
+        }
+    }
+}
+
+impl TryFrom<Payload> for StoredCrontab {
+    type Error = anyhow::Error;
+
+    /// Restores a stored crontab entry, failing if its timezone is not known, e.g. when
+    /// stored by a node with a newer timezone database.
+    fn try_from(payload: Payload) -> anyhow::Result<Self> {
+        let timezone = payload
+            .timezone
+            .map(|tz| {
+                tz.parse()
+                    .map_err(|err| anyhow::anyhow!("Unknown crontab timezone `{tz}`: {err}"))
+            })
+            .transpose()?;
+        Ok(Self {
+            timestamp: payload.timestamp.into(),
+            event: OnCronEvent {
+                tag: CronTagged {
+                    when: payload.when,
+                    tag: payload.tag,
+                },
+                last: payload.last,
+                timezone,
+            },
+            misfire: misfire_from_name(&payload.misfire),
+            last_fire: payload.last_fire.map(CronDuration::from),
+            jitter: payload.jitter.into(),
+            overlap: overlap_from_name(&payload.overlap),
+            priority: priority_from_name(&payload.priority),
+        })
+    }
+}
+
+/// Key of the job of a crontab entry.
+fn key(cron_tagged: &CronTagged) -> String {
+    serde_json::json!([cron_tagged.tag, cron_tagged.when]).to_string()
+}
+
+/// Name of a misfire policy, as stored.
+fn misfire_name(misfire: MisfirePolicy) -> &'static str {
+    match misfire {
+        MisfirePolicy::Skip => "skip",
+        MisfirePolicy::Coalesce => "coalesce",
+        MisfirePolicy::FireImmediately => "fire-immediately",
+    }
+}
+
+/// Misfire policy from its stored name, defaulting to `coalesce`.
+fn misfire_from_name(name: &str) -> MisfirePolicy {
+    match name {
+        "skip" => MisfirePolicy::Skip,
+        "fire-immediately" => MisfirePolicy::FireImmediately,
+        _ => MisfirePolicy::Coalesce,
+    }
+}
+
+/// Name of an overlap policy, as stored.
+fn overlap_name(overlap: OverlapPolicy) -> &'static str {
+    match overlap {
+        OverlapPolicy::Queue => "queue",
+        OverlapPolicy::Skip => "skip",
+        OverlapPolicy::Coalesce => "coalesce",
+    }
+}
+
+/// Overlap policy from its stored name, defaulting to `queue`.
+fn overlap_from_name(name: &str) -> OverlapPolicy {
+    match name {
+        "skip" => OverlapPolicy::Skip,
+        "coalesce" => OverlapPolicy::Coalesce,
+        _ => OverlapPolicy::Queue,
+    }
+}
+
+/// Name of a crontab priority, as stored.
+fn priority_name(priority: CronPriority) -> &'static str {
+    match priority {
+        CronPriority::High => "high",
+        CronPriority::Normal => "normal",
+        CronPriority::Low => "low",
+    }
+}
+
+/// Crontab priority from its stored name, defaulting to `normal`.
+fn priority_from_name(name: &str) -> CronPriority {
+    match name {
+        "high" => CronPriority::High,
+        "low" => CronPriority::Low,
+        _ => CronPriority::Normal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_test() {
+        let crontab = StoredCrontab {
+            timestamp: 1_000.into(),
+            event: OnCronEvent {
+                tag: CronTagged {
+                    when: "* * * * *".to_string(),
+                    tag: "tag".to_string(),
+                },
+                last: false,
+                timezone: Some(chrono_tz::Europe::Berlin),
+            },
+            misfire: MisfirePolicy::FireImmediately,
+            last_fire: Some(500.into()),
+            jitter: 100.into(),
+            overlap: OverlapPolicy::Skip,
+            priority: CronPriority::Low,
+        };
+        let payload = serde_json::to_vec(&Payload::from(&crontab)).unwrap();
+        let restored =
+            StoredCrontab::try_from(serde_json::from_slice::<Payload>(&payload).unwrap()).unwrap();
+        assert_eq!(restored.event, crontab.event);
+        assert_eq!(restored.timestamp, crontab.timestamp);
+        assert_eq!(restored.last_fire, crontab.last_fire);
+        assert_eq!(restored.jitter, crontab.jitter);
+        assert!(matches!(restored.misfire, MisfirePolicy::FireImmediately));
+        assert!(matches!(restored.overlap, OverlapPolicy::Skip));
+        assert!(matches!(restored.priority, CronPriority::Low));
+        assert_eq!(key(&crontab.event.tag), r#"["tag","* * * * *"]"#);
+
+        let mut unknown_timezone = Payload::from(&crontab);
+        unknown_timezone.timezone = Some("Mars/Olympus_Mons".to_string());
+        assert!(StoredCrontab::try_from(unknown_timezone).is_err());
+    }
+}
@@ -1,20 +1,62 @@
 //! Cron Event Queue implementation.
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
+use rand::Rng;
 use tokio::sync::{mpsc, oneshot};
 
 use super::{
     event::{CronDuration, OnCronEvent},
-    state::{cron_queue_delay, cron_queue_trigger, send_hermes_on_cron_event},
+    persist::{self, StoredCrontab},
+    state::{cron_queue_trigger, send_hermes_on_cron_event},
     Error,
 };
 use crate::{
     app::ApplicationName,
-    runtime_extensions::bindings::hermes::cron::api::{CronEventTag, CronTagged},
+    runtime_extensions::bindings::hermes::cron::api::{
+        CronEventTag, CronPriority, CronSchedule, CronTagged, MisfirePolicy, OverlapPolicy,
+    },
 };
 
+/// A tick delivered later than this many nanoseconds is missed.
+const MISFIRE_THRESHOLD: u64 = 60_000_000_000;
+
+/// Most missed ticks delivered at once with the `fire-immediately` misfire policy.
+const MAX_CATCH_UP: u64 = 100;
+
+/// Longest the waiting task sleeps before checking the wall clock again.
+/// The monotonic clock stops while the host is suspended, so a single long sleep
+/// would wake up late.
+const MAX_WAITING_TASK_SLEEP: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Schedule state of a crontab entry.
+#[derive(Debug, Clone, Copy)]
+struct ScheduleState {
+    /// What to do with the missed ticks of the entry.
+    misfire: MisfirePolicy,
+    /// When the entry last fired.
+    last_fire: Option<CronDuration>,
+    /// Longest random delay of the ticks of the entry.
+    jitter: CronDuration,
+    /// What to do with a tick while the previous one is not handled yet.
+    overlap: OverlapPolicy,
+    /// Priority of the ticks in the event queue.
+    priority: CronPriority,
+}
+
+impl Default for ScheduleState {
+    fn default() -> Self {
+        Self {
+            misfire: MisfirePolicy::Coalesce,
+            last_fire: None,
+            jitter: 0.into(),
+            overlap: OverlapPolicy::Queue,
+            priority: CronPriority::Normal,
+        }
+    }
+}
+
 /// Cron Job Delay.
 #[derive(Debug)]
 pub(crate) struct CronJobDelay {
@@ -39,12 +81,50 @@ pub(crate) enum CronJob {
     Delay(ApplicationName, CronJobDelay, oneshot::Sender<bool>),
     /// Remove a cron job from the given app.
     Remove(ApplicationName, CronTagged, oneshot::Sender<bool>),
+    /// List the schedule state of the cron jobs for the given app.
+    ListSchedule(
+        ApplicationName,
+        Option<CronEventTag>,
+        oneshot::Sender<Vec<CronSchedule>>,
+    ),
+    /// Set the misfire policy of a cron job of the given app.
+    SetMisfirePolicy(
+        ApplicationName,
+        CronTagged,
+        MisfirePolicy,
+        oneshot::Sender<bool>,
+    ),
+    /// Set the jitter window of a cron job of the given app.
+    SetJitter(
+        ApplicationName,
+        CronTagged,
+        CronDuration,
+        oneshot::Sender<bool>,
+    ),
+    /// Set the overlap policy of a cron job of the given app.
+    SetOverlapPolicy(
+        ApplicationName,
+        CronTagged,
+        OverlapPolicy,
+        oneshot::Sender<bool>,
+    ),
+    /// Set the priority of a cron job of the given app.
+    SetPriority(
+        ApplicationName,
+        CronTagged,
+        CronPriority,
+        oneshot::Sender<bool>,
+    ),
 }
 
 /// The crontab queue task runs in the background.
 pub(crate) struct CronEventQueue {
     /// The crontab events.
     events: DashMap<ApplicationName, BTreeMap<CronDuration, HashSet<OnCronEvent>>>,
+    /// The schedule state of the crontab entries.
+    schedules: DashMap<ApplicationName, HashMap<CronTagged, ScheduleState>>,
+    /// The apps whose crontab events are not delivered.
+    paused: DashSet<ApplicationName>,
     /// Send events to the crontab queue.
     sender: Option<mpsc::Sender<CronJob>>,
     /// Next scheduled Cron Task.
@@ -59,6 +139,8 @@ impl CronEventQueue {
     pub(crate) fn new(sender: Option<mpsc::Sender<CronJob>>) -> Self {
         Self {
             events: DashMap::default(),
+            schedules: DashMap::default(),
+            paused: DashSet::default(),
             sender,
             waiting_event: DashMap::with_capacity(1),
         }
@@ -77,6 +159,7 @@ impl CronEventQueue {
     pub(crate) fn add_event(
         &self, app_name: ApplicationName, timestamp: CronDuration, on_cron_event: OnCronEvent,
     ) {
+        self.persist(&app_name, timestamp, &on_cron_event);
         self.events
             .entry(app_name)
             .and_modify(|e| {
@@ -96,14 +179,14 @@ impl CronEventQueue {
         if let Some(app) = self.events.get(app_name) {
             app.iter().fold(vec![], |mut v, (_, cron_events)| {
                 if let Some(tag) = cron_tagged {
-                    for OnCronEvent { tag, last } in cron_events
+                    for OnCronEvent { tag, last, .. } in cron_events
                         .iter()
                         .filter(|event| event.tag.tag == tag.clone())
                     {
                         v.push((tag.clone(), *last));
                     }
                 } else {
-                    for OnCronEvent { tag, last } in cron_events {
+                    for OnCronEvent { tag, last, .. } in cron_events {
                         v.push((tag.clone(), *last));
                     }
                 };
@@ -114,6 +197,260 @@ impl CronEventQueue {
         }
     }
 
+    /// List the schedule state of the crontab entries for the given app.
+    pub(crate) fn ls_schedule(
+        &self, app_name: &ApplicationName, tag: &Option<CronEventTag>,
+    ) -> Vec<CronSchedule> {
+        let Some(app) = self.events.get(app_name) else {
+            return vec![];
+        };
+        app.iter()
+            .flat_map(|(ts, cron_events)| cron_events.iter().map(move |event| (ts, event)))
+            .filter(|(_, event)| tag.as_ref().map_or(true, |tag| event.tag.tag == *tag))
+            .map(
+                |(
+                    ts,
+                    OnCronEvent {
+                        tag,
+                        last,
+                        timezone,
+                    },
+                )| {
+                    let state = self.schedule_state(app_name, tag);
+                    CronSchedule {
+                        entry: tag.clone(),
+                        retrigger: !last,
+                        misfire: state.misfire,
+                        last_fire: state.last_fire.map(u64::from),
+                        next_fire: Some((*ts).into()),
+                        timezone: timezone.map(|tz| tz.name().to_string()),
+                        jitter: state.jitter.into(),
+                        overlap: state.overlap,
+                        priority: state.priority,
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Set the misfire policy of a crontab entry for the given app.
+    ///
+    /// Returns `false` if the crontab entry does not exist.
+    pub(crate) fn set_misfire_policy(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged, misfire: MisfirePolicy,
+    ) -> bool {
+        self.update_schedule(app_name, cron_tagged, |state| state.misfire = misfire)
+    }
+
+    /// Set the overlap policy of a crontab entry for the given app.
+    ///
+    /// Returns `false` if the crontab entry does not exist.
+    pub(crate) fn set_overlap_policy(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged, overlap: OverlapPolicy,
+    ) -> bool {
+        self.update_schedule(app_name, cron_tagged, |state| state.overlap = overlap)
+    }
+
+    /// Set the priority of a crontab entry for the given app.
+    ///
+    /// Returns `false` if the crontab entry does not exist.
+    pub(crate) fn set_priority(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged, priority: CronPriority,
+    ) -> bool {
+        self.update_schedule(app_name, cron_tagged, |state| state.priority = priority)
+    }
+
+    /// Set the jitter window of a crontab entry for the given app.
+    ///
+    /// The pending tick of the entry is delayed within the window too, unless the entry
+    /// does not retrigger.
+    ///
+    /// Returns `false` if the crontab entry does not exist.
+    pub(crate) fn set_jitter(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged, jitter: CronDuration,
+    ) -> bool {
+        if !self.update_schedule(app_name, cron_tagged, |state| state.jitter = jitter) {
+            return false;
+        }
+        for (ts, event) in self.scheduled(app_name, cron_tagged) {
+            if event.last {
+                continue;
+            }
+            // Delay the pending tick from its scheduled time, not from a delayed one.
+            let Some(tick) = event.tick_from(None) else {
+                continue;
+            };
+            self.remove_pending(app_name, ts, &event);
+            self.add_event(app_name.clone(), with_jitter(tick, jitter), event);
+        }
+        true
+    }
+
+    /// Update the schedule state of a crontab entry for the given app.
+    ///
+    /// Returns `false` if the crontab entry does not exist.
+    fn update_schedule(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged,
+        update: impl FnOnce(&mut ScheduleState),
+    ) -> bool {
+        let scheduled = self.scheduled(app_name, cron_tagged);
+        if scheduled.is_empty() {
+            return false;
+        }
+        update(
+            self.schedules
+                .entry(app_name.clone())
+                .or_default()
+                .entry(cron_tagged.clone())
+                .or_default(),
+        );
+        for (ts, event) in scheduled {
+            self.persist(app_name, ts, &event);
+        }
+        true
+    }
+
+    /// List the pending events of a crontab entry for the given app, with their
+    /// timestamp.
+    fn scheduled(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged,
+    ) -> Vec<(CronDuration, OnCronEvent)> {
+        self.events
+            .get(app_name)
+            .map(|app| {
+                app.iter()
+                    .flat_map(|(ts, events)| events.iter().map(move |event| (*ts, event)))
+                    .filter(|(_, event)| event.tag == *cron_tagged)
+                    .map(|(ts, event)| (ts, event.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Remove a pending event scheduled at `timestamp` for the given app.
+    fn remove_pending(
+        &self, app_name: &ApplicationName, timestamp: CronDuration, on_cron_event: &OnCronEvent,
+    ) {
+        if let Some(mut app) = self.events.get_mut(app_name) {
+            if let Some(events) = app.get_mut(&timestamp) {
+                events.remove(on_cron_event);
+                if events.is_empty() {
+                    app.remove(&timestamp);
+                }
+            }
+        }
+    }
+
+    /// Restore the stored crontab entries of the app into the queue.
+    ///
+    /// Entries whose tick passed while the node was down are due at once, and fire
+    /// according to their misfire policy when the queue is triggered.
+    ///
+    /// The schedule state set again by the app while it was initialized takes precedence
+    /// over the stored one, e.g. so a changed misfire policy applies to the ticks missed
+    /// while the node was down. Only when the entry last fired is restored then.
+    pub(crate) fn restore(&self, app_name: &ApplicationName, crontabs: Vec<StoredCrontab>) {
+        match persist::is_paused(app_name) {
+            Ok(true) => {
+                self.paused.insert(app_name.clone());
+            },
+            Ok(false) => (),
+            Err(err) => {
+                tracing::warn!(app_name = %app_name, "Failed to load the paused cron queue state: {err}");
+            },
+        }
+        for crontab in crontabs {
+            {
+                let mut schedules = self.schedules.entry(app_name.clone()).or_default();
+                if let Some(state) = schedules.get_mut(&crontab.event.tag) {
+                    state.last_fire = crontab.last_fire;
+                } else {
+                    schedules.insert(crontab.event.tag.clone(), ScheduleState {
+                        misfire: crontab.misfire,
+                        last_fire: crontab.last_fire,
+                        jitter: crontab.jitter,
+                        overlap: crontab.overlap,
+                        priority: crontab.priority,
+                    });
+                }
+            }
+            self.add_event(app_name.clone(), crontab.timestamp, crontab.event);
+        }
+    }
+
+    /// Store a crontab entry of the app scheduled at `timestamp`, so it is restored when
+    /// the node restarts.
+    fn persist(
+        &self, app_name: &ApplicationName, timestamp: CronDuration, on_cron_event: &OnCronEvent,
+    ) {
+        let state = self.schedule_state(app_name, &on_cron_event.tag);
+        let crontab = StoredCrontab {
+            timestamp,
+            event: on_cron_event.clone(),
+            misfire: state.misfire,
+            last_fire: state.last_fire,
+            jitter: state.jitter,
+            overlap: state.overlap,
+            priority: state.priority,
+        };
+        if let Err(err) = persist::save(app_name, &crontab) {
+            tracing::warn!(app_name = %app_name, tag = on_cron_event.tag.tag.as_str(), "Failed to store crontab entry: {err}");
+        }
+    }
+
+    /// Get the schedule state of a crontab entry for the given app.
+    fn schedule_state(
+        &self, app_name: &ApplicationName, cron_tagged: &CronTagged,
+    ) -> ScheduleState {
+        self.schedules
+            .get(app_name)
+            .and_then(|schedules| schedules.get(cron_tagged).copied())
+            .unwrap_or_default()
+    }
+
+    /// Pause the delivery of the crontab events of the given app. Its entries stay
+    /// scheduled, and can still be added and removed.
+    ///
+    /// Returns `false` if the app is already paused.
+    pub(crate) fn pause(&self, app_name: &ApplicationName) -> bool {
+        if !self.paused.insert(app_name.clone()) {
+            return false;
+        }
+        if let Err(err) = persist::save_paused(app_name, true) {
+            tracing::warn!(app_name = %app_name, "Failed to store the paused cron queue state: {err}");
+        }
+        true
+    }
+
+    /// Resume the delivery of the crontab events of the given app.
+    ///
+    /// The ticks missed while the app was paused are due at once, and fire according to
+    /// the misfire policy of their entry when the queue is triggered.
+    ///
+    /// Returns `false` if the app is not paused.
+    pub(crate) fn resume(&self, app_name: &ApplicationName) -> bool {
+        if self.paused.remove(app_name).is_none() {
+            return false;
+        }
+        if let Err(err) = persist::save_paused(app_name, false) {
+            tracing::warn!(app_name = %app_name, "Failed to store the paused cron queue state: {err}");
+        }
+        true
+    }
+
+    /// Whether the delivery of the crontab events of the given app is paused.
+    pub(crate) fn is_paused(&self, app_name: &ApplicationName) -> bool {
+        self.paused.contains(app_name)
+    }
+
+    /// Remove the crontab entries of the given app from the queue, when it is unloaded.
+    /// The stored entries are kept, so they are restored when the app is loaded again.
+    pub(crate) fn unload(&self, app_name: &ApplicationName) {
+        self.events.remove(app_name);
+        self.schedules.remove(app_name);
+        self.paused.remove(app_name);
+    }
+
     /// Remove a crontab entry for the given app.
     pub(crate) fn rm_event(&self, app_name: &ApplicationName, cron_tagged: &CronTagged) -> bool {
         let mut response = false;
@@ -131,6 +468,10 @@ impl CronEventQueue {
                 !events.is_empty()
             });
         }
+        if let Some(mut schedules) = self.schedules.get_mut(app_name) {
+            schedules.remove(cron_tagged);
+        }
+        unpersist(app_name, cron_tagged);
         response
     }
 
@@ -189,7 +530,7 @@ impl CronEventQueue {
 
     /// Pop the first item from all the `BTreeMap`s belonging
     /// to each `HermesAppName` in the queue. Then send the `OnCronEvent`s
-    /// to the Hermes Event Queue.
+    /// to the Hermes Event Queue, according to their misfire policy.
     ///
     /// This method will also re-schedule the events that have `last = false`.
     fn pop_app_queues_and_send(
@@ -198,13 +539,43 @@ impl CronEventQueue {
         for app_name in app_names {
             if let Some(events) = self.pop_from_app_queue(app_name, ts) {
                 for on_cron_event in events {
-                    send_hermes_on_cron_event(app_name, on_cron_event.clone())?;
-                    if !on_cron_event.last {
-                        // Re-schedule the event by calculating the next timestamp after now.
-                        if let Some(next_timestamp) = on_cron_event.tick_after(None) {
-                            let duration = next_timestamp - trigger_time;
-                            cron_queue_delay(app_name, duration.into(), on_cron_event.tag.tag)?;
+                    let mut state = self.schedule_state(app_name, &on_cron_event.tag);
+                    let fires = fire_count(&on_cron_event, state.misfire, ts, trigger_time);
+                    let next_fire = if on_cron_event.last {
+                        None
+                    } else {
+                        on_cron_event
+                            .tick_after(None)
+                            .map(|next_timestamp| with_jitter(next_timestamp, state.jitter))
+                    };
+                    for _ in 0..fires {
+                        send_hermes_on_cron_event(
+                            app_name,
+                            on_cron_event.clone(),
+                            state.overlap,
+                            state.priority,
+                            ts,
+                            next_fire,
+                        )?;
+                    }
+                    if fires > 0 {
+                        state.last_fire = Some(trigger_time);
+                    }
+
+                    if on_cron_event.last {
+                        if let Some(mut schedules) = self.schedules.get_mut(app_name) {
+                            schedules.remove(&on_cron_event.tag);
                         }
+                        unpersist(app_name, &on_cron_event.tag);
+                    } else if let Some(next_timestamp) = next_fire {
+                        // Re-schedule the event at its next timestamp after now.
+                        self.schedules
+                            .entry(app_name.clone())
+                            .or_default()
+                            .insert(on_cron_event.tag.clone(), state);
+                        self.add_event(app_name.clone(), next_timestamp, on_cron_event);
+                    } else {
+                        unpersist(app_name, &on_cron_event.tag);
                     }
                 }
             }
@@ -224,12 +595,13 @@ impl CronEventQueue {
     }
 
     /// Get the next timestamp to schedule, collected from all the `BTreeMap`s belonging
-    /// to each `HermesAppName` in the queue.
+    /// to each `HermesAppName` in the queue, except the paused ones.
     fn next_in_queue(&self) -> Option<(CronDuration, HashSet<ApplicationName>)> {
         // Start by fetching the first entry of every app, and putting it into a `BtreeMap`.
         let mut next_events: BTreeMap<CronDuration, HashSet<ApplicationName>> = self
             .events
             .iter()
+            .filter(|mut_ref| !self.paused.contains(mut_ref.key()))
             .fold(BTreeMap::new(), |mut acc, mut_ref| {
                 let (app_name, events) = mut_ref.pair();
                 if let Some((ts, _)) = events.first_key_value() {
@@ -245,12 +617,74 @@ impl CronEventQueue {
     }
 }
 
-/// Create a new thread that will sleep for `duration` nanoseconds
+/// Delay a tick by a random duration shorter than the jitter window.
+fn with_jitter(timestamp: CronDuration, jitter: CronDuration) -> CronDuration {
+    let window = u64::from(jitter);
+    if window == 0 {
+        return timestamp;
+    }
+    u64::from(timestamp)
+        .saturating_add(rand::thread_rng().gen_range(0..window))
+        .into()
+}
+
+/// Remove the stored crontab entry of the app, it is not restored when the node restarts.
+fn unpersist(app_name: &ApplicationName, cron_tagged: &CronTagged) {
+    if let Err(err) = persist::remove(app_name, cron_tagged) {
+        tracing::warn!(app_name = %app_name, tag = cron_tagged.tag.as_str(), "Failed to remove stored crontab entry: {err}");
+    }
+}
+
+/// Number of `on-cron` events to deliver for a tick scheduled at `ts` and triggered at
+/// `trigger_time`, according to the misfire policy.
+fn fire_count(
+    on_cron_event: &OnCronEvent, misfire: MisfirePolicy, ts: CronDuration,
+    trigger_time: CronDuration,
+) -> u64 {
+    if u64::from(trigger_time - ts) <= MISFIRE_THRESHOLD {
+        return 1;
+    }
+    match misfire {
+        MisfirePolicy::Skip => 0,
+        MisfirePolicy::Coalesce => 1,
+        MisfirePolicy::FireImmediately => {
+            let mut fires = 1;
+            let mut tick = ts;
+            while fires < MAX_CATCH_UP && !on_cron_event.last {
+                match on_cron_event.tick_after(Some(tick)) {
+                    Some(next) if next <= trigger_time => {
+                        fires += 1;
+                        tick = next;
+                    },
+                    _ => break,
+                }
+            }
+            fires
+        },
+    }
+}
+
+/// Current wall clock time as a `CronDuration`.
+fn now() -> Option<CronDuration> {
+    chrono::Utc::now().timestamp_nanos_opt()?.try_into().ok()
+}
+
+/// Create a new thread that will sleep for `duration` nanoseconds, or until the wall
+/// clock reaches `timestamp` if the host was suspended in between.
 fn new_waiting_task(
     timestamp: CronDuration, duration: CronDuration,
 ) -> (CronDuration, std::thread::JoinHandle<()>) {
     let handle = std::thread::spawn(move || {
-        std::thread::sleep(std::time::Duration::from_nanos(duration.into()));
+        let mut remaining = std::time::Duration::from_nanos(duration.into());
+        while !remaining.is_zero() {
+            std::thread::sleep(remaining.min(MAX_WAITING_TASK_SLEEP));
+            remaining = match now() {
+                Some(now) if now < timestamp => {
+                    std::time::Duration::from_nanos((timestamp - now).into())
+                },
+                _ => std::time::Duration::ZERO,
+            };
+        }
         if let Err(_err) = cron_queue_trigger() {
             // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
         }
@@ -287,6 +721,7 @@ mod tests {
                 tag: EXAMPLE_TAG.into(),
             },
             last: IS_LAST,
+            timezone: None,
         }
     }
     // triggers every minute
@@ -297,6 +732,7 @@ mod tests {
                 tag: EXAMPLE_TAG.into(),
             },
             last: IS_NOT_LAST,
+            timezone: None,
         }
     }
     // triggers every minute
@@ -307,6 +743,7 @@ mod tests {
                 tag: EXAMPLE_TAG.into(),
             },
             last: IS_LAST,
+            timezone: None,
         }
     }
     // triggers every minute
@@ -317,6 +754,7 @@ mod tests {
                 tag: OTHER_TAG.into(),
             },
             last: IS_LAST,
+            timezone: None,
         }
     }
 
@@ -538,9 +976,236 @@ mod tests {
         sleep(std::time::Duration::from_millis(500));
         // Trigger manually
         assert!(queue.trigger().is_ok());
-        // THe waiting event should be empty
-        assert!(queue.waiting_event.is_empty());
-        // The queue should be empty
-        assert!(queue.ls_events(&hermes_app_name, &None).is_empty());
+        // The retriggering event is re-scheduled at its next timestamp, with a new waiting
+        // task
+        assert!(!queue.waiting_event.is_empty());
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert_eq!(schedule.len(), 1);
+        let schedule = schedule.first().unwrap();
+        assert!(schedule.entry == cron_entry_2().tag);
+        assert!(schedule.last_fire.is_some());
+        assert!(schedule.next_fire > Some(u64::from(trigger_time)));
+    }
+
+    #[test]
+    fn test_cron_queue_misfire_policy() {
+        let hermes_app_name = hermes_app_name(APP_NAME);
+        let ts = get_triggering_timestamp(chrono::TimeDelta::try_minutes(-10).unwrap());
+        let trigger_time = get_triggering_timestamp(chrono::TimeDelta::zero());
+
+        // On time ticks always fire once.
+        for misfire in [
+            MisfirePolicy::FireImmediately,
+            MisfirePolicy::Skip,
+            MisfirePolicy::Coalesce,
+        ] {
+            assert_eq!(fire_count(&cron_entry_1(), misfire, ts, ts), 1);
+        }
+
+        // Ticks delivered 10 minutes late are missed.
+        let every_minute = OnCronEvent {
+            last: IS_NOT_LAST,
+            ..cron_entry_1()
+        };
+        assert_eq!(
+            fire_count(&every_minute, MisfirePolicy::Skip, ts, trigger_time),
+            0
+        );
+        assert_eq!(
+            fire_count(&every_minute, MisfirePolicy::Coalesce, ts, trigger_time),
+            1
+        );
+        let fires = fire_count(
+            &every_minute,
+            MisfirePolicy::FireImmediately,
+            ts,
+            trigger_time,
+        );
+        assert!((10..=11).contains(&fires));
+        // A one-shot event has no further ticks to catch up.
+        assert_eq!(
+            fire_count(
+                &cron_entry_1(),
+                MisfirePolicy::FireImmediately,
+                ts,
+                trigger_time
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn test_cron_queue_restore() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name("restored-app");
+        let every_minute = OnCronEvent {
+            last: IS_NOT_LAST,
+            ..cron_entry_1()
+        };
+        let stored = |misfire| {
+            StoredCrontab {
+                timestamp: 10.into(),
+                event: every_minute.clone(),
+                misfire,
+                last_fire: Some(5.into()),
+                jitter: 0.into(),
+                overlap: OverlapPolicy::Queue,
+                priority: CronPriority::Normal,
+            }
+        };
+
+        // The stored schedule state is restored, and the missed tick is due at once.
+        queue.restore(&hermes_app_name, vec![stored(
+            MisfirePolicy::FireImmediately,
+        )]);
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        let schedule = schedule.first().unwrap();
+        assert_eq!(schedule.misfire, MisfirePolicy::FireImmediately);
+        assert_eq!(schedule.last_fire, Some(5));
+        assert_eq!(schedule.next_fire, Some(10));
+
+        // The misfire policy set again by the app wins over the stored one.
+        assert!(queue.set_misfire_policy(&hermes_app_name, &every_minute.tag, MisfirePolicy::Skip));
+        queue.restore(&hermes_app_name, vec![stored(MisfirePolicy::Coalesce)]);
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        let schedule = schedule.first().unwrap();
+        assert_eq!(schedule.misfire, MisfirePolicy::Skip);
+        assert_eq!(schedule.last_fire, Some(5));
+    }
+
+    #[test]
+    fn test_cron_queue_set_misfire_policy() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name(APP_NAME);
+
+        assert!(!queue.set_misfire_policy(
+            &hermes_app_name,
+            &cron_entry_1().tag,
+            MisfirePolicy::Skip
+        ));
+
+        queue.add_event(hermes_app_name.clone(), 0.into(), cron_entry_1());
+        assert!(queue.set_misfire_policy(
+            &hermes_app_name,
+            &cron_entry_1().tag,
+            MisfirePolicy::Skip
+        ));
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert_eq!(schedule.len(), 1);
+        let schedule = schedule.first().unwrap();
+        assert_eq!(schedule.misfire, MisfirePolicy::Skip);
+        assert_eq!(schedule.next_fire, Some(0));
+        assert!(schedule.last_fire.is_none());
+
+        assert!(queue.rm_event(&hermes_app_name, &cron_entry_1().tag));
+        assert!(queue.ls_schedule(&hermes_app_name, &None).is_empty());
+    }
+
+    #[test]
+    fn test_cron_queue_set_jitter() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name(APP_NAME);
+        let every_minute = OnCronEvent {
+            last: IS_NOT_LAST,
+            ..cron_entry_1()
+        };
+        let window = CronDuration::from(30_000_000_000);
+
+        assert_eq!(with_jitter(10.into(), 0.into()), 10.into());
+        let delayed = u64::from(with_jitter(10.into(), window));
+        assert!((10..10 + u64::from(window)).contains(&delayed));
+
+        assert!(!queue.set_jitter(&hermes_app_name, &every_minute.tag, window));
+
+        let tick = every_minute.tick_from(None).unwrap();
+        queue.add_event(hermes_app_name.clone(), tick, every_minute.clone());
+        assert!(queue.set_jitter(&hermes_app_name, &every_minute.tag, window));
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert_eq!(schedule.len(), 1);
+        let schedule = schedule.first().unwrap();
+        assert_eq!(schedule.jitter, u64::from(window));
+        // The pending tick is delayed, the next minute may have started meanwhile.
+        let next_fire = schedule.next_fire.unwrap();
+        assert!(next_fire >= u64::from(tick));
+        assert!(next_fire < u64::from(tick) + 60_000_000_000 + u64::from(window));
+    }
+
+    #[test]
+    fn test_cron_queue_set_overlap_policy() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name(APP_NAME);
+        let entry = cron_entry_1();
+
+        assert!(!queue.set_overlap_policy(&hermes_app_name, &entry.tag, OverlapPolicy::Skip));
+
+        queue.add_event(hermes_app_name.clone(), 10.into(), entry.clone());
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert!(matches!(
+            schedule.first().map(|schedule| schedule.overlap),
+            Some(OverlapPolicy::Queue)
+        ));
+        assert!(queue.set_overlap_policy(&hermes_app_name, &entry.tag, OverlapPolicy::Skip));
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert!(matches!(
+            schedule.first().map(|schedule| schedule.overlap),
+            Some(OverlapPolicy::Skip)
+        ));
+    }
+
+    #[test]
+    fn test_cron_queue_set_priority() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name(APP_NAME);
+        let entry = cron_entry_1();
+
+        assert!(!queue.set_priority(&hermes_app_name, &entry.tag, CronPriority::High));
+
+        queue.add_event(hermes_app_name.clone(), 10.into(), entry.clone());
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert!(matches!(
+            schedule.first().map(|schedule| schedule.priority),
+            Some(CronPriority::Normal)
+        ));
+        assert!(queue.set_priority(&hermes_app_name, &entry.tag, CronPriority::High));
+        let schedule = queue.ls_schedule(&hermes_app_name, &None);
+        assert!(matches!(
+            schedule.first().map(|schedule| schedule.priority),
+            Some(CronPriority::High)
+        ));
+    }
+
+    #[test]
+    fn test_cron_queue_pause_and_resume() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name(APP_NAME);
+
+        queue.add_event(hermes_app_name.clone(), 0.into(), cron_entry_1());
+        assert!(!queue.resume(&hermes_app_name));
+        assert!(queue.pause(&hermes_app_name));
+        assert!(!queue.pause(&hermes_app_name));
+        assert!(queue.is_paused(&hermes_app_name));
+        // The entries of a paused app stay scheduled, but are not due.
+        assert!(queue.next_in_queue().is_none());
+        assert_eq!(queue.ls_schedule(&hermes_app_name, &None).len(), 1);
+
+        assert!(queue.resume(&hermes_app_name));
+        assert!(!queue.is_paused(&hermes_app_name));
+        let (ts, app_names) = queue.next_in_queue().unwrap();
+        assert_eq!(ts, 0.into());
+        assert_eq!(app_names, HashSet::from([hermes_app_name]));
+    }
+
+    #[test]
+    fn test_cron_queue_unload() {
+        let queue = CronEventQueue::new(None);
+        let hermes_app_name = hermes_app_name(APP_NAME);
+
+        queue.add_event(hermes_app_name.clone(), 0.into(), cron_entry_1());
+        // Not `pause`, which stores the paused state.
+        queue.paused.insert(hermes_app_name.clone());
+        queue.unload(&hermes_app_name);
+        assert!(!queue.is_paused(&hermes_app_name));
+        assert!(queue.next_in_queue().is_none());
+        assert!(queue.ls_schedule(&hermes_app_name, &None).is_empty());
     }
 }
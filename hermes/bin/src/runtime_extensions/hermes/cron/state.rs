@@ -2,6 +2,7 @@
 /// The crontabs hash map.
 use std::hash::{Hash, Hasher};
 
+use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use tokio::{
     runtime::Builder,
@@ -9,14 +10,19 @@ use tokio::{
 };
 
 use super::{
-    event::OnCronEvent,
+    event::{CronDuration, OnCronEvent},
+    overlap::CronDelivery,
+    persist::StoredCrontab,
     queue::{CronEventQueue, CronJob, CronJobDelay},
 };
 use crate::{
     app::ApplicationName,
-    event::{queue::send, HermesEvent, TargetApp, TargetModule},
+    event::{queue::send, EventPriority, HermesEvent, TargetApp, TargetModule},
     runtime_extensions::{
-        bindings::hermes::cron::api::{CronEventTag, CronTagged, Instant},
+        bindings::hermes::cron::api::{
+            CronEventTag, CronPriority, CronSchedule, CronTagged, Instant, MisfirePolicy,
+            OverlapPolicy,
+        },
         hermes::cron::mkdelay_crontab,
     },
 };
@@ -69,15 +75,20 @@ impl InternalState {
     /// - `entry`:  `CronTagged`. The crontab entry to add.
     /// - `retrigger`:  `bool`. If `true`, the event will re-trigger every time the
     ///   crontab entry matches until cancelled.
+    /// - `timezone`:  `Option<Tz>`. The timezone the crontab entry is matched in, UTC if
+    ///   `None`.
     ///
     /// ## Returns
     ///
     /// - `true`: Crontab added successfully.
     /// - `false`: Crontab failed to be added.
-    fn add_crontab(&self, app_name: &ApplicationName, entry: CronTagged, retrigger: bool) -> bool {
+    fn add_crontab(
+        &self, app_name: &ApplicationName, entry: CronTagged, retrigger: bool, timezone: Option<Tz>,
+    ) -> bool {
         let crontab = OnCronEvent {
             tag: entry,
             last: !retrigger,
+            timezone,
         };
         let (cmd_tx, cmd_rx) = oneshot::channel();
         drop(
@@ -184,6 +195,101 @@ impl InternalState {
             false
         }
     }
+
+    /// List the schedule state of the crontabs for an application.
+    ///
+    /// ## Parameters
+    ///
+    /// - `tag`: Optional, the tag to limit the list to.  If `none` then all crons listed.
+    ///
+    /// ## Returns
+    ///
+    /// - A list of the schedule state of the crontab entries, sorted from the crontab
+    ///   that will trigger soonest to latest.
+    fn ls_schedule(
+        &self, app_name: &ApplicationName, tag: Option<CronEventTag>,
+    ) -> Vec<CronSchedule> {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        drop(
+            self.cron_queue
+                .spawn_cron_job(CronJob::ListSchedule(app_name.clone(), tag, cmd_tx)),
+        );
+        cmd_rx.blocking_recv().unwrap_or_default()
+    }
+
+    /// Set the misfire policy of the requested crontab.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The misfire policy was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_misfire_policy(
+        &self, app_name: &ApplicationName, entry: CronTagged, policy: MisfirePolicy,
+    ) -> bool {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        drop(self.cron_queue.spawn_cron_job(CronJob::SetMisfirePolicy(
+            app_name.clone(),
+            entry,
+            policy,
+            cmd_tx,
+        )));
+        cmd_rx.blocking_recv().unwrap_or(false)
+    }
+
+    /// Set the jitter window of the requested crontab.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The jitter window was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_jitter(&self, app_name: &ApplicationName, entry: CronTagged, window: Instant) -> bool {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        drop(self.cron_queue.spawn_cron_job(CronJob::SetJitter(
+            app_name.clone(),
+            entry,
+            window.into(),
+            cmd_tx,
+        )));
+        cmd_rx.blocking_recv().unwrap_or(false)
+    }
+
+    /// Set the overlap policy of the requested crontab.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The overlap policy was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_overlap_policy(
+        &self, app_name: &ApplicationName, entry: CronTagged, policy: OverlapPolicy,
+    ) -> bool {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        drop(self.cron_queue.spawn_cron_job(CronJob::SetOverlapPolicy(
+            app_name.clone(),
+            entry,
+            policy,
+            cmd_tx,
+        )));
+        cmd_rx.blocking_recv().unwrap_or(false)
+    }
+
+    /// Set the priority of the requested crontab.
+    ///
+    /// ## Returns
+    ///
+    /// - `true`: The priority was set.
+    /// - `false`: The requested crontab does not exist.
+    fn set_priority(
+        &self, app_name: &ApplicationName, entry: CronTagged, priority: CronPriority,
+    ) -> bool {
+        let (cmd_tx, cmd_rx) = oneshot::channel();
+        drop(self.cron_queue.spawn_cron_job(CronJob::SetPriority(
+            app_name.clone(),
+            entry,
+            priority,
+            cmd_tx,
+        )));
+        cmd_rx.blocking_recv().unwrap_or(false)
+    }
 }
 
 impl Hash for CronTagged {
@@ -195,9 +301,9 @@ impl Hash for CronTagged {
 
 /// Add a crontab to the cron queue.
 pub(crate) fn cron_queue_add(
-    app_name: &ApplicationName, entry: CronTagged, retrigger: bool,
+    app_name: &ApplicationName, entry: CronTagged, retrigger: bool, timezone: Option<Tz>,
 ) -> bool {
-    CRON_INTERNAL_STATE.add_crontab(app_name, entry, retrigger)
+    CRON_INTERNAL_STATE.add_crontab(app_name, entry, retrigger, timezone)
 }
 
 /// List crontabs from the cron queue.
@@ -219,24 +325,111 @@ pub(crate) fn cron_queue_rm(app_name: &ApplicationName, entry: CronTagged) -> bo
     CRON_INTERNAL_STATE.rm_crontab(app_name, entry)
 }
 
+/// List the schedule state of the crontabs from the cron queue.
+pub(crate) fn cron_queue_ls_schedule(
+    app_name: &ApplicationName, tag: Option<CronEventTag>,
+) -> Vec<CronSchedule> {
+    CRON_INTERNAL_STATE.ls_schedule(app_name, tag)
+}
+
+/// Set the misfire policy of a crontab in the cron queue.
+pub(crate) fn cron_queue_set_misfire_policy(
+    app_name: &ApplicationName, entry: CronTagged, policy: MisfirePolicy,
+) -> bool {
+    CRON_INTERNAL_STATE.set_misfire_policy(app_name, entry, policy)
+}
+
+/// Restore the stored crontabs of an app into the cron queue.
+pub(crate) fn cron_queue_restore(app_name: &ApplicationName, crontabs: Vec<StoredCrontab>) {
+    CRON_INTERNAL_STATE.cron_queue.restore(app_name, crontabs);
+    if let Err(err) = cron_queue_trigger() {
+        tracing::warn!(app_name = %app_name, "Failed to trigger the cron queue: {err}");
+    }
+}
+
+/// Set the jitter window of a crontab in the cron queue.
+pub(crate) fn cron_queue_set_jitter(
+    app_name: &ApplicationName, entry: CronTagged, window: Instant,
+) -> bool {
+    CRON_INTERNAL_STATE.set_jitter(app_name, entry, window)
+}
+
+/// Set the overlap policy of a crontab in the cron queue.
+pub(crate) fn cron_queue_set_overlap_policy(
+    app_name: &ApplicationName, entry: CronTagged, policy: OverlapPolicy,
+) -> bool {
+    CRON_INTERNAL_STATE.set_overlap_policy(app_name, entry, policy)
+}
+
+/// Set the priority of a crontab in the cron queue.
+pub(crate) fn cron_queue_set_priority(
+    app_name: &ApplicationName, entry: CronTagged, priority: CronPriority,
+) -> bool {
+    CRON_INTERNAL_STATE.set_priority(app_name, entry, priority)
+}
+
+/// Pause the delivery of the crontab events of an app.
+pub(crate) fn cron_queue_pause(app_name: &ApplicationName) -> bool {
+    CRON_INTERNAL_STATE.cron_queue.pause(app_name)
+}
+
+/// Resume the delivery of the crontab events of an app, and trigger the cron queue so
+/// the ticks missed meanwhile are delivered.
+pub(crate) fn cron_queue_resume(app_name: &ApplicationName) -> bool {
+    let resumed = CRON_INTERNAL_STATE.cron_queue.resume(app_name);
+    if resumed {
+        if let Err(err) = cron_queue_trigger() {
+            tracing::warn!(app_name = %app_name, "Failed to trigger the cron queue: {err}");
+        }
+    }
+    resumed
+}
+
+/// Whether the delivery of the crontab events of an app is paused.
+pub(crate) fn cron_queue_is_paused(app_name: &ApplicationName) -> bool {
+    CRON_INTERNAL_STATE.cron_queue.is_paused(app_name)
+}
+
+/// Remove the crontabs of an unloaded app from the cron queue.
+pub(crate) fn cron_queue_unload(app_name: &ApplicationName) {
+    CRON_INTERNAL_STATE.cron_queue.unload(app_name);
+}
+
 /// Trigger the cron queue events dispatch.
 pub(crate) fn cron_queue_trigger() -> anyhow::Result<()> {
     CRON_INTERNAL_STATE.cron_queue.trigger()
 }
 
-/// Send event to the Hermes Event Queue.
+/// Send event to the Hermes Event Queue at the priority of its crontab, unless the
+/// overlap policy of its crontab drops it, with when it was scheduled and when its
+/// crontab fires next.
 pub(crate) fn send_hermes_on_cron_event(
-    app_name: &ApplicationName, on_cron_event: OnCronEvent,
+    app_name: &ApplicationName, on_cron_event: OnCronEvent, overlap: OverlapPolicy,
+    priority: CronPriority, scheduled: CronDuration, next_fire: Option<CronDuration>,
 ) -> anyhow::Result<()> {
-    //
+    let Some(delivery) =
+        CronDelivery::admit(app_name, on_cron_event, overlap, scheduled, next_fire)
+    else {
+        return Ok(());
+    };
     let event = HermesEvent::new(
-        on_cron_event,
+        delivery,
         TargetApp::List(vec![app_name.clone()]),
         TargetModule::All,
-    );
+    )
+    .with_priority(event_priority(priority));
     send(event)
 }
 
+/// Priority of the events of a crontab in the Hermes Event Queue.
+fn event_priority(priority: CronPriority) -> EventPriority {
+    match priority {
+        CronPriority::High => EventPriority::High,
+        CronPriority::Normal => EventPriority::Normal,
+        CronPriority::Low => EventPriority::Low,
+    }
+}
+
 /// The crontab queue task runs in the background.
 async fn cron_queue_task(mut queue_rx: mpsc::Receiver<CronJob>) {
     while let Some(cron_job) = queue_rx.recv().await {
@@ -265,6 +458,54 @@ async fn cron_queue_task(mut queue_rx: mpsc::Receiver<CronJob>) {
                     // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
                 }
             },
+            CronJob::ListSchedule(app_name, tag, response_tx) => {
+                let response = CRON_INTERNAL_STATE.cron_queue.ls_schedule(&app_name, &tag);
+                if let Err(_err) = response_tx.send(response) {
+                    // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
+                }
+            },
+            CronJob::SetMisfirePolicy(app_name, cron_tagged, policy, response_tx) => {
+                let response = CRON_INTERNAL_STATE.cron_queue.set_misfire_policy(
+                    &app_name,
+                    &cron_tagged,
+                    policy,
+                );
+                if let Err(_err) = response_tx.send(response) {
+                    // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
+                }
+            },
+            CronJob::SetJitter(app_name, cron_tagged, jitter, response_tx) => {
+                let response =
+                    CRON_INTERNAL_STATE
+                        .cron_queue
+                        .set_jitter(&app_name, &cron_tagged, jitter);
+                if let Err(_err) = response_tx.send(response) {
+                    // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
+                }
+                // Trigger the cron queue
+                if let Err(_err) = cron_queue_trigger() {
+                    // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
+                }
+            },
+            CronJob::SetOverlapPolicy(app_name, cron_tagged, policy, response_tx) => {
+                let response = CRON_INTERNAL_STATE.cron_queue.set_overlap_policy(
+                    &app_name,
+                    &cron_tagged,
+                    policy,
+                );
+                if let Err(_err) = response_tx.send(response) {
+                    // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
+                }
+            },
+            CronJob::SetPriority(app_name, cron_tagged, priority, response_tx) => {
+                let response =
+                    CRON_INTERNAL_STATE
+                        .cron_queue
+                        .set_priority(&app_name, &cron_tagged, priority);
+                if let Err(_err) = response_tx.send(response) {
+                    // TODO (@saibatizoku): log error https://github.com/input-output-hk/hermes/issues/15
+                }
+            },
         }
     }
 }
@@ -381,7 +622,7 @@ mod tests {
         let hermes_app = hermes_app_name(APP_NAME);
 
         // Add returns false
-        assert!(!state.add_crontab(&hermes_app, crontab_example_1(), RETRIGGER_YES));
+        assert!(!state.add_crontab(&hermes_app, crontab_example_1(), RETRIGGER_YES, None));
         // List returns empty vec.
         assert!(state.ls_crontabs(&hermes_app, None).is_empty());
         // Delay returns false
@@ -418,13 +659,14 @@ mod tests {
         assert!(cron_queue_add(
             &hermes_app_name(APP_NAME),
             crontab_example_1(),
-            RETRIGGER_YES
+            RETRIGGER_YES,
+            None
         ));
 
         // inserting separate thread
         let h = std::thread::spawn(move || {
             let app_name = hermes_app_name(APP_NAME);
-            cron_queue_add(&app_name, crontab_example_1(), RETRIGGER_NO)
+            cron_queue_add(&app_name, crontab_example_1(), RETRIGGER_NO, None)
         });
         assert!(h.join().unwrap());
 
@@ -435,12 +677,13 @@ mod tests {
         assert!(cron_queue_add(
             &app_name,
             crontab_example_2(),
-            RETRIGGER_YES
+            RETRIGGER_YES,
+            None
         ));
 
         let h = std::thread::spawn(move || {
             let app_name = hermes_app_name(APP_NAME);
-            cron_queue_add(&app_name.clone(), crontab_example_2(), RETRIGGER_YES)
+            cron_queue_add(&app_name.clone(), crontab_example_2(), RETRIGGER_YES, None)
         });
         assert!(h.join().unwrap());
 
@@ -456,9 +699,15 @@ mod tests {
         assert!(cron_queue_add(
             &app_name,
             crontab_example_3(),
-            RETRIGGER_YES
+            RETRIGGER_YES,
+            None
+        ));
+        assert!(cron_queue_add(
+            &app_name,
+            crontab_other_1(),
+            RETRIGGER_YES,
+            None
         ));
-        assert!(cron_queue_add(&app_name, crontab_other_1(), RETRIGGER_YES));
 
         // List
         let queue_ls = cron_queue_ls(&app_name, None);
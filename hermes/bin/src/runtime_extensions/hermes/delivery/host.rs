@@ -0,0 +1,47 @@
+//! Delivery host implementation for WASM runtime.
+
+use super::state::{ack, get_guarantee, set_guarantee};
+use crate::{
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::bindings::hermes::delivery::api::{DeliveryGuarantee, EventSource, Host},
+};
+
+impl Host for HermesRuntimeContext {
+    /// # Set the delivery guarantee of an event source.
+    ///
+    /// Applies to the events of the source delivered to the calling module.
+    ///
+    /// ## Parameters
+    ///
+    /// - `source`: The event source.
+    /// - `guarantee`: The delivery guarantee of its events.
+    fn set_guarantee(
+        &mut self, source: EventSource, guarantee: DeliveryGuarantee,
+    ) -> wasmtime::Result<()> {
+        set_guarantee(self.app_name(), self.module_id(), source, guarantee);
+        Ok(())
+    }
+
+    /// # Get the delivery guarantee of an event source.
+    ///
+    /// ## Parameters
+    ///
+    /// - `source`: The event source.
+    ///
+    /// ## Returns
+    ///
+    /// - The delivery guarantee of the events of the source delivered to the calling
+    ///   module.
+    fn get_guarantee(&mut self, source: EventSource) -> wasmtime::Result<DeliveryGuarantee> {
+        Ok(get_guarantee(self.app_name(), self.module_id(), source))
+    }
+
+    /// # Acknowledge the event being handled.
+    ///
+    /// Must be called by the handler of an at-least-once event once it is fully
+    /// processed. Has no effect for at-most-once events.
+    fn ack(&mut self) -> wasmtime::Result<()> {
+        ack(self.app_name(), self.module_id());
+        Ok(())
+    }
+}
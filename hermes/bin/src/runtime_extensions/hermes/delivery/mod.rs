@@ -0,0 +1,9 @@
+//! Delivery runtime extension implementation.
+
+mod host;
+mod state;
+
+pub(crate) use state::deliver;
+
+/// Advise Runtime Extensions of a new context
+pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
@@ -0,0 +1,192 @@
+//! Delivery state.
+
+use std::time::Duration;
+
+use dashmap::{DashMap, DashSet};
+use once_cell::sync::Lazy;
+
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::bindings::hermes::delivery::api::{DeliveryGuarantee, EventSource},
+    wasm::module::ModuleId,
+};
+
+/// Number of times an at-least-once event is delivered before it is given up.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// Delay before the first redelivery of an event, doubled on every later one.
+const REDELIVERY_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Delivery guarantees of the event sources of every module.
+static GUARANTEES: Lazy<DashMap<(ApplicationName, ModuleId), Guarantees>> = Lazy::new(DashMap::new);
+
+/// Modules which acknowledged the event they are handling.
+/// Events are dispatched to a module one at a time, so one flag per module is enough.
+static ACKED: Lazy<DashSet<(ApplicationName, ModuleId)>> = Lazy::new(DashSet::new);
+
+/// Delivery guarantee of every event source of a module.
+struct Guarantees {
+    /// Guarantee of `on-cron` events.
+    cron: DeliveryGuarantee,
+    /// Guarantee of cardano events.
+    cardano: DeliveryGuarantee,
+    /// Guarantee of document sync events.
+    doc_sync: DeliveryGuarantee,
+}
+
+impl Default for Guarantees {
+    fn default() -> Self {
+        Self {
+            cron: DeliveryGuarantee::AtMostOnce,
+            cardano: DeliveryGuarantee::AtMostOnce,
+            doc_sync: DeliveryGuarantee::AtMostOnce,
+        }
+    }
+}
+
+impl Guarantees {
+    /// Guarantee of the event source.
+    fn get_mut(&mut self, source: EventSource) -> &mut DeliveryGuarantee {
+        match source {
+            EventSource::Cron => &mut self.cron,
+            EventSource::Cardano => &mut self.cardano,
+            EventSource::DocSync => &mut self.doc_sync,
+        }
+    }
+}
+
+/// Source of an event, by its name.
+/// `None` for events which are always delivered at most once.
+fn event_source(event_name: &str) -> Option<EventSource> {
+    match event_name {
+        "on-cron" => Some(EventSource::Cron),
+        "on-cardano-block"
+        | "on-cardano-blocks"
+        | "on-cardano-txn"
+        | "on-cardano-rollback"
+        | "on-cardano-immutable-window" => Some(EventSource::Cardano),
+        _ => None,
+    }
+}
+
+/// Set the delivery guarantee of the events of a source delivered to a module.
+pub(super) fn set_guarantee(
+    app_name: &ApplicationName, module_id: &ModuleId, source: EventSource,
+    guarantee: DeliveryGuarantee,
+) {
+    let mut guarantees = GUARANTEES
+        .entry((app_name.clone(), module_id.clone()))
+        .or_default();
+    *guarantees.get_mut(source) = guarantee;
+}
+
+/// Get the delivery guarantee of the events of a source delivered to a module.
+pub(super) fn get_guarantee(
+    app_name: &ApplicationName, module_id: &ModuleId, source: EventSource,
+) -> DeliveryGuarantee {
+    GUARANTEES
+        .get_mut(&(app_name.clone(), module_id.clone()))
+        .map_or(DeliveryGuarantee::AtMostOnce, |mut guarantees| {
+            *guarantees.get_mut(source)
+        })
+}
+
+/// Acknowledge the event being handled by a module.
+pub(super) fn ack(app_name: &ApplicationName, module_id: &ModuleId) {
+    ACKED.insert((app_name.clone(), module_id.clone()));
+}
+
+/// Deliver an event to a module, by calling `execute`.
+///
+/// At-least-once events are delivered again, with a backoff, until the handler succeeds
+/// and acknowledges the event, up to `MAX_DELIVERY_ATTEMPTS` times.
+pub(crate) fn deliver(
+    app_name: &ApplicationName, module_id: &ModuleId, event_name: &str,
+    mut execute: impl FnMut() -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let at_least_once = event_source(event_name).is_some_and(|source| {
+        get_guarantee(app_name, module_id, source) == DeliveryGuarantee::AtLeastOnce
+    });
+    if !at_least_once {
+        return execute();
+    }
+
+    let key = (app_name.clone(), module_id.clone());
+    let mut backoff = REDELIVERY_BACKOFF;
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        ACKED.remove(&key);
+        let result = execute();
+        let acked = ACKED.remove(&key).is_some();
+        match result {
+            Ok(()) if acked => return Ok(()),
+            Err(err) if attempt == MAX_DELIVERY_ATTEMPTS => return Err(err),
+            Ok(()) if attempt == MAX_DELIVERY_ATTEMPTS => break,
+            Ok(()) => {
+                tracing::warn!(%app_name, %module_id, event_name, attempt, "Event was not acknowledged, redelivering");
+            },
+            Err(err) => {
+                tracing::warn!(%app_name, %module_id, event_name, attempt, "Event handler failed, redelivering: {err}");
+            },
+        }
+        std::thread::sleep(backoff);
+        backoff = backoff.saturating_mul(2);
+    }
+    anyhow::bail!(
+        "Event {event_name} was not acknowledged after {MAX_DELIVERY_ATTEMPTS} delivery attempts"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(name: &str) -> (ApplicationName, ModuleId) {
+        (
+            ApplicationName(name.to_string()),
+            ModuleId(rusty_ulid::Ulid::generate()),
+        )
+    }
+
+    #[test]
+    fn at_most_once_is_delivered_once() {
+        let (app_name, module_id) = module("delivery_at_most_once");
+        let mut calls = 0;
+        let result = deliver(&app_name, &module_id, "on-cron", || {
+            calls += 1;
+            anyhow::bail!("trap")
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn at_least_once_is_redelivered_until_acked() {
+        let (app_name, module_id) = module("delivery_at_least_once");
+        set_guarantee(
+            &app_name,
+            &module_id,
+            EventSource::Cron,
+            DeliveryGuarantee::AtLeastOnce,
+        );
+
+        let mut calls = 0;
+        let result = deliver(&app_name, &module_id, "on-cron", || {
+            calls += 1;
+            if calls == 3 {
+                ack(&app_name, &module_id);
+            }
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(calls, 3);
+
+        // Other sources are not affected.
+        let mut calls = 0;
+        let result = deliver(&app_name, &module_id, "on-cardano-block", || {
+            calls += 1;
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,7 @@
+//! Error host implementation for WASM runtime.
+
+use crate::{
+    runtime_context::HermesRuntimeContext, runtime_extensions::bindings::hermes::error::api::Host,
+};
+
+impl Host for HermesRuntimeContext {}
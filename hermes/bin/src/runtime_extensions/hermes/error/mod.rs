@@ -0,0 +1,261 @@
+//! Error runtime extension implementation.
+//!
+//! Maps the errors of every Hermes API to the structured `hermes-error` record.
+
+mod host;
+
+use libsqlite3_sys::{
+    SQLITE_AUTH, SQLITE_BUSY, SQLITE_CONSTRAINT, SQLITE_FULL, SQLITE_LOCKED, SQLITE_MISMATCH,
+    SQLITE_MISUSE, SQLITE_NOMEM, SQLITE_NOTFOUND, SQLITE_PERM, SQLITE_RANGE, SQLITE_READONLY,
+    SQLITE_TOOBIG,
+};
+
+pub(crate) use crate::runtime_extensions::bindings::hermes::error::api::{
+    ErrorCategory, HermesError,
+};
+use crate::runtime_extensions::bindings::hermes::{
+    cardano::api::{AddressError, FetchError, TxnError},
+    ipfs::api::Errno as IpfsErrno,
+    sqlite::api::Errno as SqliteErrno,
+};
+
+/// Advise Runtime Extensions of a new context
+pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
+
+impl HermesError {
+    /// Create a new error, retryable according to its category.
+    pub(crate) fn new(category: ErrorCategory, code: i32, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            retryable: matches!(
+                category,
+                ErrorCategory::Unavailable | ErrorCategory::Timeout
+            ),
+            message: message.into(),
+            code,
+        }
+    }
+}
+
+impl From<SqliteErrno> for HermesError {
+    fn from(err: SqliteErrno) -> Self {
+        let (category, code, message) = match err {
+            SqliteErrno::Sqlite(rc) => {
+                // Extended result codes keep the primary result code in the low byte.
+                let category = match rc & 0xFF {
+                    SQLITE_BUSY | SQLITE_LOCKED => ErrorCategory::Unavailable,
+                    SQLITE_NOMEM | SQLITE_FULL => ErrorCategory::ResourceExhausted,
+                    SQLITE_PERM | SQLITE_READONLY | SQLITE_AUTH => ErrorCategory::PermissionDenied,
+                    SQLITE_NOTFOUND => ErrorCategory::NotFound,
+                    SQLITE_TOOBIG | SQLITE_CONSTRAINT | SQLITE_MISMATCH | SQLITE_MISUSE
+                    | SQLITE_RANGE => ErrorCategory::InvalidInput,
+                    _ => ErrorCategory::Internal,
+                };
+                return Self::new(category, rc, format!("SQLite error code {rc}"));
+            },
+            SqliteErrno::ConvertingCString => {
+                (
+                    ErrorCategory::InvalidInput,
+                    -1,
+                    "String contains a nul byte",
+                )
+            },
+            SqliteErrno::InvalidInMemoryConfig => {
+                (
+                    ErrorCategory::Internal,
+                    -2,
+                    "Invalid in-memory database configuration",
+                )
+            },
+            SqliteErrno::InvalidPersistentConfig => {
+                (
+                    ErrorCategory::Internal,
+                    -3,
+                    "Invalid persistent database configuration",
+                )
+            },
+            SqliteErrno::MissingDatabaseNameForPersistentConfig => {
+                (
+                    ErrorCategory::Internal,
+                    -4,
+                    "Missing database name in the persistent database configuration",
+                )
+            },
+            SqliteErrno::FailedOpeningDatabase => {
+                (
+                    ErrorCategory::Unavailable,
+                    -5,
+                    "Failed to open the database",
+                )
+            },
+            SqliteErrno::FailedSettingDatabaseSize => {
+                (
+                    ErrorCategory::Internal,
+                    -6,
+                    "Failed to set the database size limit",
+                )
+            },
+            SqliteErrno::UnknownColumnType => {
+                (ErrorCategory::Unsupported, -7, "Unknown column type")
+            },
+            SqliteErrno::ForbiddenPragmaCommand => {
+                (
+                    ErrorCategory::PermissionDenied,
+                    -8,
+                    "PRAGMA commands are not allowed",
+                )
+            },
+            SqliteErrno::ReturnedNullPointer => {
+                (
+                    ErrorCategory::Internal,
+                    -9,
+                    "The database returned a null pointer",
+                )
+            },
+            SqliteErrno::ConvertingNumeric => {
+                (
+                    ErrorCategory::InvalidInput,
+                    -10,
+                    "Numeric value out of range",
+                )
+            },
+            SqliteErrno::InvalidDatabaseName => {
+                (ErrorCategory::InvalidInput, -11, "Invalid database name")
+            },
+        };
+        Self::new(category, code, message)
+    }
+}
+
+impl From<IpfsErrno> for HermesError {
+    fn from(err: IpfsErrno) -> Self {
+        let (category, message) = match err {
+            IpfsErrno::DhtGetError => (ErrorCategory::Unavailable, "Unable to get DHT value"),
+            IpfsErrno::DhtPutError => (ErrorCategory::Unavailable, "Unable to put DHT value"),
+            IpfsErrno::FileAddError => (ErrorCategory::Unavailable, "Unable to add file to IPFS"),
+            IpfsErrno::FileGetError => (ErrorCategory::Unavailable, "Unable to get file from IPFS"),
+            IpfsErrno::FilePinError => (ErrorCategory::Unavailable, "Unable to pin file"),
+            IpfsErrno::InvalidCid => (ErrorCategory::InvalidInput, "Invalid CID"),
+            IpfsErrno::InvalidDhtKey => (ErrorCategory::InvalidInput, "Invalid DHT key"),
+            IpfsErrno::InvalidDhtValue => (ErrorCategory::InvalidInput, "Invalid DHT value"),
+            IpfsErrno::InvalidIpfsPath => (ErrorCategory::InvalidInput, "Invalid IPFS path"),
+            IpfsErrno::InvalidPeerId => (ErrorCategory::InvalidInput, "Invalid peer ID"),
+            IpfsErrno::InvalidPubsubMessage => {
+                (ErrorCategory::InvalidInput, "Invalid PubSub message")
+            },
+            IpfsErrno::PeerEvictionError => (ErrorCategory::Unavailable, "Unable to evict peer"),
+            IpfsErrno::PubsubPublishError => {
+                (ErrorCategory::Unavailable, "Unable to publish to topic")
+            },
+            IpfsErrno::PubsubSubscribeError => {
+                (ErrorCategory::Unavailable, "Unable to subscribe to topic")
+            },
+            IpfsErrno::ServiceUnavailable => {
+                (ErrorCategory::Unavailable, "IPFS service is unavailable")
+            },
+            IpfsErrno::FileTooLarge => {
+                (
+                    ErrorCategory::ResourceExhausted,
+                    "File is larger than the app limit",
+                )
+            },
+            IpfsErrno::DhtValueTooLarge => {
+                (
+                    ErrorCategory::ResourceExhausted,
+                    "DHT value is larger than the app limit",
+                )
+            },
+            IpfsErrno::PubsubMessageTooLarge => {
+                (
+                    ErrorCategory::ResourceExhausted,
+                    "PubSub message is larger than the app limit",
+                )
+            },
+            IpfsErrno::BandwidthCapExceeded => {
+                (
+                    ErrorCategory::ResourceExhausted,
+                    "The app reached its bandwidth cap",
+                )
+            },
+        };
+        Self::new(category, err as i32, message)
+    }
+}
+
+impl From<FetchError> for HermesError {
+    fn from(err: FetchError) -> Self {
+        let (category, message) = match err {
+            FetchError::BlockchainNotAvailable => {
+                (
+                    ErrorCategory::Unavailable,
+                    "The blockchain is not available",
+                )
+            },
+            FetchError::InvalidSlot => {
+                (
+                    ErrorCategory::NotFound,
+                    "The slot is not valid for the blockchain",
+                )
+            },
+        };
+        Self::new(category, err as i32, message)
+    }
+}
+
+impl From<AddressError> for HermesError {
+    fn from(err: AddressError) -> Self {
+        let message = match err {
+            AddressError::InvalidAddress => "The address is not a valid Cardano address",
+            AddressError::NetworkMismatch => "The address is not an address of the blockchain",
+        };
+        Self::new(ErrorCategory::InvalidInput, err as i32, message)
+    }
+}
+
+impl From<TxnError> for HermesError {
+    fn from(err: TxnError) -> Self {
+        let (category, message) = match err {
+            TxnError::BlockchainNotAvailable => {
+                (
+                    ErrorCategory::Unavailable,
+                    "The blockchain is not available",
+                )
+            },
+            TxnError::MalformedTransaction => {
+                (ErrorCategory::InvalidInput, "The transaction is malformed")
+            },
+            TxnError::PostTxnNotAllowed => {
+                (
+                    ErrorCategory::PermissionDenied,
+                    "Posting transactions is not allowed",
+                )
+            },
+        };
+        Self::new(category, err as i32, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retryable_by_category() {
+        let busy = HermesError::from(SqliteErrno::Sqlite(SQLITE_BUSY));
+        assert_eq!(busy.category, ErrorCategory::Unavailable);
+        assert!(busy.retryable);
+        assert_eq!(busy.code, SQLITE_BUSY);
+
+        let pragma = HermesError::from(SqliteErrno::ForbiddenPragmaCommand);
+        assert_eq!(pragma.category, ErrorCategory::PermissionDenied);
+        assert!(!pragma.retryable);
+        assert_eq!(pragma.code, -8);
+
+        let cid = HermesError::from(IpfsErrno::InvalidCid);
+        assert_eq!(cid.category, ErrorCategory::InvalidInput);
+        assert_eq!(cid.code, 5);
+
+        let txn = HermesError::from(TxnError::MalformedTransaction);
+        assert_eq!(txn.code, 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Conditional request handling for module responses which declare a cache validator.
+//!
+//! A module declares a validator by returning an `ETag` and/or `Last-Modified` header.
+//! Requests carrying a matching `If-None-Match` or `If-Modified-Since` are answered with
+//! `304 Not Modified`. While the module response is fresh (`Cache-Control: max-age`), the
+//! gateway answers them from its cache without invoking the module again.
+
+use std::time::{Duration, Instant};
+
+use chrono::DateTime;
+use dashmap::DashMap;
+use hyper::{
+    header::{CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    http::response::Builder,
+    Body, HeaderMap, Method, Request, Response, StatusCode,
+};
+use once_cell::sync::Lazy;
+
+use super::event::HeadersKV;
+use crate::app::ApplicationName;
+
+/// Module response code the validators are cached for.
+const OK_CODE: u16 = 200;
+
+/// Cache validators of module responses, keyed by app and request path.
+static VALIDATORS: Lazy<DashMap<CacheKey, Validator>> = Lazy::new(DashMap::new);
+
+/// Gateway cache key of a request.
+type CacheKey = (ApplicationName, String);
+
+/// Cache validator declared by a module response.
+#[derive(Debug, Clone)]
+struct Validator {
+    /// `ETag` of the response.
+    etag: Option<String>,
+    /// `Last-Modified` date of the response.
+    last_modified: Option<String>,
+    /// How long the response can be revalidated without invoking the module.
+    freshness: Freshness,
+}
+
+/// How long a response can be revalidated without invoking the module.
+#[derive(Debug, Clone, Copy)]
+enum Freshness {
+    /// Never, the response has no `max-age`.
+    Stale,
+    /// Until the instant.
+    Until(Instant),
+    /// Forever, its `max-age` ends past the instants the clock can represent.
+    Forever,
+}
+
+impl Validator {
+    /// Validator declared in the module response headers, if any.
+    fn from_headers(headers: &HeadersKV) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .and_then(|(_, values)| values.first().cloned())
+        };
+
+        let etag = header(ETAG.as_str());
+        let last_modified = header(LAST_MODIFIED.as_str());
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+
+        let freshness = match header(CACHE_CONTROL.as_str())
+            .and_then(|cache_control| max_age(&cache_control))
+        {
+            None => Freshness::Stale,
+            Some(max_age) => {
+                Instant::now()
+                    .checked_add(max_age)
+                    .map_or(Freshness::Forever, Freshness::Until)
+            },
+        };
+
+        Some(Self {
+            etag,
+            last_modified,
+            freshness,
+        })
+    }
+
+    /// Can the response still be revalidated without invoking the module.
+    fn is_fresh(&self) -> bool {
+        match self.freshness {
+            Freshness::Stale => false,
+            Freshness::Until(fresh_until) => Instant::now() < fresh_until,
+            Freshness::Forever => true,
+        }
+    }
+
+    /// Do the request conditional headers match the validator.
+    ///
+    /// `If-Modified-Since` is only evaluated when `If-None-Match` is absent.
+    fn matches(&self, request_headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = request_headers.get(IF_NONE_MATCH) {
+            let (Ok(if_none_match), Some(etag)) = (if_none_match.to_str(), &self.etag) else {
+                return false;
+            };
+            return if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || weak_eq(tag, etag));
+        }
+
+        if let Some(if_modified_since) = request_headers.get(IF_MODIFIED_SINCE) {
+            let since = if_modified_since
+                .to_str()
+                .ok()
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok());
+            let last_modified = self
+                .last_modified
+                .as_deref()
+                .and_then(|date| DateTime::parse_from_rfc2822(date).ok());
+            if let (Some(since), Some(last_modified)) = (since, last_modified) {
+                return last_modified <= since;
+            }
+        }
+        false
+    }
+
+    /// Add the validator headers to a response.
+    fn apply(&self, mut response: Builder) -> Builder {
+        if let Some(etag) = &self.etag {
+            response = response.header(ETAG, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            response = response.header(LAST_MODIFIED, last_modified);
+        }
+        response
+    }
+
+    /// `304 Not Modified` response.
+    fn not_modified(&self) -> anyhow::Result<Response<Body>> {
+        Ok(self
+            .apply(Response::builder().status(StatusCode::NOT_MODIFIED))
+            .body(Body::empty())?)
+    }
+}
+
+/// A `GET` or `HEAD` request routed to the modules, which may be answered conditionally.
+pub(crate) struct ConditionalRequest {
+    /// Gateway cache key of the request.
+    key: CacheKey,
+    /// Request headers.
+    headers: HeaderMap,
+}
+
+impl ConditionalRequest {
+    /// Only `GET` and `HEAD` requests can be answered conditionally.
+    pub(crate) fn new(app_name: &ApplicationName, req: &Request<Body>) -> Option<Self> {
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            return None;
+        }
+        let path = req
+            .uri()
+            .path_and_query()
+            .map_or_else(|| req.uri().path().to_string(), ToString::to_string);
+        Some(Self {
+            key: (app_name.clone(), path),
+            headers: req.headers().clone(),
+        })
+    }
+
+    /// `304 Not Modified` if a fresh cached validator matches the request, in which case
+    /// the modules do not need to be invoked.
+    pub(crate) fn cached_response(&self) -> Option<anyhow::Result<Response<Body>>> {
+        let validator = VALIDATORS.get(&self.key)?;
+        (validator.is_fresh() && validator.matches(&self.headers)).then(|| validator.not_modified())
+    }
+
+    /// Caches the validator of a module response, and answers with `304 Not Modified` if
+    /// it matches the request.
+    ///
+    /// Returns `None` if the module response must be sent as is.
+    pub(crate) fn module_response(
+        &self, code: u16, headers: &HeadersKV,
+    ) -> Option<anyhow::Result<Response<Body>>> {
+        let validator = match Validator::from_headers(headers) {
+            Some(validator) if code == OK_CODE => validator,
+            _ => {
+                VALIDATORS.remove(&self.key);
+                return None;
+            },
+        };
+        VALIDATORS.insert(self.key.clone(), validator.clone());
+
+        validator
+            .matches(&self.headers)
+            .then(|| validator.not_modified())
+    }
+}
+
+/// Add the validator headers declared by a module to a response.
+pub(crate) fn with_validator_headers(response: Builder, headers: &HeadersKV) -> Builder {
+    match Validator::from_headers(headers) {
+        Some(validator) => validator.apply(response),
+        None => response,
+    }
+}
+
+/// Forget the validators of the responses of an app, when it is unloaded.
+pub(super) fn remove_app(app_name: &ApplicationName) {
+    VALIDATORS.retain(|(app, _), _| app != app_name);
+}
+
+/// Weak comparison of two entity tags.
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+/// `max-age` directive of a `Cache-Control` header.
+fn max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|seconds| seconds.parse().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(headers: &[(&str, &str)]) -> Validator {
+        let headers: HeadersKV = headers
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), vec![(*v).to_string()]))
+            .collect();
+        Validator::from_headers(&headers).unwrap()
+    }
+
+    fn request_headers(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(k, v)| (k.parse().unwrap(), v.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn if_none_match() {
+        let validator = validator(&[("ETag", "\"v1\"")]);
+
+        assert!(validator.matches(&request_headers(&[("if-none-match", "\"v1\"")])));
+        assert!(validator.matches(&request_headers(&[("if-none-match", "W/\"v1\"")])));
+        assert!(validator.matches(&request_headers(&[("if-none-match", "\"v0\", \"v1\"")])));
+        assert!(validator.matches(&request_headers(&[("if-none-match", "*")])));
+        assert!(!validator.matches(&request_headers(&[("if-none-match", "\"v2\"")])));
+        assert!(!validator.matches(&request_headers(&[])));
+    }
+
+    #[test]
+    fn if_modified_since() {
+        let validator = validator(&[("last-modified", "Sun, 06 Nov 1994 08:49:37 GMT")]);
+
+        assert!(validator.matches(&request_headers(&[(
+            "if-modified-since",
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        )])));
+        assert!(!validator.matches(&request_headers(&[(
+            "if-modified-since",
+            "Sat, 05 Nov 1994 08:49:37 GMT"
+        )])));
+    }
+
+    #[test]
+    fn freshness() {
+        assert!(
+            validator(&[("etag", "\"v1\""), ("cache-control", "public, max-age=60")]).is_fresh()
+        );
+        assert!(!validator(&[("etag", "\"v1\"")]).is_fresh());
+        assert!(!validator(&[("etag", "\"v1\""), ("cache-control", "max-age=0")]).is_fresh());
+        assert!(validator(&[
+            ("etag", "\"v1\""),
+            ("cache-control", "max-age=18446744073709551615")
+        ])
+        .is_fresh());
+    }
+}
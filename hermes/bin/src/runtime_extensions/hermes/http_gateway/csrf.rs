@@ -0,0 +1,411 @@
+//! CSRF protection of the state-changing requests of gateway routes.
+//!
+//! Two protections can be configured per route:
+//!
+//! - `double-submit`: safe requests are issued a signed token in the `hermes-csrf`
+//!   cookie, which state-changing requests must echo in the `X-CSRF-Token` header.
+//! - `synchronizer`: safe requests are issued a single-use token held by the host in the
+//!   `X-CSRF-Token` response header, which state-changing requests must send back in the
+//!   `X-CSRF-Token` header. The tokens are bound to the `hermes-csrf-session` cookie of
+//!   the client, which holds a limited number of them.
+
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use hyper::{
+    header::{HeaderValue, SET_COOKIE},
+    Body, HeaderMap, Method, Response,
+};
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::Sha256;
+
+use super::routing::request_cookie;
+use crate::app::ApplicationName;
+
+/// Cookie holding the double-submit token.
+const CSRF_COOKIE: &str = "hermes-csrf";
+
+/// Cookie identifying the session the synchronizer tokens are bound to.
+const SESSION_COOKIE: &str = "hermes-csrf-session";
+
+/// Header carrying the CSRF token.
+const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Number of random bytes in a token.
+const TOKEN_NONCE_LEN: usize = 16;
+
+/// How long a synchronizer token is accepted after it was issued.
+const SYNCHRONIZER_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Maximum number of synchronizer tokens a session holds, the oldest are dropped.
+const MAX_SESSION_TOKENS: usize = 16;
+
+/// Minimum interval between two removals of the expired synchronizer tokens.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Key signing the double-submit tokens.
+static SIGNING_KEY: Lazy<[u8; 32]> = Lazy::new(|| {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    key
+});
+
+/// Issued synchronizer tokens with their expiry, oldest first, keyed by app and session.
+static SYNCHRONIZER_TOKENS: Lazy<DashMap<(ApplicationName, String), VecDeque<(String, Instant)>>> =
+    Lazy::new(DashMap::new);
+
+/// When the expired synchronizer tokens were last removed.
+static LAST_PRUNE: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+
+/// CSRF protection of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CsrfProtection {
+    /// Signed token in a cookie, echoed in a request header.
+    DoubleSubmit,
+    /// Token held by the host, sent back in a request header.
+    Synchronizer,
+}
+
+/// Is the request method safe, i.e. not state-changing.
+pub(crate) fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Random token nonce.
+fn nonce() -> String {
+    let mut nonce = [0u8; TOKEN_NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    hex::encode(nonce)
+}
+
+/// Double-submit token signature MAC of a nonce of an app.
+fn token_mac(app_name: &ApplicationName, nonce: &str) -> anyhow::Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SIGNING_KEY.as_slice())?;
+    mac.update(app_name.0.as_bytes());
+    mac.update(&[0]);
+    mac.update(nonce.as_bytes());
+    Ok(mac)
+}
+
+/// New signed double-submit token of an app.
+fn signed_token(app_name: &ApplicationName) -> anyhow::Result<String> {
+    let nonce = nonce();
+    let signature = token_mac(app_name, &nonce)?.finalize().into_bytes();
+    Ok(format!("{nonce}.{}", hex::encode(signature)))
+}
+
+/// Is the double-submit token signed for the app.
+fn is_signed_token(app_name: &ApplicationName, token: &str) -> bool {
+    let Some((nonce, signature)) = token.split_once('.') else {
+        return false;
+    };
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    token_mac(app_name, nonce).is_ok_and(|mac| mac.verify_slice(&signature).is_ok())
+}
+
+/// Value of the CSRF cookie of the request, if any.
+fn cookie_token(headers: &HeaderMap) -> Option<&str> {
+    request_cookie(headers, CSRF_COOKIE)
+}
+
+/// Value of the session cookie of the request, if any.
+fn session_id(headers: &HeaderMap) -> Option<&str> {
+    request_cookie(headers, SESSION_COOKIE)
+}
+
+/// Consume a synchronizer token issued to the session, if it has not expired.
+fn consume_synchronizer_token(
+    app_name: &ApplicationName, session: &str, token: &str, now: Instant,
+) -> bool {
+    let key = (app_name.clone(), session.to_string());
+    let Some(mut tokens) = SYNCHRONIZER_TOKENS.get_mut(&key) else {
+        return false;
+    };
+    let Some(pos) = tokens.iter().position(|(issued, _)| issued == token) else {
+        return false;
+    };
+    let is_valid = tokens.remove(pos).is_some_and(|(_, expiry)| expiry > now);
+    let is_empty = tokens.is_empty();
+    drop(tokens);
+    if is_empty {
+        SYNCHRONIZER_TOKENS.remove_if(&key, |_, tokens| tokens.is_empty());
+    }
+    is_valid
+}
+
+/// Hold a new synchronizer token for the session, dropping its expired and oldest
+/// tokens.
+fn hold_synchronizer_token(app_name: &ApplicationName, session: &str, token: String, now: Instant) {
+    let mut tokens = SYNCHRONIZER_TOKENS
+        .entry((app_name.clone(), session.to_string()))
+        .or_default();
+    // Tokens are held in the order they were issued, so expire in that order.
+    while tokens.front().is_some_and(|(_, expiry)| *expiry <= now) {
+        tokens.pop_front();
+    }
+    while tokens.len() >= MAX_SESSION_TOKENS {
+        tokens.pop_front();
+    }
+    tokens.push_back((token, now + SYNCHRONIZER_TOKEN_TTL));
+}
+
+/// Remove the expired synchronizer tokens, at most every `PRUNE_INTERVAL`, so the
+/// sessions of the clients which went away do not pile up.
+fn prune(now: Instant) {
+    prune_since(&LAST_PRUNE, now);
+}
+
+/// Remove the expired synchronizer tokens at `now`, unless they were removed less than
+/// `PRUNE_INTERVAL` after `last_prune`.
+fn prune_since(last_prune: &Mutex<Instant>, now: Instant) {
+    // Another request is already pruning if the lock is held.
+    let Ok(mut last_prune) = last_prune.try_lock() else {
+        return;
+    };
+    if now.duration_since(*last_prune) < PRUNE_INTERVAL {
+        return;
+    }
+    *last_prune = now;
+    SYNCHRONIZER_TOKENS.retain(|_, tokens| {
+        tokens.retain(|(_, expiry)| *expiry > now);
+        !tokens.is_empty()
+    });
+}
+
+/// Forget the synchronizer tokens of an app, when it is unloaded.
+pub(super) fn remove_app(app_name: &ApplicationName) {
+    SYNCHRONIZER_TOKENS.retain(|(app, _), _| app != app_name);
+}
+
+/// Value of the CSRF header of the request, if any.
+fn header_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Validate the CSRF token of a state-changing request, consuming a synchronizer token.
+/// Safe requests are always valid.
+pub(crate) fn validate(
+    protection: CsrfProtection, app_name: &ApplicationName, method: &Method, headers: &HeaderMap,
+) -> anyhow::Result<()> {
+    if is_safe(method) {
+        return Ok(());
+    }
+    let token = header_token(headers).ok_or(anyhow::anyhow!("Missing CSRF token"))?;
+
+    let is_valid = match protection {
+        CsrfProtection::DoubleSubmit => {
+            cookie_token(headers) == Some(token) && is_signed_token(app_name, token)
+        },
+        CsrfProtection::Synchronizer => {
+            session_id(headers).is_some_and(|session| {
+                consume_synchronizer_token(app_name, session, token, Instant::now())
+            })
+        },
+    };
+    anyhow::ensure!(is_valid, "Invalid CSRF token");
+    Ok(())
+}
+
+/// Issue a CSRF token on the response to a safe request.
+pub(crate) fn issue(
+    protection: CsrfProtection, app_name: &ApplicationName, method: &Method,
+    request_headers: &HeaderMap, response: &mut Response<Body>,
+) -> anyhow::Result<()> {
+    if !is_safe(method) {
+        return Ok(());
+    }
+
+    match protection {
+        CsrfProtection::DoubleSubmit => {
+            if cookie_token(request_headers).is_some_and(|token| is_signed_token(app_name, token)) {
+                return Ok(());
+            }
+            // Not `HttpOnly`, the client script has to read the token to echo it.
+            let cookie = format!(
+                "{CSRF_COOKIE}={}; Path=/; Secure; SameSite=Strict",
+                signed_token(app_name)?
+            );
+            response
+                .headers_mut()
+                .append(SET_COOKIE, HeaderValue::from_str(&cookie)?);
+        },
+        CsrfProtection::Synchronizer => {
+            let now = Instant::now();
+            prune(now);
+
+            let session = match session_id(request_headers) {
+                Some(session) => session.to_string(),
+                None => {
+                    let session = nonce();
+                    let cookie = format!(
+                        "{SESSION_COOKIE}={session}; Path=/; Secure; HttpOnly; SameSite=Strict"
+                    );
+                    response
+                        .headers_mut()
+                        .append(SET_COOKIE, HeaderValue::from_str(&cookie)?);
+                    session
+                },
+            };
+
+            let token = nonce();
+            response
+                .headers_mut()
+                .insert(CSRF_HEADER, HeaderValue::from_str(&token)?);
+            hold_synchronizer_token(app_name, &session, token, now);
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::COOKIE;
+
+    use super::*;
+
+    /// Request headers carrying the CSRF token issued on the response.
+    fn echo_token(response: &Response<Body>, protection: CsrfProtection) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        match protection {
+            CsrfProtection::DoubleSubmit => {
+                let cookie = response.headers()[SET_COOKIE].to_str().unwrap();
+                let (cookie, _) = cookie.split_once(';').unwrap();
+                let (_, token) = cookie.split_once('=').unwrap();
+                headers.insert(COOKIE, HeaderValue::from_str(cookie).unwrap());
+                headers.insert(CSRF_HEADER, HeaderValue::from_str(token).unwrap());
+            },
+            CsrfProtection::Synchronizer => {
+                let cookie = response.headers()[SET_COOKIE].to_str().unwrap();
+                let (cookie, _) = cookie.split_once(';').unwrap();
+                headers.insert(COOKIE, HeaderValue::from_str(cookie).unwrap());
+                headers.insert(CSRF_HEADER, response.headers()[CSRF_HEADER].clone());
+            },
+        }
+        headers
+    }
+
+    #[test]
+    fn issued_token_is_accepted() {
+        let app_name = ApplicationName("csrf_app".to_string());
+        for protection in [CsrfProtection::DoubleSubmit, CsrfProtection::Synchronizer] {
+            let mut response = Response::new(Body::empty());
+            issue(
+                protection,
+                &app_name,
+                &Method::GET,
+                &HeaderMap::new(),
+                &mut response,
+            )
+            .unwrap();
+
+            let headers = echo_token(&response, protection);
+            assert!(validate(protection, &app_name, &Method::POST, &headers).is_ok());
+
+            let other_app = ApplicationName("other_app".to_string());
+            assert!(validate(protection, &other_app, &Method::POST, &headers).is_err());
+        }
+    }
+
+    #[test]
+    fn state_changing_request_needs_token() {
+        let app_name = ApplicationName("csrf_app".to_string());
+        for protection in [CsrfProtection::DoubleSubmit, CsrfProtection::Synchronizer] {
+            assert!(validate(protection, &app_name, &Method::GET, &HeaderMap::new()).is_ok());
+            assert!(validate(protection, &app_name, &Method::PUT, &HeaderMap::new()).is_err());
+
+            let mut headers = HeaderMap::new();
+            headers.insert(CSRF_HEADER, HeaderValue::from_static("forged"));
+            headers.insert(COOKIE, HeaderValue::from_static("hermes-csrf=forged"));
+            assert!(validate(protection, &app_name, &Method::POST, &headers).is_err());
+        }
+    }
+
+    /// Validate a state-changing synchronizer request.
+    fn validate_synchronizer(
+        app_name: &ApplicationName, headers: &HeaderMap,
+    ) -> anyhow::Result<()> {
+        validate(
+            CsrfProtection::Synchronizer,
+            app_name,
+            &Method::POST,
+            headers,
+        )
+    }
+
+    /// Issue a synchronizer token on the response to a safe request.
+    fn issue_synchronizer(app_name: &ApplicationName, headers: &HeaderMap) -> Response<Body> {
+        let mut response = Response::new(Body::empty());
+        issue(
+            CsrfProtection::Synchronizer,
+            app_name,
+            &Method::GET,
+            headers,
+            &mut response,
+        )
+        .unwrap();
+        response
+    }
+
+    #[test]
+    fn synchronizer_token_is_single_use() {
+        let app_name = ApplicationName("csrf_single_use_app".to_string());
+        let response = issue_synchronizer(&app_name, &HeaderMap::new());
+        let headers = echo_token(&response, CsrfProtection::Synchronizer);
+        assert!(validate_synchronizer(&app_name, &headers).is_ok());
+        assert!(validate_synchronizer(&app_name, &headers).is_err());
+
+        // The session cookie is kept, and the tokens are bound to it.
+        let response = issue_synchronizer(&app_name, &headers);
+        assert!(!response.headers().contains_key(SET_COOKIE));
+        let mut other_session = HeaderMap::new();
+        other_session.insert(CSRF_HEADER, response.headers()[CSRF_HEADER].clone());
+        other_session.insert(
+            COOKIE,
+            HeaderValue::from_static("hermes-csrf-session=other"),
+        );
+        assert!(validate_synchronizer(&app_name, &other_session).is_err());
+    }
+
+    #[test]
+    fn session_tokens_are_capped_and_expire() {
+        let app_name = ApplicationName("csrf_cap_app".to_string());
+        let now = Instant::now();
+        for i in 0..=MAX_SESSION_TOKENS {
+            hold_synchronizer_token(&app_name, "session", i.to_string(), now);
+        }
+        // The oldest token was dropped to hold the newest.
+        assert!(!consume_synchronizer_token(&app_name, "session", "0", now));
+        assert!(consume_synchronizer_token(&app_name, "session", "1", now));
+
+        let expired = now + SYNCHRONIZER_TOKEN_TTL;
+        assert!(!consume_synchronizer_token(
+            &app_name, "session", "2", expired
+        ));
+
+        // Pruned with its own state, so the requests of the other tests do not interfere.
+        let key = (app_name, "session".to_string());
+        let last_prune = Mutex::new(now);
+        prune_since(&last_prune, now + PRUNE_INTERVAL / 2);
+        assert!(SYNCHRONIZER_TOKENS.contains_key(&key));
+        prune_since(
+            &last_prune,
+            now + PRUNE_INTERVAL.max(SYNCHRONIZER_TOKEN_TTL),
+        );
+        assert!(!SYNCHRONIZER_TOKENS.contains_key(&key));
+    }
+}
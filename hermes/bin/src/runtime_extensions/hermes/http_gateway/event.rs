@@ -5,7 +5,13 @@ use std::sync::mpsc::Sender;
 use hyper::{self, body::Bytes};
 use serde::{Deserialize, Serialize};
 
-use crate::event::HermesEventPayload;
+use crate::{
+    event::HermesEventPayload,
+    runtime_extensions::{
+        bindings::wasi::http::types::{Method as WasiMethod, Scheme},
+        wasi::http::{new_incoming_request, IncomingRequestState},
+    },
+};
 
 /// HTTP response code
 type Code = u16;
@@ -71,3 +77,95 @@ impl HermesEventPayload for HTTPEvent {
         }
     }
 }
+
+/// HTTP Event dispatched to the modules implementing the standard
+/// `wasi:http/incoming-handler` interface.
+pub(crate) struct WasiHttpEvent {
+    /// HTTP Headers
+    pub(crate) headers: HeadersKV,
+    /// HTTP Method
+    pub(crate) method: hyper::Method,
+    /// HTTP Path with the query
+    pub(crate) path_with_query: String,
+    /// HTTP Authority, from the `Host` header
+    pub(crate) authority: Option<String>,
+    /// HTTP Body
+    pub(crate) body: Bytes,
+    /// Waits for wasm modules to complete and sends the response back to the waiting
+    /// receiver.
+    pub(crate) sender: Sender<HTTPEventMsg>,
+}
+
+impl HermesEventPayload for WasiHttpEvent {
+    fn event_name(&self) -> &str {
+        "wasi-http-event"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        let app_name = module.store.data().app_name().clone();
+        let (request, response_out, slot) =
+            new_incoming_request(&app_name, IncomingRequestState {
+                method: wasi_method(&self.method),
+                path_with_query: Some(self.path_with_query.clone()),
+                scheme: Some(Scheme::Http),
+                authority: self.authority.clone(),
+                headers: self
+                    .headers
+                    .iter()
+                    .flat_map(|(name, values)| {
+                        values
+                            .iter()
+                            .map(|value| (name.clone(), value.clone().into_bytes()))
+                    })
+                    .collect(),
+                body: Some(self.body.to_vec()),
+            })?;
+
+        module.instance.wasi_http_incoming_handler().call_handle(
+            &mut module.store,
+            request,
+            response_out,
+        )?;
+
+        // The module did not send a response, e.g. it does not handle the request.
+        let Some(resp) = slot
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Response slot lock poisoned"))?
+            .take()
+        else {
+            return Ok(());
+        };
+        let mut headers: HeadersKV = Vec::new();
+        for (name, value) in resp.headers {
+            let value = String::from_utf8_lossy(&value).into_owned();
+            match headers
+                .iter_mut()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&name))
+            {
+                Some((_, values)) => values.push(value),
+                None => headers.push((name, vec![value])),
+            }
+        }
+        Ok(self.sender.send(HTTPEventMsg::HttpEventResponse((
+            resp.status_code,
+            headers,
+            resp.body.contents(),
+        )))?)
+    }
+}
+
+/// `wasi:http` method of an HTTP method.
+fn wasi_method(method: &hyper::Method) -> WasiMethod {
+    match *method {
+        hyper::Method::GET => WasiMethod::Get,
+        hyper::Method::HEAD => WasiMethod::Head,
+        hyper::Method::POST => WasiMethod::Post,
+        hyper::Method::PUT => WasiMethod::Put,
+        hyper::Method::DELETE => WasiMethod::Delete,
+        hyper::Method::CONNECT => WasiMethod::Connect,
+        hyper::Method::OPTIONS => WasiMethod::Options,
+        hyper::Method::TRACE => WasiMethod::Trace,
+        hyper::Method::PATCH => WasiMethod::Patch,
+        ref other => WasiMethod::Other(other.to_string()),
+    }
+}
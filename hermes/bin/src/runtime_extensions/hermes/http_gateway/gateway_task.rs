@@ -4,7 +4,10 @@ use std::{
     collections::HashMap,
     convert::Infallible,
     net::SocketAddr,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use hyper::{
@@ -14,11 +17,14 @@ use hyper::{
 };
 use tracing::{error, info};
 
-use super::routing::router;
+use super::{routing::router, warmup};
 
 /// HTTP Gateway port
 const GATEWAY_PORT: u16 = 5000;
 
+/// Whether the gateway is bound and accepting connections.
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
 /// hostname (node name)
 #[derive(Debug, Clone)]
 pub(crate) struct Hostname(pub String);
@@ -82,6 +88,11 @@ impl ConnectionManager {
     }
 }
 
+/// Is the gateway bound and accepting connections.
+pub(crate) fn is_listening() -> bool {
+    LISTENING.load(Ordering::Acquire)
+}
+
 /// Spawns a OS thread running the Tokio runtime task.
 pub(crate) fn spawn() {
     std::thread::spawn(move || {
@@ -125,12 +136,16 @@ fn executor() {
             }
         });
 
-        match Server::bind(&config.local_addr)
-            .serve(gateway_service)
-            .await
-        {
-            Ok(()) => (),
+        // Do not accept connections before the modules are ready to serve them.
+        warmup::wait().await;
+
+        let server = Server::bind(&config.local_addr).serve(gateway_service);
+        LISTENING.store(true, Ordering::Release);
+
+        match server.await {
+            Ok(()) => LISTENING.store(false, Ordering::Release),
             Err(err) => {
+                LISTENING.store(false, Ordering::Release);
                 error!("Failing to start HTTP gateway server: {:?}", err);
                 error!("Retrying!");
                 executor();
@@ -0,0 +1,84 @@
+//! Catalyst ID of the authenticated caller of a gateway request.
+//!
+//! A caller authenticates with an RBAC token in the `Authorization` header,
+//! `Bearer catid.<catalyst id>.<signature>`, where
+//! - the Catalyst ID is `[<username>@][<nonce>@]<network>/<role 0 key>`, the role 0 key
+//!   being an Ed25519 public key, and
+//! - the signature is the Ed25519 signature of `catid.<catalyst id>.` by the role 0 key,
+//!
+//! both keys and signatures encoded in unpadded base64url.
+//!
+//! The gateway only checks the caller holds the role 0 key of the Catalyst ID, whether
+//! the key is registered on chain is up to the modules of the app.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use hyper::{header::AUTHORIZATION, HeaderMap};
+
+/// Prefix of the RBAC tokens.
+const TOKEN_PREFIX: &str = "catid.";
+
+/// Authenticated Catalyst ID of the caller, as `<network>/<role 0 key>`, if the request
+/// has a valid RBAC token.
+///
+/// The username and nonce are left out, they do not change who the caller is.
+pub(crate) fn catalyst_id(headers: &HeaderMap) -> Option<String> {
+    let token = headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+    let (signed, signature) = token.rsplit_once('.')?;
+    let catalyst_id = signed.strip_prefix(TOKEN_PREFIX)?;
+
+    let id = catalyst_id.rsplit('@').next()?;
+    let (_network, key) = id.split_once('/')?;
+    let key = VerifyingKey::from_bytes(&URL_SAFE_NO_PAD.decode(key).ok()?.try_into().ok()?).ok()?;
+    let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature).ok()?).ok()?;
+    // The signature covers the trailing separator too.
+    let message = token.get(..=signed.len())?;
+    key.verify(message.as_bytes(), &signature).ok()?;
+
+    Some(id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    /// Headers with an RBAC token of the Catalyst ID signed by `key`.
+    fn headers(catalyst_id: &str, key: &SigningKey) -> HeaderMap {
+        let signed = format!("{TOKEN_PREFIX}{catalyst_id}.");
+        let signature = URL_SAFE_NO_PAD.encode(key.sign(signed.as_bytes()).to_bytes());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {signed}{signature}")).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn catalyst_id_test() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let id = format!(
+            "cardano/{}",
+            URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes())
+        );
+
+        assert_eq!(catalyst_id(&headers(&id, &key)), Some(id.clone()));
+        // The username and nonce are not part of the identity.
+        assert_eq!(
+            catalyst_id(&headers(&format!("alice@1735689600@{id}"), &key)),
+            Some(id.clone())
+        );
+
+        // Signed by another key than the role 0 key of the Catalyst ID.
+        let other_key = SigningKey::from_bytes(&[2; 32]);
+        assert_eq!(catalyst_id(&headers(&id, &other_key)), None);
+        assert_eq!(catalyst_id(&HeaderMap::new()), None);
+    }
+}
@@ -1,11 +1,25 @@
 //! HTTP Gateway
 
-use gateway_task::spawn;
+pub(crate) use event::{HTTPEvent, HTTPEventMsg};
+use gateway_task::{is_listening, spawn};
+pub(crate) use variants::metrics as variant_metrics;
+pub(crate) use warmup::emit_warmup_events;
 
+use crate::{app::ApplicationName, runtime_extensions::bindings::hermes::init::api::HealthStatus};
+
+mod conditional;
+mod csrf;
 mod event;
 mod gateway_task;
+mod identity;
+mod rate_limit;
+/// Per-app gateway route configuration
+mod routes;
 /// Gateway routing logic
 mod routing;
+mod rpc;
+mod variants;
+mod warmup;
 
 ///  State.
 static STATE: once_cell::sync::Lazy<()> = once_cell::sync::Lazy::new(|| {
@@ -17,3 +31,21 @@ pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {
     // Init state event
     let () = *STATE;
 }
+
+/// Forget the routes and request state of an unloaded app.
+pub(crate) fn unload_app(app_name: &ApplicationName) {
+    routes::remove_app(app_name);
+    conditional::remove_app(app_name);
+    csrf::remove_app(app_name);
+    rate_limit::remove_app(app_name);
+    variants::remove_app(app_name);
+}
+
+/// Health of the HTTP gateway.
+pub(crate) fn health() -> HealthStatus {
+    if is_listening() {
+        HealthStatus::Ready
+    } else {
+        HealthStatus::Unavailable
+    }
+}
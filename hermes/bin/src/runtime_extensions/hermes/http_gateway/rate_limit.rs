@@ -0,0 +1,181 @@
+//! Per-client rate limiting of gateway routes.
+//!
+//! A client is the authenticated Catalyst ID of the caller, if the request has one, its
+//! IP address otherwise.
+
+use std::{
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::app::ApplicationName;
+
+/// Interval at which the expired windows are removed.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Current rate limit windows, keyed by app, route path and client.
+static WINDOWS: Lazy<DashMap<(ApplicationName, String, Client), Window>> = Lazy::new(DashMap::new);
+
+/// When the expired windows were last removed.
+static LAST_PRUNE: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+
+/// Client whose requests are rate limited.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Client {
+    /// Authenticated Catalyst ID of the caller.
+    CatalystId(String),
+    /// IP address of an unauthenticated caller.
+    Ip(IpAddr),
+}
+
+/// Requests counted in a rate limit window.
+struct Window {
+    /// When the window started.
+    started: Instant,
+    /// Length of the window.
+    period: Duration,
+    /// Number of requests in the window.
+    requests: u32,
+}
+
+impl Window {
+    /// Whether the window ended at `now`.
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.started) >= self.period
+    }
+}
+
+/// Rate limit of a route: at most `requests` per client every `period_secs` seconds.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RateLimit {
+    /// Requests allowed per period.
+    requests: u32,
+    /// Length of the period in seconds.
+    period_secs: u64,
+}
+
+impl RateLimit {
+    /// Count a request of a client to a route.
+    /// Returns how long to wait before retrying if the client exceeded the limit.
+    pub(crate) fn check(
+        self, app_name: &ApplicationName, route: &str, client: Client,
+    ) -> Result<(), Duration> {
+        let period = Duration::from_secs(self.period_secs);
+        let now = Instant::now();
+        prune(now);
+
+        let mut window = WINDOWS
+            .entry((app_name.clone(), route.to_string(), client))
+            .or_insert(Window {
+                started: now,
+                period,
+                requests: 0,
+            });
+        if window.is_expired(now) {
+            window.started = now;
+            window.period = period;
+            window.requests = 0;
+        }
+
+        if window.requests >= self.requests {
+            return Err(period.saturating_sub(now.duration_since(window.started)));
+        }
+        window.requests = window.requests.saturating_add(1);
+        Ok(())
+    }
+}
+
+/// Forget the rate limit windows of an app, when it is unloaded.
+pub(super) fn remove_app(app_name: &ApplicationName) {
+    WINDOWS.retain(|(app, ..), _| app != app_name);
+}
+
+/// Remove the expired windows, at most every `PRUNE_INTERVAL`, so the windows of the
+/// clients which stopped sending requests do not pile up.
+fn prune(now: Instant) {
+    prune_since(&LAST_PRUNE, now);
+}
+
+/// Remove the expired windows at `now`, unless they were removed less than
+/// `PRUNE_INTERVAL` after `last_prune`.
+fn prune_since(last_prune: &Mutex<Instant>, now: Instant) {
+    // Another request is already pruning if the lock is held.
+    let Ok(mut last_prune) = last_prune.try_lock() else {
+        return;
+    };
+    if now.duration_since(*last_prune) < PRUNE_INTERVAL {
+        return;
+    }
+    *last_prune = now;
+    WINDOWS.retain(|_, window| !window.is_expired(now));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_over_limit_are_rejected() {
+        let app_name = ApplicationName("rate_limit_app".to_string());
+        let limit = RateLimit {
+            requests: 2,
+            period_secs: 60,
+        };
+        let client = Client::Ip(IpAddr::from([127, 0, 0, 1]));
+        let other_client = Client::Ip(IpAddr::from([127, 0, 0, 2]));
+
+        assert!(limit.check(&app_name, "/api", client.clone()).is_ok());
+        assert!(limit.check(&app_name, "/api", client.clone()).is_ok());
+        assert!(limit.check(&app_name, "/api", client.clone()).is_err());
+
+        assert!(limit.check(&app_name, "/api", other_client).is_ok());
+        assert!(limit.check(&app_name, "/api/other", client).is_ok());
+    }
+
+    #[test]
+    fn authenticated_callers_are_limited_by_catalyst_id() {
+        let app_name = ApplicationName("rate_limit_catalyst_id_app".to_string());
+        let limit = RateLimit {
+            requests: 1,
+            period_secs: 60,
+        };
+        let caller =
+            Client::CatalystId("cardano/FftxFnOrj2qmTuB2oZG2v0YEWJfKvQ9Gg8AgNAhDsKE".to_string());
+        let other_caller =
+            Client::CatalystId("cardano/AAtxFnOrj2qmTuB2oZG2v0YEWJfKvQ9Gg8AgNAhDsKE".to_string());
+        let ip = Client::Ip(IpAddr::from([127, 0, 0, 4]));
+
+        // Each caller has its own limit, even when calling from the same IP address.
+        assert!(limit.check(&app_name, "/api", caller.clone()).is_ok());
+        assert!(limit.check(&app_name, "/api", caller).is_err());
+        assert!(limit.check(&app_name, "/api", other_caller).is_ok());
+        // Unauthenticated requests from that IP address are limited separately.
+        assert!(limit.check(&app_name, "/api", ip.clone()).is_ok());
+        assert!(limit.check(&app_name, "/api", ip).is_err());
+    }
+
+    #[test]
+    fn expired_windows_are_pruned() {
+        let app_name = ApplicationName("rate_limit_prune_app".to_string());
+        let limit = RateLimit {
+            requests: 1,
+            period_secs: 0,
+        };
+        let client = Client::Ip(IpAddr::from([127, 0, 0, 3]));
+        let key = (app_name.clone(), "/api".to_string(), client.clone());
+
+        assert!(limit.check(&app_name, "/api", client).is_ok());
+        assert!(WINDOWS.contains_key(&key));
+
+        // Pruned with its own state, so the requests of the other tests do not interfere.
+        let now = Instant::now();
+        prune_since(&Mutex::new(now), now + PRUNE_INTERVAL);
+        assert!(!WINDOWS.contains_key(&key));
+    }
+}
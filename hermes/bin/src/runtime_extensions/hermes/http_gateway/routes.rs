@@ -0,0 +1,205 @@
+//! Per-app gateway route configuration.
+//!
+//! An app configures its gateway routes in the `share/gateway.json` file of its
+//! package, e.g.
+//!
+//! ```json
+//! { "routes": [{
+//!     "path": "/api",
+//!     "csrf": "double-submit",
+//!     "request_schema": "share/schemas/request.schema.json",
+//!     "rate_limit": { "requests": 10, "period_secs": 60 },
+//!     "handler": "hermes",
+//!     "variants": [
+//!         { "module": "doc-sync", "weight": 90 },
+//!         { "module": "doc-sync-canary", "weight": 10 }
+//!     ]
+//! }] }
+//! ```
+//!
+//! A request is handled by the route with the longest `path` prefixing its path.
+//!
+//! The `handler` of a route selects the interface the requests are dispatched to, the
+//! bespoke `hermes:http-gateway/event` by default, or the standard
+//! `wasi:http/incoming-handler` with `"handler": "wasi-http"`.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use super::{csrf::CsrfProtection, rate_limit::RateLimit, variants::Variant};
+use crate::{app::ApplicationName, packaging::schema_validation::SchemaValidator, reactor};
+
+/// Gateway route config file in the app VFS.
+const ROUTES_CONFIG_FILE: &str = "share/gateway.json";
+
+/// Loaded route config of every app.
+static ROUTES: Lazy<DashMap<ApplicationName, Arc<RoutesConfig>>> = Lazy::new(DashMap::new);
+
+/// Gateway route config of an app.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RoutesConfig {
+    /// Configured routes.
+    #[serde(default)]
+    routes: Vec<RouteConfig>,
+}
+
+/// Config of a single gateway route.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RouteConfig {
+    /// Path prefix of the requests the route handles.
+    pub(crate) path: String,
+    /// CSRF protection of the state-changing requests of the route.
+    #[serde(default)]
+    pub(crate) csrf: Option<CsrfProtection>,
+    /// VFS path of the JSON schema (draft 7) the request bodies of the route must match.
+    #[serde(default)]
+    request_schema: Option<String>,
+    /// Validator of the request schema, compiled when the config is loaded.
+    #[serde(skip)]
+    request_validator: Option<Arc<SchemaValidator>>,
+    /// Rate limit of the requests of each client to the route.
+    #[serde(default)]
+    pub(crate) rate_limit: Option<RateLimit>,
+    /// Module variants serving the route, all the app modules if empty.
+    #[serde(default)]
+    pub(crate) variants: Vec<Variant>,
+    /// Interface of the modules the requests of the route are dispatched to.
+    #[serde(default)]
+    pub(crate) handler: RouteHandler,
+}
+
+/// Interface of the modules handling the requests of a route.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum RouteHandler {
+    /// `hermes:http-gateway/event`, responding with a JSON encoded response.
+    #[default]
+    Hermes,
+    /// `wasi:http/incoming-handler`, responding with the status, headers and body the
+    /// module sets.
+    WasiHttp,
+}
+
+impl RouteConfig {
+    /// Validator of the request bodies of the route, if the route declares a request
+    /// schema. An error if the declared schema could not be loaded.
+    pub(crate) fn request_validator(&self) -> Option<anyhow::Result<Arc<SchemaValidator>>> {
+        let schema = self.request_schema.as_ref()?;
+        Some(
+            self.request_validator
+                .clone()
+                .ok_or(anyhow::anyhow!("Invalid request schema {schema}")),
+        )
+    }
+}
+
+impl RoutesConfig {
+    /// Route config handling the request path, if any.
+    pub(crate) fn route(&self, path: &str) -> Option<&RouteConfig> {
+        self.routes
+            .iter()
+            .filter(|route| matches_prefix(&route.path, path))
+            .max_by_key(|route| route.path.len())
+    }
+}
+
+/// Is `path` equal to `prefix` or below it.
+pub(crate) fn matches_prefix(prefix: &str, path: &str) -> bool {
+    match path.strip_prefix(prefix.trim_end_matches('/')) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Load the route config of an app from its VFS.
+/// Apps without a route config get an empty one.
+fn load(app_name: &ApplicationName) -> RoutesConfig {
+    let Ok(app) = reactor::get_app(app_name) else {
+        return RoutesConfig::default();
+    };
+    let Ok(config) = app.vfs().read(ROUTES_CONFIG_FILE) else {
+        return RoutesConfig::default();
+    };
+    let mut config: RoutesConfig = serde_json::from_slice(&config).unwrap_or_else(|err| {
+        tracing::error!(app_name = %app_name, "invalid gateway route config: {err}");
+        RoutesConfig::default()
+    });
+
+    for route in &mut config.routes {
+        let Some(schema) = &route.request_schema else {
+            continue;
+        };
+        match app
+            .vfs()
+            .read(schema)
+            .and_then(|schema| SchemaValidator::from_reader(schema.as_slice()))
+        {
+            Ok(validator) => route.request_validator = Some(Arc::new(validator)),
+            // Requests to the route are rejected, rather than let through unvalidated.
+            Err(err) => {
+                tracing::error!(app_name = %app_name, route = %route.path, "invalid request schema {schema}: {err}");
+            },
+        }
+    }
+    config
+}
+
+/// Route config of an app.
+pub(crate) fn config(app_name: &ApplicationName) -> Arc<RoutesConfig> {
+    ROUTES
+        .entry(app_name.clone())
+        .or_insert_with(|| Arc::new(load(app_name)))
+        .clone()
+}
+
+/// Forget the route config of an app, when it is unloaded.
+pub(super) fn remove_app(app_name: &ApplicationName) {
+    ROUTES.remove(app_name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_route() {
+        let config: RoutesConfig = serde_json::from_str(
+            r#"{ "routes": [
+                { "path": "/api" },
+                { "path": "/api/doc-sync/", "csrf": "double-submit" }
+            ] }"#,
+        )
+        .unwrap();
+
+        assert!(config.route("/api/doc-sync/post").unwrap().csrf.is_some());
+        assert!(config.route("/api/doc-sync").unwrap().csrf.is_some());
+        assert!(config.route("/api/doc-syncer").unwrap().csrf.is_none());
+        assert!(config.route("/apis").is_none());
+    }
+
+    #[test]
+    fn route_handler() {
+        let config: RoutesConfig = serde_json::from_str(
+            r#"{ "routes": [
+                { "path": "/api" },
+                { "path": "/api/wasi/", "handler": "wasi-http" }
+            ] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.route("/api").unwrap().handler, RouteHandler::Hermes);
+        assert_eq!(
+            config.route("/api/wasi/get").unwrap().handler,
+            RouteHandler::WasiHttp
+        );
+        assert!(serde_json::from_str::<RoutesConfig>(
+            r#"{ "routes": [{ "path": "/api", "handler": "grpc" }] }"#
+        )
+        .is_err());
+    }
+}
@@ -12,24 +12,37 @@ use anyhow::{anyhow, Ok};
 use hyper::{
     self,
     body::{Bytes, HttpBody},
-    Body, HeaderMap, Request, Response, StatusCode,
+    header::{CONTENT_TYPE, COOKIE, HOST, RETRY_AFTER},
+    Body, HeaderMap, Method, Request, Response, StatusCode,
 };
 use regex::Regex;
 use tracing::info;
 
 use super::{
-    event::{HTTPEvent, HTTPEventMsg, HeadersKV},
+    conditional::{with_validator_headers, ConditionalRequest},
+    csrf,
+    event::{HTTPEvent, HTTPEventMsg, HeadersKV, WasiHttpEvent},
     gateway_task::{ClientIPAddr, Config, ConnectionManager, EventUID, LiveConnection, Processed},
+    identity,
+    rate_limit::Client,
+    routes::{self, RouteConfig, RouteHandler, RoutesConfig},
+    rpc::Protocol,
+    variants,
 };
 use crate::{
     app::ApplicationName,
     event::{HermesEvent, TargetApp, TargetModule},
+    packaging::schema_validation::SchemaValidator,
     reactor,
+    runtime_extensions::{bindings::hermes::init::api::HealthStatus, hermes::init::health},
 };
 
 /// Everything that hits /api routes to Webasm Component Modules
 const WEBASM_ROUTE: &str = "/api";
 
+/// Aggregated runtime extension health of the node
+const HEALTH_ROUTE: &str = "/health";
+
 /// Check path is valid for static files
 const VALID_PATH: &str = r"^((/[a-zA-Z0-9-_]+)+|/)$";
 
@@ -56,6 +69,39 @@ pub(crate) fn not_found() -> anyhow::Result<Response<Body>> {
         .body("Not Found".into())?)
 }
 
+/// HTTP bad request response generator
+pub(crate) fn bad_request(err: String) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .body(err.into())?)
+}
+
+/// HTTP too many requests response generator
+pub(crate) fn too_many_requests(retry_after: Duration) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(RETRY_AFTER, retry_after.as_secs().max(1))
+        .body("Too Many Requests".into())?)
+}
+
+/// HTTP forbidden response generator
+pub(crate) fn forbidden(err: String) -> anyhow::Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .body(err.into())?)
+}
+
+/// Value of a cookie of the request, if any.
+pub(crate) fn request_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find_map(|(cookie_name, value)| (cookie_name == name).then_some(value))
+}
+
 /// Extractor that resolves the hostname of the request.
 /// Hostname is resolved through the Host header
 pub(crate) fn host_resolver(headers: &HeaderMap) -> anyhow::Result<(ApplicationName, Hostname)> {
@@ -98,7 +144,7 @@ pub(crate) async fn router(
         .iter()
         .any(|host| host.0 == resolved_host.0.as_str())
     {
-        route_to_hermes(req, app_name.clone()).await?
+        route_to_hermes(req, app_name.clone(), ip).await?
     } else {
         return Ok(error_response("Hostname not valid".to_owned())?);
     };
@@ -120,48 +166,281 @@ pub(crate) async fn router(
     Ok(response)
 }
 
+/// Where a request is dispatched to.
+#[derive(Debug)]
+enum Dispatch {
+    /// The WASM modules of the app, with the config of the route handling the request.
+    ConfiguredRoute(RouteConfig),
+    /// The WASM modules of the app, without a route config.
+    Modules,
+    /// The node health report.
+    Health,
+    /// The static files of the app.
+    StaticData,
+    /// Nowhere, the path is invalid.
+    NotFound,
+}
+
+/// Where a request to the path is dispatched, given the route config of the app.
+///
+/// Requests handled by a configured route, and RPCs and requests to `/api` or below
+/// without one, go to the WASM modules, except for the health report.
+fn dispatch(path: &str, is_rpc: bool, config: &RoutesConfig) -> Dispatch {
+    if path == HEALTH_ROUTE {
+        return Dispatch::Health;
+    }
+    // RPCs go through the config of the route of their path like any other request.
+    if let Some(route) = config.route(path) {
+        Dispatch::ConfiguredRoute(route.clone())
+    } else if is_rpc || routes::matches_prefix(WEBASM_ROUTE, path) {
+        Dispatch::Modules
+    } else if is_valid_path(path).is_ok() {
+        Dispatch::StaticData
+    } else {
+        Dispatch::NotFound
+    }
+}
+
 /// Route single request to hermes backend
 async fn route_to_hermes(
-    req: Request<Body>, app_name: ApplicationName,
+    req: Request<Body>, app_name: ApplicationName, client: SocketAddr,
 ) -> anyhow::Result<Response<Body>> {
-    let (lambda_send, lambda_recv_answer): (Sender<HTTPEventMsg>, Receiver<HTTPEventMsg>) =
-        channel();
-
-    let uri = req.uri().to_owned();
-    let method = req.method().to_owned().to_string();
     let path = req.uri().path().to_string();
+    let is_rpc = Protocol::detect(req.method(), req.headers()).is_some();
 
-    let mut header_map: HashMap<String, Vec<String>> = HashMap::new();
+    match dispatch(&path, is_rpc, &routes::config(&app_name)) {
+        Dispatch::ConfiguredRoute(route) => {
+            route_to_configured_route(req, app_name, route, client).await
+        },
+        Dispatch::Modules => {
+            let target = (TargetApp::All, TargetModule::All);
+            route_to_modules(req, app_name, None, RouteHandler::Hermes, target).await
+        },
+        Dispatch::Health => health_response().await,
+        Dispatch::StaticData => serve_static_data(&path, &app_name),
+        Dispatch::NotFound => Ok(not_found()?),
+    }
+}
 
-    for (header_name, header_val) in req.headers() {
-        header_map
-            .entry(header_name.to_string())
-            .or_default()
-            .push(header_val.to_str()?.to_string());
+/// Route a request to the WASM modules of the app, applying the route config.
+async fn route_to_configured_route(
+    req: Request<Body>, app_name: ApplicationName, route: RouteConfig, client: SocketAddr,
+) -> anyhow::Result<Response<Body>> {
+    if let Some(rate_limit) = route.rate_limit {
+        let client = identity::catalyst_id(req.headers())
+            .map_or(Client::Ip(client.ip()), Client::CatalystId);
+        if let Err(retry_after) = rate_limit.check(&app_name, &route.path, client) {
+            return too_many_requests(retry_after);
+        }
     }
 
-    if uri.path() == WEBASM_ROUTE {
-        compose_http_event(
-            method,
-            header_map.into_iter().collect(),
-            req.collect().await?.to_bytes(), // body
-            path,
-            lambda_send,
-            &lambda_recv_answer,
-        )
-    } else if is_valid_path(uri.path()).is_ok() {
-        serve_static_data(uri.path(), &app_name)
-    } else {
-        Ok(not_found()?)
+    let method = req.method().clone();
+    let headers = req.headers().clone();
+    if let Some(csrf) = route.csrf {
+        if let Err(err) = csrf::validate(csrf, &app_name, &method, &headers) {
+            return forbidden(err.to_string());
+        }
+    }
+
+    let variant = variants::select(&route.path, &route.variants, &headers);
+    let target = match &variant {
+        Some(variant) => {
+            let app = reactor::get_app(&app_name)?;
+            let Some(module_id) = app.module_id(&variant.module) else {
+                return error_response(format!("Unknown variant module {}", variant.module));
+            };
+            (
+                TargetApp::List(vec![app_name.clone()]),
+                TargetModule::List(vec![module_id.clone()]),
+            )
+        },
+        None => (TargetApp::All, TargetModule::All),
+    };
+
+    let response = route_to_modules(
+        req,
+        app_name.clone(),
+        route.request_validator(),
+        route.handler,
+        target,
+    )
+    .await;
+    if let Some(variant) = &variant {
+        let failed = response
+            .as_ref()
+            .map_or(true, |response| response.status().is_server_error());
+        variants::record(&app_name, &route.path, &variant.module, failed);
+    }
+
+    let mut response = response?;
+    if let Some(csrf) = route.csrf {
+        csrf::issue(csrf, &app_name, &method, &headers, &mut response)?;
+    }
+    if let Some(variant) = &variant {
+        variants::stick(variant, &mut response)?;
+    }
+    Ok(response)
+}
+
+/// Route a request to the target WASM modules.
+/// RPC requests are unwrapped, and the module response wrapped, by their protocol.
+/// Request bodies not matching the route request schema are rejected with `400`.
+async fn route_to_modules(
+    req: Request<Body>, app_name: ApplicationName,
+    request_validator: Option<anyhow::Result<Arc<SchemaValidator>>>, handler: RouteHandler,
+    target: (TargetApp, TargetModule),
+) -> anyhow::Result<Response<Body>> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let path_with_query = req
+        .uri()
+        .path_and_query()
+        .map_or_else(|| path.clone(), ToString::to_string);
+    let authority = req
+        .headers()
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .map(ToString::to_string);
+    let request_headers = req.headers().clone();
+    let headers = headers_kv(&request_headers)?;
+    let rpc = Protocol::detect(&method, &request_headers);
+
+    let conditional = ConditionalRequest::new(&app_name, &req);
+    if let Some(response) = conditional
+        .as_ref()
+        .and_then(ConditionalRequest::cached_response)
+    {
+        return response;
+    }
+
+    let has_body = matches!(*req.method(), Method::POST | Method::PUT | Method::PATCH);
+    let mut body = req.collect().await?.to_bytes();
+    if let Some(protocol) = rpc {
+        match protocol.request_message(body) {
+            Result::Ok(message) => body = message,
+            Err(err) => return bad_request(err.to_string()),
+        }
+    }
+    let request_validator = match request_validator {
+        Some(Err(err)) if has_body => return error_response(err.to_string()),
+        request_validator => request_validator.and_then(Result::ok),
+    };
+    if let (true, Some(request_validator)) = (has_body, request_validator) {
+        let validation = serde_json::from_slice(&body)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| request_validator.validate(&json));
+        if let Err(err) = validation {
+            return bad_request(format!("Malformed request body: {err}"));
+        }
+    }
+
+    match (rpc, handler) {
+        (Some(protocol), RouteHandler::Hermes) => {
+            match send_http_event(method.to_string(), headers, body, path, target.0, target.1)? {
+                HTTPEventMsg::HttpEventResponse((code, _, body)) => {
+                    protocol.response(code, body, &request_headers)
+                },
+                HTTPEventMsg::HTTPEventReceiver => {
+                    Ok(error_response("HTTP event msg error".to_owned())?)
+                },
+            }
+        },
+        (Some(_), RouteHandler::WasiHttp) => {
+            bad_request("RPCs are not supported on wasi-http routes".to_owned())
+        },
+        (None, RouteHandler::Hermes) => {
+            compose_http_event(
+                method.to_string(),
+                headers,
+                body,
+                path,
+                conditional.as_ref(),
+                target,
+            )
+        },
+        (None, RouteHandler::WasiHttp) => {
+            compose_wasi_http_event(
+                method,
+                headers,
+                body,
+                (path_with_query, authority),
+                conditional.as_ref(),
+                target,
+            )
+        },
     }
 }
 
 /// Compose http event and send to global queue, await queue response and relay back to
 /// waiting receiver channel for HTTP response
 fn compose_http_event(
-    method: String, headers: HeadersKV, body: Bytes, path: String, sender: Sender<HTTPEventMsg>,
-    receiver: &Receiver<HTTPEventMsg>,
+    method: String, headers: HeadersKV, body: Bytes, path: String,
+    conditional: Option<&ConditionalRequest>,
+    (target_app, target_module): (TargetApp, TargetModule),
+) -> anyhow::Result<Response<Body>> {
+    match &send_http_event(method, headers, body, path, target_app, target_module)? {
+        HTTPEventMsg::HttpEventResponse(resp) => {
+            let (code, headers, _) = resp;
+            if let Some(response) =
+                conditional.and_then(|conditional| conditional.module_response(*code, headers))
+            {
+                return response;
+            }
+            Ok(with_validator_headers(Response::builder(), headers)
+                .body(serde_json::to_string(&resp)?.into())?)
+        },
+        HTTPEventMsg::HTTPEventReceiver => Ok(error_response("HTTP event msg error".to_owned())?),
+    }
+}
+
+/// Compose a `wasi:http` event and send to global queue, await queue response and relay
+/// the status, headers and body the module set as the HTTP response
+fn compose_wasi_http_event(
+    method: Method, headers: HeadersKV, body: Bytes,
+    (path_with_query, authority): (String, Option<String>),
+    conditional: Option<&ConditionalRequest>,
+    (target_app, target_module): (TargetApp, TargetModule),
 ) -> anyhow::Result<Response<Body>> {
+    let (sender, receiver): (Sender<HTTPEventMsg>, Receiver<HTTPEventMsg>) = channel();
+
+    let on_wasi_http_event = WasiHttpEvent {
+        headers,
+        method,
+        path_with_query,
+        authority,
+        body,
+        sender,
+    };
+
+    let event = HermesEvent::new(on_wasi_http_event, target_app, target_module);
+    crate::event::queue::send(event)?;
+
+    match receiver.recv_timeout(Duration::from_secs(EVENT_TIMEOUT))? {
+        HTTPEventMsg::HttpEventResponse((code, headers, body)) => {
+            if let Some(response) =
+                conditional.and_then(|conditional| conditional.module_response(code, &headers))
+            {
+                return response;
+            }
+            let mut response = Response::builder().status(code);
+            for (name, values) in &headers {
+                for value in values {
+                    response = response.header(name, value);
+                }
+            }
+            Ok(response.body(body.into())?)
+        },
+        HTTPEventMsg::HTTPEventReceiver => Ok(error_response("HTTP event msg error".to_owned())?),
+    }
+}
+
+/// Send http event to global queue and await the module response
+fn send_http_event(
+    method: String, headers: HeadersKV, body: Bytes, path: String, target_app: TargetApp,
+    target_module: TargetModule,
+) -> anyhow::Result<HTTPEventMsg> {
+    let (sender, receiver): (Sender<HTTPEventMsg>, Receiver<HTTPEventMsg>) = channel();
+
     let on_http_event = HTTPEvent {
         headers,
         method,
@@ -170,16 +449,46 @@ fn compose_http_event(
         sender,
     };
 
-    let event = HermesEvent::new(on_http_event, TargetApp::All, TargetModule::All);
+    let event = HermesEvent::new(on_http_event, target_app, target_module);
 
     crate::event::queue::send(event)?;
 
-    match &receiver.recv_timeout(Duration::from_secs(EVENT_TIMEOUT))? {
-        HTTPEventMsg::HttpEventResponse(resp) => {
-            Ok(Response::new(serde_json::to_string(&resp)?.into()))
-        },
-        HTTPEventMsg::HTTPEventReceiver => Ok(error_response("HTTP event msg error".to_owned())?),
+    Ok(receiver.recv_timeout(Duration::from_secs(EVENT_TIMEOUT))?)
+}
+
+/// Request headers in kv form
+fn headers_kv(headers: &HeaderMap) -> anyhow::Result<HeadersKV> {
+    let mut header_map: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (header_name, header_val) in headers {
+        header_map
+            .entry(header_name.to_string())
+            .or_default()
+            .push(header_val.to_str()?.to_string());
     }
+
+    Ok(header_map.into_iter().collect())
+}
+
+/// Reports the health of every runtime extension as a JSON object.
+/// Responds with `503` if any of them is unavailable.
+async fn health_response() -> anyhow::Result<Response<Body>> {
+    // Health checks block on the extension tasks, so keep them off the gateway runtime.
+    let report = tokio::task::spawn_blocking(health::report).await?;
+
+    let status = match health::overall(&report) {
+        HealthStatus::Ready | HealthStatus::Degraded => StatusCode::OK,
+        HealthStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    let body: serde_json::Map<String, serde_json::Value> = report
+        .iter()
+        .map(|(extension, status)| (extension.to_string(), status.as_str().into()))
+        .collect();
+
+    Ok(Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&body)?.into())?)
 }
 
 /// Serves static data with 1:1 mapping
@@ -205,9 +514,58 @@ fn is_valid_path(path: &str) -> anyhow::Result<()> {
 mod tests {
     use regex::Regex;
 
-    use super::VALID_PATH;
+    use super::{dispatch, Dispatch, RoutesConfig, VALID_PATH};
     use crate::runtime_extensions::hermes::http_gateway::routing::is_valid_path;
 
+    #[test]
+    fn test_dispatch() {
+        let config: RoutesConfig = serde_json::from_str(
+            r#"{ "routes": [
+                { "path": "/api" },
+                { "path": "/api/doc-sync/", "csrf": "double-submit" }
+            ] }"#,
+        )
+        .unwrap();
+        let route_path = |path: &str| {
+            match dispatch(path, false, &config) {
+                Dispatch::ConfiguredRoute(route) => Some(route.path),
+                _ => None,
+            }
+        };
+
+        // Nested paths reach the longest configured route, not the static files.
+        assert_eq!(
+            route_path("/api/doc-sync/post").as_deref(),
+            Some("/api/doc-sync/")
+        );
+        assert_eq!(route_path("/api/other").as_deref(), Some("/api"));
+        assert_eq!(route_path("/api").as_deref(), Some("/api"));
+        assert!(matches!(
+            dispatch("/index", false, &config),
+            Dispatch::StaticData
+        ));
+        assert!(matches!(
+            dispatch("/health", false, &config),
+            Dispatch::Health
+        ));
+
+        // Without a route config, `/api` and below still reach the modules.
+        let config = RoutesConfig::default();
+        assert!(matches!(
+            dispatch("/api/doc-sync/post", false, &config),
+            Dispatch::Modules
+        ));
+        assert!(matches!(
+            dispatch("/apis", false, &config),
+            Dispatch::StaticData
+        ));
+        assert!(matches!(dispatch("/rpc", true, &config), Dispatch::Modules));
+        assert!(matches!(
+            dispatch("/abc/def/", false, &config),
+            Dispatch::NotFound
+        ));
+    }
+
     #[test]
     fn test_valid_paths_regex() {
         // ^ and $: Match the entire string/line
@@ -0,0 +1,218 @@
+//! gRPC-web and Connect protocol bridging of unary RPCs.
+//!
+//! RPC requests are `POST`s to `/<package>.<Service>/<Method>`. The gateway unwraps
+//! the request message and dispatches it to the modules as an HTTP event with that
+//! path, then wraps the body of the module response as the response message. The
+//! messages are passed through as is, encoding them is up to the modules.
+//! Like any other request, an RPC is subject to the rate limit, CSRF protection,
+//! request schema and variants of the gateway route of its path.
+//!
+//! The module response code is mapped to the status of the RPC.
+
+use hyper::{
+    body::Bytes,
+    header::{HeaderValue, CONTENT_TYPE},
+    Body, HeaderMap, Method, Response, StatusCode,
+};
+
+/// Header present on Connect protocol requests.
+const CONNECT_PROTOCOL_VERSION: &str = "connect-protocol-version";
+
+/// Content type prefix of gRPC-web requests.
+const GRPC_WEB_CONTENT_TYPE: &str = "application/grpc-web";
+
+/// Flag of a gRPC-web trailers frame.
+const TRAILERS_FLAG: u8 = 0x80;
+
+/// Length of a gRPC-web frame header: a flag byte and a big endian `u32` length.
+const FRAME_HEADER_LEN: usize = 5;
+
+/// RPC protocol of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    /// gRPC-web, messages in length-prefixed frames, status in a trailers frame.
+    GrpcWeb,
+    /// Connect unary, messages as plain bodies, status as HTTP status.
+    Connect,
+}
+
+impl Protocol {
+    /// RPC protocol of the request, if it is an RPC.
+    pub(crate) fn detect(method: &Method, headers: &HeaderMap) -> Option<Self> {
+        if *method != Method::POST {
+            return None;
+        }
+        let content_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        if content_type.starts_with(GRPC_WEB_CONTENT_TYPE) {
+            Some(Self::GrpcWeb)
+        } else if headers.contains_key(CONNECT_PROTOCOL_VERSION) {
+            Some(Self::Connect)
+        } else {
+            None
+        }
+    }
+
+    /// Request message of the request body.
+    pub(crate) fn request_message(self, body: Bytes) -> anyhow::Result<Bytes> {
+        match self {
+            Self::GrpcWeb => {
+                let (flag, message, rest) = split_frame(&body)?;
+                anyhow::ensure!(flag == 0, "Compressed gRPC-web messages are not supported");
+                anyhow::ensure!(rest.is_empty(), "Only unary gRPC-web calls are supported");
+                Ok(body.slice_ref(message))
+            },
+            Self::Connect => Ok(body),
+        }
+    }
+
+    /// Response carrying the module response to the client.
+    pub(crate) fn response(
+        self, code: u16, message: Vec<u8>, request_headers: &HeaderMap,
+    ) -> anyhow::Result<Response<Body>> {
+        let content_type = request_headers
+            .get(CONTENT_TYPE)
+            .cloned()
+            .unwrap_or(HeaderValue::from_static("application/proto"));
+        let is_ok = StatusCode::from_u16(code).is_ok_and(|code| code.is_success());
+
+        match self {
+            Self::GrpcWeb => {
+                let status = grpc_status(code);
+                let mut body = Vec::new();
+                if is_ok {
+                    body.extend(frame(0, &message)?);
+                }
+                body.extend(frame(
+                    TRAILERS_FLAG,
+                    format!("grpc-status:{status}\r\n").as_bytes(),
+                )?);
+                Ok(Response::builder()
+                    .header(CONTENT_TYPE, content_type)
+                    .body(body.into())?)
+            },
+            Self::Connect if is_ok => {
+                Ok(Response::builder()
+                    .header(CONTENT_TYPE, content_type)
+                    .body(message.into())?)
+            },
+            Self::Connect => {
+                let error = serde_json::json!({ "code": connect_code(code) });
+                Ok(Response::builder()
+                    .status(code)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(serde_json::to_string(&error)?.into())?)
+            },
+        }
+    }
+}
+
+/// Split the first gRPC-web frame off `data`, returning its flag, payload and the
+/// remaining data.
+fn split_frame(data: &[u8]) -> anyhow::Result<(u8, &[u8], &[u8])> {
+    anyhow::ensure!(data.len() >= FRAME_HEADER_LEN, "Truncated gRPC-web frame");
+    let (header, data) = data.split_at(FRAME_HEADER_LEN);
+    let (flag, len) = header.split_at(1);
+    let len = usize::try_from(u32::from_be_bytes(len.try_into()?))?;
+    anyhow::ensure!(data.len() >= len, "Truncated gRPC-web frame");
+    let (payload, rest) = data.split_at(len);
+    Ok((flag.first().copied().unwrap_or_default(), payload, rest))
+}
+
+/// gRPC-web frame of a payload.
+fn frame(flag: u8, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN.saturating_add(payload.len()));
+    frame.push(flag);
+    frame.extend(u32::try_from(payload.len())?.to_be_bytes());
+    frame.extend(payload);
+    Ok(frame)
+}
+
+/// gRPC status code of a module response code, as gRPC maps HTTP status codes.
+fn grpc_status(code: u16) -> u8 {
+    match code {
+        200..=299 => 0,
+        400 => 13,
+        401 => 16,
+        403 => 7,
+        404 => 12,
+        429 | 502..=504 => 14,
+        _ => 2,
+    }
+}
+
+/// Connect error code of a module response code.
+fn connect_code(code: u16) -> &'static str {
+    match code {
+        400 => "invalid_argument",
+        401 => "unauthenticated",
+        403 => "permission_denied",
+        404 => "unimplemented",
+        409 => "already_exists",
+        429 => "resource_exhausted",
+        503 => "unavailable",
+        504 => "deadline_exceeded",
+        500 => "internal",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grpc_web_round_trip() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc-web+proto"),
+        );
+        let protocol = Protocol::detect(&Method::POST, &headers).unwrap();
+        assert_eq!(protocol, Protocol::GrpcWeb);
+
+        let body = Bytes::from(frame(0, b"request").unwrap());
+        assert_eq!(protocol.request_message(body).unwrap(), "request");
+
+        let response = protocol.response(200, b"reply".to_vec(), &headers).unwrap();
+        let body = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(hyper::body::to_bytes(response.into_body()))
+            .unwrap();
+        let (flag, message, rest) = split_frame(&body).unwrap();
+        assert_eq!((flag, message), (0, b"reply".as_slice()));
+        let (flag, trailers, rest) = split_frame(rest).unwrap();
+        assert_eq!(
+            (flag, trailers),
+            (TRAILERS_FLAG, b"grpc-status:0\r\n".as_slice())
+        );
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn connect_error_response() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONNECT_PROTOCOL_VERSION, HeaderValue::from_static("1"));
+        let protocol = Protocol::detect(&Method::POST, &headers).unwrap();
+        assert_eq!(protocol, Protocol::Connect);
+        assert!(Protocol::detect(&Method::GET, &headers).is_none());
+
+        let response = protocol.response(404, Vec::new(), &headers).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn truncated_frame_is_rejected() {
+        let protocol = Protocol::GrpcWeb;
+        assert!(protocol
+            .request_message(Bytes::from_static(&[0, 0, 0]))
+            .is_err());
+        assert!(protocol
+            .request_message(Bytes::from_static(&[0, 0, 0, 0, 2, 1]))
+            .is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Canary and A/B variants of gateway routes.
+//!
+//! A route can be served by several module variants, e.g. two versions of the same
+//! module. A client is assigned a variant by weight on its first request and sticks to
+//! it through a `hermes-variant-<route hash>` cookie, one per route, as every route has
+//! its own variants. The `X-Hermes-Variant` header selects a variant explicitly.
+
+use dashmap::DashMap;
+use hyper::{
+    header::{HeaderValue, SET_COOKIE},
+    Body, HeaderMap, Response,
+};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::routing::request_cookie;
+use crate::app::ApplicationName;
+
+/// Header selecting a variant explicitly.
+const VARIANT_HEADER: &str = "x-hermes-variant";
+
+/// Prefix of the cookies sticking a client to a variant of a route, followed by the hash
+/// of the route path.
+const VARIANT_COOKIE_PREFIX: &str = "hermes-variant-";
+
+/// Length in bytes of the route path hash in the variant cookie names.
+const ROUTE_HASH_LENGTH: usize = 8;
+
+/// Request metrics of every variant, keyed by app, route path and variant module.
+static METRICS: Lazy<DashMap<(ApplicationName, String, String), VariantMetrics>> =
+    Lazy::new(DashMap::new);
+
+/// Module variant serving a route.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Variant {
+    /// Package name of the module serving the variant.
+    module: String,
+    /// Relative weight of the variant when assigning clients.
+    weight: u32,
+}
+
+/// Request metrics of a variant.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub(crate) struct VariantMetrics {
+    /// Requests served by the variant.
+    requests: u64,
+    /// Requests the variant failed to serve.
+    failures: u64,
+}
+
+/// Variant selected for a request.
+#[derive(Debug)]
+pub(crate) struct Selection {
+    /// Package name of the module serving the variant.
+    pub(crate) module: String,
+    /// Whether the client already sticks to the variant.
+    sticky: bool,
+    /// Cookie sticking the client to the variant of the route.
+    cookie: String,
+}
+
+/// Name of the cookie sticking a client to a variant of the route with the given path.
+fn variant_cookie(route: &str) -> String {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(ROUTE_HASH_LENGTH)
+        .hash(route.as_bytes());
+    format!("{VARIANT_COOKIE_PREFIX}{}", hash.to_hex())
+}
+
+/// Select the variant of the route with the given path serving a request.
+pub(crate) fn select(route: &str, variants: &[Variant], headers: &HeaderMap) -> Option<Selection> {
+    let named = |name: &str| variants.iter().find(|variant| variant.module == name);
+    let cookie = variant_cookie(route);
+
+    let requested = headers
+        .get(VARIANT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(named);
+    if let Some(variant) = requested {
+        return Some(Selection {
+            module: variant.module.clone(),
+            sticky: true,
+            cookie,
+        });
+    }
+    if let Some(variant) = request_cookie(headers, &cookie).and_then(named) {
+        return Some(Selection {
+            module: variant.module.clone(),
+            sticky: true,
+            cookie,
+        });
+    }
+
+    // Summed as `u64`, so weights up to `u32::MAX` can not overflow.
+    let total: u64 = variants
+        .iter()
+        .map(|variant| u64::from(variant.weight))
+        .sum();
+    if total == 0 {
+        return None;
+    }
+    let mut pick = rand::thread_rng().gen_range(0..total);
+    variants
+        .iter()
+        .find(|variant| {
+            let weight = u64::from(variant.weight);
+            if pick < weight {
+                return true;
+            }
+            pick = pick.saturating_sub(weight);
+            false
+        })
+        .map(|variant| {
+            Selection {
+                module: variant.module.clone(),
+                sticky: false,
+                cookie,
+            }
+        })
+}
+
+/// Stick the client to the selected variant.
+pub(crate) fn stick(selection: &Selection, response: &mut Response<Body>) -> anyhow::Result<()> {
+    if !selection.sticky {
+        let cookie = format!(
+            "{}={}; Path=/; HttpOnly; Secure; SameSite=Lax",
+            selection.cookie, selection.module
+        );
+        response
+            .headers_mut()
+            .append(SET_COOKIE, HeaderValue::from_str(&cookie)?);
+    }
+    Ok(())
+}
+
+/// Record a request served by a variant of a route.
+pub(crate) fn record(app_name: &ApplicationName, route: &str, module: &str, failed: bool) {
+    let mut metrics = METRICS
+        .entry((app_name.clone(), route.to_string(), module.to_string()))
+        .or_default();
+    metrics.requests = metrics.requests.saturating_add(1);
+    if failed {
+        metrics.failures = metrics.failures.saturating_add(1);
+    }
+}
+
+/// Forget the metrics of the variants of an app, when it is unloaded.
+pub(super) fn remove_app(app_name: &ApplicationName) {
+    METRICS.retain(|(app, ..), _| app != app_name);
+}
+
+/// Request metrics of every variant, with their app, route path and variant module.
+pub(crate) fn metrics() -> Vec<(ApplicationName, String, String, VariantMetrics)> {
+    METRICS
+        .iter()
+        .map(|entry| {
+            let (app_name, route, module) = entry.key();
+            (
+                app_name.clone(),
+                route.clone(),
+                module.clone(),
+                *entry.value(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::COOKIE;
+
+    use super::*;
+
+    fn variants() -> Vec<Variant> {
+        vec![
+            Variant {
+                module: "stable".to_string(),
+                weight: 0,
+            },
+            Variant {
+                module: "canary".to_string(),
+                weight: 1,
+            },
+        ]
+    }
+
+    #[test]
+    fn new_client_is_assigned_by_weight() {
+        let selection = select("/api", &variants(), &HeaderMap::new()).unwrap();
+        assert_eq!(selection.module, "canary");
+
+        let mut response = Response::new(Body::empty());
+        stick(&selection, &mut response).unwrap();
+        assert!(response.headers()[SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .starts_with(&format!("{}=canary;", variant_cookie("/api"))));
+    }
+
+    #[test]
+    fn large_weights_do_not_overflow() {
+        let variants: Vec<_> = ["stable", "canary"]
+            .into_iter()
+            .map(|module| {
+                Variant {
+                    module: module.to_string(),
+                    weight: u32::MAX,
+                }
+            })
+            .collect();
+        assert!(select("/api", &variants, &HeaderMap::new()).is_some());
+    }
+
+    #[test]
+    fn sticky_client_keeps_its_variant() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_str(&format!("{}=stable", variant_cookie("/api"))).unwrap(),
+        );
+        let selection = select("/api", &variants(), &headers).unwrap();
+        assert_eq!(selection.module, "stable");
+
+        let mut response = Response::new(Body::empty());
+        stick(&selection, &mut response).unwrap();
+        assert!(response.headers().get(SET_COOKIE).is_none());
+
+        headers.insert(VARIANT_HEADER, HeaderValue::from_static("canary"));
+        assert_eq!(
+            select("/api", &variants(), &headers).unwrap().module,
+            "canary"
+        );
+    }
+
+    #[test]
+    fn routes_have_their_own_sticky_variant() {
+        let doc_variants = variants();
+        let vote_variants: Vec<_> = ["vote", "vote-canary"]
+            .into_iter()
+            .map(|module| {
+                Variant {
+                    module: module.to_string(),
+                    weight: 1,
+                }
+            })
+            .collect();
+        assert_ne!(variant_cookie("/api/doc"), variant_cookie("/api/vote"));
+
+        // A client sticking to a variant of a route is assigned a variant of another
+        // route, and the variant cookies of both routes are sent back.
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            COOKIE,
+            HeaderValue::from_str(&format!("{}=stable", variant_cookie("/api/doc"))).unwrap(),
+        );
+        let vote = select("/api/vote", &vote_variants, &headers).unwrap();
+        assert!(!vote.sticky);
+        assert!(vote.module.starts_with("vote"));
+
+        let mut response = Response::new(Body::empty());
+        stick(&vote, &mut response).unwrap();
+        let vote_cookie = response.headers()[SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string();
+        headers.append(COOKIE, HeaderValue::from_str(&vote_cookie).unwrap());
+
+        let doc = select("/api/doc", &doc_variants, &headers).unwrap();
+        assert!(doc.sticky);
+        assert_eq!(doc.module, "stable");
+        let vote_again = select("/api/vote", &vote_variants, &headers).unwrap();
+        assert!(vote_again.sticky);
+        assert_eq!(vote_again.module, vote.module);
+    }
+}
@@ -0,0 +1,97 @@
+//! Warm-up of the modules serving the gateway.
+//!
+//! When an app is loaded, a warm-up event is queued for each of its modules right after
+//! its init, which instantiates the module and calls its
+//! `hermes:http-gateway/event-warmup.warmup` export. When the node starts, the gateway
+//! waits for the queued warm-up events to be executed, for at most `WARMUP_TIMEOUT`,
+//! before accepting connections, so the first requests after a restart are not slowed
+//! down.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashSet;
+use once_cell::sync::Lazy;
+
+use crate::{
+    app::ApplicationName,
+    event::{self as hermes_event, HermesEvent, HermesEventPayload, TargetApp, TargetModule},
+    reactor,
+    wasm::module::ModuleId,
+};
+
+/// Maximum time the gateway waits for the modules to be warmed up.
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval at which the gateway checks whether the modules are warmed up.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Modules whose warm-up event was queued but not executed yet.
+static PENDING: Lazy<DashSet<(ApplicationName, ModuleId)>> = Lazy::new(DashSet::new);
+
+/// Warm-up event
+struct WarmupEvent {}
+
+impl HermesEventPayload for WarmupEvent {
+    fn event_name(&self) -> &str {
+        "warmup"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        let res = module
+            .instance
+            .hermes_http_gateway_event_warmup()
+            .call_warmup(&mut module.store);
+        PENDING.remove(&(
+            module.store.data().app_name().clone(),
+            module.store.data().module_id().clone(),
+        ));
+        res
+    }
+}
+
+/// Queue the warm-up event of every module of the app.
+///
+/// Events are executed in order, so the modules are warmed up once they are initialized.
+pub(crate) fn emit_warmup_events(app_name: &ApplicationName) -> anyhow::Result<()> {
+    let module_ids: Vec<_> = {
+        let app = reactor::get_app(app_name)?;
+        app.module_names()
+            .iter()
+            .filter_map(|name| app.module_id(name).cloned())
+            .collect()
+    };
+    for module_id in module_ids {
+        PENDING.insert((app_name.clone(), module_id.clone()));
+        let event = HermesEvent::new(
+            WarmupEvent {},
+            TargetApp::List(vec![app_name.clone()]),
+            TargetModule::List(vec![module_id.clone()]),
+        );
+        if let Err(err) = hermes_event::queue::send(event) {
+            PENDING.remove(&(app_name.clone(), module_id));
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Wait until the modules whose warm-up event was queued are warmed up, for at most
+/// `WARMUP_TIMEOUT`.
+pub(crate) async fn wait() {
+    let started_at = Instant::now();
+    loop {
+        // The events of unloaded apps are dropped.
+        PENDING.retain(|(app_name, _)| reactor::get_app(app_name).is_ok());
+        if PENDING.is_empty() {
+            return;
+        }
+        if started_at.elapsed() >= WARMUP_TIMEOUT {
+            tracing::warn!(
+                pending = PENDING.len(),
+                "Gateway modules not warmed up in time, accepting connections"
+            );
+            return;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,101 @@
+//! Runtime extension capability discovery.
+
+use super::health;
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::{
+        bindings::hermes::init::api::{Capability, Extension, HealthStatus},
+        plugin,
+    },
+};
+
+/// Version of the built-in runtime extensions and plugins, which is the node version.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Built-in runtime extensions, with the extension reporting their health, if any.
+const BUILT_IN: &[(&str, Option<Extension>)] = &[
+    ("hermes:binary", None),
+    ("hermes:cardano", None),
+    ("hermes:cbor", None),
+    ("hermes:cluster", None),
+    ("hermes:cron", None),
+    ("hermes:crypto", None),
+    ("hermes:delivery", None),
+    ("hermes:hash", None),
+    ("hermes:http-gateway", Some(Extension::HttpGateway)),
+    ("hermes:init", None),
+    ("hermes:ipfs", Some(Extension::Ipfs)),
+    ("hermes:json", None),
+    ("hermes:kv-store", None),
+    ("hermes:localtime", None),
+    ("hermes:logging", None),
+    ("hermes:session", None),
+    ("hermes:sqlite", None),
+    ("hermes:timer", None),
+    ("wasi:cli", None),
+    ("wasi:clocks", None),
+    ("wasi:filesystem", None),
+    ("wasi:http", None),
+    ("wasi:io", None),
+    ("wasi:random", None),
+];
+
+/// Optional features of a built-in runtime extension enabled on the node.
+fn features(name: &str) -> Vec<String> {
+    let features: &[&str] = match name {
+        "hermes:sqlite" if cfg!(feature = "replication") => &["replication"],
+        // Only modules exporting `incoming-handler` are served, outgoing requests are not
+        // supported.
+        "wasi:http" => &["incoming-handler"],
+        _ => &[],
+    };
+    features.iter().map(ToString::to_string).collect()
+}
+
+/// Runtime extensions available to the app.
+///
+/// Extensions which are unavailable to the app, as reported by their health, are not
+/// listed.
+pub(crate) fn list(app_name: &ApplicationName) -> Vec<Capability> {
+    let built_in = BUILT_IN
+        .iter()
+        .filter(|(_, extension)| {
+            extension.as_ref().map_or(true, |extension| {
+                health::status(extension, Some(app_name)) != HealthStatus::Unavailable
+            })
+        })
+        .map(|(name, _)| {
+            Capability {
+                name: (*name).to_string(),
+                version: VERSION.to_string(),
+                features: features(name),
+            }
+        });
+    let plugins = plugin::names().into_iter().map(|name| {
+        Capability {
+            name,
+            version: VERSION.to_string(),
+            features: Vec::new(),
+        }
+    });
+    built_in.chain(plugins).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unavailable_extensions_are_not_listed() {
+        let app_name = ApplicationName("capabilities-test".to_string());
+        let capabilities = list(&app_name);
+        let names: Vec<_> = capabilities.iter().map(|c| c.name.as_str()).collect();
+
+        assert!(names.contains(&"hermes:init"));
+        assert!(names.contains(&"wasi:http"));
+        // Neither the app IPFS node nor the gateway are started.
+        assert!(!names.contains(&"hermes:ipfs"));
+        assert!(!names.contains(&"hermes:http-gateway"));
+        assert!(capabilities.iter().all(|c| c.version == VERSION));
+    }
+}
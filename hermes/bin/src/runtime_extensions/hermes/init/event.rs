@@ -1,9 +1,39 @@
 //! Init runtime extension event handler implementation.
 
-use crate::event::HermesEventPayload;
+use crate::{
+    event::HermesEventPayload,
+    runtime_extensions::bindings::exports::hermes::init::event::InitResult,
+};
+
+/// Module init failed error
+#[derive(thiserror::Error, Debug)]
+#[error("Init of module {module_id} of app {app_name} failed: {reason}")]
+struct InitFailedError {
+    /// App of the module.
+    app_name: String,
+    /// Module which failed its init.
+    module_id: String,
+    /// Why the init failed.
+    reason: String,
+}
+
+/// Module self-test failed error
+#[derive(thiserror::Error, Debug)]
+#[error("Self-test of module {module_id} of app {app_name} failed: {reason}")]
+struct SelfTestFailedError {
+    /// App of the module.
+    app_name: String,
+    /// Module which failed its self-test.
+    module_id: String,
+    /// Why the self-test failed.
+    reason: String,
+}
 
 /// Init event
-pub(crate) struct InitEvent {}
+pub(crate) struct InitEvent {
+    /// Number of earlier inits of the module which asked to be retried later.
+    pub(crate) attempts: u32,
+}
 
 impl HermesEventPayload for InitEvent {
     fn event_name(&self) -> &str {
@@ -11,10 +41,62 @@ impl HermesEventPayload for InitEvent {
     }
 
     fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
-        let _res = module
+        let res = module
             .instance
             .hermes_init_event()
             .call_init(&mut module.store)?;
+        let app_name = module.store.data().app_name().clone();
+        let module_id = module.store.data().module_id().clone();
+        match res {
+            InitResult::Ok => super::initialized(&app_name, &module_id),
+            InitResult::RetryLater(reason) => {
+                super::retry_init(app_name, module_id, self.attempts.saturating_add(1), reason);
+                Ok(())
+            },
+            InitResult::Fatal(reason) => {
+                super::stop_app(app_name.clone(), "its init failed");
+                Err(InitFailedError {
+                    app_name: app_name.to_string(),
+                    module_id: module_id.to_string(),
+                    reason,
+                }
+                .into())
+            },
+        }
+    }
+}
+
+/// Self-test event, executed once every module executed the init event.
+pub(crate) struct SelfTestEvent {}
+
+impl HermesEventPayload for SelfTestEvent {
+    fn event_name(&self) -> &str {
+        "self-test"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        // A module still retrying its init is self-tested once it is initialized.
+        if super::is_pending_init(
+            module.store.data().app_name(),
+            module.store.data().module_id(),
+        ) {
+            return Ok(());
+        }
+        let res = module
+            .instance
+            .hermes_init_event_self_test()
+            .call_self_test(&mut module.store)?;
+        if let Err(reason) = res {
+            let app_name = module.store.data().app_name().clone();
+            let module_id = module.store.data().module_id().to_string();
+            super::stop_app(app_name.clone(), "its self-test failed");
+            return Err(SelfTestFailedError {
+                app_name: app_name.to_string(),
+                module_id,
+                reason,
+            }
+            .into());
+        }
         Ok(())
     }
 }
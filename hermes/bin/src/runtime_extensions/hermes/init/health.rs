@@ -0,0 +1,91 @@
+//! Runtime extension health reporting.
+
+use std::fmt::Display;
+
+use crate::{
+    app::ApplicationName,
+    ipfs::hermes_ipfs_health,
+    runtime_extensions::{
+        bindings::hermes::{
+            cardano::api::CardanoBlockchainId,
+            init::api::{Extension, HealthStatus},
+        },
+        hermes::{cardano, http_gateway},
+    },
+};
+
+impl Display for Extension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Extension::Cardano(chain_id) => {
+                let network = match chain_id {
+                    CardanoBlockchainId::Mainnet => "mainnet",
+                    CardanoBlockchainId::Preprod => "preprod",
+                    CardanoBlockchainId::Preview => "preview",
+                    CardanoBlockchainId::LocalTestBlockchain => "local-test-blockchain",
+                };
+                write!(f, "cardano/{network}")
+            },
+            Extension::Ipfs => write!(f, "ipfs"),
+            Extension::HttpGateway => write!(f, "http-gateway"),
+        }
+    }
+}
+
+impl HealthStatus {
+    /// Name of the status, as used in the WIT definition.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            HealthStatus::Ready => "ready",
+            HealthStatus::Degraded => "degraded",
+            HealthStatus::Unavailable => "unavailable",
+        }
+    }
+
+    /// Ranking used to find the worst status, higher is worse.
+    fn severity(self) -> u8 {
+        match self {
+            HealthStatus::Ready => 0,
+            HealthStatus::Degraded => 1,
+            HealthStatus::Unavailable => 2,
+        }
+    }
+}
+
+/// Current health of a runtime extension, as seen by the app if one is given.
+pub(crate) fn status(extension: &Extension, app_name: Option<&ApplicationName>) -> HealthStatus {
+    match extension {
+        Extension::Cardano(chain_id) => cardano::health(*chain_id),
+        Extension::Ipfs => hermes_ipfs_health(app_name),
+        Extension::HttpGateway => http_gateway::health(),
+    }
+}
+
+/// Health of every runtime extension in use by the node.
+///
+/// Cardano networks are only reported once a module follows them.
+pub(crate) fn report() -> Vec<(Extension, HealthStatus)> {
+    let mut extensions: Vec<Extension> = cardano::followed_networks()
+        .into_iter()
+        .map(Extension::Cardano)
+        .collect();
+    extensions.push(Extension::Ipfs);
+    extensions.push(Extension::HttpGateway);
+
+    extensions
+        .into_iter()
+        .map(|extension| {
+            let status = status(&extension, None);
+            (extension, status)
+        })
+        .collect()
+}
+
+/// Overall health of the node, which is the worst status of a `report`.
+pub(crate) fn overall(report: &[(Extension, HealthStatus)]) -> HealthStatus {
+    report
+        .iter()
+        .map(|(_, status)| *status)
+        .max_by_key(|status| status.severity())
+        .unwrap_or(HealthStatus::Ready)
+}
@@ -0,0 +1,25 @@
+//! Init host implementation for WASM runtime.
+
+use super::{capabilities, health};
+use crate::{
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::bindings::hermes::init::api::{Capability, Extension, HealthStatus, Host},
+};
+
+impl Host for HermesRuntimeContext {
+    /// Get the current health of a runtime extension.
+    ///
+    /// `degraded` means the extension works, but with reduced functionality, e.g. a
+    /// chain follower that is still syncing.
+    fn health(&mut self, extension: Extension) -> wasmtime::Result<HealthStatus> {
+        Ok(health::status(&extension, Some(self.app_name())))
+    }
+
+    /// Get the runtime extensions available on the node.
+    ///
+    /// Extensions which are not running, e.g. IPFS when its node failed to start, are not
+    /// listed.
+    fn capabilities(&mut self) -> wasmtime::Result<Vec<Capability>> {
+        Ok(capabilities::list(self.app_name()))
+    }
+}
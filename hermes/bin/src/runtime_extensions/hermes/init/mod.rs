@@ -1,23 +1,135 @@
 //! Init runtime extension implementation.
+//!
+//! A module whose init asks to be retried later leaves its app in degraded mode: the init
+//! of the module is retried with an exponential backoff, and the module is self-tested
+//! once it is initialized.
+
+use std::time::Duration;
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 
 use crate::{
     app::ApplicationName,
     event as hermes_event,
     event::{HermesEvent, TargetApp, TargetModule},
+    reactor,
+    wasm::module::ModuleId,
 };
 
+mod capabilities;
 mod event;
+pub(crate) mod health;
+mod host;
+
+/// Delay before the first retry of the init of a module, doubled on every later one.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay before a retry of the init of a module.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Modules whose init asked to be retried later, with the reason given.
+static PENDING_INIT: Lazy<DashMap<(ApplicationName, ModuleId), String>> = Lazy::new(DashMap::new);
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
 
-/// Emit Init event for a provided Hermes app target
+/// Emit Init event for a provided Hermes app target, followed by the self-test event.
 pub(crate) fn emit_init_event(target_app: ApplicationName) -> anyhow::Result<()> {
     let init_event = HermesEvent::new(
-        event::InitEvent {},
-        TargetApp::List(vec![target_app]),
+        event::InitEvent { attempts: 0 },
+        TargetApp::List(vec![target_app.clone()]),
         TargetModule::All,
     );
     hermes_event::queue::send(init_event)?;
+
+    // Events are executed in order, so the self-test runs once every module is
+    // initialized.
+    let self_test_event = HermesEvent::new(
+        event::SelfTestEvent {},
+        TargetApp::List(vec![target_app]),
+        TargetModule::All,
+    );
+    hermes_event::queue::send(self_test_event)?;
     Ok(())
 }
+
+/// Whether the init of the module asked to be retried later, and was not retried
+/// successfully yet.
+fn is_pending_init(app_name: &ApplicationName, module_id: &ModuleId) -> bool {
+    PENDING_INIT.contains_key(&(app_name.clone(), module_id.clone()))
+}
+
+/// Retry the init of a module after a delay growing with the number of `attempts`.
+fn retry_init(app_name: ApplicationName, module_id: ModuleId, attempts: u32, reason: String) {
+    let delay = retry_delay(attempts);
+    tracing::warn!(
+        app_name = %app_name,
+        module_id = %module_id,
+        attempts,
+        delay = ?delay,
+        "Module init failed, app is degraded until it is retried successfully: {reason}"
+    );
+    PENDING_INIT.insert((app_name.clone(), module_id.clone()), reason);
+
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let init_event = HermesEvent::new(
+            event::InitEvent { attempts },
+            TargetApp::List(vec![app_name.clone()]),
+            TargetModule::List(vec![module_id]),
+        );
+        if let Err(err) = hermes_event::queue::send(init_event) {
+            tracing::error!(app_name = %app_name, "Failed to retry module init: {err}");
+        }
+    });
+}
+
+/// Mark a module as initialized, self-testing it if its init had to be retried.
+fn initialized(app_name: &ApplicationName, module_id: &ModuleId) -> anyhow::Result<()> {
+    if PENDING_INIT
+        .remove(&(app_name.clone(), module_id.clone()))
+        .is_none()
+    {
+        return Ok(());
+    }
+    tracing::info!(app_name = %app_name, module_id = %module_id, "Module initialized after retries");
+    let self_test_event = HermesEvent::new(
+        event::SelfTestEvent {},
+        TargetApp::List(vec![app_name.clone()]),
+        TargetModule::List(vec![module_id.clone()]),
+    );
+    hermes_event::queue::send(self_test_event)
+}
+
+/// Delay before retrying the init of a module which asked it `attempts` times.
+fn retry_delay(attempts: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .saturating_mul(2_u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Stop an app, e.g. which failed its self-test, so it does not go live.
+///
+/// The app is in use by the event being executed, so it is unloaded from another thread
+/// once the event is done.
+fn stop_app(app_name: ApplicationName, cause: &'static str) {
+    std::thread::spawn(move || {
+        match reactor::unload_app(&app_name) {
+            Ok(()) => tracing::error!(app_name = %app_name, cause, "App stopped"),
+            Err(err) => tracing::error!(app_name = %app_name, "Failed to stop app: {err}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_test() {
+        assert_eq!(retry_delay(1), INITIAL_RETRY_DELAY);
+        assert_eq!(retry_delay(4), INITIAL_RETRY_DELAY * 8);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+}
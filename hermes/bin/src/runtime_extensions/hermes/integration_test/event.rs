@@ -2,7 +2,7 @@
 
 #![allow(clippy::module_name_repetitions)]
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use anyhow::Ok;
 use crossbeam_queue::SegQueue;
@@ -103,6 +103,7 @@ pub fn execute_event(
                 module.id().clone(),
                 vfs.clone(),
                 on_bench_event.as_ref(),
+                Instant::now(),
             ) {
                 tracing::error!("{err}");
             }
@@ -116,6 +117,7 @@ pub fn execute_event(
                 module.id().clone(),
                 vfs.clone(),
                 on_test_event.as_ref(),
+                Instant::now(),
             ) {
                 tracing::error!("{err}");
             }
@@ -3,60 +3,95 @@
 use crate::{
     ipfs::{
         hermes_ipfs_add_file, hermes_ipfs_content_validate, hermes_ipfs_evict_peer,
-        hermes_ipfs_get_dht_value, hermes_ipfs_get_file, hermes_ipfs_pin_file, hermes_ipfs_publish,
-        hermes_ipfs_put_dht_value, hermes_ipfs_subscribe, hermes_ipfs_unpin_file,
+        hermes_ipfs_get_dht_record, hermes_ipfs_get_dht_value, hermes_ipfs_get_file,
+        hermes_ipfs_pin_file, hermes_ipfs_publish, hermes_ipfs_pubsub_peers,
+        hermes_ipfs_pubsub_topic_health, hermes_ipfs_put_dht_value, hermes_ipfs_subscribe,
+        hermes_ipfs_unpin_file,
     },
     runtime_context::HermesRuntimeContext,
-    runtime_extensions::bindings::hermes::ipfs::api::{
-        DhtKey, DhtValue, Errno, Host, IpfsContent, IpfsFile, IpfsPath, MessageData, MessageId,
-        PeerId, PubsubTopic,
+    runtime_extensions::{
+        bindings::hermes::{
+            binary::api::Buffer,
+            ipfs::api::{
+                DhtKey, DhtRecord, DhtValue, Host, IpfsContent, IpfsFile, IpfsPath, MessageData,
+                MessageId, PeerId, PubsubTopic, PubsubTopicHealth,
+            },
+        },
+        hermes::{binary::new_buffer, error::HermesError},
     },
 };
 
 impl Host for HermesRuntimeContext {
-    fn file_add(&mut self, contents: IpfsFile) -> wasmtime::Result<Result<IpfsPath, Errno>> {
-        let path: IpfsPath = hermes_ipfs_add_file(self.app_name(), contents)?.to_string();
-        Ok(Ok(path))
+    fn file_add(&mut self, contents: IpfsFile) -> wasmtime::Result<Result<IpfsPath, HermesError>> {
+        Ok(hermes_ipfs_add_file(self.app_name(), contents).map_err(HermesError::from))
     }
 
-    fn file_get(&mut self, path: IpfsPath) -> wasmtime::Result<Result<IpfsFile, Errno>> {
-        let contents = hermes_ipfs_get_file(self.app_name(), &path)?;
-        Ok(Ok(contents))
+    fn file_get(&mut self, path: IpfsPath) -> wasmtime::Result<Result<IpfsFile, HermesError>> {
+        Ok(hermes_ipfs_get_file(self.app_name(), &path).map_err(HermesError::from))
     }
 
-    fn file_pin(&mut self, ipfs_path: IpfsPath) -> wasmtime::Result<Result<bool, Errno>> {
-        Ok(hermes_ipfs_pin_file(self.app_name(), &ipfs_path))
+    fn file_get_buffer(
+        &mut self, path: IpfsPath,
+    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Buffer>, HermesError>> {
+        match hermes_ipfs_get_file(self.app_name(), &path) {
+            Ok(contents) => Ok(Ok(new_buffer(self.app_name(), contents)?)),
+            Err(err) => Ok(Err(err.into())),
+        }
     }
 
-    fn file_unpin(&mut self, ipfs_path: IpfsPath) -> wasmtime::Result<Result<bool, Errno>> {
-        Ok(hermes_ipfs_unpin_file(self.app_name(), &ipfs_path))
+    fn file_pin(&mut self, ipfs_path: IpfsPath) -> wasmtime::Result<Result<bool, HermesError>> {
+        Ok(hermes_ipfs_pin_file(self.app_name(), &ipfs_path).map_err(HermesError::from))
     }
 
-    fn dht_put(&mut self, key: DhtKey, value: DhtValue) -> wasmtime::Result<Result<bool, Errno>> {
-        Ok(hermes_ipfs_put_dht_value(self.app_name(), key, value))
+    fn file_unpin(&mut self, ipfs_path: IpfsPath) -> wasmtime::Result<Result<bool, HermesError>> {
+        Ok(hermes_ipfs_unpin_file(self.app_name(), &ipfs_path).map_err(HermesError::from))
     }
 
-    fn dht_get(&mut self, key: DhtKey) -> wasmtime::Result<Result<DhtValue, Errno>> {
-        Ok(hermes_ipfs_get_dht_value(self.app_name(), key))
+    fn dht_put(
+        &mut self, key: DhtKey, value: DhtValue,
+    ) -> wasmtime::Result<Result<bool, HermesError>> {
+        Ok(hermes_ipfs_put_dht_value(self.app_name(), key, value).map_err(HermesError::from))
+    }
+
+    fn dht_get(&mut self, key: DhtKey) -> wasmtime::Result<Result<DhtValue, HermesError>> {
+        Ok(hermes_ipfs_get_dht_value(self.app_name(), key).map_err(HermesError::from))
+    }
+
+    fn dht_get_record(&mut self, key: DhtKey) -> wasmtime::Result<Result<DhtRecord, HermesError>> {
+        Ok(hermes_ipfs_get_dht_record(self.app_name(), key).map_err(HermesError::from))
     }
 
     fn pubsub_publish(
         &mut self, topic: PubsubTopic, message: MessageData,
-    ) -> wasmtime::Result<Result<MessageId, Errno>> {
-        Ok(hermes_ipfs_publish(self.app_name(), &topic, message))
+    ) -> wasmtime::Result<Result<MessageId, HermesError>> {
+        Ok(hermes_ipfs_publish(self.app_name(), &topic, message).map_err(HermesError::from))
+    }
+
+    fn pubsub_subscribe(
+        &mut self, topic: PubsubTopic,
+    ) -> wasmtime::Result<Result<bool, HermesError>> {
+        Ok(hermes_ipfs_subscribe(self.app_name(), topic).map_err(HermesError::from))
+    }
+
+    fn pubsub_peers(
+        &mut self, topic: PubsubTopic,
+    ) -> wasmtime::Result<Result<Vec<PeerId>, HermesError>> {
+        Ok(hermes_ipfs_pubsub_peers(self.app_name(), &topic).map_err(HermesError::from))
     }
 
-    fn pubsub_subscribe(&mut self, topic: PubsubTopic) -> wasmtime::Result<Result<bool, Errno>> {
-        Ok(hermes_ipfs_subscribe(self.app_name(), topic))
+    fn pubsub_topic_health(
+        &mut self, topic: PubsubTopic,
+    ) -> wasmtime::Result<Result<PubsubTopicHealth, HermesError>> {
+        Ok(hermes_ipfs_pubsub_topic_health(self.app_name(), &topic).map_err(HermesError::from))
     }
 
     fn ipfs_content_validate(
         &mut self, content: IpfsContent,
-    ) -> wasmtime::Result<Result<bool, Errno>> {
+    ) -> wasmtime::Result<Result<bool, HermesError>> {
         Ok(Ok(hermes_ipfs_content_validate(self.app_name(), &content)))
     }
 
-    fn peer_evict(&mut self, peer: PeerId) -> wasmtime::Result<Result<bool, Errno>> {
-        Ok(hermes_ipfs_evict_peer(self.app_name(), peer))
+    fn peer_evict(&mut self, peer: PeerId) -> wasmtime::Result<Result<bool, HermesError>> {
+        Ok(hermes_ipfs_evict_peer(self.app_name(), peer).map_err(HermesError::from))
     }
 }
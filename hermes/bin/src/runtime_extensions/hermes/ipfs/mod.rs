@@ -4,3 +4,8 @@ mod host;
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
+
+/// Stop delivering the `PubSub` messages of an unloaded app.
+pub(crate) fn unload_app(app_name: &crate::app::ApplicationName) {
+    crate::ipfs::unload_app(app_name);
+}
@@ -1,12 +1,15 @@
 //! Hermes runtime extensions implementations - HERMES custom extensions
 
-use crate::runtime_context::HermesRuntimeContext;
+use crate::{app::ApplicationName, runtime_context::HermesRuntimeContext};
 
 pub(crate) mod binary;
 pub(crate) mod cardano;
 pub(crate) mod cbor;
+pub(crate) mod cluster;
 pub(crate) mod cron;
 pub(crate) mod crypto;
+pub(crate) mod delivery;
+pub(crate) mod error;
 pub(crate) mod hash;
 pub(crate) mod http_gateway;
 pub(crate) mod init;
@@ -16,15 +19,20 @@ pub(crate) mod json;
 pub(crate) mod kv_store;
 pub(crate) mod localtime;
 pub(crate) mod logging;
+pub(crate) mod session;
 pub(crate) mod sqlite;
+pub(crate) mod timer;
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(ctx: &HermesRuntimeContext) {
     binary::new_context(ctx);
     cardano::new_context(ctx);
     cbor::new_context(ctx);
+    cluster::new_context(ctx);
     cron::new_context(ctx);
     crypto::new_context(ctx);
+    delivery::new_context(ctx);
+    error::new_context(ctx);
     hash::new_context(ctx);
     init::new_context(ctx);
     ipfs::new_context(ctx);
@@ -32,6 +40,17 @@ pub(crate) fn new_context(ctx: &HermesRuntimeContext) {
     kv_store::new_context(ctx);
     localtime::new_context(ctx);
     logging::new_context(ctx);
+    session::new_context(ctx);
     sqlite::new_context(ctx);
+    timer::new_context(ctx);
     http_gateway::new_context(ctx);
 }
+
+/// Advise Runtime Extensions an app was unloaded, so they release its state
+pub(crate) fn unload_app(app_name: &ApplicationName) {
+    cron::unload_app(app_name);
+    timer::unload_app(app_name);
+    ipfs::unload_app(app_name);
+    sqlite::unload_app(app_name);
+    http_gateway::unload_app(app_name);
+}
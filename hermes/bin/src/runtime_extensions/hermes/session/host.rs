@@ -0,0 +1,44 @@
+//! Session host implementation for WASM runtime.
+
+use super::state;
+use crate::{
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::bindings::hermes::{
+        binary::api::Bstr,
+        session::api::{Cookie, Errno, Host, Session},
+    },
+};
+
+impl Host for HermesRuntimeContext {
+    /// Create a session, returning the signed cookie value identifying it.
+    ///
+    /// **Parameters**
+    ///
+    /// - `data` : Application data to store in the session.
+    /// - `idle_timeout` : Seconds of inactivity after which the session expires.
+    fn create(&mut self, data: Bstr, idle_timeout: u32) -> wasmtime::Result<Cookie> {
+        state::create(self.app_name(), data, idle_timeout)
+    }
+
+    /// Look up a session, resetting its idle timeout.
+    fn lookup(&mut self, cookie: Cookie) -> wasmtime::Result<Result<Session, Errno>> {
+        Ok(state::lookup(self.app_name(), &cookie))
+    }
+
+    /// Replace the data of a session.
+    fn update(&mut self, cookie: Cookie, data: Bstr) -> wasmtime::Result<Result<(), Errno>> {
+        Ok(state::update(self.app_name(), &cookie, data))
+    }
+
+    /// Invalidate a session.
+    fn invalidate(&mut self, cookie: Cookie) -> wasmtime::Result<Result<(), Errno>> {
+        Ok(state::invalidate(self.app_name(), &cookie))
+    }
+
+    /// Build a `Set-Cookie` header value for a session cookie.
+    fn set_cookie_header(&mut self, name: String, cookie: Cookie) -> wasmtime::Result<String> {
+        Ok(format!(
+            "{name}={cookie}; Path=/; HttpOnly; Secure; SameSite=Lax"
+        ))
+    }
+}
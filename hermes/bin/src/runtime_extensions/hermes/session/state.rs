@@ -0,0 +1,191 @@
+//! Session state
+
+use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use sha2::Sha256;
+
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::bindings::hermes::{
+        binary::api::Bstr,
+        session::api::{Cookie, Errno, Session},
+    },
+};
+
+/// Number of random bytes in a session id.
+const SESSION_ID_LEN: usize = 16;
+
+/// Separator between the session id and its signature in a cookie.
+const COOKIE_SEPARATOR: char = '.';
+
+/// Session held by the host.
+struct SessionState {
+    /// Application data stored in the session.
+    data: Bstr,
+    /// When the session was created, in seconds since the UNIX epoch.
+    created: u64,
+    /// When the session was last looked up, in seconds since the UNIX epoch.
+    last_access: u64,
+    /// Seconds of inactivity after which the session expires.
+    idle_timeout: u64,
+}
+
+impl SessionState {
+    /// Has the session been idle for longer than its idle timeout.
+    fn is_expired(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_access) > self.idle_timeout
+    }
+}
+
+/// Sessions of all apps, keyed by app and session id.
+static SESSIONS: Lazy<DashMap<(ApplicationName, String), SessionState>> = Lazy::new(DashMap::new);
+
+/// Key signing the session cookies.
+/// Sessions are held in memory, so the key does not need to outlive the node.
+static SIGNING_KEY: Lazy<[u8; 32]> = Lazy::new(|| {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    key
+});
+
+/// Current time in seconds since the UNIX epoch.
+fn now() -> u64 {
+    u64::try_from(chrono::Utc::now().timestamp()).unwrap_or_default()
+}
+
+/// Cookie signature MAC of a session id of an app.
+fn cookie_mac(app_name: &ApplicationName, session_id: &str) -> anyhow::Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SIGNING_KEY.as_slice())?;
+    mac.update(app_name.0.as_bytes());
+    mac.update(&[0]);
+    mac.update(session_id.as_bytes());
+    Ok(mac)
+}
+
+/// Session id of a cookie, if its signature is valid for the app.
+fn verify_cookie(app_name: &ApplicationName, cookie: &Cookie) -> Result<String, Errno> {
+    let (session_id, signature) = cookie
+        .split_once(COOKIE_SEPARATOR)
+        .ok_or(Errno::InvalidCookie)?;
+    let signature = hex::decode(signature).map_err(|_| Errno::InvalidCookie)?;
+    cookie_mac(app_name, session_id)
+        .map_err(|_| Errno::InvalidCookie)?
+        .verify_slice(&signature)
+        .map_err(|_| Errno::InvalidCookie)?;
+    Ok(session_id.to_string())
+}
+
+/// Create a new session, returning its signed cookie.
+pub(super) fn create(
+    app_name: &ApplicationName, data: Bstr, idle_timeout: u32,
+) -> anyhow::Result<Cookie> {
+    let now = now();
+    // Expired sessions are dropped when new ones are created, so they do not pile up.
+    SESSIONS.retain(|(app, _), session| app != app_name || !session.is_expired(now));
+
+    let mut id = [0u8; SESSION_ID_LEN];
+    rand::thread_rng().fill_bytes(&mut id);
+    let session_id = hex::encode(id);
+
+    let signature = cookie_mac(app_name, &session_id)?.finalize().into_bytes();
+    let cookie = format!("{session_id}{COOKIE_SEPARATOR}{}", hex::encode(signature));
+
+    SESSIONS.insert((app_name.clone(), session_id), SessionState {
+        data,
+        created: now,
+        last_access: now,
+        idle_timeout: idle_timeout.into(),
+    });
+    Ok(cookie)
+}
+
+/// Look up a session, resetting its idle timeout.
+pub(super) fn lookup(app_name: &ApplicationName, cookie: &Cookie) -> Result<Session, Errno> {
+    let key = (app_name.clone(), verify_cookie(app_name, cookie)?);
+    let now = now();
+
+    let mut session = SESSIONS.get_mut(&key).ok_or(Errno::NotFound)?;
+    if session.is_expired(now) {
+        drop(session);
+        SESSIONS.remove(&key);
+        return Err(Errno::Expired);
+    }
+    session.last_access = now;
+
+    Ok(Session {
+        data: session.data.clone(),
+        created: session.created,
+        last_access: session.last_access,
+    })
+}
+
+/// Replace the data of a session.
+pub(super) fn update(app_name: &ApplicationName, cookie: &Cookie, data: Bstr) -> Result<(), Errno> {
+    let key = (app_name.clone(), verify_cookie(app_name, cookie)?);
+    let mut session = SESSIONS.get_mut(&key).ok_or(Errno::NotFound)?;
+    if session.is_expired(now()) {
+        drop(session);
+        SESSIONS.remove(&key);
+        return Err(Errno::Expired);
+    }
+    session.data = data;
+    Ok(())
+}
+
+/// Invalidate a session.
+pub(super) fn invalidate(app_name: &ApplicationName, cookie: &Cookie) -> Result<(), Errno> {
+    let key = (app_name.clone(), verify_cookie(app_name, cookie)?);
+    SESSIONS.remove(&key).map(|_| ()).ok_or(Errno::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_lifecycle() {
+        let app_name = ApplicationName("session_lifecycle".to_string());
+        let cookie = create(&app_name, vec![1, 2, 3], 60).unwrap();
+
+        assert_eq!(lookup(&app_name, &cookie).unwrap().data, vec![1, 2, 3]);
+        update(&app_name, &cookie, vec![4]).unwrap();
+        assert_eq!(lookup(&app_name, &cookie).unwrap().data, vec![4]);
+
+        invalidate(&app_name, &cookie).unwrap();
+        assert_eq!(lookup(&app_name, &cookie).unwrap_err(), Errno::NotFound);
+    }
+
+    #[test]
+    fn cookie_is_bound_to_app() {
+        let app_name = ApplicationName("cookie_is_bound_to_app".to_string());
+        let other_app = ApplicationName("other_app".to_string());
+        let cookie = create(&app_name, vec![], 60).unwrap();
+
+        assert_eq!(
+            lookup(&other_app, &cookie).unwrap_err(),
+            Errno::InvalidCookie
+        );
+
+        let (session_id, _) = cookie.split_once(COOKIE_SEPARATOR).unwrap();
+        let forged = format!("{session_id}{COOKIE_SEPARATOR}00");
+        assert_eq!(
+            lookup(&app_name, &forged).unwrap_err(),
+            Errno::InvalidCookie
+        );
+    }
+
+    #[test]
+    fn idle_session_expires() {
+        let app_name = ApplicationName("idle_session_expires".to_string());
+        let cookie = create(&app_name, vec![], 0).unwrap();
+        let session_id = verify_cookie(&app_name, &cookie).unwrap();
+
+        SESSIONS
+            .get_mut(&(app_name.clone(), session_id))
+            .unwrap()
+            .last_access -= 1;
+        assert_eq!(lookup(&app_name, &cookie).unwrap_err(), Errno::Expired);
+    }
+}
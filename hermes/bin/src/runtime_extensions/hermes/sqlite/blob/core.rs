@@ -0,0 +1,133 @@
+//! Core functionality implementation for `SQLite` BLOB object.
+
+use libsqlite3_sys::{
+    sqlite3, sqlite3_blob, sqlite3_blob_bytes, sqlite3_blob_close, sqlite3_blob_open,
+    sqlite3_blob_read, sqlite3_blob_write, SQLITE_OK,
+};
+
+use crate::runtime_extensions::bindings::hermes::sqlite::api::Errno;
+
+/// Opens the BLOB in `column` of the `rowid` row of `table` for incremental I/O.
+pub(crate) fn open(
+    db_ptr: *mut sqlite3, table: &str, column: &str, rowid: i64, writable: bool,
+) -> Result<*mut sqlite3_blob, Errno> {
+    let table = std::ffi::CString::new(table).map_err(|_| Errno::ConvertingCString)?;
+    let column = std::ffi::CString::new(column).map_err(|_| Errno::ConvertingCString)?;
+
+    let mut blob_ptr: *mut sqlite3_blob = std::ptr::null_mut();
+
+    let rc = unsafe {
+        sqlite3_blob_open(
+            db_ptr,
+            c"main".as_ptr(),
+            table.as_ptr(),
+            column.as_ptr(),
+            rowid,
+            i32::from(writable),
+            &mut blob_ptr,
+        )
+    };
+
+    if rc != SQLITE_OK {
+        // A handle is allocated on some errors.
+        unsafe { sqlite3_blob_close(blob_ptr) };
+        return Err(Errno::Sqlite(rc));
+    }
+    if blob_ptr.is_null() {
+        return Err(Errno::ReturnedNullPointer);
+    }
+    Ok(blob_ptr)
+}
+
+/// Size of the BLOB in bytes.
+pub(crate) fn size(blob_ptr: *mut sqlite3_blob) -> u32 {
+    u32::try_from(unsafe { sqlite3_blob_bytes(blob_ptr) }).unwrap_or_default()
+}
+
+/// Reads `length` bytes of the BLOB from `offset`.
+pub(crate) fn read(
+    blob_ptr: *mut sqlite3_blob, offset: u32, length: u32,
+) -> Result<Vec<u8>, Errno> {
+    let offset = i32::try_from(offset).map_err(|_| Errno::ConvertingNumeric)?;
+    let n_byte = i32::try_from(length).map_err(|_| Errno::ConvertingNumeric)?;
+    let mut buf = vec![0_u8; usize::try_from(length).map_err(|_| Errno::ConvertingNumeric)?];
+
+    let rc = unsafe { sqlite3_blob_read(blob_ptr, buf.as_mut_ptr().cast(), n_byte, offset) };
+
+    if rc == SQLITE_OK {
+        Ok(buf)
+    } else {
+        Err(Errno::Sqlite(rc))
+    }
+}
+
+/// Writes `data` into the BLOB at `offset`.
+pub(crate) fn write(blob_ptr: *mut sqlite3_blob, offset: u32, data: &[u8]) -> Result<(), Errno> {
+    let offset = i32::try_from(offset).map_err(|_| Errno::ConvertingNumeric)?;
+    let n_byte = i32::try_from(data.len()).map_err(|_| Errno::ConvertingNumeric)?;
+
+    let rc = unsafe { sqlite3_blob_write(blob_ptr, data.as_ptr().cast(), n_byte, offset) };
+
+    if rc == SQLITE_OK {
+        Ok(())
+    } else {
+        Err(Errno::Sqlite(rc))
+    }
+}
+
+/// Closes the BLOB handle, destructor for `sqlite3_blob`.
+pub(crate) fn close(blob_ptr: *mut sqlite3_blob) -> Result<(), Errno> {
+    let rc = unsafe { sqlite3_blob_close(blob_ptr) };
+
+    if rc == SQLITE_OK {
+        Ok(())
+    } else {
+        Err(Errno::Sqlite(rc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::ApplicationName,
+        runtime_extensions::hermes::sqlite::{
+            connection::core::{self as connection, execute},
+            core::open as open_db,
+        },
+    };
+
+    const TMP_DIR: &str = "tmp-dir";
+
+    #[test]
+    fn test_blob_incremental_io() -> Result<(), Errno> {
+        let db_ptr = open_db(false, true, ApplicationName(String::from(TMP_DIR)))?;
+        execute(
+            db_ptr,
+            "CREATE TABLE blocks(slot INTEGER PRIMARY KEY, cbor BLOB);
+            INSERT INTO blocks(slot, cbor) VALUES(1, zeroblob(8));",
+        )?;
+
+        let blob_ptr = open(db_ptr, "blocks", "cbor", 1, true)?;
+        assert_eq!(size(blob_ptr), 8);
+        write(blob_ptr, 0, b"hermes")?;
+        write(blob_ptr, 6, b"!!")?;
+        assert_eq!(read(blob_ptr, 4, 4)?, b"es!!");
+        // A BLOB can not grow.
+        assert!(write(blob_ptr, 7, b"??").is_err());
+        assert!(read(blob_ptr, 0, 9).is_err());
+        close(blob_ptr)?;
+
+        let blob_ptr = open(db_ptr, "blocks", "cbor", 1, false)?;
+        assert_eq!(read(blob_ptr, 0, 8)?, b"hermes!!");
+        assert!(write(blob_ptr, 0, b"H").is_err());
+        close(blob_ptr)?;
+
+        assert!(matches!(
+            open(db_ptr, "blocks", "cbor", 2, false),
+            Err(Errno::Sqlite(_))
+        ));
+
+        connection::close(db_ptr)
+    }
+}
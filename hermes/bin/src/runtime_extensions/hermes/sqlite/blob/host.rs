@@ -0,0 +1,66 @@
+//! `SQLite` BLOB host implementation for WASM runtime.
+
+use super::{super::state::get_blob_state, core};
+use crate::{
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::{
+        bindings::hermes::sqlite::api::{Blob, HostBlob},
+        hermes::error::HermesError,
+    },
+};
+
+impl HostBlob for HermesRuntimeContext {
+    /// Size of the BLOB in bytes.
+    fn size(&mut self, resource: wasmtime::component::Resource<Blob>) -> wasmtime::Result<u32> {
+        let mut app_state = get_blob_state().get_app_state(self.app_name())?;
+        let blob_ptr = app_state.get_object(&resource)?;
+        Ok(core::size(*blob_ptr as *mut _))
+    }
+
+    /// Reads a chunk of the BLOB.
+    ///
+    /// ## Parameters
+    ///
+    /// - `offset`: Offset of the chunk in the BLOB, in bytes.
+    /// - `length`: Length of the chunk in bytes.
+    fn read(
+        &mut self, resource: wasmtime::component::Resource<Blob>, offset: u32, length: u32,
+    ) -> wasmtime::Result<Result<Vec<u8>, HermesError>> {
+        let mut app_state = get_blob_state().get_app_state(self.app_name())?;
+        let blob_ptr = app_state.get_object(&resource)?;
+        Ok(core::read(*blob_ptr as *mut _, offset, length).map_err(HermesError::from))
+    }
+
+    /// Writes a chunk of the BLOB.
+    ///
+    /// ## Parameters
+    ///
+    /// - `offset`: Offset of the chunk in the BLOB, in bytes.
+    /// - `data`: Content of the chunk.
+    fn write(
+        &mut self, resource: wasmtime::component::Resource<Blob>, offset: u32, data: Vec<u8>,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_blob_state().get_app_state(self.app_name())?;
+        let blob_ptr = app_state.get_object(&resource)?;
+        Ok(core::write(*blob_ptr as *mut _, offset, &data).map_err(HermesError::from))
+    }
+
+    /// Closes the BLOB handle, destructor for `sqlite3_blob`.
+    fn close(
+        &mut self, resource: wasmtime::component::Resource<Blob>,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let app_state = get_blob_state().get_app_state(self.app_name())?;
+        let blob_ptr = app_state.delete_resource(resource)?;
+
+        Ok(core::close(blob_ptr as *mut _).map_err(HermesError::from))
+    }
+
+    fn drop(&mut self, resource: wasmtime::component::Resource<Blob>) -> wasmtime::Result<()> {
+        let app_state = get_blob_state().get_app_state(self.app_name())?;
+        if let Ok(blob_ptr) = app_state.delete_resource(resource) {
+            let _ = core::close(blob_ptr as *mut _);
+        }
+
+        Ok(())
+    }
+}
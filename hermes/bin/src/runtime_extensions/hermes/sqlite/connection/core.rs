@@ -2,15 +2,39 @@
 
 //! Core functionality implementation for `SQLite` connection object.
 
-use std::ptr::null_mut;
+use std::{
+    ptr::null_mut,
+    time::{Duration, Instant},
+};
 
+use dashmap::DashMap;
 use libsqlite3_sys::{
-    sqlite3, sqlite3_close, sqlite3_errcode, sqlite3_errmsg, sqlite3_exec, sqlite3_prepare_v3,
-    sqlite3_stmt, SQLITE_OK,
+    sqlite3, sqlite3_busy_timeout, sqlite3_close, sqlite3_errcode, sqlite3_errmsg, sqlite3_exec,
+    sqlite3_get_autocommit, sqlite3_prepare_v3, sqlite3_stmt, SQLITE_BUSY, SQLITE_OK,
 };
+use once_cell::sync::Lazy;
 use stringzilla::StringZilla;
 
-use crate::runtime_extensions::bindings::hermes::sqlite::api::{Errno, ErrorInfo};
+use crate::runtime_extensions::bindings::hermes::sqlite::api::{Errno, ErrorInfo, TransactionMode};
+
+/// Shortest time an operation failing with `SQLITE_BUSY` is retried for, when the busy
+/// timeout of the connection is shorter.
+const MIN_BUSY_RETRY: Duration = Duration::from_secs(1);
+
+/// Longest pause between two retries of an operation failing with `SQLITE_BUSY`.
+const MAX_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Locking behavior of the connections which changed it, keyed by connection pointer.
+static BUSY_SETTINGS: Lazy<DashMap<usize, BusySettings>> = Lazy::new(DashMap::new);
+
+/// How a connection waits for the locks held by other connections.
+#[derive(Debug, Clone, Copy, Default)]
+struct BusySettings {
+    /// How long `SQLite` waits for a lock, in milliseconds.
+    timeout_ms: u32,
+    /// Whether the operations failing with `SQLITE_BUSY` are retried.
+    retry: bool,
+}
 
 /// Checks if the provided SQL string contains a `PRAGMA` statement.
 /// Generally, `PRAGMA` is intended for internal use only.
@@ -20,11 +44,20 @@ pub(crate) fn validate_sql(sql: &str) -> bool {
 
 /// Closes a database connection, destructor for `sqlite3`.
 pub(crate) fn close(db_ptr: *mut sqlite3) -> Result<(), Errno> {
+    #[cfg(feature = "replication")]
+    let replicated_app = super::super::replication::flush(db_ptr);
+
     let rc = unsafe { sqlite3_close(db_ptr) };
 
     if rc == SQLITE_OK {
+        BUSY_SETTINGS.remove(&(db_ptr as usize));
         Ok(())
     } else {
+        // The connection stays open, keep recording its changes.
+        #[cfg(feature = "replication")]
+        if let Some(app_name) = replicated_app {
+            super::super::replication::track(&app_name, db_ptr);
+        }
         Err(Errno::Sqlite(rc))
     }
 }
@@ -98,12 +131,125 @@ pub(crate) fn execute(db_ptr: *mut sqlite3, sql: &str) -> Result<(), Errno> {
     }
 }
 
+/// Begins a transaction.
+pub(crate) fn begin(db_ptr: *mut sqlite3, mode: TransactionMode) -> Result<(), Errno> {
+    let sql = match mode {
+        TransactionMode::Deferred => "BEGIN DEFERRED;",
+        TransactionMode::Immediate => "BEGIN IMMEDIATE;",
+        TransactionMode::Exclusive => "BEGIN EXCLUSIVE;",
+    };
+    retry_on_busy(db_ptr, || execute(db_ptr, sql))
+}
+
+/// Commits the open transaction.
+///
+/// A commit failing with `SQLITE_BUSY` keeps the transaction open, so it can be retried.
+pub(crate) fn commit(db_ptr: *mut sqlite3) -> Result<(), Errno> {
+    retry_on_busy(db_ptr, || execute(db_ptr, "COMMIT;"))
+}
+
+/// Rolls the open transaction back.
+pub(crate) fn rollback(db_ptr: *mut sqlite3) -> Result<(), Errno> {
+    execute(db_ptr, "ROLLBACK;")
+}
+
+/// Checks if a transaction is open on the database connection.
+pub(crate) fn in_transaction(db_ptr: *mut sqlite3) -> bool {
+    unsafe { sqlite3_get_autocommit(db_ptr) == 0 }
+}
+
+/// Opens a savepoint.
+pub(crate) fn savepoint(db_ptr: *mut sqlite3, name: &str) -> Result<(), Errno> {
+    execute(db_ptr, &format!("SAVEPOINT {};", quote_identifier(name)))
+}
+
+/// Releases a savepoint.
+pub(crate) fn release(db_ptr: *mut sqlite3, name: &str) -> Result<(), Errno> {
+    execute(
+        db_ptr,
+        &format!("RELEASE SAVEPOINT {};", quote_identifier(name)),
+    )
+}
+
+/// Rolls the changes made since a savepoint back.
+pub(crate) fn rollback_to(db_ptr: *mut sqlite3, name: &str) -> Result<(), Errno> {
+    execute(
+        db_ptr,
+        &format!("ROLLBACK TO SAVEPOINT {};", quote_identifier(name)),
+    )
+}
+
+/// Sets how long the connection waits for a lock held by another connection, in
+/// milliseconds, before failing with `SQLITE_BUSY`. It does not wait if 0.
+pub(crate) fn set_busy_timeout(db_ptr: *mut sqlite3, ms: u32) -> Result<(), Errno> {
+    let timeout = i32::try_from(ms).map_err(|_| Errno::ConvertingNumeric)?;
+
+    let rc = unsafe { sqlite3_busy_timeout(db_ptr, timeout) };
+
+    if rc == SQLITE_OK {
+        BUSY_SETTINGS.entry(db_ptr as usize).or_default().timeout_ms = ms;
+        Ok(())
+    } else {
+        Err(Errno::Sqlite(rc))
+    }
+}
+
+/// Sets whether the operations of the connection failing with `SQLITE_BUSY` are retried.
+pub(crate) fn set_retry_on_busy(db_ptr: *mut sqlite3, enabled: bool) {
+    BUSY_SETTINGS.entry(db_ptr as usize).or_default().retry = enabled;
+}
+
+/// Runs `op` again while it fails with `SQLITE_BUSY`, if the connection retries on busy,
+/// for at most its busy timeout, or `MIN_BUSY_RETRY`.
+///
+/// `SQLite` fails without waiting for the busy timeout when waiting could deadlock, or
+/// when a lock is released after the timeout, e.g. by a long write of another module.
+/// Only operations which can be run again after failing are retried.
+pub(crate) fn retry_on_busy<T>(
+    db_ptr: *mut sqlite3, mut op: impl FnMut() -> Result<T, Errno>,
+) -> Result<T, Errno> {
+    let Some(timeout) = BUSY_SETTINGS
+        .get(&(db_ptr as usize))
+        .filter(|settings| settings.retry)
+        .map(|settings| Duration::from_millis(settings.timeout_ms.into()).max(MIN_BUSY_RETRY))
+    else {
+        return op();
+    };
+
+    let started_at = Instant::now();
+    let mut interval = Duration::from_millis(1);
+    loop {
+        match op() {
+            Err(Errno::Sqlite(rc))
+                if rc & 0xFF == SQLITE_BUSY && started_at.elapsed() < timeout =>
+            {
+                std::thread::sleep(interval);
+                interval = interval.saturating_mul(2).min(MAX_RETRY_INTERVAL);
+            },
+            res => return res,
+        }
+    }
+}
+
+/// Quotes an identifier, e.g. a savepoint name, so it can not inject SQL.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
 #[cfg(test)]
 mod tests {
+    use serial_test::file_serial;
+
     use super::*;
     use crate::{
         app::ApplicationName,
-        runtime_extensions::hermes::sqlite::{core::open, statement::core::finalize},
+        runtime_extensions::{
+            bindings::hermes::sqlite::api::Value,
+            hermes::sqlite::{
+                core::{open, open_named},
+                statement::core::{column, finalize},
+            },
+        },
     };
 
     const TMP_DIR: &str = "tmp-dir";
@@ -214,6 +360,95 @@ mod tests {
         close(db_ptr).unwrap();
     }
 
+    #[test]
+    fn test_transaction() -> Result<(), Errno> {
+        let db_ptr = init()?;
+        let count = |db_ptr| -> Result<_, Errno> {
+            let stmt_ptr = prepare(db_ptr, "SELECT count(*) FROM blocks;")?;
+            unsafe { libsqlite3_sys::sqlite3_step(stmt_ptr) };
+            let count = column(stmt_ptr, 0);
+            finalize(stmt_ptr)?;
+            count
+        };
+
+        execute(db_ptr, "CREATE TABLE blocks(slot INTEGER PRIMARY KEY);")?;
+        assert!(!in_transaction(db_ptr));
+
+        begin(db_ptr, TransactionMode::Immediate)?;
+        assert!(in_transaction(db_ptr));
+        assert!(begin(db_ptr, TransactionMode::Deferred).is_err());
+        execute(db_ptr, "INSERT INTO blocks(slot) VALUES(1);")?;
+        rollback(db_ptr)?;
+        assert!(!in_transaction(db_ptr));
+        assert!(matches!(count(db_ptr)?, Value::Int32(0)));
+
+        begin(db_ptr, TransactionMode::Deferred)?;
+        execute(db_ptr, "INSERT INTO blocks(slot) VALUES(1);")?;
+        savepoint(db_ptr, "block \"2\"")?;
+        execute(db_ptr, "INSERT INTO blocks(slot) VALUES(2);")?;
+        rollback_to(db_ptr, "block \"2\"")?;
+        execute(db_ptr, "INSERT INTO blocks(slot) VALUES(3);")?;
+        release(db_ptr, "block \"2\"")?;
+        commit(db_ptr)?;
+        assert!(matches!(count(db_ptr)?, Value::Int32(2)));
+
+        // A savepoint outside of a transaction begins one.
+        savepoint(db_ptr, "outer")?;
+        assert!(in_transaction(db_ptr));
+        release(db_ptr, "outer")?;
+        assert!(!in_transaction(db_ptr));
+        assert!(commit(db_ptr).is_err());
+
+        close(db_ptr)
+    }
+
+    #[test]
+    #[file_serial]
+    fn test_busy() -> Result<(), Errno> {
+        let app_name = ApplicationName(String::from(TMP_DIR));
+        let writer = open_named("busy_test", false, false, app_name.clone())?;
+        let db_ptr = open_named("busy_test", false, false, app_name)?;
+        execute(
+            writer,
+            "CREATE TABLE IF NOT EXISTS blocks(slot INTEGER PRIMARY KEY);",
+        )?;
+
+        // The writer releases its lock 100 ms after taking it.
+        let lock = |writer: *mut sqlite3| -> Result<_, Errno> {
+            begin(writer, TransactionMode::Immediate)?;
+            let writer = writer as usize;
+            Ok(std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(100));
+                commit(writer as *mut sqlite3)
+            }))
+        };
+
+        let committer = lock(writer)?;
+        assert!(matches!(
+            begin(db_ptr, TransactionMode::Immediate),
+            Err(Errno::Sqlite(SQLITE_BUSY))
+        ));
+        committer.join().unwrap()?;
+
+        set_busy_timeout(db_ptr, 5_000)?;
+        let committer = lock(writer)?;
+        begin(db_ptr, TransactionMode::Immediate)?;
+        commit(db_ptr)?;
+        committer.join().unwrap()?;
+
+        set_busy_timeout(db_ptr, 0)?;
+        set_retry_on_busy(db_ptr, true);
+        let committer = lock(writer)?;
+        begin(db_ptr, TransactionMode::Immediate)?;
+        commit(db_ptr)?;
+        committer.join().unwrap()?;
+
+        close(db_ptr)?;
+        close(writer)?;
+        std::fs::remove_dir_all(format!("hermes_datastore-{TMP_DIR}")).unwrap();
+        Ok(())
+    }
+
     #[test]
     fn test_close_simple() {
         let db_ptr = init().unwrap();
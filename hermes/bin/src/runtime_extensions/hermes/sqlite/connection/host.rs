@@ -2,12 +2,20 @@
 
 //! `SQLite` connection object host implementation for WASM runtime.
 
-use super::{super::state::get_db_state, core};
+use super::{
+    super::{blob, state::get_db_state},
+    core,
+};
 use crate::{
     runtime_context::HermesRuntimeContext,
     runtime_extensions::{
-        bindings::hermes::sqlite::api::{Errno, ErrorInfo, HostSqlite, Sqlite, Statement},
-        hermes::sqlite::state::get_statement_state,
+        bindings::hermes::sqlite::api::{
+            Blob, Errno, ErrorInfo, HostSqlite, Sqlite, Statement, TransactionMode,
+        },
+        hermes::{
+            error::HermesError,
+            sqlite::state::{get_blob_state, get_statement_state},
+        },
     },
 };
 
@@ -24,11 +32,11 @@ impl HostSqlite for HermesRuntimeContext {
     /// is automatically rolled back.
     fn close(
         &mut self, resource: wasmtime::component::Resource<Sqlite>,
-    ) -> wasmtime::Result<Result<(), Errno>> {
+    ) -> wasmtime::Result<Result<(), HermesError>> {
         let app_state = get_db_state().get_app_state(self.app_name())?;
         let db_ptr = app_state.delete_resource(resource)?;
 
-        Ok(core::close(db_ptr as *mut _))
+        Ok(core::close(db_ptr as *mut _).map_err(HermesError::from))
     }
 
     /// Retrieves the numeric result code for the most recent failed `SQLite` operation on
@@ -61,7 +69,7 @@ impl HostSqlite for HermesRuntimeContext {
     /// string or a comment) then an error code is returned.
     fn prepare(
         &mut self, resource: wasmtime::component::Resource<Sqlite>, sql: String,
-    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Statement>, Errno>> {
+    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Statement>, HermesError>> {
         let mut db_app_state = get_db_state().get_app_state(self.app_name())?;
         let db_ptr = db_app_state.get_object(&resource)?;
 
@@ -70,7 +78,7 @@ impl HostSqlite for HermesRuntimeContext {
         match result {
             Ok(stmt_ptr) => {
                 if stmt_ptr.is_null() {
-                    Ok(Err(Errno::ReturnedNullPointer))
+                    Ok(Err(Errno::ReturnedNullPointer.into()))
                 } else {
                     let stm_app_state = get_statement_state().get_app_state(self.app_name())?;
                     let stmt = stm_app_state.create_resource(stmt_ptr as _);
@@ -78,7 +86,7 @@ impl HostSqlite for HermesRuntimeContext {
                     Ok(Ok(stmt))
                 }
             },
-            Err(errno) => Ok(Err(errno)),
+            Err(errno) => Ok(Err(errno.into())),
         }
     }
 
@@ -90,11 +98,155 @@ impl HostSqlite for HermesRuntimeContext {
     /// - `sql`: SQL statement, UTF-8 encoded.
     fn execute(
         &mut self, resource: wasmtime::component::Resource<Sqlite>, sql: String,
-    ) -> wasmtime::Result<Result<(), Errno>> {
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::execute(*db_ptr as *mut _, sql.as_str()).map_err(HermesError::from))
+    }
+
+    /// Begins a transaction, so the following changes are applied at once on `commit`,
+    /// or not at all on `rollback`, instead of one by one.
+    ///
+    /// ## Parameters
+    ///
+    /// - `mode`: How the transaction locks the database.
+    fn begin(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, mode: TransactionMode,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::begin(*db_ptr as *mut _, mode).map_err(HermesError::from))
+    }
+
+    /// Commits the open transaction, and releases all its savepoints.
+    fn commit(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::commit(*db_ptr as *mut _).map_err(HermesError::from))
+    }
+
+    /// Rolls the open transaction back, and releases all its savepoints.
+    fn rollback(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::rollback(*db_ptr as *mut _).map_err(HermesError::from))
+    }
+
+    /// Whether a transaction is open on the connection.
+    fn in_transaction(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>,
+    ) -> wasmtime::Result<bool> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::in_transaction(*db_ptr as *mut _))
+    }
+
+    /// Opens a savepoint, a nested transaction which can be rolled back on its own.
+    ///
+    /// ## Parameters
+    ///
+    /// - `name`: Name of the savepoint.
+    fn savepoint(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, name: String,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::savepoint(*db_ptr as *mut _, &name).map_err(HermesError::from))
+    }
+
+    /// Releases a savepoint, and the savepoints opened after it, keeping their changes
+    /// in the enclosing transaction.
+    ///
+    /// ## Parameters
+    ///
+    /// - `name`: Name of the savepoint.
+    fn release(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, name: String,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::release(*db_ptr as *mut _, &name).map_err(HermesError::from))
+    }
+
+    /// Rolls the changes made since a savepoint back, and releases the savepoints opened
+    /// after it.
+    ///
+    /// ## Parameters
+    ///
+    /// - `name`: Name of the savepoint.
+    fn rollback_to(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, name: String,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        Ok(core::rollback_to(*db_ptr as *mut _, &name).map_err(HermesError::from))
+    }
+
+    /// Sets how long the connection waits for a lock held by another connection before
+    /// failing with the `busy` error code.
+    ///
+    /// ## Parameters
+    ///
+    /// - `ms`: Busy timeout in milliseconds, the connection does not wait if 0.
+    fn set_busy_timeout(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, ms: u32,
+    ) -> wasmtime::Result<Result<(), HermesError>> {
         let mut app_state = get_db_state().get_app_state(self.app_name())?;
         let db_ptr = app_state.get_object(&resource)?;
 
-        Ok(core::execute(*db_ptr as *mut _, sql.as_str()))
+        Ok(core::set_busy_timeout(*db_ptr as *mut _, ms).map_err(HermesError::from))
+    }
+
+    /// Sets whether the operations of the connection failing with the `busy` error code
+    /// are retried.
+    ///
+    /// ## Parameters
+    ///
+    /// - `enabled`: Whether the operations are retried.
+    fn set_retry_on_busy(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, enabled: bool,
+    ) -> wasmtime::Result<()> {
+        let mut app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = app_state.get_object(&resource)?;
+
+        core::set_retry_on_busy(*db_ptr as *mut _, enabled);
+        Ok(())
+    }
+
+    /// Opens a BLOB for incremental I/O, so it is read or written in chunks.
+    ///
+    /// ## Parameters
+    ///
+    /// - `table`: Table of the BLOB, in the `main` database.
+    /// - `column`: Column of the BLOB.
+    /// - `rowid`: Row of the BLOB.
+    /// - `writable`: Whether the BLOB is opened for writing too.
+    fn blob_open(
+        &mut self, resource: wasmtime::component::Resource<Sqlite>, table: String, column: String,
+        rowid: i64, writable: bool,
+    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Blob>, HermesError>> {
+        let mut db_app_state = get_db_state().get_app_state(self.app_name())?;
+        let db_ptr = db_app_state.get_object(&resource)?;
+
+        match blob::core::open(*db_ptr as *mut _, &table, &column, rowid, writable) {
+            Ok(blob_ptr) => {
+                let blob_app_state = get_blob_state().get_app_state(self.app_name())?;
+                Ok(Ok(blob_app_state.create_resource(blob_ptr as _)))
+            },
+            Err(errno) => Ok(Err(errno.into())),
+        }
     }
 
     fn drop(&mut self, rep: wasmtime::component::Resource<Sqlite>) -> wasmtime::Result<()> {
@@ -1,14 +1,18 @@
 //! Core functionality implementation for the `SQLite` open function.
 
 use libsqlite3_sys::{
-    sqlite3, sqlite3_exec, sqlite3_open_v2, sqlite3_soft_heap_limit64, SQLITE_OK,
+    sqlite3, sqlite3_close, sqlite3_exec, sqlite3_open_v2, sqlite3_soft_heap_limit64, SQLITE_OK,
     SQLITE_OPEN_CREATE, SQLITE_OPEN_READONLY, SQLITE_OPEN_READWRITE,
 };
 
 use crate::{
     app::ApplicationName,
+    keystore,
     runtime_extensions::{
-        app_config::{get_app_in_memory_sqlite_db_cfg, get_app_persistent_sqlite_db_cfg},
+        app_config::{
+            get_app_in_memory_sqlite_db_cfg, get_app_named_sqlite_db_cfg,
+            get_app_persistent_sqlite_db_cfg,
+        },
         bindings::hermes::sqlite::api::Errno,
     },
 };
@@ -16,26 +20,78 @@ use crate::{
 /// The default page size of `SQLite`.
 const PAGE_SIZE: u32 = 4_096;
 
+/// Enables write-ahead logging, synced at checkpoints only, which is safe in WAL mode.
+const WAL_PRAGMA: &str = "PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;";
+
+/// Lets the maintenance give the free pages of the database back to the file system.
+/// Only applies to databases without tables yet.
+const AUTO_VACUUM_PRAGMA: &str = "PRAGMA auto_vacuum = INCREMENTAL;";
+
+/// Purpose of the keys derived from the node keystore to encrypt the app databases.
+const KEY_PURPOSE: &str = "hermes-sqlite";
+
+/// Maximum length of the name of a named database.
+const MAX_DB_NAME_LEN: usize = 64;
+
 /// Opens a connection to a new or existing `SQLite` database.
 pub(super) fn open(
     readonly: bool, memory: bool, app_name: ApplicationName,
+) -> Result<*mut sqlite3, Errno> {
+    open_db(readonly, memory, app_name, None)
+}
+
+/// Opens a connection to a new or existing named `SQLite` database of the app.
+pub(super) fn open_named(
+    name: &str, readonly: bool, memory: bool, app_name: ApplicationName,
+) -> Result<*mut sqlite3, Errno> {
+    if !is_valid_db_name(name) {
+        return Err(Errno::InvalidDatabaseName);
+    }
+    open_db(readonly, memory, app_name, Some(name))
+}
+
+/// Whether `name` can name a database, it is used in its file name.
+pub(super) fn is_valid_db_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_DB_NAME_LEN
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Opens a connection to the default database of the app, or to a named one.
+fn open_db(
+    readonly: bool, memory: bool, app_name: ApplicationName, name: Option<&str>,
 ) -> Result<*mut sqlite3, Errno> {
     let mut db_ptr: *mut sqlite3 = std::ptr::null_mut();
 
     let (db_path, config) = if memory {
-        let in_memory_config =
-            get_app_in_memory_sqlite_db_cfg(app_name).ok_or(Errno::InvalidInMemoryConfig)?;
+        // An in-memory database is private to its connection, whatever its name.
+        let in_memory_config = get_app_in_memory_sqlite_db_cfg(app_name.clone())
+            .ok_or(Errno::InvalidInMemoryConfig)?;
 
         (":memory:".into(), in_memory_config)
     } else {
-        let persistent_config =
-            get_app_persistent_sqlite_db_cfg(app_name).ok_or(Errno::InvalidPersistentConfig)?;
+        let persistent_config = match name {
+            Some(name) => get_app_named_sqlite_db_cfg(app_name.clone(), name),
+            None => get_app_persistent_sqlite_db_cfg(app_name.clone()),
+        }
+        .ok_or(Errno::InvalidPersistentConfig)?;
 
         let db_name = persistent_config
             .db_file
             .clone()
             .ok_or(Errno::MissingDatabaseNameForPersistentConfig)?;
 
+        if !readonly {
+            if let Some(dir) = db_name.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir).map_err(|err| {
+                    tracing::error!(app_name = %app_name, "Failed to create the database directory: {err}");
+                    Errno::FailedOpeningDatabase
+                })?;
+            }
+        }
+
         (db_name, persistent_config)
     };
     let flags = if readonly {
@@ -59,6 +115,14 @@ pub(super) fn open(
         return Err(Errno::FailedOpeningDatabase);
     }
 
+    // The key must be set before anything else reads the database.
+    if config.encrypted {
+        if let Err(errno) = set_key(db_ptr, &app_name) {
+            unsafe { sqlite3_close(db_ptr) };
+            return Err(errno);
+        }
+    }
+
     // config database size limitation
     let rc = if memory {
         let size_limit = i64::from(config.max_db_size);
@@ -88,9 +152,71 @@ pub(super) fn open(
         return Err(Errno::FailedSettingDatabaseSize);
     }
 
+    if !memory && !readonly {
+        let rc = exec(db_ptr, AUTO_VACUUM_PRAGMA)?;
+        if rc != SQLITE_OK {
+            tracing::warn!(app_name = %app_name, rc, "Failed to enable incremental vacuum");
+        }
+
+        // With write-ahead logging readers do not block the writer and the writer does
+        // not block readers, so modules can query a database while another one writes
+        // to it. The journal mode is persistent, so read-only connections use it too.
+        let rc = exec(db_ptr, WAL_PRAGMA)?;
+        if rc != SQLITE_OK {
+            // e.g. the file system does not support the shared memory WAL needs, the
+            // database is still usable with the rollback journal.
+            tracing::warn!(app_name = %app_name, rc, "Failed to enable WAL journaling");
+        }
+    }
+
+    // Only the default database is replicated.
+    #[cfg(feature = "replication")]
+    if !memory && !readonly && name.is_none() {
+        super::replication::track(&app_name, db_ptr);
+    }
+
     Ok(db_ptr)
 }
 
+/// Sets the key of the encrypted database of the app, derived from the node keystore,
+/// and checks it decrypts the database.
+fn set_key(db_ptr: *mut sqlite3, app_name: &ApplicationName) -> Result<(), Errno> {
+    if !cfg!(feature = "sqlcipher") {
+        tracing::error!(app_name = %app_name, "Encrypted databases require SQLCipher support");
+        return Err(Errno::FailedOpeningDatabase);
+    }
+    let key = keystore::derive_key(KEY_PURPOSE, app_name.0.as_bytes()).map_err(|err| {
+        tracing::error!(app_name = %app_name, "Failed to derive the database key: {err}");
+        Errno::FailedOpeningDatabase
+    })?;
+
+    // A wrong key is only detected when the database is read.
+    let key_stmt = format!(
+        "PRAGMA key = \"x'{}'\"; SELECT count(*) FROM sqlite_master;",
+        hex::encode(key)
+    );
+    let rc = exec(db_ptr, &key_stmt)?;
+    if rc != SQLITE_OK {
+        tracing::error!(app_name = %app_name, rc, "Failed to decrypt the database");
+        return Err(Errno::Sqlite(rc));
+    }
+    Ok(())
+}
+
+/// Executes SQL statements on the connection, returning the `SQLite` result code.
+fn exec(db_ptr: *mut sqlite3, sql: &str) -> Result<i32, Errno> {
+    let c_sql = std::ffi::CString::new(sql).map_err(|_| Errno::ConvertingCString)?;
+    Ok(unsafe {
+        sqlite3_exec(
+            db_ptr,
+            c_sql.as_ptr(),
+            None,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -101,7 +227,16 @@ mod tests {
     use serial_test::file_serial;
 
     use super::*;
-    use crate::{app::ApplicationName, runtime_extensions::hermes::sqlite::connection::core};
+    use crate::{
+        app::ApplicationName,
+        runtime_extensions::{
+            bindings::hermes::sqlite::api::{TransactionMode, Value},
+            hermes::sqlite::{
+                connection::core,
+                statement::core::{column, finalize, step},
+            },
+        },
+    };
 
     const TMP_DIR: &str = "tmp-dir";
 
@@ -142,6 +277,78 @@ mod tests {
         assert!(has_db_file && is_remove_success.is_ok());
     }
 
+    #[test]
+    #[file_serial]
+    fn test_open_wal_concurrent_reader() -> Result<(), Errno> {
+        let app_name = ApplicationName(String::from(TMP_DIR));
+        let config = get_app_persistent_sqlite_db_cfg(app_name.clone()).unwrap();
+        let db_file = config.db_file.clone().unwrap();
+        let query = |db_ptr, sql| -> Result<_, Errno> {
+            let stmt_ptr = core::prepare(db_ptr, sql)?;
+            step(stmt_ptr)?;
+            let value = column(stmt_ptr, 0);
+            finalize(stmt_ptr)?;
+            value
+        };
+
+        let writer = open(false, false, app_name.clone())?;
+        let journal_mode = query(writer, "SELECT journal_mode FROM pragma_journal_mode;")?;
+        assert!(matches!(journal_mode, Value::Text(mode) if mode == "wal"));
+        core::execute(writer, "CREATE TABLE blocks(slot INTEGER PRIMARY KEY);")?;
+        core::begin(writer, TransactionMode::Immediate)?;
+        core::execute(writer, "INSERT INTO blocks(slot) VALUES(1);")?;
+
+        // The reader is not blocked by the pending write, and does not see it.
+        let reader = open(true, false, app_name)?;
+        assert!(matches!(
+            query(reader, "SELECT count(*) FROM blocks;")?,
+            Value::Int32(0)
+        ));
+        core::commit(writer)?;
+        assert!(matches!(
+            query(reader, "SELECT count(*) FROM blocks;")?,
+            Value::Int32(1)
+        ));
+
+        core::close(reader)?;
+        core::close(writer)?;
+        fs::remove_file(Path::new(&db_file)).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    #[file_serial]
+    fn test_open_named() -> Result<(), Errno> {
+        let app_name = ApplicationName(String::from(TMP_DIR));
+        let config = get_app_named_sqlite_db_cfg(app_name.clone(), "index").unwrap();
+        let db_file = config.db_file.clone().unwrap();
+
+        let index = open_named("index", false, false, app_name.clone())?;
+        core::execute(index, "CREATE TABLE blocks(slot INTEGER PRIMARY KEY);")?;
+        assert!(Path::new(&db_file).exists());
+
+        // Named databases are separate from each other and from the default one.
+        let cache = open_named("cache", false, false, app_name.clone())?;
+        assert!(core::execute(cache, "SELECT count(*) FROM blocks;").is_err());
+        let default = open(false, true, app_name.clone())?;
+        assert!(core::execute(default, "SELECT count(*) FROM blocks;").is_err());
+
+        assert!(matches!(
+            open_named("../index", false, false, app_name.clone()),
+            Err(Errno::InvalidDatabaseName)
+        ));
+        assert!(matches!(
+            open_named("", false, false, app_name),
+            Err(Errno::InvalidDatabaseName)
+        ));
+
+        core::close(default)?;
+        core::close(cache)?;
+        core::close(index)?;
+        fs::remove_dir_all(db_file.parent().unwrap()).unwrap();
+        Ok(())
+    }
+
     #[test]
     #[file_serial]
     fn test_open_readonly_without_existing_file() {
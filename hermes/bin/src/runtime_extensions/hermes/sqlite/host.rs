@@ -3,7 +3,10 @@
 use super::{core, state::get_db_state};
 use crate::{
     runtime_context::HermesRuntimeContext,
-    runtime_extensions::bindings::hermes::sqlite::api::{Errno, Host, Sqlite},
+    runtime_extensions::{
+        bindings::hermes::sqlite::api::{Host, Sqlite},
+        hermes::error::HermesError,
+    },
 };
 
 impl Host for HermesRuntimeContext {
@@ -21,7 +24,7 @@ impl Host for HermesRuntimeContext {
     /// is returned. Otherwise an error code is returned.
     fn open(
         &mut self, readonly: bool, memory: bool,
-    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Sqlite>, Errno>> {
+    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Sqlite>, HermesError>> {
         match core::open(readonly, memory, self.app_name().clone()) {
             Ok(db_ptr) => {
                 let app_state = get_db_state().get_app_state(self.app_name())?;
@@ -29,7 +32,34 @@ impl Host for HermesRuntimeContext {
 
                 Ok(Ok(db_id))
             },
-            Err(err) => Ok(Err(err)),
+            Err(err) => Ok(Err(err.into())),
+        }
+    }
+
+    /// Opens a connection to a new or existing named `SQLite` database of the app.
+    ///
+    /// ## Parameters
+    ///
+    /// - `name`: Name of the database, letters, digits, `-` and `_` only.
+    /// - `readonly`: If set to true, the database is opened in read-only mode. An error
+    ///   is returned if the database doesn't already exist.
+    /// - `memory`: If set to true, the database will be opened as an in-memory database.
+    ///
+    /// ## Returns
+    ///
+    /// If the database is opened (and/or created) successfully, then the `sqlite3` object
+    /// is returned. Otherwise an error code is returned.
+    fn open_named(
+        &mut self, name: String, readonly: bool, memory: bool,
+    ) -> wasmtime::Result<Result<wasmtime::component::Resource<Sqlite>, HermesError>> {
+        match core::open_named(&name, readonly, memory, self.app_name().clone()) {
+            Ok(db_ptr) => {
+                let app_state = get_db_state().get_app_state(self.app_name())?;
+                let db_id = app_state.create_resource(db_ptr as _);
+
+                Ok(Ok(db_id))
+            },
+            Err(err) => Ok(Err(err.into())),
         }
     }
 }
@@ -0,0 +1,351 @@
+//! Maintenance of the persistent `SQLite` databases of the apps.
+//!
+//! Once a day, in the maintenance window of the app, the host runs an incremental
+//! vacuum, `ANALYZE` and an integrity check on the default and named databases of the
+//! app, so long-running indexer databases stay healthy without module code. The results
+//! are logged and reported in the node metrics. The maintenance can also be run on
+//! demand with `hermes app maintain`.
+//!
+//! Only databases created since the incremental vacuum is enabled, i.e. whose
+//! `auto_vacuum` mode is `INCREMENTAL`, give their free pages back to the file system.
+
+use std::{
+    path::Path,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use chrono::{NaiveDate, Timelike, Utc};
+use dashmap::DashMap;
+use libsqlite3_sys::{sqlite3, sqlite3_busy_timeout};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    connection::core::{close, execute},
+    core::{is_valid_db_name, open, open_named},
+    stats::query,
+};
+use crate::{
+    app::ApplicationName,
+    reactor,
+    runtime_extensions::{
+        app_config::{get_app_named_sqlite_db_dir, get_app_persistent_sqlite_db_cfg},
+        bindings::hermes::sqlite::api::{Errno, Value},
+    },
+};
+
+/// Interval at which the scheduler checks whether an app is in its maintenance window.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long the maintenance waits for a module to release a database lock.
+const BUSY_TIMEOUT_MS: i32 = 5_000;
+
+/// Maximum number of problems the integrity check reports.
+const MAX_INTEGRITY_ERRORS: u32 = 100;
+
+/// Maintenance state of every app.
+static APPS: Lazy<DashMap<ApplicationName, AppMaintenance>> = Lazy::new(DashMap::new);
+
+/// Thread running the scheduled maintenance, started with the first scheduled app.
+static SCHEDULER: Lazy<()> = Lazy::new(|| {
+    std::thread::spawn(|| {
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            run_due(Utc::now().date_naive(), Utc::now().hour());
+        }
+    });
+});
+
+/// Maintenance window of an app, from its metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct MaintenanceWindow {
+    /// Whether the databases of the app are maintained on schedule.
+    #[serde(default = "MaintenanceWindow::default_enabled")]
+    pub(crate) enabled: bool,
+    /// UTC hour of the day the maintenance runs at, from 0 to 23.
+    #[serde(default = "MaintenanceWindow::default_hour")]
+    pub(crate) hour: u32,
+}
+
+impl MaintenanceWindow {
+    /// Maintenance is enabled by default.
+    fn default_enabled() -> bool {
+        true
+    }
+
+    /// Maintenance runs at 3 AM UTC by default.
+    fn default_hour() -> u32 {
+        3
+    }
+}
+
+impl Default for MaintenanceWindow {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            hour: Self::default_hour(),
+        }
+    }
+}
+
+/// Result of the maintenance of a database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MaintenanceReport {
+    /// Name of the database, not set for the default one.
+    pub(crate) database: Option<String>,
+    /// When the maintenance started, in seconds since the UNIX epoch.
+    pub(crate) started: u64,
+    /// How long the maintenance took, in milliseconds.
+    pub(crate) duration: u64,
+    /// Pages given back to the file system by the incremental vacuum.
+    pub(crate) freed_pages: u64,
+    /// Problems found by the integrity check, `ok` if none.
+    pub(crate) integrity: Vec<String>,
+    /// Error, if the maintenance failed.
+    pub(crate) error: Option<String>,
+}
+
+impl MaintenanceReport {
+    /// Whether the database was maintained and is healthy.
+    pub(crate) fn is_healthy(&self) -> bool {
+        self.error.is_none() && self.integrity == ["ok"]
+    }
+}
+
+/// Maintenance state of an app.
+#[derive(Debug, Default)]
+struct AppMaintenance {
+    /// Maintenance window of the app.
+    window: MaintenanceWindow,
+    /// Day the databases of the app were last maintained on schedule.
+    last_run: Option<NaiveDate>,
+    /// Latest report of every database of the app.
+    reports: Vec<MaintenanceReport>,
+}
+
+impl AppMaintenance {
+    /// Whether the scheduled maintenance is due in the `hour` of `today`.
+    fn is_due(&self, today: NaiveDate, hour: u32) -> bool {
+        self.window.enabled && self.window.hour == hour && self.last_run != Some(today)
+    }
+}
+
+/// Set the maintenance window of an app.
+pub(crate) fn set_app_window(app_name: ApplicationName, window: MaintenanceWindow) {
+    APPS.entry(app_name).or_default().window = window;
+    Lazy::force(&SCHEDULER);
+}
+
+/// Latest maintenance reports of the databases of every app.
+pub(crate) fn reports() -> Vec<(ApplicationName, Vec<MaintenanceReport>)> {
+    APPS.iter()
+        .filter(|entry| !entry.reports.is_empty())
+        .map(|entry| (entry.key().clone(), entry.reports.clone()))
+        .collect()
+}
+
+/// Maintain the persistent databases of an app now, returning their reports.
+pub(crate) fn maintain(app_name: &ApplicationName) -> Vec<MaintenanceReport> {
+    let reports: Vec<_> = databases(app_name)
+        .into_iter()
+        .map(|name| maintain_db(app_name, name))
+        .collect();
+    APPS.entry(app_name.clone())
+        .or_default()
+        .reports
+        .clone_from(&reports);
+    reports
+}
+
+/// Maintain the databases of the loaded apps in their maintenance window.
+fn run_due(today: NaiveDate, hour: u32) {
+    let due: Vec<_> = APPS
+        .iter_mut()
+        .filter(|entry| entry.is_due(today, hour) && reactor::get_app(entry.key()).is_ok())
+        .map(|mut entry| {
+            entry.last_run = Some(today);
+            entry.key().clone()
+        })
+        .collect();
+    for app_name in due {
+        maintain(&app_name);
+    }
+}
+
+/// Names of the existing persistent databases of an app, `None` for the default one.
+pub(super) fn databases(app_name: &ApplicationName) -> Vec<Option<String>> {
+    let exists = |db_file: Option<&Path>| db_file.is_some_and(Path::exists);
+    let mut databases = Vec::new();
+    if exists(
+        get_app_persistent_sqlite_db_cfg(app_name.clone())
+            .and_then(|config| config.db_file)
+            .as_deref(),
+    ) {
+        databases.push(None);
+    }
+
+    // The named databases are the files of the database directory of the app.
+    let Some(Ok(entries)) = get_app_named_sqlite_db_dir(app_name.clone()).map(std::fs::read_dir)
+    else {
+        return databases;
+    };
+    let mut names: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_stem()?.to_str()?;
+            (path.extension()? == "db" && is_valid_db_name(name)).then(|| name.to_string())
+        })
+        .collect();
+    names.sort();
+    databases.extend(names.into_iter().map(Some));
+    databases
+}
+
+/// Maintain a persistent database of an app, and log the result.
+fn maintain_db(app_name: &ApplicationName, name: Option<String>) -> MaintenanceReport {
+    let started_at = Instant::now();
+    let started = UNIX_EPOCH.elapsed().unwrap_or_default().as_secs();
+
+    let res = match &name {
+        Some(name) => open_named(name, false, false, app_name.clone()),
+        None => open(false, false, app_name.clone()),
+    }
+    .and_then(|db_ptr| {
+        let res = run(db_ptr);
+        close(db_ptr)?;
+        res
+    });
+
+    let duration = u64::try_from(started_at.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let database = name.as_deref().unwrap_or("default");
+    let (freed_pages, integrity, error) = match res {
+        Ok((freed_pages, integrity)) => {
+            if integrity == ["ok"] {
+                tracing::info!(
+                    app_name = %app_name,
+                    database,
+                    freed_pages,
+                    duration_ms = duration,
+                    "Maintained database"
+                );
+            } else {
+                tracing::warn!(
+                    app_name = %app_name,
+                    database,
+                    ?integrity,
+                    "Database integrity check failed"
+                );
+            }
+            (freed_pages, integrity, None)
+        },
+        Err(errno) => {
+            tracing::error!(
+                app_name = %app_name,
+                database,
+                ?errno,
+                "Database maintenance failed"
+            );
+            (0, Vec::new(), Some(format!("{errno:?}")))
+        },
+    };
+
+    MaintenanceReport {
+        database: name,
+        started,
+        duration,
+        freed_pages,
+        integrity,
+        error,
+    }
+}
+
+/// Run the incremental vacuum, `ANALYZE` and the integrity check on a database,
+/// returning the freed pages and the integrity check result.
+fn run(db_ptr: *mut sqlite3) -> Result<(u64, Vec<String>), Errno> {
+    // Wait for the modules writing to the database instead of failing.
+    unsafe { sqlite3_busy_timeout(db_ptr, BUSY_TIMEOUT_MS) };
+
+    let free_pages_before = free_pages(db_ptr)?;
+    execute(db_ptr, "PRAGMA incremental_vacuum;")?;
+    let freed_pages = free_pages_before.saturating_sub(free_pages(db_ptr)?);
+
+    execute(db_ptr, "ANALYZE;")?;
+
+    // `PRAGMA` statements can not be prepared, their table-valued functions can.
+    let integrity = query(
+        db_ptr,
+        &format!("SELECT * FROM pragma_integrity_check({MAX_INTEGRITY_ERRORS})"),
+    )?
+    .into_iter()
+    .map(|value| {
+        match value {
+            Value::Text(text) => text,
+            value => format!("{value:?}"),
+        }
+    })
+    .collect();
+
+    Ok((freed_pages, integrity))
+}
+
+/// Number of free pages of a database.
+fn free_pages(db_ptr: *mut sqlite3) -> Result<u64, Errno> {
+    Ok(
+        match query(db_ptr, "SELECT * FROM pragma_freelist_count()")?.first() {
+            Some(Value::Int32(count)) => u64::try_from(*count).unwrap_or_default(),
+            Some(Value::Int64(count)) => u64::try_from(*count).unwrap_or_default(),
+            _ => 0,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance_run() -> Result<(), Errno> {
+        let db_ptr = open(false, true, ApplicationName(String::from("tmp-dir")))?;
+        execute(
+            db_ptr,
+            "PRAGMA auto_vacuum = INCREMENTAL;
+            CREATE TABLE blocks(slot INTEGER PRIMARY KEY, cbor BLOB);
+            WITH RECURSIVE slots(slot) AS (SELECT 1 UNION ALL SELECT slot + 1 FROM slots WHERE slot < 100)
+            INSERT INTO blocks SELECT slot, zeroblob(4096) FROM slots;
+            DELETE FROM blocks;",
+        )?;
+
+        let res = run(db_ptr);
+        close(db_ptr)?;
+
+        let (freed_pages, integrity) = res?;
+        assert!(freed_pages > 0);
+        assert_eq!(integrity, [String::from("ok")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_maintenance_window() {
+        let day = NaiveDate::from_ymd_opt(2024, 1, 30).unwrap();
+        let mut app = AppMaintenance {
+            window: serde_json::from_str(r#"{"hour": 2}"#).unwrap(),
+            ..AppMaintenance::default()
+        };
+
+        assert!(!app.is_due(day, 3));
+        assert!(app.is_due(day, 2));
+        app.last_run = Some(day);
+        assert!(!app.is_due(day, 2));
+        assert!(app.is_due(day.succ_opt().unwrap(), 2));
+
+        app.window.enabled = false;
+        assert!(!app.is_due(day.succ_opt().unwrap(), 2));
+
+        assert_eq!(
+            serde_json::from_str::<MaintenanceWindow>("{}").unwrap(),
+            MaintenanceWindow::default()
+        );
+    }
+}
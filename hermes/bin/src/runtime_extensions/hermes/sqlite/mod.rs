@@ -1,16 +1,43 @@
 //! `SQLite` runtime extension implementation.
 
+mod blob;
 mod connection;
 mod core;
 mod host;
+pub(crate) mod maintenance;
+#[cfg(feature = "replication")]
+pub(crate) mod replication;
 mod state;
 mod statement;
+mod stats;
+
+pub(crate) use stats::{database_hashes, table_row_counts};
+
+use crate::app::ApplicationName;
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(ctx: &crate::runtime_context::HermesRuntimeContext) {
     state::get_db_state().add_app(ctx.app_name().clone());
     state::get_statement_state().add_app(ctx.app_name().clone());
+    state::get_blob_state().add_app(ctx.app_name().clone());
 
+    blob::new_context(ctx);
     connection::new_context(ctx);
     statement::new_context(ctx);
 }
+
+/// Release the BLOBs, statements and databases an unloaded app left open.
+pub(crate) fn unload_app(app_name: &ApplicationName) {
+    // A database can not be closed while it has open BLOBs or statements.
+    for blob_ptr in state::get_blob_state().remove_app(app_name) {
+        let _ = blob::core::close(blob_ptr as *mut _);
+    }
+    for stmt_ptr in state::get_statement_state().remove_app(app_name) {
+        let _ = statement::core::finalize(stmt_ptr as *mut _);
+    }
+    for db_ptr in state::get_db_state().remove_app(app_name) {
+        if let Err(errno) = connection::core::close(db_ptr as *mut _) {
+            tracing::warn!(app_name = %app_name, ?errno, "Failed to close the database of an unloaded app");
+        }
+    }
+}
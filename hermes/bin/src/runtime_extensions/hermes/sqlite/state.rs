@@ -3,11 +3,12 @@
 use once_cell::sync::Lazy;
 
 use crate::runtime_extensions::{
-    bindings::hermes::sqlite::api::{Sqlite, Statement},
+    bindings::hermes::sqlite::api::{Blob, Sqlite, Statement},
     resource_manager::ApplicationResourceStorage,
 };
 
-/// The object pointer used specifically with C objects like `sqlite3` or `sqlite3_stmt`.
+/// The object pointer used specifically with C objects like `sqlite3`, `sqlite3_stmt` or
+/// `sqlite3_blob`.
 pub(super) type ObjectPointer = usize;
 
 /// Map of app name to db resource holder
@@ -16,12 +17,18 @@ pub(super) type DbState = ApplicationResourceStorage<Sqlite, ObjectPointer>;
 /// Map of app name to db statement resource holder
 pub(super) type StatementState = ApplicationResourceStorage<Statement, ObjectPointer>;
 
+/// Map of app name to db BLOB resource holder
+pub(super) type BlobState = ApplicationResourceStorage<Blob, ObjectPointer>;
+
 /// Global state to hold `SQLite` db resources.
 static SQLITE_DB_STATE: Lazy<DbState> = Lazy::new(DbState::new);
 
 /// Global state to hold `SQLite` statement resources.
 static SQLITE_STATEMENT_STATE: Lazy<StatementState> = Lazy::new(StatementState::new);
 
+/// Global state to hold `SQLite` BLOB resources.
+static SQLITE_BLOB_STATE: Lazy<BlobState> = Lazy::new(BlobState::new);
+
 /// Get the global state of `SQLite` db resources.
 pub(super) fn get_db_state() -> &'static DbState {
     &SQLITE_DB_STATE
@@ -31,3 +38,8 @@ pub(super) fn get_db_state() -> &'static DbState {
 pub(super) fn get_statement_state() -> &'static StatementState {
     &SQLITE_STATEMENT_STATE
 }
+
+/// Get the global state of `SQLite` BLOB resources.
+pub(super) fn get_blob_state() -> &'static BlobState {
+    &SQLITE_BLOB_STATE
+}
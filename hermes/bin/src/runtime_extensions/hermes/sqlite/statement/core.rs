@@ -1,16 +1,27 @@
 //! Core functionality implementation for `SQLite` statement object.
 use std::os::raw::c_char;
 
+use dashmap::DashSet;
 use libsqlite3_sys::{
     sqlite3_bind_blob, sqlite3_bind_double, sqlite3_bind_int, sqlite3_bind_int64,
     sqlite3_bind_null, sqlite3_bind_text, sqlite3_column_blob, sqlite3_column_bytes,
     sqlite3_column_double, sqlite3_column_int64, sqlite3_column_text, sqlite3_column_type,
-    sqlite3_finalize, sqlite3_step, sqlite3_stmt, SQLITE_BLOB, SQLITE_DONE, SQLITE_FLOAT,
-    SQLITE_INTEGER, SQLITE_NULL, SQLITE_OK, SQLITE_ROW, SQLITE_TEXT, SQLITE_TRANSIENT,
+    sqlite3_db_handle, sqlite3_finalize, sqlite3_step, sqlite3_stmt, SQLITE_BLOB, SQLITE_DONE,
+    SQLITE_FLOAT, SQLITE_INTEGER, SQLITE_NULL, SQLITE_OK, SQLITE_ROW, SQLITE_TEXT,
+    SQLITE_TRANSIENT,
 };
+use once_cell::sync::Lazy;
 
+use super::super::connection::core::{in_transaction, retry_on_busy};
 use crate::runtime_extensions::bindings::hermes::sqlite::api::{Errno, Value};
 
+/// Statements which returned a row since they were last reset, keyed by statement
+/// pointer.
+///
+/// Stepping them again continues their result set, so a step failing with
+/// `SQLITE_BUSY` can not be retried without skipping or repeating rows.
+static RETURNED_ROWS: Lazy<DashSet<usize>> = Lazy::new(DashSet::new);
+
 /// Stores application data into parameters of the original SQL.
 pub(crate) fn bind(stmt_ptr: *mut sqlite3_stmt, index: i32, value: Value) -> Result<(), Errno> {
     let rc = unsafe {
@@ -57,13 +68,33 @@ pub(crate) fn bind(stmt_ptr: *mut sqlite3_stmt, index: i32, value: Value) -> Res
 
 /// Advances a statement to the next result row or to completion.
 pub(crate) fn step(stmt_ptr: *mut sqlite3_stmt) -> Result<(), Errno> {
-    let rc = unsafe { sqlite3_step(stmt_ptr) };
+    let step = || {
+        let rc = unsafe { sqlite3_step(stmt_ptr) };
 
-    if rc != SQLITE_DONE && rc != SQLITE_ROW {
-        Err(Errno::Sqlite(rc))
+        if rc != SQLITE_DONE && rc != SQLITE_ROW {
+            Err(Errno::Sqlite(rc))
+        } else {
+            Ok(rc == SQLITE_ROW)
+        }
+    };
+
+    // Outside of a transaction, a statement failing with `SQLITE_BUSY` before it returned
+    // any row is rolled back and can be run again. In a transaction the whole transaction
+    // should be, and once the statement returned rows the application should start over.
+    let db_ptr = unsafe { sqlite3_db_handle(stmt_ptr) };
+    let key = stmt_ptr as usize;
+    let result = if in_transaction(db_ptr) || RETURNED_ROWS.contains(&key) {
+        step()
     } else {
-        Ok(())
+        retry_on_busy(db_ptr, step)
+    };
+    // The statement is reset when stepped again after it completed or failed.
+    if let Ok(true) = result {
+        RETURNED_ROWS.insert(key);
+    } else {
+        RETURNED_ROWS.remove(&key);
     }
+    result.map(|_| ())
 }
 
 /// Returns information about a single column of the current result row of a query.
@@ -112,6 +143,7 @@ pub(crate) fn column(stmt_ptr: *mut sqlite3_stmt, index: i32) -> Result<Value, E
 /// then the function results without errors. If the most recent evaluation of
 /// statement failed, then the function results the appropriate error code.
 pub(crate) fn finalize(stmt_ptr: *mut sqlite3_stmt) -> Result<(), Errno> {
+    RETURNED_ROWS.remove(&(stmt_ptr as usize));
     let rc = unsafe { sqlite3_finalize(stmt_ptr) };
 
     if rc == SQLITE_OK {
@@ -279,4 +311,30 @@ mod tests {
 
         close(db_ptr)
     }
+
+    #[test]
+    fn test_step_tracks_returned_rows() -> Result<(), Errno> {
+        let db_ptr = init()?;
+
+        execute(db_ptr, "CREATE TABLE Rows(Id INTEGER PRIMARY KEY);")?;
+        execute(db_ptr, "INSERT INTO Rows(Id) VALUES(1), (2);")?;
+        let stmt_ptr = prepare(db_ptr, "SELECT Id FROM Rows;")?;
+        let key = stmt_ptr as usize;
+
+        // Once a row is returned, the statement is not retried on `SQLITE_BUSY`.
+        step(stmt_ptr)?;
+        assert!(RETURNED_ROWS.contains(&key));
+        step(stmt_ptr)?;
+        assert!(RETURNED_ROWS.contains(&key));
+        // Completing it resets it.
+        step(stmt_ptr)?;
+        assert!(!RETURNED_ROWS.contains(&key));
+
+        step(stmt_ptr)?;
+        assert!(RETURNED_ROWS.contains(&key));
+        finalize(stmt_ptr)?;
+        assert!(!RETURNED_ROWS.contains(&key));
+
+        close(db_ptr)
+    }
 }
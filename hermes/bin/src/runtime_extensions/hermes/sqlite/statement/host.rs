@@ -3,7 +3,10 @@
 use super::{super::state::get_statement_state, core};
 use crate::{
     runtime_context::HermesRuntimeContext,
-    runtime_extensions::bindings::hermes::sqlite::api::{Errno, HostStatement, Statement, Value},
+    runtime_extensions::{
+        bindings::hermes::sqlite::api::{Errno, HostStatement, Statement, Value},
+        hermes::error::HermesError,
+    },
 };
 
 impl HostStatement for HermesRuntimeContext {
@@ -15,11 +18,13 @@ impl HostStatement for HermesRuntimeContext {
     /// - `value`: The value to bind to the parameter.
     fn bind(
         &mut self, resource: wasmtime::component::Resource<Statement>, index: u32, value: Value,
-    ) -> wasmtime::Result<Result<(), Errno>> {
+    ) -> wasmtime::Result<Result<(), HermesError>> {
         let mut app_state = get_statement_state().get_app_state(self.app_name())?;
         let stmt_ptr = app_state.get_object(&resource)?;
-        let index = i32::try_from(index).map_err(|_| Errno::ConvertingNumeric)?;
-        Ok(core::bind(*stmt_ptr as *mut _, index, value))
+        let Ok(index) = i32::try_from(index) else {
+            return Ok(Err(Errno::ConvertingNumeric.into()));
+        };
+        Ok(core::bind(*stmt_ptr as *mut _, index, value).map_err(HermesError::from))
     }
 
     /// Advances a statement to the next result row or to completion.
@@ -28,10 +33,10 @@ impl HostStatement for HermesRuntimeContext {
     /// more times to evaluate the statement.
     fn step(
         &mut self, resource: wasmtime::component::Resource<Statement>,
-    ) -> wasmtime::Result<Result<(), Errno>> {
+    ) -> wasmtime::Result<Result<(), HermesError>> {
         let mut app_state = get_statement_state().get_app_state(self.app_name())?;
         let stmt_ptr = app_state.get_object(&resource)?;
-        Ok(core::step(*stmt_ptr as *mut _))
+        Ok(core::step(*stmt_ptr as *mut _).map_err(HermesError::from))
     }
 
     /// Returns information about a single column of the current result row of a query.
@@ -49,11 +54,13 @@ impl HostStatement for HermesRuntimeContext {
     /// The value of a result column in a specific data format.
     fn column(
         &mut self, resource: wasmtime::component::Resource<Statement>, index: u32,
-    ) -> wasmtime::Result<Result<Value, Errno>> {
+    ) -> wasmtime::Result<Result<Value, HermesError>> {
         let mut app_state = get_statement_state().get_app_state(self.app_name())?;
         let stmt_ptr = app_state.get_object(&resource)?;
-        let index = i32::try_from(index).map_err(|_| Errno::ConvertingNumeric)?;
-        Ok(core::column(*stmt_ptr as *mut _, index))
+        let Ok(index) = i32::try_from(index) else {
+            return Ok(Err(Errno::ConvertingNumeric.into()));
+        };
+        Ok(core::column(*stmt_ptr as *mut _, index).map_err(HermesError::from))
     }
 
     /// Destroys a prepared statement object. If the most recent evaluation of the
@@ -68,11 +75,11 @@ impl HostStatement for HermesRuntimeContext {
     /// segfaults and heap corruption.
     fn finalize(
         &mut self, resource: wasmtime::component::Resource<Statement>,
-    ) -> wasmtime::Result<Result<(), Errno>> {
+    ) -> wasmtime::Result<Result<(), HermesError>> {
         let app_state = get_statement_state().get_app_state(self.app_name())?;
         let stmt_ptr = app_state.delete_resource(resource)?;
 
-        Ok(core::finalize(stmt_ptr as *mut _))
+        Ok(core::finalize(stmt_ptr as *mut _).map_err(HermesError::from))
     }
 
     fn drop(&mut self, resource: wasmtime::component::Resource<Statement>) -> wasmtime::Result<()> {
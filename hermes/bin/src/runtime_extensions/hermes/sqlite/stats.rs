@@ -0,0 +1,231 @@
+//! Statistics of the persistent `SQLite` databases of an app, e.g. to attest its state.
+
+use std::collections::BTreeMap;
+
+use libsqlite3_sys::{sqlite3, sqlite3_column_count, sqlite3_step, SQLITE_DONE, SQLITE_ROW};
+
+use super::{
+    connection::core::{close, prepare},
+    core::{open, open_named},
+    maintenance::databases,
+    statement::core::{column, finalize},
+};
+use crate::{
+    app::ApplicationName,
+    packaging::hash::{Blake2b256, Blake2b256Hasher},
+    runtime_extensions::bindings::hermes::sqlite::api::{Errno, Value},
+};
+
+/// Key of the default database of an app in the database hashes, which can not name a
+/// named database.
+const DEFAULT_DB_KEY: &str = "(default)";
+
+/// Number of rows of every table of the persistent database of the app, keyed by table
+/// name.
+pub(crate) fn table_row_counts(
+    app_name: &ApplicationName,
+) -> anyhow::Result<BTreeMap<String, u64>> {
+    let db_ptr = open(true, false, app_name.clone())
+        .map_err(|err| anyhow::anyhow!("Failed to open the database of {app_name}: {err:?}"))?;
+    let counts = row_counts(db_ptr);
+    close(db_ptr)
+        .map_err(|err| anyhow::anyhow!("Failed to close the database of {app_name}: {err:?}"))?;
+    counts.map_err(|err| anyhow::anyhow!("Failed to count the rows of {app_name}: {err:?}"))
+}
+
+/// Hash hex of the canonical dump of every persistent database of the app, keyed by the
+/// database name, `DEFAULT_DB_KEY` for the default one.
+///
+/// The dump holds the content of the databases, not their file layout, so the hashes of
+/// two nodes holding the same data match.
+pub(crate) fn database_hashes(
+    app_name: &ApplicationName,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut hashes = BTreeMap::new();
+    for name in databases(app_name) {
+        let db_ptr = match &name {
+            Some(name) => open_named(name, true, false, app_name.clone()),
+            None => open(true, false, app_name.clone()),
+        }
+        .map_err(|err| anyhow::anyhow!("Failed to open the database of {app_name}: {err:?}"))?;
+        let hash = dump_hash(db_ptr);
+        close(db_ptr).map_err(|err| {
+            anyhow::anyhow!("Failed to close the database of {app_name}: {err:?}")
+        })?;
+        let hash = hash
+            .map_err(|err| anyhow::anyhow!("Failed to dump the database of {app_name}: {err:?}"))?;
+        hashes.insert(
+            name.unwrap_or_else(|| DEFAULT_DB_KEY.to_string()),
+            hash.to_hex(),
+        );
+    }
+    Ok(hashes)
+}
+
+/// Hash of the canonical dump of the database.
+///
+/// The dump holds the schema and the rows of every table, the tables ordered by name and
+/// their rows by all of their columns, every value tagged with its type.
+fn dump_hash(db_ptr: *mut sqlite3) -> Result<Blake2b256, Errno> {
+    let mut tables = Vec::new();
+    for_each_row(
+        db_ptr,
+        "SELECT name, sql FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' \
+         ORDER BY name",
+        |row| tables.push(row),
+    )?;
+
+    let mut hasher = Blake2b256Hasher::new();
+    for table in tables {
+        hasher.update(b"table");
+        hash_values(&mut hasher, &table);
+        let Some(Value::Text(name)) = table.first() else {
+            continue;
+        };
+        let select = format!("SELECT * FROM \"{}\"", name.replace('"', "\"\""));
+        let stmt_ptr = prepare(db_ptr, &select)?;
+        let columns = unsafe { sqlite3_column_count(stmt_ptr) };
+        finalize(stmt_ptr)?;
+        let order: Vec<_> = (1..=columns).map(|column| column.to_string()).collect();
+        let sql = format!("{select} ORDER BY {}", order.join(", "));
+        for_each_row(db_ptr, &sql, |row| {
+            hasher.update(b"row");
+            hash_values(&mut hasher, &row);
+        })?;
+    }
+    Ok(hasher.finalize())
+}
+
+/// Adds the values to the hash, every value tagged with its type and its length.
+fn hash_values(hasher: &mut Blake2b256Hasher, values: &[Value]) {
+    for value in values {
+        match value {
+            Value::Null => hasher.update(b"n"),
+            Value::Int32(int) => {
+                hasher.update(b"i");
+                hasher.update(&i64::from(*int).to_be_bytes());
+            },
+            Value::Int64(int) => {
+                hasher.update(b"i");
+                hasher.update(&int.to_be_bytes());
+            },
+            Value::Double(double) => {
+                hasher.update(b"d");
+                hasher.update(&double.to_bits().to_be_bytes());
+            },
+            Value::Text(text) => {
+                hasher.update(b"t");
+                hasher.update(&u64::try_from(text.len()).unwrap_or(u64::MAX).to_be_bytes());
+                hasher.update(text.as_bytes());
+            },
+            Value::Blob(blob) => {
+                hasher.update(b"b");
+                hasher.update(&u64::try_from(blob.len()).unwrap_or(u64::MAX).to_be_bytes());
+                hasher.update(blob);
+            },
+        }
+    }
+}
+
+/// Number of rows of every table of the database, keyed by table name.
+fn row_counts(db_ptr: *mut sqlite3) -> Result<BTreeMap<String, u64>, Errno> {
+    let tables = query(
+        db_ptr,
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )?;
+    tables
+        .into_iter()
+        .filter_map(|name| {
+            match name {
+                Value::Text(name) => Some(name),
+                _ => None,
+            }
+        })
+        .map(|name| {
+            let sql = format!("SELECT count(*) FROM \"{}\"", name.replace('"', "\"\""));
+            let count = match query(db_ptr, &sql)?.first() {
+                Some(Value::Int32(count)) => u64::try_from(*count).unwrap_or_default(),
+                Some(Value::Int64(count)) => u64::try_from(*count).unwrap_or_default(),
+                _ => 0,
+            };
+            Ok((name, count))
+        })
+        .collect()
+}
+
+/// Values of the first column of every row the query returns.
+pub(super) fn query(db_ptr: *mut sqlite3, sql: &str) -> Result<Vec<Value>, Errno> {
+    let mut values = Vec::new();
+    for_each_row(db_ptr, sql, |row| values.extend(row.into_iter().take(1)))?;
+    Ok(values)
+}
+
+/// Calls `f` with the values of every row the query returns.
+fn for_each_row(
+    db_ptr: *mut sqlite3, sql: &str, mut f: impl FnMut(Vec<Value>),
+) -> Result<(), Errno> {
+    let stmt_ptr = prepare(db_ptr, sql)?;
+    let columns = unsafe { sqlite3_column_count(stmt_ptr) };
+    let res = loop {
+        match unsafe { sqlite3_step(stmt_ptr) } {
+            SQLITE_ROW => {
+                match (0..columns).map(|index| column(stmt_ptr, index)).collect() {
+                    Ok(row) => f(row),
+                    Err(err) => break Err(err),
+                }
+            },
+            SQLITE_DONE => break Ok(()),
+            rc => break Err(Errno::Sqlite(rc)),
+        }
+    };
+    finalize(stmt_ptr)?;
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime_extensions::hermes::sqlite::connection::core::execute;
+
+    #[test]
+    fn test_row_counts() -> Result<(), Errno> {
+        let db_ptr = open(false, true, ApplicationName(String::from("tmp-dir")))?;
+
+        execute(db_ptr, "CREATE TABLE Blocks(Slot INTEGER PRIMARY KEY);")?;
+        execute(db_ptr, "INSERT INTO Blocks(Slot) VALUES(1), (2), (3);")?;
+        execute(db_ptr, "CREATE TABLE \"Tx \"\"Inputs\"\"\"(Id INTEGER);")?;
+
+        let counts = row_counts(db_ptr);
+        close(db_ptr)?;
+
+        assert_eq!(
+            counts?,
+            BTreeMap::from([("Blocks".to_string(), 3), ("Tx \"Inputs\"".to_string(), 0)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_hash() -> Result<(), Errno> {
+        let dump = |rows: &[&str]| -> Result<Blake2b256, Errno> {
+            let db_ptr = open(false, true, ApplicationName(String::from("tmp-dir")))?;
+            execute(db_ptr, "CREATE TABLE Blocks(Slot INTEGER, Hash BLOB);")?;
+            for row in rows {
+                execute(db_ptr, &format!("INSERT INTO Blocks VALUES({row});"))?;
+            }
+            let hash = dump_hash(db_ptr);
+            close(db_ptr)?;
+            hash
+        };
+
+        // The order the rows were written in does not change the dump.
+        assert_eq!(
+            dump(&["1, x'01'", "2, x'02'"])?,
+            dump(&["2, x'02'", "1, x'01'"])?
+        );
+        assert_ne!(dump(&["1, x'01'", "2, x'02'"])?, dump(&["1, x'01'"])?);
+        // Values of different types do not collide.
+        assert_ne!(dump(&["1, '1'"])?, dump(&["1, x'31'"])?);
+        Ok(())
+    }
+}
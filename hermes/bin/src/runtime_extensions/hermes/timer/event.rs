@@ -0,0 +1,26 @@
+//! Timer runtime extension event handler implementation.
+
+use crate::{
+    event::HermesEventPayload, runtime_extensions::bindings::hermes::timer::api::TimerTag,
+};
+
+/// On timer event
+#[derive(Clone, Debug)]
+pub(crate) struct OnTimerEvent {
+    /// The tag of the timer that fired.
+    pub(crate) tag: TimerTag,
+}
+
+impl HermesEventPayload for OnTimerEvent {
+    fn event_name(&self) -> &str {
+        "on-timer"
+    }
+
+    fn execute(&self, module: &mut crate::wasm::module::ModuleInstance) -> anyhow::Result<()> {
+        module
+            .instance
+            .hermes_timer_event()
+            .call_on_timer(&mut module.store, &self.tag)?;
+        Ok(())
+    }
+}
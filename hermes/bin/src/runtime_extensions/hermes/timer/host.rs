@@ -0,0 +1,76 @@
+//! Timer host implementation for WASM runtime.
+
+use super::state::{cancel, get_resources, remaining, remove, reset, start};
+use crate::{
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::bindings::hermes::timer::api::{Host, HostTimer, Timer, TimerTag},
+};
+
+impl HostTimer for HermesRuntimeContext {
+    /// Start a timer which fires once after `ms` milliseconds.
+    ///
+    /// **Parameters**
+    ///
+    /// - `tag` : The tag which will accompany the triggered event.
+    /// - `ms` : Milliseconds until the timer fires.
+    fn new(
+        &mut self, tag: TimerTag, ms: u64,
+    ) -> wasmtime::Result<wasmtime::component::Resource<Timer>> {
+        let id = start(self.app_name().clone(), self.module_id().clone(), tag, ms)?;
+        let app_state = get_resources().get_app_state(self.app_name())?;
+        Ok(app_state.create_resource(id))
+    }
+
+    /// Cancel the timer.
+    ///
+    /// **Returns**
+    ///
+    /// - `true` : The timer was pending, and will not fire.
+    /// - `false` : The timer already fired, or was already cancelled.
+    fn cancel(&mut self, resource: wasmtime::component::Resource<Timer>) -> wasmtime::Result<bool> {
+        let mut app_state = get_resources().get_app_state(self.app_name())?;
+        let id = *app_state.get_object(&resource)?;
+        Ok(cancel(id))
+    }
+
+    /// Restart the timer, so it fires once after `ms` milliseconds from now.
+    ///
+    /// **Parameters**
+    ///
+    /// - `ms` : Milliseconds until the timer fires.
+    ///
+    /// **Returns**
+    ///
+    /// - `true` : The timer was pending, and its previous deadline is discarded.
+    /// - `false` : The timer was not pending.
+    fn reset(
+        &mut self, resource: wasmtime::component::Resource<Timer>, ms: u64,
+    ) -> wasmtime::Result<bool> {
+        let mut app_state = get_resources().get_app_state(self.app_name())?;
+        let id = *app_state.get_object(&resource)?;
+        reset(id, ms)
+    }
+
+    /// Milliseconds left until the timer fires.
+    ///
+    /// **Returns**
+    ///
+    /// - `some(ms)` : The timer is pending.
+    /// - `none` : The timer already fired, or was cancelled.
+    fn remaining(
+        &mut self, resource: wasmtime::component::Resource<Timer>,
+    ) -> wasmtime::Result<Option<u64>> {
+        let mut app_state = get_resources().get_app_state(self.app_name())?;
+        let id = *app_state.get_object(&resource)?;
+        Ok(remaining(id))
+    }
+
+    fn drop(&mut self, resource: wasmtime::component::Resource<Timer>) -> wasmtime::Result<()> {
+        let app_state = get_resources().get_app_state(self.app_name())?;
+        let id = app_state.delete_resource(resource)?;
+        remove(id);
+        Ok(())
+    }
+}
+
+impl Host for HermesRuntimeContext {}
@@ -0,0 +1,17 @@
+//! Timer runtime extension implementation.
+
+use crate::app::ApplicationName;
+
+mod event;
+mod host;
+mod state;
+
+/// Advise Runtime Extensions of a new context
+pub(crate) fn new_context(ctx: &crate::runtime_context::HermesRuntimeContext) {
+    state::get_resources().add_app(ctx.app_name().clone());
+}
+
+/// Cancel the timers of an unloaded app.
+pub(crate) fn unload_app(app_name: &ApplicationName) {
+    state::remove_app(app_name);
+}
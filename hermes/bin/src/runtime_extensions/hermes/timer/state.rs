@@ -0,0 +1,250 @@
+//! Timer state.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use tokio::{runtime::Handle, task::JoinHandle};
+
+use super::event::OnTimerEvent;
+use crate::{
+    app::ApplicationName,
+    event::{queue::send, HermesEvent, TargetApp, TargetModule},
+    runtime_extensions::{
+        bindings::hermes::timer::api::{Timer, TimerTag},
+        resource_manager::ApplicationResourceStorage,
+    },
+    wasm::module::ModuleId,
+};
+
+/// Timer id, unique across all apps.
+pub(super) type TimerId = u64;
+
+/// Map of app name to timer resources.
+pub(super) type Resources = ApplicationResourceStorage<Timer, TimerId>;
+
+/// Global state to hold the timer resources.
+static TIMER_RESOURCES: Lazy<Resources> = Lazy::new(ApplicationResourceStorage::new);
+
+/// Next available timer id.
+static NEXT_TIMER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// State of every timer.
+static TIMERS: Lazy<DashMap<TimerId, TimerState>> = Lazy::new(DashMap::new);
+
+/// Handle of the runtime sleeping until the timers fire.
+/// It runs on its own OS thread, as host functions are not called from a runtime.
+static TIMER_RUNTIME: Lazy<Option<Handle>> = Lazy::new(|| {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to start the timer runtime");
+            return None;
+        },
+    };
+    let handle = runtime.handle().clone();
+    std::thread::spawn(move || runtime.block_on(std::future::pending::<()>()));
+    Some(handle)
+});
+
+/// State of a timer.
+struct TimerState {
+    /// App which started the timer.
+    app_name: ApplicationName,
+    /// Module which started the timer, and receives its `on-timer` event.
+    module_id: ModuleId,
+    /// Tag which accompanies the `on-timer` event.
+    tag: TimerTag,
+    /// When the timer fires, `None` if it fired or was cancelled.
+    deadline: Option<Instant>,
+    /// Incremented every time the timer is started or cancelled, so a superseded
+    /// sleeping task does not fire it.
+    generation: u64,
+    /// Task sleeping until the deadline.
+    task: Option<JoinHandle<()>>,
+}
+
+impl TimerState {
+    /// Discard the pending deadline, if any.
+    /// Returns `true` if the timer was pending.
+    fn disarm(&mut self) -> bool {
+        self.generation = self.generation.wrapping_add(1);
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.deadline.take().is_some()
+    }
+
+    /// Start the timer, so it fires once after `ms` milliseconds from now.
+    fn arm(&mut self, id: TimerId, ms: u64) -> anyhow::Result<()> {
+        let runtime = TIMER_RUNTIME
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Timer runtime is not running"))?;
+        let duration = Duration::from_millis(ms);
+        let deadline = Instant::now()
+            .checked_add(duration)
+            .ok_or_else(|| anyhow::anyhow!("Timer duration of {ms}ms is too long"))?;
+
+        self.disarm();
+        let generation = self.generation;
+        self.deadline = Some(deadline);
+        self.task = Some(runtime.spawn(async move {
+            tokio::time::sleep(duration).await;
+            fire(id, generation);
+        }));
+        Ok(())
+    }
+}
+
+/// Get the timer resources.
+pub(super) fn get_resources() -> &'static Resources {
+    &TIMER_RESOURCES
+}
+
+/// Start a new timer, which fires once after `ms` milliseconds.
+pub(super) fn start(
+    app_name: ApplicationName, module_id: ModuleId, tag: TimerTag, ms: u64,
+) -> anyhow::Result<TimerId> {
+    let id = NEXT_TIMER_ID.fetch_add(1, Ordering::Relaxed);
+    TIMERS.insert(id, TimerState {
+        app_name,
+        module_id,
+        tag,
+        deadline: None,
+        generation: 0,
+        task: None,
+    });
+    // Armed once in the map, so a timer firing right away finds its state.
+    let armed = match TIMERS.get_mut(&id) {
+        Some(mut state) => state.arm(id, ms),
+        None => Err(anyhow::anyhow!("Timer {id} not found")),
+    };
+    if let Err(err) = armed {
+        TIMERS.remove(&id);
+        return Err(err);
+    }
+    Ok(id)
+}
+
+/// Cancel a timer.
+/// Returns `true` if the timer was pending.
+pub(super) fn cancel(id: TimerId) -> bool {
+    TIMERS.get_mut(&id).is_some_and(|mut state| state.disarm())
+}
+
+/// Restart a timer, so it fires once after `ms` milliseconds from now.
+/// Returns `true` if the timer was pending.
+pub(super) fn reset(id: TimerId, ms: u64) -> anyhow::Result<bool> {
+    let mut state = TIMERS
+        .get_mut(&id)
+        .ok_or_else(|| anyhow::anyhow!("Timer {id} not found"))?;
+    let pending = state.deadline.is_some();
+    state.arm(id, ms)?;
+    Ok(pending)
+}
+
+/// Milliseconds left until a timer fires, `None` if it is not pending.
+pub(super) fn remaining(id: TimerId) -> Option<u64> {
+    let state = TIMERS.get(&id)?;
+    let remaining = state.deadline?.saturating_duration_since(Instant::now());
+    Some(remaining.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+/// Cancel and forget a timer, when its resource is dropped.
+pub(super) fn remove(id: TimerId) {
+    if let Some((_, mut state)) = TIMERS.remove(&id) {
+        state.disarm();
+    }
+}
+
+/// Cancel and forget every timer of an unloaded app.
+pub(super) fn remove_app(app_name: &ApplicationName) {
+    TIMERS.retain(|_, state| {
+        if &state.app_name != app_name {
+            return true;
+        }
+        state.disarm();
+        false
+    });
+    TIMER_RESOURCES.remove_app(app_name);
+}
+
+/// Fire a timer, sending the `on-timer` event to the module which started it.
+/// Does nothing if the timer was restarted or cancelled since `generation`.
+fn fire(id: TimerId, generation: u64) {
+    let Some(mut state) = TIMERS.get_mut(&id) else {
+        return;
+    };
+    if state.generation != generation || state.deadline.take().is_none() {
+        return;
+    }
+    state.task = None;
+
+    let event = HermesEvent::new(
+        OnTimerEvent {
+            tag: state.tag.clone(),
+        },
+        TargetApp::List(vec![state.app_name.clone()]),
+        TargetModule::List(vec![state.module_id.clone()]),
+    );
+    if let Err(err) = send(event) {
+        tracing::error!(app_name = %state.app_name, tag = %state.tag, "Failed to send on-timer event: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_and_reset() {
+        let id = start(
+            ApplicationName("timer_test".to_string()),
+            ModuleId(rusty_ulid::Ulid::generate()),
+            "retry".to_string(),
+            60_000,
+        )
+        .unwrap();
+
+        assert!(remaining(id).is_some_and(|ms| ms <= 60_000));
+        assert!(cancel(id));
+        assert!(!cancel(id));
+        assert_eq!(remaining(id), None);
+
+        assert!(!reset(id, 1_000).unwrap());
+        assert!(remaining(id).is_some_and(|ms| ms <= 1_000));
+        assert!(reset(id, 2_000).unwrap());
+
+        remove(id);
+        assert_eq!(remaining(id), None);
+        assert!(reset(id, 1_000).is_err());
+    }
+
+    #[test]
+    fn zero_ms_timer_fires() {
+        let id = start(
+            ApplicationName("timer_test".to_string()),
+            ModuleId(rusty_ulid::Ulid::generate()),
+            "now".to_string(),
+            0,
+        )
+        .unwrap();
+
+        let started = Instant::now();
+        while remaining(id).is_some() {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "timer did not fire"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!cancel(id));
+        remove(id);
+    }
+}
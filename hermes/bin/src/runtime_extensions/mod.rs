@@ -4,16 +4,26 @@
 
 use tracing::{span, Level};
 
-mod app_config;
+pub(crate) mod app_config;
 pub(crate) mod bindings;
 pub mod hermes;
-mod resource_manager;
-mod wasi;
+pub mod plugin;
+pub(crate) mod resource_manager;
+pub mod versioning;
+pub(crate) mod wasi;
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(ctx: &crate::runtime_context::HermesRuntimeContext) {
     span!(Level::INFO, "Context Span", ctx = ?ctx).in_scope(|| {
         hermes::new_context(ctx);
         wasi::new_context(ctx);
+        plugin::new_context(ctx);
+    });
+}
+
+/// Advise Runtime Extensions an app was unloaded
+pub(crate) fn unload_app(app_name: &crate::app::ApplicationName) {
+    span!(Level::INFO, "Unload Span", app_name = %app_name).in_scope(|| {
+        hermes::unload_app(app_name);
     });
 }
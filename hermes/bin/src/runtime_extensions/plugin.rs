@@ -0,0 +1,168 @@
+//! Runtime extension plugins.
+//!
+//! Native host extensions which implement WIT interfaces outside of the Hermes world, so
+//! deployments can expose their own host APIs (e.g. a proprietary data source) to their
+//! modules without forking the Hermes node.
+//!
+//! Plugins are registered before any app is loaded, either by a binary embedding Hermes
+//! calling [`register`], or, with the `plugins` feature, by loading a shared library
+//! which exports a [`PLUGIN_ENTRY`] constructor.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+use once_cell::sync::Lazy;
+use wasmtime::component::Linker;
+
+use crate::runtime_context::HermesRuntimeContext;
+
+/// A native runtime extension.
+pub trait RuntimeExtensionPlugin: Send + Sync + 'static {
+    /// Unique name of the plugin.
+    fn name(&self) -> &str;
+
+    /// Add the host functions of the WIT interfaces implemented by the plugin to the
+    /// linker of a module, e.g. with `linker.instance("acme:data/api")?.func_wrap(..)`.
+    ///
+    /// # Errors
+    ///
+    /// If a function can not be defined, e.g. it is already defined by Hermes.
+    fn add_to_linker(&self, linker: &mut Linker<HermesRuntimeContext>) -> anyhow::Result<()>;
+
+    /// Advise the plugin of a new context, before an event is executed by a module.
+    fn new_context(&self, _ctx: &HermesRuntimeContext) {}
+}
+
+/// Name of the constructor exported by plugin shared libraries.
+///
+/// It must have the [`PluginCreate`] signature. As Rust has no stable ABI, the library
+/// must be built with the same compiler and the same version of this crate as the node.
+#[allow(dead_code)]
+pub const PLUGIN_ENTRY: &str = "hermes_plugin_create";
+
+/// Signature of the constructor exported by plugin shared libraries, returning an owned
+/// `Box::into_raw(Box::new(Box::new(plugin)))`.
+#[allow(dead_code)]
+pub type PluginCreate = unsafe extern "C" fn() -> *mut Box<dyn RuntimeExtensionPlugin>;
+
+/// Registered plugins, in registration order.
+static PLUGINS: Lazy<RwLock<Vec<Arc<dyn RuntimeExtensionPlugin>>>> =
+    Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Register a plugin.
+///
+/// Must be called before any app is loaded, modules only link the plugins registered
+/// when they are loaded.
+///
+/// # Errors
+///
+/// If a plugin with the same name is already registered.
+#[allow(dead_code)]
+pub fn register(plugin: impl RuntimeExtensionPlugin) -> anyhow::Result<()> {
+    register_arc(Arc::new(plugin))
+}
+
+/// Register a shared plugin.
+#[allow(dead_code)]
+fn register_arc(plugin: Arc<dyn RuntimeExtensionPlugin>) -> anyhow::Result<()> {
+    let mut plugins = PLUGINS.write().unwrap_or_else(PoisonError::into_inner);
+    anyhow::ensure!(
+        plugins.iter().all(|p| p.name() != plugin.name()),
+        "Runtime extension plugin {} is already registered",
+        plugin.name()
+    );
+    tracing::info!(
+        plugin = plugin.name(),
+        "Registered runtime extension plugin"
+    );
+    plugins.push(plugin);
+    Ok(())
+}
+
+/// Registered plugins.
+fn plugins() -> Vec<Arc<dyn RuntimeExtensionPlugin>> {
+    PLUGINS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Names of the registered plugins, in registration order.
+pub(crate) fn names() -> Vec<String> {
+    plugins()
+        .iter()
+        .map(|plugin| plugin.name().to_string())
+        .collect()
+}
+
+/// Add the host functions of every registered plugin to the linker of a module.
+pub(crate) fn add_to_linker(linker: &mut Linker<HermesRuntimeContext>) -> anyhow::Result<()> {
+    for plugin in plugins() {
+        plugin.add_to_linker(linker).map_err(|err| {
+            anyhow::anyhow!("Runtime extension plugin {} failed: {err}", plugin.name())
+        })?;
+    }
+    Ok(())
+}
+
+/// Advise every registered plugin of a new context.
+pub(crate) fn new_context(ctx: &HermesRuntimeContext) {
+    for plugin in plugins() {
+        plugin.new_context(ctx);
+    }
+}
+
+/// Load and register a plugin from a shared library.
+///
+/// The library stays loaded for the lifetime of the node.
+///
+/// # Errors
+///
+/// If the library can not be loaded, does not export [`PLUGIN_ENTRY`], or the plugin is
+/// already registered.
+#[cfg(feature = "plugins")]
+pub(crate) fn load(path: &std::path::Path) -> anyhow::Result<()> {
+    // SAFETY: Loading a library runs its initializers, the node trusts the plugins it is
+    // told to load.
+    let library = unsafe { libloading::Library::new(path)? };
+    let plugin = {
+        // SAFETY: `PLUGIN_ENTRY` is documented to have the `PluginCreate` signature.
+        let create = unsafe { library.get::<PluginCreate>(PLUGIN_ENTRY.as_bytes())? };
+        // SAFETY: The constructor returns an owned pointer, created by `Box::into_raw`.
+        unsafe { Box::from_raw(create()) }
+    };
+    // The code of the plugin must outlive it.
+    std::mem::forget(library);
+    register_arc(Arc::from(*plugin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plugin with an `echo` function returning its argument.
+    struct Echo;
+
+    impl RuntimeExtensionPlugin for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn add_to_linker(&self, linker: &mut Linker<HermesRuntimeContext>) -> anyhow::Result<()> {
+            linker
+                .instance("test:echo/api")?
+                .func_wrap("echo", |_store, (value,): (u32,)| Ok((value,)))
+        }
+    }
+
+    #[test]
+    fn plugins_are_linked_once() {
+        register(Echo).unwrap();
+        assert!(register(Echo).is_err());
+
+        let engine = wasmtime::Engine::default();
+        let mut linker = Linker::new(&engine);
+        add_to_linker(&mut linker).unwrap();
+        // Linking the same interface twice fails.
+        assert!(add_to_linker(&mut linker).is_err());
+    }
+}
@@ -2,14 +2,32 @@
 
 use std::{
     any::type_name,
+    collections::BTreeMap,
     ops::DerefMut,
-    sync::atomic::{AtomicU32, Ordering},
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use dashmap::DashMap;
+use once_cell::sync::Lazy;
 
 use crate::app::ApplicationName;
 
+/// Number of resource handles held by every app, by resource type.
+static RESOURCE_HANDLES: Lazy<DashMap<(ApplicationName, &'static str), Arc<AtomicUsize>>> =
+    Lazy::new(DashMap::new);
+
+/// Get the number of resource handles held by the app, by resource type.
+pub(crate) fn resource_handles(app_name: &ApplicationName) -> BTreeMap<&'static str, usize> {
+    RESOURCE_HANDLES
+        .iter()
+        .filter(|entry| &entry.key().0 == app_name)
+        .map(|entry| (entry.key().1, entry.value().load(Ordering::Acquire)))
+        .collect()
+}
+
 /// `ResourceStorage` struct.
 /// - `WitType` represents the type from the wit file definitions and which will appear in
 ///   the `wasmtime::component::Resource<WitType>` object.
@@ -20,6 +38,8 @@ pub(crate) struct ResourceStorage<WitType, RustType> {
     state: DashMap<u32, RustType>,
     /// Next available address id of the resource.
     available_address: AtomicU32,
+    /// Number of resources held.
+    handles: Arc<AtomicUsize>,
     /// `WitType` type phantom.
     _phantom: std::marker::PhantomData<WitType>,
 }
@@ -32,6 +52,7 @@ where WitType: 'static
         Self {
             state: DashMap::new(),
             available_address: AtomicU32::default(),
+            handles: Arc::default(),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -43,6 +64,7 @@ where WitType: 'static
     ) -> wasmtime::component::Resource<WitType> {
         let available_address = self.available_address.load(Ordering::Acquire);
         self.state.insert(available_address, object);
+        self.handles.store(self.state.len(), Ordering::Release);
 
         // Increment the value of the available address to 1,
         // so that it can be used for the next resource.
@@ -77,10 +99,13 @@ where WitType: 'static
     pub(crate) fn delete_resource(
         &self, resource: wasmtime::component::Resource<WitType>,
     ) -> anyhow::Result<RustType> {
-        self.state
+        let object = self
+            .state
             .remove(&resource.rep())
             .map(|(_, v)| v)
-            .ok_or(Self::resource_not_found_err())
+            .ok_or(Self::resource_not_found_err())?;
+        self.handles.store(self.state.len(), Ordering::Release);
+        Ok(object)
     }
 
     /// Resource not found error message.
@@ -118,7 +143,12 @@ where WitType: 'static
     /// If the application state already exists, do nothing.
     pub(crate) fn add_app(&self, app_name: ApplicationName) {
         if !self.state.contains_key(&app_name) {
-            self.state.insert(app_name, ResourceStorage::new());
+            let storage = ResourceStorage::new();
+            RESOURCE_HANDLES.insert(
+                (app_name.clone(), type_name::<WitType>()),
+                storage.handles.clone(),
+            );
+            self.state.insert(app_name, storage);
         }
     }
 
@@ -138,9 +168,19 @@ where WitType: 'static
     }
 
     /// Removes application and all associated resources from the resource manager.
-    #[allow(dead_code)]
-    pub(crate) fn remove_app(&self, app_name: &ApplicationName) {
-        self.state.remove(app_name);
+    /// Returns the objects of the removed resources, e.g. to release them.
+    pub(crate) fn remove_app(&self, app_name: &ApplicationName) -> Vec<RustType> {
+        RESOURCE_HANDLES.remove(&(app_name.clone(), type_name::<WitType>()));
+        self.state
+            .remove(app_name)
+            .map(|(_, storage)| {
+                storage
+                    .state
+                    .into_iter()
+                    .map(|(_, object)| object)
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     /// Application not found error message.
@@ -183,7 +223,7 @@ mod tests {
             assert!(resource_manager.get_app_state(&app_name_1).is_err());
             resource_manager.add_app(app_name_1.clone());
             assert!(resource_manager.get_app_state(&app_name_1).is_ok());
-            resource_manager.remove_app(&app_name_1);
+            assert!(resource_manager.remove_app(&app_name_1).is_empty());
             assert!(resource_manager.get_app_state(&app_name_1).is_err());
         }
 
@@ -198,6 +238,14 @@ mod tests {
             resource_manager.add_app(app_name_1.clone());
             let mut app_state = resource_manager.get_app_state(&app_name_1).unwrap();
             assert!(app_state.get_object(&res).is_ok());
+            assert_eq!(
+                resource_handles(&app_name_1).get(type_name::<WitType>()),
+                Some(&1)
+            );
+
+            drop(app_state);
+            assert_eq!(resource_manager.remove_app(&app_name_1), vec![object]);
+            assert!(resource_handles(&app_name_1).is_empty());
         }
     }
 }
@@ -0,0 +1,142 @@
+//! Version negotiation of the `hermes:*` interfaces imported by modules.
+//!
+//! The runtime implements the current version of every `hermes:*` interface, which
+//! modules import unversioned. A module compiled against an older version imports
+//! `hermes:<package>/<interface>@<version>` instead, and is linked with the adapter which
+//! implements that version on top of the current host, if the runtime has one.
+//!
+//! Modules are checked when they are packaged and when they are loaded, so a module
+//! importing a version without an adapter is rejected up front, with the list of every
+//! unsupported import.
+
+use std::sync::{PoisonError, RwLock};
+
+use wasmtime::{
+    component::{Component, Linker},
+    Engine,
+};
+
+use crate::runtime_context::HermesRuntimeContext;
+
+/// Links an older version of a `hermes:*` interface.
+pub type AdapterFn = fn(&mut Linker<HermesRuntimeContext>) -> anyhow::Result<()>;
+
+/// Adapter of an older version of a `hermes:*` interface.
+#[derive(Clone)]
+struct InterfaceAdapter {
+    /// Interface name, without version, e.g. `hermes:cron/api`.
+    interface: String,
+    /// Version of the interface implemented by the adapter, e.g. `0.1.0`.
+    version: String,
+    /// Adds the interface at `version` to the linker.
+    add_to_linker: AdapterFn,
+}
+
+/// Registered interface adapters.
+static ADAPTERS: RwLock<Vec<InterfaceAdapter>> = RwLock::new(Vec::new());
+
+/// Register the adapter of an older version of a `hermes:*` interface.
+///
+/// `add_to_linker` must define the interface under its versioned name,
+/// `<interface>@<version>`.
+///
+/// # Errors
+///
+/// If an adapter of the same interface version is already registered.
+#[allow(dead_code)]
+pub fn register_adapter(
+    interface: &str, version: &str, add_to_linker: AdapterFn,
+) -> anyhow::Result<()> {
+    let mut adapters = ADAPTERS.write().unwrap_or_else(PoisonError::into_inner);
+    anyhow::ensure!(
+        adapter(&adapters, interface, version).is_none(),
+        "Adapter of {interface}@{version} is already registered"
+    );
+    adapters.push(InterfaceAdapter {
+        interface: interface.to_string(),
+        version: version.to_string(),
+        add_to_linker,
+    });
+    Ok(())
+}
+
+/// Find the adapter of an interface version.
+fn adapter<'a>(
+    adapters: &'a [InterfaceAdapter], interface: &str, version: &str,
+) -> Option<&'a InterfaceAdapter> {
+    adapters
+        .iter()
+        .find(|adapter| adapter.interface == interface && adapter.version == version)
+}
+
+/// Split a versioned `hermes:*` import name into its interface and version.
+/// `None` for unversioned and non Hermes imports.
+fn versioned_hermes_import(name: &str) -> Option<(&str, &str)> {
+    name.split_once('@')
+        .filter(|(interface, _)| interface.starts_with("hermes:"))
+}
+
+/// Add the adapters of the older `hermes:*` interface versions imported by a module to
+/// its linker.
+///
+/// # Errors
+///
+/// If the module imports interface versions the runtime has no adapter for, or an
+/// adapter fails to link.
+pub(crate) fn add_to_linker(
+    component: &Component, engine: &Engine, linker: &mut Linker<HermesRuntimeContext>,
+) -> anyhow::Result<()> {
+    let adapters = ADAPTERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+
+    let mut unsupported = Vec::new();
+    let mut needed = Vec::new();
+    for (name, _) in component.component_type().imports(engine) {
+        let Some((interface, version)) = versioned_hermes_import(name) else {
+            continue;
+        };
+        match adapter(&adapters, interface, version) {
+            Some(adapter) => needed.push(adapter),
+            None => unsupported.push(name.to_string()),
+        }
+    }
+    anyhow::ensure!(
+        unsupported.is_empty(),
+        "Module imports Hermes interface versions which are not supported by this runtime: {}",
+        unsupported.join(", ")
+    );
+
+    for adapter in needed {
+        (adapter.add_to_linker)(linker).map_err(|err| {
+            anyhow::anyhow!(
+                "Adapter of {}@{} failed: {err}",
+                adapter.interface,
+                adapter.version
+            )
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_imports() {
+        assert_eq!(
+            versioned_hermes_import("hermes:cron/api@0.1.0"),
+            Some(("hermes:cron/api", "0.1.0"))
+        );
+        assert_eq!(versioned_hermes_import("hermes:cron/api"), None);
+        assert_eq!(versioned_hermes_import("wasi:http/types@0.2.0"), None);
+
+        register_adapter("hermes:test/api", "0.1.0", |_| Ok(())).unwrap();
+        assert!(register_adapter("hermes:test/api", "0.1.0", |_| Ok(())).is_err());
+        let adapters = ADAPTERS.read().unwrap().clone();
+        assert!(adapter(&adapters, "hermes:test/api", "0.1.0").is_some());
+        assert!(adapter(&adapters, "hermes:test/api", "0.2.0").is_none());
+    }
+}
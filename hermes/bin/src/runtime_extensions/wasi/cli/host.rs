@@ -9,7 +9,10 @@ use crate::{
             cli,
             io::streams::{InputStream, OutputStream},
         },
-        wasi::io::streams::{get_input_streams_state, get_output_streams_state},
+        wasi::{
+            cli::state::get_environment,
+            io::streams::{get_input_streams_state, get_output_streams_state},
+        },
     },
 };
 
@@ -23,7 +26,7 @@ impl cli::environment::Host for HermesRuntimeContext {
     /// in the component model, this import function should return the same
     /// values each time it is called.
     fn get_environment(&mut self) -> wasmtime::Result<Vec<(String, String)>> {
-        Ok(Vec::new())
+        Ok(get_environment(self.app_name(), self.module_id()))
     }
 
     /// Get the POSIX-style arguments to the program.
@@ -55,16 +58,14 @@ impl cli::stdin::Host for HermesRuntimeContext {
 
 impl cli::stdout::Host for HermesRuntimeContext {
     fn get_stdout(&mut self) -> wasmtime::Result<wasmtime::component::Resource<OutputStream>> {
-        // TODO: Redirect stdout to Hermes' logging api.
         let app_state = get_output_streams_state().get_app_state(self.app_name())?;
-        Ok(app_state.create_resource(Box::new(std::io::empty())))
+        Ok(app_state.create_resource(Box::new(self.guest_stdout().clone())))
     }
 }
 
 impl cli::stderr::Host for HermesRuntimeContext {
     fn get_stderr(&mut self) -> wasmtime::Result<wasmtime::component::Resource<OutputStream>> {
-        // TODO: Redirect stderr to Hermes' logging api.
         let app_state = get_output_streams_state().get_app_state(self.app_name())?;
-        Ok(app_state.create_resource(Box::new(std::io::empty())))
+        Ok(app_state.create_resource(Box::new(self.guest_stderr().clone())))
     }
 }
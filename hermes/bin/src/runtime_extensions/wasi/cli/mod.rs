@@ -1,6 +1,11 @@
 //! CLI runtime extension implementation.
 
 mod host;
+mod output;
+mod state;
+
+pub(crate) use output::{GuestOutput, Stream};
+pub(crate) use state::set_environment;
 
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
@@ -0,0 +1,204 @@
+//! Output a module writes to its standard streams, routed to the logs.
+//!
+//! Every complete line is logged on its own, tagged with the app, the module and the
+//! stream, so the output of modules running concurrently is not interleaved. The level
+//! of a line is guessed from its content, e.g. a line mentioning an error is logged as
+//! an error, and otherwise defaults to the level of the stream.
+
+use std::{
+    io::{Seek, SeekFrom, Write},
+    sync::{Arc, Mutex},
+};
+
+use crate::{app::ApplicationName, logger::LogLevel, wasm::module::ModuleId};
+
+/// Number of the last bytes written which are kept, and the longest line logged.
+const CAPACITY: usize = 4096;
+
+/// Standard stream of a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+    /// Standard output.
+    Stdout,
+    /// Standard error.
+    Stderr,
+}
+
+impl Stream {
+    /// Name of the stream.
+    fn name(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Output written by a module to one of its standard streams.
+///
+/// The last bytes written are kept, e.g. for the message of a panic of the guest written
+/// to its stderr.
+#[derive(Clone, Debug)]
+pub(crate) struct GuestOutput(Arc<Mutex<OutputState>>);
+
+/// State of a `GuestOutput`.
+#[derive(Debug)]
+struct OutputState {
+    /// Stream written to.
+    stream: Stream,
+    /// App of the module.
+    app_name: ApplicationName,
+    /// Module writing to the stream.
+    module_id: ModuleId,
+    /// Line being written, not logged yet.
+    line: Vec<u8>,
+    /// Last bytes written.
+    tail: Vec<u8>,
+}
+
+impl GuestOutput {
+    /// Create the output of a module to one of its standard streams.
+    pub(crate) fn new(stream: Stream, app_name: ApplicationName, module_id: ModuleId) -> Self {
+        Self(Arc::new(Mutex::new(OutputState {
+            stream,
+            app_name,
+            module_id,
+            line: Vec::new(),
+            tail: Vec::new(),
+        })))
+    }
+
+    /// Forget the output written so far.
+    pub(crate) fn clear(&self) {
+        if let Ok(mut state) = self.0.lock() {
+            state.tail.clear();
+        }
+    }
+
+    /// Last output written, trimmed.
+    pub(crate) fn text(&self) -> String {
+        self.0
+            .lock()
+            .map(|state| String::from_utf8_lossy(&state.tail).trim().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Log the line being written, even if it is not complete.
+    pub(crate) fn flush_line(&self) {
+        if let Ok(mut state) = self.0.lock() {
+            let line = std::mem::take(&mut state.line);
+            state.log(&line);
+        }
+    }
+}
+
+impl OutputState {
+    /// Log a line written to the stream.
+    fn log(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        tracing::info!(
+            level = line_level(self.stream, line).to_string(),
+            app_name = %self.app_name,
+            module_id = %self.module_id,
+            stream = self.stream.name(),
+            message = line,
+        );
+    }
+}
+
+impl Write for GuestOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut state = self
+            .0
+            .lock()
+            .map_err(|_| std::io::Error::other("Guest output lock poisoned"))?;
+
+        state.tail.extend_from_slice(buf);
+        let overflow = state.tail.len().saturating_sub(CAPACITY);
+        state.tail.drain(..overflow);
+
+        state.line.extend_from_slice(buf);
+        while let Some(end) = state.line.iter().position(|b| *b == b'\n') {
+            let line: Vec<_> = state.line.drain(..=end).collect();
+            state.log(&line);
+        }
+        if state.line.len() >= CAPACITY {
+            let line = std::mem::take(&mut state.line);
+            state.log(&line);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for GuestOutput {
+    fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Guess the level of a line written to a stream from its content.
+fn line_level(stream: Stream, line: &str) -> LogLevel {
+    let line = line.to_ascii_lowercase();
+    if line.contains("error") || line.contains("panicked") {
+        LogLevel::Error
+    } else if line.contains("warn") {
+        LogLevel::Warn
+    } else if line.contains("debug") {
+        LogLevel::Debug
+    } else if line.contains("trace") {
+        LogLevel::Trace
+    } else if stream == Stream::Stderr {
+        LogLevel::Warn
+    } else {
+        LogLevel::Info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_ulid::Ulid;
+
+    use super::*;
+
+    #[test]
+    fn guest_output_test() {
+        let mut output = GuestOutput::new(
+            Stream::Stderr,
+            ApplicationName("app".to_string()),
+            ModuleId(Ulid::generate()),
+        );
+        output.write_all(b"panicked at src/lib.rs:1:1:\n").unwrap();
+        output.write_all(b"boom").unwrap();
+        assert_eq!(output.text(), "panicked at src/lib.rs:1:1:\nboom");
+        assert_eq!(output.0.lock().unwrap().line, b"boom");
+        output.flush_line();
+        assert!(output.0.lock().unwrap().line.is_empty());
+
+        output.write_all(&[b'x'; CAPACITY]).unwrap();
+        assert_eq!(output.text().len(), CAPACITY);
+        assert!(output.0.lock().unwrap().line.is_empty());
+
+        output.clear();
+        assert_eq!(output.text(), "");
+    }
+
+    #[test]
+    fn line_level_test() {
+        assert_eq!(
+            line_level(Stream::Stdout, "ERROR: db locked").to_string(),
+            "Error"
+        );
+        assert_eq!(line_level(Stream::Stdout, "warning").to_string(), "Warn");
+        assert_eq!(line_level(Stream::Stdout, "[debug] x").to_string(), "Debug");
+        assert_eq!(line_level(Stream::Stdout, "hello").to_string(), "Info");
+        assert_eq!(line_level(Stream::Stderr, "hello").to_string(), "Warn");
+    }
+}
@@ -0,0 +1,27 @@
+//! CLI runtime extension state.
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::{app::ApplicationName, wasm::module::ModuleId};
+
+/// Environment variables of each module, declared in their package manifest.
+static ENVIRONMENT: Lazy<DashMap<(ApplicationName, ModuleId), Vec<(String, String)>>> =
+    Lazy::new(DashMap::new);
+
+/// Set the environment variables of a module.
+pub(crate) fn set_environment(
+    app_name: ApplicationName, module_id: ModuleId, vars: Vec<(String, String)>,
+) {
+    ENVIRONMENT.insert((app_name, module_id), vars);
+}
+
+/// Get the environment variables of a module.
+pub(super) fn get_environment(
+    app_name: &ApplicationName, module_id: &ModuleId,
+) -> Vec<(String, String)> {
+    ENVIRONMENT
+        .get(&(app_name.clone(), module_id.clone()))
+        .map(|vars| vars.clone())
+        .unwrap_or_default()
+}
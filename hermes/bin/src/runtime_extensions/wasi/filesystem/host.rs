@@ -2,7 +2,7 @@
 
 use std::io::{Seek, SeekFrom};
 
-use super::state::{get_state, Descriptor};
+use super::state::{get_preopens, get_state, Descriptor};
 use crate::{
     hdf5::Path,
     runtime_context::HermesRuntimeContext,
@@ -20,6 +20,7 @@ use crate::{
         },
         wasi::io::streams::{get_input_streams_state, get_output_streams_state},
     },
+    vfs::PermissionLevel,
 };
 
 impl filesystem::types::HostDescriptor for HermesRuntimeContext {
@@ -39,8 +40,8 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
             return Ok(Err(ErrorCode::BadDescriptor));
         };
         let mut file = match &*descriptor {
-            Descriptor::File(f) => f.clone(),
-            Descriptor::Dir(_) => return Ok(Err(ErrorCode::IsDirectory)),
+            Descriptor::File(f, _) => f.clone(),
+            Descriptor::Dir(..) => return Ok(Err(ErrorCode::IsDirectory)),
         };
         file.seek(SeekFrom::Start(offset))?;
 
@@ -61,9 +62,12 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
         let Ok(descriptor) = fs_app_state.get_object(&res) else {
             return Ok(Err(ErrorCode::BadDescriptor));
         };
+        if !descriptor.is_writable() {
+            return Ok(Err(ErrorCode::ReadOnly));
+        }
         let mut file = match &*descriptor {
-            Descriptor::File(f) => f.clone(),
-            Descriptor::Dir(_) => return Ok(Err(ErrorCode::IsDirectory)),
+            Descriptor::File(f, _) => f.clone(),
+            Descriptor::Dir(..) => return Ok(Err(ErrorCode::IsDirectory)),
         };
         file.seek(SeekFrom::Start(offset))?;
 
@@ -84,9 +88,12 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
         let Ok(descriptor) = fs_app_state.get_object(&res) else {
             return Ok(Err(ErrorCode::BadDescriptor));
         };
+        if !descriptor.is_writable() {
+            return Ok(Err(ErrorCode::ReadOnly));
+        }
         let mut file = match &*descriptor {
-            Descriptor::File(f) => f.clone(),
-            Descriptor::Dir(_) => return Ok(Err(ErrorCode::IsDirectory)),
+            Descriptor::File(f, _) => f.clone(),
+            Descriptor::Dir(..) => return Ok(Err(ErrorCode::IsDirectory)),
         };
         file.seek(SeekFrom::End(0))?;
 
@@ -147,8 +154,8 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
         };
 
         let dt = match &*descriptor {
-            Descriptor::File(_) => DescriptorType::RegularFile,
-            Descriptor::Dir(_) => DescriptorType::Directory,
+            Descriptor::File(..) => DescriptorType::RegularFile,
+            Descriptor::Dir(..) => DescriptorType::Directory,
         };
 
         Ok(Ok(dt))
@@ -265,8 +272,8 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
         };
 
         let f = match &*descriptor {
-            Descriptor::File(f) => f,
-            Descriptor::Dir(_) => todo!(),
+            Descriptor::File(f, _) => f,
+            Descriptor::Dir(..) => todo!(),
         };
 
         let Ok(size) = f
@@ -345,17 +352,25 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
     /// Note: This is similar to `openat` in POSIX.
     fn open_at(
         &mut self, res: wasmtime::component::Resource<WasiDescriptor>, _path_flags: PathFlags,
-        path: String, open_flags: OpenFlags, _flags: DescriptorFlags,
+        path: String, open_flags: OpenFlags, flags: DescriptorFlags,
     ) -> wasmtime::Result<Result<wasmtime::component::Resource<WasiDescriptor>, ErrorCode>> {
         let mut app_state = get_state().get_app_state(self.app_name())?;
         let Ok(descriptor) = app_state.get_object(&res) else {
             return Ok(Err(ErrorCode::BadDescriptor));
         };
-        let dir = match &*descriptor {
-            Descriptor::Dir(dir) => dir,
-            Descriptor::File(_) => return Ok(Err(ErrorCode::NotDirectory)),
+        let (dir, permission) = match &*descriptor {
+            Descriptor::Dir(dir, permission) => (dir, *permission),
+            Descriptor::File(..) => return Ok(Err(ErrorCode::NotDirectory)),
         };
 
+        let mutates = open_flags.contains(OpenFlags::CREATE)
+            || open_flags.contains(OpenFlags::TRUNCATE)
+            || flags.contains(DescriptorFlags::WRITE)
+            || flags.contains(DescriptorFlags::MUTATE_DIRECTORY);
+        if mutates && permission == PermissionLevel::Read {
+            return Ok(Err(ErrorCode::ReadOnly));
+        }
+
         let create = open_flags.contains(OpenFlags::CREATE);
         let exclusive = open_flags.contains(OpenFlags::EXCLUSIVE);
         let f = match dir.get_file(Path::from_str(&path)) {
@@ -392,7 +407,9 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
             f
         };
         drop(descriptor);
-        Ok(Ok(app_state.create_resource(Descriptor::File(f))))
+        Ok(Ok(
+            app_state.create_resource(Descriptor::File(f, permission))
+        ))
     }
 
     /// Read the contents of a symbolic link.
@@ -455,7 +472,8 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
         };
 
         match &*descriptor {
-            Descriptor::Dir(dir) => {
+            Descriptor::Dir(_, PermissionLevel::Read) => Ok(Err(ErrorCode::ReadOnly)),
+            Descriptor::Dir(dir, PermissionLevel::ReadAndWrite) => {
                 let path: Path = path.into();
 
                 if dir.get_file(path.clone()).is_err() {
@@ -468,7 +486,7 @@ impl filesystem::types::HostDescriptor for HermesRuntimeContext {
                     Ok(Ok(()))
                 }
             },
-            Descriptor::File(_) => Ok(Err(ErrorCode::NotDirectory)),
+            Descriptor::File(..) => Ok(Err(ErrorCode::NotDirectory)),
         }
     }
 
@@ -568,7 +586,19 @@ impl filesystem::preopens::Host for HermesRuntimeContext {
     ) -> wasmtime::Result<Vec<(wasmtime::component::Resource<WasiDescriptor>, String)>> {
         let vfs_root = self.vfs().root().clone();
         let app_state = get_state().get_app_state(self.app_name())?;
-        let res = app_state.create_resource(Descriptor::Dir(vfs_root));
-        Ok(vec![(res, "/".to_string())])
+
+        let Some(preopens) = get_preopens(self.app_name(), self.module_id()) else {
+            let res =
+                app_state.create_resource(Descriptor::Dir(vfs_root, PermissionLevel::ReadAndWrite));
+            return Ok(vec![(res, "/".to_string())]);
+        };
+
+        let mut dirs = Vec::with_capacity(preopens.len());
+        for (path, permission) in preopens {
+            let dir = vfs_root.get_dir(&Path::from_str(&path))?;
+            let res = app_state.create_resource(Descriptor::Dir(dir, permission));
+            dirs.push((res, path));
+        }
+        Ok(dirs)
     }
 }
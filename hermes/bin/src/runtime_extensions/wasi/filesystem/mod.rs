@@ -3,6 +3,8 @@
 mod host;
 mod state;
 
+pub(crate) use state::set_preopens;
+
 /// Advise Runtime Extensions of a new context
 pub(crate) fn new_context(ctx: &crate::runtime_context::HermesRuntimeContext) {
     state::get_state().add_app(ctx.app_name().clone());
@@ -1,20 +1,35 @@
 //! Filesystem state.
 
+use dashmap::DashMap;
 use once_cell::sync::Lazy;
 
-use crate::runtime_extensions::{
-    bindings::wasi::filesystem, resource_manager::ApplicationResourceStorage,
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::{
+        bindings::wasi::filesystem, resource_manager::ApplicationResourceStorage,
+    },
+    vfs::PermissionLevel,
+    wasm::module::ModuleId,
 };
 /// Map of app name to descriptors.
 pub(crate) type Descriptors = ApplicationResourceStorage<filesystem::types::Descriptor, Descriptor>;
 
-/// Represents an open file or directory.
+/// Represents an open file or directory, with the permission level of the module on it.
 #[derive(Clone, Debug)]
 pub(crate) enum Descriptor {
     /// File descriptor.
-    File(crate::hdf5::File),
+    File(crate::hdf5::File, PermissionLevel),
     /// Directory descriptor.
-    Dir(crate::hdf5::Dir),
+    Dir(crate::hdf5::Dir, PermissionLevel),
+}
+
+impl Descriptor {
+    /// Whether the module can modify the file or the directory content.
+    pub(crate) fn is_writable(&self) -> bool {
+        match self {
+            Self::File(_, permission) | Self::Dir(_, permission) => (*permission).into(),
+        }
+    }
 }
 
 /// Global state to hold the descriptors resources.
@@ -24,3 +39,24 @@ static DESCRIPTORS_STATE: Lazy<Descriptors> = Lazy::new(Descriptors::new);
 pub(super) fn get_state() -> &'static Descriptors {
     &DESCRIPTORS_STATE
 }
+
+/// Preopened VFS directories of each module and their permission level.
+/// Modules without declared preopens see the whole application VFS.
+static PREOPENS: Lazy<DashMap<(ApplicationName, ModuleId), Vec<(String, PermissionLevel)>>> =
+    Lazy::new(DashMap::new);
+
+/// Set the preopened directories of a module.
+pub(crate) fn set_preopens(
+    app_name: ApplicationName, module_id: ModuleId, preopens: Vec<(String, PermissionLevel)>,
+) {
+    PREOPENS.insert((app_name, module_id), preopens);
+}
+
+/// Get the preopened directories of a module, `None` if it sees the whole VFS.
+pub(super) fn get_preopens(
+    app_name: &ApplicationName, module_id: &ModuleId,
+) -> Option<Vec<(String, PermissionLevel)>> {
+    PREOPENS
+        .get(&(app_name.clone(), module_id.clone()))
+        .map(|preopens| preopens.clone())
+}
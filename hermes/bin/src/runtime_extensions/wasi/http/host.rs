@@ -1,21 +1,32 @@
 //! HTTP host implementation for WASM runtime.
 
+use std::io::Cursor;
+
+use super::state::{
+    get_fields_state, get_future_trailers_state, get_incoming_bodies_state,
+    get_incoming_requests_state, get_outgoing_bodies_state, get_outgoing_responses_state,
+    get_response_outparams_state, FieldsState, OutgoingBodyState, OutgoingResponseState,
+    SentResponse, SharedBody,
+};
 use crate::{
     runtime_context::HermesRuntimeContext,
-    runtime_extensions::bindings::wasi::{
-        http::{
-            self,
-            outgoing_handler::{
-                ErrorCode, FutureIncomingResponse, OutgoingRequest, RequestOptions,
-            },
-            types::{
-                Duration, FieldKey, FieldValue, Fields, FutureTrailers, HeaderError, Headers,
-                HostIncomingResponse, HostOutgoingResponse, IncomingBody, IncomingRequest,
-                IncomingResponse, IoError, Method, OutgoingBody, OutgoingResponse,
-                ResponseOutparam, Scheme, StatusCode, Trailers,
+    runtime_extensions::{
+        bindings::wasi::{
+            http::{
+                self,
+                outgoing_handler::{
+                    ErrorCode, FutureIncomingResponse, OutgoingRequest, RequestOptions,
+                },
+                types::{
+                    Duration, FieldKey, FieldValue, Fields, FutureTrailers, HeaderError, Headers,
+                    HostIncomingResponse, HostOutgoingResponse, IncomingBody, IncomingRequest,
+                    IncomingResponse, IoError, Method, OutgoingBody, OutgoingResponse,
+                    ResponseOutparam, Scheme, StatusCode, Trailers,
+                },
             },
+            io::streams::{InputStream, OutputStream},
         },
-        io::streams::{InputStream, OutputStream},
+        wasi::io::streams::{get_input_streams_state, get_output_streams_state},
     },
 };
 
@@ -54,7 +65,8 @@ impl http::types::HostFields for HermesRuntimeContext {
     ///
     /// The resulting `fields` is mutable.
     fn new(&mut self) -> wasmtime::Result<wasmtime::component::Resource<Fields>> {
-        todo!()
+        let app_state = get_fields_state().get_app_state(self.app_name())?;
+        Ok(app_state.create_resource(FieldsState::default()))
     }
 
     /// Construct an HTTP Fields.
@@ -73,9 +85,16 @@ impl http::types::HostFields for HermesRuntimeContext {
     /// An error result will be returned if any header or value was
     /// syntactically invalid, or if a header was forbidden.
     fn from_list(
-        &mut self, _entries: Vec<(FieldKey, FieldValue)>,
+        &mut self, entries: Vec<(FieldKey, FieldValue)>,
     ) -> wasmtime::Result<Result<wasmtime::component::Resource<Fields>, HeaderError>> {
-        todo!()
+        if entries.iter().any(|(key, _)| !is_valid_key(key)) {
+            return Ok(Err(HeaderError::InvalidSyntax));
+        }
+        let app_state = get_fields_state().get_app_state(self.app_name())?;
+        Ok(Ok(app_state.create_resource(FieldsState {
+            entries,
+            immutable: false,
+        })))
     }
 
     /// Get all of the values corresponding to a key. If the key is not present
@@ -83,17 +102,29 @@ impl http::types::HostFields for HermesRuntimeContext {
     /// present but empty, this is represented by a list with one or more
     /// empty field-values present.
     fn get(
-        &mut self, _fields: wasmtime::component::Resource<Fields>, _name: FieldKey,
+        &mut self, fields: wasmtime::component::Resource<Fields>, name: FieldKey,
     ) -> wasmtime::Result<Vec<FieldValue>> {
-        todo!()
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let fields = app_state.get_object(&fields)?;
+        Ok(fields
+            .entries
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(&name))
+            .map(|(_, value)| value.clone())
+            .collect())
     }
 
     /// Returns `true` when the key is present in this `fields`. If the key is
     /// syntactically invalid, `false` is returned.
     fn has(
-        &mut self, _fields: wasmtime::component::Resource<Fields>, _name: FieldKey,
+        &mut self, fields: wasmtime::component::Resource<Fields>, name: FieldKey,
     ) -> wasmtime::Result<bool> {
-        todo!()
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let fields = app_state.get_object(&fields)?;
+        Ok(fields
+            .entries
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case(&name)))
     }
 
     /// Set all of the values for a key. Clears any existing values for that
@@ -101,10 +132,24 @@ impl http::types::HostFields for HermesRuntimeContext {
     ///
     /// Fails with `header-error.immutable` if the `fields` are immutable.
     fn set(
-        &mut self, _fields: wasmtime::component::Resource<Fields>, _name: FieldKey,
-        _value: Vec<FieldValue>,
+        &mut self, fields: wasmtime::component::Resource<Fields>, name: FieldKey,
+        value: Vec<FieldValue>,
     ) -> wasmtime::Result<Result<(), HeaderError>> {
-        todo!()
+        if !is_valid_key(&name) {
+            return Ok(Err(HeaderError::InvalidSyntax));
+        }
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let mut fields = app_state.get_object(&fields)?;
+        if fields.immutable {
+            return Ok(Err(HeaderError::Immutable));
+        }
+        fields
+            .entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(&name));
+        fields
+            .entries
+            .extend(value.into_iter().map(|value| (name.clone(), value)));
+        Ok(Ok(()))
     }
 
     /// Delete all values for a key. Does nothing if no values for the key
@@ -112,9 +157,17 @@ impl http::types::HostFields for HermesRuntimeContext {
     ///
     /// Fails with `header-error.immutable` if the `fields` are immutable.
     fn delete(
-        &mut self, _fields: wasmtime::component::Resource<Fields>, _name: FieldKey,
+        &mut self, fields: wasmtime::component::Resource<Fields>, name: FieldKey,
     ) -> wasmtime::Result<Result<(), HeaderError>> {
-        todo!()
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let mut fields = app_state.get_object(&fields)?;
+        if fields.immutable {
+            return Ok(Err(HeaderError::Immutable));
+        }
+        fields
+            .entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(&name));
+        Ok(Ok(()))
     }
 
     /// Append a value for a key. Does not change or delete any existing
@@ -122,10 +175,18 @@ impl http::types::HostFields for HermesRuntimeContext {
     ///
     /// Fails with `header-error.immutable` if the `fields` are immutable.
     fn append(
-        &mut self, _fields: wasmtime::component::Resource<Fields>, _name: FieldKey,
-        _value: FieldValue,
+        &mut self, fields: wasmtime::component::Resource<Fields>, name: FieldKey, value: FieldValue,
     ) -> wasmtime::Result<Result<(), HeaderError>> {
-        todo!()
+        if !is_valid_key(&name) {
+            return Ok(Err(HeaderError::InvalidSyntax));
+        }
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let mut fields = app_state.get_object(&fields)?;
+        if fields.immutable {
+            return Ok(Err(HeaderError::Immutable));
+        }
+        fields.entries.push((name, value));
+        Ok(Ok(()))
     }
 
     /// Retrieve the full set of keys and values in the Fields. Like the
@@ -135,22 +196,31 @@ impl http::types::HostFields for HermesRuntimeContext {
     /// which have multiple values are represented by multiple entries in this
     /// list with the same key.
     fn entries(
-        &mut self, _fields: wasmtime::component::Resource<Fields>,
+        &mut self, fields: wasmtime::component::Resource<Fields>,
     ) -> wasmtime::Result<Vec<(FieldKey, FieldValue)>> {
-        todo!()
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let fields = app_state.get_object(&fields)?;
+        Ok(fields.entries.clone())
     }
 
     /// Make a deep copy of the Fields. Equivalent in behavior to calling the
     /// `fields` constructor on the return value of `entries`. The resulting
     /// `fields` is mutable.
     fn clone(
-        &mut self, _fields: wasmtime::component::Resource<Fields>,
+        &mut self, fields: wasmtime::component::Resource<Fields>,
     ) -> wasmtime::Result<wasmtime::component::Resource<Fields>> {
-        todo!()
+        let mut app_state = get_fields_state().get_app_state(self.app_name())?;
+        let entries = app_state.get_object(&fields)?.entries.clone();
+        Ok(app_state.create_resource(FieldsState {
+            entries,
+            immutable: false,
+        }))
     }
 
-    fn drop(&mut self, _rep: wasmtime::component::Resource<Fields>) -> wasmtime::Result<()> {
-        todo!()
+    fn drop(&mut self, rep: wasmtime::component::Resource<Fields>) -> wasmtime::Result<()> {
+        let app_state = get_fields_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -175,17 +245,24 @@ impl http::types::HostFutureTrailers for HermesRuntimeContext {
     /// `delete` methods will return an error, and the resource must be
     /// dropped before the parent `future-trailers` is dropped.
     fn get(
-        &mut self, _rep: wasmtime::component::Resource<FutureTrailers>,
+        &mut self, rep: wasmtime::component::Resource<FutureTrailers>,
     ) -> wasmtime::Result<
         Option<Result<Result<Option<wasmtime::component::Resource<Trailers>>, ErrorCode>, ()>>,
     > {
-        todo!()
+        let mut app_state = get_future_trailers_state().get_app_state(self.app_name())?;
+        let mut taken = app_state.get_object(&rep)?;
+        if *taken {
+            return Ok(Some(Err(())));
+        }
+        // Incoming bodies are received whole, without trailers.
+        *taken = true;
+        Ok(Some(Ok(Ok(None))))
     }
 
-    fn drop(
-        &mut self, _rep: wasmtime::component::Resource<FutureTrailers>,
-    ) -> wasmtime::Result<()> {
-        todo!()
+    fn drop(&mut self, rep: wasmtime::component::Resource<FutureTrailers>) -> wasmtime::Result<()> {
+        let app_state = get_future_trailers_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -200,9 +277,20 @@ impl http::types::HostOutgoingBody for HermesRuntimeContext {
     /// this `outgoing-body` may be retrieved at most once. Subsequent calls
     /// will return error.
     fn write(
-        &mut self, _rep: wasmtime::component::Resource<OutgoingBody>,
+        &mut self, rep: wasmtime::component::Resource<OutgoingBody>,
     ) -> wasmtime::Result<Result<wasmtime::component::Resource<OutputStream>, ()>> {
-        todo!()
+        let mut app_state = get_outgoing_bodies_state().get_app_state(self.app_name())?;
+        let mut outgoing_body = app_state.get_object(&rep)?;
+        if outgoing_body.stream_taken {
+            return Ok(Err(()));
+        }
+        outgoing_body.stream_taken = true;
+        let body = outgoing_body.body.clone();
+        drop(outgoing_body);
+        drop(app_state);
+
+        let output_streams_app_state = get_output_streams_state().get_app_state(self.app_name())?;
+        Ok(Ok(output_streams_app_state.create_resource(Box::new(body))))
     }
 
     /// Finalize an outgoing body, optionally providing trailers. This must be
@@ -215,14 +303,24 @@ impl http::types::HostOutgoingBody for HermesRuntimeContext {
     /// to the body (via `write`) does not match the value given in the
     /// Content-Length.
     fn finish(
-        &mut self, _this: wasmtime::component::Resource<OutgoingBody>,
-        _trailers: Option<wasmtime::component::Resource<Trailers>>,
+        &mut self, this: wasmtime::component::Resource<OutgoingBody>,
+        trailers: Option<wasmtime::component::Resource<Trailers>>,
     ) -> wasmtime::Result<Result<(), ErrorCode>> {
-        todo!()
-    }
-
-    fn drop(&mut self, _rep: wasmtime::component::Resource<OutgoingBody>) -> wasmtime::Result<()> {
-        todo!()
+        // Trailers are not sent, the body is complete once written.
+        if let Some(trailers) = trailers {
+            get_fields_state()
+                .get_app_state(self.app_name())?
+                .delete_resource(trailers)?;
+        }
+        let app_state = get_outgoing_bodies_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(this)?;
+        Ok(Ok(()))
+    }
+
+    fn drop(&mut self, rep: wasmtime::component::Resource<OutgoingBody>) -> wasmtime::Result<()> {
+        let app_state = get_outgoing_bodies_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -233,24 +331,40 @@ impl HostOutgoingResponse for HermesRuntimeContext {
     ///
     /// * `headers` is the HTTP Headers for the Response.
     fn new(
-        &mut self, _headers: wasmtime::component::Resource<Headers>,
+        &mut self, headers: wasmtime::component::Resource<Headers>,
     ) -> wasmtime::Result<wasmtime::component::Resource<OutgoingResponse>> {
-        todo!()
+        let headers = get_fields_state()
+            .get_app_state(self.app_name())?
+            .delete_resource(headers)?
+            .entries;
+        let app_state = get_outgoing_responses_state().get_app_state(self.app_name())?;
+        Ok(app_state.create_resource(OutgoingResponseState {
+            status_code: 200,
+            headers,
+            body: SharedBody::default(),
+            body_taken: false,
+        }))
     }
 
     /// Get the HTTP Status Code for the Response.
     fn status_code(
-        &mut self, _rep: wasmtime::component::Resource<OutgoingResponse>,
+        &mut self, rep: wasmtime::component::Resource<OutgoingResponse>,
     ) -> wasmtime::Result<StatusCode> {
-        todo!()
+        let mut app_state = get_outgoing_responses_state().get_app_state(self.app_name())?;
+        Ok(app_state.get_object(&rep)?.status_code)
     }
 
     /// Set the HTTP Status Code for the Response. Fails if the status-code
     /// given is not a valid http status code.
     fn set_status_code(
-        &mut self, _rep: wasmtime::component::Resource<OutgoingResponse>, _status_code: StatusCode,
+        &mut self, rep: wasmtime::component::Resource<OutgoingResponse>, status_code: StatusCode,
     ) -> wasmtime::Result<Result<(), ()>> {
-        todo!()
+        if !(100..=999).contains(&status_code) {
+            return Ok(Err(()));
+        }
+        let mut app_state = get_outgoing_responses_state().get_app_state(self.app_name())?;
+        app_state.get_object(&rep)?.status_code = status_code;
+        Ok(Ok(()))
     }
 
     /// Get the headers associated with the Request.
@@ -262,9 +376,17 @@ impl HostOutgoingResponse for HermesRuntimeContext {
     /// `outgoing-request` is dropped, or its ownership is transferred to
     /// another component by e.g. `outgoing-handler.handle`.
     fn headers(
-        &mut self, _rep: wasmtime::component::Resource<OutgoingResponse>,
+        &mut self, rep: wasmtime::component::Resource<OutgoingResponse>,
     ) -> wasmtime::Result<wasmtime::component::Resource<Headers>> {
-        todo!()
+        let mut app_state = get_outgoing_responses_state().get_app_state(self.app_name())?;
+        let entries = app_state.get_object(&rep)?.headers.clone();
+        drop(app_state);
+
+        let fields_app_state = get_fields_state().get_app_state(self.app_name())?;
+        Ok(fields_app_state.create_resource(FieldsState {
+            entries,
+            immutable: true,
+        }))
     }
 
     /// Returns the resource corresponding to the outgoing Body for this Response.
@@ -273,15 +395,31 @@ impl HostOutgoingResponse for HermesRuntimeContext {
     /// this `outgoing-response` can be retrieved at most once. Subsequent
     /// calls will return error.
     fn body(
-        &mut self, _rep: wasmtime::component::Resource<OutgoingResponse>,
+        &mut self, rep: wasmtime::component::Resource<OutgoingResponse>,
     ) -> wasmtime::Result<Result<wasmtime::component::Resource<OutgoingBody>, ()>> {
-        todo!()
+        let mut app_state = get_outgoing_responses_state().get_app_state(self.app_name())?;
+        let mut response = app_state.get_object(&rep)?;
+        if response.body_taken {
+            return Ok(Err(()));
+        }
+        response.body_taken = true;
+        let body = response.body.clone();
+        drop(response);
+        drop(app_state);
+
+        let bodies_app_state = get_outgoing_bodies_state().get_app_state(self.app_name())?;
+        Ok(Ok(bodies_app_state.create_resource(OutgoingBodyState {
+            body,
+            stream_taken: false,
+        })))
     }
 
     fn drop(
-        &mut self, _rep: wasmtime::component::Resource<OutgoingResponse>,
+        &mut self, rep: wasmtime::component::Resource<OutgoingResponse>,
     ) -> wasmtime::Result<()> {
-        todo!()
+        let app_state = get_outgoing_responses_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -302,21 +440,36 @@ impl http::types::HostIncomingBody for HermesRuntimeContext {
     /// and for that backpressure to not inhibit delivery of the trailers if
     /// the user does not read the entire body.
     fn stream(
-        &mut self, _rep: wasmtime::component::Resource<IncomingBody>,
+        &mut self, rep: wasmtime::component::Resource<IncomingBody>,
     ) -> wasmtime::Result<Result<wasmtime::component::Resource<InputStream>, ()>> {
-        todo!()
+        let mut app_state = get_incoming_bodies_state().get_app_state(self.app_name())?;
+        let Some(body) = app_state.get_object(&rep)?.take() else {
+            return Ok(Err(()));
+        };
+        drop(app_state);
+
+        let input_streams_app_state = get_input_streams_state().get_app_state(self.app_name())?;
+        Ok(Ok(
+            input_streams_app_state.create_resource(Box::new(Cursor::new(body)))
+        ))
     }
 
     /// Takes ownership of `incoming-body`, and returns a `future-trailers`.
     /// This function will trap if the `input-stream` child is still alive.
     fn finish(
-        &mut self, _this: wasmtime::component::Resource<IncomingBody>,
+        &mut self, this: wasmtime::component::Resource<IncomingBody>,
     ) -> wasmtime::Result<wasmtime::component::Resource<FutureTrailers>> {
-        todo!()
+        get_incoming_bodies_state()
+            .get_app_state(self.app_name())?
+            .delete_resource(this)?;
+        let app_state = get_future_trailers_state().get_app_state(self.app_name())?;
+        Ok(app_state.create_resource(false))
     }
 
-    fn drop(&mut self, _rep: wasmtime::component::Resource<IncomingBody>) -> wasmtime::Result<()> {
-        todo!()
+    fn drop(&mut self, rep: wasmtime::component::Resource<IncomingBody>) -> wasmtime::Result<()> {
+        let app_state = get_incoming_bodies_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -367,16 +520,44 @@ impl http::types::HostResponseOutparam for HermesRuntimeContext {
     /// The user may provide an `error` to `response` to allow the
     /// implementation determine how to respond with an HTTP error response.
     fn set(
-        &mut self, _param: wasmtime::component::Resource<ResponseOutparam>,
-        _response: Result<wasmtime::component::Resource<OutgoingResponse>, ErrorCode>,
+        &mut self, param: wasmtime::component::Resource<ResponseOutparam>,
+        response: Result<wasmtime::component::Resource<OutgoingResponse>, ErrorCode>,
     ) -> wasmtime::Result<()> {
-        todo!()
+        let slot = get_response_outparams_state()
+            .get_app_state(self.app_name())?
+            .delete_resource(param)?;
+        let sent = match response {
+            Ok(response) => {
+                let response = get_outgoing_responses_state()
+                    .get_app_state(self.app_name())?
+                    .delete_resource(response)?;
+                SentResponse {
+                    status_code: response.status_code,
+                    headers: response.headers,
+                    body: response.body,
+                }
+            },
+            Err(err) => {
+                tracing::warn!(app_name = %self.app_name(), "Module failed to handle the request: {err:?}");
+                SentResponse {
+                    status_code: 500,
+                    headers: Vec::new(),
+                    body: SharedBody::default(),
+                }
+            },
+        };
+        slot.lock()
+            .map_err(|_| wasmtime::Error::msg("Response slot lock poisoned"))?
+            .replace(sent);
+        Ok(())
     }
 
     fn drop(
-        &mut self, _rep: wasmtime::component::Resource<ResponseOutparam>,
+        &mut self, rep: wasmtime::component::Resource<ResponseOutparam>,
     ) -> wasmtime::Result<()> {
-        todo!()
+        let app_state = get_response_outparams_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -562,30 +743,34 @@ impl http::types::HostOutgoingRequest for HermesRuntimeContext {
 impl http::types::HostIncomingRequest for HermesRuntimeContext {
     /// Returns the method of the incoming request.
     fn method(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<Method> {
-        todo!()
+        let mut app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        Ok(app_state.get_object(&rep)?.method.clone())
     }
 
     /// Returns the path with query parameters from the request, as a string.
     fn path_with_query(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<Option<String>> {
-        todo!()
+        let mut app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        Ok(app_state.get_object(&rep)?.path_with_query.clone())
     }
 
     /// Returns the protocol scheme from the request.
     fn scheme(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<Option<Scheme>> {
-        todo!()
+        let mut app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        Ok(app_state.get_object(&rep)?.scheme.clone())
     }
 
     /// Returns the authority from the request, if it was present.
     fn authority(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<Option<String>> {
-        todo!()
+        let mut app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        Ok(app_state.get_object(&rep)?.authority.clone())
     }
 
     /// Get the `headers` associated with the request.
@@ -597,23 +782,40 @@ impl http::types::HostIncomingRequest for HermesRuntimeContext {
     /// the parent `incoming-request` is dropped. Dropping this
     /// `incoming-request` before all children are dropped will trap.
     fn headers(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<wasmtime::component::Resource<Headers>> {
-        todo!()
+        let mut app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        let entries = app_state.get_object(&rep)?.headers.clone();
+        drop(app_state);
+
+        let fields_app_state = get_fields_state().get_app_state(self.app_name())?;
+        Ok(fields_app_state.create_resource(FieldsState {
+            entries,
+            immutable: true,
+        }))
     }
 
     /// Gives the `incoming-body` associated with this request. Will only
     /// return success at most once, and subsequent calls will return error.
     fn consume(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<Result<wasmtime::component::Resource<IncomingBody>, ()>> {
-        todo!()
+        let mut app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        let Some(body) = app_state.get_object(&rep)?.body.take() else {
+            return Ok(Err(()));
+        };
+        drop(app_state);
+
+        let bodies_app_state = get_incoming_bodies_state().get_app_state(self.app_name())?;
+        Ok(Ok(bodies_app_state.create_resource(Some(body))))
     }
 
     fn drop(
-        &mut self, _rep: wasmtime::component::Resource<IncomingRequest>,
+        &mut self, rep: wasmtime::component::Resource<IncomingRequest>,
     ) -> wasmtime::Result<()> {
-        todo!()
+        let app_state = get_incoming_requests_state().get_app_state(self.app_name())?;
+        app_state.delete_resource(rep)?;
+        Ok(())
     }
 }
 
@@ -632,7 +834,8 @@ impl http::types::Host for HermesRuntimeContext {
     fn http_error_code(
         &mut self, _err: wasmtime::component::Resource<IoError>,
     ) -> wasmtime::Result<Option<ErrorCode>> {
-        todo!()
+        // Streams of the HTTP bodies do not fail with HTTP related errors.
+        Ok(None)
     }
 }
 
@@ -655,3 +858,11 @@ impl http::outgoing_handler::Host for HermesRuntimeContext {
         todo!()
     }
 }
+
+/// Whether `key` is a syntactically valid field key, i.e. an HTTP token.
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
@@ -1,6 +1,47 @@
 //! HTTP runtime extension implementation.
 
 mod host;
+mod state;
+
+use std::sync::Arc;
+
+pub(crate) use state::{IncomingRequestState, ResponseSlot};
+
+use crate::{
+    app::ApplicationName,
+    runtime_extensions::bindings::wasi::http::types::{IncomingRequest, ResponseOutparam},
+};
 
 /// Advise Runtime Extensions of a new context
-pub(crate) fn new_context(_ctx: &crate::runtime_context::HermesRuntimeContext) {}
+pub(crate) fn new_context(ctx: &crate::runtime_context::HermesRuntimeContext) {
+    state::get_fields_state().add_app(ctx.app_name().clone());
+    state::get_incoming_requests_state().add_app(ctx.app_name().clone());
+    state::get_incoming_bodies_state().add_app(ctx.app_name().clone());
+    state::get_future_trailers_state().add_app(ctx.app_name().clone());
+    state::get_outgoing_responses_state().add_app(ctx.app_name().clone());
+    state::get_outgoing_bodies_state().add_app(ctx.app_name().clone());
+    state::get_response_outparams_state().add_app(ctx.app_name().clone());
+}
+
+/// Create the resources passed to `wasi:http/incoming-handler.handle` for a request to
+/// the app, along with the slot the response the module sends is put into.
+///
+/// # Errors
+/// - The app state is not found, the runtime extension was not advised of a context of
+///   the app.
+pub(crate) fn new_incoming_request(
+    app_name: &ApplicationName, request: IncomingRequestState,
+) -> anyhow::Result<(
+    wasmtime::component::Resource<IncomingRequest>,
+    wasmtime::component::Resource<ResponseOutparam>,
+    ResponseSlot,
+)> {
+    let slot = ResponseSlot::default();
+    let request = state::get_incoming_requests_state()
+        .get_app_state(app_name)?
+        .create_resource(request);
+    let response_out = state::get_response_outparams_state()
+        .get_app_state(app_name)?
+        .create_resource(Arc::clone(&slot));
+    Ok((request, response_out, slot))
+}
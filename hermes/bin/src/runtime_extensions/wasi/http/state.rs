@@ -0,0 +1,199 @@
+//! HTTP state.
+
+use std::{
+    io::{Cursor, Seek, SeekFrom, Write},
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::runtime_extensions::{
+    bindings::wasi::http::types::{
+        FieldKey, FieldValue, Fields, FutureTrailers, IncomingBody, IncomingRequest, Method,
+        OutgoingBody, OutgoingResponse, ResponseOutparam, Scheme,
+    },
+    resource_manager::ApplicationResourceStorage,
+};
+
+/// Entries of a `fields` resource, e.g. headers or trailers.
+#[derive(Clone, Default)]
+pub(crate) struct FieldsState {
+    /// Key-value pairs, a key with multiple values has an entry for each value.
+    pub(crate) entries: Vec<(FieldKey, FieldValue)>,
+    /// Whether the fields are a child of another resource, so they can not be changed.
+    pub(crate) immutable: bool,
+}
+
+/// An incoming HTTP request.
+pub(crate) struct IncomingRequestState {
+    /// Method of the request.
+    pub(crate) method: Method,
+    /// Path with query of the request.
+    pub(crate) path_with_query: Option<String>,
+    /// Scheme of the request.
+    pub(crate) scheme: Option<Scheme>,
+    /// Authority of the request.
+    pub(crate) authority: Option<String>,
+    /// Headers of the request.
+    pub(crate) headers: Vec<(FieldKey, FieldValue)>,
+    /// Body of the request, until it is consumed.
+    pub(crate) body: Option<Vec<u8>>,
+}
+
+/// Body of an incoming HTTP request, until its stream is taken.
+pub(crate) type IncomingBodyState = Option<Vec<u8>>;
+
+/// Whether the trailers of a body were taken.
+pub(crate) type FutureTrailersState = bool;
+
+/// Body of an outgoing HTTP response, written through an `output-stream` and read once
+/// the module is done with it.
+#[derive(Clone, Default)]
+pub(crate) struct SharedBody(Arc<Mutex<Cursor<Vec<u8>>>>);
+
+impl SharedBody {
+    /// Content of the body.
+    pub(crate) fn contents(&self) -> Vec<u8> {
+        self.0
+            .lock()
+            .map(|body| body.get_ref().clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Write for SharedBody {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("Body lock poisoned"))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for SharedBody {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("Body lock poisoned"))?
+            .seek(pos)
+    }
+}
+
+/// An outgoing HTTP response.
+pub(crate) struct OutgoingResponseState {
+    /// Status code of the response.
+    pub(crate) status_code: u16,
+    /// Headers of the response.
+    pub(crate) headers: Vec<(FieldKey, FieldValue)>,
+    /// Body of the response.
+    pub(crate) body: SharedBody,
+    /// Whether the `outgoing-body` of the response was taken.
+    pub(crate) body_taken: bool,
+}
+
+/// Body of an outgoing HTTP response.
+pub(crate) struct OutgoingBodyState {
+    /// Content of the body.
+    pub(crate) body: SharedBody,
+    /// Whether the `output-stream` of the body was taken.
+    pub(crate) stream_taken: bool,
+}
+
+/// Response a module sent through a `response-outparam`.
+pub(crate) struct SentResponse {
+    /// Status code of the response.
+    pub(crate) status_code: u16,
+    /// Headers of the response.
+    pub(crate) headers: Vec<(FieldKey, FieldValue)>,
+    /// Body of the response, which the module may still write after sending it.
+    pub(crate) body: SharedBody,
+}
+
+/// Where the response sent through a `response-outparam` is put, `None` until a module
+/// sends it.
+pub(crate) type ResponseSlot = Arc<Mutex<Option<SentResponse>>>;
+
+/// Map of app name to fields resource holder.
+pub(crate) type FieldsResources = ApplicationResourceStorage<Fields, FieldsState>;
+
+/// Map of app name to incoming request resource holder.
+pub(crate) type IncomingRequests =
+    ApplicationResourceStorage<IncomingRequest, IncomingRequestState>;
+
+/// Map of app name to incoming body resource holder.
+pub(crate) type IncomingBodies = ApplicationResourceStorage<IncomingBody, IncomingBodyState>;
+
+/// Map of app name to future trailers resource holder.
+pub(crate) type FutureTrailersResources =
+    ApplicationResourceStorage<FutureTrailers, FutureTrailersState>;
+
+/// Map of app name to outgoing response resource holder.
+pub(crate) type OutgoingResponses =
+    ApplicationResourceStorage<OutgoingResponse, OutgoingResponseState>;
+
+/// Map of app name to outgoing body resource holder.
+pub(crate) type OutgoingBodies = ApplicationResourceStorage<OutgoingBody, OutgoingBodyState>;
+
+/// Map of app name to response outparam resource holder.
+pub(crate) type ResponseOutparams = ApplicationResourceStorage<ResponseOutparam, ResponseSlot>;
+
+/// Global state to hold the fields resources.
+static FIELDS_STATE: Lazy<FieldsResources> = Lazy::new(FieldsResources::new);
+
+/// Global state to hold the incoming request resources.
+static INCOMING_REQUESTS_STATE: Lazy<IncomingRequests> = Lazy::new(IncomingRequests::new);
+
+/// Global state to hold the incoming body resources.
+static INCOMING_BODIES_STATE: Lazy<IncomingBodies> = Lazy::new(IncomingBodies::new);
+
+/// Global state to hold the future trailers resources.
+static FUTURE_TRAILERS_STATE: Lazy<FutureTrailersResources> =
+    Lazy::new(FutureTrailersResources::new);
+
+/// Global state to hold the outgoing response resources.
+static OUTGOING_RESPONSES_STATE: Lazy<OutgoingResponses> = Lazy::new(OutgoingResponses::new);
+
+/// Global state to hold the outgoing body resources.
+static OUTGOING_BODIES_STATE: Lazy<OutgoingBodies> = Lazy::new(OutgoingBodies::new);
+
+/// Global state to hold the response outparam resources.
+static RESPONSE_OUTPARAMS_STATE: Lazy<ResponseOutparams> = Lazy::new(ResponseOutparams::new);
+
+/// Get the fields state.
+pub(crate) fn get_fields_state() -> &'static FieldsResources {
+    &FIELDS_STATE
+}
+
+/// Get the incoming requests state.
+pub(crate) fn get_incoming_requests_state() -> &'static IncomingRequests {
+    &INCOMING_REQUESTS_STATE
+}
+
+/// Get the incoming bodies state.
+pub(crate) fn get_incoming_bodies_state() -> &'static IncomingBodies {
+    &INCOMING_BODIES_STATE
+}
+
+/// Get the future trailers state.
+pub(crate) fn get_future_trailers_state() -> &'static FutureTrailersResources {
+    &FUTURE_TRAILERS_STATE
+}
+
+/// Get the outgoing responses state.
+pub(crate) fn get_outgoing_responses_state() -> &'static OutgoingResponses {
+    &OUTGOING_RESPONSES_STATE
+}
+
+/// Get the outgoing bodies state.
+pub(crate) fn get_outgoing_bodies_state() -> &'static OutgoingBodies {
+    &OUTGOING_BODIES_STATE
+}
+
+/// Get the response outparams state.
+pub(crate) fn get_response_outparams_state() -> &'static ResponseOutparams {
+    &RESPONSE_OUTPARAMS_STATE
+}
@@ -135,6 +135,14 @@ impl VfsBootstrapper {
         Ok(())
     }
 
+    /// Permission level of a path of the VFS directories structure, e.g. `srv/share`,
+    /// regardless of the content mounted to it.
+    pub(crate) fn structure_permission(path: &str) -> PermissionLevel {
+        let mut permissions = PermissionsState::new();
+        Self::setup_vfs_permissions(&mut permissions);
+        permissions.get_permission(path)
+    }
+
     /// Setup VFS directories permissions.
     fn setup_vfs_permissions(permissions: &mut PermissionsState) {
         permissions.add_permission(Vfs::TMP_DIR, PermissionLevel::ReadAndWrite);
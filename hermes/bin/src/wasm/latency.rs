@@ -0,0 +1,236 @@
+//! Latency budget of the host function calls.
+//!
+//! Every host function call of a module is wrapped in a `wit-bindgen import` span by the
+//! generated bindings. Once budgets are set, the `LatencyLayer` of the logger times
+//! these spans, and logs a warning naming the module, the function, the duration and the
+//! event being executed whenever a call exceeds the budget of its function.
+
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+use once_cell::sync::OnceCell;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    Metadata, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+use crate::{app::ApplicationName, runtime_context::HermesRuntimeContext, wasm::module::ModuleId};
+
+/// Name of the span wrapping a host function call.
+pub(crate) const HOST_CALL_SPAN: &str = "wit-bindgen import";
+
+/// Latency budgets, set by `init`.
+static BUDGETS: OnceCell<Budgets> = OnceCell::new();
+
+thread_local! {
+    /// Event executed by the current thread.
+    static CURRENT_EVENT: RefCell<Option<EventContext>> = const { RefCell::new(None) };
+}
+
+/// Latency budgets of the host functions.
+struct Budgets {
+    /// Budget of the functions without their own budget.
+    default: Option<Duration>,
+    /// Budget by function, named `<function>` or `<interface>.<function>`.
+    functions: HashMap<String, Duration>,
+}
+
+impl Budgets {
+    /// Budget of a function of an interface.
+    fn get(&self, interface: &str, function: &str) -> Option<Duration> {
+        self.functions
+            .get(&format!("{interface}.{function}"))
+            .or_else(|| self.functions.get(function))
+            .copied()
+            .or(self.default)
+    }
+}
+
+/// Event being executed by a module.
+struct EventContext {
+    /// App of the module.
+    app_name: ApplicationName,
+    /// Module executing the event.
+    module_id: ModuleId,
+    /// Name of the event.
+    event_name: String,
+    /// Execution counter of the module, identifying the event.
+    event_id: u32,
+}
+
+/// Host function call, stored in the extensions of its span.
+struct HostCall {
+    /// Interface of the function.
+    interface: String,
+    /// Function called.
+    function: String,
+    /// When the call started.
+    start: std::time::Instant,
+}
+
+/// Set the latency budgets of the host functions, `default` applying to the functions
+/// without their own budget.
+///
+/// ## Errors
+///
+/// Returns an error if the budgets are already set.
+pub(crate) fn init(
+    default: Option<Duration>, functions: HashMap<String, Duration>,
+) -> anyhow::Result<()> {
+    BUDGETS
+        .set(Budgets { default, functions })
+        .map_err(|_| anyhow::anyhow!("Host call latency budgets already set"))
+}
+
+/// Whether the host function calls are timed.
+pub(crate) fn is_enabled() -> bool {
+    BUDGETS.get().is_some()
+}
+
+/// Parse a `<function>=<milliseconds>` budget of a function.
+pub(crate) fn parse_function_budget(s: &str) -> Result<(String, Duration), String> {
+    let (function, millis) = s.split_once('=').ok_or(format!(
+        "Invalid budget `{s}`, expected `<function>=<milliseconds>`"
+    ))?;
+    let millis = millis
+        .parse()
+        .map_err(|err| format!("Invalid budget milliseconds `{millis}`: {err}"))?;
+    Ok((function.to_string(), Duration::from_millis(millis)))
+}
+
+/// Guard of the event executed by the current thread, cleared on drop.
+pub(crate) struct EventGuard;
+
+impl Drop for EventGuard {
+    fn drop(&mut self) {
+        CURRENT_EVENT.with(|event| event.borrow_mut().take());
+    }
+}
+
+/// Mark the event of the context as executed by the current thread, until the guard is
+/// dropped.
+pub(crate) fn enter_event(ctx: &HermesRuntimeContext) -> EventGuard {
+    CURRENT_EVENT.with(|event| {
+        *event.borrow_mut() = Some(EventContext {
+            app_name: ctx.app_name().clone(),
+            module_id: ctx.module_id().clone(),
+            event_name: ctx.event_name().to_string(),
+            event_id: ctx.exc_counter(),
+        });
+    });
+    EventGuard
+}
+
+/// Whether the span is a host function call timed by the `LatencyLayer`.
+pub(crate) fn is_host_call(metadata: &Metadata<'_>) -> bool {
+    is_enabled() && metadata.is_span() && metadata.name() == HOST_CALL_SPAN
+}
+
+/// Layer timing the host function calls, logging the calls exceeding their budget.
+pub(crate) struct LatencyLayer;
+
+impl<S> Layer<S> for LatencyLayer
+where S: Subscriber + for<'a> LookupSpan<'a>
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut call = HostCall {
+            interface: String::new(),
+            function: String::new(),
+            start: std::time::Instant::now(),
+        };
+        attrs.record(&mut call);
+        span.extensions_mut().insert(call);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let (Some(budgets), Some(span)) = (BUDGETS.get(), ctx.span(&id)) else {
+            return;
+        };
+        let Some(call) = span.extensions_mut().remove::<HostCall>() else {
+            return;
+        };
+        let duration = call.start.elapsed();
+        let Some(budget) = budgets.get(&call.interface, &call.function) else {
+            return;
+        };
+        if duration <= budget {
+            return;
+        }
+        drop(span);
+        CURRENT_EVENT.with(|event| {
+            match event.borrow().as_ref() {
+                Some(event) => {
+                    tracing::warn!(
+                        app_name = %event.app_name,
+                        module_id = %event.module_id,
+                        event_name = event.event_name.as_str(),
+                        event_id = event.event_id,
+                        interface = call.interface.as_str(),
+                        function = call.function.as_str(),
+                        duration = ?duration,
+                        budget = ?budget,
+                        "Host call exceeded its latency budget"
+                    );
+                },
+                None => {
+                    tracing::warn!(
+                        interface = call.interface.as_str(),
+                        function = call.function.as_str(),
+                        duration = ?duration,
+                        budget = ?budget,
+                        "Host call exceeded its latency budget"
+                    );
+                },
+            }
+        });
+    }
+}
+
+impl Visit for HostCall {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "module" => self.interface = value.to_string(),
+            "function" => self.function = value.to_string(),
+            _ => (),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record_str(field, &format!("{value:?}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budgets_test() {
+        let (function, budget) = parse_function_budget("[method]statement.step=50").unwrap();
+        assert_eq!(function, "[method]statement.step");
+        assert_eq!(budget, Duration::from_millis(50));
+        assert!(parse_function_budget("step").is_err());
+        assert!(parse_function_budget("step=fast").is_err());
+
+        let budgets = Budgets {
+            default: Some(Duration::from_millis(100)),
+            functions: HashMap::from([
+                ("step".to_string(), Duration::from_millis(10)),
+                ("api.step".to_string(), Duration::from_millis(20)),
+            ]),
+        };
+        assert_eq!(budgets.get("api", "step"), Some(Duration::from_millis(20)));
+        assert_eq!(
+            budgets.get("other", "step"),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            budgets.get("api", "reset"),
+            Some(Duration::from_millis(100))
+        );
+    }
+}
@@ -2,4 +2,7 @@
 //! All implementation based on [wasmtime](https://crates.io/crates/wasmtime) crate dependency.
 
 mod engine;
+pub(crate) mod latency;
 pub mod module;
+pub(crate) mod module_store;
+pub(crate) mod profiler;
@@ -6,9 +6,14 @@
 
 use std::{
     io::Read,
-    sync::atomic::{AtomicU32, Ordering},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Weak,
+    },
 };
 
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
 use rusty_ulid::Ulid;
 use wasmtime::{
     component::{Component as WasmModule, InstancePre as WasmInstancePre, Linker as WasmLinker},
@@ -16,8 +21,11 @@ use wasmtime::{
 };
 
 use crate::{
-    event::HermesEventPayload, runtime_context::HermesRuntimeContext, runtime_extensions::bindings,
-    wasm::engine::Engine,
+    event::HermesEventPayload,
+    packaging::hash::Blake2b256,
+    runtime_context::HermesRuntimeContext,
+    runtime_extensions::{bindings, plugin, versioning},
+    wasm::{engine::Engine, latency, module_store, profiler},
 };
 
 /// Bad WASM module error
@@ -25,6 +33,12 @@ use crate::{
 #[error("Bad WASM module:\n {0}")]
 struct BadWASMModuleError(String);
 
+/// WASM module trap error, with the output of the module before it trapped, e.g. the
+/// message of a panic of the guest
+#[derive(thiserror::Error, Debug)]
+#[error("WASM module trapped:\n {0}")]
+struct ModuleTrapError(String);
+
 /// Structure defines an abstraction over the WASM module instance.
 /// It holds the state of the WASM module along with its context data.
 /// It is used to interact with the WASM module.
@@ -46,15 +60,14 @@ impl std::fmt::Display for ModuleId {
     }
 }
 
-/// Structure defines an abstraction over the WASM module
-/// It instantiates the module with the provided context data,
-/// links all provided imports to the module instance,
-/// handles an internal state of the WASM module.
+/// Compiled modules by the hash of their content.
 ///
-/// The primary goal for it is to make a WASM state *immutable* along WASM module
-/// execution. It means that `Module::call_func` execution does not have as side effect
-/// for the WASM module's state, it becomes unchanged.
-pub struct Module {
+/// Identical modules, e.g. the same module shipped by several apps, are compiled and
+/// linked once and share the result, which is dropped once no module uses it anymore.
+static COMPILED_MODULES: Lazy<DashMap<Blake2b256, Weak<CompiledModule>>> = Lazy::new(DashMap::new);
+
+/// WASM module compiled and linked to the Hermes runtime, ready to be instantiated.
+struct CompiledModule {
     /// `wasmtime::InstancePre` entity
     ///
     /// A reason why it is used a `wasmtime::InstancePre` instead of `wasmtime::Instance`
@@ -65,6 +78,73 @@ pub struct Module {
 
     /// `Engine` entity
     engine: Engine,
+}
+
+impl CompiledModule {
+    /// Compile WASM module from bytes, with the given hash, and link it.
+    ///
+    /// The module is loaded from the module store instead, if it was already compiled,
+    /// and stored once compiled otherwise.
+    ///
+    /// Plugins and interface adapters are registered before any app is loaded, so the
+    /// linkage only depends on the module content.
+    fn new(hash: &Blake2b256, module_bytes: &[u8]) -> anyhow::Result<Self> {
+        let engine = Engine::new()?;
+        let wasm_module = match module_store::get(&engine, hash) {
+            Some(wasm_module) => wasm_module,
+            None => {
+                let wasm_module = WasmModule::new(&engine, module_bytes)
+                    .map_err(|e| BadWASMModuleError(e.to_string()))?;
+                module_store::put(hash, &wasm_module);
+                wasm_module
+            },
+        };
+
+        let mut linker = WasmLinker::new(&engine);
+        bindings::Hermes::add_to_linker(&mut linker, |state: &mut HermesRuntimeContext| state)
+            .map_err(|e| BadWASMModuleError(e.to_string()))?;
+        plugin::add_to_linker(&mut linker).map_err(|e| BadWASMModuleError(e.to_string()))?;
+        versioning::add_to_linker(&wasm_module, &engine, &mut linker)
+            .map_err(|e| BadWASMModuleError(e.to_string()))?;
+        let pre_instance = linker
+            .instantiate_pre(&wasm_module)
+            .map_err(|e| BadWASMModuleError(e.to_string()))?;
+
+        Ok(Self {
+            pre_instance,
+            engine,
+        })
+    }
+
+    /// Get the compiled module with the given content, compiling it if no module with the
+    /// same content is loaded.
+    fn get_or_compile(module_bytes: &[u8]) -> anyhow::Result<Arc<Self>> {
+        let hash = Blake2b256::hash(module_bytes);
+        let cached = COMPILED_MODULES
+            .get(&hash)
+            .and_then(|module| module.upgrade());
+        if let Some(compiled) = cached {
+            return Ok(compiled);
+        }
+
+        let compiled = Arc::new(Self::new(&hash, module_bytes)?);
+        COMPILED_MODULES.retain(|_, module| module.strong_count() > 0);
+        COMPILED_MODULES.insert(hash, Arc::downgrade(&compiled));
+        Ok(compiled)
+    }
+}
+
+/// Structure defines an abstraction over the WASM module
+/// It instantiates the module with the provided context data,
+/// links all provided imports to the module instance,
+/// handles an internal state of the WASM module.
+///
+/// The primary goal for it is to make a WASM state *immutable* along WASM module
+/// execution. It means that `Module::call_func` execution does not have as side effect
+/// for the WASM module's state, it becomes unchanged.
+pub struct Module {
+    /// Compiled module, shared with the modules with the same content.
+    compiled: Arc<CompiledModule>,
 
     /// Module id
     id: ModuleId,
@@ -80,20 +160,8 @@ impl Module {
     ///  - `BadWASMModuleError`
     ///  - `BadEngineConfigError`
     pub fn from_bytes(module_bytes: &[u8]) -> anyhow::Result<Self> {
-        let engine = Engine::new()?;
-        let wasm_module = WasmModule::new(&engine, module_bytes)
-            .map_err(|e| BadWASMModuleError(e.to_string()))?;
-
-        let mut linker = WasmLinker::new(&engine);
-        bindings::Hermes::add_to_linker(&mut linker, |state: &mut HermesRuntimeContext| state)
-            .map_err(|e| BadWASMModuleError(e.to_string()))?;
-        let pre_instance = linker
-            .instantiate_pre(&wasm_module)
-            .map_err(|e| BadWASMModuleError(e.to_string()))?;
-
         Ok(Self {
-            pre_instance,
-            engine,
+            compiled: CompiledModule::get_or_compile(module_bytes)?,
             id: ModuleId(Ulid::generate()),
             exc_counter: AtomicU32::new(0),
         })
@@ -137,11 +205,26 @@ impl Module {
     pub(crate) fn execute_event(
         &self, event: &dyn HermesEventPayload, state: HermesRuntimeContext,
     ) -> anyhow::Result<()> {
-        let mut store = WasmStore::new(&self.engine, state);
-        let (instance, _) = bindings::Hermes::instantiate_pre(&mut store, &self.pre_instance)
-            .map_err(|e| BadWASMModuleError(e.to_string()))?;
-
-        event.execute(&mut ModuleInstance { store, instance })?;
+        state.guest_stderr().clear();
+        let mut store = WasmStore::new(&self.compiled.engine, state);
+        if profiler::is_enabled() {
+            store.limiter(|ctx| ctx.memory_usage_mut());
+        }
+        let (instance, _) =
+            bindings::Hermes::instantiate_pre(&mut store, &self.compiled.pre_instance)
+                .map_err(|e| BadWASMModuleError(e.to_string()))?;
+
+        let mut module_instance = ModuleInstance { store, instance };
+        let event_guard =
+            latency::is_enabled().then(|| latency::enter_event(module_instance.store.data()));
+        let result = event.execute(&mut module_instance);
+        drop(event_guard);
+        module_instance.store.data().guest_stdout().flush_line();
+        module_instance.store.data().guest_stderr().flush_line();
+        if profiler::is_enabled() {
+            profiler::sample(module_instance.store.data());
+        }
+        result.map_err(|err| with_guest_output(err, module_instance.store.data()))?;
 
         // Using the highest memory ordering constraint.
         // It provides a highest consistency guarantee and in some cases could decrease
@@ -152,6 +235,42 @@ impl Module {
     }
 }
 
+/// Attach the output the module wrote to its stderr to the error of a trap, so the
+/// message of a panic of the guest is reported along with the failure of the event.
+fn with_guest_output(err: anyhow::Error, ctx: &HermesRuntimeContext) -> anyhow::Error {
+    if err.downcast_ref::<wasmtime::Trap>().is_none() {
+        return err;
+    }
+    let output = ctx.guest_stderr().text();
+    if output.is_empty() {
+        return err;
+    }
+    err.context(ModuleTrapError(output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_modules_are_compiled_once() {
+        let first = Module::from_bytes(b"(component)").unwrap();
+        let second = Module::from_bytes(b"(component)").unwrap();
+        let other = Module::from_bytes(b"(component (core module))").unwrap();
+
+        assert!(Arc::ptr_eq(&first.compiled, &second.compiled));
+        assert!(!Arc::ptr_eq(&first.compiled, &other.compiled));
+        assert_ne!(first.id(), second.id());
+
+        // The compiled module is dropped with the last module using it.
+        let hash = Blake2b256::hash(b"(component (core module))");
+        drop(other);
+        assert!(COMPILED_MODULES
+            .get(&hash)
+            .map_or(true, |module| module.upgrade().is_none()));
+    }
+}
+
 #[allow(missing_docs)]
 #[cfg(feature = "bench")]
 pub mod bench {
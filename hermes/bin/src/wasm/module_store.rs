@@ -0,0 +1,239 @@
+//! Content-addressed store of the compiled WASM modules loaded by the node.
+//!
+//! The compiled component of every loaded module is stored in the `modules` directory of
+//! the Hermes home, named after the Blake2b-256 hash of the module content. A module
+//! shipped by several apps, e.g. the same bindings module, is stored once, and a stored
+//! module is loaded without being compiled again when the node restarts.
+//!
+//! Stored modules not loaded for `UNUSED_MODULE_TTL` are removed when the store is
+//! opened.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use once_cell::sync::OnceCell;
+use wasmtime::component::Component;
+
+use crate::{packaging::hash::Blake2b256, wasm::engine::Engine};
+
+/// Directory of the module store in the Hermes home directory.
+const MODULES_DIR: &str = "modules";
+
+/// Extension of the stored compiled components.
+const MODULE_EXTENSION: &str = "cwasm";
+
+/// Extension of the compiled components being written.
+const TMP_EXTENSION: &str = "tmp";
+
+/// Time after which a stored module that was not loaded is removed.
+const UNUSED_MODULE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Directory of the module store, set by `init`.
+static MODULES_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// Counter of the writes to the store, making their temporary file names unique.
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// Open the module store in the Hermes home directory, removing the stored modules not
+/// loaded recently.
+///
+/// ## Errors
+///
+/// Returns errors if the store directory cannot be created, or the store is already
+/// open.
+pub(crate) fn init(hermes_home: &Path) -> anyhow::Result<()> {
+    let path = hermes_home.join(MODULES_DIR);
+    std::fs::create_dir_all(&path)?;
+    prune(&path, UNUSED_MODULE_TTL);
+    MODULES_PATH
+        .set(path)
+        .map_err(|_| anyhow::anyhow!("Module store already initialized"))
+}
+
+/// Load the stored compiled component of the module with the given hash, if the store is
+/// open and holds it.
+pub(crate) fn get(engine: &Engine, hash: &Blake2b256) -> Option<Component> {
+    get_in(MODULES_PATH.get()?, engine, hash)
+}
+
+/// Store the compiled component of the module with the given hash, unless the store is
+/// not open.
+///
+/// A failure is only logged, the module is then compiled again the next time it is
+/// loaded.
+pub(crate) fn put(hash: &Blake2b256, component: &Component) {
+    let Some(dir) = MODULES_PATH.get() else {
+        return;
+    };
+    if let Err(err) = put_in(dir, hash, component) {
+        tracing::warn!(hash = %hash.to_hex(), "Failed to store compiled module: {err}");
+    }
+}
+
+/// Path of the compiled component of the module with the given hash in the `dir` store
+/// directory.
+fn module_path(dir: &Path, hash: &Blake2b256) -> PathBuf {
+    dir.join(hash.to_hex()).with_extension(MODULE_EXTENSION)
+}
+
+/// Load the compiled component of the module with the given hash from the `dir` store
+/// directory.
+///
+/// A stored component the engine cannot load, e.g. one compiled by another version of
+/// the node, is removed.
+fn get_in(dir: &Path, engine: &Engine, hash: &Blake2b256) -> Option<Component> {
+    let path = module_path(dir, hash);
+    if !path.is_file() {
+        return None;
+    }
+
+    // SAFETY: The store is only written by the node, with components it compiled, in the
+    // Hermes home it trusts, like the keys it keeps there. The engine checks the
+    // component was compiled by the same `wasmtime` version and configuration.
+    match unsafe { Component::deserialize_file(engine, &path) } {
+        Ok(component) => {
+            // The modification time marks when the module was last loaded, to prune it.
+            if let Err(err) = std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+            {
+                tracing::debug!(path = %path.display(), "Failed to mark stored module as used: {err}");
+            }
+            Some(component)
+        },
+        Err(err) => {
+            tracing::warn!(path = %path.display(), "Removing unloadable stored module: {err}");
+            if let Err(err) = std::fs::remove_file(&path) {
+                tracing::warn!(path = %path.display(), "Failed to remove stored module: {err}");
+            }
+            None
+        },
+    }
+}
+
+/// Store the compiled component of the module with the given hash in the `dir` store
+/// directory, returning its path.
+fn put_in(dir: &Path, hash: &Blake2b256, component: &Component) -> anyhow::Result<PathBuf> {
+    let path = module_path(dir, hash);
+    let serialized = component.serialize()?;
+
+    // Written to a temporary file first, so a stored component is never half written.
+    // Its name is unique, as the same module can be stored by concurrent loads.
+    let tmp_path = dir.join(format!(
+        "{}-{}-{}.{TMP_EXTENSION}",
+        hash.to_hex(),
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written =
+        std::fs::write(&tmp_path, serialized).and_then(|()| std::fs::rename(&tmp_path, &path));
+    if let Err(err) = written {
+        // The temporary file may not exist, if it could not be created.
+        let _unused = std::fs::remove_file(&tmp_path);
+        return Err(err.into());
+    }
+    tracing::debug!(path = %path.display(), "Stored compiled module");
+    Ok(path)
+}
+
+/// Remove the stored modules of the `dir` store directory not loaded for `ttl`, and the
+/// temporary files left by interrupted writes.
+fn prune(dir: &Path, ttl: Duration) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let stale = match path.extension().and_then(OsStr::to_str) {
+            Some(TMP_EXTENSION) => true,
+            Some(MODULE_EXTENSION) => {
+                entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| {
+                        now.duration_since(modified)
+                            .is_ok_and(|unused| unused > ttl)
+                    })
+            },
+            _ => false,
+        };
+        if stale {
+            if let Err(err) = std::fs::remove_file(&path) {
+                tracing::warn!(path = %path.display(), "Failed to prune stored module: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn put_in_get_in_test() {
+        let dir = TempDir::new().unwrap();
+        let engine = Engine::new().unwrap();
+        let module = b"(component)";
+        let hash = Blake2b256::hash(module);
+        assert!(get_in(dir.path(), &engine, &hash).is_none());
+
+        let component = Component::new(&engine, module).unwrap();
+        let path = put_in(dir.path(), &hash, &component).unwrap();
+        assert_eq!(
+            path,
+            dir.path()
+                .join(format!("{}.{MODULE_EXTENSION}", hash.to_hex()))
+        );
+        assert!(get_in(dir.path(), &engine, &hash).is_some());
+
+        // Identical modules are stored once.
+        assert_eq!(put_in(dir.path(), &hash, &component).unwrap(), path);
+        let other = b"(component (core module))";
+        let other_component = Component::new(&engine, other).unwrap();
+        let other_path = put_in(dir.path(), &Blake2b256::hash(other), &other_component).unwrap();
+        assert_ne!(other_path, path);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // A corrupted component is removed.
+        std::fs::write(&path, b"corrupted").unwrap();
+        assert!(get_in(dir.path(), &engine, &hash).is_none());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn prune_test() {
+        let dir = TempDir::new().unwrap();
+        let engine = Engine::new().unwrap();
+        let module = b"(component)";
+        let hash = Blake2b256::hash(module);
+        let component = Component::new(&engine, module).unwrap();
+        let path = put_in(dir.path(), &hash, &component).unwrap();
+        let tmp_path = dir
+            .path()
+            .join(format!("{}-1-0.{TMP_EXTENSION}", hash.to_hex()));
+        std::fs::write(&tmp_path, b"half written").unwrap();
+
+        // Recently loaded modules are kept, interrupted writes are removed.
+        prune(dir.path(), UNUSED_MODULE_TTL);
+        assert!(path.exists());
+        assert!(!tmp_path.exists());
+
+        let unused_since = SystemTime::now() - UNUSED_MODULE_TTL - Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(unused_since)
+            .unwrap();
+        prune(dir.path(), UNUSED_MODULE_TTL);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,198 @@
+//! Opt-in memory profiler of the WASM modules.
+//!
+//! After every event a module executes, the profiler samples the size of the linear
+//! memories and tables of the module instance, and the number of resource handles its
+//! app holds. As every event runs on a fresh instance, the memory and table sizes are the
+//! peak sizes reached while handling the event. A report is periodically logged,
+//! flagging the modules whose samples keep growing across the last events, e.g. leaking
+//! prepared statements or never dropping subscribed resources.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::{
+    app::ApplicationName, runtime_context::HermesRuntimeContext,
+    runtime_extensions::resource_manager, wasm::module::ModuleId,
+};
+
+/// Number of the last samples of a module a growth is looked for in.
+const WINDOW: usize = 16;
+
+/// Interval between the reports, set by `init`.
+static REPORT_INTERVAL: OnceCell<Duration> = OnceCell::new();
+
+/// Last samples of every module.
+static SAMPLES: Lazy<DashMap<(ApplicationName, ModuleId), VecDeque<Sample>>> =
+    Lazy::new(DashMap::new);
+
+/// Size of the linear memories and tables of a module instance, tracked as they grow.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemoryUsage {
+    /// Bytes of all linear memories.
+    memory_bytes: usize,
+    /// Elements of all tables.
+    table_elements: usize,
+}
+
+impl wasmtime::ResourceLimiter for MemoryUsage {
+    fn memory_growing(
+        &mut self, current: usize, desired: usize, _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        self.memory_bytes = self
+            .memory_bytes
+            .saturating_add(desired.saturating_sub(current));
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self, current: u32, desired: u32, _maximum: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        let grown = usize::try_from(desired.saturating_sub(current)).unwrap_or(usize::MAX);
+        self.table_elements = self.table_elements.saturating_add(grown);
+        Ok(true)
+    }
+}
+
+/// Sample taken after a module executed an event.
+#[derive(Debug, Clone)]
+struct Sample {
+    /// Bytes of all linear memories.
+    memory_bytes: usize,
+    /// Elements of all tables.
+    table_elements: usize,
+    /// Resource handles held by the app, by resource type.
+    resource_handles: BTreeMap<&'static str, usize>,
+}
+
+/// Enable the profiler, logging a report every `interval`.
+///
+/// ## Errors
+///
+/// Returns an error if the profiler is already enabled, or the report thread can not be
+/// spawned.
+pub(crate) fn init(interval: Duration) -> anyhow::Result<()> {
+    REPORT_INTERVAL
+        .set(interval)
+        .map_err(|_| anyhow::anyhow!("Memory profiler already enabled"))?;
+
+    std::thread::Builder::new()
+        .name("memory-profiler".to_string())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                report();
+            }
+        })?;
+    Ok(())
+}
+
+/// Whether the profiler is enabled.
+pub(crate) fn is_enabled() -> bool {
+    REPORT_INTERVAL.get().is_some()
+}
+
+/// Sample the memory usage of a module instance, after it executed an event.
+pub(crate) fn sample(ctx: &HermesRuntimeContext) {
+    let usage = ctx.memory_usage();
+    let sample = Sample {
+        memory_bytes: usage.memory_bytes,
+        table_elements: usage.table_elements,
+        resource_handles: resource_manager::resource_handles(ctx.app_name()),
+    };
+
+    let mut samples = SAMPLES
+        .entry((ctx.app_name().clone(), ctx.module_id().clone()))
+        .or_default();
+    if samples.len() == WINDOW {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+/// Log the report of the samples of every module.
+fn report() {
+    for entry in SAMPLES.iter() {
+        let (app_name, module_id) = entry.key();
+        let samples = entry.value();
+        let Some(last) = samples.back() else {
+            continue;
+        };
+        tracing::info!(
+            app_name = %app_name,
+            module_id = %module_id,
+            memory_bytes = last.memory_bytes,
+            table_elements = last.table_elements,
+            resource_handles = ?last.resource_handles,
+            "Module memory usage"
+        );
+
+        if samples.len() < WINDOW {
+            continue;
+        }
+        let memory: Vec<_> = samples.iter().map(|s| s.memory_bytes).collect();
+        if is_growing(&memory) {
+            tracing::warn!(
+                app_name = %app_name,
+                module_id = %module_id,
+                events = WINDOW,
+                memory_bytes = last.memory_bytes,
+                "Linear memory of the module keeps growing"
+            );
+        }
+        let tables: Vec<_> = samples.iter().map(|s| s.table_elements).collect();
+        if is_growing(&tables) {
+            tracing::warn!(
+                app_name = %app_name,
+                module_id = %module_id,
+                events = WINDOW,
+                table_elements = last.table_elements,
+                "Tables of the module keep growing"
+            );
+        }
+        for resource in last.resource_handles.keys() {
+            let handles: Vec<_> = samples
+                .iter()
+                .map(|s| {
+                    s.resource_handles
+                        .get(resource)
+                        .copied()
+                        .unwrap_or_default()
+                })
+                .collect();
+            if is_growing(&handles) {
+                tracing::warn!(
+                    app_name = %app_name,
+                    module_id = %module_id,
+                    events = WINDOW,
+                    resource,
+                    handles = last.resource_handles.get(resource),
+                    "Resource handles of the app keep growing, they may be leaked"
+                );
+            }
+        }
+    }
+}
+
+/// Whether the values never decrease, and end higher than they start.
+fn is_growing(values: &[usize]) -> bool {
+    values.windows(2).all(|pair| pair.first() <= pair.last()) && values.first() < values.last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_growing_test() {
+        assert!(is_growing(&[1, 2, 2, 3]));
+        assert!(!is_growing(&[1, 1, 1, 1]));
+        assert!(!is_growing(&[1, 3, 2, 4]));
+        assert!(!is_growing(&[4, 3, 2, 1]));
+        assert!(!is_growing(&[]));
+    }
+}
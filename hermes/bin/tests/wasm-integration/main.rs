@@ -47,8 +47,17 @@ fn init_logger() -> Result<(), SetGlobalDefaultError> {
 fn init_ipfs() -> anyhow::Result<()> {
     let base_dir = temp_dir::TempDir::new()?;
     // disable bootstrapping the IPFS node to default addresses for testing
-    let default_bootstrap = false;
-    hermes::ipfs::bootstrap(base_dir.path(), default_bootstrap)
+    let network = hermes::ipfs::IpfsNetworkConfig {
+        default_bootstrap: false,
+        ..Default::default()
+    };
+    hermes::ipfs::bootstrap(
+        base_dir.path(),
+        network,
+        hermes::ipfs::IpfsTopology::default(),
+        hermes::ipfs::DEFAULT_REPLAY_WINDOW,
+        hermes::ipfs::DEFAULT_DHT_REPUBLISH_INTERVAL,
+    )
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -164,6 +164,15 @@ pub fn network_genesis_values(network: &Network) -> Option<GenesisValues> {
     }
 }
 
+/// Tip of the Mithril snapshot at the given path.
+///
+/// # Errors
+///
+/// Returns Err if the tip of the snapshot can not be read.
+pub fn mithril_snapshot_tip(path: std::path::PathBuf) -> Result<Point> {
+    mithril_snapshot::MithrilSnapshot::from_path(path).map(|snapshot| snapshot.tip)
+}
+
 /// Validate a multi-era block.
 ///
 /// This does not execute Plutus scripts nor validates ledger state.
@@ -157,12 +157,24 @@ impl MithrilSnapshot {
         Some(MithrilSnapshotIterator { inner: iter })
     }
 
-    /// Naively checks if the snapshot contains a point.
+    /// Naively checks if the snapshot contains a point, which is between the first block
+    /// still stored and the tip.
     ///
     /// # Arguments
     ///
     /// * `point`: Point to check.
     pub fn contains_point(&self, point: &Point) -> bool {
-        point.slot_or_default() <= self.tip.slot_or_default()
+        let slot = point.slot_or_default();
+        slot <= self.tip.slot_or_default() && self.first_slot().is_some_and(|first| slot >= first)
+    }
+
+    /// Slot of the first block stored in the snapshot.
+    ///
+    /// It is read on every call, as the oldest chunks of the snapshot can be pruned while
+    /// it is used.
+    fn first_slot(&self) -> Option<u64> {
+        let mut blocks = pallas_hardano::storage::immutable::read_blocks(&self.path).ok()?;
+        let block_data = MultiEraBlockData(blocks.next()?.ok()?);
+        block_data.decode().ok().map(|block| block.slot())
     }
 }
@@ -7,5 +7,6 @@ mod elements;
 mod groups;
 mod identifiers;
 mod repetitions;
+mod roundtrip;
 mod rules;
 mod values;
@@ -0,0 +1,129 @@
+use std::{ffi::OsStr, fs, io::Result};
+
+use abnf_parser::abnf::{ABNFParser, Parser, Rule};
+use proptest::prelude::*;
+
+/// Parses an ABNF document and prints it back as the list of its rules.
+///
+/// Each printed rule keeps its terminating newline (and trailing comment, if any),
+/// while blank lines and comment-only lines between rules are dropped.
+fn parse_and_print(input: &str) -> std::result::Result<Vec<String>, String> {
+    let pairs = ABNFParser::parse(Rule::abnf, input).map_err(|e| e.to_string())?;
+
+    Ok(pairs
+        .flat_map(pest::iterators::Pair::into_inner)
+        .filter(|p| p.as_rule() == Rule::rule)
+        .map(|p| p.as_str().to_string())
+        .collect())
+}
+
+/// Generates a valid ABNF rule name.
+fn arb_rulename() -> impl Strategy<Value = String> {
+    "[a-zA-Z][a-zA-Z0-9-]{0,8}"
+}
+
+/// Generates a valid ABNF alternation.
+fn arb_alternation() -> impl Strategy<Value = String> {
+    let element = prop_oneof![
+        arb_rulename(),
+        "[a-zA-Z0-9 ]{0,10}".prop_map(|v| format!("\"{v}\"")),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(a, b)| format!("%x{:02X}-{:02X}", a.min(b), a.max(b))),
+        prop::collection::vec(any::<u8>(), 1..4).prop_map(|v| {
+            let values = v.iter().map(u8::to_string).collect::<Vec<_>>().join(".");
+            format!("%d{values}")
+        }),
+        "[a-z ]{0,10}".prop_map(|v| format!("<{v}>")),
+    ];
+
+    let repetition = (
+        prop_oneof![
+            Just(String::new()),
+            Just("*".to_string()),
+            (1..5u8).prop_map(|n| n.to_string()),
+            (0..3u8, 3..6u8).prop_map(|(min, max)| format!("{min}*{max}")),
+        ],
+        element,
+    )
+        .prop_map(|(repeat, element)| format!("{repeat}{element}"));
+
+    let concatenation = prop::collection::vec(repetition, 1..4).prop_map(|reps| reps.join(" "));
+
+    prop::collection::vec(concatenation, 1..4)
+        .prop_map(|concats| concats.join(" / "))
+        .prop_recursive(2, 8, 3, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|alt| format!("({alt})")),
+                inner.clone().prop_map(|alt| format!("[{alt}]")),
+                (inner.clone(), inner).prop_map(|(a, b)| format!("{a} / {b}")),
+            ]
+        })
+}
+
+/// Generates a valid ABNF rule, including its terminating newline.
+fn arb_rule() -> impl Strategy<Value = String> {
+    (
+        arb_rulename(),
+        prop_oneof![Just("="), Just("=/")],
+        arb_alternation(),
+    )
+        .prop_map(|(name, defined_as, elements)| format!("{name} {defined_as} {elements}\n"))
+}
+
+proptest! {
+    #[test]
+    /// Generated documents parse, and printing them yields the generated rules.
+    fn generated_abnf_roundtrip(rules in prop::collection::vec(arb_rule(), 1..8)) {
+        let document = rules.concat();
+
+        let printed = parse_and_print(&document);
+        prop_assert_eq!(printed.as_ref(), Ok(&rules), "{}", document);
+
+        let reprinted = parse_and_print(&rules.concat());
+        prop_assert_eq!(reprinted, Ok(rules));
+    }
+}
+
+#[test]
+/// Every valid corpus file survives a parse -> print -> parse round-trip.
+///
+/// # Panics
+fn roundtrip_abnf_files() {
+    let entries = fs::read_dir("tests/abnf").unwrap();
+
+    let mut file_paths: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter_map(|x| x.path().is_file().then_some(x.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|p| p.starts_with("valid"))
+        })
+        .collect();
+
+    file_paths.sort();
+
+    let mut err_messages = vec![];
+    for file_path in file_paths {
+        let content = fs::read_to_string(&file_path).unwrap();
+
+        let result = parse_and_print(&content).and_then(|printed| {
+            let reprinted = parse_and_print(&printed.concat())?;
+            if printed == reprinted {
+                Ok(())
+            } else {
+                Err(format!(
+                    "printed output differs after re-parsing: {reprinted:?}"
+                ))
+            }
+        });
+
+        if let Err(e) = result {
+            err_messages.push(format!("{}) {file_path:?} {e}", err_messages.len() + 1));
+        }
+    }
+
+    // summary
+    let err_msg = err_messages.join("\n\n");
+    assert!(err_msg.is_empty(), "{err_msg}");
+}
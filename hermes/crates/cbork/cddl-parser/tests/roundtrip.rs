@@ -0,0 +1,119 @@
+// cspell: words tstr bstr
+
+use std::{ffi::OsStr, fs, io::Result};
+
+use cddl_parser::cddl::{Parser, RFC8610Parser, Rule};
+use proptest::prelude::*;
+
+/// Parses a CDDL document and prints it back as the list of its rules.
+///
+/// Comments and whitespace between rules are not part of the printed output, so
+/// printing is stable across repeated parse/print cycles.
+fn parse_and_print(input: &str) -> std::result::Result<Vec<String>, String> {
+    let pairs = RFC8610Parser::parse(Rule::cddl, input).map_err(|e| e.to_string())?;
+
+    Ok(pairs
+        .flat_map(pest::iterators::Pair::into_inner)
+        .filter(|p| p.as_rule() == Rule::rule)
+        .map(|p| p.as_str().to_string())
+        .collect())
+}
+
+/// Generates a valid CDDL identifier.
+fn arb_name() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9_]{0,8}"
+}
+
+/// Generates a valid CDDL type expression.
+fn arb_type() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![
+        Just("uint".to_string()),
+        Just("tstr".to_string()),
+        Just("bstr".to_string()),
+        Just("bool".to_string()),
+        any::<u32>().prop_map(|v| v.to_string()),
+        "[a-zA-Z0-9 ]{0,10}".prop_map(|v| format!("\"{v}\"")),
+        (any::<u16>(), any::<u16>()).prop_map(|(a, b)| format!("{} .. {}", a.min(b), a.max(b))),
+        arb_name(),
+    ];
+
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| format!("{a} / {b}")),
+            (prop_oneof![Just("*"), Just("+"), Just("?")], inner.clone())
+                .prop_map(|(occur, t)| format!("[{occur} {t}]")),
+            prop::collection::vec((arb_name(), inner.clone()), 1..4).prop_map(|members| {
+                let members = members
+                    .into_iter()
+                    .map(|(key, t)| format!("{key}: {t}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{{ {members} }}")
+            }),
+            inner.prop_map(|t| format!("({t})")),
+        ]
+    })
+}
+
+/// Generates a valid CDDL rule.
+fn arb_rule() -> impl Strategy<Value = String> {
+    (arb_name(), arb_type()).prop_map(|(name, t)| format!("{name} = {t}"))
+}
+
+proptest! {
+    #[test]
+    /// Generated documents parse, and printing them yields the generated rules.
+    fn generated_cddl_roundtrip(rules in prop::collection::vec(arb_rule(), 1..8)) {
+        let document = rules.join("\n");
+
+        let printed = parse_and_print(&document);
+        prop_assert_eq!(printed.as_ref(), Ok(&rules), "{}", document);
+
+        let reprinted = parse_and_print(&rules.join("\n"));
+        prop_assert_eq!(reprinted, Ok(rules));
+    }
+}
+
+#[test]
+/// Every valid corpus file survives a parse -> print -> parse round-trip.
+///
+/// # Panics
+fn roundtrip_cddl_files() {
+    let entries = fs::read_dir("tests/cddl").unwrap();
+
+    let mut file_paths: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter_map(|x| x.path().is_file().then_some(x.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|p| p.starts_with("valid"))
+        })
+        .collect();
+
+    file_paths.sort();
+
+    let mut err_messages = vec![];
+    for file_path in file_paths {
+        let content = fs::read_to_string(&file_path).unwrap();
+
+        let result = parse_and_print(&content).and_then(|printed| {
+            let reprinted = parse_and_print(&printed.join("\n"))?;
+            if printed == reprinted {
+                Ok(())
+            } else {
+                Err(format!(
+                    "printed output differs after re-parsing: {reprinted:?}"
+                ))
+            }
+        });
+
+        if let Err(e) = result {
+            err_messages.push(format!("{}) {file_path:?} {e}", err_messages.len() + 1));
+        }
+    }
+
+    // summary
+    let err_msg = err_messages.join("\n\n");
+    assert!(err_msg.is_empty(), "{err_msg}");
+}
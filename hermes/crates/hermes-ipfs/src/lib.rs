@@ -2,7 +2,12 @@
 //!
 //! Provides support for storage, and `PubSub` functionality.
 
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use derive_more::{Display, From, Into};
 /// IPFS Content Identifier.
@@ -13,6 +18,10 @@ pub use libipld::Ipld;
 pub use rust_ipfs;
 /// libp2p re-exports.
 pub use rust_ipfs::libp2p::futures::{pin_mut, stream::BoxStream, FutureExt, StreamExt};
+/// Keypair type, the identity of a node.
+pub use rust_ipfs::libp2p::identity::Keypair;
+/// Public key type, verifying the signatures of a node.
+pub use rust_ipfs::libp2p::identity::PublicKey;
 /// Peer Info type.
 pub use rust_ipfs::p2p::PeerInfo;
 /// Enum for specifying paths in IPFS.
@@ -35,8 +44,9 @@ use rust_ipfs::{
     dag::ResolveError,
     libp2p::gossipsub::{Message as PubsubMessage, MessageId as PubsubMessageId},
     unixfs::AddOpt,
-    PubsubEvent, Quorum,
+    PinMode, PubsubEvent, Quorum,
 };
+use tokio::sync::RwLock;
 
 #[derive(Debug, Display, From, Into)]
 /// `PubSub` Message ID.
@@ -64,6 +74,24 @@ impl IpfsBuilder {
         Self(self.0.set_default_listener())
     }
 
+    #[must_use]
+    /// Set the addresses the IPFS node listens on, instead of the default listener.
+    pub fn set_listening_addrs(self, addrs: Vec<Multiaddr>) -> Self {
+        Self(self.0.set_listening_addrs(addrs))
+    }
+
+    #[must_use]
+    /// Set the keypair of the IPFS node, instead of generating a new identity.
+    pub fn set_keypair(self, keypair: &Keypair) -> Self {
+        Self(self.0.set_keypair(keypair))
+    }
+
+    #[must_use]
+    /// Enable mDNS discovery of peers on the local network.
+    pub fn with_mdns(self) -> Self {
+        Self(self.0.with_mdns())
+    }
+
     #[must_use]
     /// Set the storage type for the IPFS node to local disk.
     ///
@@ -107,10 +135,41 @@ impl Default for IpfsBuilder {
     }
 }
 
+/// Options of a repo garbage collection.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GcOptions {
+    /// Only remove the blocks stored at least this long ago.
+    ///
+    /// With no minimum age, files added or fetched but not yet pinned are removed too.
+    pub min_age: Duration,
+    /// Report the reclaimable blocks without removing them.
+    pub dry_run: bool,
+}
+
+/// Result of a repo garbage collection.
+#[derive(Debug, Clone, Default)]
+pub struct GcReport {
+    /// Removed blocks, or the reclaimable blocks in dry-run mode.
+    pub blocks: Vec<Cid>,
+    /// Size of the removed blocks, or of the reclaimable blocks in dry-run mode, in
+    /// bytes.
+    pub bytes: u64,
+}
+
 /// Hermes IPFS Node.
 pub struct HermesIpfs {
     /// IPFS node
     node: Ipfs,
+    /// When the blocks of the repo were stored.
+    ///
+    /// The blockstore does not record when blocks are stored, so blocks added through
+    /// this node are recorded when they are added, and other blocks when a garbage
+    /// collection first sees them. It is not persisted, so ages restart with the node,
+    /// which only delays the removal of blocks.
+    block_stored_at: Mutex<HashMap<Cid, Instant>>,
+    /// Held shared while blocks are added or pinned, and exclusively by a garbage
+    /// collection, so a collection never removes blocks which are being pinned.
+    gc_lock: RwLock<()>,
 }
 
 impl HermesIpfs {
@@ -129,7 +188,7 @@ impl HermesIpfs {
             .set_default_listener()
             .start()
             .await?;
-        Ok(HermesIpfs { node })
+        Ok(node.into())
     }
 
     /// Add a file to IPFS.
@@ -147,7 +206,12 @@ impl HermesIpfs {
     ///
     /// Returns an error if the file fails to upload.
     pub async fn add_ipfs_file(&self, ipfs_file: AddIpfsFile) -> anyhow::Result<IpfsPath> {
+        let _gc_guard = self.gc_lock.read().await;
         let ipfs_path = self.node.add_unixfs(ipfs_file).await?;
+        if let Some(cid) = ipfs_path.root().cid() {
+            let blocks = self.reachable_blocks(vec![*cid]).await?;
+            self.record_stored(blocks);
+        }
         Ok(ipfs_path)
     }
 
@@ -169,6 +233,100 @@ impl HermesIpfs {
         Ok(stream_bytes.to_vec())
     }
 
+    /// Get a file from IPFS, retrying with backoff when it can not be fetched.
+    ///
+    /// Before each retry the providers of the file are discovered through the DHT, and
+    /// the ones the node is not connected to are dialed, so bitswap can fetch the file
+    /// from them.
+    ///
+    /// ## Parameters
+    ///
+    /// * `ipfs_path` - `GetIpfsFile(IpfsPath)` Path used to get the file from IPFS.
+    /// * `policy` - `FetchRetryPolicy` Attempts, timeouts and backoff of the fetch.
+    ///
+    /// ## Returns
+    ///
+    /// * `A result with Vec<u8>`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a `FetchError` describing every failed attempt if the file could not be
+    /// fetched.
+    pub async fn get_ipfs_file_with_retry(
+        &self, ipfs_path: GetIpfsFile, policy: &FetchRetryPolicy,
+    ) -> Result<Vec<u8>, FetchError> {
+        let path: IpfsPath = ipfs_path.into();
+        let cid = path.root().cid().copied();
+        let (node, fetched_path) = (&self.node, &path);
+        retry(
+            policy,
+            move || {
+                async move {
+                    node.cat_unixfs(fetched_path.clone())
+                        .await
+                        .map(|bytes| bytes.to_vec())
+                        .map_err(|err| err.to_string())
+                }
+            },
+            move || {
+                async move {
+                    match cid {
+                        Some(cid) => self.connect_providers(cid, policy).await,
+                        None => (0, 0),
+                    }
+                }
+            },
+        )
+        .await
+        .map_err(|attempts| {
+            FetchError {
+                path: path.to_string(),
+                attempts,
+            }
+        })
+    }
+
+    /// Discover the providers of `cid` and dial the ones the node is not connected to.
+    ///
+    /// Returns the number of providers found and successfully dialed.
+    async fn connect_providers(&self, cid: Cid, policy: &FetchRetryPolicy) -> (usize, usize) {
+        let mut providers = HashSet::new();
+        if let Ok(mut stream) = self.node.get_providers(cid).await {
+            // Provider discovery may not end by itself, stop it after one attempt timeout.
+            let _unused = tokio::time::timeout(policy.attempt_timeout, async {
+                while let Some(batch) = stream.next().await {
+                    providers.extend(batch);
+                    if providers.len() >= policy.max_providers {
+                        break;
+                    }
+                }
+            })
+            .await;
+        }
+        let found = providers.len();
+
+        let connected: HashSet<_> = self
+            .node
+            .connected()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        let node = &self.node;
+        let dialed = rust_ipfs::libp2p::futures::stream::iter(
+            providers
+                .into_iter()
+                .filter(|peer| !connected.contains(peer))
+                .take(policy.max_providers),
+        )
+        .map(|peer| async move { node.connect(peer).await })
+        .buffer_unordered(policy.dial_concurrency.max(1))
+        .filter(|res| std::future::ready(res.is_ok()))
+        .count()
+        .await;
+        (found, dialed)
+    }
+
     /// Pin content to IPFS.
     ///
     /// ## Parameters
@@ -179,6 +337,7 @@ impl HermesIpfs {
     ///
     /// Returns an error if pinning fails.
     pub async fn insert_pin(&self, cid: &Cid) -> anyhow::Result<()> {
+        let _gc_guard = self.gc_lock.read().await;
         self.node.insert_pin(cid).await
     }
 
@@ -239,9 +398,139 @@ impl HermesIpfs {
         self.node.remove_pin(cid).recursive().await
     }
 
+    /// Remove all blocks which are not pinned from the repo.
+    ///
+    /// ## Returns
+    ///
+    /// * `Cid`s of the removed blocks.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the blockstore cleanup fails.
+    pub async fn gc(&self) -> anyhow::Result<Vec<Cid>> {
+        Ok(self.node.gc().await?)
+    }
+
+    /// Remove the blocks which are not pinned, and are old enough, from the repo.
+    ///
+    /// Blocks reachable from a recursive pin are never removed, even when the pinned DAG
+    /// is only partially stored. Adding and pinning blocks waits for the collection to
+    /// finish.
+    ///
+    /// ## Parameters
+    ///
+    /// * `options` - `GcOptions` Minimum age of the removed blocks, and whether to only
+    ///   report them.
+    ///
+    /// ## Returns
+    ///
+    /// * `GcReport` of the removed, or reclaimable, blocks.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if listing the pins or blocks, or removing a block fails.
+    pub async fn gc_with(&self, options: GcOptions) -> anyhow::Result<GcReport> {
+        let _gc_guard = self.gc_lock.write().await;
+        let now = Instant::now();
+        let blocks: HashSet<Cid> = self.node.repo().list_blocks().await.collect().await;
+        let candidates: Vec<Cid> = {
+            let mut stored_at = self
+                .block_stored_at
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            stored_at.retain(|cid, _| blocks.contains(cid));
+            blocks
+                .into_iter()
+                .filter(|cid| {
+                    let stored = *stored_at.entry(*cid).or_insert(now);
+                    now.saturating_duration_since(stored) >= options.min_age
+                })
+                .collect()
+        };
+
+        let protected = self.pinned_closure().await?;
+        let mut report = GcReport::default();
+        for cid in candidates {
+            if protected.contains(&cid) {
+                continue;
+            }
+            let Some(block) = self.node.repo().get_block_now(&cid).await? else {
+                continue;
+            };
+            if !options.dry_run {
+                self.node.repo().remove_block(&cid, false).await?;
+            }
+            report.bytes = report
+                .bytes
+                .saturating_add(u64::try_from(block.data().len()).unwrap_or(u64::MAX));
+            report.blocks.push(cid);
+        }
+        if !options.dry_run {
+            let mut stored_at = self
+                .block_stored_at
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            for cid in &report.blocks {
+                stored_at.remove(cid);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Pinned blocks, with every block reachable from a recursive pin.
+    async fn pinned_closure(&self) -> anyhow::Result<HashSet<Cid>> {
+        let pins_stream = self.node.list_pins(None).await;
+        pin_mut!(pins_stream);
+        let mut protected = HashSet::new();
+        let mut recursive = Vec::new();
+        while let Some(pinned) = pins_stream.next().await {
+            let (cid, mode) = pinned?;
+            protected.insert(cid);
+            if mode == PinMode::Recursive {
+                recursive.push(cid);
+            }
+        }
+        protected.extend(self.reachable_blocks(recursive).await?);
+        Ok(protected)
+    }
+
+    /// The given blocks, with every block reachable from them.
+    async fn reachable_blocks(&self, mut pending: Vec<Cid>) -> anyhow::Result<HashSet<Cid>> {
+        let mut reachable: HashSet<Cid> = pending.iter().copied().collect();
+        let mut visited = HashSet::new();
+        while let Some(cid) = pending.pop() {
+            if !visited.insert(cid) {
+                continue;
+            }
+            // Blocks missing from a partially stored DAG have no stored children.
+            let Some(block) = self.node.repo().get_block_now(&cid).await? else {
+                continue;
+            };
+            let mut references = Vec::new();
+            block.references(&mut references)?;
+            for reference in references {
+                reachable.insert(reference);
+                pending.push(reference);
+            }
+        }
+        Ok(reachable)
+    }
+
+    /// Record the given blocks as stored now.
+    fn record_stored(&self, blocks: impl IntoIterator<Item = Cid>) {
+        let now = Instant::now();
+        let mut stored_at = self
+            .block_stored_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for cid in blocks {
+            stored_at.insert(cid, now);
+        }
+    }
+
     /// Stop and exit the IPFS node daemon.
-    pub async fn stop(self) {
-        self.node.exit_daemon().await;
+    pub async fn stop(&self) {
+        self.node.clone().exit_daemon().await;
     }
 
     /// Returns the peer identity information. If no peer id is supplied the local node
@@ -285,6 +574,19 @@ impl HermesIpfs {
         self.node.listening_addresses().await
     }
 
+    /// List of peers the node is currently connected to.
+    ///
+    /// ## Returns
+    ///
+    /// * `Result<Vec<PeerId>>`
+    ///
+    /// ## Errors
+    ///
+    /// Returns error if connected peers cannot be retrieved.
+    pub async fn connected_peers(&self) -> anyhow::Result<Vec<PeerId>> {
+        self.node.connected().await
+    }
+
     /// Sets DHT mode in the IPFS node.
     ///
     /// ## Parameters
@@ -316,7 +618,10 @@ impl HermesIpfs {
     ///
     /// Returns error if unable to add DAG content.
     pub async fn dag_put(&self, ipld: Ipld) -> anyhow::Result<Cid> {
-        self.node.put_dag(ipld).await
+        let _gc_guard = self.gc_lock.read().await;
+        let cid = self.node.put_dag(ipld).await?;
+        self.record_stored([cid]);
+        Ok(cid)
     }
 
     /// Get DAG data from IPFS.
@@ -370,13 +675,64 @@ impl HermesIpfs {
     ///
     /// Returns error if unable to get content from DHT
     pub async fn dht_get(&self, key: impl AsRef<[u8]>) -> anyhow::Result<Vec<u8>> {
+        self.dht_get_record(key).await.map(|(value, _)| value)
+    }
+
+    /// Get content from DHT, with the time left until the record expires.
+    ///
+    /// ## Parameters
+    ///
+    /// * `key` - `impl AsRef<[u8]>`
+    ///
+    /// ## Returns
+    ///
+    /// * `Result<(Vec<u8>, Option<Duration>)>`, the time to live is `None` if the record
+    ///   does not expire.
+    ///
+    /// ## Errors
+    ///
+    /// Returns error if unable to get content from DHT
+    pub async fn dht_get_record(
+        &self, key: impl AsRef<[u8]>,
+    ) -> anyhow::Result<(Vec<u8>, Option<Duration>)> {
         let record_stream = self.node.dht_get(key).await?;
         pin_mut!(record_stream);
         let record = record_stream
             .next()
             .await
             .ok_or(anyhow::anyhow!("No record found"))?;
-        Ok(record.value)
+        let ttl = record
+            .expires
+            .map(|expires| expires.saturating_duration_since(std::time::Instant::now()));
+        Ok((record.value, ttl))
+    }
+
+    /// Get the content of a key from up to `max` DHT records, as returned by the
+    /// different peers holding the key, to compare them.
+    ///
+    /// ## Parameters
+    ///
+    /// * `key` - `impl AsRef<[u8]>`
+    /// * `max` - `usize`, the maximum number of records returned.
+    ///
+    /// ## Returns
+    ///
+    /// * `Result<Vec<Vec<u8>>>`
+    ///
+    /// ## Errors
+    ///
+    /// Returns error if unable to get content from DHT
+    pub async fn dht_get_records(
+        &self, key: impl AsRef<[u8]>, max: usize,
+    ) -> anyhow::Result<Vec<Vec<u8>>> {
+        let record_stream = self.node.dht_get(key).await?;
+        let values: Vec<_> = record_stream
+            .take(max)
+            .map(|record| record.value)
+            .collect()
+            .await;
+        anyhow::ensure!(!values.is_empty(), "No record found");
+        Ok(values)
     }
 
     /// Add address to bootstrap nodes.
@@ -464,6 +820,19 @@ impl HermesIpfs {
         self.node.pubsub_unsubscribe(topic).await
     }
 
+    /// List the peers known to be subscribed to a topic.
+    ///
+    /// ## Parameters
+    ///
+    /// * `topic` - `impl Into<String>`
+    ///
+    /// ## Errors
+    ///
+    /// Returns error if the topic peers cannot be retrieved.
+    pub async fn pubsub_peers(&self, topic: impl Into<String>) -> anyhow::Result<Vec<PeerId>> {
+        self.node.pubsub_peers(Some(topic.into())).await
+    }
+
     /// Publishes a message to a pubsub topic.
     ///
     /// ## Parameters
@@ -507,7 +876,11 @@ impl HermesIpfs {
 
 impl From<Ipfs> for HermesIpfs {
     fn from(node: Ipfs) -> Self {
-        Self { node }
+        Self {
+            node,
+            block_stored_at: Mutex::new(HashMap::new()),
+            gc_lock: RwLock::new(()),
+        }
     }
 }
 
@@ -561,6 +934,129 @@ impl From<(Option<String>, Vec<u8>)> for AddIpfsFile {
     }
 }
 
+/// Retry policy of `HermesIpfs::get_ipfs_file_with_retry`.
+#[derive(Debug, Clone)]
+pub struct FetchRetryPolicy {
+    /// Maximum number of fetch attempts.
+    pub attempts: usize,
+    /// Time after which an attempt is abandoned.
+    pub attempt_timeout: Duration,
+    /// Delay before the first retry, doubled after every retry.
+    pub initial_backoff: Duration,
+    /// Maximum delay between retries.
+    pub max_backoff: Duration,
+    /// Maximum number of providers discovered and dialed before a retry.
+    pub max_providers: usize,
+    /// Maximum number of providers dialed at the same time.
+    pub dial_concurrency: usize,
+}
+
+impl Default for FetchRetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            attempt_timeout: Duration::from_secs(30),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            max_providers: 20,
+            dial_concurrency: 5,
+        }
+    }
+}
+
+impl FetchRetryPolicy {
+    /// Delays before the successive retries, doubled after every retry up to
+    /// `max_backoff`.
+    fn backoffs(&self) -> impl Iterator<Item = Duration> + '_ {
+        std::iter::successors(Some(self.initial_backoff), |backoff| {
+            Some(backoff.saturating_mul(2).min(self.max_backoff))
+        })
+    }
+}
+
+/// Run `attempt` until it succeeds, at most `policy.attempts` times, each attempt
+/// abandoned after `policy.attempt_timeout`.
+///
+/// Before every retry `before_retry` runs, returning the number of providers found and
+/// dialed, and the backoff of the policy is waited.
+///
+/// Returns every failed attempt if none succeeded.
+async fn retry<T, Attempt, Retry>(
+    policy: &FetchRetryPolicy, mut attempt: impl FnMut() -> Attempt,
+    mut before_retry: impl FnMut() -> Retry,
+) -> Result<T, Vec<FetchAttemptFailure>>
+where
+    Attempt: std::future::Future<Output = Result<T, String>>,
+    Retry: std::future::Future<Output = (usize, usize)>,
+{
+    let mut failures = Vec::new();
+    let mut backoffs = policy.backoffs();
+    let (mut providers_found, mut providers_dialed) = (0, 0);
+    for number in 1..=policy.attempts.max(1) {
+        let error = match tokio::time::timeout(policy.attempt_timeout, attempt()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(err)) => err,
+            Err(_) => format!("timed out after {:?}", policy.attempt_timeout),
+        };
+        failures.push(FetchAttemptFailure {
+            attempt: number,
+            providers_found,
+            providers_dialed,
+            error,
+        });
+        if number >= policy.attempts {
+            break;
+        }
+
+        (providers_found, providers_dialed) = before_retry().await;
+        tokio::time::sleep(backoffs.next().unwrap_or(policy.max_backoff)).await;
+    }
+    Err(failures)
+}
+
+/// Failed attempt of a file fetch.
+#[derive(Debug, Clone)]
+pub struct FetchAttemptFailure {
+    /// Number of the attempt, starting from 1.
+    pub attempt: usize,
+    /// Number of providers discovered before the attempt.
+    pub providers_found: usize,
+    /// Number of providers successfully dialed before the attempt.
+    pub providers_dialed: usize,
+    /// Why the attempt failed.
+    pub error: String,
+}
+
+/// Error of a file fetch which failed after all its attempts.
+#[derive(Debug, Clone)]
+pub struct FetchError {
+    /// IPFS path of the file.
+    pub path: String,
+    /// Failed attempts.
+    pub attempts: Vec<FetchAttemptFailure>,
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to fetch {} after {} attempts",
+            self.path,
+            self.attempts.len()
+        )?;
+        for failure in &self.attempts {
+            write!(
+                f,
+                "; attempt {} ({} providers found, {} dialed): {}",
+                failure.attempt, failure.providers_found, failure.providers_dialed, failure.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FetchError {}
+
 /// Path to get the file from IPFS
 pub struct GetIpfsFile(IpfsPath);
 
@@ -592,7 +1088,7 @@ impl FromStr for GetIpfsFile {
 
 /// Handle stream of messages from the IPFS pubsub topic
 pub fn subscription_stream_task(
-    stream: SubscriptionStream, handler: fn(PubsubMessage),
+    stream: SubscriptionStream, handler: impl Fn(PubsubMessage) + Send + 'static,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         pin_mut!(stream);
@@ -601,3 +1097,72 @@ pub fn subscription_stream_task(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Retry policy with short, distinct backoffs.
+    fn policy(attempts: usize) -> FetchRetryPolicy {
+        FetchRetryPolicy {
+            attempts,
+            attempt_timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(3),
+            ..FetchRetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let backoffs: Vec<_> = policy(5).backoffs().take(4).map(|b| b.as_secs()).collect();
+        assert_eq!(backoffs, [1, 2, 3, 3]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fetch_is_retried_after_backoff() {
+        let started = tokio::time::Instant::now();
+        let mut calls = 0;
+        let res = retry(
+            &policy(4),
+            || {
+                calls += 1;
+                let call = calls;
+                async move {
+                    if call < 4 {
+                        Err(format!("attempt {call} failed"))
+                    } else {
+                        Ok(call)
+                    }
+                }
+            },
+            || async { (2, 1) },
+        )
+        .await;
+
+        assert_eq!(res.ok(), Some(4));
+        // Backoffs of 1, 2 and 3 seconds between the 4 attempts.
+        assert_eq!(started.elapsed(), Duration::from_secs(6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fetch_fails_after_all_attempts() {
+        let res = retry(
+            &policy(3),
+            std::future::pending::<Result<(), String>>,
+            || async { (2, 1) },
+        )
+        .await;
+
+        let failures = res.unwrap_err();
+        assert_eq!(failures.len(), 3);
+        let providers = |failure: Option<&FetchAttemptFailure>| {
+            failure.map(|failure| (failure.providers_found, failure.providers_dialed))
+        };
+        assert_eq!(providers(failures.first()), Some((0, 0)));
+        assert_eq!(providers(failures.last()), Some((2, 1)));
+        assert!(failures
+            .iter()
+            .all(|failure| failure.error.starts_with("timed out")));
+    }
+}
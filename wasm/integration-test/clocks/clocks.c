@@ -11,26 +11,54 @@ void exports_hermes_cardano_event_on_block_on_cardano_block(exports_hermes_carda
 {
 }
 
+// Exported Functions from `hermes:cardano/event-on-blocks`
+void exports_hermes_cardano_event_on_blocks_on_cardano_blocks(exports_hermes_cardano_event_on_blocks_cardano_blockchain_id_t blockchain, exports_hermes_cardano_event_on_blocks_list_cardano_block_t *blocks, exports_hermes_cardano_event_on_blocks_block_src_t source)
+{
+}
+
 // Exported Functions from `hermes:cardano/event-on-txn`
 void exports_hermes_cardano_event_on_txn_on_cardano_txn(exports_hermes_cardano_event_on_txn_cardano_blockchain_id_t blockchain, uint64_t slot, uint32_t txn_index, exports_hermes_cardano_event_on_txn_cardano_txn_t *txn)
 {
 }
 
 // Exported Functions from `hermes:cardano/event-on-rollback`
-void exports_hermes_cardano_event_on_rollback_on_cardano_rollback(exports_hermes_cardano_event_on_rollback_cardano_blockchain_id_t blockchain, uint64_t slot)
+void exports_hermes_cardano_event_on_rollback_on_cardano_rollback(exports_hermes_cardano_event_on_rollback_cardano_blockchain_id_t blockchain, uint64_t slot, exports_hermes_cardano_event_on_rollback_bstr_t *hash, uint64_t depth)
+{
+}
+
+// Exported Functions from `hermes:cardano/event-on-immutable-window`
+void exports_hermes_cardano_event_on_immutable_window_on_cardano_immutable_window(exports_hermes_cardano_event_on_immutable_window_cardano_blockchain_id_t blockchain, exports_hermes_cardano_event_on_immutable_window_tuple2_u64_bstr_t *from_slot, exports_hermes_cardano_event_on_immutable_window_tuple2_u64_bstr_t *to_slot)
 {
 }
 
+// Exported Functions from `hermes:cardano/event-on-reindex`
+bool exports_hermes_cardano_event_on_reindex_on_cardano_reindex(exports_hermes_cardano_event_on_reindex_cardano_blockchain_id_t blockchain, uint64_t from_slot, exports_hermes_cardano_event_on_reindex_slot_t *ret)
+{
+  return false;
+}
+
 // Exported Functions from `hermes:cron/event`
-bool exports_hermes_cron_event_on_cron(exports_hermes_cron_event_cron_tagged_t *event, bool last)
+bool exports_hermes_cron_event_on_cron(exports_hermes_cron_event_cron_tagged_t *event, bool last, exports_hermes_cron_event_cron_delivery_info_t *delivery)
 {
     return false;
 }
 
+// Exported Functions from `hermes:timer/event`
+void exports_hermes_timer_event_on_timer(exports_hermes_timer_event_timer_tag_t *tag)
+{
+
+}
+
 // Exported Functions from `hermes:init/event`
-bool exports_hermes_init_event_init(void)
+void exports_hermes_init_event_init(exports_hermes_init_event_init_result_t *ret)
 {
-    return false;
+    ret->tag = EXPORTS_HERMES_INIT_EVENT_INIT_RESULT_OK;
+}
+
+// Exported Functions from `hermes:init/event-self-test`
+bool exports_hermes_init_event_self_test_self_test(hermes_string_t *err)
+{
+  return true;
 }
 
 // Exported Functions from `hermes:ipfs/event`
@@ -85,3 +113,7 @@ bool exports_hermes_integration_test_event_bench(uint32_t test, bool run, export
 bool exports_hermes_http_gateway_event_reply(exports_hermes_http_gateway_event_bstr_t *body, exports_hermes_http_gateway_event_headers_t *headers, hermes_string_t *path, hermes_string_t *method, exports_hermes_http_gateway_event_http_response_t *ret){
   return false;
 };
+
+// Exported Functions from `hermes:http-gateway/event-warmup`
+void exports_hermes_http_gateway_event_warmup_warmup(void) {
+}
@@ -25,8 +25,15 @@ func (t TestModule) Bench(test uint32, run bool) hermes.Option[hermes.ExportsHer
 	return hermes.None[hermes.ExportsHermesIntegrationTestEventTestResult]()
 }
 
-func (t TestModule) Init() bool {
-	return true
+func (t TestModule) Init() hermes.ExportsHermesInitEventInitResult {
+	return hermes.ExportsHermesInitEventInitResultOk()
+}
+
+func (t TestModule) SelfTest() hermes.Result[struct{}, string] {
+	return hermes.Ok[struct{}, string](struct{}{})
+}
+
+func (t TestModule) Warmup() {
 }
 
 func (t TestModule) OnCardanoTxn(blockchain hermes.ExportsHermesCardanoEventOnTxnCardanoBlockchainId, slot uint64, txnIndex uint32, txn hermes.ExportsHermesCardanoEventOnTxnCardanoTxn) {
@@ -35,20 +42,39 @@ func (t TestModule) OnCardanoTxn(blockchain hermes.ExportsHermesCardanoEventOnTx
 func (t TestModule) OnCardanoBlock(blockchain hermes.ExportsHermesCardanoEventOnBlockCardanoBlockchainId, block hermes.ExportsHermesCardanoEventOnBlockCardanoBlock, source hermes.ExportsHermesCardanoEventOnBlockBlockSrc) {
 }
 
-func (t TestModule) OnCardanoRollback(blockchain hermes.ExportsHermesCardanoEventOnRollbackCardanoBlockchainId, slot uint64) {
+func (t TestModule) OnCardanoBlocks(blockchain hermes.ExportsHermesCardanoEventOnBlocksCardanoBlockchainId, blocks []hermes.ExportsHermesCardanoEventOnBlocksCardanoBlock, source hermes.ExportsHermesCardanoEventOnBlocksBlockSrc) {
+}
+
+func (t TestModule) OnCardanoRollback(blockchain hermes.ExportsHermesCardanoEventOnRollbackCardanoBlockchainId, slot uint64, hash hermes.ExportsHermesCardanoEventOnRollbackBstr, depth uint64) {
 }
 
-func (t TestModule) OnCron(event hermes.ExportsHermesCronEventCronTagged, last bool) bool {
+func (t TestModule) OnCardanoImmutableWindow(blockchain hermes.ExportsHermesCardanoEventOnImmutableWindowCardanoBlockchainId, fromSlot hermes.ExportsHermesCardanoEventOnImmutableWindowTuple2U64BstrT, toSlot hermes.ExportsHermesCardanoEventOnImmutableWindowTuple2U64BstrT) {
+}
+
+func (t TestModule) OnCardanoReindex(blockchain hermes.ExportsHermesCardanoEventOnReindexCardanoBlockchainId, fromSlot uint64) hermes.Option[hermes.ExportsHermesCardanoEventOnReindexSlot] {
+	return hermes.None[hermes.ExportsHermesCardanoEventOnReindexSlot]()
+}
+
+func (t TestModule) OnCron(event hermes.ExportsHermesCronEventCronTagged, last bool, delivery hermes.ExportsHermesCronEventCronDeliveryInfo) bool {
 	return true
 }
 
+func (t TestModule) OnTimer(tag string) {
+}
+
 func init() {
 	testModule := &TestModule{}
 	hermes.SetExportsHermesCronEvent(testModule)
+	hermes.SetExportsHermesTimerEvent(testModule)
 	hermes.SetExportsHermesCardanoEventOnRollback(testModule)
+	hermes.SetExportsHermesCardanoEventOnImmutableWindow(testModule)
+	hermes.SetExportsHermesCardanoEventOnReindex(testModule)
 	hermes.SetExportsHermesCardanoEventOnBlock(testModule)
+	hermes.SetExportsHermesCardanoEventOnBlocks(testModule)
 	hermes.SetExportsHermesCardanoEventOnTxn(testModule)
 	hermes.SetExportsHermesInitEvent(testModule)
+	hermes.SetExportsHermesInitEventSelfTest(testModule)
+	hermes.SetExportsHermesHttpGatewayEventWarmup(testModule)
 	hermes.SetExportsHermesIntegrationTestEvent(testModule)
 	hermes.SetExportsHermesKvStoreEvent(testModule)
 	hermes.SetExportsWasiHttp0_2_0_IncomingHandler(testModule)
@@ -9,11 +9,12 @@ mod test;
 use hermes::{
     exports::hermes::{
         http_gateway::event::{Bstr, Headers, HttpResponse},
+        init::event::InitResult,
         integration_test::event::TestResult,
     },
     hermes::{
         cardano::api::{BlockSrc, CardanoBlock, CardanoBlockchainId, CardanoTxn},
-        cron::api::CronTagged,
+        cron::api::{CronDeliveryInfo, CronTagged},
         ipfs::api::PubsubMessage,
         kv_store::api::KvValues,
         sqlite,
@@ -57,8 +58,31 @@ impl hermes::exports::hermes::cardano::event_on_block::Guest for TestComponent {
     }
 }
 
+impl hermes::exports::hermes::cardano::event_on_blocks::Guest for TestComponent {
+    fn on_cardano_blocks(
+        _blockchain: CardanoBlockchainId, _blocks: Vec<CardanoBlock>, _source: BlockSrc,
+    ) {
+    }
+}
+
 impl hermes::exports::hermes::cardano::event_on_rollback::Guest for TestComponent {
-    fn on_cardano_rollback(_blockchain: CardanoBlockchainId, _slot: u64) {}
+    fn on_cardano_rollback(
+        _blockchain: CardanoBlockchainId, _slot: u64, _hash: Vec<u8>, _depth: u64,
+    ) {
+    }
+}
+
+impl hermes::exports::hermes::cardano::event_on_immutable_window::Guest for TestComponent {
+    fn on_cardano_immutable_window(
+        _blockchain: CardanoBlockchainId, _from_slot: (u64, Vec<u8>), _to_slot: (u64, Vec<u8>),
+    ) {
+    }
+}
+
+impl hermes::exports::hermes::cardano::event_on_reindex::Guest for TestComponent {
+    fn on_cardano_reindex(_blockchain: CardanoBlockchainId, _from_slot: u64) -> Option<hermes::hermes::cardano::api::Slot> {
+        None
+    }
 }
 
 impl hermes::exports::hermes::cardano::event_on_txn::Guest for TestComponent {
@@ -72,14 +96,24 @@ impl hermes::exports::hermes::cardano::event_on_txn::Guest for TestComponent {
 }
 
 impl hermes::exports::hermes::cron::event::Guest for TestComponent {
-    fn on_cron(_event: CronTagged, _last: bool) -> bool {
+    fn on_cron(_event: CronTagged, _last: bool, _delivery: CronDeliveryInfo) -> bool {
         false
     }
 }
 
+impl hermes::exports::hermes::timer::event::Guest for TestComponent {
+    fn on_timer(_tag: String) {}
+}
+
 impl hermes::exports::hermes::init::event::Guest for TestComponent {
-    fn init() -> bool {
-        true
+    fn init() -> InitResult {
+        InitResult::Ok
+    }
+}
+
+impl hermes::exports::hermes::init::event_self_test::Guest for TestComponent {
+    fn self_test() -> Result<(), String> {
+        Ok(())
     }
 }
 
@@ -104,6 +138,10 @@ impl hermes::exports::hermes::http_gateway::event::Guest for TestComponent {
     }
 }
 
+impl hermes::exports::hermes::http_gateway::event_warmup::Guest for TestComponent {
+    fn warmup() {}
+}
+
 impl hermes::exports::wasi::http::incoming_handler::Guest for TestComponent {
     fn handle(_request: IncomingRequest, _response_out: ResponseOutparam) {}
 }
@@ -1,6 +1,9 @@
-use crate::hermes::hermes::sqlite;
+use crate::hermes::hermes::{
+    error::api::{ErrorCategory, HermesError},
+    sqlite,
+};
 
-type TestResult = Result<(), sqlite::api::Errno>;
+type TestResult = Result<(), HermesError>;
 
 pub(crate) struct TestItem {
     pub(crate) name: &'static str,
@@ -40,11 +43,20 @@ pub(crate) const TESTS: &[TestItem] = &[
         name: "text-value-simple",
         executor: item::text_value_simple,
     },
+    TestItem {
+        name: "transaction-savepoint-rollback",
+        executor: item::transaction_savepoint_rollback,
+    },
+    TestItem {
+        name: "busy-timeout-locked-database",
+        executor: item::busy_timeout_locked_database,
+    },
 ];
 
 pub(crate) const BENCHES: &[TestItem] = &[
     TestItem {
-        // FIXME: right now, according to the config, still sharing the same file. If you need to add a new case for a bench related to a file, you need to clean up the old one.
+        // FIXME: right now, according to the config, still sharing the same file. If you need to
+        // add a new case for a bench related to a file, you need to clean up the old one.
         name: "bench-persistent-insert",
         executor: || helper::bench_insert(false),
     },
@@ -150,15 +162,94 @@ mod item {
 
         match (value, retrieved_value) {
             (sqlite::api::Value::Text(a), sqlite::api::Value::Text(b)) if a == b => Ok(()),
-            _ => Err(sqlite::api::Errno::Sqlite(1)),
+            _ => {
+                Err(HermesError {
+                    category: ErrorCategory::Internal,
+                    retryable: false,
+                    message: "Retrieved value does not match the inserted value".to_string(),
+                    code: 1,
+                })
+            },
+        }
+    }
+
+    pub(super) fn transaction_savepoint_rollback() -> TestResult {
+        let sqlite = sqlite::api::open(false, true)?;
+        sqlite.execute("CREATE TABLE dummy(id INTEGER PRIMARY KEY);")?;
+
+        sqlite.begin(sqlite::api::TransactionMode::Immediate)?;
+        sqlite.execute("INSERT INTO dummy(id) VALUES(1);")?;
+        sqlite.savepoint("second")?;
+        sqlite.execute("INSERT INTO dummy(id) VALUES(2);")?;
+        sqlite.rollback_to("second")?;
+        sqlite.release("second")?;
+        sqlite.commit()?;
+
+        sqlite.begin(sqlite::api::TransactionMode::Deferred)?;
+        sqlite.execute("INSERT INTO dummy(id) VALUES(3);")?;
+        sqlite.rollback()?;
+
+        let in_transaction = sqlite.in_transaction();
+        let count = super::helper::count_rows(&sqlite)?;
+        sqlite.close()?;
+
+        match count {
+            sqlite::api::Value::Int32(1) if !in_transaction => Ok(()),
+            _ => {
+                Err(HermesError {
+                    category: ErrorCategory::Internal,
+                    retryable: false,
+                    message: "Rolled back rows are kept in the database".to_string(),
+                    code: 1,
+                })
+            },
+        }
+    }
+
+    pub(super) fn busy_timeout_locked_database() -> TestResult {
+        let writer = sqlite::api::open_named("busy", false, false)?;
+        let sqlite = sqlite::api::open_named("busy", false, false)?;
+        sqlite.set_busy_timeout(10)?;
+        sqlite.set_retry_on_busy(true);
+
+        writer.begin(sqlite::api::TransactionMode::Immediate)?;
+        let locked = sqlite.begin(sqlite::api::TransactionMode::Immediate);
+        writer.commit()?;
+        let unlocked = sqlite.begin(sqlite::api::TransactionMode::Immediate);
+        sqlite.rollback()?;
+
+        writer.close()?;
+        sqlite.close()?;
+
+        match (locked, unlocked) {
+            (Err(_), Ok(())) => Ok(()),
+            _ => {
+                Err(HermesError {
+                    category: ErrorCategory::Internal,
+                    retryable: false,
+                    message: "Locked database is not reported busy".to_string(),
+                    code: 1,
+                })
+            },
         }
     }
 }
 
 mod helper {
-    use super::TestResult;
+    use super::{HermesError, TestResult};
     use crate::sqlite;
 
+    /// Count the rows of the `dummy` table.
+    pub(super) fn count_rows(
+        sqlite: &sqlite::api::Sqlite,
+    ) -> Result<sqlite::api::Value, HermesError> {
+        let stmt = sqlite.prepare("SELECT count(*) FROM dummy;")?;
+        stmt.step()?;
+        let count = stmt.column(0)?;
+        stmt.finalize()?;
+        Ok(count)
+    }
+
     pub(super) fn bench_insert(memory: bool) -> TestResult {
         let sqlite = sqlite::api::open(false, memory)?;
 
@@ -1,4 +1,4 @@
-use hermes::exports::hermes::integration_test::event::TestResult;
+use hermes::exports::hermes::{init::event::InitResult, integration_test::event::TestResult};
 
 mod hermes;
 mod tests;
@@ -6,17 +6,30 @@ mod tests;
 struct TestComponent;
 
 impl hermes::exports::hermes::init::event::Guest for TestComponent {
-    fn init() -> bool {
-        true
+    fn init() -> InitResult {
+        InitResult::Ok
+    }
+}
+
+impl hermes::exports::hermes::init::event_self_test::Guest for TestComponent {
+    fn self_test() -> Result<(), String> {
+        Ok(())
     }
 }
 
 impl hermes::exports::hermes::cron::event::Guest for TestComponent {
-    fn on_cron(_event: hermes::exports::hermes::cron::event::CronTagged, _last: bool) -> bool {
+    fn on_cron(
+        _event: hermes::exports::hermes::cron::event::CronTagged, _last: bool,
+        _delivery: hermes::exports::hermes::cron::event::CronDeliveryInfo,
+    ) -> bool {
         true
     }
 }
 
+impl hermes::exports::hermes::timer::event::Guest for TestComponent {
+    fn on_timer(_tag: String) {}
+}
+
 impl hermes::exports::hermes::ipfs::event::Guest for TestComponent {
     fn on_topic(_message: hermes::exports::hermes::ipfs::event::PubsubMessage) -> bool {
         true
@@ -42,14 +55,43 @@ impl hermes::exports::hermes::cardano::event_on_block::Guest for TestComponent {
     }
 }
 
+impl hermes::exports::hermes::cardano::event_on_blocks::Guest for TestComponent {
+    fn on_cardano_blocks(
+        _blockchain: hermes::exports::hermes::cardano::event_on_blocks::CardanoBlockchainId,
+        _blocks: Vec<hermes::exports::hermes::cardano::event_on_blocks::CardanoBlock>,
+        _source: hermes::exports::hermes::cardano::event_on_blocks::BlockSrc,
+    ) {
+    }
+}
+
 impl hermes::exports::hermes::cardano::event_on_rollback::Guest for TestComponent {
     fn on_cardano_rollback(
         _blockchain: hermes::exports::hermes::cardano::event_on_rollback::CardanoBlockchainId,
         _slot: u64,
+        _hash: Vec<u8>,
+        _depth: u64,
     ) {
     }
 }
 
+impl hermes::exports::hermes::cardano::event_on_immutable_window::Guest for TestComponent {
+    fn on_cardano_immutable_window(
+        _blockchain: hermes::exports::hermes::cardano::event_on_immutable_window::CardanoBlockchainId,
+        _from_slot: (u64, Vec<u8>),
+        _to_slot: (u64, Vec<u8>),
+    ) {
+    }
+}
+
+impl hermes::exports::hermes::cardano::event_on_reindex::Guest for TestComponent {
+    fn on_cardano_reindex(
+        _blockchain: hermes::exports::hermes::cardano::event_on_reindex::CardanoBlockchainId,
+        _from_slot: u64,
+    ) -> Option<hermes::exports::hermes::cardano::event_on_reindex::Slot> {
+        None
+    }
+}
+
 impl hermes::exports::hermes::kv_store::event::Guest for TestComponent {
     fn kv_update(_key: String, _value: hermes::exports::hermes::kv_store::event::KvValues) {}
 }
@@ -95,6 +137,10 @@ impl hermes::exports::hermes::http_gateway::event::Guest for TestComponent {
     }
 }
 
+impl hermes::exports::hermes::http_gateway::event_warmup::Guest for TestComponent {
+    fn warmup() {}
+}
+
 impl hermes::exports::wasi::http::incoming_handler::Guest for TestComponent {
     fn handle(
         _request: hermes::exports::wasi::http::incoming_handler::IncomingRequest,